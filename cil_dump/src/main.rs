@@ -0,0 +1,211 @@
+use std::{path::PathBuf, process::ExitCode};
+
+use clap::{Parser, ValueEnum};
+use clr_profiler::{
+    MetadataImportTrait,
+    cil::{Disassembler, Method, PatchPreview, ldc_i4_0, ldc_i4_1, nop, ret},
+    ffi::HResult,
+    metadata::{MethodDefToken, TypeDefToken},
+    pe::AssemblyFile,
+};
+
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    /// Assembly (.dll or .exe) to read
+    assembly: PathBuf,
+
+    /// Only dump methods whose qualified name contains this text
+    ///
+    /// Names look like `Namespace.Type+Nested::Method(int, string)`.
+    /// A MethodDef token such as 0x06000012 selects a single method.
+    filter: Option<String>,
+
+    /// Only list the matching methods
+    #[arg(short, long)]
+    list: bool,
+
+    /// Apply a rewrite to the matching methods and show the IL before and after
+    ///
+    /// Exits with an error if any rewritten body fails validation.
+    #[arg(short, long, value_name = "REWRITE", conflicts_with = "list")]
+    patch: Option<Patch>,
+}
+
+/// The rewrites the profilers in this repository apply when a method is JIT
+/// compiled.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Patch {
+    /// Replace the body with `return true;`
+    ReturnTrue,
+    /// Replace the body with `return false;`
+    ReturnFalse,
+    /// Replace the body with `return;`
+    Return,
+    /// Insert a `nop` before the first instruction
+    NopPrelude,
+}
+
+impl Patch {
+    fn apply(self, method: &mut Method) -> Result<(), String> {
+        let instructions = match self {
+            Patch::ReturnTrue => vec![ldc_i4_1(), ret()],
+            Patch::ReturnFalse => vec![ldc_i4_0(), ret()],
+            Patch::Return => vec![ret()],
+            Patch::NopPrelude => {
+                return method
+                    .insert_prelude(vec![nop()])
+                    .map_err(|error| format!("{error:?}"));
+            }
+        };
+        *method = Method::from_instructions(instructions);
+        Ok(())
+    }
+}
+
+enum Filter {
+    All,
+    Token(MethodDefToken),
+    Name(String),
+}
+
+impl Filter {
+    fn new(filter: Option<&str>) -> Self {
+        let Some(filter) = filter else {
+            return Filter::All;
+        };
+        let token = filter
+            .strip_prefix("0x")
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+            .and_then(|token| MethodDefToken::try_from(token).ok());
+        match token {
+            Some(token) => Filter::Token(token),
+            None => Filter::Name(filter.to_string()),
+        }
+    }
+
+    fn matches(&self, token: MethodDefToken, name: &str) -> bool {
+        match self {
+            Filter::All => true,
+            Filter::Token(filter) => *filter == token,
+            Filter::Name(filter) => name.contains(filter.as_str()),
+        }
+    }
+}
+
+fn dump_method(file: &AssemblyFile, token: MethodDefToken, name: &str) -> Result<(), HResult> {
+    println!("{name} // {token}");
+    let body = match file.method_body(token) {
+        Ok(body) => body,
+        Err(HResult::CLDB_E_RECORD_NOTFOUND) => {
+            println!("    // no IL body\n");
+            return Ok(());
+        }
+        Err(hr) => return Err(hr),
+    };
+    let method = match Method::new(body.as_ptr(), body.len() as u32) {
+        Ok(method) => method,
+        Err(error) => {
+            println!("    // invalid method body: {error:?}\n");
+            return Ok(());
+        }
+    };
+    let disassembler = Disassembler::new(file);
+    println!("    // {}", disassembler.header(&method));
+    println!("{{");
+    for instruction in disassembler.instructions(&method) {
+        println!("    {instruction}");
+    }
+    for clause in disassembler.exception_clauses(&method) {
+        println!("    {clause}");
+    }
+    println!("}}\n");
+    Ok(())
+}
+
+/// Returns whether the rewritten body is valid.
+fn preview_patch(
+    file: &AssemblyFile,
+    token: MethodDefToken,
+    name: &str,
+    patch: Patch,
+) -> Result<bool, HResult> {
+    println!("{name} // {token}");
+    let body = match file.method_body(token) {
+        Ok(body) => body,
+        Err(HResult::CLDB_E_RECORD_NOTFOUND) => {
+            println!("    // no IL body\n");
+            return Ok(true);
+        }
+        Err(hr) => return Err(hr),
+    };
+    // parsed twice as methods can't be cloned
+    let (old, mut new) = match (
+        Method::new(body.as_ptr(), body.len() as u32),
+        Method::new(body.as_ptr(), body.len() as u32),
+    ) {
+        (Ok(old), Ok(new)) => (old, new),
+        (Err(error), _) | (_, Err(error)) => {
+            println!("    // invalid method body: {error:?}\n");
+            return Ok(true);
+        }
+    };
+    if let Err(error) = patch.apply(&mut new) {
+        println!("    // rewrite failed: {error}\n");
+        return Ok(false);
+    }
+    let preview = PatchPreview::new(&Disassembler::new(file), &old, &new);
+    println!("{preview}\n");
+    Ok(preview.is_valid())
+}
+
+fn run(cli: &Cli) -> Result<(), String> {
+    let file = AssemblyFile::open(&cli.assembly)
+        .map_err(|error| format!("can't read {}: {error:?}", cli.assembly.display()))?;
+    let filter = Filter::new(cli.filter.as_deref());
+    let hresult = |hr: HResult| format!("failed to read metadata: {hr}");
+
+    // <Module> holds the global functions
+    let mut type_defs = vec![TypeDefToken::from_rid(1)];
+    type_defs.extend(file.enum_type_defs().map_err(hresult)?);
+    let mut matched = 0;
+    let mut invalid = 0;
+    for td in type_defs {
+        for mb in file.enum_methods(td).map_err(hresult)? {
+            let name = file
+                .qualified_name(mb.token())
+                .unwrap_or_else(|_| mb.to_string());
+            if !filter.matches(mb, &name) {
+                continue;
+            }
+            matched += 1;
+            if cli.list {
+                println!("{mb} {name}");
+            } else if let Some(patch) = cli.patch {
+                if !preview_patch(&file, mb, &name, patch).map_err(hresult)? {
+                    invalid += 1;
+                }
+            } else {
+                dump_method(&file, mb, &name).map_err(hresult)?;
+            }
+        }
+    }
+    if matched == 0 {
+        return Err("no matching methods".to_string());
+    }
+    if invalid > 0 {
+        return Err(format!("{invalid} rewritten method(s) failed validation"));
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(&cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("cil-dump: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
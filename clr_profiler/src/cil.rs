@@ -1,15 +1,21 @@
+mod disassembler;
 mod error;
 mod helpers;
 mod instruction;
 mod method;
 mod method_header;
 mod opcode;
+mod patch_preview;
 mod section;
+mod validation;
 
+pub use self::disassembler::*;
 pub use self::error::*;
 pub use self::helpers::*;
 pub use self::instruction::*;
 pub use self::method::*;
 pub use self::method_header::*;
 pub use self::opcode::*;
+pub use self::patch_preview::*;
 pub use self::section::*;
+pub use self::validation::*;
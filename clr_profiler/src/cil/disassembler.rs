@@ -0,0 +1,124 @@
+use crate::{
+    cil::{ExceptionClause, ExceptionClauseKind, Instruction, Method, MethodHeader, Operand},
+    metadata::{StringToken, Token},
+    MetadataImportTrait,
+};
+use std::convert::TryFrom;
+
+/// Formats method bodies like ildasm, resolving tokens to names and string
+/// literals through the module's metadata:
+///
+/// ```text
+/// IL_0000: ldstr "https://example.com"
+/// IL_0005: call System.Uri::.ctor(string)
+/// IL_000a: brtrue.s IL_0010
+/// ```
+///
+/// Tokens that can't be resolved are printed as is, e.g. `MemberRef(0x0a00000c)`.
+pub struct Disassembler<'a, M> {
+    metadata: &'a M,
+}
+
+impl<'a, M: MetadataImportTrait> Disassembler<'a, M> {
+    pub fn new(metadata: &'a M) -> Self {
+        Disassembler { metadata }
+    }
+
+    pub fn header(&self, method: &Method) -> String {
+        match &method.method_header {
+            MethodHeader::Tiny(header) => format!("tiny header, code size {}", header.code_size),
+            MethodHeader::Fat(header) => {
+                let mut description = format!(
+                    "fat header, code size {}, max stack {}",
+                    header.code_size, header.max_stack
+                );
+                if header.local_var_sig_tok != 0 {
+                    let locals = Token::new(header.local_var_sig_tok);
+                    description.push_str(&format!(", locals {locals}"));
+                }
+                if header.init_locals {
+                    description.push_str(", init locals");
+                }
+                description
+            }
+        }
+    }
+
+    pub fn instructions(&self, method: &Method) -> Vec<String> {
+        method
+            .instruction_offsets()
+            .into_iter()
+            .map(|(offset, instruction)| self.instruction(offset, instruction))
+            .collect()
+    }
+
+    /// Formats one instruction located at `offset`; branch targets are
+    /// relative to the following instruction.
+    pub fn instruction(&self, offset: u32, instruction: &Instruction) -> String {
+        let next = offset as i64 + instruction.length() as i64;
+        let operand = match &instruction.operand {
+            Operand::InlineNone => String::new(),
+            Operand::ShortInlineBrTarget(delta) => label(next + *delta as i64),
+            Operand::InlineBrTarget(delta) => label(next + *delta as i64),
+            Operand::InlineSwitch(_, deltas) => {
+                let targets = deltas
+                    .iter()
+                    .map(|delta| label(next + *delta as i64))
+                    .collect::<Vec<_>>();
+                format!("({})", targets.join(", "))
+            }
+            Operand::InlineString(token) => match StringToken::try_from(*token) {
+                Ok(stk) => match self.metadata.get_user_string(stk) {
+                    Ok(string) => format!("{string:?}"),
+                    Err(_) => stk.to_string(),
+                },
+                Err(token) => token.to_string(),
+            },
+            Operand::InlineMethod(token)
+            | Operand::InlineField(token)
+            | Operand::InlineType(token)
+            | Operand::InlineTok(token) => self.token(Token::new(*token)),
+            operand => operand.to_string(),
+        };
+        let opcode = instruction.opcode.name;
+        if operand.is_empty() {
+            format!("{}: {opcode}", label(offset as i64))
+        } else {
+            format!("{}: {opcode} {operand}", label(offset as i64))
+        }
+    }
+
+    pub fn exception_clauses(&self, method: &Method) -> Vec<String> {
+        method
+            .exception_clauses()
+            .iter()
+            .map(|clause| self.exception_clause(clause))
+            .collect()
+    }
+
+    pub fn exception_clause(&self, clause: &ExceptionClause) -> String {
+        let handler = match &clause.kind {
+            ExceptionClauseKind::Catch(token) => format!("catch {}", self.token(*token)),
+            ExceptionClauseKind::Filter(offset) => format!("filter {}", label(*offset as i64)),
+            ExceptionClauseKind::Finally => "finally".to_string(),
+            ExceptionClauseKind::Fault => "fault".to_string(),
+        };
+        format!(
+            ".try {} to {} {handler} handler {} to {}",
+            label(clause.try_offset as i64),
+            label(clause.try_offset as i64 + clause.try_length as i64),
+            label(clause.handler_offset as i64),
+            label(clause.handler_offset as i64 + clause.handler_length as i64),
+        )
+    }
+
+    fn token(&self, token: Token) -> String {
+        self.metadata
+            .qualified_name(token)
+            .unwrap_or_else(|_| token.to_string())
+    }
+}
+
+fn label(offset: i64) -> String {
+    format!("IL_{offset:04x}")
+}
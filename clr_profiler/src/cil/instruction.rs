@@ -3,6 +3,7 @@ use std::fmt::Display;
 use crate::cil::{
     il_f32, il_f64, il_i32, il_i64, il_i8, il_u16, il_u32, il_u8, opcode::*, Error, OperandParams,
 };
+use crate::metadata::Token;
 
 #[derive(Debug)]
 pub enum Operand {
@@ -63,7 +64,7 @@ impl Display for Operand {
             | Self::InlineField(val)
             | Self::InlineString(val)
             | Self::InlineTok(val)
-            | Self::InlineSig(val) => format!("{val:#04x}"),
+            | Self::InlineSig(val) => format!("{}", Token::new(*val)),
             Self::ShortInlineBrTarget(val) => format!("{val}"),
             Self::InlineBrTarget(val) => format!("{val:#04x}"),
             Self::InlineSwitch(length, _) => format!("switch[len={length}]"),             
@@ -1,5 +1,9 @@
 #![allow(non_upper_case_globals)]
-use crate::cil::{nearest_multiple, Error, Instruction, MethodHeader, Section};
+use crate::cil::{
+    nearest_multiple, Error, ExceptionClause, ExceptionClauseKind, FatMethodHeader, Instruction,
+    MethodHeader, Section, TinyMethodHeader,
+};
+use crate::metadata::Token;
 use std::convert::TryFrom;
 use std::slice;
 
@@ -33,11 +37,102 @@ impl Method {
             sections,
         })
     }
+    /// Builds a method body without locals or exception clauses. A tiny header
+    /// is used when the code fits in one, otherwise a fat header with the same
+    /// max stack of 8 the runtime assumes for tiny headers.
+    pub fn from_instructions(instructions: Vec<Instruction>) -> Self {
+        let code_size: usize = instructions.iter().map(|i| i.length()).sum();
+        let method_header = match u8::try_from(code_size) {
+            Ok(code_size) if code_size < 64 => MethodHeader::Tiny(TinyMethodHeader { code_size }),
+            _ => MethodHeader::Fat(FatMethodHeader {
+                more_sects: false,
+                init_locals: false,
+                max_stack: 8,
+                code_size: code_size as u32,
+                local_var_sig_tok: 0,
+            }),
+        };
+        Method {
+            method_header,
+            instructions,
+            sections: Vec::new(),
+        }
+    }
+    /// Pairs each instruction with its offset in the IL.
+    pub fn instruction_offsets(&self) -> Vec<(u32, &Instruction)> {
+        let mut offset = 0;
+        self.instructions
+            .iter()
+            .map(|instruction| {
+                let start = offset;
+                offset += instruction.length() as u32;
+                (start, instruction)
+            })
+            .collect()
+    }
+
+    pub fn exception_clauses(&self) -> Vec<ExceptionClause> {
+        fn kind(
+            is_filter: bool,
+            is_finally: bool,
+            is_fault: bool,
+            token: u32,
+        ) -> ExceptionClauseKind {
+            if is_filter {
+                ExceptionClauseKind::Filter(token)
+            } else if is_finally {
+                ExceptionClauseKind::Finally
+            } else if is_fault {
+                ExceptionClauseKind::Fault
+            } else {
+                ExceptionClauseKind::Catch(Token::new(token))
+            }
+        }
+        let mut clauses = Vec::new();
+        for section in &self.sections {
+            match section {
+                Section::FatSection(_, fat_clauses) => {
+                    clauses.extend(fat_clauses.iter().map(|clause| ExceptionClause {
+                        kind: kind(
+                            clause.is_filter,
+                            clause.is_finally,
+                            clause.is_fault,
+                            clause.class_token_or_filter_offset,
+                        ),
+                        try_offset: clause.try_offset,
+                        try_length: clause.try_length,
+                        handler_offset: clause.handler_offset,
+                        handler_length: clause.handler_length,
+                    }))
+                }
+                Section::SmallSection(_, small_clauses) => {
+                    clauses.extend(small_clauses.iter().map(|clause| ExceptionClause {
+                        kind: kind(
+                            clause.is_filter,
+                            clause.is_finally,
+                            clause.is_fault,
+                            clause.class_token_or_filter_offset,
+                        ),
+                        try_offset: clause.try_offset as u32,
+                        try_length: clause.try_length as u32,
+                        handler_offset: clause.handler_offset as u32,
+                        handler_length: clause.handler_length as u32,
+                    }))
+                }
+            }
+        }
+        clauses
+    }
     pub fn into_bytes(&self) -> Vec<u8> {
         let mut bytes = Vec::new();
         bytes.append(&mut self.method_header.into_bytes());
         bytes.append(&mut self.instructions_to_bytes());
-        bytes.append(&mut self.sections_to_bytes());
+        let mut sections = self.sections_to_bytes();
+        if !sections.is_empty() {
+            // Sections must be DWORD aligned. Pad the code with zeros to achieve alignment.
+            bytes.resize(nearest_multiple(4, bytes.len()), 0);
+        }
+        bytes.append(&mut sections);
         bytes
     }
     pub fn insert_prelude(&mut self, prelude: Vec<Instruction>) -> Result<(), Error> {
@@ -121,11 +216,6 @@ impl Method {
         let mut bytes = Vec::new();
         match &self.method_header {
             MethodHeader::Fat(header) if header.more_sects => {
-                // Sections must be DWORD aligned. Add zero padding at the end to achieve alignment.
-                let padding_byte_size = 4 - bytes.len() % 4;
-                for _ in 0..padding_byte_size {
-                    bytes.push(0);
-                }
                 let mut section_bytes = self.sections.iter().flat_map(|s| s.into_bytes()).collect();
                 bytes.append(&mut section_bytes);
             }
@@ -0,0 +1,215 @@
+use crate::{
+    cil::{validate, Disassembler, Method, ValidationIssue},
+    MetadataImportTrait,
+};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Unchanged,
+    /// Same instruction, or clause, at a different offset.
+    Moved,
+    Changed,
+    Removed,
+    Added,
+}
+
+impl Change {
+    pub fn marker(self) -> char {
+        match self {
+            Change::Unchanged => ' ',
+            Change::Moved => '~',
+            Change::Changed => '!',
+            Change::Removed => '-',
+            Change::Added => '+',
+        }
+    }
+}
+
+/// One line of a side by side diff; `old` or `new` is `None` for added and
+/// removed lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffRow {
+    pub change: Change,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// The difference between a method body as it is on disk or in the runtime and
+/// the body a rewrite produced, together with the issues `validate` found in
+/// the rewritten body:
+///
+/// ```text
+///   old: tiny header, code size 7
+///   new: tiny header, code size 2
+/// ! IL_0000: ldstr "hello"                   | IL_0000: ldc.i4.1
+/// ! IL_0005: call System.Console::WriteLine  | IL_0001: ret
+/// - IL_000a: ret                             |
+/// validation: ok
+/// ```
+#[derive(Debug, Clone)]
+pub struct PatchPreview {
+    pub old_header: String,
+    pub new_header: String,
+    pub instructions: Vec<DiffRow>,
+    pub exception_clauses: Vec<DiffRow>,
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl PatchPreview {
+    pub fn new<M: MetadataImportTrait>(
+        disassembler: &Disassembler<M>,
+        old: &Method,
+        new: &Method,
+    ) -> Self {
+        // instructions are matched on their raw operands, so a branch whose
+        // target shifted along with it still counts as moved, not changed
+        let old_instructions = old.instruction_offsets();
+        let new_instructions = new.instruction_offsets();
+        let old_keys = old_instructions
+            .iter()
+            .map(|(_, instruction)| instruction.to_string())
+            .collect::<Vec<_>>();
+        let new_keys = new_instructions
+            .iter()
+            .map(|(_, instruction)| instruction.to_string())
+            .collect::<Vec<_>>();
+        let instructions = diff(&old_keys, &new_keys)
+            .into_iter()
+            .map(|(change, old_index, new_index)| {
+                let old_line = old_index.map(|i| {
+                    let (offset, instruction) = old_instructions[i];
+                    disassembler.instruction(offset, instruction)
+                });
+                let new_line = new_index.map(|i| {
+                    let (offset, instruction) = new_instructions[i];
+                    disassembler.instruction(offset, instruction)
+                });
+                let change = match (change, old_index, new_index) {
+                    (Change::Unchanged, Some(o), Some(n))
+                        if old_instructions[o].0 != new_instructions[n].0 =>
+                    {
+                        Change::Moved
+                    }
+                    _ => change,
+                };
+                DiffRow {
+                    change,
+                    old: old_line,
+                    new: new_line,
+                }
+            })
+            .collect();
+
+        let old_clauses = old.exception_clauses();
+        let new_clauses = new.exception_clauses();
+        let exception_clauses = (0..old_clauses.len().max(new_clauses.len()))
+            .map(|i| {
+                let (old_clause, new_clause) = (old_clauses.get(i), new_clauses.get(i));
+                let change = match (old_clause, new_clause) {
+                    (Some(o), Some(n)) if o == n => Change::Unchanged,
+                    (Some(o), Some(n)) if o.kind == n.kind => Change::Moved,
+                    (Some(_), Some(_)) => Change::Changed,
+                    (Some(_), None) => Change::Removed,
+                    _ => Change::Added,
+                };
+                DiffRow {
+                    change,
+                    old: old_clause.map(|clause| disassembler.exception_clause(clause)),
+                    new: new_clause.map(|clause| disassembler.exception_clause(clause)),
+                }
+            })
+            .collect();
+
+        PatchPreview {
+            old_header: disassembler.header(old),
+            new_header: disassembler.header(new),
+            instructions,
+            exception_clauses,
+            issues: validate(new),
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl fmt::Display for PatchPreview {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "  old: {}", self.old_header)?;
+        writeln!(f, "  new: {}", self.new_header)?;
+        let rows = self.instructions.iter().chain(&self.exception_clauses);
+        let width = rows
+            .clone()
+            .filter_map(|row| row.old.as_ref())
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        for row in rows {
+            let old = row.old.as_deref().unwrap_or("");
+            let new = row.new.as_deref().unwrap_or("");
+            let line = format!("{} {old:width$} | {new}", row.change.marker());
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        if self.issues.is_empty() {
+            write!(f, "validation: ok")
+        } else {
+            write!(f, "validation:")?;
+            for issue in &self.issues {
+                write!(f, "\n  {issue}")?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Longest common subsequence diff of two lines of keys. A run of removed
+/// lines directly followed by added ones is paired up into changed lines.
+fn diff(old: &[String], new: &[String]) -> Vec<(Change, Option<usize>, Option<usize>)> {
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut rows = Vec::new();
+    let (mut removed, mut added) = (Vec::new(), Vec::new());
+    let flush = |rows: &mut Vec<_>, removed: &mut Vec<usize>, added: &mut Vec<usize>| {
+        let paired = removed.len().min(added.len());
+        for k in 0..removed.len().max(added.len()) {
+            let change = if k < paired {
+                Change::Changed
+            } else if k < removed.len() {
+                Change::Removed
+            } else {
+                Change::Added
+            };
+            rows.push((change, removed.get(k).copied(), added.get(k).copied()));
+        }
+        removed.clear();
+        added.clear();
+    };
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            flush(&mut rows, &mut removed, &mut added);
+            rows.push((Change::Unchanged, Some(i), Some(j)));
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && lengths[i + 1][j] >= lengths[i][j + 1]) {
+            removed.push(i);
+            i += 1;
+        } else {
+            added.push(j);
+            j += 1;
+        }
+    }
+    flush(&mut rows, &mut removed, &mut added);
+    rows
+}
@@ -1,5 +1,6 @@
 #![allow(non_upper_case_globals)]
 use crate::cil::{check_flag, il_u16, il_u32, il_u8, Error};
+use crate::metadata::Token;
 
 bitflags! {
     pub struct SectionHeaderFlags: u8 {
@@ -290,3 +291,21 @@ impl Section {
         Ok(clauses)
     }
 }
+
+/// An exception handling clause, with offsets relative to the start of the IL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExceptionClause {
+    pub kind: ExceptionClauseKind,
+    pub try_offset: u32,
+    pub try_length: u32,
+    pub handler_offset: u32,
+    pub handler_length: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExceptionClauseKind {
+    Catch(Token),
+    Filter(u32),
+    Finally,
+    Fault,
+}
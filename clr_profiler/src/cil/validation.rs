@@ -0,0 +1,163 @@
+use crate::cil::{ControlFlow, Method, MethodHeader, Operand};
+use std::{collections::HashSet, fmt};
+
+/// A structural problem with a method body that would make the runtime reject
+/// it, or make it behave differently than intended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationIssue {
+    EmptyBody,
+    CodeSizeMismatch {
+        header: u32,
+        actual: u32,
+    },
+    TinyHeaderTooLarge(u32),
+    /// A branch or switch at `offset` whose target is outside the code.
+    BranchOutOfRange {
+        offset: u32,
+        target: i64,
+    },
+    /// A branch or switch at `offset` whose target is inside another instruction.
+    BranchIntoInstruction {
+        offset: u32,
+        target: i64,
+    },
+    /// An exception clause, by index, whose ranges don't start and end on
+    /// instruction boundaries within the code.
+    InvalidExceptionClause(usize),
+    FallsThroughEnd,
+    /// The serialized body doesn't parse back into the same instructions.
+    RoundTrip,
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationIssue::EmptyBody => write!(f, "method body has no instructions"),
+            ValidationIssue::CodeSizeMismatch { header, actual } => write!(
+                f,
+                "header code size {header} doesn't match instructions size {actual}"
+            ),
+            ValidationIssue::TinyHeaderTooLarge(size) => {
+                write!(f, "code size {size} doesn't fit in a tiny header")
+            }
+            ValidationIssue::BranchOutOfRange { offset, target } => write!(
+                f,
+                "branch at IL_{offset:04x} targets {target:#x}, outside the method"
+            ),
+            ValidationIssue::BranchIntoInstruction { offset, target } => write!(
+                f,
+                "branch at IL_{offset:04x} targets IL_{target:04x}, inside an instruction"
+            ),
+            ValidationIssue::InvalidExceptionClause(index) => {
+                write!(
+                    f,
+                    "exception clause {index} isn't on instruction boundaries"
+                )
+            }
+            ValidationIssue::FallsThroughEnd => {
+                write!(f, "last instruction falls through the end of the method")
+            }
+            ValidationIssue::RoundTrip => write!(f, "serialized body doesn't parse back"),
+        }
+    }
+}
+
+/// Checks a (usually rewritten) method body before it's handed to the runtime
+/// with `SetILFunctionBody`. Returns no issues for a valid body.
+pub fn validate(method: &Method) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    let offsets = method.instruction_offsets();
+    let code_size = method
+        .instructions
+        .iter()
+        .map(|i| i.length() as u32)
+        .sum::<u32>();
+    let header_size = match &method.method_header {
+        MethodHeader::Tiny(header) => {
+            if header.code_size >= 64 {
+                issues.push(ValidationIssue::TinyHeaderTooLarge(header.code_size as u32));
+            }
+            header.code_size as u32
+        }
+        MethodHeader::Fat(header) => header.code_size,
+    };
+    if header_size != code_size {
+        issues.push(ValidationIssue::CodeSizeMismatch {
+            header: header_size,
+            actual: code_size,
+        });
+    }
+
+    let Some((_, last)) = offsets.last() else {
+        issues.push(ValidationIssue::EmptyBody);
+        return issues;
+    };
+    let ends_block = matches!(
+        last.opcode.control_flow,
+        ControlFlow::Return | ControlFlow::Throw | ControlFlow::Branch
+    ) || last.opcode.name == "jmp";
+    if !ends_block {
+        issues.push(ValidationIssue::FallsThroughEnd);
+    }
+
+    // the end of the code is a valid boundary for exception clauses, not branches
+    let boundaries = offsets
+        .iter()
+        .map(|(offset, _)| *offset as i64)
+        .collect::<HashSet<_>>();
+    for (offset, instruction) in &offsets {
+        let next = *offset as i64 + instruction.length() as i64;
+        let targets = match &instruction.operand {
+            Operand::ShortInlineBrTarget(delta) => vec![next + *delta as i64],
+            Operand::InlineBrTarget(delta) => vec![next + *delta as i64],
+            Operand::InlineSwitch(_, deltas) => {
+                deltas.iter().map(|delta| next + *delta as i64).collect()
+            }
+            _ => continue,
+        };
+        for target in targets {
+            if target < 0 || target >= code_size as i64 {
+                issues.push(ValidationIssue::BranchOutOfRange {
+                    offset: *offset,
+                    target,
+                });
+            } else if !boundaries.contains(&target) {
+                issues.push(ValidationIssue::BranchIntoInstruction {
+                    offset: *offset,
+                    target,
+                });
+            }
+        }
+    }
+
+    let on_boundary =
+        |offset: u64| offset == code_size as u64 || boundaries.contains(&(offset as i64));
+    for (index, clause) in method.exception_clauses().iter().enumerate() {
+        let try_end = clause.try_offset as u64 + clause.try_length as u64;
+        let handler_end = clause.handler_offset as u64 + clause.handler_length as u64;
+        let valid = clause.try_length > 0
+            && clause.handler_length > 0
+            && on_boundary(clause.try_offset as u64)
+            && on_boundary(try_end)
+            && on_boundary(clause.handler_offset as u64)
+            && on_boundary(handler_end)
+            && try_end <= code_size as u64
+            && handler_end <= code_size as u64;
+        if !valid {
+            issues.push(ValidationIssue::InvalidExceptionClause(index));
+        }
+    }
+
+    // parsing a body whose sizes are off can read out of bounds
+    if issues.is_empty() {
+        let bytes = method.into_bytes();
+        let round_trips = Method::new(bytes.as_ptr(), bytes.len() as u32).is_ok_and(|parsed| {
+            parsed.instructions.len() == method.instructions.len()
+                && parsed.exception_clauses() == method.exception_clauses()
+        });
+        if !round_trips {
+            issues.push(ValidationIssue::RoundTrip);
+        }
+    }
+    issues
+}
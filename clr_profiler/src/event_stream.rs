@@ -0,0 +1,1536 @@
+mod bounded_queue;
+
+use self::bounded_queue::BoundedQueue;
+use crate::{
+    ffi::{
+        AppDomainID, AssemblyID, ClassID, ComPtr, CorProfilerAssemblyReferenceProvider,
+        CorProfilerFunctionControl, FunctionID, GCHandleID, HResult, ModuleID, ObjectID, ReJITID,
+        ThreadID, COR_PRF_FINALIZER_FLAGS, COR_PRF_GC_REASON, COR_PRF_GC_ROOT_FLAGS,
+        COR_PRF_GC_ROOT_KIND, COR_PRF_JIT_CACHE, COR_PRF_SUSPEND_REASON, COR_PRF_TRANSITION_REASON,
+        DWORD, EVENTPIPE_PROVIDER, GUID, UINT_PTR,
+    },
+    metadata::MethodDefToken,
+    ClrProfiler, CorProfilerInfo, ProfilerInfo,
+};
+use log::{error, warn};
+use std::{
+    marker::PhantomData,
+    panic::{self, AssertUnwindSafe},
+    slice,
+    sync::{
+        atomic::{self, AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        mpsc, Arc, Condvar, Mutex, OnceLock,
+    },
+    thread::{self, JoinHandle, Thread},
+};
+use uuid::Uuid;
+
+/// A callback, with what it was passed. Variants are named after the
+/// `ICorProfilerCallback` methods and have the fields of the matching trait
+/// method, copied out of the runtime's buffers.
+#[derive(Clone, Debug)]
+pub enum ProfilerEvent {
+    Initialize {
+        profiler_info: ProfilerInfo,
+    },
+    Shutdown,
+    AppDomainCreationStarted {
+        app_domain_id: AppDomainID,
+    },
+    AppDomainCreationFinished {
+        app_domain_id: AppDomainID,
+        hr_status: HResult,
+    },
+    AppDomainShutdownStarted {
+        app_domain_id: AppDomainID,
+    },
+    AppDomainShutdownFinished {
+        app_domain_id: AppDomainID,
+        hr_status: HResult,
+    },
+    AssemblyLoadStarted {
+        assembly_id: AssemblyID,
+    },
+    AssemblyLoadFinished {
+        assembly_id: AssemblyID,
+        hr_status: HResult,
+    },
+    AssemblyUnloadStarted {
+        assembly_id: AssemblyID,
+    },
+    AssemblyUnloadFinished {
+        assembly_id: AssemblyID,
+        hr_status: HResult,
+    },
+    ModuleLoadStarted {
+        module_id: ModuleID,
+    },
+    ModuleLoadFinished {
+        module_id: ModuleID,
+        hr_status: HResult,
+    },
+    ModuleUnloadStarted {
+        module_id: ModuleID,
+    },
+    ModuleUnloadFinished {
+        module_id: ModuleID,
+        hr_status: HResult,
+    },
+    ModuleAttachedToAssembly {
+        module_id: ModuleID,
+        assembly_id: AssemblyID,
+    },
+    ClassLoadStarted {
+        class_id: ClassID,
+    },
+    ClassLoadFinished {
+        class_id: ClassID,
+        hr_status: HResult,
+    },
+    ClassUnloadStarted {
+        class_id: ClassID,
+    },
+    ClassUnloadFinished {
+        class_id: ClassID,
+        hr_status: HResult,
+    },
+    FunctionUnloadStarted {
+        function_id: FunctionID,
+    },
+    JITCompilationStarted {
+        function_id: FunctionID,
+        is_safe_to_block: bool,
+    },
+    JITCompilationFinished {
+        function_id: FunctionID,
+        hr_status: HResult,
+        is_safe_to_block: bool,
+    },
+    JITCachedFunctionSearchStarted {
+        function_id: FunctionID,
+        use_cached_function: bool,
+    },
+    JITCachedFunctionSearchFinished {
+        function_id: FunctionID,
+        result: COR_PRF_JIT_CACHE,
+    },
+    JITFunctionPitched {
+        function_id: FunctionID,
+    },
+    JITInlining {
+        caller_id: FunctionID,
+        callee_id: FunctionID,
+        should_inline: bool,
+    },
+    ThreadCreated {
+        thread_id: ThreadID,
+    },
+    ThreadDestroyed {
+        thread_id: ThreadID,
+    },
+    ThreadAssignedToOSThread {
+        managed_thread_id: ThreadID,
+        os_thread_id: DWORD,
+    },
+    RemotingClientInvocationStarted,
+    RemotingClientSendingMessage {
+        cookie: GUID,
+        is_async: bool,
+    },
+    RemotingClientReceivingReply {
+        cookie: GUID,
+        is_async: bool,
+    },
+    RemotingClientInvocationFinished,
+    RemotingServerReceivingMessage {
+        cookie: GUID,
+        is_async: bool,
+    },
+    RemotingServerInvocationStarted,
+    RemotingServerInvocationReturned,
+    RemotingServerSendingReply {
+        cookie: GUID,
+        is_async: bool,
+    },
+    UnmanagedToManagedTransition {
+        function_id: FunctionID,
+        reason: COR_PRF_TRANSITION_REASON,
+    },
+    ManagedToUnmanagedTransition {
+        function_id: FunctionID,
+        reason: COR_PRF_TRANSITION_REASON,
+    },
+    RuntimeSuspendStarted {
+        suspend_reason: COR_PRF_SUSPEND_REASON,
+    },
+    RuntimeSuspendFinished,
+    RuntimeSuspendAborted,
+    RuntimeResumeStarted,
+    RuntimeResumeFinished,
+    RuntimeThreadSuspended {
+        thread_id: ThreadID,
+    },
+    RuntimeThreadResumed {
+        thread_id: ThreadID,
+    },
+    MovedReferences {
+        old_object_id_range_start: Vec<ObjectID>,
+        new_object_id_range_start: Vec<ObjectID>,
+        object_id_range_length: Vec<u32>,
+    },
+    ObjectAllocated {
+        object_id: ObjectID,
+        class_id: ClassID,
+    },
+    ObjectsAllocatedByClass {
+        class_ids: Vec<ClassID>,
+        num_objects: Vec<u32>,
+    },
+    ObjectReferences {
+        object_id: ObjectID,
+        class_id: ClassID,
+        object_ref_ids: Vec<ObjectID>,
+    },
+    RootReferences {
+        root_ref_ids: Vec<ObjectID>,
+    },
+    ExceptionThrown {
+        thrown_object_id: ObjectID,
+    },
+    ExceptionSearchFunctionEnter {
+        function_id: FunctionID,
+    },
+    ExceptionSearchFunctionLeave,
+    ExceptionSearchFilterEnter {
+        function_id: FunctionID,
+    },
+    ExceptionSearchFilterLeave,
+    ExceptionSearchCatcherFound {
+        function_id: FunctionID,
+    },
+    ExceptionOSHandlerEnter,
+    ExceptionOSHandlerLeave,
+    ExceptionUnwindFunctionEnter {
+        function_id: FunctionID,
+    },
+    ExceptionUnwindFunctionLeave,
+    ExceptionUnwindFinallyEnter {
+        function_id: FunctionID,
+    },
+    ExceptionUnwindFinallyLeave,
+    ExceptionCatcherEnter {
+        function_id: FunctionID,
+        object_id: ObjectID,
+    },
+    ExceptionCatcherLeave,
+    COMClassicVTableCreated {
+        wrapped_class_id: ClassID,
+        implemented_iid: Option<GUID>,
+        vtable: UINT_PTR,
+        slots: u32,
+    },
+    COMClassicVTableDestroyed {
+        wrapped_class_id: ClassID,
+        implemented_iid: Option<GUID>,
+        vtable: UINT_PTR,
+    },
+    ExceptionCLRCatcherFound,
+    ExceptionCLRCatcherExecute,
+    ThreadNameChanged {
+        thread_id: ThreadID,
+        name: String,
+    },
+    GarbageCollectionStarted {
+        generation_collected: Vec<bool>,
+        reason: COR_PRF_GC_REASON,
+    },
+    SurvivingReferences {
+        object_id_range_start: Vec<ObjectID>,
+        object_id_range_length: Vec<u32>,
+    },
+    GarbageCollectionFinished,
+    FinalizeableObjectQueued {
+        finalizer_flags: COR_PRF_FINALIZER_FLAGS,
+        object_id: ObjectID,
+    },
+    RootReferences2 {
+        root_ref_ids: Vec<ObjectID>,
+        root_kinds: Vec<COR_PRF_GC_ROOT_KIND>,
+        root_flags: Vec<COR_PRF_GC_ROOT_FLAGS>,
+        root_ids: Vec<UINT_PTR>,
+    },
+    HandleCreated {
+        handle_id: GCHandleID,
+        initial_object_id: ObjectID,
+    },
+    HandleDestroyed {
+        handle_id: GCHandleID,
+    },
+    InitializeForAttach {
+        profiler_info: ProfilerInfo,
+        client_data: Vec<u8>,
+    },
+    ProfilerAttachComplete,
+    ProfilerDetachSucceeded,
+    ReJITCompilationStarted {
+        function_id: FunctionID,
+        rejit_id: ReJITID,
+        is_safe_to_block: bool,
+    },
+    /// The function control only works until the event has been handled.
+    GetReJITParameters {
+        module_id: ModuleID,
+        method_id: MethodDefToken,
+        function_control: ComPtr<CorProfilerFunctionControl>,
+    },
+    ReJITCompilationFinished {
+        function_id: FunctionID,
+        rejit_id: ReJITID,
+        hr_status: HResult,
+        is_safe_to_block: bool,
+    },
+    ReJITError {
+        module_id: ModuleID,
+        method_id: MethodDefToken,
+        function_id: FunctionID,
+        hr_status: HResult,
+    },
+    MovedReferences2 {
+        old_object_id_range_start: Vec<ObjectID>,
+        new_object_id_range_start: Vec<ObjectID>,
+        object_id_range_length: Vec<usize>,
+    },
+    SurvivingReferences2 {
+        object_id_range_start: Vec<ObjectID>,
+        object_id_range_length: Vec<usize>,
+    },
+    ConditionalWeakTableElementReferences {
+        key_ref_ids: Vec<ObjectID>,
+        value_ref_ids: Vec<ObjectID>,
+        root_ids: Vec<GCHandleID>,
+    },
+    /// The provider only works until the event has been handled.
+    GetAssemblyReferences {
+        assembly_path: String,
+        asm_ref_provider: ComPtr<CorProfilerAssemblyReferenceProvider>,
+    },
+    ModuleInMemorySymbolsUpdated {
+        module_id: ModuleID,
+    },
+    DynamicMethodJITCompilationStarted {
+        function_id: FunctionID,
+        is_safe_to_block: bool,
+        il_header: Vec<u8>,
+    },
+    DynamicMethodJITCompilationFinished {
+        function_id: FunctionID,
+        hr_status: HResult,
+        is_safe_to_block: bool,
+    },
+    DynamicMethodUnloaded {
+        function_id: FunctionID,
+    },
+    EventPipeEventDelivered {
+        provider: EVENTPIPE_PROVIDER,
+        event_id: u32,
+        event_version: u32,
+        metadata_blob: Vec<u8>,
+        event_data: Vec<u8>,
+        activity_id: Option<GUID>,
+        related_activity_id: Option<GUID>,
+        event_thread: ThreadID,
+        stack_frames: Vec<UINT_PTR>,
+    },
+    EventPipeProviderCreated {
+        provider: EVENTPIPE_PROVIDER,
+    },
+    /// Not a callback: `count` events didn't fit in the queue between the
+    /// event handled before and the one after, see [`OverflowPolicy::Count`].
+    Dropped {
+        count: u64,
+    },
+}
+
+impl ProfilerEvent {
+    /// Whether the callback waits for the event to be handled, because what
+    /// the handler does depends on the callback still running: setting the
+    /// event mask, answering with a function control or an assembly reference
+    /// provider, or draining the queue on shutdown. The events queued before
+    /// are handled first, so the callback waits for those too.
+    pub fn is_synchronous(&self) -> bool {
+        matches!(
+            self,
+            ProfilerEvent::Initialize { .. }
+                | ProfilerEvent::InitializeForAttach { .. }
+                | ProfilerEvent::Shutdown
+                | ProfilerEvent::GetReJITParameters { .. }
+                | ProfilerEvent::GetAssemblyReferences { .. }
+        )
+    }
+}
+
+/// What a callback does when the queue is full. Synchronous events always
+/// wait for room.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Drop the event.
+    Drop,
+    /// Drop the event and report how many were dropped with
+    /// [`ProfilerEvent::Dropped`].
+    Count,
+    /// Wait for the worker to make room.
+    Block,
+}
+
+/// Handles the events of an [`EventStream`] on its worker thread, one at a
+/// time and in the order their callbacks were made.
+pub trait EventHandler: ClrProfiler + Send + 'static {
+    /// How many events the queue holds, rounded up to a power of two.
+    const CAPACITY: usize = 4096;
+    const OVERFLOW_POLICY: OverflowPolicy = OverflowPolicy::Count;
+
+    /// A failure is returned from synchronous callbacks and logged for the
+    /// others, whose callback has already returned.
+    fn handle(&mut self, event: ProfilerEvent) -> Result<(), HResult>;
+
+    /// Rewrites methods as they're JIT compiled. Asked for once, before the
+    /// worker starts.
+    fn il_rewriter(&self) -> Option<Arc<dyn IlRewriter>> {
+        None
+    }
+}
+
+/// Rewrites the IL of methods for an [`EventStream`], on the thread that
+/// compiles them: the runtime takes a new body from within
+/// `JITCompilationStarted` only.
+pub trait IlRewriter: Send + Sync {
+    /// `il_body` is the method header and body; `None` leaves it as it is.
+    /// Called before the handler gets [`ProfilerEvent::JITCompilationStarted`].
+    fn rewrite_il(
+        &self,
+        module_id: ModuleID,
+        method_id: MethodDefToken,
+        il_body: &[u8],
+    ) -> Result<Option<Vec<u8>>, HResult>;
+}
+
+/// A profiler that turns callbacks into [`ProfilerEvent`]s and hands them to
+/// an [`EventHandler`] on a worker thread, so logging, name resolution and
+/// I/O stay out of the target's threads.
+///
+/// Callbacks push onto a bounded lock-free queue and return, unless the event
+/// [is synchronous](ProfilerEvent::is_synchronous); those wait for the handler
+/// and return its result. The handler turns events on through the
+/// `ProfilerInfo` of [`ProfilerEvent::Initialize`], IL is rewritten by its
+/// [`IlRewriter`] on the callback's thread.
+/// `LoadAsNotificationOnly` isn't an event, the profiler is always loaded as
+/// the main profiler.
+///
+/// The worker stops once every clone of the stream is dropped.
+pub struct EventStream<H: EventHandler> {
+    shared: Arc<Shared>,
+    handler: PhantomData<fn() -> H>,
+}
+
+struct Shared {
+    clsid: Uuid,
+    channel: Arc<Channel>,
+    worker: Thread,
+    join_handle: Mutex<Option<JoinHandle<()>>>,
+    rewriter: Option<Arc<dyn IlRewriter>>,
+    profiler_info: OnceLock<ProfilerInfo>,
+}
+
+struct Channel {
+    queue: BoundedQueue<Queued>,
+    policy: OverflowPolicy,
+    /// Events dropped since the last one queued.
+    dropped: AtomicU64,
+    closed: AtomicBool,
+    /// Callbacks waiting for room, woken by the worker as it pops.
+    waiting: AtomicUsize,
+    room: Mutex<()>,
+    has_room: Condvar,
+}
+
+struct Queued {
+    event: ProfilerEvent,
+    /// Set for synchronous events.
+    reply: Option<mpsc::SyncSender<Result<(), HResult>>>,
+    /// How many events were dropped right before this one.
+    dropped: u64,
+}
+
+// the COM objects in events are only used while the callback that passed them
+// waits, and ICorProfilerInfo can be called from any thread
+unsafe impl Send for Queued {}
+
+impl<H: EventHandler> EventStream<H> {
+    pub fn with_handler(handler: H) -> Self {
+        let clsid = *handler.clsid();
+        let rewriter = handler.il_rewriter();
+        let channel = Arc::new(Channel {
+            queue: BoundedQueue::new(H::CAPACITY),
+            policy: H::OVERFLOW_POLICY,
+            dropped: AtomicU64::new(0),
+            closed: AtomicBool::new(false),
+            waiting: AtomicUsize::new(0),
+            room: Mutex::new(()),
+            has_room: Condvar::new(),
+        });
+        let worker_channel = channel.clone();
+        let join_handle = thread::Builder::new()
+            .name("profiler events".to_string())
+            .spawn(move || run(handler, &worker_channel))
+            .expect("failed to start the profiler event worker");
+        EventStream {
+            shared: Arc::new(Shared {
+                clsid,
+                channel,
+                worker: join_handle.thread().clone(),
+                join_handle: Mutex::new(Some(join_handle)),
+                rewriter,
+                profiler_info: OnceLock::new(),
+            }),
+            handler: PhantomData,
+        }
+    }
+
+    fn send(&self, event: ProfilerEvent) -> Result<(), HResult> {
+        let rewritten = match event {
+            ProfilerEvent::Initialize { ref profiler_info }
+            | ProfilerEvent::InitializeForAttach {
+                ref profiler_info, ..
+            } => {
+                let _ = self.shared.profiler_info.set(profiler_info.clone());
+                Ok(())
+            }
+            ProfilerEvent::JITCompilationStarted { function_id, .. } => {
+                self.rewrite_jit(function_id)
+            }
+            _ => Ok(()),
+        };
+        self.queue(event).and(rewritten)
+    }
+
+    fn queue(&self, event: ProfilerEvent) -> Result<(), HResult> {
+        let channel = &self.shared.channel;
+        if event.is_synchronous() {
+            let (reply, answer) = mpsc::sync_channel(1);
+            self.push_waiting(Queued {
+                event,
+                reply: Some(reply),
+                dropped: channel.dropped.swap(0, Ordering::Relaxed),
+            });
+            // the handler panicked if the reply was dropped
+            return answer.recv().unwrap_or(Err(HResult::E_FAIL));
+        }
+        let queued = Queued {
+            event,
+            reply: None,
+            dropped: channel.dropped.swap(0, Ordering::Relaxed),
+        };
+        match channel.policy {
+            OverflowPolicy::Block => self.push_waiting(queued),
+            OverflowPolicy::Drop => {
+                let _ = channel.queue.push(queued);
+                self.shared.worker.unpark();
+            }
+            OverflowPolicy::Count => {
+                if let Err(rejected) = channel.queue.push(queued) {
+                    // the drops it carried are reported with the next event
+                    channel
+                        .dropped
+                        .fetch_add(rejected.dropped + 1, Ordering::Relaxed);
+                }
+                self.shared.worker.unpark();
+            }
+        }
+        Ok(())
+    }
+
+    fn push_waiting(&self, mut queued: Queued) {
+        let channel = &self.shared.channel;
+        loop {
+            match channel.queue.push(queued) {
+                Ok(()) => break,
+                Err(rejected) => queued = rejected,
+            }
+            self.shared.worker.unpark();
+            let room = channel
+                .room
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            channel.waiting.fetch_add(1, Ordering::SeqCst);
+            // the worker may have popped since the push failed, before it
+            // could see this callback waiting
+            atomic::fence(Ordering::SeqCst);
+            match channel.queue.push(queued) {
+                Ok(()) => {
+                    channel.waiting.fetch_sub(1, Ordering::SeqCst);
+                    break;
+                }
+                Err(rejected) => queued = rejected,
+            }
+            let room = channel
+                .has_room
+                .wait(room)
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            channel.waiting.fetch_sub(1, Ordering::SeqCst);
+            drop(room);
+        }
+        self.shared.worker.unpark();
+    }
+
+    /// Runs the [`IlRewriter`] on a method about to be compiled.
+    fn rewrite_jit(&self, function_id: FunctionID) -> Result<(), HResult> {
+        let (Some(rewriter), Some(profiler_info)) =
+            (&self.shared.rewriter, self.shared.profiler_info.get())
+        else {
+            return Ok(());
+        };
+        let function = profiler_info.get_function_info(function_id)?;
+        // methods without IL, like those implemented by the runtime
+        let Ok(body) = profiler_info.get_il_function_body(function.module_id, function.token)
+        else {
+            return Ok(());
+        };
+        let il_body =
+            unsafe { slice::from_raw_parts(body.method_header, body.method_size as usize) };
+        match rewriter.rewrite_il(function.module_id, function.token, il_body)? {
+            Some(rewritten) => profiler_info.replace_il_function_body(
+                function.module_id,
+                function.token,
+                &rewritten,
+            ),
+            None => Ok(()),
+        }
+    }
+}
+
+fn run<H: EventHandler>(mut handler: H, channel: &Channel) {
+    loop {
+        let Some(Queued {
+            event,
+            reply,
+            dropped,
+        }) = channel.queue.pop()
+        else {
+            if channel.closed.load(Ordering::Acquire) {
+                return;
+            }
+            // pushes unpark the worker, so one that came in since the pop
+            // makes this return at once
+            thread::park();
+            continue;
+        };
+        atomic::fence(Ordering::SeqCst);
+        if channel.waiting.load(Ordering::SeqCst) > 0 {
+            let _room = channel
+                .room
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            channel.has_room.notify_all();
+        }
+        if dropped > 0 {
+            let _ = handle(&mut handler, ProfilerEvent::Dropped { count: dropped });
+        }
+        let result = handle(&mut handler, event);
+        if let Some(reply) = reply {
+            let _ = reply.send(result);
+        }
+    }
+}
+
+fn handle<H: EventHandler>(handler: &mut H, event: ProfilerEvent) -> Result<(), HResult> {
+    let synchronous = event.is_synchronous();
+    match panic::catch_unwind(AssertUnwindSafe(|| handler.handle(event))) {
+        Ok(Err(hr)) if !synchronous => {
+            warn!("profiler event handler failed: {hr}");
+            Err(hr)
+        }
+        Ok(result) => result,
+        Err(_) => {
+            error!("profiler event handler panicked");
+            Err(HResult::E_FAIL)
+        }
+    }
+}
+
+impl Drop for Shared {
+    fn drop(&mut self) {
+        self.channel.closed.store(true, Ordering::Release);
+        self.worker.unpark();
+        if let Some(join_handle) = self.join_handle.get_mut().unwrap().take() {
+            let _ = join_handle.join();
+        }
+    }
+}
+
+impl<H: EventHandler> Clone for EventStream<H> {
+    fn clone(&self) -> Self {
+        EventStream {
+            shared: self.shared.clone(),
+            handler: PhantomData,
+        }
+    }
+}
+
+impl<H: EventHandler> ClrProfiler for EventStream<H> {
+    fn new() -> Self {
+        Self::with_handler(H::new())
+    }
+
+    fn clsid(&self) -> &Uuid {
+        &self.shared.clsid
+    }
+}
+
+/// Implements every `CorProfilerCallbackN` for a profiler that turns
+/// callbacks into events with a `send(&self, ProfilerEvent) -> Result<(),
+/// HResult>` method. `LoadAsNotificationOnly` keeps its default.
+macro_rules! event_callbacks {
+    ([$($generics:tt)*] $profiler:ty) => {
+        const _: () = {
+            use $crate::{
+                ffi::{
+                    AppDomainID, AssemblyID, ClassID, ComPtr, CorProfilerAssemblyReferenceProvider,
+                    CorProfilerFunctionControl, FunctionID, GCHandleID, HResult, ModuleID,
+                    ObjectID, ReJITID, ThreadID, BOOL, COR_PRF_FINALIZER_FLAGS, COR_PRF_GC_REASON,
+                    COR_PRF_GC_ROOT_FLAGS, COR_PRF_GC_ROOT_KIND, COR_PRF_JIT_CACHE,
+                    COR_PRF_SUSPEND_REASON, COR_PRF_TRANSITION_REASON, DWORD, EVENTPIPE_PROVIDER,
+                    GUID, LPCBYTE, REFGUID, UINT_PTR,
+                },
+                metadata::MethodDefToken,
+                CorProfilerCallback, CorProfilerCallback10, CorProfilerCallback11,
+                CorProfilerCallback2, CorProfilerCallback3, CorProfilerCallback4,
+                CorProfilerCallback5, CorProfilerCallback6, CorProfilerCallback7,
+                CorProfilerCallback8, CorProfilerCallback9, EventPipeEvent, ProfilerEvent,
+                ProfilerInfo,
+            };
+            use std::{ffi::c_void, slice};
+
+            // The runtime's pointers are valid for the callback they're passed to, so
+            // these are safe to call from callbacks only.
+
+            fn copy_items<T: Clone>(items: *const T, count: usize) -> Vec<T> {
+                if items.is_null() {
+                    return Vec::new();
+                }
+                unsafe { slice::from_raw_parts(items, count) }.to_vec()
+            }
+
+            fn copy_guid(guid: REFGUID) -> Option<GUID> {
+                unsafe { guid.as_ref() }.copied()
+            }
+
+            impl<$($generics)*> CorProfilerCallback for $profiler {
+                fn initialize(&self, profiler_info: ProfilerInfo) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::Initialize { profiler_info })
+                }
+
+                fn shutdown(&self) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::Shutdown)
+                }
+
+                fn app_domain_creation_started(
+                    &self,
+                    app_domain_id: AppDomainID,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::AppDomainCreationStarted { app_domain_id })
+                }
+
+                fn app_domain_creation_finished(
+                    &self,
+                    app_domain_id: AppDomainID,
+                    hr_status: HResult,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::AppDomainCreationFinished {
+                        app_domain_id,
+                        hr_status,
+                    })
+                }
+
+                fn app_domain_shutdown_started(
+                    &self,
+                    app_domain_id: AppDomainID,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::AppDomainShutdownStarted { app_domain_id })
+                }
+
+                fn app_domain_shutdown_finished(
+                    &self,
+                    app_domain_id: AppDomainID,
+                    hr_status: HResult,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::AppDomainShutdownFinished {
+                        app_domain_id,
+                        hr_status,
+                    })
+                }
+
+                fn assembly_load_started(&self, assembly_id: AssemblyID) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::AssemblyLoadStarted { assembly_id })
+                }
+
+                fn assembly_load_finished(
+                    &self,
+                    assembly_id: AssemblyID,
+                    hr_status: HResult,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::AssemblyLoadFinished {
+                        assembly_id,
+                        hr_status,
+                    })
+                }
+
+                fn assembly_unload_started(&self, assembly_id: AssemblyID) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::AssemblyUnloadStarted { assembly_id })
+                }
+
+                fn assembly_unload_finished(
+                    &self,
+                    assembly_id: AssemblyID,
+                    hr_status: HResult,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::AssemblyUnloadFinished {
+                        assembly_id,
+                        hr_status,
+                    })
+                }
+
+                fn module_load_started(&self, module_id: ModuleID) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ModuleLoadStarted { module_id })
+                }
+
+                fn module_load_finished(
+                    &self,
+                    module_id: ModuleID,
+                    hr_status: HResult,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ModuleLoadFinished {
+                        module_id,
+                        hr_status,
+                    })
+                }
+
+                fn module_unload_started(&self, module_id: ModuleID) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ModuleUnloadStarted { module_id })
+                }
+
+                fn module_unload_finished(
+                    &self,
+                    module_id: ModuleID,
+                    hr_status: HResult,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ModuleUnloadFinished {
+                        module_id,
+                        hr_status,
+                    })
+                }
+
+                fn module_attached_to_assembly(
+                    &self,
+                    module_id: ModuleID,
+                    assembly_id: AssemblyID,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ModuleAttachedToAssembly {
+                        module_id,
+                        assembly_id,
+                    })
+                }
+
+                fn class_load_started(&self, class_id: ClassID) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ClassLoadStarted { class_id })
+                }
+
+                fn class_load_finished(
+                    &self,
+                    class_id: ClassID,
+                    hr_status: HResult,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ClassLoadFinished {
+                        class_id,
+                        hr_status,
+                    })
+                }
+
+                fn class_unload_started(&self, class_id: ClassID) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ClassUnloadStarted { class_id })
+                }
+
+                fn class_unload_finished(
+                    &self,
+                    class_id: ClassID,
+                    hr_status: HResult,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ClassUnloadFinished {
+                        class_id,
+                        hr_status,
+                    })
+                }
+
+                fn function_unload_started(&self, function_id: FunctionID) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::FunctionUnloadStarted { function_id })
+                }
+
+                fn jit_compilation_started(
+                    &self,
+                    function_id: FunctionID,
+                    is_safe_to_block: bool,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::JITCompilationStarted {
+                        function_id,
+                        is_safe_to_block,
+                    })
+                }
+
+                fn jit_compilation_finished(
+                    &self,
+                    function_id: FunctionID,
+                    hr_status: HResult,
+                    is_safe_to_block: bool,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::JITCompilationFinished {
+                        function_id,
+                        hr_status,
+                        is_safe_to_block,
+                    })
+                }
+
+                fn jit_cached_function_search_started(
+                    &self,
+                    function_id: FunctionID,
+                    use_cached_function: bool,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::JITCachedFunctionSearchStarted {
+                        function_id,
+                        use_cached_function,
+                    })
+                }
+
+                fn jit_cached_function_search_finished(
+                    &self,
+                    function_id: FunctionID,
+                    result: COR_PRF_JIT_CACHE,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::JITCachedFunctionSearchFinished {
+                        function_id,
+                        result,
+                    })
+                }
+
+                fn jit_function_pitched(&self, function_id: FunctionID) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::JITFunctionPitched { function_id })
+                }
+
+                fn jit_inlining(
+                    &self,
+                    caller_id: FunctionID,
+                    callee_id: FunctionID,
+                    should_inline: bool,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::JITInlining {
+                        caller_id,
+                        callee_id,
+                        should_inline,
+                    })
+                }
+
+                fn thread_created(&self, thread_id: ThreadID) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ThreadCreated { thread_id })
+                }
+
+                fn thread_destroyed(&self, thread_id: ThreadID) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ThreadDestroyed { thread_id })
+                }
+
+                fn thread_assigned_to_os_thread(
+                    &self,
+                    managed_thread_id: ThreadID,
+                    os_thread_id: DWORD,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ThreadAssignedToOSThread {
+                        managed_thread_id,
+                        os_thread_id,
+                    })
+                }
+
+                fn remoting_client_invocation_started(&self) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::RemotingClientInvocationStarted)
+                }
+
+                fn remoting_client_sending_message(
+                    &self,
+                    cookie: GUID,
+                    is_async: bool,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::RemotingClientSendingMessage { cookie, is_async })
+                }
+
+                fn remoting_client_receiving_reply(
+                    &self,
+                    cookie: GUID,
+                    is_async: bool,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::RemotingClientReceivingReply { cookie, is_async })
+                }
+
+                fn remoting_client_invocation_finished(&self) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::RemotingClientInvocationFinished)
+                }
+
+                fn remoting_server_receiving_message(
+                    &self,
+                    cookie: GUID,
+                    is_async: bool,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::RemotingServerReceivingMessage { cookie, is_async })
+                }
+
+                fn remoting_server_invocation_started(&self) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::RemotingServerInvocationStarted)
+                }
+
+                fn remoting_server_invocation_returned(&self) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::RemotingServerInvocationReturned)
+                }
+
+                fn remoting_server_sending_reply(
+                    &self,
+                    cookie: GUID,
+                    is_async: bool,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::RemotingServerSendingReply { cookie, is_async })
+                }
+
+                fn unmanaged_to_managed_transition(
+                    &self,
+                    function_id: FunctionID,
+                    reason: COR_PRF_TRANSITION_REASON,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::UnmanagedToManagedTransition {
+                        function_id,
+                        reason,
+                    })
+                }
+
+                fn managed_to_unmanaged_transition(
+                    &self,
+                    function_id: FunctionID,
+                    reason: COR_PRF_TRANSITION_REASON,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ManagedToUnmanagedTransition {
+                        function_id,
+                        reason,
+                    })
+                }
+
+                fn runtime_suspend_started(
+                    &self,
+                    suspend_reason: COR_PRF_SUSPEND_REASON,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::RuntimeSuspendStarted { suspend_reason })
+                }
+
+                fn runtime_suspend_finished(&self) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::RuntimeSuspendFinished)
+                }
+
+                fn runtime_suspend_aborted(&self) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::RuntimeSuspendAborted)
+                }
+
+                fn runtime_resume_started(&self) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::RuntimeResumeStarted)
+                }
+
+                fn runtime_resume_finished(&self) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::RuntimeResumeFinished)
+                }
+
+                fn runtime_thread_suspended(&self, thread_id: ThreadID) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::RuntimeThreadSuspended { thread_id })
+                }
+
+                fn runtime_thread_resumed(&self, thread_id: ThreadID) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::RuntimeThreadResumed { thread_id })
+                }
+
+                fn moved_references(
+                    &self,
+                    old_object_id_range_start: &[ObjectID],
+                    new_object_id_range_start: &[ObjectID],
+                    object_id_range_length: &[u32],
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::MovedReferences {
+                        old_object_id_range_start: old_object_id_range_start.to_vec(),
+                        new_object_id_range_start: new_object_id_range_start.to_vec(),
+                        object_id_range_length: object_id_range_length.to_vec(),
+                    })
+                }
+
+                fn object_allocated(
+                    &self,
+                    object_id: ObjectID,
+                    class_id: ClassID,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ObjectAllocated {
+                        object_id,
+                        class_id,
+                    })
+                }
+
+                fn objects_allocated_by_class(
+                    &self,
+                    class_ids: &[ClassID],
+                    num_objects: &[u32],
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ObjectsAllocatedByClass {
+                        class_ids: class_ids.to_vec(),
+                        num_objects: num_objects.to_vec(),
+                    })
+                }
+
+                fn object_references(
+                    &self,
+                    object_id: ObjectID,
+                    class_id: ClassID,
+                    object_ref_ids: &[ObjectID],
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ObjectReferences {
+                        object_id,
+                        class_id,
+                        object_ref_ids: object_ref_ids.to_vec(),
+                    })
+                }
+
+                fn root_references(&self, root_ref_ids: &[ObjectID]) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::RootReferences {
+                        root_ref_ids: root_ref_ids.to_vec(),
+                    })
+                }
+
+                fn exception_thrown(&self, thrown_object_id: ObjectID) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ExceptionThrown { thrown_object_id })
+                }
+
+                fn exception_search_function_enter(
+                    &self,
+                    function_id: FunctionID,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ExceptionSearchFunctionEnter { function_id })
+                }
+
+                fn exception_search_function_leave(&self) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ExceptionSearchFunctionLeave)
+                }
+
+                fn exception_search_filter_enter(
+                    &self,
+                    function_id: FunctionID,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ExceptionSearchFilterEnter { function_id })
+                }
+
+                fn exception_search_filter_leave(&self) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ExceptionSearchFilterLeave)
+                }
+
+                fn exception_search_catcher_found(
+                    &self,
+                    function_id: FunctionID,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ExceptionSearchCatcherFound { function_id })
+                }
+
+                fn exception_os_handler_enter(&self, _unused: UINT_PTR) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ExceptionOSHandlerEnter)
+                }
+
+                fn exception_os_handler_leave(&self, _unused: UINT_PTR) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ExceptionOSHandlerLeave)
+                }
+
+                fn exception_unwind_function_enter(
+                    &self,
+                    function_id: FunctionID,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ExceptionUnwindFunctionEnter { function_id })
+                }
+
+                fn exception_unwind_function_leave(&self) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ExceptionUnwindFunctionLeave)
+                }
+
+                fn exception_unwind_finally_enter(
+                    &self,
+                    function_id: FunctionID,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ExceptionUnwindFinallyEnter { function_id })
+                }
+
+                fn exception_unwind_finally_leave(&self) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ExceptionUnwindFinallyLeave)
+                }
+
+                fn exception_catcher_enter(
+                    &self,
+                    function_id: FunctionID,
+                    object_id: ObjectID,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ExceptionCatcherEnter {
+                        function_id,
+                        object_id,
+                    })
+                }
+
+                fn exception_catcher_leave(&self) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ExceptionCatcherLeave)
+                }
+
+                fn com_classic_vtable_created(
+                    &self,
+                    wrapped_class_id: ClassID,
+                    implemented_iid: REFGUID,
+                    p_vtable: *const c_void,
+                    c_slots: u32,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::COMClassicVTableCreated {
+                        wrapped_class_id,
+                        implemented_iid: copy_guid(implemented_iid),
+                        vtable: p_vtable as UINT_PTR,
+                        slots: c_slots,
+                    })
+                }
+
+                fn com_classic_vtable_destroyed(
+                    &self,
+                    wrapped_class_id: ClassID,
+                    implemented_iid: REFGUID,
+                    p_vtable: *const c_void,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::COMClassicVTableDestroyed {
+                        wrapped_class_id,
+                        implemented_iid: copy_guid(implemented_iid),
+                        vtable: p_vtable as UINT_PTR,
+                    })
+                }
+
+                fn exception_clr_catcher_found(&self) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ExceptionCLRCatcherFound)
+                }
+
+                fn exception_clr_catcher_execute(&self) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ExceptionCLRCatcherExecute)
+                }
+            }
+
+            impl<$($generics)*> CorProfilerCallback2 for $profiler {
+                fn thread_name_changed(
+                    &self,
+                    thread_id: ThreadID,
+                    name: &str,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ThreadNameChanged {
+                        thread_id,
+                        name: name.to_string(),
+                    })
+                }
+
+                fn garbage_collection_started(
+                    &self,
+                    generation_collected: &[BOOL],
+                    reason: COR_PRF_GC_REASON,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::GarbageCollectionStarted {
+                        generation_collected: generation_collected
+                            .iter()
+                            .map(|&b| b != 0)
+                            .collect(),
+                        reason,
+                    })
+                }
+
+                fn surviving_references(
+                    &self,
+                    object_id_range_start: &[ObjectID],
+                    object_id_range_length: &[u32],
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::SurvivingReferences {
+                        object_id_range_start: object_id_range_start.to_vec(),
+                        object_id_range_length: object_id_range_length.to_vec(),
+                    })
+                }
+
+                fn garbage_collection_finished(&self) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::GarbageCollectionFinished)
+                }
+
+                fn finalizeable_object_queued(
+                    &self,
+                    finalizer_flags: COR_PRF_FINALIZER_FLAGS,
+                    object_id: ObjectID,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::FinalizeableObjectQueued {
+                        finalizer_flags,
+                        object_id,
+                    })
+                }
+
+                fn root_references_2(
+                    &self,
+                    root_ref_ids: &[ObjectID],
+                    root_kinds: &[COR_PRF_GC_ROOT_KIND],
+                    root_flags: &[COR_PRF_GC_ROOT_FLAGS],
+                    root_ids: &[UINT_PTR],
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::RootReferences2 {
+                        root_ref_ids: root_ref_ids.to_vec(),
+                        root_kinds: root_kinds.to_vec(),
+                        root_flags: root_flags.to_vec(),
+                        root_ids: root_ids.to_vec(),
+                    })
+                }
+
+                fn handle_created(
+                    &self,
+                    handle_id: GCHandleID,
+                    initial_object_id: ObjectID,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::HandleCreated {
+                        handle_id,
+                        initial_object_id,
+                    })
+                }
+
+                fn handle_destroyed(&self, handle_id: GCHandleID) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::HandleDestroyed { handle_id })
+                }
+            }
+
+            impl<$($generics)*> CorProfilerCallback3 for $profiler {
+                fn initialize_for_attach(
+                    &self,
+                    profiler_info: ProfilerInfo,
+                    client_data: *const c_void,
+                    client_data_length: u32,
+                ) -> Result<(), HResult> {
+                    let client_data =
+                        copy_items(client_data as *const u8, client_data_length as usize);
+                    self.send(ProfilerEvent::InitializeForAttach {
+                        profiler_info,
+                        client_data,
+                    })
+                }
+
+                fn profiler_attach_complete(&self) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ProfilerAttachComplete)
+                }
+
+                fn profiler_detach_succeeded(&self) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ProfilerDetachSucceeded)
+                }
+            }
+
+            impl<$($generics)*> CorProfilerCallback4 for $profiler {
+                fn rejit_compilation_started(
+                    &self,
+                    function_id: FunctionID,
+                    rejit_id: ReJITID,
+                    is_safe_to_block: bool,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ReJITCompilationStarted {
+                        function_id,
+                        rejit_id,
+                        is_safe_to_block,
+                    })
+                }
+
+                fn get_rejit_parameters(
+                    &self,
+                    module_id: ModuleID,
+                    method_id: MethodDefToken,
+                    function_control: &CorProfilerFunctionControl,
+                ) -> Result<(), HResult> {
+                    let function_control = unsafe {
+                        ComPtr::from_borrowed(
+                            function_control as *const _ as *mut CorProfilerFunctionControl,
+                        )
+                    }
+                    .ok_or(HResult::E_POINTER)?;
+                    self.send(ProfilerEvent::GetReJITParameters {
+                        module_id,
+                        method_id,
+                        function_control,
+                    })
+                }
+
+                fn rejit_compilation_finished(
+                    &self,
+                    function_id: FunctionID,
+                    rejit_id: ReJITID,
+                    hr_status: HResult,
+                    is_safe_to_block: bool,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ReJITCompilationFinished {
+                        function_id,
+                        rejit_id,
+                        hr_status,
+                        is_safe_to_block,
+                    })
+                }
+
+                fn rejit_error(
+                    &self,
+                    module_id: ModuleID,
+                    method_id: MethodDefToken,
+                    function_id: FunctionID,
+                    hr_status: HResult,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ReJITError {
+                        module_id,
+                        method_id,
+                        function_id,
+                        hr_status,
+                    })
+                }
+
+                fn moved_references_2(
+                    &self,
+                    old_object_id_range_start: &[ObjectID],
+                    new_object_id_range_start: &[ObjectID],
+                    object_id_range_length: &[usize],
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::MovedReferences2 {
+                        old_object_id_range_start: old_object_id_range_start.to_vec(),
+                        new_object_id_range_start: new_object_id_range_start.to_vec(),
+                        object_id_range_length: object_id_range_length.to_vec(),
+                    })
+                }
+
+                fn surviving_references_2(
+                    &self,
+                    object_id_range_start: &[ObjectID],
+                    c_object_id_range_length: &[usize],
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::SurvivingReferences2 {
+                        object_id_range_start: object_id_range_start.to_vec(),
+                        object_id_range_length: c_object_id_range_length.to_vec(),
+                    })
+                }
+            }
+
+            impl<$($generics)*> CorProfilerCallback5 for $profiler {
+                fn conditional_weak_table_element_references(
+                    &self,
+                    key_ref_ids: &[ObjectID],
+                    value_ref_ids: &[ObjectID],
+                    root_ids: &[GCHandleID],
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ConditionalWeakTableElementReferences {
+                        key_ref_ids: key_ref_ids.to_vec(),
+                        value_ref_ids: value_ref_ids.to_vec(),
+                        root_ids: root_ids.to_vec(),
+                    })
+                }
+            }
+
+            impl<$($generics)*> CorProfilerCallback6 for $profiler {
+                fn get_assembly_references(
+                    &self,
+                    assembly_path: &str,
+                    asm_ref_provider: &CorProfilerAssemblyReferenceProvider,
+                ) -> Result<(), HResult> {
+                    let asm_ref_provider = unsafe {
+                        ComPtr::from_borrowed(
+                            asm_ref_provider as *const _
+                                as *mut CorProfilerAssemblyReferenceProvider,
+                        )
+                    }
+                    .ok_or(HResult::E_POINTER)?;
+                    self.send(ProfilerEvent::GetAssemblyReferences {
+                        assembly_path: assembly_path.to_string(),
+                        asm_ref_provider,
+                    })
+                }
+            }
+
+            impl<$($generics)*> CorProfilerCallback7 for $profiler {
+                fn module_in_memory_symbols_updated(
+                    &self,
+                    module_id: ModuleID,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::ModuleInMemorySymbolsUpdated { module_id })
+                }
+            }
+
+            impl<$($generics)*> CorProfilerCallback8 for $profiler {
+                fn dynamic_method_jit_compilation_started(
+                    &self,
+                    function_id: FunctionID,
+                    is_safe_to_block: bool,
+                    il_header: LPCBYTE,
+                    il_header_length: u32,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::DynamicMethodJITCompilationStarted {
+                        function_id,
+                        is_safe_to_block,
+                        il_header: copy_items(il_header, il_header_length as usize),
+                    })
+                }
+
+                fn dynamic_method_jit_compilation_finished(
+                    &self,
+                    function_id: FunctionID,
+                    hr_status: HResult,
+                    f_is_safe_to_block: bool,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::DynamicMethodJITCompilationFinished {
+                        function_id,
+                        hr_status,
+                        is_safe_to_block: f_is_safe_to_block,
+                    })
+                }
+            }
+
+            impl<$($generics)*> CorProfilerCallback9 for $profiler {
+                fn dynamic_method_unloaded(&self, function_id: FunctionID) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::DynamicMethodUnloaded { function_id })
+                }
+            }
+
+            impl<$($generics)*> CorProfilerCallback10 for $profiler {
+                fn event_pipe_event_delivered(
+                    &self,
+                    event: EventPipeEvent,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::EventPipeEventDelivered {
+                        provider: event.provider,
+                        event_id: event.event_id,
+                        event_version: event.event_version,
+                        metadata_blob: event.metadata_blob.to_vec(),
+                        event_data: event.event_data.to_vec(),
+                        activity_id: event.activity_id.copied(),
+                        related_activity_id: event.related_activity_id.copied(),
+                        event_thread: event.event_thread,
+                        stack_frames: event.stack_frames.to_vec(),
+                    })
+                }
+
+                fn event_pipe_provider_created(
+                    &self,
+                    provider: EVENTPIPE_PROVIDER,
+                ) -> Result<(), HResult> {
+                    self.send(ProfilerEvent::EventPipeProviderCreated { provider })
+                }
+            }
+
+            impl<$($generics)*> CorProfilerCallback11 for $profiler {}
+        };
+    };
+}
+
+pub(crate) use event_callbacks;
+
+event_callbacks!([H: EventHandler] EventStream<H>);
@@ -0,0 +1,112 @@
+use std::{
+    cell::UnsafeCell,
+    mem::MaybeUninit,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// A fixed size ring buffer any number of threads can push to and pop from
+/// without locking (Dmitry Vyukov's bounded MPMC queue).
+///
+/// Each slot has a sequence number telling whose turn it is: a slot is free
+/// for the push at position `pos` when its sequence is `pos`, and full for
+/// the pop at `pos` when it is `pos + 1`.
+pub(super) struct BoundedQueue<T> {
+    slots: Box<[Slot<T>]>,
+    mask: usize,
+    push_position: AtomicUsize,
+    pop_position: AtomicUsize,
+}
+
+struct Slot<T> {
+    sequence: AtomicUsize,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+// a value is only touched by the one thread that claimed its slot
+unsafe impl<T: Send> Send for BoundedQueue<T> {}
+unsafe impl<T: Send> Sync for BoundedQueue<T> {}
+
+impl<T> BoundedQueue<T> {
+    /// A queue of at least `capacity` slots, rounded up to a power of two.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(2).next_power_of_two();
+        let slots = (0..capacity)
+            .map(|sequence| Slot {
+                sequence: AtomicUsize::new(sequence),
+                value: UnsafeCell::new(MaybeUninit::uninit()),
+            })
+            .collect();
+        BoundedQueue {
+            slots,
+            mask: capacity - 1,
+            push_position: AtomicUsize::new(0),
+            pop_position: AtomicUsize::new(0),
+        }
+    }
+
+    /// Hands `value` back if the queue is full.
+    pub fn push(&self, value: T) -> Result<(), T> {
+        let mut position = self.push_position.load(Ordering::Relaxed);
+        loop {
+            let slot = &self.slots[position & self.mask];
+            let sequence = slot.sequence.load(Ordering::Acquire);
+            let lag = sequence.wrapping_sub(position) as isize;
+            if lag == 0 {
+                match self.push_position.compare_exchange_weak(
+                    position,
+                    position.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        unsafe { (*slot.value.get()).write(value) };
+                        slot.sequence
+                            .store(position.wrapping_add(1), Ordering::Release);
+                        return Ok(());
+                    }
+                    Err(current) => position = current,
+                }
+            } else if lag < 0 {
+                // the slot still holds the value pushed one lap ago
+                return Err(value);
+            } else {
+                position = self.push_position.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        let mut position = self.pop_position.load(Ordering::Relaxed);
+        loop {
+            let slot = &self.slots[position & self.mask];
+            let sequence = slot.sequence.load(Ordering::Acquire);
+            let lag = sequence.wrapping_sub(position.wrapping_add(1)) as isize;
+            if lag == 0 {
+                match self.pop_position.compare_exchange_weak(
+                    position,
+                    position.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        let value = unsafe { (*slot.value.get()).assume_init_read() };
+                        slot.sequence
+                            .store(position.wrapping_add(self.mask + 1), Ordering::Release);
+                        return Some(value);
+                    }
+                    Err(current) => position = current,
+                }
+            } else if lag < 0 {
+                return None;
+            } else {
+                position = self.pop_position.load(Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+impl<T> Drop for BoundedQueue<T> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
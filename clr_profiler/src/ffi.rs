@@ -1,9 +1,11 @@
 #![allow(non_camel_case_types, non_snake_case, non_upper_case_globals)]
 mod class;
+mod com_ptr;
 mod hresult;
 mod interface;
 
 pub use self::class::*;
+pub use self::com_ptr::*;
 pub use self::hresult::*;
 pub use self::interface::*;
 
@@ -27,6 +29,9 @@ pub type ULONG32 = c_uint;
 pub type ULONG = c_ulong;
 pub type DWORD = c_ulong;
 pub type BYTE = c_uchar;
+pub type UINT8 = u8;
+pub type UINT32 = u32;
+pub type UINT64 = u64;
 pub type COR_SIGNATURE = BYTE;
 
 // char types
@@ -79,6 +84,7 @@ pub type IID = GUID;
 pub type REFGUID = *const GUID;
 pub type REFCLSID = *const IID;
 pub type REFIID = *const IID;
+pub type LPCGUID = *const GUID;
 
 // profiler-specific pointers
 pub type AppDomainID = UINT_PTR;
@@ -98,6 +104,10 @@ pub type ReJITID = UINT_PTR;
 pub type ThreadID = UINT_PTR;
 pub type ClrInstanceID = USHORT;
 pub type HCORENUM = *const c_void;
+pub type ObjectHandleID = *mut c_void;
+pub type EVENTPIPE_PROVIDER = UINT_PTR;
+pub type EVENTPIPE_EVENT = UINT_PTR;
+pub type EVENTPIPE_SESSION = UINT64;
 
 #[repr(C)]
 pub union FunctionIDOrClientID {
@@ -196,22 +206,31 @@ pub type ObjectReferenceCallback = unsafe extern "system" fn(
     reference: *const ObjectID,
     clientData: *const c_void,
 ) -> BOOL;
+pub type EventPipeProviderCallback = unsafe extern "system" fn(
+    sourceId: *const UINT8,
+    isEnabled: UINT32,
+    level: UINT8,
+    matchAnyKeywords: UINT64,
+    matchAllKeywords: UINT64,
+    filterData: *const COR_PRF_FILTER_DATA,
+    callbackData: *const c_void,
+);
 
 // profiler types
 #[repr(C)]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum COR_PRF_JIT_CACHE {
     COR_PRF_CACHED_FUNCTION_FOUND = 0,
     COR_PRF_CACHED_FUNCTION_NOT_FOUND = 1,
 }
 #[repr(C)]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum COR_PRF_TRANSITION_REASON {
     COR_PRF_TRANSITION_CALL = 0,
     COR_PRF_TRANSITION_RETURN = 1,
 }
 #[repr(C)]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum COR_PRF_SUSPEND_REASON {
     COR_PRF_SUSPEND_OTHER = 0,
     COR_PRF_SUSPEND_FOR_GC = 1,
@@ -223,13 +242,13 @@ pub enum COR_PRF_SUSPEND_REASON {
     COR_PRF_SUSPEND_FOR_REJIT = 8,
 }
 #[repr(C)]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum COR_PRF_GC_REASON {
     COR_PRF_GC_INDUCED = 1,
     COR_PRF_GC_OTHER = 0,
 }
 #[repr(C)]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum COR_PRF_GC_ROOT_KIND {
     COR_PRF_GC_ROOT_STACK = 1,
     COR_PRF_GC_ROOT_FINALIZER = 2,
@@ -237,7 +256,7 @@ pub enum COR_PRF_GC_ROOT_KIND {
     COR_PRF_GC_ROOT_OTHER = 0,
 }
 #[repr(C)]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum COR_PRF_GC_ROOT_FLAGS {
     COR_PRF_GC_ROOT_PINNING = 1,
     COR_PRF_GC_ROOT_WEAKREF = 2,
@@ -421,13 +440,13 @@ pub struct COR_PRF_ASSEMBLY_REFERENCE_INFO {
     dwAssemblyRefFlags: DWORD,
 }
 #[repr(C)]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub struct COR_PRF_FUNCTION {
-    functionId: FunctionID,
-    reJitId: ReJITID,
+    pub functionId: FunctionID,
+    pub reJitId: ReJITID,
 }
 bitflags! {
-    #[derive(Debug)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub struct COR_PRF_MONITOR: DWORD {
         const COR_PRF_MONITOR_NONE = 0;
         const COR_PRF_MONITOR_FUNCTION_UNLOADS = 0x1;
@@ -497,6 +516,7 @@ bitflags! {
 }
 
 bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub struct COR_PRF_HIGH_MONITOR: DWORD {
         const COR_PRF_HIGH_MONITOR_NONE = 0;
         const COR_PRF_HIGH_ADD_ASSEMBLY_REFERENCES = 0x1;
@@ -507,20 +527,97 @@ bitflags! {
         const COR_PRF_HIGH_MONITOR_GC_MOVED_OBJECTS = 0x20;
         const COR_PRF_HIGH_REQUIRE_PROFILE_IMAGE = 0;
         const COR_PRF_HIGH_MONITOR_LARGEOBJECT_ALLOCATED = 0x40;
+        const COR_PRF_HIGH_MONITOR_EVENT_PIPE = 0x80;
         const COR_PRF_HIGH_ALLOWABLE_AFTER_ATTACH = Self::COR_PRF_HIGH_IN_MEMORY_SYMBOLS_UPDATED.bits()
             | Self::COR_PRF_HIGH_MONITOR_DYNAMIC_FUNCTION_UNLOADS.bits()
             | Self::COR_PRF_HIGH_BASIC_GC.bits()
             | Self::COR_PRF_HIGH_MONITOR_GC_MOVED_OBJECTS.bits()
-            | Self::COR_PRF_HIGH_MONITOR_LARGEOBJECT_ALLOCATED.bits();
+            | Self::COR_PRF_HIGH_MONITOR_LARGEOBJECT_ALLOCATED.bits()
+            | Self::COR_PRF_HIGH_MONITOR_EVENT_PIPE.bits();
         const COR_PRF_HIGH_MONITOR_IMMUTABLE = COR_PRF_HIGH_MONITOR::COR_PRF_HIGH_DISABLE_TIERED_COMPILATION.bits();
     }
 }
 
 #[repr(C)]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub struct COR_PRF_METHOD {
-    moduleId: ModuleID,
-    methodId: mdMethodDef,
+    pub moduleId: ModuleID,
+    pub methodId: mdMethodDef,
+}
+#[repr(C)]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum COR_PRF_HANDLE_TYPE {
+    COR_PRF_HANDLE_TYPE_WEAK = 0x1,
+    COR_PRF_HANDLE_TYPE_STRONG = 0x2,
+    COR_PRF_HANDLE_TYPE_PINNED = 0x3,
+}
+#[repr(C)]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum COR_PRF_EVENTPIPE_LEVEL {
+    COR_PRF_EVENTPIPE_LOGALWAYS = 0,
+    COR_PRF_EVENTPIPE_CRITICAL = 1,
+    COR_PRF_EVENTPIPE_ERROR = 2,
+    COR_PRF_EVENTPIPE_WARNING = 3,
+    COR_PRF_EVENTPIPE_INFORMATIONAL = 4,
+    COR_PRF_EVENTPIPE_VERBOSE = 5,
+}
+#[repr(C)]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum COR_PRF_EVENTPIPE_PARAM_TYPE {
+    COR_PRF_EVENTPIPE_OBJECT = 1,
+    COR_PRF_EVENTPIPE_BOOLEAN = 3,
+    COR_PRF_EVENTPIPE_CHAR = 4,
+    COR_PRF_EVENTPIPE_SBYTE = 5,
+    COR_PRF_EVENTPIPE_BYTE = 6,
+    COR_PRF_EVENTPIPE_INT16 = 7,
+    COR_PRF_EVENTPIPE_UINT16 = 8,
+    COR_PRF_EVENTPIPE_INT32 = 9,
+    COR_PRF_EVENTPIPE_UINT32 = 10,
+    COR_PRF_EVENTPIPE_INT64 = 11,
+    COR_PRF_EVENTPIPE_UINT64 = 12,
+    COR_PRF_EVENTPIPE_SINGLE = 13,
+    COR_PRF_EVENTPIPE_DOUBLE = 14,
+    COR_PRF_EVENTPIPE_DECIMAL = 15,
+    COR_PRF_EVENTPIPE_DATETIME = 16,
+    COR_PRF_EVENTPIPE_GUID = 17,
+    COR_PRF_EVENTPIPE_STRING = 18,
+    COR_PRF_EVENTPIPE_ARRAY = 19,
+}
+#[repr(C)]
+#[derive(Debug, PartialEq)]
+pub struct COR_PRF_EVENTPIPE_PROVIDER_CONFIG {
+    pub providerName: *const WCHAR,
+    pub keywords: UINT64,
+    pub loggingLevel: UINT32,
+    pub filterData: *const WCHAR,
+}
+#[repr(C)]
+#[derive(Debug, PartialEq)]
+pub struct COR_PRF_EVENTPIPE_PARAM_DESC {
+    pub r#type: UINT32,
+    pub elementType: UINT32,
+    pub name: *const WCHAR,
+}
+#[repr(C)]
+#[derive(Debug, PartialEq)]
+pub struct COR_PRF_EVENT_DATA {
+    pub ptr: UINT64,
+    pub size: UINT32,
+    pub reserved: UINT32,
+}
+#[repr(C)]
+#[derive(Debug, PartialEq)]
+pub struct COR_PRF_FILTER_DATA {
+    pub Ptr: UINT64,
+    pub Size: UINT32,
+    pub Type: UINT32,
+}
+#[repr(C)]
+#[derive(Debug, PartialEq)]
+pub struct COR_PRF_NONGC_HEAP_RANGE {
+    pub rangeStart: ObjectID,
+    pub rangeLength: UINT_PTR,
+    pub rangeLengthReserved: UINT_PTR,
 }
 bitflags! {
     pub struct CorOpenFlags: DWORD {
@@ -567,6 +664,7 @@ bitflags! {
     }
 }
 bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub struct COR_PRF_FINALIZER_FLAGS: DWORD {
         const COR_PRF_FINALIZER_CRITICAL = 0x1;
     }
@@ -586,7 +684,7 @@ pub struct COR_SECATTR {
     cbCustomAttribute: ULONG,        // Length of the above blob.
 }
 bitflags! {
-    #[derive(Debug)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub struct CorMethodAttr: DWORD {
         // member access mask - Use this mask to retrieve accessibility information.
         const mdMemberAccessMask          =   0x0007;
@@ -628,7 +726,7 @@ bitflags! {
     }
 }
 bitflags! {
-    #[derive(Debug)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub struct CorMethodImpl: DWORD
 {
     // code impl mask
@@ -676,7 +774,7 @@ bitflags! {
 
 
 bitflags! {
-    #[derive(Debug)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub struct CorTypeAttr: DWORD {
         const tdVisibilityMask        =   0x00000007;
         const tdNotPublic             =   0x00000000;
@@ -719,4 +817,55 @@ bitflags! {
         const tdRTSpecialName         =   0x00000800;
         const tdHasSecurity           =   0x00040000;
     }
-}
\ No newline at end of file
+}
+bitflags! {
+    #[derive(Debug)]
+    pub struct CorFieldAttr: DWORD {
+        // member access mask - Use this mask to retrieve accessibility information.
+        const fdFieldAccessMask           =   0x0007;
+        const fdPrivateScope              =   0x0000;     // Member not referenceable.
+        const fdPrivate                   =   0x0001;     // Accessible only by the parent type.
+        const fdFamANDAssem               =   0x0002;     // Accessible by sub-types only in this Assembly.
+        const fdAssembly                  =   0x0003;     // Accessibly by anyone in the Assembly.
+        const fdFamily                    =   0x0004;     // Accessible only by type and sub-types.
+        const fdFamORAssem                =   0x0005;     // Accessibly by sub-types anywhere, plus anyone in assembly.
+        const fdPublic                    =   0x0006;     // Accessibly by anyone who has visibility to this scope.
+        // end member access mask
+
+        // field contract attributes.
+        const fdStatic                    =   0x0010;     // Defined on type, else per instance.
+        const fdInitOnly                  =   0x0020;     // Field may only be initialized, not written to after init.
+        const fdLiteral                   =   0x0040;     // Value is compile time constant.
+        const fdNotSerialized             =   0x0080;     // Field does not have to be serialized when type is remoted.
+
+        const fdSpecialName               =   0x0200;     // field is special.  Name describes how.
+
+        // interop attributes
+        const fdPinvokeImpl               =   0x2000;     // Implementation is forwarded through pinvoke.
+
+        // Reserved flags for runtime use only.
+        const fdReservedMask              =   0x9500;
+        const fdRTSpecialName             =   0x0400;     // Runtime(metadata internal APIs) should check name encoding.
+        const fdHasFieldMarshal           =   0x1000;     // Field has marshalling information.
+        const fdHasDefault                =   0x8000;     // Field has default.
+        const fdHasFieldRVA               =   0x0100;     // Field has RVA.
+    }
+}
+bitflags! {
+    #[derive(Debug)]
+    pub struct CorGenericParamAttr: DWORD {
+        // Variance of type parameters, only applicable to generic parameters
+        // for generic interfaces and delegates
+        const gpVarianceMask              =   0x0003;
+        const gpNonVariant                =   0x0000;
+        const gpCovariant                 =   0x0001;
+        const gpContravariant             =   0x0002;
+
+        // Special constraints, applicable to any type parameters
+        const gpSpecialConstraintMask     =   0x001C;
+        const gpNoSpecialConstraint       =   0x0000;
+        const gpReferenceTypeConstraint   =   0x0004;     // type argument must be a reference type
+        const gpNotNullableValueTypeConstraint = 0x0008;  // type argument must be a value type but not Nullable
+        const gpDefaultConstructorConstraint = 0x0010;    // type argument must have a public default constructor
+    }
+}
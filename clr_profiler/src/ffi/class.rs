@@ -1,3 +1,4 @@
+mod catch_panic;
 mod class_factory;
 mod cor_profiler_assembly_reference_provider;
 mod cor_profiler_callback;
@@ -13,21 +14,29 @@ mod metadata_assembly_import;
 mod metadata_emit;
 mod metadata_import;
 mod method_malloc;
+mod server;
 mod unknown;
 
-pub use self::class_factory::ClassFactory;
-pub use self::cor_profiler_assembly_reference_provider::CorProfilerAssemblyReferenceProvider;
+pub(crate) use self::catch_panic::catch_panic;
+pub use self::class_factory::{get_class_object, ClassFactory};
+pub use self::cor_profiler_assembly_reference_provider::{
+    CorProfilerAssemblyReferenceProvider, CorProfilerAssemblyReferenceProviderVtbl,
+};
+pub(crate) use self::cor_profiler_callback::add_events;
 pub use self::cor_profiler_callback::CorProfilerCallback;
-pub use self::cor_profiler_function_control::CorProfilerFunctionControl;
+pub use self::cor_profiler_function_control::{
+    CorProfilerFunctionControl, CorProfilerFunctionControlVtbl,
+};
 pub use self::cor_profiler_function_enum::CorProfilerFunctionEnum;
-pub use self::cor_profiler_info::CorProfilerInfo;
+pub use self::cor_profiler_info::{CorProfilerInfo, CorProfilerInfoVtbl};
 pub use self::cor_profiler_method_enum::CorProfilerMethodEnum;
-pub use self::cor_profiler_module_enum::CorProfilerModuleEnum;
+pub use self::cor_profiler_module_enum::{CorProfilerModuleEnum, CorProfilerModuleEnumVtbl};
 pub use self::cor_profiler_object_enum::CorProfilerObjectEnum;
 pub use self::cor_profiler_thread_enum::CorProfilerThreadEnum;
 pub use self::metadata_assembly_emit::MetaDataAssemblyEmit;
 pub use self::metadata_assembly_import::MetaDataAssemblyImport;
 pub use self::metadata_emit::MetaDataEmit;
-pub use self::metadata_import::MetaDataImport;
-pub use self::method_malloc::MethodMalloc;
-pub use self::unknown::Unknown;
+pub use self::metadata_import::{MetaDataImport, MetaDataImportVtbl};
+pub use self::method_malloc::{MethodMalloc, MethodMallocVtbl};
+pub use self::server::can_unload_now;
+pub use self::unknown::{Unknown, UnknownVtbl};
@@ -0,0 +1,30 @@
+use log::error;
+use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
+};
+
+/// Runs the body of a COM entry point, returning `None` if it panicked.
+/// Unwinding into the runtime is undefined behavior, so every function
+/// handed to the runtime in a vtable goes through this.
+pub(crate) fn catch_panic<R>(entry_point: &str, f: impl FnOnce() -> R) -> Option<R> {
+    // the callers are left in whatever state the panic left them in, and are
+    // expected to stop relying on it
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(result) => Some(result),
+        Err(payload) => {
+            error!("panic in {entry_point}: {}", panic_message(&*payload));
+            None
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown panic payload"
+    }
+}
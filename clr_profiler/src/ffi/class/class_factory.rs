@@ -1,19 +1,25 @@
 #![allow(non_snake_case)]
+use super::{
+    catch_panic::catch_panic,
+    server::{lock_server, RefCount},
+};
 use crate::{
     ffi::{
-        CorProfilerCallback, IClassFactory, IUnknown, BOOL, E_NOINTERFACE, HRESULT, LPVOID, REFIID,
-        S_OK, ULONG,
+        CorProfilerCallback, IClassFactory, IUnknown, BOOL, CLASS_E_CLASSNOTAVAILABLE,
+        CLASS_E_NOAGGREGATION, E_FAIL, E_NOINTERFACE, E_POINTER, GUID, HRESULT, LPVOID, REFCLSID,
+        REFIID, S_OK, ULONG,
     },
-    traits::CorProfilerCallback9,
+    traits::{ClrProfiler, CorProfilerCallback11},
+    EventMask2,
 };
+use log::{debug, info, warn};
 use std::ffi::c_void;
 use std::ptr;
-use std::sync::atomic::{AtomicU32, Ordering};
 
 #[repr(C)]
 pub struct ClassFactoryVtbl<T>
 where
-    T: CorProfilerCallback9 + Clone,
+    T: CorProfilerCallback11 + Clone,
 {
     pub IUnknown: IUnknown<ClassFactory<T>>,
     pub IClassFactory: IClassFactory<ClassFactory<T>>,
@@ -22,18 +28,25 @@ where
 #[repr(C)]
 pub struct ClassFactory<T>
 where
-    T: CorProfilerCallback9 + Clone,
+    T: CorProfilerCallback11 + Clone,
 {
     pub lpVtbl: *const ClassFactoryVtbl<T>,
-    ref_count: AtomicU32,
+    ref_count: RefCount,
     profiler: T,
+    events: EventMask2,
 }
 
 impl<T> ClassFactory<T>
 where
-    T: CorProfilerCallback9 + Clone,
+    T: CorProfilerCallback11 + Clone,
 {
     pub fn new<'b>(profiler: T) -> &'b mut ClassFactory<T> {
+        Self::with_events(profiler, EventMask2::NONE)
+    }
+
+    /// Like `new`, for profilers whose callbacks need `events`, see
+    /// `CorProfilerCallback::with_events`.
+    pub fn with_events<'b>(profiler: T, events: EventMask2) -> &'b mut ClassFactory<T> {
         let class_factory = ClassFactory {
             lpVtbl: &ClassFactoryVtbl {
                 IUnknown: IUnknown {
@@ -46,63 +59,119 @@ where
                     LockServer: Self::LockServer,
                 },
             },
-            ref_count: AtomicU32::new(0),
+            ref_count: RefCount::new(),
             profiler,
+            events,
         };
         Box::leak(Box::new(class_factory))
     }
 
     pub unsafe extern "system" fn QueryInterface(
-        &mut self,
+        &self,
         riid: REFIID,
         ppvObject: *mut *mut c_void,
     ) -> HRESULT {
-        if *riid == IUnknown::IID || *riid == IClassFactory::IID {
-            *ppvObject = self as *mut ClassFactory<T> as LPVOID;
-            self.AddRef();
-            S_OK
-        } else {
-            *ppvObject = ptr::null_mut();
-            E_NOINTERFACE
-        }
+        catch_panic("IClassFactory::QueryInterface", || {
+            if *riid == IUnknown::IID || *riid == IClassFactory::IID {
+                *ppvObject = self as *const ClassFactory<T> as LPVOID;
+                self.AddRef();
+                S_OK
+            } else {
+                *ppvObject = ptr::null_mut();
+                E_NOINTERFACE
+            }
+        })
+        .unwrap_or(E_FAIL)
     }
 
-    pub unsafe extern "system" fn AddRef(&mut self) -> ULONG {
-        // TODO: Which ordering is appropriate?
-        let prev_ref_count = self.ref_count.fetch_add(1, Ordering::Relaxed);
-        prev_ref_count + 1
+    pub unsafe extern "system" fn AddRef(&self) -> ULONG {
+        catch_panic("IClassFactory::AddRef", || self.ref_count.add_ref()).unwrap_or(0)
     }
 
-    pub unsafe extern "system" fn Release(&mut self) -> ULONG {
-        // Ensure we are not trying to release the memory twice if
-        // client calls release despite the ref_count being zero.
-        // TODO: Which ordering is appropriate?
-        if self.ref_count.load(Ordering::Relaxed) == 0 {
-            panic!("Cannot release the COM object, it has already been released.");
-        }
-
-        let prev_ref_count = self.ref_count.fetch_sub(1, Ordering::Relaxed);
-        let ref_count = prev_ref_count - 1;
-
-        if ref_count == 0 {
-            drop(Box::from_raw(self as *mut ClassFactory<T>));
-        }
-
-        ref_count
+    pub unsafe extern "system" fn Release(&self) -> ULONG {
+        catch_panic("IClassFactory::Release", || {
+            match self.ref_count.release() {
+                Some(0) => {
+                    drop(Box::from_raw(
+                        self as *const ClassFactory<T> as *mut ClassFactory<T>,
+                    ));
+                    0
+                }
+                Some(ref_count) => ref_count,
+                None => 0,
+            }
+        })
+        .unwrap_or(0)
     }
 
     pub unsafe extern "system" fn CreateInstance(
-        &mut self,
-        _pUnkOuter: *mut IUnknown<()>,
-        _riid: REFIID,
+        &self,
+        pUnkOuter: *mut IUnknown<()>,
+        riid: REFIID,
         ppvObject: *mut *mut c_void,
     ) -> HRESULT {
-        *ppvObject = CorProfilerCallback::new(self.profiler.clone()) as *mut CorProfilerCallback<T>
-            as LPVOID;
-        S_OK
+        if riid.is_null() || ppvObject.is_null() {
+            return E_POINTER;
+        }
+        *ppvObject = ptr::null_mut();
+        if !pUnkOuter.is_null() {
+            return CLASS_E_NOAGGREGATION;
+        }
+        catch_panic("IClassFactory::CreateInstance", || {
+            let callback = CorProfilerCallback::with_events(self.profiler.clone(), self.events);
+            // the interface asked for holds its own reference, if there is one
+            let hr = callback.query_interface(riid, ppvObject);
+            callback.release();
+            hr
+        })
+        .unwrap_or(E_FAIL)
+    }
+
+    pub extern "system" fn LockServer(&self, fLock: BOOL) -> HRESULT {
+        catch_panic("IClassFactory::LockServer", || {
+            lock_server(fLock != 0);
+            S_OK
+        })
+        .unwrap_or(E_FAIL)
     }
+}
 
-    pub extern "system" fn LockServer(&mut self, _fLock: BOOL) -> HRESULT {
-        S_OK
+/// The body of `DllGetClassObject` for the profiler `T`, as exported by
+/// `#[clr_profiler]`. The runtime asks for the CLSID it was configured with;
+/// anything other than `clsid` is refused unless `accept_any_clsid` is set.
+/// `events` are turned on once the profiler has initialized.
+///
+/// # Safety
+///
+/// The pointers must be the ones the runtime passed to `DllGetClassObject`.
+pub unsafe fn get_class_object<T>(
+    rclsid: REFCLSID,
+    riid: REFIID,
+    ppv: *mut LPVOID,
+    clsid: &GUID,
+    accept_any_clsid: bool,
+    events: EventMask2,
+) -> HRESULT
+where
+    T: ClrProfiler + CorProfilerCallback11 + Clone,
+{
+    if rclsid.is_null() || riid.is_null() || ppv.is_null() {
+        return E_POINTER;
+    }
+    *ppv = ptr::null_mut();
+    debug!("DllGetClassObject for {}, riid {}", *rclsid, *riid);
+    if *rclsid != *clsid {
+        if !accept_any_clsid {
+            warn!(
+                "refusing to create profiler {}, expected {}",
+                *rclsid, clsid
+            );
+            return CLASS_E_CLASSNOTAVAILABLE;
+        }
+        info!("creating profiler {} as {}", clsid, *rclsid);
     }
+    let class_factory: &mut ClassFactory<T> = ClassFactory::with_events(T::new(), events);
+    let hr = class_factory.QueryInterface(riid, ppv);
+    class_factory.Release();
+    hr
 }
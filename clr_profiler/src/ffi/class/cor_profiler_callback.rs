@@ -1,27 +1,30 @@
 #![allow(non_snake_case)]
-use super::{CorProfilerAssemblyReferenceProvider, CorProfilerFunctionControl, CorProfilerInfo};
+use super::{
+    catch_panic::catch_panic, server::RefCount, CorProfilerAssemblyReferenceProvider, CorProfilerFunctionControl, CorProfilerInfo};
 use crate::{
     ffi::{
         int, mdMethodDef, AppDomainID, AssemblyID, ClassID, FunctionID, GCHandleID,
-        ICorProfilerCallback, ICorProfilerCallback2, ICorProfilerCallback3, ICorProfilerCallback4,
+        ICorProfilerCallback, ICorProfilerCallback10, ICorProfilerCallback11, ICorProfilerCallback2, ICorProfilerCallback3, ICorProfilerCallback4,
         ICorProfilerCallback5, ICorProfilerCallback6, ICorProfilerCallback7, ICorProfilerCallback8,
-        ICorProfilerCallback9, IUnknown, ModuleID, ObjectID, ReJITID, ThreadID, BOOL,
-        COR_PRF_FINALIZER_FLAGS, COR_PRF_GC_REASON, COR_PRF_GC_ROOT_FLAGS, COR_PRF_GC_ROOT_KIND,
+        ICorProfilerCallback9, IUnknown, ModuleID, ObjectID, ReJITID, ThreadID, BOOL, EVENTPIPE_PROVIDER, LPCGUID,
+        COR_PRF_FINALIZER_FLAGS, COR_PRF_GC_REASON, COR_PRF_GC_ROOT_FLAGS, COR_PRF_GC_ROOT_KIND, COR_PRF_HIGH_MONITOR, COR_PRF_MONITOR,
         COR_PRF_JIT_CACHE, COR_PRF_SUSPEND_REASON, COR_PRF_TRANSITION_REASON, DWORD, E_FAIL,
-        E_NOINTERFACE, GUID, HRESULT, LPCBYTE, LPVOID, REFGUID, REFIID, SIZE_T, S_OK, UINT,
+        E_NOINTERFACE, E_POINTER, GUID, HResult, HRESULT, LPCBYTE, LPVOID, REFGUID, REFIID, SIZE_T, S_OK, UINT,
         UINT_PTR, ULONG, WCHAR,
     },
-    traits::CorProfilerCallback9,
-    ProfilerInfo,
+    metadata::MethodDefToken,
+    recording::{Arg, Callback, Recorder},
+    traits::CorProfilerCallback11,
+    CorProfilerInfo as _, CorProfilerInfo5, EventMask2, EventPipeEvent, ProfilerInfo,
 };
 use std::{
-    ffi::c_void, ptr, slice, sync::atomic::{AtomicU32, Ordering}
+    ffi::c_void, ptr, slice, sync::OnceLock
 };
-use log::{debug, info};
+use log::{debug, error, info, warn};
 use widestring::{U16CString, U16String};
 
 #[repr(C)]
-pub struct CorProfilerCallbackVtbl<T: CorProfilerCallback9> {
+pub struct CorProfilerCallbackVtbl<T: CorProfilerCallback11> {
     pub IUnknown: IUnknown<CorProfilerCallback<T>>,
     pub ICorProfilerCallback: ICorProfilerCallback<CorProfilerCallback<T>>,
     pub ICorProfilerCallback2: ICorProfilerCallback2<CorProfilerCallback<T>>,
@@ -32,17 +35,36 @@ pub struct CorProfilerCallbackVtbl<T: CorProfilerCallback9> {
     pub ICorProfilerCallback7: ICorProfilerCallback7<CorProfilerCallback<T>>,
     pub ICorProfilerCallback8: ICorProfilerCallback8<CorProfilerCallback<T>>,
     pub ICorProfilerCallback9: ICorProfilerCallback9<CorProfilerCallback<T>>,
+    pub ICorProfilerCallback10: ICorProfilerCallback10<CorProfilerCallback<T>>,
+    pub ICorProfilerCallback11: ICorProfilerCallback11<CorProfilerCallback<T>>,
 }
 
 #[repr(C)]
-pub struct CorProfilerCallback<T: CorProfilerCallback9> {
+pub struct CorProfilerCallback<T: CorProfilerCallback11> {
     pub lpVtbl: *const CorProfilerCallbackVtbl<T>,
-    ref_count: AtomicU32,
+    ref_count: RefCount,
     profiler: T,
+    /// Kept to turn off events after a panic, see `ClrProfiler::disable_events_on_panic`.
+    profiler_info: OnceLock<ProfilerInfo>,
+    /// Turned on once the profiler has initialized, see `#[clr_profiler]`.
+    events: EventMask2,
+    /// Set when initializing with `CLR_PROFILER_RECORD` set, see `recording`.
+    recorder: OnceLock<Recorder>,
 }
 
-impl<T: CorProfilerCallback9> CorProfilerCallback<T> {
+// the runtime calls in from any thread; the vtable is immutable and every
+// other field is either atomic or requires `Sync` through the callback traits
+unsafe impl<T: CorProfilerCallback11> Send for CorProfilerCallback<T> {}
+unsafe impl<T: CorProfilerCallback11> Sync for CorProfilerCallback<T> {}
+
+impl<T: CorProfilerCallback11> CorProfilerCallback<T> {
     pub fn new<'b>(profiler: T) -> &'b mut CorProfilerCallback<T> {
+        Self::with_events(profiler, EventMask2::NONE)
+    }
+
+    /// Like `new`, and adds `events` to the event mask the profiler sets in
+    /// `initialize` or `initialize_for_attach`.
+    pub fn with_events<'b>(profiler: T, events: EventMask2) -> &'b mut CorProfilerCallback<T> {
         let cor_profiler_callback = CorProfilerCallback {
             lpVtbl: &CorProfilerCallbackVtbl {
                 IUnknown: IUnknown {
@@ -161,12 +183,82 @@ impl<T: CorProfilerCallback9> CorProfilerCallback<T> {
                 ICorProfilerCallback9: ICorProfilerCallback9 {
                     DynamicMethodUnloaded: Self::DynamicMethodUnloaded,
                 },
+                ICorProfilerCallback10: ICorProfilerCallback10 {
+                    EventPipeEventDelivered: Self::EventPipeEventDelivered,
+                    EventPipeProviderCreated: Self::EventPipeProviderCreated,
+                },
+                ICorProfilerCallback11: ICorProfilerCallback11 {
+                    LoadAsNotificationOnly: Self::LoadAsNotificationOnly,
+                },
             },
-            ref_count: AtomicU32::new(1), // TODO: Why does ref_count have to start at 1? Isn't 0 more appropriate? Why is release called by profiling api without calling add_ref?
+            ref_count: RefCount::new(),
             profiler,
+            profiler_info: OnceLock::new(),
+            events,
+            recorder: OnceLock::new(),
         };
         Box::leak(Box::new(cor_profiler_callback))
     }
+
+    /// Calls into the profiler for the callback `name`. A panic is contained
+    /// here and reported to the runtime as `E_FAIL`.
+    fn guard(&self, name: &str, callback: impl FnOnce(&T) -> HRESULT) -> HRESULT {
+        let profiler = &self.profiler;
+        if let Some(hr) = catch_panic(name, || callback(profiler)) {
+            return hr;
+        }
+        let disable_events = catch_panic(name, || self.profiler.disable_events_on_panic());
+        if disable_events.unwrap_or(true) {
+            self.disable_events();
+        }
+        E_FAIL
+    }
+
+    /// Appends the callback to the recording, if there is one.
+    fn record(&self, callback: Callback, args: impl FnOnce() -> Vec<Arg>) {
+        let Some(recorder) = self.recorder.get() else {
+            return;
+        };
+        catch_panic("recording", || {
+            recorder.record(self.profiler_info.get(), callback, args())
+        });
+    }
+
+    /// Adds the events the profiler's callbacks need to whatever mask it set
+    /// itself.
+    fn enable_events(&self, attach: bool) -> HRESULT {
+        if self.events.is_empty() {
+            return S_OK;
+        }
+        let Some(profiler_info) = self.profiler_info.get() else {
+            return S_OK;
+        };
+        match add_events(profiler_info, self.events, attach) {
+            Ok(()) => S_OK,
+            Err(hr) => hr.into(),
+        }
+    }
+
+    /// Clears every event flag that can still be changed after `Initialize`.
+    fn disable_events(&self) {
+        let Some(profiler_info) = self.profiler_info.get() else {
+            return;
+        };
+        // ICorProfilerInfo5 is needed for the high flags, older runtimes only have the low ones
+        let result = match profiler_info.get_event_mask_2() {
+            Ok(mask) => profiler_info.set_event_mask_2(
+                mask.events_low & COR_PRF_MONITOR::COR_PRF_MONITOR_IMMUTABLE,
+                mask.events_high & COR_PRF_HIGH_MONITOR::COR_PRF_HIGH_MONITOR_IMMUTABLE,
+            ),
+            Err(_) => profiler_info.get_event_mask().and_then(|mask| {
+                profiler_info.set_event_mask(mask & COR_PRF_MONITOR::COR_PRF_MONITOR_IMMUTABLE)
+            }),
+        };
+        match result {
+            Ok(()) => warn!("disabled profiler events after a panic"),
+            Err(hr) => error!("failed to disable profiler events: {hr}"),
+        }
+    }
 }
 
 // fn uuid_str(guid: GUID) -> String {
@@ -178,897 +270,1223 @@ impl<T: CorProfilerCallback9> CorProfilerCallback<T> {
 // }
 
 // IUnknown
-impl<T: CorProfilerCallback9> CorProfilerCallback<T> {
+impl<T: CorProfilerCallback11> CorProfilerCallback<T> {
     pub unsafe extern "system" fn query_interface(
-        &mut self,
+        &self,
         riid: REFIID,
         ppvObject: *mut *mut c_void,
     ) -> HRESULT {
-        info!("CorProfilerCallback hit query_interface! Querying riid: {}", *riid);
+        catch_panic("ICorProfilerCallback::QueryInterface", || {
+            info!("CorProfilerCallback hit query_interface! Querying riid: {}", *riid);
 
-        if *riid == IUnknown::IID
-            || *riid == ICorProfilerCallback::IID
-            || *riid == ICorProfilerCallback2::IID
-            || *riid == ICorProfilerCallback3::IID
-            || *riid == ICorProfilerCallback4::IID
-            || *riid == ICorProfilerCallback5::IID
-            || *riid == ICorProfilerCallback6::IID
-            || *riid == ICorProfilerCallback7::IID
-            || *riid == ICorProfilerCallback8::IID
-            || *riid == ICorProfilerCallback9::IID
-        {
-            *ppvObject = self as *mut CorProfilerCallback<T> as LPVOID;
-            self.add_ref();
-            S_OK
-        } else {
-            *ppvObject = ptr::null_mut();
-            E_NOINTERFACE
-        }
+            if *riid == IUnknown::IID
+                || *riid == ICorProfilerCallback::IID
+                || *riid == ICorProfilerCallback2::IID
+                || *riid == ICorProfilerCallback3::IID
+                || *riid == ICorProfilerCallback4::IID
+                || *riid == ICorProfilerCallback5::IID
+                || *riid == ICorProfilerCallback6::IID
+                || *riid == ICorProfilerCallback7::IID
+                || *riid == ICorProfilerCallback8::IID
+                || *riid == ICorProfilerCallback9::IID
+                || *riid == ICorProfilerCallback10::IID
+                || *riid == ICorProfilerCallback11::IID
+            {
+                *ppvObject = self as *const CorProfilerCallback<T> as LPVOID;
+                self.add_ref();
+                S_OK
+            } else {
+                *ppvObject = ptr::null_mut();
+                E_NOINTERFACE
+            }
+        })
+        .unwrap_or(E_FAIL)
     }
 
-    pub unsafe extern "system" fn add_ref(&mut self) -> ULONG {
-        debug!(
-            "CorProfilerCallback hit add_ref! Ref count is: {}",
-            self.ref_count.load(Ordering::Relaxed)
-        );
-        // TODO: Which ordering is appropriate?
-        let prev_ref_count = self.ref_count.fetch_add(1, Ordering::Relaxed);
-        prev_ref_count + 1
+    pub unsafe extern "system" fn add_ref(&self) -> ULONG {
+        catch_panic("ICorProfilerCallback::AddRef", || {
+            debug!("CorProfilerCallback hit add_ref! Ref count is: {}", self.ref_count.get());
+            self.ref_count.add_ref()
+        })
+        .unwrap_or(0)
     }
 
-    pub unsafe extern "system" fn release(&mut self) -> ULONG {
-        debug!(
-            "CorProfilerCallback hit release! Ref count is: {}",
-            self.ref_count.load(Ordering::Relaxed)
-        );
-        // Ensure we are not trying to release the memory twice if
-        // client calls release despite the ref_count being zero.
-        // TODO: Which ordering is appropriate?
-        if self.ref_count.load(Ordering::Relaxed) == 0 {
-            panic!("Cannot release the COM object, it has already been released.");
-        }
-
-        let prev_ref_count = self.ref_count.fetch_sub(1, Ordering::Relaxed);
-        let ref_count = prev_ref_count - 1;
-
-        if ref_count == 0 {
-            drop(Box::from_raw(self as *mut CorProfilerCallback<T>));
-        }
-
-        ref_count
+    pub unsafe extern "system" fn release(&self) -> ULONG {
+        catch_panic("ICorProfilerCallback::Release", || {
+            debug!("CorProfilerCallback hit release! Ref count is: {}", self.ref_count.get());
+            match self.ref_count.release() {
+                Some(0) => {
+                    drop(Box::from_raw(self as *const CorProfilerCallback<T> as *mut CorProfilerCallback<T>));
+                    0
+                }
+                Some(ref_count) => ref_count,
+                None => 0,
+            }
+        })
+        .unwrap_or(0)
     }
 }
 
 // TODO: Make sure I'm checking for null pointers from the CLR
 
 // ICorProfilerCallback
-impl<T: CorProfilerCallback9> CorProfilerCallback<T> {
+impl<T: CorProfilerCallback11> CorProfilerCallback<T> {
     pub unsafe extern "system" fn Initialize(
-        &mut self,
+        &self,
         pICorProfilerInfoUnk: *const CorProfilerInfo,
     ) -> HRESULT {
         if pICorProfilerInfoUnk.is_null() {
             // TODO: Add logging to indicate we got a null ptr for cor_profiler_info
             return E_FAIL;
         }
-        let profiler_info = ProfilerInfo::new(pICorProfilerInfoUnk);
+        let profiler_info = match ProfilerInfo::new(pICorProfilerInfoUnk) {
+            Ok(profiler_info) => profiler_info,
+            Err(hr) => return hr.into(),
+        };
+        // only one of Initialize and InitializeForAttach is ever called
+        let _ = self.profiler_info.set(profiler_info.clone());
+        if let Some(recorder) = Recorder::from_env() {
+            let _ = self.recorder.set(recorder);
+        }
+        self.record(Callback::Initialize, Vec::new);
 
-        let result = self.profiler.initialize(profiler_info);
-        match result {
-            Ok(_) => S_OK,
-            Err(hr) => hr,
+        let hr = self.guard("Initialize", |profiler| {
+            let result = profiler.initialize(profiler_info);
+            match result {
+                Ok(_) => S_OK,
+                Err(hr) => hr.into(),
+            }
+        });
+        if hr != S_OK {
+            return hr;
         }
+        self.enable_events(false)
     }
-    pub unsafe extern "system" fn Shutdown(&mut self) -> HRESULT {
-        let result = self.profiler.shutdown();
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+    pub unsafe extern "system" fn Shutdown(&self) -> HRESULT {
+        self.record(Callback::Shutdown, Vec::new);
+        self.guard("Shutdown", |profiler| {
+            let result = profiler.shutdown();
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn AppDomainCreationStarted(
-        &mut self,
+        &self,
         appDomainId: AppDomainID,
     ) -> HRESULT {
-        let result = self.profiler.app_domain_creation_started(appDomainId);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::AppDomainCreationStarted, || vec![Arg::from(appDomainId)]);
+        self.guard("AppDomainCreationStarted", |profiler| {
+            let result = profiler.app_domain_creation_started(appDomainId);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn AppDomainCreationFinished(
-        &mut self,
+        &self,
         appDomainId: AppDomainID,
         hrStatus: HRESULT,
     ) -> HRESULT {
-        let result = self
-            .profiler
-            .app_domain_creation_finished(appDomainId, hrStatus);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::AppDomainCreationFinished, || {
+            vec![Arg::from(appDomainId), Arg::from(hrStatus)]
+        });
+        self.guard("AppDomainCreationFinished", |profiler| {
+            let result = profiler
+                .app_domain_creation_finished(appDomainId, hrStatus.into());
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn AppDomainShutdownStarted(
-        &mut self,
+        &self,
         appDomainId: AppDomainID,
     ) -> HRESULT {
-        let result = self.profiler.app_domain_shutdown_started(appDomainId);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::AppDomainShutdownStarted, || vec![Arg::from(appDomainId)]);
+        self.guard("AppDomainShutdownStarted", |profiler| {
+            let result = profiler.app_domain_shutdown_started(appDomainId);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn AppDomainShutdownFinished(
-        &mut self,
+        &self,
         appDomainId: AppDomainID,
         hrStatus: HRESULT,
     ) -> HRESULT {
-        let result = self
-            .profiler
-            .app_domain_shutdown_finished(appDomainId, hrStatus);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::AppDomainShutdownFinished, || {
+            vec![Arg::from(appDomainId), Arg::from(hrStatus)]
+        });
+        self.guard("AppDomainShutdownFinished", |profiler| {
+            let result = profiler
+                .app_domain_shutdown_finished(appDomainId, hrStatus.into());
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn AssemblyLoadStarted(
-        &mut self,
+        &self,
         assemblyId: AssemblyID,
     ) -> HRESULT {
-        let result = self.profiler.assembly_load_started(assemblyId);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::AssemblyLoadStarted, || vec![Arg::from(assemblyId)]);
+        self.guard("AssemblyLoadStarted", |profiler| {
+            let result = profiler.assembly_load_started(assemblyId);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn AssemblyLoadFinished(
-        &mut self,
+        &self,
         assemblyId: AssemblyID,
         hrStatus: HRESULT,
     ) -> HRESULT {
-        let result = self.profiler.assembly_load_finished(assemblyId, hrStatus);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::AssemblyLoadFinished, || {
+            vec![Arg::from(assemblyId), Arg::from(hrStatus)]
+        });
+        self.guard("AssemblyLoadFinished", |profiler| {
+            let result = profiler.assembly_load_finished(assemblyId, hrStatus.into());
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn AssemblyUnloadStarted(
-        &mut self,
+        &self,
         assemblyId: AssemblyID,
     ) -> HRESULT {
-        let result = self.profiler.assembly_unload_started(assemblyId);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::AssemblyUnloadStarted, || vec![Arg::from(assemblyId)]);
+        self.guard("AssemblyUnloadStarted", |profiler| {
+            let result = profiler.assembly_unload_started(assemblyId);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn AssemblyUnloadFinished(
-        &mut self,
+        &self,
         assemblyId: AssemblyID,
         hrStatus: HRESULT,
     ) -> HRESULT {
-        let result = self.profiler.assembly_unload_finished(assemblyId, hrStatus);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::AssemblyUnloadFinished, || {
+            vec![Arg::from(assemblyId), Arg::from(hrStatus)]
+        });
+        self.guard("AssemblyUnloadFinished", |profiler| {
+            let result = profiler.assembly_unload_finished(assemblyId, hrStatus.into());
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
-    pub unsafe extern "system" fn ModuleLoadStarted(&mut self, moduleId: ModuleID) -> HRESULT {
-        let result = self.profiler.module_load_started(moduleId);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+    pub unsafe extern "system" fn ModuleLoadStarted(&self, moduleId: ModuleID) -> HRESULT {
+        self.record(Callback::ModuleLoadStarted, || vec![Arg::Module(moduleId)]);
+        self.guard("ModuleLoadStarted", |profiler| {
+            let result = profiler.module_load_started(moduleId);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn ModuleLoadFinished(
-        &mut self,
+        &self,
         moduleId: ModuleID,
         hrStatus: HRESULT,
     ) -> HRESULT {
-        let result = self.profiler.module_load_finished(moduleId, hrStatus);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::ModuleLoadFinished, || {
+            vec![Arg::Module(moduleId), Arg::from(hrStatus)]
+        });
+        self.guard("ModuleLoadFinished", |profiler| {
+            let result = profiler.module_load_finished(moduleId, hrStatus.into());
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
-    pub unsafe extern "system" fn ModuleUnloadStarted(&mut self, moduleId: ModuleID) -> HRESULT {
-        let result = self.profiler.module_unload_started(moduleId);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+    pub unsafe extern "system" fn ModuleUnloadStarted(&self, moduleId: ModuleID) -> HRESULT {
+        self.record(Callback::ModuleUnloadStarted, || vec![Arg::Module(moduleId)]);
+        self.guard("ModuleUnloadStarted", |profiler| {
+            let result = profiler.module_unload_started(moduleId);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn ModuleUnloadFinished(
-        &mut self,
+        &self,
         moduleId: ModuleID,
         hrStatus: HRESULT,
     ) -> HRESULT {
-        let result = self.profiler.module_unload_finished(moduleId, hrStatus);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::ModuleUnloadFinished, || {
+            vec![Arg::Module(moduleId), Arg::from(hrStatus)]
+        });
+        self.guard("ModuleUnloadFinished", |profiler| {
+            let result = profiler.module_unload_finished(moduleId, hrStatus.into());
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn ModuleAttachedToAssembly(
-        &mut self,
+        &self,
         moduleId: ModuleID,
         AssemblyId: AssemblyID,
     ) -> HRESULT {
-        let result = self
-            .profiler
-            .module_attached_to_assembly(moduleId, AssemblyId);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::ModuleAttachedToAssembly, || {
+            vec![Arg::Module(moduleId), Arg::from(AssemblyId)]
+        });
+        self.guard("ModuleAttachedToAssembly", |profiler| {
+            let result = profiler
+                .module_attached_to_assembly(moduleId, AssemblyId);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
-    pub unsafe extern "system" fn ClassLoadStarted(&mut self, classId: ClassID) -> HRESULT {
-        let result = self.profiler.class_load_started(classId);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+    pub unsafe extern "system" fn ClassLoadStarted(&self, classId: ClassID) -> HRESULT {
+        self.record(Callback::ClassLoadStarted, || vec![Arg::Class(classId)]);
+        self.guard("ClassLoadStarted", |profiler| {
+            let result = profiler.class_load_started(classId);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn ClassLoadFinished(
-        &mut self,
+        &self,
         classId: ClassID,
         hrStatus: HRESULT,
     ) -> HRESULT {
-        let result = self.profiler.class_load_finished(classId, hrStatus);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::ClassLoadFinished, || vec![Arg::Class(classId), Arg::from(hrStatus)]);
+        self.guard("ClassLoadFinished", |profiler| {
+            let result = profiler.class_load_finished(classId, hrStatus.into());
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
-    pub unsafe extern "system" fn ClassUnloadStarted(&mut self, classId: ClassID) -> HRESULT {
-        let result = self.profiler.class_unload_started(classId);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+    pub unsafe extern "system" fn ClassUnloadStarted(&self, classId: ClassID) -> HRESULT {
+        self.record(Callback::ClassUnloadStarted, || vec![Arg::Class(classId)]);
+        self.guard("ClassUnloadStarted", |profiler| {
+            let result = profiler.class_unload_started(classId);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn ClassUnloadFinished(
-        &mut self,
+        &self,
         classId: ClassID,
         hrStatus: HRESULT,
     ) -> HRESULT {
-        let result = self.profiler.class_unload_finished(classId, hrStatus);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::ClassUnloadFinished, || {
+            vec![Arg::Class(classId), Arg::from(hrStatus)]
+        });
+        self.guard("ClassUnloadFinished", |profiler| {
+            let result = profiler.class_unload_finished(classId, hrStatus.into());
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn FunctionUnloadStarted(
-        &mut self,
+        &self,
         functionId: FunctionID,
     ) -> HRESULT {
-        let result = self.profiler.function_unload_started(functionId);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::FunctionUnloadStarted, || vec![Arg::Function(functionId)]);
+        self.guard("FunctionUnloadStarted", |profiler| {
+            let result = profiler.function_unload_started(functionId);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn JITCompilationStarted(
-        &mut self,
+        &self,
         functionId: FunctionID,
         fIsSafeToBlock: BOOL,
     ) -> HRESULT {
-        let result = self
-            .profiler
-            .jit_compilation_started(functionId, fIsSafeToBlock.is_positive());
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::JITCompilationStarted, || {
+            vec![Arg::Function(functionId), Arg::from(fIsSafeToBlock)]
+        });
+        self.guard("JITCompilationStarted", |profiler| {
+            let result = profiler
+                .jit_compilation_started(functionId, fIsSafeToBlock.is_positive());
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn JITCompilationFinished(
-        &mut self,
+        &self,
         functionId: FunctionID,
         hrStatus: HRESULT,
         fIsSafeToBlock: BOOL,
     ) -> HRESULT {
-        let result = self.profiler.jit_compilation_finished(
-            functionId,
-            hrStatus,
-            fIsSafeToBlock.is_positive(),
-        );
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::JITCompilationFinished, || {
+            vec![Arg::Function(functionId), Arg::from(hrStatus), Arg::from(fIsSafeToBlock)]
+        });
+        self.guard("JITCompilationFinished", |profiler| {
+            let result = profiler.jit_compilation_finished(
+                functionId,
+                hrStatus.into(),
+                fIsSafeToBlock.is_positive(),
+            );
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn JITCachedFunctionSearchStarted(
-        &mut self,
+        &self,
         functionId: FunctionID,
         pbUseCachedFunction: *mut BOOL,
     ) -> HRESULT {
-        let result = self
-            .profiler
-            .jit_cached_function_search_started(functionId, (*pbUseCachedFunction).is_positive());
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::JITCachedFunctionSearchStarted, || vec![Arg::Function(functionId)]);
+        self.guard("JITCachedFunctionSearchStarted", |profiler| {
+            let result = profiler
+                .jit_cached_function_search_started(functionId, (*pbUseCachedFunction).is_positive());
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn JITCachedFunctionSearchFinished(
-        &mut self,
+        &self,
         functionId: FunctionID,
         result: COR_PRF_JIT_CACHE,
     ) -> HRESULT {
-        let result = self
-            .profiler
-            .jit_cached_function_search_finished(functionId, result);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::JITCachedFunctionSearchFinished, || {
+            vec![Arg::Function(functionId), Arg::from(result as u32)]
+        });
+        self.guard("JITCachedFunctionSearchFinished", |profiler| {
+            let result = profiler
+                .jit_cached_function_search_finished(functionId, result);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
-    pub unsafe extern "system" fn JITFunctionPitched(&mut self, functionId: FunctionID) -> HRESULT {
-        let result = self.profiler.jit_function_pitched(functionId);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+    pub unsafe extern "system" fn JITFunctionPitched(&self, functionId: FunctionID) -> HRESULT {
+        self.record(Callback::JITFunctionPitched, || vec![Arg::Function(functionId)]);
+        self.guard("JITFunctionPitched", |profiler| {
+            let result = profiler.jit_function_pitched(functionId);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn JITInlining(
-        &mut self,
+        &self,
         callerId: FunctionID,
         calleeId: FunctionID,
         pfShouldInline: *mut BOOL,
     ) -> HRESULT {
-        let result =
-            self.profiler
-                .jit_inlining(callerId, calleeId, (*pfShouldInline).is_positive());
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::JITInlining, || {
+            vec![Arg::Function(callerId), Arg::Function(calleeId)]
+        });
+        self.guard("JITInlining", |profiler| {
+            let result =
+                profiler
+                    .jit_inlining(callerId, calleeId, (*pfShouldInline).is_positive());
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
-    pub unsafe extern "system" fn ThreadCreated(&mut self, threadId: ThreadID) -> HRESULT {
-        let result = self.profiler.thread_created(threadId);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+    pub unsafe extern "system" fn ThreadCreated(&self, threadId: ThreadID) -> HRESULT {
+        self.record(Callback::ThreadCreated, || vec![Arg::from(threadId)]);
+        self.guard("ThreadCreated", |profiler| {
+            let result = profiler.thread_created(threadId);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
-    pub unsafe extern "system" fn ThreadDestroyed(&mut self, threadId: ThreadID) -> HRESULT {
-        let result = self.profiler.thread_destroyed(threadId);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+    pub unsafe extern "system" fn ThreadDestroyed(&self, threadId: ThreadID) -> HRESULT {
+        self.record(Callback::ThreadDestroyed, || vec![Arg::from(threadId)]);
+        self.guard("ThreadDestroyed", |profiler| {
+            let result = profiler.thread_destroyed(threadId);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn ThreadAssignedToOSThread(
-        &mut self,
+        &self,
         managedThreadId: ThreadID,
         osThreadId: DWORD,
     ) -> HRESULT {
-        let result = self
-            .profiler
-            .thread_assigned_to_os_thread(managedThreadId, osThreadId);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::ThreadAssignedToOSThread, || {
+            vec![Arg::from(managedThreadId), Arg::from(osThreadId)]
+        });
+        self.guard("ThreadAssignedToOSThread", |profiler| {
+            let result = profiler
+                .thread_assigned_to_os_thread(managedThreadId, osThreadId);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
-    pub unsafe extern "system" fn RemotingClientInvocationStarted(&mut self) -> HRESULT {
-        let result = self.profiler.remoting_client_invocation_started();
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+    pub unsafe extern "system" fn RemotingClientInvocationStarted(&self) -> HRESULT {
+        self.record(Callback::RemotingClientInvocationStarted, Vec::new);
+        self.guard("RemotingClientInvocationStarted", |profiler| {
+            let result = profiler.remoting_client_invocation_started();
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn RemotingClientSendingMessage(
-        &mut self,
+        &self,
         pCookie: *const GUID,
         fIsAsync: BOOL,
     ) -> HRESULT {
-        let result = self
-            .profiler
-            .remoting_client_sending_message(*pCookie, fIsAsync.is_positive());
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::RemotingClientSendingMessage, || {
+            vec![Arg::from_guid(pCookie), Arg::from(fIsAsync)]
+        });
+        self.guard("RemotingClientSendingMessage", |profiler| {
+            let result = profiler
+                .remoting_client_sending_message(*pCookie, fIsAsync.is_positive());
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn RemotingClientReceivingReply(
-        &mut self,
+        &self,
         pCookie: *const GUID,
         fIsAsync: BOOL,
     ) -> HRESULT {
-        let result = self
-            .profiler
-            .remoting_client_receiving_reply(*pCookie, fIsAsync.is_positive());
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::RemotingClientReceivingReply, || {
+            vec![Arg::from_guid(pCookie), Arg::from(fIsAsync)]
+        });
+        self.guard("RemotingClientReceivingReply", |profiler| {
+            let result = profiler
+                .remoting_client_receiving_reply(*pCookie, fIsAsync.is_positive());
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
-    pub unsafe extern "system" fn RemotingClientInvocationFinished(&mut self) -> HRESULT {
-        let result = self.profiler.remoting_client_invocation_finished();
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+    pub unsafe extern "system" fn RemotingClientInvocationFinished(&self) -> HRESULT {
+        self.record(Callback::RemotingClientInvocationFinished, Vec::new);
+        self.guard("RemotingClientInvocationFinished", |profiler| {
+            let result = profiler.remoting_client_invocation_finished();
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn RemotingServerReceivingMessage(
-        &mut self,
+        &self,
         pCookie: *const GUID,
         fIsAsync: BOOL,
     ) -> HRESULT {
-        let result = self
-            .profiler
-            .remoting_server_receiving_message(*pCookie, fIsAsync.is_positive());
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::RemotingServerReceivingMessage, || {
+            vec![Arg::from_guid(pCookie), Arg::from(fIsAsync)]
+        });
+        self.guard("RemotingServerReceivingMessage", |profiler| {
+            let result = profiler
+                .remoting_server_receiving_message(*pCookie, fIsAsync.is_positive());
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
-    pub unsafe extern "system" fn RemotingServerInvocationStarted(&mut self) -> HRESULT {
-        let result = self.profiler.remoting_server_invocation_started();
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+    pub unsafe extern "system" fn RemotingServerInvocationStarted(&self) -> HRESULT {
+        self.record(Callback::RemotingServerInvocationStarted, Vec::new);
+        self.guard("RemotingServerInvocationStarted", |profiler| {
+            let result = profiler.remoting_server_invocation_started();
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
-    pub unsafe extern "system" fn RemotingServerInvocationReturned(&mut self) -> HRESULT {
-        let result = self.profiler.remoting_server_invocation_returned();
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+    pub unsafe extern "system" fn RemotingServerInvocationReturned(&self) -> HRESULT {
+        self.record(Callback::RemotingServerInvocationReturned, Vec::new);
+        self.guard("RemotingServerInvocationReturned", |profiler| {
+            let result = profiler.remoting_server_invocation_returned();
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn RemotingServerSendingReply(
-        &mut self,
+        &self,
         pCookie: *const GUID,
         fIsAsync: BOOL,
     ) -> HRESULT {
-        let result = self
-            .profiler
-            .remoting_server_sending_reply(*pCookie, fIsAsync.is_positive());
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::RemotingServerSendingReply, || {
+            vec![Arg::from_guid(pCookie), Arg::from(fIsAsync)]
+        });
+        self.guard("RemotingServerSendingReply", |profiler| {
+            let result = profiler
+                .remoting_server_sending_reply(*pCookie, fIsAsync.is_positive());
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn UnmanagedToManagedTransition(
-        &mut self,
+        &self,
         functionId: FunctionID,
         reason: COR_PRF_TRANSITION_REASON,
     ) -> HRESULT {
-        let result = self
-            .profiler
-            .unmanaged_to_managed_transition(functionId, reason);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::UnmanagedToManagedTransition, || {
+            vec![Arg::Function(functionId), Arg::from(reason as u32)]
+        });
+        self.guard("UnmanagedToManagedTransition", |profiler| {
+            let result = profiler
+                .unmanaged_to_managed_transition(functionId, reason);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn ManagedToUnmanagedTransition(
-        &mut self,
+        &self,
         functionId: FunctionID,
         reason: COR_PRF_TRANSITION_REASON,
     ) -> HRESULT {
-        let result = self
-            .profiler
-            .managed_to_unmanaged_transition(functionId, reason);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::ManagedToUnmanagedTransition, || {
+            vec![Arg::Function(functionId), Arg::from(reason as u32)]
+        });
+        self.guard("ManagedToUnmanagedTransition", |profiler| {
+            let result = profiler
+                .managed_to_unmanaged_transition(functionId, reason);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn RuntimeSuspendStarted(
-        &mut self,
+        &self,
         suspendReason: COR_PRF_SUSPEND_REASON,
     ) -> HRESULT {
-        let result = self.profiler.runtime_suspend_started(suspendReason);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::RuntimeSuspendStarted, || vec![Arg::from(suspendReason as u32)]);
+        self.guard("RuntimeSuspendStarted", |profiler| {
+            let result = profiler.runtime_suspend_started(suspendReason);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
-    pub unsafe extern "system" fn RuntimeSuspendFinished(&mut self) -> HRESULT {
-        let result = self.profiler.runtime_suspend_finished();
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+    pub unsafe extern "system" fn RuntimeSuspendFinished(&self) -> HRESULT {
+        self.record(Callback::RuntimeSuspendFinished, Vec::new);
+        self.guard("RuntimeSuspendFinished", |profiler| {
+            let result = profiler.runtime_suspend_finished();
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
-    pub unsafe extern "system" fn RuntimeSuspendAborted(&mut self) -> HRESULT {
-        let result = self.profiler.runtime_suspend_aborted();
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+    pub unsafe extern "system" fn RuntimeSuspendAborted(&self) -> HRESULT {
+        self.record(Callback::RuntimeSuspendAborted, Vec::new);
+        self.guard("RuntimeSuspendAborted", |profiler| {
+            let result = profiler.runtime_suspend_aborted();
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
-    pub unsafe extern "system" fn RuntimeResumeStarted(&mut self) -> HRESULT {
-        let result = self.profiler.runtime_resume_started();
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+    pub unsafe extern "system" fn RuntimeResumeStarted(&self) -> HRESULT {
+        self.record(Callback::RuntimeResumeStarted, Vec::new);
+        self.guard("RuntimeResumeStarted", |profiler| {
+            let result = profiler.runtime_resume_started();
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
-    pub unsafe extern "system" fn RuntimeResumeFinished(&mut self) -> HRESULT {
-        let result = self.profiler.runtime_resume_finished();
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+    pub unsafe extern "system" fn RuntimeResumeFinished(&self) -> HRESULT {
+        self.record(Callback::RuntimeResumeFinished, Vec::new);
+        self.guard("RuntimeResumeFinished", |profiler| {
+            let result = profiler.runtime_resume_finished();
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
-    pub unsafe extern "system" fn RuntimeThreadSuspended(&mut self, threadId: ThreadID) -> HRESULT {
-        let result = self.profiler.runtime_thread_suspended(threadId);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+    pub unsafe extern "system" fn RuntimeThreadSuspended(&self, threadId: ThreadID) -> HRESULT {
+        self.record(Callback::RuntimeThreadSuspended, || vec![Arg::from(threadId)]);
+        self.guard("RuntimeThreadSuspended", |profiler| {
+            let result = profiler.runtime_thread_suspended(threadId);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
-    pub unsafe extern "system" fn RuntimeThreadResumed(&mut self, threadId: ThreadID) -> HRESULT {
-        let result = self.profiler.runtime_thread_resumed(threadId);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+    pub unsafe extern "system" fn RuntimeThreadResumed(&self, threadId: ThreadID) -> HRESULT {
+        self.record(Callback::RuntimeThreadResumed, || vec![Arg::from(threadId)]);
+        self.guard("RuntimeThreadResumed", |profiler| {
+            let result = profiler.runtime_thread_resumed(threadId);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn MovedReferences(
-        &mut self,
+        &self,
         cMovedObjectIDRanges: ULONG,
         oldObjectIDRangeStart: *const ObjectID,
         newObjectIDRangeStart: *const ObjectID,
         cObjectIDRangeLength: *const ULONG,
     ) -> HRESULT {
-        let oldObjectIDRangeStart: &[ObjectID] =
-            slice::from_raw_parts(oldObjectIDRangeStart, cMovedObjectIDRanges as usize);
-        let newObjectIDRangeStart: &[ObjectID] =
-            slice::from_raw_parts(newObjectIDRangeStart, cMovedObjectIDRanges as usize);
-        let cObjectIDRangeLength: &[ULONG] =
-            slice::from_raw_parts(cObjectIDRangeLength, cMovedObjectIDRanges as usize);
-        let result = self.profiler.moved_references(
-            oldObjectIDRangeStart,
-            newObjectIDRangeStart,
-            cObjectIDRangeLength,
-        );
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::MovedReferences, || {
+            vec![
+                Arg::from(cMovedObjectIDRanges),
+                Arg::from_array(oldObjectIDRangeStart, cMovedObjectIDRanges as usize),
+                Arg::from_array(newObjectIDRangeStart, cMovedObjectIDRanges as usize),
+                Arg::from_array(cObjectIDRangeLength, cMovedObjectIDRanges as usize),
+            ]
+        });
+        self.guard("MovedReferences", |profiler| {
+            let oldObjectIDRangeStart: &[ObjectID] =
+                slice::from_raw_parts(oldObjectIDRangeStart, cMovedObjectIDRanges as usize);
+            let newObjectIDRangeStart: &[ObjectID] =
+                slice::from_raw_parts(newObjectIDRangeStart, cMovedObjectIDRanges as usize);
+            let cObjectIDRangeLength: &[ULONG] =
+                slice::from_raw_parts(cObjectIDRangeLength, cMovedObjectIDRanges as usize);
+            let result = profiler.moved_references(
+                oldObjectIDRangeStart,
+                newObjectIDRangeStart,
+                cObjectIDRangeLength,
+            );
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn ObjectAllocated(
-        &mut self,
+        &self,
         objectId: ObjectID,
         classId: ClassID,
     ) -> HRESULT {
-        let result = self.profiler.object_allocated(objectId, classId);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::ObjectAllocated, || vec![Arg::from(objectId), Arg::Class(classId)]);
+        self.guard("ObjectAllocated", |profiler| {
+            let result = profiler.object_allocated(objectId, classId);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn ObjectsAllocatedByClass(
-        &mut self,
+        &self,
         cClassCount: ULONG,
         classIds: *const ClassID,
         cObjects: *const ULONG,
     ) -> HRESULT {
-        let classIds: &[ClassID] = slice::from_raw_parts(classIds, cClassCount as usize);
-        let cObjects: &[ULONG] = slice::from_raw_parts(cObjects, cClassCount as usize);
-        let result = self.profiler.objects_allocated_by_class(classIds, cObjects);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::ObjectsAllocatedByClass, || {
+            vec![
+                Arg::from(cClassCount),
+                Arg::from_array(classIds, cClassCount as usize),
+                Arg::from_array(cObjects, cClassCount as usize),
+            ]
+        });
+        self.guard("ObjectsAllocatedByClass", |profiler| {
+            let classIds: &[ClassID] = slice::from_raw_parts(classIds, cClassCount as usize);
+            let cObjects: &[ULONG] = slice::from_raw_parts(cObjects, cClassCount as usize);
+            let result = profiler.objects_allocated_by_class(classIds, cObjects);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn ObjectReferences(
-        &mut self,
+        &self,
         objectId: ObjectID,
         classId: ClassID,
         cObjectRefs: ULONG,
         objectRefIds: *const ObjectID,
     ) -> HRESULT {
-        let objectRefIds: &[ObjectID] = slice::from_raw_parts(objectRefIds, cObjectRefs as usize);
-        let result = self
-            .profiler
-            .object_references(objectId, classId, objectRefIds);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::ObjectReferences, || {
+            vec![
+                Arg::from(objectId),
+                Arg::Class(classId),
+                Arg::from(cObjectRefs),
+                Arg::from_array(objectRefIds, cObjectRefs as usize),
+            ]
+        });
+        self.guard("ObjectReferences", |profiler| {
+            let objectRefIds: &[ObjectID] = slice::from_raw_parts(objectRefIds, cObjectRefs as usize);
+            let result = profiler
+                .object_references(objectId, classId, objectRefIds);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn RootReferences(
-        &mut self,
+        &self,
         cRootRefs: ULONG,
         rootRefIds: *const ObjectID,
     ) -> HRESULT {
-        let rootRefIds: &[ObjectID] = slice::from_raw_parts(rootRefIds, cRootRefs as usize);
-        let result = self.profiler.root_references(rootRefIds);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::RootReferences, || {
+            vec![Arg::from(cRootRefs), Arg::from_array(rootRefIds, cRootRefs as usize)]
+        });
+        self.guard("RootReferences", |profiler| {
+            let rootRefIds: &[ObjectID] = slice::from_raw_parts(rootRefIds, cRootRefs as usize);
+            let result = profiler.root_references(rootRefIds);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
-    pub unsafe extern "system" fn ExceptionThrown(&mut self, thrownObjectId: ObjectID) -> HRESULT {
-        let result = self.profiler.exception_thrown(thrownObjectId);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+    pub unsafe extern "system" fn ExceptionThrown(&self, thrownObjectId: ObjectID) -> HRESULT {
+        self.record(Callback::ExceptionThrown, || vec![Arg::from(thrownObjectId)]);
+        self.guard("ExceptionThrown", |profiler| {
+            let result = profiler.exception_thrown(thrownObjectId);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn ExceptionSearchFunctionEnter(
-        &mut self,
+        &self,
         functionId: FunctionID,
     ) -> HRESULT {
-        let result = self.profiler.exception_search_function_enter(functionId);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::ExceptionSearchFunctionEnter, || vec![Arg::Function(functionId)]);
+        self.guard("ExceptionSearchFunctionEnter", |profiler| {
+            let result = profiler.exception_search_function_enter(functionId);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
-    pub unsafe extern "system" fn ExceptionSearchFunctionLeave(&mut self) -> HRESULT {
-        let result = self.profiler.exception_search_function_leave();
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+    pub unsafe extern "system" fn ExceptionSearchFunctionLeave(&self) -> HRESULT {
+        self.record(Callback::ExceptionSearchFunctionLeave, Vec::new);
+        self.guard("ExceptionSearchFunctionLeave", |profiler| {
+            let result = profiler.exception_search_function_leave();
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn ExceptionSearchFilterEnter(
-        &mut self,
+        &self,
         functionId: FunctionID,
     ) -> HRESULT {
-        let result = self.profiler.exception_search_filter_enter(functionId);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::ExceptionSearchFilterEnter, || vec![Arg::Function(functionId)]);
+        self.guard("ExceptionSearchFilterEnter", |profiler| {
+            let result = profiler.exception_search_filter_enter(functionId);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
-    pub unsafe extern "system" fn ExceptionSearchFilterLeave(&mut self) -> HRESULT {
-        let result = self.profiler.exception_search_filter_leave();
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+    pub unsafe extern "system" fn ExceptionSearchFilterLeave(&self) -> HRESULT {
+        self.record(Callback::ExceptionSearchFilterLeave, Vec::new);
+        self.guard("ExceptionSearchFilterLeave", |profiler| {
+            let result = profiler.exception_search_filter_leave();
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn ExceptionSearchCatcherFound(
-        &mut self,
+        &self,
         functionId: FunctionID,
     ) -> HRESULT {
-        let result = self.profiler.exception_search_catcher_found(functionId);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::ExceptionSearchCatcherFound, || vec![Arg::Function(functionId)]);
+        self.guard("ExceptionSearchCatcherFound", |profiler| {
+            let result = profiler.exception_search_catcher_found(functionId);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn ExceptionOSHandlerEnter(
-        &mut self,
+        &self,
         __unused: UINT_PTR,
     ) -> HRESULT {
-        let result = self.profiler.exception_os_handler_enter(__unused);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::ExceptionOSHandlerEnter, || vec![Arg::from(__unused)]);
+        self.guard("ExceptionOSHandlerEnter", |profiler| {
+            let result = profiler.exception_os_handler_enter(__unused);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn ExceptionOSHandlerLeave(
-        &mut self,
+        &self,
         __unused: UINT_PTR,
     ) -> HRESULT {
-        let result = self.profiler.exception_os_handler_leave(__unused);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::ExceptionOSHandlerLeave, || vec![Arg::from(__unused)]);
+        self.guard("ExceptionOSHandlerLeave", |profiler| {
+            let result = profiler.exception_os_handler_leave(__unused);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn ExceptionUnwindFunctionEnter(
-        &mut self,
+        &self,
         functionId: FunctionID,
     ) -> HRESULT {
-        let result = self.profiler.exception_unwind_function_enter(functionId);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::ExceptionUnwindFunctionEnter, || vec![Arg::Function(functionId)]);
+        self.guard("ExceptionUnwindFunctionEnter", |profiler| {
+            let result = profiler.exception_unwind_function_enter(functionId);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
-    pub unsafe extern "system" fn ExceptionUnwindFunctionLeave(&mut self) -> HRESULT {
-        let result = self.profiler.exception_unwind_function_leave();
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+    pub unsafe extern "system" fn ExceptionUnwindFunctionLeave(&self) -> HRESULT {
+        self.record(Callback::ExceptionUnwindFunctionLeave, Vec::new);
+        self.guard("ExceptionUnwindFunctionLeave", |profiler| {
+            let result = profiler.exception_unwind_function_leave();
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn ExceptionUnwindFinallyEnter(
-        &mut self,
+        &self,
         functionId: FunctionID,
     ) -> HRESULT {
-        let result = self.profiler.exception_unwind_finally_enter(functionId);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::ExceptionUnwindFinallyEnter, || vec![Arg::Function(functionId)]);
+        self.guard("ExceptionUnwindFinallyEnter", |profiler| {
+            let result = profiler.exception_unwind_finally_enter(functionId);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
-    pub unsafe extern "system" fn ExceptionUnwindFinallyLeave(&mut self) -> HRESULT {
-        let result = self.profiler.exception_unwind_finally_leave();
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+    pub unsafe extern "system" fn ExceptionUnwindFinallyLeave(&self) -> HRESULT {
+        self.record(Callback::ExceptionUnwindFinallyLeave, Vec::new);
+        self.guard("ExceptionUnwindFinallyLeave", |profiler| {
+            let result = profiler.exception_unwind_finally_leave();
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn ExceptionCatcherEnter(
-        &mut self,
+        &self,
         functionId: FunctionID,
         objectId: ObjectID,
     ) -> HRESULT {
-        let result = self.profiler.exception_catcher_enter(functionId, objectId);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::ExceptionCatcherEnter, || {
+            vec![Arg::Function(functionId), Arg::from(objectId)]
+        });
+        self.guard("ExceptionCatcherEnter", |profiler| {
+            let result = profiler.exception_catcher_enter(functionId, objectId);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
-    pub unsafe extern "system" fn ExceptionCatcherLeave(&mut self) -> HRESULT {
-        let result = self.profiler.exception_catcher_leave();
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+    pub unsafe extern "system" fn ExceptionCatcherLeave(&self) -> HRESULT {
+        self.record(Callback::ExceptionCatcherLeave, Vec::new);
+        self.guard("ExceptionCatcherLeave", |profiler| {
+            let result = profiler.exception_catcher_leave();
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn COMClassicVTableCreated(
-        &mut self,
+        &self,
         wrappedClassId: ClassID,
         implementedIID: REFGUID,
         pVTable: *const c_void,
         cSlots: ULONG,
     ) -> HRESULT {
-        let result = self.profiler.com_classic_vtable_created(
-            wrappedClassId,
-            implementedIID,
-            pVTable,
-            cSlots,
-        );
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::COMClassicVTableCreated, || {
+            vec![
+                Arg::Class(wrappedClassId),
+                Arg::from_guid(implementedIID),
+                Arg::from(pVTable as usize),
+                Arg::from(cSlots),
+            ]
+        });
+        self.guard("COMClassicVTableCreated", |profiler| {
+            let result = profiler.com_classic_vtable_created(
+                wrappedClassId,
+                implementedIID,
+                pVTable,
+                cSlots,
+            );
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn COMClassicVTableDestroyed(
-        &mut self,
+        &self,
         wrappedClassId: ClassID,
         implementedIID: REFGUID,
         pVTable: *const c_void,
     ) -> HRESULT {
-        let result =
-            self.profiler
-                .com_classic_vtable_destroyed(wrappedClassId, implementedIID, pVTable);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::COMClassicVTableDestroyed, || {
+            vec![
+                Arg::Class(wrappedClassId),
+                Arg::from_guid(implementedIID),
+                Arg::from(pVTable as usize),
+            ]
+        });
+        self.guard("COMClassicVTableDestroyed", |profiler| {
+            let result =
+                profiler
+                    .com_classic_vtable_destroyed(wrappedClassId, implementedIID, pVTable);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
-    pub unsafe extern "system" fn ExceptionCLRCatcherFound(&mut self) -> HRESULT {
-        let result = self.profiler.exception_clr_catcher_found();
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+    pub unsafe extern "system" fn ExceptionCLRCatcherFound(&self) -> HRESULT {
+        self.record(Callback::ExceptionCLRCatcherFound, Vec::new);
+        self.guard("ExceptionCLRCatcherFound", |profiler| {
+            let result = profiler.exception_clr_catcher_found();
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
-    pub unsafe extern "system" fn ExceptionCLRCatcherExecute(&mut self) -> HRESULT {
-        let result = self.profiler.exception_clr_catcher_execute();
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+    pub unsafe extern "system" fn ExceptionCLRCatcherExecute(&self) -> HRESULT {
+        self.record(Callback::ExceptionCLRCatcherExecute, Vec::new);
+        self.guard("ExceptionCLRCatcherExecute", |profiler| {
+            let result = profiler.exception_clr_catcher_execute();
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
 }
 
 // ICorProfilerCallback2
-impl<T: CorProfilerCallback9> CorProfilerCallback<T> {
+impl<T: CorProfilerCallback11> CorProfilerCallback<T> {
     pub unsafe extern "system" fn ThreadNameChanged(
-        &mut self,
+        &self,
         threadId: ThreadID,
         cchName: ULONG,
         name: *const WCHAR,
     ) -> HRESULT {
-        let name = U16String::from_ptr(name, cchName as usize).to_string_lossy();
-        let result = self.profiler.thread_name_changed(threadId, &name);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::ThreadNameChanged, || {
+            vec![
+                Arg::from(threadId),
+                Arg::Text(U16String::from_ptr(name, cchName as usize).to_string_lossy()),
+            ]
+        });
+        self.guard("ThreadNameChanged", |profiler| {
+            let name = U16String::from_ptr(name, cchName as usize).to_string_lossy();
+            let result = profiler.thread_name_changed(threadId, &name);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn GarbageCollectionStarted(
-        &mut self,
+        &self,
         cGenerations: int,
         generationCollected: *const BOOL,
         reason: COR_PRF_GC_REASON,
     ) -> HRESULT {
-        let generationCollected: &[BOOL] =
-            slice::from_raw_parts(generationCollected, cGenerations as usize);
-        let result = self
-            .profiler
-            .garbage_collection_started(generationCollected, reason);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::GarbageCollectionStarted, || {
+            vec![
+                Arg::from(cGenerations),
+                Arg::from_array(generationCollected, cGenerations as usize),
+                Arg::from(reason as u32),
+            ]
+        });
+        self.guard("GarbageCollectionStarted", |profiler| {
+            let generationCollected: &[BOOL] =
+                slice::from_raw_parts(generationCollected, cGenerations as usize);
+            let result = profiler
+                .garbage_collection_started(generationCollected, reason);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn SurvivingReferences(
-        &mut self,
+        &self,
         cSurvivingObjectIDRanges: ULONG,
         objectIDRangeStart: *const ObjectID,
         cObjectIDRangeLength: *const ULONG,
     ) -> HRESULT {
-        let objectIDRangeStart: &[ObjectID] =
-            slice::from_raw_parts(objectIDRangeStart, cSurvivingObjectIDRanges as usize);
-        let cObjectIDRangeLength: &[ULONG] =
-            slice::from_raw_parts(cObjectIDRangeLength, cSurvivingObjectIDRanges as usize);
-        let result = self
-            .profiler
-            .surviving_references(objectIDRangeStart, cObjectIDRangeLength);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::SurvivingReferences, || {
+            vec![
+                Arg::from(cSurvivingObjectIDRanges),
+                Arg::from_array(objectIDRangeStart, cSurvivingObjectIDRanges as usize),
+                Arg::from_array(cObjectIDRangeLength, cSurvivingObjectIDRanges as usize),
+            ]
+        });
+        self.guard("SurvivingReferences", |profiler| {
+            let objectIDRangeStart: &[ObjectID] =
+                slice::from_raw_parts(objectIDRangeStart, cSurvivingObjectIDRanges as usize);
+            let cObjectIDRangeLength: &[ULONG] =
+                slice::from_raw_parts(cObjectIDRangeLength, cSurvivingObjectIDRanges as usize);
+            let result = profiler
+                .surviving_references(objectIDRangeStart, cObjectIDRangeLength);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
-    pub unsafe extern "system" fn GarbageCollectionFinished(&mut self) -> HRESULT {
-        let result = self.profiler.garbage_collection_finished();
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+    pub unsafe extern "system" fn GarbageCollectionFinished(&self) -> HRESULT {
+        self.record(Callback::GarbageCollectionFinished, Vec::new);
+        self.guard("GarbageCollectionFinished", |profiler| {
+            let result = profiler.garbage_collection_finished();
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn FinalizeableObjectQueued(
-        &mut self,
+        &self,
         finalizerFlags: DWORD,
         objectID: ObjectID,
     ) -> HRESULT {
-        let result = self.profiler.finalizeable_object_queued(
-            COR_PRF_FINALIZER_FLAGS::from_bits(finalizerFlags).unwrap(),
-            objectID,
-        );
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::FinalizeableObjectQueued, || {
+            vec![Arg::from(finalizerFlags), Arg::from(objectID)]
+        });
+        self.guard("FinalizeableObjectQueued", |profiler| {
+            let result = profiler.finalizeable_object_queued(
+                COR_PRF_FINALIZER_FLAGS::from_bits(finalizerFlags).unwrap(),
+                objectID,
+            );
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn RootReferences2(
-        &mut self,
+        &self,
         cRootRefs: ULONG,
         rootRefIds: *const ObjectID,
         rootKinds: *const COR_PRF_GC_ROOT_KIND,
         rootFlags: *const COR_PRF_GC_ROOT_FLAGS,
         rootIds: *const UINT_PTR,
     ) -> HRESULT {
-        let rootRefIds: &[ObjectID] = slice::from_raw_parts(rootRefIds, cRootRefs as usize);
-        let rootKinds: &[COR_PRF_GC_ROOT_KIND] =
-            slice::from_raw_parts(rootKinds, cRootRefs as usize);
-        let rootFlags: &[COR_PRF_GC_ROOT_FLAGS] =
-            slice::from_raw_parts(rootFlags, cRootRefs as usize);
-        let rootIds: &[UINT_PTR] = slice::from_raw_parts(rootIds, cRootRefs as usize);
-        let result = self
-            .profiler
-            .root_references_2(rootRefIds, rootKinds, rootFlags, rootIds);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::RootReferences2, || {
+            vec![
+                Arg::from(cRootRefs),
+                Arg::from_array(rootRefIds, cRootRefs as usize),
+                Arg::from_array(rootKinds as *const u32, cRootRefs as usize),
+                Arg::from_array(rootFlags as *const u32, cRootRefs as usize),
+                Arg::from_array(rootIds, cRootRefs as usize),
+            ]
+        });
+        self.guard("RootReferences2", |profiler| {
+            let rootRefIds: &[ObjectID] = slice::from_raw_parts(rootRefIds, cRootRefs as usize);
+            let rootKinds: &[COR_PRF_GC_ROOT_KIND] =
+                slice::from_raw_parts(rootKinds, cRootRefs as usize);
+            let rootFlags: &[COR_PRF_GC_ROOT_FLAGS] =
+                slice::from_raw_parts(rootFlags, cRootRefs as usize);
+            let rootIds: &[UINT_PTR] = slice::from_raw_parts(rootIds, cRootRefs as usize);
+            let result = profiler
+                .root_references_2(rootRefIds, rootKinds, rootFlags, rootIds);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn HandleCreated(
-        &mut self,
+        &self,
         handleId: GCHandleID,
         initialObjectId: ObjectID,
     ) -> HRESULT {
-        let result = self.profiler.handle_created(handleId, initialObjectId);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::HandleCreated, || {
+            vec![Arg::from(handleId), Arg::from(initialObjectId)]
+        });
+        self.guard("HandleCreated", |profiler| {
+            let result = profiler.handle_created(handleId, initialObjectId);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
-    pub unsafe extern "system" fn HandleDestroyed(&mut self, handleId: GCHandleID) -> HRESULT {
-        let result = self.profiler.handle_destroyed(handleId);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+    pub unsafe extern "system" fn HandleDestroyed(&self, handleId: GCHandleID) -> HRESULT {
+        self.record(Callback::HandleDestroyed, || vec![Arg::from(handleId)]);
+        self.guard("HandleDestroyed", |profiler| {
+            let result = profiler.handle_destroyed(handleId);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn InitializeForAttach(
-        &mut self,
+        &self,
         pCorProfilerInfoUnk: *const CorProfilerInfo,
         pvClientData: *const c_void,
         cbClientData: UINT,
@@ -1077,230 +1495,482 @@ impl<T: CorProfilerCallback9> CorProfilerCallback<T> {
             // TODO: Add logging to indicate we got a null ptr for cor_profiler_info
             return E_FAIL;
         }
-        let profiler_info = ProfilerInfo::new(pCorProfilerInfoUnk);
+        let profiler_info = match ProfilerInfo::new(pCorProfilerInfoUnk) {
+            Ok(profiler_info) => profiler_info,
+            Err(hr) => return hr.into(),
+        };
+        // only one of Initialize and InitializeForAttach is ever called
+        let _ = self.profiler_info.set(profiler_info.clone());
+        if let Some(recorder) = Recorder::from_env() {
+            let _ = self.recorder.set(recorder);
+        }
+        self.record(Callback::InitializeForAttach, || {
+            vec![Arg::from_buffer(pvClientData as *const u8, cbClientData as usize)]
+        });
 
-        let result = self
-            .profiler
-            .initialize_for_attach(profiler_info, pvClientData, cbClientData);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
+        let hr = self.guard("InitializeForAttach", |profiler| {
+            let result = profiler
+                .initialize_for_attach(profiler_info, pvClientData, cbClientData);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        });
+        if hr != S_OK {
+            return hr;
         }
+        self.enable_events(true)
     }
-    pub unsafe extern "system" fn ProfilerAttachComplete(&mut self) -> HRESULT {
-        let result = self.profiler.profiler_attach_complete();
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+    pub unsafe extern "system" fn ProfilerAttachComplete(&self) -> HRESULT {
+        self.record(Callback::ProfilerAttachComplete, Vec::new);
+        self.guard("ProfilerAttachComplete", |profiler| {
+            let result = profiler.profiler_attach_complete();
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
-    pub unsafe extern "system" fn ProfilerDetachSucceeded(&mut self) -> HRESULT {
-        let result = self.profiler.profiler_detach_succeeded();
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+    pub unsafe extern "system" fn ProfilerDetachSucceeded(&self) -> HRESULT {
+        self.record(Callback::ProfilerDetachSucceeded, Vec::new);
+        self.guard("ProfilerDetachSucceeded", |profiler| {
+            let result = profiler.profiler_detach_succeeded();
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn ReJITCompilationStarted(
-        &mut self,
+        &self,
         functionId: FunctionID,
         rejitId: ReJITID,
         fIsSafeToBlock: BOOL,
     ) -> HRESULT {
-        let result = self.profiler.rejit_compilation_started(
-            functionId,
-            rejitId,
-            fIsSafeToBlock.is_positive(),
-        );
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::ReJITCompilationStarted, || {
+            vec![Arg::Function(functionId), Arg::from(rejitId), Arg::from(fIsSafeToBlock)]
+        });
+        self.guard("ReJITCompilationStarted", |profiler| {
+            let result = profiler.rejit_compilation_started(
+                functionId,
+                rejitId,
+                fIsSafeToBlock.is_positive(),
+            );
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn GetReJITParameters(
-        &mut self,
+        &self,
         moduleId: ModuleID,
         methodId: mdMethodDef,
         pFunctionControl: *const CorProfilerFunctionControl,
     ) -> HRESULT {
-        let pFunctionControl = pFunctionControl.as_ref();
-        if let Some(pFunctionControl) = pFunctionControl {
-            let result = self
-                .profiler
-                .get_rejit_parameters(moduleId, methodId, pFunctionControl);
-            match result {
-                Ok(_) => S_OK,
-                Err(error) => error,
+        self.record(Callback::GetReJITParameters, || {
+            vec![
+                Arg::Module(moduleId),
+                Arg::MethodDef(MethodDefToken::from_raw_unchecked(methodId)),
+            ]
+        });
+        self.guard("GetReJITParameters", |profiler| {
+            let pFunctionControl = pFunctionControl.as_ref();
+            if let Some(pFunctionControl) = pFunctionControl {
+                let result = profiler
+                    .get_rejit_parameters(
+                        moduleId,
+                        MethodDefToken::from_raw_unchecked(methodId),
+                        pFunctionControl,
+                    );
+                match result {
+                    Ok(_) => S_OK,
+                    Err(error) => error.into(),
+                }
+            } else {
+                E_FAIL
             }
-        } else {
-            E_FAIL
-        }
+        })
     }
     pub unsafe extern "system" fn ReJITCompilationFinished(
-        &mut self,
+        &self,
         functionId: FunctionID,
         rejitId: ReJITID,
         hrStatus: HRESULT,
         fIsSafeToBlock: BOOL,
     ) -> HRESULT {
-        let result = self.profiler.rejit_compilation_finished(
-            functionId,
-            rejitId,
-            hrStatus,
-            fIsSafeToBlock.is_positive(),
-        );
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::ReJITCompilationFinished, || {
+            vec![
+                Arg::Function(functionId),
+                Arg::from(rejitId),
+                Arg::from(hrStatus),
+                Arg::from(fIsSafeToBlock),
+            ]
+        });
+        self.guard("ReJITCompilationFinished", |profiler| {
+            let result = profiler.rejit_compilation_finished(
+                functionId,
+                rejitId,
+                hrStatus.into(),
+                fIsSafeToBlock.is_positive(),
+            );
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn ReJITError(
-        &mut self,
+        &self,
         moduleId: ModuleID,
         methodId: mdMethodDef,
         functionId: FunctionID,
         hrStatus: HRESULT,
     ) -> HRESULT {
-        let result = self
-            .profiler
-            .rejit_error(moduleId, methodId, functionId, hrStatus);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::ReJITError, || {
+            vec![
+                Arg::Module(moduleId),
+                Arg::MethodDef(MethodDefToken::from_raw_unchecked(methodId)),
+                Arg::Function(functionId),
+                Arg::from(hrStatus),
+            ]
+        });
+        self.guard("ReJITError", |profiler| {
+            let result = profiler
+                .rejit_error(
+                moduleId,
+                MethodDefToken::from_raw_unchecked(methodId),
+                functionId,
+                hrStatus.into(),
+            );
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn MovedReferences2(
-        &mut self,
+        &self,
         cMovedObjectIDRanges: ULONG,
         oldObjectIDRangeStart: *const ObjectID,
         newObjectIDRangeStart: *const ObjectID,
         cObjectIDRangeLength: *const SIZE_T,
     ) -> HRESULT {
-        let oldObjectIDRangeStart: &[ObjectID] =
-            slice::from_raw_parts(oldObjectIDRangeStart, cMovedObjectIDRanges as usize);
-        let newObjectIDRangeStart: &[ObjectID] =
-            slice::from_raw_parts(newObjectIDRangeStart, cMovedObjectIDRanges as usize);
-        let cObjectIDRangeLength: &[SIZE_T] =
-            slice::from_raw_parts(cObjectIDRangeLength, cMovedObjectIDRanges as usize);
-        let result = self.profiler.moved_references_2(
-            oldObjectIDRangeStart,
-            newObjectIDRangeStart,
-            cObjectIDRangeLength,
-        );
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::MovedReferences2, || {
+            vec![
+                Arg::from(cMovedObjectIDRanges),
+                Arg::from_array(oldObjectIDRangeStart, cMovedObjectIDRanges as usize),
+                Arg::from_array(newObjectIDRangeStart, cMovedObjectIDRanges as usize),
+                Arg::from_array(cObjectIDRangeLength, cMovedObjectIDRanges as usize),
+            ]
+        });
+        self.guard("MovedReferences2", |profiler| {
+            let oldObjectIDRangeStart: &[ObjectID] =
+                slice::from_raw_parts(oldObjectIDRangeStart, cMovedObjectIDRanges as usize);
+            let newObjectIDRangeStart: &[ObjectID] =
+                slice::from_raw_parts(newObjectIDRangeStart, cMovedObjectIDRanges as usize);
+            let cObjectIDRangeLength: &[SIZE_T] =
+                slice::from_raw_parts(cObjectIDRangeLength, cMovedObjectIDRanges as usize);
+            let result = profiler.moved_references_2(
+                oldObjectIDRangeStart,
+                newObjectIDRangeStart,
+                cObjectIDRangeLength,
+            );
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn SurvivingReferences2(
-        &mut self,
+        &self,
         cSurvivingObjectIDRanges: ULONG,
         objectIDRangeStart: *const ObjectID,
         cObjectIDRangeLength: *const SIZE_T,
     ) -> HRESULT {
-        let objectIDRangeStart: &[ObjectID] =
-            slice::from_raw_parts(objectIDRangeStart, cSurvivingObjectIDRanges as usize);
-        let cObjectIDRangeLength: &[ObjectID] =
-            slice::from_raw_parts(cObjectIDRangeLength, cSurvivingObjectIDRanges as usize);
-        let result = self
-            .profiler
-            .surviving_references_2(objectIDRangeStart, cObjectIDRangeLength);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::SurvivingReferences2, || {
+            vec![
+                Arg::from(cSurvivingObjectIDRanges),
+                Arg::from_array(objectIDRangeStart, cSurvivingObjectIDRanges as usize),
+                Arg::from_array(cObjectIDRangeLength, cSurvivingObjectIDRanges as usize),
+            ]
+        });
+        self.guard("SurvivingReferences2", |profiler| {
+            let objectIDRangeStart: &[ObjectID] =
+                slice::from_raw_parts(objectIDRangeStart, cSurvivingObjectIDRanges as usize);
+            let cObjectIDRangeLength: &[ObjectID] =
+                slice::from_raw_parts(cObjectIDRangeLength, cSurvivingObjectIDRanges as usize);
+            let result = profiler
+                .surviving_references_2(objectIDRangeStart, cObjectIDRangeLength);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn ConditionalWeakTableElementReferences(
-        &mut self,
+        &self,
         cRootRefs: ULONG,
         keyRefIds: *const ObjectID,
         valueRefIds: *const ObjectID,
         rootIds: *const GCHandleID,
     ) -> HRESULT {
-        let keyRefIds: &[ObjectID] = slice::from_raw_parts(keyRefIds, cRootRefs as usize);
-        let valueRefIds: &[ObjectID] = slice::from_raw_parts(valueRefIds, cRootRefs as usize);
-        let rootIds: &[GCHandleID] = slice::from_raw_parts(rootIds, cRootRefs as usize);
-        let result = self.profiler.conditional_weak_table_element_references(
-            keyRefIds,
-            valueRefIds,
-            rootIds,
-        );
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::ConditionalWeakTableElementReferences, || {
+            vec![
+                Arg::from(cRootRefs),
+                Arg::from_array(keyRefIds, cRootRefs as usize),
+                Arg::from_array(valueRefIds, cRootRefs as usize),
+                Arg::from_array(rootIds, cRootRefs as usize),
+            ]
+        });
+        self.guard("ConditionalWeakTableElementReferences", |profiler| {
+            let keyRefIds: &[ObjectID] = slice::from_raw_parts(keyRefIds, cRootRefs as usize);
+            let valueRefIds: &[ObjectID] = slice::from_raw_parts(valueRefIds, cRootRefs as usize);
+            let rootIds: &[GCHandleID] = slice::from_raw_parts(rootIds, cRootRefs as usize);
+            let result = profiler.conditional_weak_table_element_references(
+                keyRefIds,
+                valueRefIds,
+                rootIds,
+            );
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn GetAssemblyReferences(
-        &mut self,
+        &self,
         wszAssemblyPath: *const WCHAR,
         pAsmRefProvider: *const CorProfilerAssemblyReferenceProvider,
     ) -> HRESULT {
-        let wszAssemblyPath = U16CString::from_ptr_str(wszAssemblyPath).to_string_lossy();
-        let pAsmRefProvider = pAsmRefProvider.as_ref();
-        if let Some(pAsmRefProvider) = pAsmRefProvider {
-            let result = self
-                .profiler
-                .get_assembly_references(&wszAssemblyPath, pAsmRefProvider);
-            match result {
-                Ok(_) => S_OK,
-                Err(error) => error,
+        self.record(Callback::GetAssemblyReferences, || {
+            vec![Arg::Text(U16CString::from_ptr_str(wszAssemblyPath).to_string_lossy())]
+        });
+        self.guard("GetAssemblyReferences", |profiler| {
+            let wszAssemblyPath = U16CString::from_ptr_str(wszAssemblyPath).to_string_lossy();
+            let pAsmRefProvider = pAsmRefProvider.as_ref();
+            if let Some(pAsmRefProvider) = pAsmRefProvider {
+                let result = profiler
+                    .get_assembly_references(&wszAssemblyPath, pAsmRefProvider);
+                match result {
+                    Ok(_) => S_OK,
+                    Err(error) => error.into(),
+                }
+            } else {
+                E_FAIL
             }
-        } else {
-            E_FAIL
-        }
+        })
     }
     pub unsafe extern "system" fn ModuleInMemorySymbolsUpdated(
-        &mut self,
+        &self,
         moduleId: ModuleID,
     ) -> HRESULT {
-        let result = self.profiler.module_in_memory_symbols_updated(moduleId);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::ModuleInMemorySymbolsUpdated, || vec![Arg::Module(moduleId)]);
+        self.guard("ModuleInMemorySymbolsUpdated", |profiler| {
+            let result = profiler.module_in_memory_symbols_updated(moduleId);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn DynamicMethodJITCompilationStarted(
-        &mut self,
+        &self,
         functionId: FunctionID,
         fIsSafeToBlock: BOOL,
         pILHeader: LPCBYTE,
         cbILHeader: ULONG,
     ) -> HRESULT {
-        let result = self.profiler.dynamic_method_jit_compilation_started(
-            functionId,
-            fIsSafeToBlock.is_positive(),
-            pILHeader,
-            cbILHeader,
-        );
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::DynamicMethodJITCompilationStarted, || {
+            vec![
+                Arg::from(functionId),
+                Arg::from(fIsSafeToBlock),
+                Arg::from_buffer(pILHeader, cbILHeader as usize),
+            ]
+        });
+        self.guard("DynamicMethodJITCompilationStarted", |profiler| {
+            let result = profiler.dynamic_method_jit_compilation_started(
+                functionId,
+                fIsSafeToBlock.is_positive(),
+                pILHeader,
+                cbILHeader,
+            );
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn DynamicMethodJITCompilationFinished(
-        &mut self,
+        &self,
         functionId: FunctionID,
         hrStatus: HRESULT,
         fIsSafeToBlock: BOOL,
     ) -> HRESULT {
-        let result = self.profiler.dynamic_method_jit_compilation_finished(
-            functionId,
-            hrStatus,
-            fIsSafeToBlock.is_positive(),
-        );
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::DynamicMethodJITCompilationFinished, || {
+            vec![Arg::from(functionId), Arg::from(hrStatus), Arg::from(fIsSafeToBlock)]
+        });
+        self.guard("DynamicMethodJITCompilationFinished", |profiler| {
+            let result = profiler.dynamic_method_jit_compilation_finished(
+                functionId,
+                hrStatus.into(),
+                fIsSafeToBlock.is_positive(),
+            );
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
     pub unsafe extern "system" fn DynamicMethodUnloaded(
-        &mut self,
+        &self,
         functionId: FunctionID,
     ) -> HRESULT {
-        let result = self.profiler.dynamic_method_unloaded(functionId);
-        match result {
-            Ok(_) => S_OK,
-            Err(error) => error,
-        }
+        self.record(Callback::DynamicMethodUnloaded, || vec![Arg::from(functionId)]);
+        self.guard("DynamicMethodUnloaded", |profiler| {
+            let result = profiler.dynamic_method_unloaded(functionId);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
     }
 }
+
+// ICorProfilerCallback10
+impl<T: CorProfilerCallback11> CorProfilerCallback<T> {
+    pub unsafe extern "system" fn EventPipeEventDelivered(
+        &self,
+        provider: EVENTPIPE_PROVIDER,
+        eventId: DWORD,
+        eventVersion: DWORD,
+        cbMetadataBlob: ULONG,
+        metadataBlob: LPCBYTE,
+        cbEventData: ULONG,
+        eventData: LPCBYTE,
+        pActivityId: LPCGUID,
+        pRelatedActivityId: LPCGUID,
+        eventThread: ThreadID,
+        numStackFrames: ULONG,
+        stackFrames: *const UINT_PTR,
+    ) -> HRESULT {
+        self.record(Callback::EventPipeEventDelivered, || {
+            vec![
+                Arg::from(provider),
+                Arg::from(eventId),
+                Arg::from(eventVersion),
+                Arg::from_buffer(metadataBlob, cbMetadataBlob as usize),
+                Arg::from_buffer(eventData, cbEventData as usize),
+                Arg::from_guid(pActivityId),
+                Arg::from_guid(pRelatedActivityId),
+                Arg::from(eventThread),
+                Arg::from_array(stackFrames, numStackFrames as usize),
+            ]
+        });
+        self.guard("EventPipeEventDelivered", |profiler| {
+            let result = profiler.event_pipe_event_delivered(EventPipeEvent {
+                provider,
+                event_id: eventId,
+                event_version: eventVersion,
+                metadata_blob: nullable_slice(metadataBlob, cbMetadataBlob),
+                event_data: nullable_slice(eventData, cbEventData),
+                activity_id: pActivityId.as_ref(),
+                related_activity_id: pRelatedActivityId.as_ref(),
+                event_thread: eventThread,
+                stack_frames: nullable_slice(stackFrames, numStackFrames),
+            });
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
+    }
+    pub unsafe extern "system" fn EventPipeProviderCreated(
+        &self,
+        provider: EVENTPIPE_PROVIDER,
+    ) -> HRESULT {
+        self.record(Callback::EventPipeProviderCreated, || vec![Arg::from(provider)]);
+        self.guard("EventPipeProviderCreated", |profiler| {
+            let result = profiler.event_pipe_provider_created(provider);
+            match result {
+                Ok(_) => S_OK,
+                Err(error) => error.into(),
+            }
+        })
+    }
+}
+
+// ICorProfilerCallback11
+impl<T: CorProfilerCallback11> CorProfilerCallback<T> {
+    pub unsafe extern "system" fn LoadAsNotificationOnly(
+        &self,
+        pbNotificationOnly: *mut BOOL,
+    ) -> HRESULT {
+        self.record(Callback::LoadAsNotificationOnly, Vec::new);
+        if pbNotificationOnly.is_null() {
+            return E_POINTER;
+        }
+        self.guard("LoadAsNotificationOnly", |profiler| {
+            match profiler.load_as_notification_only() {
+                Ok(notification_only) => {
+                    *pbNotificationOnly = notification_only.into();
+                    S_OK
+                }
+                Err(error) => error.into(),
+            }
+        })
+    }
+}
+
+/// The runtime passes null for empty arrays, which `slice::from_raw_parts` does not allow.
+unsafe fn nullable_slice<'a, T>(data: *const T, length: ULONG) -> &'a [T] {
+    if data.is_null() || length == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(data, length as usize)
+    }
+}
+
+/// Adds `events` to whatever mask is already set. After attaching only some
+/// events can be turned on, and the immutable ones only in `Initialize`:
+/// the runtime fails the whole mask if it changes them later.
+pub(crate) fn add_events(
+    profiler_info: &ProfilerInfo,
+    mut events: EventMask2,
+    attach: bool,
+) -> Result<(), HResult> {
+    if attach {
+        let immutable_low = events.events_low & COR_PRF_MONITOR::COR_PRF_MONITOR_IMMUTABLE;
+        let immutable_high =
+            events.events_high & COR_PRF_HIGH_MONITOR::COR_PRF_HIGH_MONITOR_IMMUTABLE;
+        if !immutable_low.is_empty() || !immutable_high.is_empty() {
+            warn!("dropped events only Initialize can set: {immutable_low:?} {immutable_high:?}");
+        }
+        let allowed = EventMask2 {
+            events_low: COR_PRF_MONITOR::COR_PRF_ALLOWABLE_AFTER_ATTACH,
+            events_high: COR_PRF_HIGH_MONITOR::COR_PRF_HIGH_ALLOWABLE_AFTER_ATTACH,
+        };
+        let refused_low = events.events_low.difference(allowed.events_low | immutable_low);
+        let refused_high = events.events_high.difference(allowed.events_high | immutable_high);
+        if !refused_low.is_empty() || !refused_high.is_empty() {
+            warn!("dropped events not allowed after attaching: {refused_low:?} {refused_high:?}");
+        }
+        events.events_low &= allowed.events_low;
+        events.events_high &= allowed.events_high;
+    }
+    let result = match profiler_info.get_event_mask_2() {
+        Ok(mask) => profiler_info.set_event_mask_2(
+            mask.events_low | events.events_low,
+            mask.events_high | events.events_high,
+        ),
+        Err(_) if events.events_high.is_empty() => profiler_info
+            .get_event_mask()
+            .and_then(|mask| profiler_info.set_event_mask(mask | events.events_low)),
+        Err(hr) => Err(hr),
+    };
+    match result {
+        Ok(()) => debug!("enabled events {events:?}"),
+        Err(hr) => error!("failed to enable events {events:?}: {hr}"),
+    }
+    result
+}
@@ -3,18 +3,22 @@ use super::MetaDataImport;
 use crate::ffi::{
     int, mdFieldDef, mdMethodDef, mdToken, mdTypeDef, AppDomainID, AssemblyID, ClassID, ContextID,
     CorElementType, CorProfilerFunctionEnum, CorProfilerMethodEnum, CorProfilerModuleEnum,
-    CorProfilerObjectEnum, CorProfilerThreadEnum, FunctionEnter, FunctionEnter2, FunctionEnter3,
-    FunctionEnter3WithInfo, FunctionID, FunctionIDMapper, FunctionIDMapper2, FunctionLeave,
-    FunctionLeave2, FunctionLeave3, FunctionLeave3WithInfo, FunctionTailcall, FunctionTailcall2,
-    FunctionTailcall3, FunctionTailcall3WithInfo, ICorProfilerInfo, ICorProfilerInfo10,
-    ICorProfilerInfo2, ICorProfilerInfo3, ICorProfilerInfo4, ICorProfilerInfo5, ICorProfilerInfo6,
-    ICorProfilerInfo7, ICorProfilerInfo8, ICorProfilerInfo9, IUnknown, MethodMalloc, ModuleID,
-    ObjectID, ObjectReferenceCallback, ProcessID, ReJITID, StackSnapshotCallback, ThreadID,
-    Unknown, BOOL, BYTE, COR_DEBUG_IL_TO_NATIVE_MAP, COR_FIELD_OFFSET, COR_IL_MAP,
-    COR_PRF_CODE_INFO, COR_PRF_ELT_INFO, COR_PRF_EX_CLAUSE_INFO, COR_PRF_FRAME_INFO,
-    COR_PRF_FUNCTION_ARGUMENT_INFO, COR_PRF_FUNCTION_ARGUMENT_RANGE, COR_PRF_GC_GENERATION_RANGE,
-    COR_PRF_RUNTIME_TYPE, COR_PRF_STATIC_TYPE, DWORD, HANDLE, HRESULT, LPCBYTE, PCCOR_SIGNATURE,
-    REFIID, SIZE_T, UINT_PTR, ULONG, ULONG32, USHORT, WCHAR,
+    CorProfilerObjectEnum, CorProfilerThreadEnum, EventPipeProviderCallback, FunctionEnter,
+    FunctionEnter2, FunctionEnter3, FunctionEnter3WithInfo, FunctionID, FunctionIDMapper,
+    FunctionIDMapper2, FunctionLeave, FunctionLeave2, FunctionLeave3, FunctionLeave3WithInfo,
+    FunctionTailcall, FunctionTailcall2, FunctionTailcall3, FunctionTailcall3WithInfo,
+    ICorProfilerInfo, ICorProfilerInfo10, ICorProfilerInfo11, ICorProfilerInfo12,
+    ICorProfilerInfo13, ICorProfilerInfo14, ICorProfilerInfo2, ICorProfilerInfo3,
+    ICorProfilerInfo4, ICorProfilerInfo5, ICorProfilerInfo6, ICorProfilerInfo7, ICorProfilerInfo8,
+    ICorProfilerInfo9, IUnknown, MethodMalloc, ModuleID, ObjectHandleID, ObjectID,
+    ObjectReferenceCallback, ProcessID, ReJITID, StackSnapshotCallback, ThreadID, Unknown, BOOL,
+    BYTE, COR_DEBUG_IL_TO_NATIVE_MAP, COR_FIELD_OFFSET, COR_IL_MAP, COR_PRF_CODE_INFO,
+    COR_PRF_ELT_INFO, COR_PRF_EVENTPIPE_PARAM_DESC, COR_PRF_EVENTPIPE_PROVIDER_CONFIG,
+    COR_PRF_EVENT_DATA, COR_PRF_EX_CLAUSE_INFO, COR_PRF_FRAME_INFO, COR_PRF_FUNCTION_ARGUMENT_INFO,
+    COR_PRF_FUNCTION_ARGUMENT_RANGE, COR_PRF_GC_GENERATION_RANGE, COR_PRF_HANDLE_TYPE,
+    COR_PRF_NONGC_HEAP_RANGE, COR_PRF_RUNTIME_TYPE, COR_PRF_STATIC_TYPE, DWORD, EVENTPIPE_EVENT,
+    EVENTPIPE_PROVIDER, EVENTPIPE_SESSION, HANDLE, HRESULT, LPCBYTE, LPCGUID, PCCOR_SIGNATURE,
+    REFIID, SIZE_T, UINT32, UINT64, UINT8, UINT_PTR, ULONG, ULONG32, USHORT, WCHAR,
 };
 use std::ffi::c_void;
 #[repr(C)]
@@ -30,6 +34,10 @@ pub struct CorProfilerInfoVtbl {
     pub ICorProfilerInfo8: ICorProfilerInfo8<CorProfilerInfo>,
     pub ICorProfilerInfo9: ICorProfilerInfo9<CorProfilerInfo>,
     pub ICorProfilerInfo10: ICorProfilerInfo10<CorProfilerInfo>,
+    pub ICorProfilerInfo11: ICorProfilerInfo11<CorProfilerInfo>,
+    pub ICorProfilerInfo12: ICorProfilerInfo12<CorProfilerInfo>,
+    pub ICorProfilerInfo13: ICorProfilerInfo13<CorProfilerInfo>,
+    pub ICorProfilerInfo14: ICorProfilerInfo14<CorProfilerInfo>,
 }
 
 #[derive(Clone)]
@@ -69,6 +77,18 @@ impl CorProfilerInfo {
     unsafe fn i_cor_profiler_info_10(&self) -> &ICorProfilerInfo10<Self> {
         &(*self.lpVtbl).ICorProfilerInfo10
     }
+    unsafe fn i_cor_profiler_info_11(&self) -> &ICorProfilerInfo11<Self> {
+        &(*self.lpVtbl).ICorProfilerInfo11
+    }
+    unsafe fn i_cor_profiler_info_12(&self) -> &ICorProfilerInfo12<Self> {
+        &(*self.lpVtbl).ICorProfilerInfo12
+    }
+    unsafe fn i_cor_profiler_info_13(&self) -> &ICorProfilerInfo13<Self> {
+        &(*self.lpVtbl).ICorProfilerInfo13
+    }
+    unsafe fn i_cor_profiler_info_14(&self) -> &ICorProfilerInfo14<Self> {
+        &(*self.lpVtbl).ICorProfilerInfo14
+    }
     pub unsafe fn GetClassFromObject(&self, objectId: ObjectID, pClassId: *mut ClassID) -> HRESULT {
         (self.i_cor_profiler_info().GetClassFromObject)(self, objectId, pClassId)
     }
@@ -1017,4 +1037,162 @@ impl CorProfilerInfo {
     pub unsafe fn ResumeRuntime(&self) -> HRESULT {
         (self.i_cor_profiler_info_10().ResumeRuntime)(self)
     }
+    pub unsafe fn GetEnvironmentVariable(
+        &self,
+        szName: *const WCHAR,
+        cchValue: ULONG,
+        pcchValue: *mut ULONG,
+        szValue: *mut WCHAR,
+    ) -> HRESULT {
+        (self.i_cor_profiler_info_11().GetEnvironmentVariable)(
+            self, szName, cchValue, pcchValue, szValue,
+        )
+    }
+    pub unsafe fn SetEnvironmentVariable(
+        &self,
+        szName: *const WCHAR,
+        szValue: *const WCHAR,
+    ) -> HRESULT {
+        (self.i_cor_profiler_info_11().SetEnvironmentVariable)(self, szName, szValue)
+    }
+    pub unsafe fn EventPipeStartSession(
+        &self,
+        cProviderConfigs: UINT32,
+        pProviderConfigs: *const COR_PRF_EVENTPIPE_PROVIDER_CONFIG,
+        requestRundown: BOOL,
+        pSession: *mut EVENTPIPE_SESSION,
+    ) -> HRESULT {
+        (self.i_cor_profiler_info_12().EventPipeStartSession)(
+            self,
+            cProviderConfigs,
+            pProviderConfigs,
+            requestRundown,
+            pSession,
+        )
+    }
+    pub unsafe fn EventPipeAddProviderToSession(
+        &self,
+        session: EVENTPIPE_SESSION,
+        providerConfig: COR_PRF_EVENTPIPE_PROVIDER_CONFIG,
+    ) -> HRESULT {
+        (self.i_cor_profiler_info_12().EventPipeAddProviderToSession)(self, session, providerConfig)
+    }
+    pub unsafe fn EventPipeStopSession(&self, session: EVENTPIPE_SESSION) -> HRESULT {
+        (self.i_cor_profiler_info_12().EventPipeStopSession)(self, session)
+    }
+    pub unsafe fn EventPipeCreateProvider(
+        &self,
+        providerName: *const WCHAR,
+        pProvider: *mut EVENTPIPE_PROVIDER,
+    ) -> HRESULT {
+        (self.i_cor_profiler_info_12().EventPipeCreateProvider)(self, providerName, pProvider)
+    }
+    pub unsafe fn EventPipeGetProviderInfo(
+        &self,
+        provider: EVENTPIPE_PROVIDER,
+        cchName: ULONG,
+        pcchName: *mut ULONG,
+        providerName: *mut WCHAR,
+    ) -> HRESULT {
+        (self.i_cor_profiler_info_12().EventPipeGetProviderInfo)(
+            self,
+            provider,
+            cchName,
+            pcchName,
+            providerName,
+        )
+    }
+    pub unsafe fn EventPipeDefineEvent(
+        &self,
+        provider: EVENTPIPE_PROVIDER,
+        eventName: *const WCHAR,
+        eventID: UINT32,
+        keywords: UINT64,
+        eventVersion: UINT32,
+        level: UINT32,
+        opcode: UINT8,
+        needStack: BOOL,
+        cParamDescs: UINT32,
+        pParamDescs: *const COR_PRF_EVENTPIPE_PARAM_DESC,
+        pEvent: *mut EVENTPIPE_EVENT,
+    ) -> HRESULT {
+        (self.i_cor_profiler_info_12().EventPipeDefineEvent)(
+            self,
+            provider,
+            eventName,
+            eventID,
+            keywords,
+            eventVersion,
+            level,
+            opcode,
+            needStack,
+            cParamDescs,
+            pParamDescs,
+            pEvent,
+        )
+    }
+    pub unsafe fn EventPipeWriteEvent(
+        &self,
+        event: EVENTPIPE_EVENT,
+        cData: UINT32,
+        data: *const COR_PRF_EVENT_DATA,
+        pActivityId: LPCGUID,
+        pRelatedActivityId: LPCGUID,
+    ) -> HRESULT {
+        (self.i_cor_profiler_info_12().EventPipeWriteEvent)(
+            self,
+            event,
+            cData,
+            data,
+            pActivityId,
+            pRelatedActivityId,
+        )
+    }
+    pub unsafe fn CreateHandle(
+        &self,
+        object: ObjectID,
+        r#type: COR_PRF_HANDLE_TYPE,
+        pHandle: *mut ObjectHandleID,
+    ) -> HRESULT {
+        (self.i_cor_profiler_info_13().CreateHandle)(self, object, r#type, pHandle)
+    }
+    pub unsafe fn DestroyHandle(&self, handle: ObjectHandleID) -> HRESULT {
+        (self.i_cor_profiler_info_13().DestroyHandle)(self, handle)
+    }
+    pub unsafe fn GetObjectIDFromHandle(
+        &self,
+        handle: ObjectHandleID,
+        pObject: *mut ObjectID,
+    ) -> HRESULT {
+        (self.i_cor_profiler_info_13().GetObjectIDFromHandle)(self, handle, pObject)
+    }
+    pub unsafe fn EnumerateNonGCObjects(&self, ppEnum: *mut *mut CorProfilerObjectEnum) -> HRESULT {
+        (self.i_cor_profiler_info_14().EnumerateNonGCObjects)(self, ppEnum)
+    }
+    pub unsafe fn GetNonGCHeapBounds(
+        &self,
+        cObjectRanges: ULONG,
+        pcObjectRanges: *mut ULONG,
+        ranges: *mut COR_PRF_NONGC_HEAP_RANGE,
+    ) -> HRESULT {
+        (self.i_cor_profiler_info_14().GetNonGCHeapBounds)(
+            self,
+            cObjectRanges,
+            pcObjectRanges,
+            ranges,
+        )
+    }
+    pub unsafe fn EventPipeCreateProvider2(
+        &self,
+        providerName: *const WCHAR,
+        pCallback: Option<EventPipeProviderCallback>,
+        pProvider: *mut EVENTPIPE_PROVIDER,
+    ) -> HRESULT {
+        (self.i_cor_profiler_info_14().EventPipeCreateProvider2)(
+            self,
+            providerName,
+            pCallback,
+            pProvider,
+        )
+    }
 }
@@ -1,5 +1,5 @@
 #![allow(non_snake_case)]
-use crate::ffi::{IMethodMalloc, IUnknown, HRESULT, ULONG};
+use crate::ffi::{IMethodMalloc, IUnknown, LPVOID, ULONG};
 
 #[repr(C)]
 pub struct MethodMallocVtbl {
@@ -16,7 +16,7 @@ impl MethodMalloc {
     pub unsafe fn i_method_malloc(&self) -> &IMethodMalloc<Self> {
         &(*self.lpVtbl).IMethodMalloc
     }
-    pub unsafe fn Alloc(&self, cb: ULONG) -> HRESULT {
+    pub unsafe fn Alloc(&self, cb: ULONG) -> LPVOID {
         (self.i_method_malloc().Alloc)(self, cb)
     }
 }
@@ -0,0 +1,77 @@
+//! What keeps the DLL loaded: live COM objects and `IClassFactory::LockServer`.
+use crate::ffi::{HRESULT, S_FALSE, S_OK, ULONG};
+use log::error;
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+
+static OBJECTS: AtomicUsize = AtomicUsize::new(0);
+static LOCKS: AtomicUsize = AtomicUsize::new(0);
+
+/// The reference count of a COM object, which also counts the object as live
+/// until it is dropped.
+pub(crate) struct RefCount(AtomicU32);
+
+impl RefCount {
+    /// Starts out with the reference held by whoever created the object.
+    pub(crate) fn new() -> Self {
+        OBJECTS.fetch_add(1, Ordering::Relaxed);
+        RefCount(AtomicU32::new(1))
+    }
+
+    pub(crate) fn add_ref(&self) -> ULONG {
+        self.0.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// The references left, or `None` if there were none to release. The
+    /// object is freed by the caller once this reaches zero.
+    pub(crate) fn release(&self) -> Option<ULONG> {
+        // acquire for the last release, so freeing the object happens after
+        // every other thread is done with it
+        let result = self
+            .0
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+                count.checked_sub(1)
+            });
+        match result {
+            Ok(count) => Some(count - 1),
+            Err(_) => {
+                error!("released a COM object that has no references left");
+                None
+            }
+        }
+    }
+
+    pub(crate) fn get(&self) -> ULONG {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for RefCount {
+    fn drop(&mut self) {
+        OBJECTS.fetch_sub(1, Ordering::Release);
+    }
+}
+
+/// `IClassFactory::LockServer`.
+pub(crate) fn lock_server(lock: bool) {
+    if lock {
+        LOCKS.fetch_add(1, Ordering::Relaxed);
+    } else if LOCKS
+        .fetch_update(Ordering::Release, Ordering::Relaxed, |locks| {
+            locks.checked_sub(1)
+        })
+        .is_err()
+    {
+        error!("unlocked a server that isn't locked");
+    }
+}
+
+/// The body of `DllCanUnloadNow`, as exported by `#[clr_profiler]`: the DLL
+/// can be unloaded once no object it created is alive and no client holds a
+/// lock on it.
+pub fn can_unload_now() -> HRESULT {
+    if OBJECTS.load(Ordering::Acquire) == 0 && LOCKS.load(Ordering::Acquire) == 0 {
+        S_OK
+    } else {
+        S_FALSE
+    }
+}
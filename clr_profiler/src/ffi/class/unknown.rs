@@ -16,13 +16,13 @@ impl Unknown {
     pub unsafe fn i_unknown(&self) -> &IUnknown<Self> {
         &(*self.lpVtbl).IUnknown
     }
-    pub unsafe fn QueryInterface(&mut self, riid: REFIID, ppvObject: *mut *mut c_void) -> HRESULT {
+    pub unsafe fn QueryInterface(&self, riid: REFIID, ppvObject: *mut *mut c_void) -> HRESULT {
         (self.i_unknown().QueryInterface)(self, riid, ppvObject)
     }
-    pub unsafe fn AddRef(&mut self) -> ULONG {
+    pub unsafe fn AddRef(&self) -> ULONG {
         (self.i_unknown().AddRef)(self)
     }
-    pub unsafe fn Release(&mut self) -> ULONG {
+    pub unsafe fn Release(&self) -> ULONG {
         (self.i_unknown().Release)(self)
     }
 }
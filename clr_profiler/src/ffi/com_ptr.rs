@@ -0,0 +1,102 @@
+use crate::ffi::{
+    CorProfilerAssemblyReferenceProvider, CorProfilerFunctionControl, CorProfilerFunctionEnum,
+    CorProfilerInfo, CorProfilerMethodEnum, CorProfilerModuleEnum, CorProfilerObjectEnum,
+    CorProfilerThreadEnum, MetaDataAssemblyEmit, MetaDataAssemblyImport, MetaDataEmit,
+    MetaDataImport, MethodMalloc, Unknown,
+};
+use std::{fmt, ops::Deref, ptr::NonNull};
+
+/// A COM interface, whose vtable starts with `IUnknown`.
+///
+/// # Safety
+///
+/// The type must be `#[repr(C)]` with a vtable pointer as its only field, and
+/// the vtable must start with the three `IUnknown` methods.
+pub unsafe trait ComInterface {}
+
+unsafe impl ComInterface for Unknown {}
+unsafe impl ComInterface for CorProfilerInfo {}
+unsafe impl ComInterface for CorProfilerAssemblyReferenceProvider {}
+unsafe impl ComInterface for CorProfilerFunctionControl {}
+unsafe impl ComInterface for CorProfilerFunctionEnum {}
+unsafe impl ComInterface for CorProfilerMethodEnum {}
+unsafe impl ComInterface for CorProfilerModuleEnum {}
+unsafe impl ComInterface for CorProfilerObjectEnum {}
+unsafe impl ComInterface for CorProfilerThreadEnum {}
+unsafe impl ComInterface for MetaDataAssemblyEmit {}
+unsafe impl ComInterface for MetaDataAssemblyImport {}
+unsafe impl ComInterface for MetaDataEmit {}
+unsafe impl ComInterface for MetaDataImport {}
+unsafe impl ComInterface for MethodMalloc {}
+
+/// Owns one reference to a COM object: cloning calls `AddRef` and dropping
+/// calls `Release`, so early returns can't leak a reference.
+pub struct ComPtr<T: ComInterface> {
+    ptr: NonNull<T>,
+}
+
+impl<T: ComInterface> ComPtr<T> {
+    /// Takes over a reference the runtime handed out through an out
+    /// parameter, which it already counted. Returns `None` for null.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be null or point to a live object implementing `T`.
+    pub unsafe fn from_raw(ptr: *mut T) -> Option<Self> {
+        NonNull::new(ptr).map(|ptr| ComPtr { ptr })
+    }
+
+    /// Takes a new reference to an object that is only borrowed, like an
+    /// interface passed into a callback.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be null or point to a live object implementing `T`.
+    pub unsafe fn from_borrowed(ptr: *mut T) -> Option<Self> {
+        let com_ptr = Self::from_raw(ptr)?;
+        com_ptr.unknown().AddRef();
+        Some(com_ptr)
+    }
+
+    pub fn as_ptr(&self) -> *mut T {
+        self.ptr.as_ptr()
+    }
+
+    /// Gives up ownership of the reference without releasing it.
+    pub fn into_raw(self) -> *mut T {
+        let ptr = self.ptr.as_ptr();
+        std::mem::forget(self);
+        ptr
+    }
+
+    unsafe fn unknown(&self) -> &Unknown {
+        &*(self.ptr.as_ptr() as *const Unknown)
+    }
+}
+
+impl<T: ComInterface> Deref for ComPtr<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T: ComInterface> Clone for ComPtr<T> {
+    fn clone(&self) -> Self {
+        unsafe { self.unknown().AddRef() };
+        ComPtr { ptr: self.ptr }
+    }
+}
+
+impl<T: ComInterface> Drop for ComPtr<T> {
+    fn drop(&mut self) {
+        unsafe { self.unknown().Release() };
+    }
+}
+
+impl<T: ComInterface> fmt::Debug for ComPtr<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ComPtr").field(&self.ptr).finish()
+    }
+}
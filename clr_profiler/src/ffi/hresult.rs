@@ -1,14 +1,287 @@
 #![allow(overflowing_literals)]
 use crate::ffi::c_long;
+use std::fmt;
 
 pub type HRESULT = c_long;
 
-pub const S_OK: HRESULT = 0;
+/// Defines each code as a raw `HRESULT` constant for the FFI boundary, as an
+/// `HResult` associated constant for matching on errors, and adds it to the
+/// catalogue `HResult` uses to name codes.
+macro_rules! hresults {
+    ($($name:ident = $value:literal, $description:literal;)*) => {
+        $(pub const $name: HRESULT = $value;)*
 
-pub const E_NOINTERFACE: HRESULT = 0x8000_4002;
-pub const E_OUTOFMEMORY: HRESULT = 0x8007_000E;
-pub const CLASS_E_NOAGGREGATION: HRESULT = 0x8004_0110;
-pub const E_FAIL: HRESULT = 0x8000_4005;
-pub const COR_E_INVALIDPROGRAM: HRESULT = 0x8013_153A;
-pub const COR_E_INVALIDOPERATION: HRESULT = 0x8013_1509;
-pub const COR_E_INDEXOUTOFRANGE: HRESULT = 0x8;
+        impl HResult {
+            $(pub const $name: HResult = HResult($value);)*
+        }
+
+        const CATALOGUE: &[(HRESULT, &str, &str)] = &[$(($value, stringify!($name), $description),)*];
+    };
+}
+
+hresults! {
+    S_OK = 0, "success";
+    S_FALSE = 1, "success, with a negative or partial result";
+    CLDB_S_TRUNCATION = 0x0013_1106, "success, but the data was truncated";
+
+    E_NOTIMPL = 0x8000_4001, "not implemented";
+    E_NOINTERFACE = 0x8000_4002, "interface not supported";
+    E_POINTER = 0x8000_4003, "invalid pointer";
+    E_ABORT = 0x8000_4004, "operation aborted";
+    E_FAIL = 0x8000_4005, "unspecified failure";
+    E_UNEXPECTED = 0x8000_FFFF, "unexpected failure";
+    E_ACCESSDENIED = 0x8007_0005, "access denied";
+    E_HANDLE = 0x8007_0006, "invalid handle";
+    E_OUTOFMEMORY = 0x8007_000E, "out of memory";
+    E_INVALIDARG = 0x8007_0057, "invalid argument";
+    CLASS_E_NOAGGREGATION = 0x8004_0110, "class doesn't support aggregation";
+    CLASS_E_CLASSNOTAVAILABLE = 0x8004_0111, "class factory can't supply the requested class";
+
+    COR_E_ARGUMENTOUTOFRANGE = 0x8013_1502, "argument out of range";
+    COR_E_EXECUTIONENGINE = 0x8013_1506, "internal error in the runtime";
+    COR_E_INDEXOUTOFRANGE = 0x8013_1508, "index out of range";
+    COR_E_INVALIDOPERATION = 0x8013_1509, "operation not valid in the current state";
+    COR_E_SECURITY = 0x8013_150A, "security error";
+    COR_E_MISSINGFIELD = 0x8013_1511, "field not found";
+    COR_E_MISSINGMEMBER = 0x8013_1512, "member not found";
+    COR_E_MISSINGMETHOD = 0x8013_1513, "method not found";
+    COR_E_NOTSUPPORTED = 0x8013_1515, "operation not supported";
+    COR_E_OVERFLOW = 0x8013_1516, "arithmetic overflow";
+    COR_E_TYPELOAD = 0x8013_1522, "type failed to load";
+    COR_E_INVALIDPROGRAM = 0x8013_153A, "invalid IL or metadata";
+    COR_E_BADIMAGEFORMAT = 0x8007_000B, "image has an invalid format";
+
+    CLDB_E_FILE_BADREAD = 0x8013_1100, "error reading the metadata file";
+    CLDB_E_FILE_BADWRITE = 0x8013_1101, "error writing the metadata file";
+    CLDB_E_FILE_OLDVER = 0x8013_1107, "metadata version isn't supported";
+    CLDB_E_FILE_CORRUPT = 0x8013_110E, "metadata file is corrupt";
+    CLDB_E_BADUPDATEMODE = 0x8013_1110, "metadata opened with an incompatible update mode";
+    CLDB_E_INDEX_NOTFOUND = 0x8013_1124, "metadata index not found";
+    CLDB_E_RECORD_NOTFOUND = 0x8013_1130, "metadata record not found";
+    CLDB_E_RECORD_OUTOFORDER = 0x8013_1135, "metadata records are out of order";
+    CLDB_E_TOO_BIG = 0x8013_1154, "metadata is too large";
+
+    META_E_BADMETADATA = 0x8013_118A, "merged metadata is inconsistent";
+    META_E_BAD_SIGNATURE = 0x8013_1192, "bad binary signature";
+    META_E_BAD_INPUT_PARAMETER = 0x8013_1193, "bad input parameter";
+    META_E_CANNOTRESOLVETYPEREF = 0x8013_1196, "can't resolve the TypeRef";
+    META_E_STRINGSPACE_FULL = 0x8013_1198, "no room left in the string heap";
+    META_E_CA_INVALID_TARGET = 0x8013_11C0, "custom attribute isn't valid on this target";
+    META_E_CA_INVALID_VALUE = 0x8013_11C1, "custom attribute has an invalid value";
+    META_E_CA_INVALID_BLOB = 0x8013_11C2, "custom attribute blob is malformed";
+    META_E_CA_REPEATED_ARG = 0x8013_11C3, "custom attribute argument is repeated";
+    META_E_CA_UNKNOWN_ARGUMENT = 0x8013_11C4, "custom attribute argument is unknown";
+
+    CORPROF_E_FUNCTION_NOT_COMPILED = 0x8013_1350, "function has no native code yet";
+    CORPROF_E_DATAINCOMPLETE = 0x8013_1351, "the ID isn't fully loaded or defined yet";
+    CORPROF_E_FUNCTION_NOT_IL = 0x8013_1354, "function has no IL";
+    CORPROF_E_NOT_MANAGED_THREAD = 0x8013_1355, "thread has never run managed code";
+    CORPROF_E_CALL_ONLY_FROM_INIT = 0x8013_1356, "can only be called from Initialize";
+    CORPROF_E_NOT_YET_AVAILABLE = 0x8013_135B, "not available yet in this stage of startup";
+    CORPROF_E_TYPE_IS_PARAMETERIZED = 0x8013_135C, "type is an array, pointer, byref or function pointer";
+    CORPROF_E_FUNCTION_IS_PARAMETERIZED = 0x8013_135D, "function is generic, use the *2 API";
+    CORPROF_E_STACKSNAPSHOT_INVALID_TGT_THREAD = 0x8013_135E, "can't walk the stack of the target thread";
+    CORPROF_E_STACKSNAPSHOT_UNMANAGED_CTX = 0x8013_135F, "seed context is in unmanaged code";
+    CORPROF_E_STACKSNAPSHOT_UNSAFE = 0x8013_1360, "stack walk is unsafe at this point";
+    CORPROF_E_STACKSNAPSHOT_ABORTED = 0x8013_1361, "stack walk aborted by the callback";
+    CORPROF_E_LITERALS_HAVE_NO_ADDRESS = 0x8013_1362, "literal statics have no address";
+    CORPROF_E_UNSUPPORTED_CALL_SEQUENCE = 0x8013_1363, "call isn't allowed from this callback";
+    CORPROF_E_ASYNCHRONOUS_UNSAFE = 0x8013_1364, "call isn't safe asynchronously";
+    CORPROF_E_CLASSID_IS_ARRAY = 0x8013_1365, "class is an array";
+    CORPROF_E_CLASSID_IS_COMPOSITE = 0x8013_1366, "class is a composite type";
+    CORPROF_E_PROFILER_DETACHING = 0x8013_1367, "profiler is detaching";
+    CORPROF_E_PROFILER_NOT_ATTACHABLE = 0x8013_1368, "profiler doesn't support attaching";
+    CORPROF_E_UNRECOGNIZED_PIPE_MSG_FORMAT = 0x8013_1369, "unrecognized attach message";
+    CORPROF_E_PROFILER_ALREADY_ACTIVE = 0x8013_136A, "a profiler is already active";
+    CORPROF_E_PROFILEE_INCOMPATIBLE_WITH_TRIGGER = 0x8013_136B, "target process isn't compatible with the attach trigger";
+    CORPROF_E_IPC_FAILED = 0x8013_136C, "attach communication failed";
+    CORPROF_E_PROFILEE_PROCESS_NOT_FOUND = 0x8013_136D, "target process not found";
+    CORPROF_E_CALLBACK3_REQUIRED = 0x8013_136E, "profiler must implement ICorProfilerCallback3";
+    CORPROF_E_UNSUPPORTED_FOR_ATTACHING_PROFILER = 0x8013_136F, "not supported for attaching profilers";
+    CORPROF_E_IRREVERSIBLE_INSTRUMENTATION_PRESENT = 0x8013_1370, "profiler instrumented code and can't detach";
+    CORPROF_E_RUNTIME_UNINITIALIZED = 0x8013_1371, "runtime isn't initialized yet";
+    CORPROF_E_IMMUTABLE_FLAGS_SET = 0x8013_1372, "event mask flags can only be set in Initialize";
+    CORPROF_E_PROFILER_NOT_YET_INITIALIZED = 0x8013_1373, "profiler hasn't finished initializing";
+    CORPROF_E_INCONSISTENT_WITH_FLAGS = 0x8013_1374, "call is inconsistent with the event mask";
+    CORPROF_E_PROFILER_CANCEL_ACTIVATION = 0x8013_1375, "profiler cancelled its activation";
+    CORPROF_E_CONCURRENT_GC_NOT_PROFILABLE = 0x8013_1376, "concurrent GC is enabled";
+    CORPROF_E_DEBUGGING_DISABLED = 0x8013_1378, "debugging support is disabled";
+    CORPROF_E_TIMEOUT_WAITING_FOR_CONCURRENT_GC = 0x8013_1379, "timed out waiting for concurrent GC";
+    CORPROF_E_MODULE_IS_DYNAMIC = 0x8013_137A, "module is dynamic";
+    CORPROF_E_CALLBACK4_REQUIRED = 0x8013_137B, "profiler must implement ICorProfilerCallback4";
+    CORPROF_E_REJIT_NOT_ENABLED = 0x8013_137C, "ReJIT isn't enabled in the event mask";
+    CORPROF_E_FUNCTION_IS_COLLECTIBLE = 0x8013_137E, "function is in a collectible assembly";
+    CORPROF_E_CALLBACK6_REQUIRED = 0x8013_1380, "profiler must implement ICorProfilerCallback6";
+    CORPROF_E_CALLBACK7_REQUIRED = 0x8013_1382, "profiler must implement ICorProfilerCallback7";
+    CORPROF_E_REJIT_INLINING_DISABLED = 0x8013_1383, "ReJIT with inlining is disabled";
+}
+
+const FACILITIES: &[(u16, &str)] = &[
+    (0, "FACILITY_NULL"),
+    (1, "FACILITY_RPC"),
+    (2, "FACILITY_DISPATCH"),
+    (3, "FACILITY_STORAGE"),
+    (4, "FACILITY_ITF"),
+    (7, "FACILITY_WIN32"),
+    (8, "FACILITY_WINDOWS"),
+    (0x13, "FACILITY_URT"),
+];
+
+const WIN32_ERRORS: &[(u16, &str, &str)] = &[
+    (2, "ERROR_FILE_NOT_FOUND", "file not found"),
+    (3, "ERROR_PATH_NOT_FOUND", "path not found"),
+    (8, "ERROR_NOT_ENOUGH_MEMORY", "not enough memory"),
+    (
+        32,
+        "ERROR_SHARING_VIOLATION",
+        "file is in use by another process",
+    ),
+    (122, "ERROR_INSUFFICIENT_BUFFER", "buffer is too small"),
+    (126, "ERROR_MOD_NOT_FOUND", "module not found"),
+    (183, "ERROR_ALREADY_EXISTS", "already exists"),
+    (1460, "ERROR_TIMEOUT", "timed out"),
+];
+
+/// An `HRESULT` returned by, or handed to, the runtime. Displays as the
+/// symbolic name of known codes, e.g. `CORPROF_E_DATAINCOMPLETE (0x80131351)`,
+/// and with `{:#}` also their description.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct HResult(pub HRESULT);
+
+impl HResult {
+    const FACILITY_ITF: u16 = 4;
+    const FACILITY_WIN32: u16 = 7;
+    const CUSTOMER: u32 = 0x2000_0000;
+
+    /// `HRESULT_FROM_WIN32`
+    pub const fn from_win32(error: u32) -> Self {
+        if error as HRESULT <= 0 {
+            HResult(error as HRESULT)
+        } else {
+            HResult(
+                ((error & 0xffff) | (Self::FACILITY_WIN32 as u32) << 16 | 0x8000_0000) as HRESULT,
+            )
+        }
+    }
+
+    /// Returned by methods of an `ICorProfilerInfo` version newer than the
+    /// runtime implements. The code has the customer bit set, so it can't be
+    /// mistaken for one returned by the runtime.
+    pub const fn unsupported_interface(level: u32) -> Self {
+        HResult(
+            ((level & 0xffff) | (Self::FACILITY_ITF as u32) << 16 | Self::CUSTOMER | 0x8000_0000)
+                as HRESULT,
+        )
+    }
+
+    /// The `ICorProfilerInfo` version a call needed, if it failed with
+    /// [`HResult::unsupported_interface`].
+    pub const fn required_interface(self) -> Option<u32> {
+        let code = self.0 as u32;
+        if code & 0xffff_0000 == Self::unsupported_interface(0).0 as u32 {
+            Some(code & 0xffff)
+        } else {
+            None
+        }
+    }
+
+    pub const fn code(self) -> HRESULT {
+        self.0
+    }
+
+    pub const fn is_success(self) -> bool {
+        self.0 >= 0
+    }
+
+    pub const fn facility(self) -> u16 {
+        ((self.0 as u32 >> 16) & 0x1fff) as u16
+    }
+
+    pub fn facility_name(self) -> Option<&'static str> {
+        FACILITIES
+            .iter()
+            .find(|(facility, _)| *facility == self.facility())
+            .map(|(_, name)| *name)
+    }
+
+    fn lookup(self) -> Option<(&'static str, &'static str)> {
+        if let Some((_, name, description)) = CATALOGUE.iter().find(|(hr, ..)| *hr == self.0) {
+            return Some((name, description));
+        }
+        if self.facility() == Self::FACILITY_WIN32 && !self.is_success() {
+            let code = self.0 as u32 as u16;
+            return WIN32_ERRORS
+                .iter()
+                .find(|(error, ..)| *error == code)
+                .map(|(_, name, description)| (*name, *description));
+        }
+        None
+    }
+
+    /// The symbolic name of a known code, e.g. `CLDB_E_RECORD_NOTFOUND`.
+    /// Win32 errors are named by their `ERROR_*` code.
+    pub fn name(self) -> Option<&'static str> {
+        self.lookup().map(|(name, _)| name)
+    }
+
+    pub fn description(self) -> Option<&'static str> {
+        self.lookup().map(|(_, description)| description)
+    }
+}
+
+impl From<HRESULT> for HResult {
+    fn from(hr: HRESULT) -> Self {
+        HResult(hr)
+    }
+}
+
+impl From<HResult> for HRESULT {
+    fn from(hr: HResult) -> Self {
+        hr.0
+    }
+}
+
+impl fmt::Display for HResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let code = self.0 as u32;
+        match self.lookup() {
+            Some((name, description)) if f.alternate() => {
+                write!(f, "{name} ({code:#010x}): {description}")
+            }
+            Some((name, _)) => write!(f, "{name} ({code:#010x})"),
+            None if self.required_interface().is_some() => write!(
+                f,
+                "ICorProfilerInfo{} required ({code:#010x})",
+                code & 0xffff
+            ),
+            None if self.facility() == Self::FACILITY_WIN32 => {
+                write!(f, "HRESULT_FROM_WIN32({}) ({code:#010x})", code & 0xffff)
+            }
+            None => write!(f, "{code:#010x}"),
+        }
+    }
+}
+
+impl fmt::Debug for HResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "HResult({name})"),
+            None => write!(f, "HResult({:#010x})", self.0 as u32),
+        }
+    }
+}
+
+impl fmt::LowerHex for HResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&(self.0 as u32), f)
+    }
+}
+
+impl fmt::UpperHex for HResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::UpperHex::fmt(&(self.0 as u32), f)
+    }
+}
+
+impl std::error::Error for HResult {}
@@ -0,0 +1,35 @@
+#![allow(non_snake_case)]
+use crate::ffi::{
+    ThreadID, DWORD, EVENTPIPE_PROVIDER, GUID, HRESULT, LPCBYTE, LPCGUID, UINT_PTR, ULONG,
+};
+
+#[repr(C)]
+pub struct ICorProfilerCallback10<T> {
+    pub EventPipeEventDelivered: unsafe extern "system" fn(
+        this: &T,
+        provider: EVENTPIPE_PROVIDER,
+        eventId: DWORD,
+        eventVersion: DWORD,
+        cbMetadataBlob: ULONG,
+        metadataBlob: LPCBYTE,
+        cbEventData: ULONG,
+        eventData: LPCBYTE,
+        pActivityId: LPCGUID,
+        pRelatedActivityId: LPCGUID,
+        eventThread: ThreadID,
+        numStackFrames: ULONG,
+        stackFrames: *const UINT_PTR,
+    ) -> HRESULT,
+    pub EventPipeProviderCreated:
+        unsafe extern "system" fn(this: &T, provider: EVENTPIPE_PROVIDER) -> HRESULT,
+}
+
+impl ICorProfilerCallback10<()> {
+    // CEC5B60E-C69C-495F-87F6-84D28EE16FFB
+    pub const IID: GUID = GUID {
+        data1: 0xCEC5B60E,
+        data2: 0xC69C,
+        data3: 0x495F,
+        data4: [0x87, 0xF6, 0x84, 0xD2, 0x8E, 0xE1, 0x6F, 0xFB],
+    };
+}
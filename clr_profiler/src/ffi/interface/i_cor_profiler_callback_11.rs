@@ -0,0 +1,18 @@
+#![allow(non_snake_case)]
+use crate::ffi::{BOOL, GUID, HRESULT};
+
+#[repr(C)]
+pub struct ICorProfilerCallback11<T> {
+    pub LoadAsNotificationOnly:
+        unsafe extern "system" fn(this: &T, pbNotificationOnly: *mut BOOL) -> HRESULT,
+}
+
+impl ICorProfilerCallback11<()> {
+    // 42350846-AAED-47F7-B128-FD0C98881CDE
+    pub const IID: GUID = GUID {
+        data1: 0x42350846,
+        data2: 0xAAED,
+        data3: 0x47F7,
+        data4: [0xB1, 0x28, 0xFD, 0x0C, 0x98, 0x88, 0x1C, 0xDE],
+    };
+}
@@ -7,31 +7,28 @@ use crate::ffi::{
 #[repr(C)]
 pub struct ICorProfilerCallback2<T> {
     pub ThreadNameChanged: unsafe extern "system" fn(
-        this: &mut T,
+        this: &T,
         threadId: ThreadID,
         cchName: ULONG,
         name: *const WCHAR,
     ) -> HRESULT,
     pub GarbageCollectionStarted: unsafe extern "system" fn(
-        this: &mut T,
+        this: &T,
         cGenerations: int,
         generationCollected: *const BOOL,
         reason: COR_PRF_GC_REASON,
     ) -> HRESULT,
     pub SurvivingReferences: unsafe extern "system" fn(
-        this: &mut T,
+        this: &T,
         cSurvivingObjectIDRanges: ULONG,
         objectIDRangeStart: *const ObjectID,
         cObjectIDRangeLength: *const ULONG,
     ) -> HRESULT,
-    pub GarbageCollectionFinished: unsafe extern "system" fn(this: &mut T) -> HRESULT,
-    pub FinalizeableObjectQueued: unsafe extern "system" fn(
-        this: &mut T,
-        finalizerFlags: DWORD,
-        objectID: ObjectID,
-    ) -> HRESULT,
+    pub GarbageCollectionFinished: unsafe extern "system" fn(this: &T) -> HRESULT,
+    pub FinalizeableObjectQueued:
+        unsafe extern "system" fn(this: &T, finalizerFlags: DWORD, objectID: ObjectID) -> HRESULT,
     pub RootReferences2: unsafe extern "system" fn(
-        this: &mut T,
+        this: &T,
         cRootRefs: ULONG,
         rootRefIds: *const ObjectID,
         rootKinds: *const COR_PRF_GC_ROOT_KIND,
@@ -39,11 +36,11 @@ pub struct ICorProfilerCallback2<T> {
         rootIds: *const UINT_PTR,
     ) -> HRESULT,
     pub HandleCreated: unsafe extern "system" fn(
-        this: &mut T,
+        this: &T,
         handleId: GCHandleID,
         initialObjectId: ObjectID,
     ) -> HRESULT,
-    pub HandleDestroyed: unsafe extern "system" fn(this: &mut T, handleId: GCHandleID) -> HRESULT,
+    pub HandleDestroyed: unsafe extern "system" fn(this: &T, handleId: GCHandleID) -> HRESULT,
 }
 
 impl ICorProfilerCallback2<()> {
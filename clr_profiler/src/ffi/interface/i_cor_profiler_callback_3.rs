@@ -5,13 +5,13 @@ use std::ffi::c_void;
 #[repr(C)]
 pub struct ICorProfilerCallback3<T> {
     pub InitializeForAttach: unsafe extern "system" fn(
-        this: &mut T,
+        this: &T,
         pCorProfilerInfoUnk: *const CorProfilerInfo,
         pvClientData: *const c_void,
         cbClientData: UINT,
     ) -> HRESULT,
-    pub ProfilerAttachComplete: unsafe extern "system" fn(this: &mut T) -> HRESULT,
-    pub ProfilerDetachSucceeded: unsafe extern "system" fn(this: &mut T) -> HRESULT,
+    pub ProfilerAttachComplete: unsafe extern "system" fn(this: &T) -> HRESULT,
+    pub ProfilerDetachSucceeded: unsafe extern "system" fn(this: &T) -> HRESULT,
 }
 
 impl ICorProfilerCallback3<()> {
@@ -7,40 +7,40 @@ use crate::ffi::{
 #[repr(C)]
 pub struct ICorProfilerCallback4<T> {
     pub ReJITCompilationStarted: unsafe extern "system" fn(
-        this: &mut T,
+        this: &T,
         functionId: FunctionID,
         rejitId: ReJITID,
         fIsSafeToBlock: BOOL,
     ) -> HRESULT,
     pub GetReJITParameters: unsafe extern "system" fn(
-        this: &mut T,
+        this: &T,
         moduleId: ModuleID,
         methodId: mdMethodDef,
         pFunctionControl: *const CorProfilerFunctionControl,
     ) -> HRESULT,
     pub ReJITCompilationFinished: unsafe extern "system" fn(
-        this: &mut T,
+        this: &T,
         functionId: FunctionID,
         rejitId: ReJITID,
         hrStatus: HRESULT,
         fIsSafeToBlock: BOOL,
     ) -> HRESULT,
     pub ReJITError: unsafe extern "system" fn(
-        this: &mut T,
+        this: &T,
         moduleId: ModuleID,
         methodId: mdMethodDef,
         functionId: FunctionID,
         hrStatus: HRESULT,
     ) -> HRESULT,
     pub MovedReferences2: unsafe extern "system" fn(
-        this: &mut T,
+        this: &T,
         cMovedObjectIDRanges: ULONG,
         oldObjectIDRangeStart: *const ObjectID,
         newObjectIDRangeStart: *const ObjectID,
         cObjectIDRangeLength: *const SIZE_T,
     ) -> HRESULT,
     pub SurvivingReferences2: unsafe extern "system" fn(
-        this: &mut T,
+        this: &T,
         cSurvivingObjectIDRanges: ULONG,
         objectIDRangeStart: *const ObjectID,
         cObjectIDRangeLength: *const SIZE_T,
@@ -4,7 +4,7 @@ use crate::ffi::{GCHandleID, ObjectID, GUID, HRESULT, ULONG};
 #[repr(C)]
 pub struct ICorProfilerCallback5<T> {
     pub ConditionalWeakTableElementReferences: unsafe extern "system" fn(
-        this: &mut T,
+        this: &T,
         cRootRefs: ULONG,
         keyRefIds: *const ObjectID,
         valueRefIds: *const ObjectID,
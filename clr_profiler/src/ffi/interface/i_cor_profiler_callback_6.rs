@@ -4,7 +4,7 @@ use crate::ffi::{CorProfilerAssemblyReferenceProvider, GUID, HRESULT, WCHAR};
 #[repr(C)]
 pub struct ICorProfilerCallback6<T> {
     pub GetAssemblyReferences: unsafe extern "system" fn(
-        this: &mut T,
+        this: &T,
         wszAssemblyPath: *const WCHAR,
         pAsmRefProvider: *const CorProfilerAssemblyReferenceProvider,
     ) -> HRESULT,
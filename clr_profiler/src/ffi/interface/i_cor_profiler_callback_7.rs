@@ -4,7 +4,7 @@ use crate::ffi::{ModuleID, GUID, HRESULT};
 #[repr(C)]
 pub struct ICorProfilerCallback7<T> {
     pub ModuleInMemorySymbolsUpdated:
-        unsafe extern "system" fn(this: &mut T, moduleId: ModuleID) -> HRESULT,
+        unsafe extern "system" fn(this: &T, moduleId: ModuleID) -> HRESULT,
 }
 
 impl ICorProfilerCallback7<()> {
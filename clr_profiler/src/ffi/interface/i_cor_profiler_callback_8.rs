@@ -4,14 +4,14 @@ use crate::ffi::{FunctionID, BOOL, GUID, HRESULT, LPCBYTE, ULONG};
 #[repr(C)]
 pub struct ICorProfilerCallback8<T> {
     pub DynamicMethodJITCompilationStarted: unsafe extern "system" fn(
-        this: &mut T,
+        this: &T,
         functionId: FunctionID,
         fIsSafeToBlock: BOOL,
         pILHeader: LPCBYTE,
         cbILHeader: ULONG,
     ) -> HRESULT,
     pub DynamicMethodJITCompilationFinished: unsafe extern "system" fn(
-        this: &mut T,
+        this: &T,
         functionId: FunctionID,
         hrStatus: HRESULT,
         fIsSafeToBlock: BOOL,
@@ -0,0 +1,25 @@
+#![allow(non_snake_case)]
+use crate::ffi::{GUID, HRESULT, ULONG, WCHAR};
+
+#[repr(C)]
+pub struct ICorProfilerInfo11<T> {
+    pub GetEnvironmentVariable: unsafe extern "system" fn(
+        this: &T,
+        szName: *const WCHAR,
+        cchValue: ULONG,
+        pcchValue: *mut ULONG,
+        szValue: *mut WCHAR,
+    ) -> HRESULT,
+    pub SetEnvironmentVariable:
+        unsafe extern "system" fn(this: &T, szName: *const WCHAR, szValue: *const WCHAR) -> HRESULT,
+}
+
+impl ICorProfilerInfo11<()> {
+    // 06398876-8987-4154-B621-40A00D6E4D04
+    pub const IID: GUID = GUID {
+        data1: 0x06398876,
+        data2: 0x8987,
+        data3: 0x4154,
+        data4: [0xB6, 0x21, 0x40, 0xA0, 0x0D, 0x6E, 0x4D, 0x04],
+    };
+}
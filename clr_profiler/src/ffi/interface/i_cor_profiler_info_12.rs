@@ -0,0 +1,68 @@
+#![allow(non_snake_case)]
+use crate::ffi::{
+    BOOL, COR_PRF_EVENTPIPE_PARAM_DESC, COR_PRF_EVENTPIPE_PROVIDER_CONFIG, COR_PRF_EVENT_DATA,
+    EVENTPIPE_EVENT, EVENTPIPE_PROVIDER, EVENTPIPE_SESSION, GUID, HRESULT, LPCGUID, UINT32, UINT64,
+    UINT8, ULONG, WCHAR,
+};
+
+#[repr(C)]
+pub struct ICorProfilerInfo12<T> {
+    pub EventPipeStartSession: unsafe extern "system" fn(
+        this: &T,
+        cProviderConfigs: UINT32,
+        pProviderConfigs: *const COR_PRF_EVENTPIPE_PROVIDER_CONFIG,
+        requestRundown: BOOL,
+        pSession: *mut EVENTPIPE_SESSION,
+    ) -> HRESULT,
+    pub EventPipeAddProviderToSession: unsafe extern "system" fn(
+        this: &T,
+        session: EVENTPIPE_SESSION,
+        providerConfig: COR_PRF_EVENTPIPE_PROVIDER_CONFIG,
+    ) -> HRESULT,
+    pub EventPipeStopSession:
+        unsafe extern "system" fn(this: &T, session: EVENTPIPE_SESSION) -> HRESULT,
+    pub EventPipeCreateProvider: unsafe extern "system" fn(
+        this: &T,
+        providerName: *const WCHAR,
+        pProvider: *mut EVENTPIPE_PROVIDER,
+    ) -> HRESULT,
+    pub EventPipeGetProviderInfo: unsafe extern "system" fn(
+        this: &T,
+        provider: EVENTPIPE_PROVIDER,
+        cchName: ULONG,
+        pcchName: *mut ULONG,
+        providerName: *mut WCHAR,
+    ) -> HRESULT,
+    pub EventPipeDefineEvent: unsafe extern "system" fn(
+        this: &T,
+        provider: EVENTPIPE_PROVIDER,
+        eventName: *const WCHAR,
+        eventID: UINT32,
+        keywords: UINT64,
+        eventVersion: UINT32,
+        level: UINT32,
+        opcode: UINT8,
+        needStack: BOOL,
+        cParamDescs: UINT32,
+        pParamDescs: *const COR_PRF_EVENTPIPE_PARAM_DESC,
+        pEvent: *mut EVENTPIPE_EVENT,
+    ) -> HRESULT,
+    pub EventPipeWriteEvent: unsafe extern "system" fn(
+        this: &T,
+        event: EVENTPIPE_EVENT,
+        cData: UINT32,
+        data: *const COR_PRF_EVENT_DATA,
+        pActivityId: LPCGUID,
+        pRelatedActivityId: LPCGUID,
+    ) -> HRESULT,
+}
+
+impl ICorProfilerInfo12<()> {
+    // 27B24CCD-1CB1-47C5-96EE-98190DC30959
+    pub const IID: GUID = GUID {
+        data1: 0x27B24CCD,
+        data2: 0x1CB1,
+        data3: 0x47C5,
+        data4: [0x96, 0xEE, 0x98, 0x19, 0x0D, 0xC3, 0x09, 0x59],
+    };
+}
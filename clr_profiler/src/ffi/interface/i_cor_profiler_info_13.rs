@@ -0,0 +1,28 @@
+#![allow(non_snake_case)]
+use crate::ffi::{ObjectHandleID, ObjectID, COR_PRF_HANDLE_TYPE, GUID, HRESULT};
+
+#[repr(C)]
+pub struct ICorProfilerInfo13<T> {
+    pub CreateHandle: unsafe extern "system" fn(
+        this: &T,
+        object: ObjectID,
+        r#type: COR_PRF_HANDLE_TYPE,
+        pHandle: *mut ObjectHandleID,
+    ) -> HRESULT,
+    pub DestroyHandle: unsafe extern "system" fn(this: &T, handle: ObjectHandleID) -> HRESULT,
+    pub GetObjectIDFromHandle: unsafe extern "system" fn(
+        this: &T,
+        handle: ObjectHandleID,
+        pObject: *mut ObjectID,
+    ) -> HRESULT,
+}
+
+impl ICorProfilerInfo13<()> {
+    // 6E6C7EE2-0701-4EC2-9D29-2E8733B66934
+    pub const IID: GUID = GUID {
+        data1: 0x6E6C7EE2,
+        data2: 0x0701,
+        data3: 0x4EC2,
+        data4: [0x9D, 0x29, 0x2E, 0x87, 0x33, 0xB6, 0x69, 0x34],
+    };
+}
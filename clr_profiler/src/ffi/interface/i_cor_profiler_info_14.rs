@@ -0,0 +1,33 @@
+#![allow(non_snake_case)]
+use crate::ffi::{
+    CorProfilerObjectEnum, EventPipeProviderCallback, COR_PRF_NONGC_HEAP_RANGE, EVENTPIPE_PROVIDER,
+    GUID, HRESULT, ULONG, WCHAR,
+};
+
+#[repr(C)]
+pub struct ICorProfilerInfo14<T> {
+    pub EnumerateNonGCObjects:
+        unsafe extern "system" fn(this: &T, ppEnum: *mut *mut CorProfilerObjectEnum) -> HRESULT,
+    pub GetNonGCHeapBounds: unsafe extern "system" fn(
+        this: &T,
+        cObjectRanges: ULONG,
+        pcObjectRanges: *mut ULONG,
+        ranges: *mut COR_PRF_NONGC_HEAP_RANGE,
+    ) -> HRESULT,
+    pub EventPipeCreateProvider2: unsafe extern "system" fn(
+        this: &T,
+        providerName: *const WCHAR,
+        pCallback: Option<EventPipeProviderCallback>,
+        pProvider: *mut EVENTPIPE_PROVIDER,
+    ) -> HRESULT,
+}
+
+impl ICorProfilerInfo14<()> {
+    // F460E352-D76D-4FE9-835F-F6AF9D6E862D
+    pub const IID: GUID = GUID {
+        data1: 0xF460E352,
+        data2: 0xD76D,
+        data3: 0x4FE9,
+        data4: [0x83, 0x5F, 0xF6, 0xAF, 0x9D, 0x6E, 0x86, 0x2D],
+    };
+}
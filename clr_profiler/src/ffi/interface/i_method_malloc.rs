@@ -1,9 +1,9 @@
 #![allow(non_snake_case)]
-use crate::ffi::{GUID, HRESULT, ULONG};
+use crate::ffi::{GUID, LPVOID, ULONG};
 
 #[repr(C)]
 pub struct IMethodMalloc<T> {
-    pub Alloc: unsafe extern "system" fn(this: &T, cb: ULONG) -> HRESULT,
+    pub Alloc: unsafe extern "system" fn(this: &T, cb: ULONG) -> LPVOID,
 }
 
 impl IMethodMalloc<()> {
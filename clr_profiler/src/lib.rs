@@ -2,14 +2,30 @@
 extern crate bitflags;
 
 pub mod cil;
+mod event_stream;
 pub mod ffi;
+pub mod metadata;
 mod metadata_import;
+mod module_metadata_cache;
+pub mod pe;
+mod per_thread;
+mod plugin_host;
+mod profiler_enum;
 mod profiler_info;
+pub mod recording;
+pub mod testing;
 mod traits;
+mod type_name_resolver;
 mod types;
 
 pub use clr_profiler_macros::*;
+pub use event_stream::*;
 pub use metadata_import::*;
+pub use module_metadata_cache::*;
+pub use per_thread::*;
+pub use plugin_host::*;
+pub use profiler_enum::*;
 pub use profiler_info::*;
 pub use traits::*;
+pub use type_name_resolver::*;
 pub use types::*;
@@ -0,0 +1,13 @@
+mod custom_attribute;
+mod error;
+mod qualified_name;
+mod signature;
+mod token;
+mod user_strings;
+
+pub use self::custom_attribute::*;
+pub use self::error::*;
+pub(crate) use self::qualified_name::*;
+pub use self::signature::*;
+pub use self::token::*;
+pub use self::user_strings::*;
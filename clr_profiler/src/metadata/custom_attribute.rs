@@ -0,0 +1,370 @@
+use crate::{
+    ffi::CorElementType,
+    metadata::{BlobReader, Error, Token},
+};
+use std::fmt::Display;
+
+const CUSTOM_ATTRIBUTE_PROLOG: u16 = 0x0001;
+const SERIALIZATION_TYPE_TYPE: u8 = 0x50;
+const SERIALIZATION_TYPE_TAGGED_OBJECT: u8 = 0x51;
+const SERIALIZATION_TYPE_FIELD: u8 = 0x53;
+const SERIALIZATION_TYPE_PROPERTY: u8 = 0x54;
+const SERIALIZATION_TYPE_ENUM: u8 = 0x55;
+const NULL_ARRAY_LENGTH: u32 = 0xFFFF_FFFF;
+/// How deep arrays and boxed values may nest. Valid attributes stay far below
+/// this; malformed blobs would otherwise recurse until the stack overflows.
+const MAX_NESTING: usize = 16;
+
+/// Types that may be used for custom attribute arguments (ECMA-335 II.23.3).
+#[derive(Debug, Clone, PartialEq)]
+pub enum CustomAttributeArgType {
+    Boolean,
+    Char,
+    I1,
+    U1,
+    I2,
+    U2,
+    I4,
+    U4,
+    I8,
+    U8,
+    R4,
+    R8,
+    String,
+    Type,
+    Object,
+    /// Enum type name and its underlying integral type.
+    Enum(String, Box<CustomAttributeArgType>),
+    SzArray(Box<CustomAttributeArgType>),
+}
+
+impl CustomAttributeArgType {
+    /// Maps a primitive `ELEMENT_TYPE_*` byte (e.g. the type of an enum's
+    /// `value__` field) to its argument type.
+    pub fn from_element_type(element_type: u8) -> Option<Self> {
+        let arg_type = match element_type {
+            0x02 => Self::Boolean,
+            0x03 => Self::Char,
+            0x04 => Self::I1,
+            0x05 => Self::U1,
+            0x06 => Self::I2,
+            0x07 => Self::U2,
+            0x08 => Self::I4,
+            0x09 => Self::U4,
+            0x0a => Self::I8,
+            0x0b => Self::U8,
+            0x0c => Self::R4,
+            0x0d => Self::R8,
+            0x0e => Self::String,
+            _ => return None,
+        };
+        Some(arg_type)
+    }
+
+    /// Parses the parameter types of an attribute constructor signature.
+    ///
+    /// `resolve_type` is called for `CLASS` and `VALUETYPE` parameters with the
+    /// referenced TypeDef/TypeRef/TypeSpec token and must return either
+    /// `Type` (for `System.Type`) or `Enum`.
+    pub fn from_constructor_signature(
+        signature: &[u8],
+        resolve_type: &dyn Fn(Token) -> Result<Self, Error>,
+    ) -> Result<Vec<Self>, Error> {
+        let mut reader = BlobReader::new(signature);
+        let calling_convention = reader.read_u8()?;
+        if calling_convention & 0x10 != 0 {
+            // generic parameter count, never present on a valid constructor
+            reader.read_compressed_u32()?;
+        }
+        let param_count = reader.read_compressed_u32()?;
+        Self::skip_custom_modifiers(&mut reader)?;
+        let return_type = reader.read_u8()?;
+        if return_type != CorElementType::ELEMENT_TYPE_VOID as u8 {
+            return Err(Error::InvalidCustomAttributeType(return_type));
+        }
+        (0..param_count)
+            .map(|_| Self::from_signature_type(&mut reader, resolve_type, 0))
+            .collect()
+    }
+
+    fn from_signature_type(
+        reader: &mut BlobReader,
+        resolve_type: &dyn Fn(Token) -> Result<Self, Error>,
+        depth: usize,
+    ) -> Result<Self, Error> {
+        if depth > MAX_NESTING {
+            return Err(Error::TooDeeplyNested);
+        }
+        Self::skip_custom_modifiers(reader)?;
+        let element_type = reader.read_u8()?;
+        if let Some(arg_type) = Self::from_element_type(element_type) {
+            return Ok(arg_type);
+        }
+        match element_type {
+            0x1c => Ok(Self::Object),
+            0x1d => {
+                let element = Self::from_signature_type(reader, resolve_type, depth + 1)?;
+                Ok(Self::SzArray(Box::new(element)))
+            }
+            0x11 | 0x12 => {
+                let token = reader.read_type_def_or_ref()?;
+                resolve_type(token)
+            }
+            _ => Err(Error::InvalidCustomAttributeType(element_type)),
+        }
+    }
+
+    fn skip_custom_modifiers(reader: &mut BlobReader) -> Result<(), Error> {
+        while matches!(reader.peek_u8()?, 0x1f | 0x20) {
+            reader.read_u8()?;
+            reader.read_type_def_or_ref()?;
+        }
+        Ok(())
+    }
+
+    /// Reads a `FieldOrPropType`, the self-describing type encoding used by
+    /// named arguments and boxed values.
+    fn from_serialization_type(
+        reader: &mut BlobReader,
+        resolve_enum: &dyn Fn(&str) -> Self,
+        depth: usize,
+    ) -> Result<Self, Error> {
+        if depth > MAX_NESTING {
+            return Err(Error::TooDeeplyNested);
+        }
+        let serialization_type = reader.read_u8()?;
+        if let Some(arg_type) = Self::from_element_type(serialization_type) {
+            return Ok(arg_type);
+        }
+        match serialization_type {
+            0x1d => {
+                let element = Self::from_serialization_type(reader, resolve_enum, depth + 1)?;
+                Ok(Self::SzArray(Box::new(element)))
+            }
+            SERIALIZATION_TYPE_TYPE => Ok(Self::Type),
+            SERIALIZATION_TYPE_TAGGED_OBJECT => Ok(Self::Object),
+            SERIALIZATION_TYPE_ENUM => {
+                let name = reader.read_ser_string()?.ok_or(Error::InvalidString)?;
+                let underlying = resolve_enum(&name);
+                Ok(Self::Enum(name, Box::new(underlying)))
+            }
+            _ => Err(Error::InvalidCustomAttributeType(serialization_type)),
+        }
+    }
+}
+
+/// A decoded custom attribute argument.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CustomAttributeValue {
+    Boolean(bool),
+    Char(u16),
+    I1(i8),
+    U1(u8),
+    I2(i16),
+    U2(u16),
+    I4(i32),
+    U4(u32),
+    I8(i64),
+    U8(u64),
+    R4(f32),
+    R8(f64),
+    String(Option<String>),
+    /// Assembly qualified type name, as written by the compiler.
+    Type(Option<String>),
+    Enum(String, Box<CustomAttributeValue>),
+    Array(Option<Vec<CustomAttributeValue>>),
+}
+
+impl CustomAttributeValue {
+    fn from_reader(
+        reader: &mut BlobReader,
+        arg_type: &CustomAttributeArgType,
+        resolve_enum: &dyn Fn(&str) -> CustomAttributeArgType,
+        depth: usize,
+    ) -> Result<Self, Error> {
+        if depth > MAX_NESTING {
+            return Err(Error::TooDeeplyNested);
+        }
+        let value = match arg_type {
+            CustomAttributeArgType::Boolean => Self::Boolean(reader.read_u8()? != 0),
+            CustomAttributeArgType::Char => Self::Char(reader.read_u16()?),
+            CustomAttributeArgType::I1 => Self::I1(reader.read_u8()? as i8),
+            CustomAttributeArgType::U1 => Self::U1(reader.read_u8()?),
+            CustomAttributeArgType::I2 => Self::I2(reader.read_u16()? as i16),
+            CustomAttributeArgType::U2 => Self::U2(reader.read_u16()?),
+            CustomAttributeArgType::I4 => Self::I4(reader.read_u32()? as i32),
+            CustomAttributeArgType::U4 => Self::U4(reader.read_u32()?),
+            CustomAttributeArgType::I8 => Self::I8(reader.read_u64()? as i64),
+            CustomAttributeArgType::U8 => Self::U8(reader.read_u64()?),
+            CustomAttributeArgType::R4 => Self::R4(f32::from_bits(reader.read_u32()?)),
+            CustomAttributeArgType::R8 => Self::R8(f64::from_bits(reader.read_u64()?)),
+            CustomAttributeArgType::String => Self::String(reader.read_ser_string()?),
+            CustomAttributeArgType::Type => Self::Type(reader.read_ser_string()?),
+            CustomAttributeArgType::Object => {
+                // Boxed values are prefixed with their actual type.
+                let boxed_type = CustomAttributeArgType::from_serialization_type(
+                    reader,
+                    resolve_enum,
+                    depth + 1,
+                )?;
+                Self::from_reader(reader, &boxed_type, resolve_enum, depth + 1)?
+            }
+            CustomAttributeArgType::Enum(name, underlying) => {
+                let value = Self::from_reader(reader, underlying, resolve_enum, depth + 1)?;
+                Self::Enum(name.clone(), Box::new(value))
+            }
+            CustomAttributeArgType::SzArray(element_type) => {
+                let length = reader.read_u32()?;
+                if length == NULL_ARRAY_LENGTH {
+                    Self::Array(None)
+                } else {
+                    let elements = (0..length)
+                        .map(|_| Self::from_reader(reader, element_type, resolve_enum, depth + 1))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    Self::Array(Some(elements))
+                }
+            }
+        };
+        Ok(value)
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(Some(value)) | Self::Type(Some(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Boolean(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns integral values (including enums) widened to `i64`.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Self::Char(value) => Some(*value as i64),
+            Self::I1(value) => Some(*value as i64),
+            Self::U1(value) => Some(*value as i64),
+            Self::I2(value) => Some(*value as i64),
+            Self::U2(value) => Some(*value as i64),
+            Self::I4(value) => Some(*value as i64),
+            Self::U4(value) => Some(*value as i64),
+            Self::I8(value) => Some(*value),
+            Self::U8(value) => Some(*value as i64),
+            Self::Enum(_, value) => value.as_i64(),
+            _ => None,
+        }
+    }
+}
+
+impl Display for CustomAttributeValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Boolean(value) => write!(f, "{value}"),
+            Self::Char(value) => match char::from_u32(*value as u32) {
+                Some(c) => write!(f, "'{c}'"),
+                None => write!(f, "'\\u{value:04x}'"),
+            },
+            Self::I1(value) => write!(f, "{value}"),
+            Self::U1(value) => write!(f, "{value}"),
+            Self::I2(value) => write!(f, "{value}"),
+            Self::U2(value) => write!(f, "{value}"),
+            Self::I4(value) => write!(f, "{value}"),
+            Self::U4(value) => write!(f, "{value}"),
+            Self::I8(value) => write!(f, "{value}"),
+            Self::U8(value) => write!(f, "{value}"),
+            Self::R4(value) => write!(f, "{value:?}"),
+            Self::R8(value) => write!(f, "{value:?}"),
+            Self::String(Some(value)) => write!(f, "{value:?}"),
+            Self::Type(Some(value)) => write!(f, "typeof({value})"),
+            Self::String(None) | Self::Type(None) | Self::Array(None) => write!(f, "null"),
+            Self::Enum(name, value) => write!(f, "({name}){value}"),
+            Self::Array(Some(elements)) => {
+                let elements = elements
+                    .iter()
+                    .map(|e| format!("{e}"))
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                write!(f, "[{elements}]")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CustomAttributeNamedArgKind {
+    Field,
+    Property,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomAttributeNamedArg {
+    pub kind: CustomAttributeNamedArgKind,
+    pub name: String,
+    pub value: CustomAttributeValue,
+}
+
+/// Decoded value blob of a custom attribute.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CustomAttributeArgs {
+    pub fixed_args: Vec<CustomAttributeValue>,
+    pub named_args: Vec<CustomAttributeNamedArg>,
+}
+
+impl CustomAttributeArgs {
+    /// Decodes a custom attribute blob.
+    ///
+    /// `fixed_arg_types` are the constructor parameter types, see
+    /// [`CustomAttributeArgType::from_constructor_signature`]. Named arguments
+    /// of enum type only carry the enum's name, so `resolve_enum` must supply
+    /// its underlying type.
+    pub fn from_bytes(
+        blob: &[u8],
+        fixed_arg_types: &[CustomAttributeArgType],
+        resolve_enum: &dyn Fn(&str) -> CustomAttributeArgType,
+    ) -> Result<Self, Error> {
+        if blob.is_empty() {
+            // Attributes without arguments may have an empty blob.
+            return Ok(Self::default());
+        }
+        let mut reader = BlobReader::new(blob);
+        if reader.read_u16()? != CUSTOM_ATTRIBUTE_PROLOG {
+            return Err(Error::InvalidCustomAttributeProlog);
+        }
+        let fixed_args = fixed_arg_types
+            .iter()
+            .map(|arg_type| {
+                CustomAttributeValue::from_reader(&mut reader, arg_type, resolve_enum, 0)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let named_arg_count = reader.read_u16()?;
+        let mut named_args = Vec::with_capacity(named_arg_count as usize);
+        for _ in 0..named_arg_count {
+            let kind = match reader.read_u8()? {
+                SERIALIZATION_TYPE_FIELD => CustomAttributeNamedArgKind::Field,
+                SERIALIZATION_TYPE_PROPERTY => CustomAttributeNamedArgKind::Property,
+                kind => return Err(Error::InvalidNamedArgumentKind(kind)),
+            };
+            let arg_type =
+                CustomAttributeArgType::from_serialization_type(&mut reader, resolve_enum, 0)?;
+            let name = reader.read_ser_string()?.ok_or(Error::InvalidString)?;
+            let value = CustomAttributeValue::from_reader(&mut reader, &arg_type, resolve_enum, 0)?;
+            named_args.push(CustomAttributeNamedArg { kind, name, value });
+        }
+        Ok(CustomAttributeArgs {
+            fixed_args,
+            named_args,
+        })
+    }
+
+    pub fn named_arg(&self, name: &str) -> Option<&CustomAttributeValue> {
+        self.named_args
+            .iter()
+            .find(|arg| arg.name == name)
+            .map(|arg| &arg.value)
+    }
+}
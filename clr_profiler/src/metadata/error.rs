@@ -0,0 +1,15 @@
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    UnexpectedEndOfBlob,
+    InvalidCompressedInteger,
+    InvalidCodedToken,
+    InvalidCustomAttributeProlog,
+    InvalidCustomAttributeType(u8),
+    InvalidNamedArgumentKind(u8),
+    InvalidString,
+    InvalidSignatureType(u8),
+    InvalidCallingConvention(u8),
+    /// Types or values nested deeper than any valid blob would.
+    TooDeeplyNested,
+}
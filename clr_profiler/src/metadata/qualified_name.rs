@@ -0,0 +1,326 @@
+use crate::{
+    ffi::HResult,
+    metadata::{
+        method_spec_instantiation, signature_bytes, FieldDefToken, MemberRefToken, MethodDefToken,
+        MethodSig, MethodSpecToken, Token, TypeDefToken, TypeRefToken, TypeSig, TypeSpecToken,
+    },
+    MetadataImportTrait,
+};
+use std::convert::TryFrom;
+
+const CALLING_CONVENTION_FIELD: u8 = 0x06;
+/// How many types deep a nesting chain is followed before giving up.
+pub(crate) const MAX_NESTING_DEPTH: usize = 64;
+
+/// Names substituted for `!n` and `!!n` while formatting a signature.
+#[derive(Default)]
+pub(crate) struct GenericContext {
+    pub type_args: Vec<String>,
+    pub method_args: Vec<String>,
+}
+
+/// Builds the names returned by [`MetadataImportTrait::qualified_name`].
+///
+/// Types are written as `Namespace.Outer+Inner<T>`, primitives as their C#
+/// keywords and members as `Type::Name<T>(params)`, without the return type.
+/// Generic parameters that can't be named are written as `!n` / `!!n`.
+pub(crate) struct NameFormatter<'a, M> {
+    import: &'a M,
+}
+
+impl<'a, M: MetadataImportTrait> NameFormatter<'a, M> {
+    pub fn new(import: &'a M) -> Self {
+        NameFormatter { import }
+    }
+
+    pub fn qualified_name(&self, token: Token) -> Result<String, HResult> {
+        if let Ok(mb) = MethodDefToken::try_from(token) {
+            self.method_def_name(mb, None)
+        } else if let Ok(mr) = MemberRefToken::try_from(token) {
+            self.member_ref_name(mr, None)
+        } else if let Ok(mi) = MethodSpecToken::try_from(token) {
+            self.method_spec_name(mi)
+        } else if let Ok(fd) = FieldDefToken::try_from(token) {
+            let props = self.import.get_field_props(fd)?;
+            let type_name = self.type_name(props.class_token.token(), None)?;
+            Ok(format!("{type_name}::{}", props.name))
+        } else {
+            self.type_name(token, None)
+        }
+    }
+
+    fn method_spec_name(&self, mi: MethodSpecToken) -> Result<String, HResult> {
+        let props = self.import.get_method_spec_props(mi)?;
+        let sig = unsafe { signature_bytes(props.sig, props.sig_length) };
+        let instantiation =
+            method_spec_instantiation(sig).or(Err(HResult::META_E_BAD_SIGNATURE))?;
+        // type arguments referring to the caller's generic parameters can't be named here
+        let method_args = instantiation
+            .iter()
+            .map(|arg| self.format_type_sig(arg, &GenericContext::default()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if let Ok(mb) = MethodDefToken::try_from(props.parent_token) {
+            self.method_def_name(mb, Some(method_args))
+        } else if let Ok(mr) = MemberRefToken::try_from(props.parent_token) {
+            self.member_ref_name(mr, Some(method_args))
+        } else {
+            Err(HResult::E_INVALIDARG)
+        }
+    }
+
+    fn method_def_name(
+        &self,
+        mb: MethodDefToken,
+        method_args: Option<Vec<String>>,
+    ) -> Result<String, HResult> {
+        let props = self.import.get_method_props(mb)?;
+        let type_args = self
+            .import
+            .get_generic_param_names(props.class_token.token())?;
+        let type_name = self.type_name(props.class_token.token(), Some(&type_args))?;
+        let method_args = match method_args {
+            Some(method_args) => method_args,
+            None => self.import.get_generic_param_names(mb.token())?,
+        };
+        let context = GenericContext {
+            type_args,
+            method_args,
+        };
+        let sig = unsafe { signature_bytes(props.sig, props.sig_length) };
+        self.member_name(&type_name, &props.name, sig, &context)
+    }
+
+    fn member_ref_name(
+        &self,
+        mr: MemberRefToken,
+        method_args: Option<Vec<String>>,
+    ) -> Result<String, HResult> {
+        let props = self.import.get_member_ref_props(mr)?;
+        let parent = props.parent_token;
+        if let Ok(mb) = MethodDefToken::try_from(parent) {
+            // vararg call site of a method defined in this module
+            return self.method_def_name(mb, method_args);
+        }
+
+        let mut context = GenericContext::default();
+        let type_name = if let Ok(ts) = TypeSpecToken::try_from(parent) {
+            let type_sig = self.type_spec(ts)?;
+            if let TypeSig::GenericInst(_, args) = &type_sig {
+                context.type_args = args
+                    .iter()
+                    .map(|arg| self.format_type_sig(arg, &GenericContext::default()))
+                    .collect::<Result<_, _>>()?;
+            }
+            self.format_type_sig(&type_sig, &GenericContext::default())?
+        } else if TypeDefToken::try_from(parent).is_ok() || TypeRefToken::try_from(parent).is_ok() {
+            context.type_args = self.open_generic_args(parent).unwrap_or_default();
+            self.type_name(parent, Some(&context.type_args))?
+        } else {
+            // global function of another module (ModuleRef parent)
+            String::new()
+        };
+
+        let sig = unsafe { signature_bytes(props.sig, props.sig_length) };
+        context.method_args = match method_args {
+            Some(method_args) => method_args,
+            None => {
+                let count = match MethodSig::from_bytes(sig) {
+                    Ok(method_sig) => method_sig.generic_param_count,
+                    Err(_) => 0,
+                };
+                (0..count).map(|n| format!("!!{n}")).collect()
+            }
+        };
+        self.member_name(&type_name, &props.name, sig, &context)
+    }
+
+    /// Formats `Type::Name<method args>(params)`, or `Type::Name` for fields.
+    pub fn member_name(
+        &self,
+        type_name: &str,
+        name: &str,
+        sig: &[u8],
+        context: &GenericContext,
+    ) -> Result<String, HResult> {
+        let mut qualified_name = if type_name.is_empty() {
+            name.to_string()
+        } else {
+            format!("{type_name}::{name}")
+        };
+        if sig.first().map(|cc| cc & 0x0f) == Some(CALLING_CONVENTION_FIELD) {
+            return Ok(qualified_name);
+        }
+        let method_sig = MethodSig::from_bytes(sig).or(Err(HResult::META_E_BAD_SIGNATURE))?;
+        if !context.method_args.is_empty() {
+            qualified_name.push_str(&format!("<{}>", context.method_args.join(", ")));
+        }
+        let params = self.format_params(&method_sig.params, context)?;
+        qualified_name.push_str(&format!("({params})"));
+        Ok(qualified_name)
+    }
+
+    /// Formats a TypeDef, TypeRef or TypeSpec. Generic TypeDefs and TypeRefs
+    /// are written with `type_args`, or their own parameter names if `None`.
+    pub fn type_name(&self, token: Token, type_args: Option<&[String]>) -> Result<String, HResult> {
+        if let Ok(ts) = TypeSpecToken::try_from(token) {
+            let type_sig = self.type_spec(ts)?;
+            return self.format_type_sig(&type_sig, &GenericContext::default());
+        }
+        let names = self.nesting_chain(token)?;
+        let own_args;
+        let type_args = match type_args {
+            Some(type_args) => Some(type_args),
+            None => {
+                own_args = self.open_generic_args(token);
+                own_args.as_deref()
+            }
+        };
+        Ok(apply_type_args(names, type_args))
+    }
+
+    pub fn format_type_sig(
+        &self,
+        type_sig: &TypeSig,
+        context: &GenericContext,
+    ) -> Result<String, HResult> {
+        if let Some(keyword) = type_sig.keyword() {
+            return Ok(keyword.to_string());
+        }
+        let name = match type_sig {
+            TypeSig::Class(token) | TypeSig::ValueType(token) => self.type_name(*token, None)?,
+            TypeSig::Var(n) => generic_arg(&context.type_args, *n, "!"),
+            TypeSig::MVar(n) => generic_arg(&context.method_args, *n, "!!"),
+            TypeSig::SzArray(element) => format!("{}[]", self.format_type_sig(element, context)?),
+            TypeSig::Array(element, rank) => {
+                let commas = ",".repeat(rank.saturating_sub(1) as usize);
+                format!("{}[{commas}]", self.format_type_sig(element, context)?)
+            }
+            TypeSig::Ptr(element) => format!("{}*", self.format_type_sig(element, context)?),
+            TypeSig::ByRef(element) => format!("{}&", self.format_type_sig(element, context)?),
+            TypeSig::Pinned(element) => self.format_type_sig(element, context)?,
+            TypeSig::GenericInst(generic_type, args) => {
+                let (TypeSig::Class(token) | TypeSig::ValueType(token)) = generic_type.as_ref()
+                else {
+                    return Err(HResult::META_E_BAD_SIGNATURE);
+                };
+                let args = args
+                    .iter()
+                    .map(|arg| self.format_type_sig(arg, context))
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.type_name(*token, Some(&args))?
+            }
+            TypeSig::FnPtr(method_sig) => format!(
+                "method {} *({})",
+                self.format_type_sig(&method_sig.return_type, context)?,
+                self.format_params(&method_sig.params, context)?
+            ),
+            TypeSig::Sentinel => "...".to_string(),
+            _ => return Err(HResult::META_E_BAD_SIGNATURE),
+        };
+        Ok(name)
+    }
+
+    fn format_params(
+        &self,
+        params: &[TypeSig],
+        context: &GenericContext,
+    ) -> Result<String, HResult> {
+        let params = params
+            .iter()
+            .map(|param| self.format_type_sig(param, context))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(params.join(", "))
+    }
+
+    fn type_spec(&self, ts: TypeSpecToken) -> Result<TypeSig, HResult> {
+        let props = self.import.get_type_spec_from_token(ts)?;
+        TypeSig::from_bytes(unsafe { signature_bytes(props.sig, props.sig_length) })
+            .or(Err(HResult::META_E_BAD_SIGNATURE))
+    }
+
+    /// Returns the metadata names of a type and the types it's nested in,
+    /// outermost first. Only the outermost one carries the namespace.
+    fn nesting_chain(&self, token: Token) -> Result<Vec<String>, HResult> {
+        let mut names = Vec::new();
+        let mut current = Some(token);
+        while let Some(token) = current {
+            // malformed metadata can nest types in each other
+            if names.len() == MAX_NESTING_DEPTH {
+                return Err(HResult::META_E_BAD_SIGNATURE);
+            }
+            if let Ok(td) = TypeDefToken::try_from(token) {
+                names.push(self.import.get_type_def_props(td)?.name);
+                current = self.import.get_nested_class_props(td)?.map(Token::from);
+            } else if let Ok(tr) = TypeRefToken::try_from(token) {
+                let props = self.import.get_type_ref_props(tr)?;
+                names.push(props.name);
+                current = Some(props.resolution_scope)
+                    .filter(|scope| TypeRefToken::try_from(*scope).is_ok());
+            } else {
+                return Err(HResult::E_INVALIDARG);
+            }
+        }
+        names.reverse();
+        Ok(names)
+    }
+
+    /// Names of the generic parameters of an uninstantiated TypeDef or TypeRef.
+    /// TypeRefs are resolved to their defining module, which may not be possible.
+    fn open_generic_args(&self, token: Token) -> Option<Vec<String>> {
+        if let Ok(td) = TypeDefToken::try_from(token) {
+            return self.import.get_generic_param_names(td.token()).ok();
+        }
+        let tr = TypeRefToken::try_from(token).ok()?;
+        // a type nested in a generic type is generic too, without an arity suffix
+        if !self
+            .nesting_chain(token)
+            .ok()?
+            .iter()
+            .any(|name| name.contains('`'))
+        {
+            return Some(Vec::new());
+        }
+        self.import
+            .with_resolved_type_ref(tr, |scope, td| scope.get_generic_param_names(td.token()))
+            .ok()?
+            .ok()
+    }
+}
+
+/// Distributes generic arguments over a nesting chain, using the arity
+/// suffix (`` `2 ``) of each name. Names keep their suffix if there aren't
+/// enough arguments; leftover arguments go to the innermost type.
+fn apply_type_args(names: Vec<String>, type_args: Option<&[String]>) -> String {
+    let Some(type_args) = type_args else {
+        return names.join("+");
+    };
+    let mut remaining = type_args;
+    let last = names.len().saturating_sub(1);
+    let mut formatted = Vec::with_capacity(names.len());
+    for (i, name) in names.into_iter().enumerate() {
+        let (base, arity) = match name.rsplit_once('`') {
+            Some((base, arity)) => match arity.parse::<usize>() {
+                Ok(arity) if arity <= remaining.len() => (base.to_string(), arity),
+                _ => (name, 0),
+            },
+            None => (name, 0),
+        };
+        let arity = if i == last { remaining.len() } else { arity };
+        let (args, rest) = remaining.split_at(arity);
+        remaining = rest;
+        if args.is_empty() {
+            formatted.push(base);
+        } else {
+            formatted.push(format!("{base}<{}>", args.join(", ")));
+        }
+    }
+    formatted.join("+")
+}
+
+fn generic_arg(args: &[String], n: u32, prefix: &str) -> String {
+    match args.get(n as usize) {
+        Some(arg) => arg.clone(),
+        None => format!("{prefix}{n}"),
+    }
+}
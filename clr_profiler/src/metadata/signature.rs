@@ -0,0 +1,377 @@
+use crate::{
+    ffi::PCCOR_SIGNATURE,
+    metadata::{Error, Token, TokenKind},
+};
+use std::slice;
+
+/// Cursor over a metadata blob (signatures, custom attribute values, ...)
+/// encoded as described in ECMA-335 II.23.2.
+pub struct BlobReader<'a> {
+    blob: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BlobReader<'a> {
+    pub fn new(blob: &'a [u8]) -> Self {
+        BlobReader { blob, position: 0 }
+    }
+    pub fn position(&self) -> usize {
+        self.position
+    }
+    pub fn remaining(&self) -> usize {
+        self.blob.len() - self.position
+    }
+    pub fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+    pub fn peek_u8(&self) -> Result<u8, Error> {
+        self.blob
+            .get(self.position)
+            .copied()
+            .ok_or(Error::UnexpectedEndOfBlob)
+    }
+    pub fn read_bytes(&mut self, length: usize) -> Result<&'a [u8], Error> {
+        let end = self
+            .position
+            .checked_add(length)
+            .ok_or(Error::UnexpectedEndOfBlob)?;
+        let bytes = self
+            .blob
+            .get(self.position..end)
+            .ok_or(Error::UnexpectedEndOfBlob)?;
+        self.position = end;
+        Ok(bytes)
+    }
+    pub fn read_u8(&mut self) -> Result<u8, Error> {
+        let byte = self.peek_u8()?;
+        self.position += 1;
+        Ok(byte)
+    }
+    pub fn read_u16(&mut self) -> Result<u16, Error> {
+        let bytes = self.read_bytes(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+    pub fn read_u32(&mut self) -> Result<u32, Error> {
+        let bytes = self.read_bytes(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+    pub fn read_u64(&mut self) -> Result<u64, Error> {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(self.read_bytes(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+    /// Reads an unsigned integer compressed into 1, 2 or 4 bytes (ECMA-335 II.23.2).
+    pub fn read_compressed_u32(&mut self) -> Result<u32, Error> {
+        let first = self.read_u8()? as u32;
+        if first & 0x80 == 0 {
+            Ok(first)
+        } else if first & 0xC0 == 0x80 {
+            let second = self.read_u8()? as u32;
+            Ok(((first & 0x3F) << 8) | second)
+        } else if first & 0xE0 == 0xC0 {
+            let rest = self.read_bytes(3)?;
+            Ok(((first & 0x1F) << 24)
+                | ((rest[0] as u32) << 16)
+                | ((rest[1] as u32) << 8)
+                | rest[2] as u32)
+        } else {
+            Err(Error::InvalidCompressedInteger)
+        }
+    }
+    /// Reads a `TypeDefOrRefOrSpecEncoded` coded index and expands it into a full token.
+    pub fn read_type_def_or_ref(&mut self) -> Result<Token, Error> {
+        let coded = self.read_compressed_u32()?;
+        let kind = match coded & 0x3 {
+            0 => TokenKind::TypeDef,
+            1 => TokenKind::TypeRef,
+            2 => TokenKind::TypeSpec,
+            _ => return Err(Error::InvalidCodedToken),
+        };
+        Ok(Token::from_parts(kind, coded >> 2))
+    }
+    /// Reads a `SerString`: a compressed length followed by UTF-8 bytes,
+    /// or a single 0xFF byte for a null string.
+    pub fn read_ser_string(&mut self) -> Result<Option<String>, Error> {
+        if self.peek_u8()? == 0xFF {
+            self.position += 1;
+            return Ok(None);
+        }
+        let length = self.read_compressed_u32()? as usize;
+        let bytes = self.read_bytes(length)?;
+        String::from_utf8(bytes.to_vec())
+            .map(Some)
+            .or(Err(Error::InvalidString))
+    }
+}
+
+const CALLING_CONVENTION_GENERIC: u8 = 0x10;
+const CALLING_CONVENTION_HAS_THIS: u8 = 0x20;
+const CALLING_CONVENTION_KIND_MASK: u8 = 0x0f;
+const CALLING_CONVENTION_FIELD: u8 = 0x06;
+const CALLING_CONVENTION_LOCAL_SIG: u8 = 0x07;
+const CALLING_CONVENTION_GENERIC_INST: u8 = 0x0a;
+const ELEMENT_TYPE_SENTINEL: u8 = 0x41;
+/// How deep types may nest. Valid signatures stay far below this; malformed
+/// blobs would otherwise recurse until the stack overflows.
+const MAX_NESTING: usize = 64;
+
+/// A type as encoded in a signature blob (ECMA-335 II.23.2.12). Custom
+/// modifiers are skipped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeSig {
+    Void,
+    Boolean,
+    Char,
+    I1,
+    U1,
+    I2,
+    U2,
+    I4,
+    U4,
+    I8,
+    U8,
+    R4,
+    R8,
+    String,
+    Object,
+    IntPtr,
+    UIntPtr,
+    TypedByRef,
+    Class(Token),
+    ValueType(Token),
+    /// `!n`, the n-th generic parameter of the enclosing type.
+    Var(u32),
+    /// `!!n`, the n-th generic parameter of the method.
+    MVar(u32),
+    SzArray(Box<TypeSig>),
+    /// Multi-dimensional array with its rank; sizes and bounds are dropped.
+    Array(Box<TypeSig>, u32),
+    Ptr(Box<TypeSig>),
+    ByRef(Box<TypeSig>),
+    Pinned(Box<TypeSig>),
+    GenericInst(Box<TypeSig>, Vec<TypeSig>),
+    FnPtr(Box<MethodSig>),
+    /// Separates fixed from variable arguments at a vararg call site.
+    Sentinel,
+}
+
+impl TypeSig {
+    /// Parses a TypeSpec blob.
+    pub fn from_bytes(signature: &[u8]) -> Result<Self, Error> {
+        Self::read(&mut BlobReader::new(signature))
+    }
+
+    pub fn read(reader: &mut BlobReader) -> Result<Self, Error> {
+        Self::read_nested(reader, 0)
+    }
+
+    fn read_nested(reader: &mut BlobReader, depth: usize) -> Result<Self, Error> {
+        if depth > MAX_NESTING {
+            return Err(Error::TooDeeplyNested);
+        }
+        let nested = |reader: &mut BlobReader| Self::read_nested(reader, depth + 1);
+        while matches!(reader.peek_u8()?, 0x1f | 0x20) {
+            reader.read_u8()?;
+            reader.read_type_def_or_ref()?;
+        }
+        let element_type = reader.read_u8()?;
+        if let Some(primitive) = Self::primitive(element_type) {
+            return Ok(primitive);
+        }
+        let type_sig = match element_type {
+            0x0f => Self::Ptr(Box::new(nested(reader)?)),
+            0x10 => Self::ByRef(Box::new(nested(reader)?)),
+            0x11 => Self::ValueType(reader.read_type_def_or_ref()?),
+            0x12 => Self::Class(reader.read_type_def_or_ref()?),
+            0x13 => Self::Var(reader.read_compressed_u32()?),
+            0x14 => {
+                let element = nested(reader)?;
+                let rank = reader.read_compressed_u32()?;
+                for _ in 0..reader.read_compressed_u32()? {
+                    reader.read_compressed_u32()?;
+                }
+                // lower bounds are signed, but have the same encoded length
+                for _ in 0..reader.read_compressed_u32()? {
+                    reader.read_compressed_u32()?;
+                }
+                Self::Array(Box::new(element), rank)
+            }
+            0x15 => {
+                let generic_type = nested(reader)?;
+                let arg_count = reader.read_compressed_u32()?;
+                let args = (0..arg_count)
+                    .map(|_| nested(reader))
+                    .collect::<Result<_, _>>()?;
+                Self::GenericInst(Box::new(generic_type), args)
+            }
+            0x1b => Self::FnPtr(Box::new(MethodSig::read_nested(reader, depth + 1)?)),
+            0x1d => Self::SzArray(Box::new(nested(reader)?)),
+            0x1e => Self::MVar(reader.read_compressed_u32()?),
+            0x45 => Self::Pinned(Box::new(nested(reader)?)),
+            _ => return Err(Error::InvalidSignatureType(element_type)),
+        };
+        Ok(type_sig)
+    }
+
+    /// Maps an element type without operands (`I4`, `STRING`, `OBJECT`, ...) to its `TypeSig`.
+    pub fn primitive(element_type: u8) -> Option<Self> {
+        let primitive = match element_type {
+            0x01 => Self::Void,
+            0x02 => Self::Boolean,
+            0x03 => Self::Char,
+            0x04 => Self::I1,
+            0x05 => Self::U1,
+            0x06 => Self::I2,
+            0x07 => Self::U2,
+            0x08 => Self::I4,
+            0x09 => Self::U4,
+            0x0a => Self::I8,
+            0x0b => Self::U8,
+            0x0c => Self::R4,
+            0x0d => Self::R8,
+            0x0e => Self::String,
+            0x16 => Self::TypedByRef,
+            0x18 => Self::IntPtr,
+            0x19 => Self::UIntPtr,
+            0x1c => Self::Object,
+            _ => return None,
+        };
+        Some(primitive)
+    }
+
+    /// Maps the name of a built-in type (e.g. `System.Int32`) to its `TypeSig`.
+    pub fn from_type_name(name: &str) -> Option<Self> {
+        let primitive = match name {
+            "System.Void" => Self::Void,
+            "System.Boolean" => Self::Boolean,
+            "System.Char" => Self::Char,
+            "System.SByte" => Self::I1,
+            "System.Byte" => Self::U1,
+            "System.Int16" => Self::I2,
+            "System.UInt16" => Self::U2,
+            "System.Int32" => Self::I4,
+            "System.UInt32" => Self::U4,
+            "System.Int64" => Self::I8,
+            "System.UInt64" => Self::U8,
+            "System.Single" => Self::R4,
+            "System.Double" => Self::R8,
+            "System.String" => Self::String,
+            "System.Object" => Self::Object,
+            "System.IntPtr" => Self::IntPtr,
+            "System.UIntPtr" => Self::UIntPtr,
+            "System.TypedReference" => Self::TypedByRef,
+            _ => return None,
+        };
+        Some(primitive)
+    }
+
+    /// The C# keyword for primitive types, e.g. `int` for `I4`.
+    pub fn keyword(&self) -> Option<&'static str> {
+        let keyword = match self {
+            Self::Void => "void",
+            Self::Boolean => "bool",
+            Self::Char => "char",
+            Self::I1 => "sbyte",
+            Self::U1 => "byte",
+            Self::I2 => "short",
+            Self::U2 => "ushort",
+            Self::I4 => "int",
+            Self::U4 => "uint",
+            Self::I8 => "long",
+            Self::U8 => "ulong",
+            Self::R4 => "float",
+            Self::R8 => "double",
+            Self::String => "string",
+            Self::Object => "object",
+            Self::IntPtr => "nint",
+            Self::UIntPtr => "nuint",
+            Self::TypedByRef => "TypedReference",
+            _ => return None,
+        };
+        Some(keyword)
+    }
+}
+
+/// A MethodDefSig, MethodRefSig or StandAloneMethodSig (ECMA-335 II.23.2.1-3).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MethodSig {
+    pub calling_convention: u8,
+    pub generic_param_count: u32,
+    pub return_type: TypeSig,
+    pub params: Vec<TypeSig>,
+}
+
+impl MethodSig {
+    pub fn from_bytes(signature: &[u8]) -> Result<Self, Error> {
+        Self::read(&mut BlobReader::new(signature))
+    }
+
+    pub fn read(reader: &mut BlobReader) -> Result<Self, Error> {
+        Self::read_nested(reader, 0)
+    }
+
+    fn read_nested(reader: &mut BlobReader, depth: usize) -> Result<Self, Error> {
+        let calling_convention = reader.read_u8()?;
+        if matches!(
+            calling_convention & CALLING_CONVENTION_KIND_MASK,
+            CALLING_CONVENTION_FIELD
+                | CALLING_CONVENTION_LOCAL_SIG
+                | CALLING_CONVENTION_GENERIC_INST
+        ) {
+            return Err(Error::InvalidCallingConvention(calling_convention));
+        }
+        let generic_param_count = if calling_convention & CALLING_CONVENTION_GENERIC != 0 {
+            reader.read_compressed_u32()?
+        } else {
+            0
+        };
+        let param_count = reader.read_compressed_u32()?;
+        let return_type = TypeSig::read_nested(reader, depth)?;
+        // the count comes from the blob, every parameter takes a byte at least
+        let mut params = Vec::with_capacity(reader.remaining().min(param_count as usize));
+        for _ in 0..param_count {
+            // the sentinel isn't counted in the parameter count
+            if reader.peek_u8()? == ELEMENT_TYPE_SENTINEL {
+                reader.read_u8()?;
+                params.push(TypeSig::Sentinel);
+            }
+            params.push(TypeSig::read_nested(reader, depth)?);
+        }
+        Ok(MethodSig {
+            calling_convention,
+            generic_param_count,
+            return_type,
+            params,
+        })
+    }
+
+    pub fn has_this(&self) -> bool {
+        self.calling_convention & CALLING_CONVENTION_HAS_THIS != 0
+    }
+}
+
+/// Parses the type arguments of a MethodSpec instantiation blob (ECMA-335 II.23.2.15).
+pub fn method_spec_instantiation(signature: &[u8]) -> Result<Vec<TypeSig>, Error> {
+    let mut reader = BlobReader::new(signature);
+    let calling_convention = reader.read_u8()?;
+    if calling_convention != CALLING_CONVENTION_GENERIC_INST {
+        return Err(Error::InvalidCallingConvention(calling_convention));
+    }
+    let arg_count = reader.read_compressed_u32()?;
+    (0..arg_count).map(|_| TypeSig::read(&mut reader)).collect()
+}
+
+/// Borrows a signature owned by the metadata scope, which may be null when empty.
+///
+/// # Safety
+///
+/// Unless null, `sig` must point to `sig_length` bytes that outlive `'a`; for
+/// signatures the runtime hands out, `'a` must not outlive the metadata
+/// interface they came from.
+pub(crate) unsafe fn signature_bytes<'a>(sig: PCCOR_SIGNATURE, sig_length: u32) -> &'a [u8] {
+    if sig.is_null() {
+        &[]
+    } else {
+        slice::from_raw_parts(sig, sig_length as usize)
+    }
+}
@@ -0,0 +1,220 @@
+use crate::ffi::mdToken;
+use std::{convert::TryFrom, fmt::Display};
+
+const RID_MASK: mdToken = 0x00ff_ffff;
+
+/// Metadata table a token refers to, encoded in the token's high byte
+/// (`CorTokenType` in corhdr.h).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    Module = 0x00,
+    TypeRef = 0x01,
+    TypeDef = 0x02,
+    FieldDef = 0x04,
+    MethodDef = 0x06,
+    ParamDef = 0x08,
+    InterfaceImpl = 0x09,
+    MemberRef = 0x0a,
+    CustomAttribute = 0x0c,
+    Permission = 0x0e,
+    Signature = 0x11,
+    Event = 0x14,
+    Property = 0x17,
+    ModuleRef = 0x1a,
+    TypeSpec = 0x1b,
+    Assembly = 0x20,
+    AssemblyRef = 0x23,
+    File = 0x26,
+    ExportedType = 0x27,
+    ManifestResource = 0x28,
+    GenericParam = 0x2a,
+    MethodSpec = 0x2b,
+    GenericParamConstraint = 0x2c,
+    /// User string heap offset, the operand of `ldstr`.
+    String = 0x70,
+    Name = 0x71,
+    BaseType = 0x72,
+}
+
+impl TokenKind {
+    pub fn from_table(table: u8) -> Option<Self> {
+        let kind = match table {
+            0x00 => Self::Module,
+            0x01 => Self::TypeRef,
+            0x02 => Self::TypeDef,
+            0x04 => Self::FieldDef,
+            0x06 => Self::MethodDef,
+            0x08 => Self::ParamDef,
+            0x09 => Self::InterfaceImpl,
+            0x0a => Self::MemberRef,
+            0x0c => Self::CustomAttribute,
+            0x0e => Self::Permission,
+            0x11 => Self::Signature,
+            0x14 => Self::Event,
+            0x17 => Self::Property,
+            0x1a => Self::ModuleRef,
+            0x1b => Self::TypeSpec,
+            0x20 => Self::Assembly,
+            0x23 => Self::AssemblyRef,
+            0x26 => Self::File,
+            0x27 => Self::ExportedType,
+            0x28 => Self::ManifestResource,
+            0x2a => Self::GenericParam,
+            0x2b => Self::MethodSpec,
+            0x2c => Self::GenericParamConstraint,
+            0x70 => Self::String,
+            0x71 => Self::Name,
+            0x72 => Self::BaseType,
+            _ => return None,
+        };
+        Some(kind)
+    }
+    pub fn table(self) -> u8 {
+        self as u8
+    }
+}
+
+/// A metadata token: table kind in the high byte, row id (RID) in the low 24 bits.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Token(mdToken);
+
+impl Token {
+    pub const fn new(raw: mdToken) -> Self {
+        Token(raw)
+    }
+    pub fn from_parts(kind: TokenKind, rid: u32) -> Self {
+        Token(((kind.table() as mdToken) << 24) | (rid & RID_MASK))
+    }
+    pub fn raw(self) -> mdToken {
+        self.0
+    }
+    /// Returns `None` for tokens with an unknown table byte.
+    pub fn kind(self) -> Option<TokenKind> {
+        TokenKind::from_table((self.0 >> 24) as u8)
+    }
+    pub fn is(self, kind: TokenKind) -> bool {
+        self.kind() == Some(kind)
+    }
+    pub fn rid(self) -> u32 {
+        self.0 & RID_MASK
+    }
+    /// Nil tokens (RID 0) stand for "no row", e.g. the base type of `System.Object`.
+    pub fn is_nil(self) -> bool {
+        self.rid() == 0
+    }
+}
+
+impl From<mdToken> for Token {
+    fn from(raw: mdToken) -> Self {
+        Token(raw)
+    }
+}
+
+impl From<Token> for mdToken {
+    fn from(token: Token) -> Self {
+        token.0
+    }
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind() {
+            Some(kind) => write!(f, "{:?}({:#010x})", kind, self.0),
+            None => write!(f, "Token({:#010x})", self.0),
+        }
+    }
+}
+
+macro_rules! typed_token {
+    ($(#[$meta:meta])* $name:ident, $kind:ident) => {
+        $(#[$meta])*
+        #[repr(transparent)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct $name(Token);
+
+        impl $name {
+            pub const KIND: TokenKind = TokenKind::$kind;
+
+            pub fn from_rid(rid: u32) -> Self {
+                $name(Token::from_parts(TokenKind::$kind, rid))
+            }
+            /// Wraps a raw token the runtime documents to be of this kind
+            /// without checking its table byte.
+            #[allow(dead_code)]
+            pub(crate) fn from_raw_unchecked(raw: mdToken) -> Self {
+                $name(Token(raw))
+            }
+            pub fn token(self) -> Token {
+                self.0
+            }
+            pub fn raw(self) -> mdToken {
+                self.0.raw()
+            }
+            pub fn rid(self) -> u32 {
+                self.0.rid()
+            }
+            pub fn is_nil(self) -> bool {
+                self.0.is_nil()
+            }
+        }
+
+        impl TryFrom<Token> for $name {
+            type Error = Token;
+
+            fn try_from(token: Token) -> Result<Self, Self::Error> {
+                if token.is(TokenKind::$kind) {
+                    Ok($name(token))
+                } else {
+                    Err(token)
+                }
+            }
+        }
+
+        impl TryFrom<mdToken> for $name {
+            type Error = Token;
+
+            fn try_from(raw: mdToken) -> Result<Self, Self::Error> {
+                Self::try_from(Token(raw))
+            }
+        }
+
+        impl From<$name> for Token {
+            fn from(token: $name) -> Self {
+                token.0
+            }
+        }
+
+        impl From<$name> for mdToken {
+            fn from(token: $name) -> Self {
+                token.raw()
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+    };
+}
+
+typed_token!(ModuleToken, Module);
+typed_token!(TypeRefToken, TypeRef);
+typed_token!(TypeDefToken, TypeDef);
+typed_token!(FieldDefToken, FieldDef);
+typed_token!(MethodDefToken, MethodDef);
+typed_token!(ParamDefToken, ParamDef);
+typed_token!(MemberRefToken, MemberRef);
+typed_token!(CustomAttributeToken, CustomAttribute);
+typed_token!(SignatureToken, Signature);
+typed_token!(ModuleRefToken, ModuleRef);
+typed_token!(TypeSpecToken, TypeSpec);
+typed_token!(AssemblyRefToken, AssemblyRef);
+typed_token!(GenericParamToken, GenericParam);
+typed_token!(MethodSpecToken, MethodSpec);
+typed_token!(
+    /// Operand of `ldstr`, an offset into the `#US` heap.
+    StringToken,
+    String
+);
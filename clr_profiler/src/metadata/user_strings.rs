@@ -0,0 +1,33 @@
+use crate::{ffi::HResult, metadata::StringToken, MetadataImportTrait};
+use std::vec;
+
+/// Iterator over the string literals of a module, returned by
+/// [`MetadataImportTrait::user_strings`].
+pub struct UserStrings<'a, M> {
+    import: &'a M,
+    tokens: vec::IntoIter<StringToken>,
+}
+
+impl<'a, M: MetadataImportTrait> UserStrings<'a, M> {
+    pub(crate) fn new(import: &'a M, tokens: Vec<StringToken>) -> Self {
+        UserStrings {
+            import,
+            tokens: tokens.into_iter(),
+        }
+    }
+}
+
+impl<M: MetadataImportTrait> Iterator for UserStrings<'_, M> {
+    type Item = Result<(StringToken, String), HResult>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let stk = self.tokens.next()?;
+        Some(self.import.get_user_string(stk).map(|string| (stk, string)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.tokens.size_hint()
+    }
+}
+
+impl<M: MetadataImportTrait> ExactSizeIterator for UserStrings<'_, M> {}
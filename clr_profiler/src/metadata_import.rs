@@ -1,42 +1,77 @@
 use crate::{
     ffi::{
-        mdMethodDef, mdTypeDef, CorMethodAttr, CorMethodImpl, CorTypeAttr, MetaDataImport as FFIMetaDataImport, HRESULT, S_OK, WCHAR
+        mdToken, ComPtr, CorFieldAttr, CorGenericParamAttr, CorMethodAttr, CorMethodImpl, CorTypeAttr,
+        IMetaDataImport2, MetaDataImport as FFIMetaDataImport, CLDB_E_RECORD_NOTFOUND, HCORENUM, HResult, HRESULT, S_FALSE, S_OK, ULONG, WCHAR,
     },
-    MetadataImportTrait, MethodProps, TypeDefProps,
+    metadata::{
+        CustomAttributeToken, FieldDefToken, GenericParamToken, MemberRefToken, MethodDefToken,
+        MethodSpecToken, StringToken, Token, TypeDefToken, TypeRefToken, TypeSpecToken,
+    },
+    CustomAttributeProps, FieldProps, GenericParamProps, MemberRefProps, MetadataImportTrait,
+    MethodProps, MethodSpecProps, TypeDefProps, TypeRefProps, TypeSpecProps,
 };
-use std::{mem::MaybeUninit, ptr};
-use widestring::U16CString;
+use std::{ffi::c_void, mem::MaybeUninit, ptr, slice};
+use widestring::{U16CString, U16String};
 
+/// A metadata scope, released when the last clone is dropped.
 #[derive(Clone)]
 pub struct MetadataImport {
-    import: *const FFIMetaDataImport,
+    import: ComPtr<FFIMetaDataImport>,
 }
 
+// Metadata scopes handed out by the runtime are free threaded.
+unsafe impl Send for MetadataImport {}
+unsafe impl Sync for MetadataImport {}
+
 impl MetadataImport {
-    pub fn new(metadata_import: *const FFIMetaDataImport) -> Self {
+    pub fn new(metadata_import: ComPtr<FFIMetaDataImport>) -> Self {
         MetadataImport {
             import: metadata_import,
         }
     }
 
     fn import(&self) -> &FFIMetaDataImport {
-        unsafe { self.import.as_ref().unwrap() }
+        &self.import
     }
 
-    pub fn release(self) {
-        unsafe { 
-            let import = self.import().i_unknown();
-            (import.Release)(&mut (*self.import.cast_mut()));
+    /// Drains a metadata enumeration (`EnumFields`, `EnumCustomAttributes`, ...)
+    /// into a vector, closing the enumerator when done.
+    fn enum_tokens<F>(&self, mut enumerate: F) -> Result<Vec<mdToken>, HResult>
+    where
+        F: FnMut(*mut HCORENUM, *mut mdToken, ULONG, *mut ULONG) -> HRESULT,
+    {
+        let mut h_enum: HCORENUM = ptr::null();
+        let mut buffer = [0 as mdToken; 64];
+        let mut tokens = Vec::new();
+        let result = loop {
+            let mut fetched = 0;
+            let hr = enumerate(
+                &mut h_enum,
+                buffer.as_mut_ptr(),
+                buffer.len() as ULONG,
+                &mut fetched,
+            );
+            if hr < 0 {
+                break Err(hr.into());
+            }
+            tokens.extend_from_slice(&buffer[..fetched as usize]);
+            if hr == S_FALSE || fetched == 0 {
+                break Ok(tokens);
+            }
+        };
+        if !h_enum.is_null() {
+            unsafe { self.import().CloseEnum(h_enum) };
         }
+        result
     }
 }
 
 impl MetadataImportTrait for MetadataImport {
-    fn get_method_props(&self, mb: mdMethodDef) -> Result<MethodProps, HRESULT> {
+    fn get_method_props(&self, mb: MethodDefToken) -> Result<MethodProps, HResult> {
         let mut name_buffer_length = MaybeUninit::uninit();
         unsafe {
             self.import().GetMethodProps(
-                mb,
+                mb.raw(),
                 ptr::null_mut(),
                 ptr::null_mut(),
                 0,
@@ -61,7 +96,7 @@ impl MetadataImportTrait for MetadataImport {
         let mut impl_flags = MaybeUninit::uninit();
         let hr = unsafe {
             self.import().GetMethodProps(
-                mb,
+                mb.raw(),
                 class_token.as_mut_ptr(),
                 name_buffer.as_mut_ptr(),
                 name_buffer_length,
@@ -75,7 +110,7 @@ impl MetadataImportTrait for MetadataImport {
         };
         match hr {
             S_OK => {
-                let class_token = unsafe { class_token.assume_init() };
+                let class_token = TypeDefToken::from_raw_unchecked(unsafe { class_token.assume_init() });
                 let name = U16CString::from_vec_with_nul(name_buffer)
                     .unwrap()
                     .to_string_lossy();
@@ -96,15 +131,15 @@ impl MetadataImportTrait for MetadataImport {
                     impl_flags,
                 })
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     
-    fn get_type_def_props(&self, td: mdTypeDef) -> Result<TypeDefProps, HRESULT> {
+    fn get_type_def_props(&self, td: TypeDefToken) -> Result<TypeDefProps, HResult> {
         let mut name_buffer_length = MaybeUninit::uninit();
         unsafe {
             self.import().GetTypeDefProps(
-                td, 
+                td.raw(),
                 ptr::null_mut(),
                 0,
                 name_buffer_length.as_mut_ptr(),
@@ -121,7 +156,7 @@ impl MetadataImportTrait for MetadataImport {
         let mut parent_token = MaybeUninit::uninit();
 
         let hr = unsafe {
-            self.import().GetTypeDefProps(td, 
+            self.import().GetTypeDefProps(td.raw(), 
                 name_buffer.as_mut_ptr(), 
                 name_buffer_length, 
                 name_length.as_mut_ptr(), 
@@ -136,7 +171,7 @@ impl MetadataImportTrait for MetadataImport {
                     .to_string_lossy();
                 let flags = unsafe { flags.assume_init() };
                 let type_def_flags = CorTypeAttr::from_bits(flags).unwrap();
-                let parent_token = unsafe { parent_token.assume_init() };
+                let parent_token = Token::new(unsafe { parent_token.assume_init() });
 
                 Ok(TypeDefProps{
                     name,
@@ -144,7 +179,459 @@ impl MetadataImportTrait for MetadataImport {
                     parent_token
                 })
             },
-            _ => Err(hr)
+            _ => Err(hr.into())
+        }
+    }
+
+    fn get_type_ref_props(&self, tr: TypeRefToken) -> Result<TypeRefProps, HResult> {
+        let mut name_buffer_length = 0;
+        unsafe {
+            self.import().GetTypeRefProps(
+                tr.raw(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                0,
+                &mut name_buffer_length,
+            )
+        };
+
+        let mut name_buffer = vec![0 as WCHAR; name_buffer_length as usize];
+        let mut name_length = 0;
+        let mut resolution_scope = MaybeUninit::uninit();
+        let hr = unsafe {
+            self.import().GetTypeRefProps(
+                tr.raw(),
+                resolution_scope.as_mut_ptr(),
+                name_buffer.as_mut_ptr(),
+                name_buffer_length,
+                &mut name_length,
+            )
+        };
+
+        match hr {
+            S_OK => {
+                let resolution_scope = Token::new(unsafe { resolution_scope.assume_init() });
+                Ok(TypeRefProps {
+                    resolution_scope,
+                    name: string_from_buffer(&name_buffer),
+                })
+            }
+            _ => Err(hr.into()),
+        }
+    }
+
+    fn get_member_ref_props(&self, mr: MemberRefToken) -> Result<MemberRefProps, HResult> {
+        let mut name_buffer_length = 0;
+        unsafe {
+            self.import().GetMemberRefProps(
+                mr.raw(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                0,
+                &mut name_buffer_length,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        };
+
+        let mut name_buffer = vec![0 as WCHAR; name_buffer_length as usize];
+        let mut name_length = 0;
+        let mut parent_token = MaybeUninit::uninit();
+        let mut sig = MaybeUninit::uninit();
+        let mut sig_length = MaybeUninit::uninit();
+        let hr = unsafe {
+            self.import().GetMemberRefProps(
+                mr.raw(),
+                parent_token.as_mut_ptr(),
+                name_buffer.as_mut_ptr(),
+                name_buffer_length,
+                &mut name_length,
+                sig.as_mut_ptr(),
+                sig_length.as_mut_ptr(),
+            )
+        };
+
+        match hr {
+            S_OK => {
+                let parent_token = Token::new(unsafe { parent_token.assume_init() });
+                let sig = unsafe { sig.assume_init() };
+                let sig_length = unsafe { sig_length.assume_init() };
+                Ok(MemberRefProps {
+                    parent_token,
+                    name: string_from_buffer(&name_buffer),
+                    sig,
+                    sig_length,
+                })
+            }
+            _ => Err(hr.into()),
+        }
+    }
+
+    fn get_field_props(&self, fd: FieldDefToken) -> Result<FieldProps, HResult> {
+        let mut name_buffer_length = 0;
+        unsafe {
+            self.import().GetFieldProps(
+                fd.raw(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                0,
+                &mut name_buffer_length,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        };
+
+        let mut name_buffer = vec![0 as WCHAR; name_buffer_length as usize];
+        let mut name_length = 0;
+        let mut class_token = MaybeUninit::uninit();
+        let mut attr_flags = MaybeUninit::uninit();
+        let mut sig = MaybeUninit::uninit();
+        let mut sig_length = MaybeUninit::uninit();
+        let hr = unsafe {
+            self.import().GetFieldProps(
+                fd.raw(),
+                class_token.as_mut_ptr(),
+                name_buffer.as_mut_ptr(),
+                name_buffer_length,
+                &mut name_length,
+                attr_flags.as_mut_ptr(),
+                sig.as_mut_ptr(),
+                sig_length.as_mut_ptr(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        };
+
+        match hr {
+            S_OK => {
+                let class_token = TypeDefToken::from_raw_unchecked(unsafe { class_token.assume_init() });
+                let attr_flags = unsafe { attr_flags.assume_init() };
+                let sig = unsafe { sig.assume_init() };
+                let sig_length = unsafe { sig_length.assume_init() };
+                Ok(FieldProps {
+                    class_token,
+                    name: string_from_buffer(&name_buffer),
+                    attr_flags: CorFieldAttr::from_bits_retain(attr_flags),
+                    sig,
+                    sig_length,
+                })
+            }
+            _ => Err(hr.into()),
         }
     }
+
+    fn get_type_spec_from_token(&self, ts: TypeSpecToken) -> Result<TypeSpecProps, HResult> {
+        let mut sig = MaybeUninit::uninit();
+        let mut sig_length = MaybeUninit::uninit();
+        let hr = unsafe {
+            self.import()
+                .GetTypeSpecFromToken(ts.raw(), sig.as_mut_ptr(), sig_length.as_mut_ptr())
+        };
+
+        match hr {
+            S_OK => {
+                let sig = unsafe { sig.assume_init() };
+                let sig_length = unsafe { sig_length.assume_init() };
+                Ok(TypeSpecProps { sig, sig_length })
+            }
+            _ => Err(hr.into()),
+        }
+    }
+
+    fn get_method_spec_props(&self, mi: MethodSpecToken) -> Result<MethodSpecProps, HResult> {
+        let mut parent_token = MaybeUninit::uninit();
+        let mut sig = MaybeUninit::uninit();
+        let mut sig_length = MaybeUninit::uninit();
+        let hr = unsafe {
+            self.import().GetMethodSpecProps(
+                mi.raw(),
+                parent_token.as_mut_ptr(),
+                sig.as_mut_ptr(),
+                sig_length.as_mut_ptr(),
+            )
+        };
+
+        match hr {
+            S_OK => {
+                let parent_token = Token::new(unsafe { parent_token.assume_init() });
+                let sig = unsafe { sig.assume_init() };
+                let sig_length = unsafe { sig_length.assume_init() };
+                Ok(MethodSpecProps {
+                    parent_token,
+                    sig,
+                    sig_length,
+                })
+            }
+            _ => Err(hr.into()),
+        }
+    }
+
+    fn get_nested_class_props(&self, td: TypeDefToken) -> Result<Option<TypeDefToken>, HResult> {
+        let mut enclosing_class = MaybeUninit::uninit();
+        let hr = unsafe {
+            self.import()
+                .GetNestedClassProps(td.raw(), enclosing_class.as_mut_ptr())
+        };
+
+        match hr {
+            S_OK => {
+                let enclosing_class = unsafe { enclosing_class.assume_init() };
+                Ok(Some(TypeDefToken::from_raw_unchecked(enclosing_class)))
+            }
+            CLDB_E_RECORD_NOTFOUND => Ok(None),
+            _ => Err(hr.into()),
+        }
+    }
+
+    fn enum_generic_params(&self, tk: Token) -> Result<Vec<GenericParamToken>, HResult> {
+        let params = self.enum_tokens(|h_enum, tokens, max, fetched| unsafe {
+            self.import()
+                .EnumGenericParams(h_enum, tk.raw(), tokens, max, fetched)
+        })?;
+        Ok(params
+            .into_iter()
+            .map(GenericParamToken::from_raw_unchecked)
+            .collect())
+    }
+
+    fn get_generic_param_props(
+        &self,
+        gp: GenericParamToken,
+    ) -> Result<GenericParamProps, HResult> {
+        let mut name_buffer_length = 0;
+        unsafe {
+            self.import().GetGenericParamProps(
+                gp.raw(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                0,
+                &mut name_buffer_length,
+            )
+        };
+
+        let mut name_buffer = vec![0 as WCHAR; name_buffer_length as usize];
+        let mut name_length = 0;
+        let mut sequence = MaybeUninit::uninit();
+        let mut flags = MaybeUninit::uninit();
+        let mut owner_token = MaybeUninit::uninit();
+        let hr = unsafe {
+            self.import().GetGenericParamProps(
+                gp.raw(),
+                sequence.as_mut_ptr(),
+                flags.as_mut_ptr(),
+                owner_token.as_mut_ptr(),
+                ptr::null_mut(),
+                name_buffer.as_mut_ptr(),
+                name_buffer_length,
+                &mut name_length,
+            )
+        };
+
+        match hr {
+            S_OK => {
+                let sequence = unsafe { sequence.assume_init() };
+                let flags = unsafe { flags.assume_init() };
+                let owner_token = Token::new(unsafe { owner_token.assume_init() });
+                Ok(GenericParamProps {
+                    sequence,
+                    flags: CorGenericParamAttr::from_bits_retain(flags),
+                    owner_token,
+                    name: string_from_buffer(&name_buffer),
+                })
+            }
+            _ => Err(hr.into()),
+        }
+    }
+
+    fn enum_type_defs(&self) -> Result<Vec<TypeDefToken>, HResult> {
+        let type_defs = self.enum_tokens(|h_enum, tokens, max, fetched| unsafe {
+            self.import().EnumTypeDefs(h_enum, tokens, max, fetched)
+        })?;
+        Ok(type_defs.into_iter().map(TypeDefToken::from_raw_unchecked).collect())
+    }
+
+    fn enum_methods(&self, td: TypeDefToken) -> Result<Vec<MethodDefToken>, HResult> {
+        let methods = self.enum_tokens(|h_enum, tokens, max, fetched| unsafe {
+            self.import().EnumMethods(h_enum, td.raw(), tokens, max, fetched)
+        })?;
+        Ok(methods.into_iter().map(MethodDefToken::from_raw_unchecked).collect())
+    }
+
+    fn enum_fields(&self, td: TypeDefToken) -> Result<Vec<FieldDefToken>, HResult> {
+        let fields = self.enum_tokens(|h_enum, tokens, max, fetched| unsafe {
+            self.import().EnumFields(h_enum, td.raw(), tokens, max, fetched)
+        })?;
+        Ok(fields.into_iter().map(FieldDefToken::from_raw_unchecked).collect())
+    }
+
+    fn get_user_string(&self, stk: StringToken) -> Result<String, HResult> {
+        let mut string_length = 0;
+        unsafe {
+            self.import()
+                .GetUserString(stk.raw(), ptr::null_mut(), 0, &mut string_length)
+        };
+
+        let mut string_buffer = vec![0 as WCHAR; string_length as usize];
+        let hr = unsafe {
+            self.import().GetUserString(
+                stk.raw(),
+                string_buffer.as_mut_ptr(),
+                string_length,
+                &mut string_length,
+            )
+        };
+
+        match hr {
+            // not null terminated, and may contain nulls
+            S_OK => Ok(String::from_utf16_lossy(
+                &string_buffer[..string_buffer.len().min(string_length as usize)],
+            )),
+            _ => Err(hr.into()),
+        }
+    }
+
+    fn enum_user_strings(&self) -> Result<Vec<StringToken>, HResult> {
+        let strings = self.enum_tokens(|h_enum, tokens, max, fetched| unsafe {
+            self.import().EnumUserStrings(h_enum, tokens, max, fetched)
+        })?;
+        Ok(strings.into_iter().map(StringToken::from_raw_unchecked).collect())
+    }
+
+    fn find_type_def_by_name(
+        &self,
+        name: &str,
+        enclosing_class: Option<TypeDefToken>,
+    ) -> Result<TypeDefToken, HResult> {
+        let name = U16CString::from_str(name).or(Err(HResult::E_INVALIDARG))?;
+        let enclosing_class = enclosing_class.map_or(0, TypeDefToken::raw);
+        let mut td = MaybeUninit::uninit();
+        let hr = unsafe {
+            self.import()
+                .FindTypeDefByName(name.as_ptr(), enclosing_class, td.as_mut_ptr())
+        };
+
+        match hr {
+            S_OK => Ok(TypeDefToken::from_raw_unchecked(unsafe { td.assume_init() })),
+            _ => Err(hr.into()),
+        }
+    }
+
+    fn enum_custom_attributes(
+        &self,
+        tk: Token,
+        tk_type: Option<Token>,
+    ) -> Result<Vec<CustomAttributeToken>, HResult> {
+        let tk_type = tk_type.map_or(0, Token::raw);
+        let attributes = self.enum_tokens(|h_enum, tokens, max, fetched| unsafe {
+            self.import()
+                .EnumCustomAttributes(h_enum, tk.raw(), tk_type, tokens, max, fetched)
+        })?;
+        Ok(attributes
+            .into_iter()
+            .map(CustomAttributeToken::from_raw_unchecked)
+            .collect())
+    }
+
+    fn get_custom_attribute_props(
+        &self,
+        cv: CustomAttributeToken,
+    ) -> Result<CustomAttributeProps, HResult> {
+        let mut owner_token = MaybeUninit::uninit();
+        let mut constructor_token = MaybeUninit::uninit();
+        let mut blob: *mut c_void = ptr::null_mut();
+        let mut blob_length = 0;
+        let hr = unsafe {
+            self.import().GetCustomAttributeProps(
+                cv.raw(),
+                owner_token.as_mut_ptr(),
+                constructor_token.as_mut_ptr(),
+                &mut blob,
+                &mut blob_length,
+            )
+        };
+
+        match hr {
+            S_OK => {
+                let owner_token = Token::new(unsafe { owner_token.assume_init() });
+                let constructor_token = Token::new(unsafe { constructor_token.assume_init() });
+                Ok(CustomAttributeProps {
+                    owner_token,
+                    constructor_token,
+                    blob: blob_to_vec(blob, blob_length),
+                })
+            }
+            _ => Err(hr.into()),
+        }
+    }
+
+    fn get_custom_attribute_by_name(
+        &self,
+        tk_obj: Token,
+        name: &str,
+    ) -> Result<Option<Vec<u8>>, HResult> {
+        let name = U16CString::from_str(name).or(Err(HResult::E_INVALIDARG))?;
+        let mut blob: *mut c_void = ptr::null_mut();
+        let mut blob_length = 0;
+        let hr = unsafe {
+            self.import().GetCustomAttributeByName(
+                tk_obj.raw(),
+                name.as_ptr(),
+                &mut blob,
+                &mut blob_length,
+            )
+        };
+
+        match hr {
+            S_OK => Ok(Some(blob_to_vec(blob, blob_length))),
+            S_FALSE => Ok(None),
+            _ => Err(hr.into()),
+        }
+    }
+
+    fn with_resolved_type_ref<R>(
+        &self,
+        tr: TypeRefToken,
+        f: impl FnOnce(&Self, TypeDefToken) -> R,
+    ) -> Result<R, HResult> {
+        let riid = IMetaDataImport2::IID;
+        let mut scope = ptr::null_mut();
+        let mut td = MaybeUninit::uninit();
+        let hr = unsafe {
+            self.import()
+                .ResolveTypeRef(tr.raw(), &riid, &mut scope, td.as_mut_ptr())
+        };
+
+        match hr {
+            S_OK => {
+                let scope = unsafe { ComPtr::from_raw(scope) }
+                    .ok_or(HResult::E_POINTER)?;
+                let td = TypeDefToken::from_raw_unchecked(unsafe { td.assume_init() });
+                Ok(f(&MetadataImport::new(scope), td))
+            }
+            _ => Err(hr.into()),
+        }
+    }
+}
+
+/// Converts a nul terminated name buffer filled in by the runtime.
+pub(crate) fn string_from_buffer(buffer: &[WCHAR]) -> String {
+    let length = buffer.iter().position(|c| *c == 0).unwrap_or(buffer.len());
+    U16String::from_vec(&buffer[..length]).to_string_lossy()
+}
+
+/// Copies a blob owned by the metadata scope, which may be null when empty.
+fn blob_to_vec(blob: *const c_void, blob_length: ULONG) -> Vec<u8> {
+    if blob.is_null() {
+        Vec::new()
+    } else {
+        unsafe { slice::from_raw_parts(blob as *const u8, blob_length as usize) }.to_vec()
+    }
 }
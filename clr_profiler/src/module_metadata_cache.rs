@@ -0,0 +1,144 @@
+use crate::{
+    ffi::{AssemblyID, CorOpenFlags, HResult, ModuleID},
+    metadata::Token,
+    CorProfilerInfo, MetadataImport, MetadataImportTrait, ProfilerInfo,
+};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, RwLock,
+    },
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub module_hits: u64,
+    pub module_misses: u64,
+    pub name_hits: u64,
+    pub name_misses: u64,
+    pub evictions: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    module_hits: AtomicU64,
+    module_misses: AtomicU64,
+    name_hits: AtomicU64,
+    name_misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+/// The metadata scope of a loaded module, with the identity of its assembly
+/// and the names resolved so far. The scope is released when the last
+/// reference is dropped.
+pub struct ModuleMetadata {
+    pub module_id: ModuleID,
+    pub assembly_id: AssemblyID,
+    pub assembly_name: String,
+    pub file_name: String,
+    metadata: MetadataImport,
+    names: Mutex<HashMap<Token, String>>,
+    counters: Arc<Counters>,
+}
+
+impl ModuleMetadata {
+    pub fn metadata(&self) -> &MetadataImport {
+        &self.metadata
+    }
+
+    /// Cached [`MetadataImportTrait::qualified_name`].
+    pub fn qualified_name(&self, token: Token) -> Result<String, HResult> {
+        if let Some(name) = self.names.lock().unwrap().get(&token) {
+            self.counters.name_hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(name.clone());
+        }
+        self.counters.name_misses.fetch_add(1, Ordering::Relaxed);
+        let name = self.metadata.qualified_name(token)?;
+        self.names.lock().unwrap().insert(token, name.clone());
+        Ok(name)
+    }
+}
+
+/// Keeps one read-only metadata scope per module, instead of opening and
+/// releasing one for every callback.
+///
+/// Call [`ModuleMetadataCache::evict`] from `module_unload_started`, since
+/// the runtime may reuse the ModuleID of an unloaded module.
+pub struct ModuleMetadataCache {
+    profiler_info: ProfilerInfo,
+    modules: RwLock<HashMap<ModuleID, Arc<ModuleMetadata>>>,
+    counters: Arc<Counters>,
+}
+
+impl ModuleMetadataCache {
+    pub fn new(profiler_info: ProfilerInfo) -> Self {
+        ModuleMetadataCache {
+            profiler_info,
+            modules: RwLock::new(HashMap::new()),
+            counters: Arc::new(Counters::default()),
+        }
+    }
+
+    pub fn get(&self, module_id: ModuleID) -> Result<Arc<ModuleMetadata>, HResult> {
+        if let Some(module) = self.modules.read().unwrap().get(&module_id) {
+            self.counters.module_hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(module.clone());
+        }
+        self.counters.module_misses.fetch_add(1, Ordering::Relaxed);
+        let module = Arc::new(self.open(module_id)?);
+        // another thread may have opened the module meanwhile, keep theirs
+        let mut modules = self.modules.write().unwrap();
+        Ok(modules.entry(module_id).or_insert(module).clone())
+    }
+
+    /// Drops the cached scope of `module_id`. Returns `false` if it wasn't cached.
+    pub fn evict(&self, module_id: ModuleID) -> bool {
+        let evicted = self.modules.write().unwrap().remove(&module_id).is_some();
+        if evicted {
+            self.counters.evictions.fetch_add(1, Ordering::Relaxed);
+        }
+        evicted
+    }
+
+    pub fn clear(&self) {
+        self.modules.write().unwrap().clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.modules.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            module_hits: self.counters.module_hits.load(Ordering::Relaxed),
+            module_misses: self.counters.module_misses.load(Ordering::Relaxed),
+            name_hits: self.counters.name_hits.load(Ordering::Relaxed),
+            name_misses: self.counters.name_misses.load(Ordering::Relaxed),
+            evictions: self.counters.evictions.load(Ordering::Relaxed),
+        }
+    }
+
+    fn open(&self, module_id: ModuleID) -> Result<ModuleMetadata, HResult> {
+        let module_info = self.profiler_info.get_module_info(module_id)?;
+        let assembly_info = self
+            .profiler_info
+            .get_assembly_info(module_info.assembly_id)?;
+        let metadata = self
+            .profiler_info
+            .get_module_metadata(module_id, CorOpenFlags::ofRead)?;
+        Ok(ModuleMetadata {
+            module_id,
+            assembly_id: module_info.assembly_id,
+            assembly_name: assembly_info.name,
+            file_name: module_info.file_name,
+            metadata,
+            names: Mutex::new(HashMap::new()),
+            counters: self.counters.clone(),
+        })
+    }
+}
@@ -0,0 +1,7 @@
+mod assembly_file;
+mod error;
+mod image;
+mod tables;
+
+pub use self::assembly_file::*;
+pub use self::error::*;
@@ -0,0 +1,540 @@
+use crate::{
+    ffi::{CorFieldAttr, CorGenericParamAttr, CorMethodAttr, CorMethodImpl, CorTypeAttr, HResult},
+    metadata::{
+        BlobReader, CustomAttributeToken, FieldDefToken, GenericParamToken, MemberRefToken,
+        MethodDefToken, MethodSpecToken, StringToken, Token, TypeDefToken, TypeRefToken,
+        TypeSpecToken, MAX_NESTING_DEPTH,
+    },
+    pe::{
+        image::{bytes_at, u16_at, u32_at, u8_at, Image},
+        tables::*,
+        Error,
+    },
+    CustomAttributeProps, FieldProps, GenericParamProps, MemberRefProps, MetadataImportTrait,
+    MethodProps, MethodSpecProps, TypeDefProps, TypeRefProps, TypeSpecProps,
+};
+use std::{convert::TryFrom, fs, ops::Range, path::Path};
+
+/// The metadata of a .NET assembly read straight from its PE file, without
+/// a runtime. Implements [`MetadataImportTrait`] like the runtime backed
+/// [`MetadataImport`](crate::MetadataImport), so the same code can inspect
+/// both loaded modules and files on disk.
+pub struct AssemblyFile {
+    data: Vec<u8>,
+    image: Image,
+    tables: Tables,
+    strings: Range<usize>,
+    user_strings: Range<usize>,
+    blobs: Range<usize>,
+    guids: Range<usize>,
+}
+
+impl AssemblyFile {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::from_bytes(fs::read(path)?)
+    }
+
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, Error> {
+        let image = Image::parse(&data)?;
+        let table_stream = image.stream("#~").or_else(|_| image.stream("#-"))?;
+        let tables = Tables::parse(&data, table_stream)?;
+        let empty = image.metadata.end..image.metadata.end;
+        Ok(AssemblyFile {
+            strings: image.stream("#Strings")?,
+            user_strings: image.stream("#US").unwrap_or_else(|_| empty.clone()),
+            blobs: image.stream("#Blob").unwrap_or_else(|_| empty.clone()),
+            guids: image.stream("#GUID").unwrap_or(empty),
+            data,
+            image,
+            tables,
+        })
+    }
+
+    /// Raw bytes of the file.
+    pub fn bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn entry_point(&self) -> Option<MethodDefToken> {
+        MethodDefToken::try_from(self.image.entry_point_token)
+            .ok()
+            .filter(|mb| !mb.is_nil())
+    }
+
+    /// Name of the module, e.g. `MyApp.dll`.
+    pub fn module_name(&self) -> Result<String, HResult> {
+        self.string_column(MODULE, 1, 1)
+    }
+
+    /// The module version ID.
+    pub fn mvid(&self) -> Result<[u8; 16], HResult> {
+        let index = self.column(MODULE, 1, 2)? as usize;
+        // GUID heap indexes start at 1
+        let offset =
+            self.guids.start + index.checked_sub(1).ok_or(HResult::CLDB_E_FILE_CORRUPT)? * 16;
+        let guid = bytes_at(&self.data[..self.guids.end], offset, 16)
+            .or(Err(HResult::CLDB_E_FILE_CORRUPT))?;
+        let mut mvid = [0; 16];
+        mvid.copy_from_slice(guid);
+        Ok(mvid)
+    }
+
+    /// Returns the method header, IL and exception sections of a method, as
+    /// expected by [`Method::new`](crate::cil::Method::new). Fails with
+    /// `CLDB_E_RECORD_NOTFOUND` for abstract, runtime and P/Invoke methods.
+    pub fn method_body(&self, mb: MethodDefToken) -> Result<&[u8], HResult> {
+        let rva = self.column(METHOD_DEF, mb.rid(), 0)?;
+        if rva == 0 {
+            return Err(HResult::CLDB_E_RECORD_NOTFOUND);
+        }
+        let start = self
+            .image
+            .rva_to_offset(rva)
+            .or(Err(HResult::CLDB_E_FILE_CORRUPT))?;
+        let size = method_body_size(&self.data, start).or(Err(HResult::CLDB_E_FILE_CORRUPT))?;
+        bytes_at(&self.data, start, size).or(Err(HResult::CLDB_E_FILE_CORRUPT))
+    }
+
+    fn column(&self, table: usize, rid: u32, column: usize) -> Result<u32, HResult> {
+        self.tables
+            .get(&self.data, table, rid, column)
+            .ok_or(HResult::CLDB_E_RECORD_NOTFOUND)
+    }
+
+    fn coded_column(
+        &self,
+        table: usize,
+        rid: u32,
+        column: usize,
+        coded: CodedIndex,
+    ) -> Result<Token, HResult> {
+        self.tables
+            .coded(&self.data, table, rid, column, coded)
+            .ok_or(HResult::CLDB_E_RECORD_NOTFOUND)
+    }
+
+    fn string_column(&self, table: usize, rid: u32, column: usize) -> Result<String, HResult> {
+        let index = self.column(table, rid, column)? as usize;
+        let start = self.strings.start + index;
+        let bytes = self
+            .data
+            .get(start..self.strings.end)
+            .ok_or(HResult::CLDB_E_FILE_CORRUPT)?;
+        let length = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+        Ok(String::from_utf8_lossy(&bytes[..length]).into_owned())
+    }
+
+    fn blob_column(&self, table: usize, rid: u32, column: usize) -> Result<&[u8], HResult> {
+        let index = self.column(table, rid, column)? as usize;
+        self.heap_entry(&self.blobs, index)
+    }
+
+    /// Reads a length prefixed `#Blob` or `#US` entry.
+    fn heap_entry(&self, heap: &Range<usize>, index: usize) -> Result<&[u8], HResult> {
+        let start = heap.start + index;
+        let entry = self
+            .data
+            .get(start..heap.end)
+            .ok_or(HResult::CLDB_E_FILE_CORRUPT)?;
+        let mut reader = BlobReader::new(entry);
+        let length = reader
+            .read_compressed_u32()
+            .or(Err(HResult::CLDB_E_FILE_CORRUPT))?;
+        reader
+            .read_bytes(length as usize)
+            .or(Err(HResult::CLDB_E_FILE_CORRUPT))
+    }
+
+    fn type_def_name(&self, rid: u32) -> Result<String, HResult> {
+        let name = self.string_column(TYPE_DEF, rid, 1)?;
+        let namespace = self.string_column(TYPE_DEF, rid, 2)?;
+        Ok(join_namespace(&namespace, name))
+    }
+
+    /// Resolves an index into the field or method list of a TypeDef, which
+    /// goes through `FieldPtr`/`MethodPtr` in uncompressed (`#-`) metadata.
+    fn list_entry(&self, ptr_table: usize, index: u32) -> Result<u32, HResult> {
+        if self.tables.rows(ptr_table) == 0 {
+            Ok(index)
+        } else {
+            self.column(ptr_table, index, 0)
+        }
+    }
+
+    fn list_len(&self, ptr_table: usize, table: usize) -> u32 {
+        match self.tables.rows(ptr_table) {
+            0 => self.tables.rows(table),
+            rows => rows,
+        }
+    }
+
+    /// Range of list indexes owned by a TypeDef, e.g. its fields or methods.
+    fn owned_range(
+        &self,
+        td: u32,
+        list_column: usize,
+        list_len: u32,
+    ) -> Result<Range<u32>, HResult> {
+        let start = self.column(TYPE_DEF, td, list_column)?;
+        let end = if td < self.tables.rows(TYPE_DEF) {
+            self.column(TYPE_DEF, td + 1, list_column)?
+        } else {
+            list_len + 1
+        };
+        Ok(start..end.max(start))
+    }
+
+    /// Finds the TypeDef owning a field or method.
+    fn owner(
+        &self,
+        rid: u32,
+        list_column: usize,
+        ptr_table: usize,
+        table: usize,
+    ) -> Result<TypeDefToken, HResult> {
+        let list_len = self.list_len(ptr_table, table);
+        let index = if self.tables.rows(ptr_table) == 0 {
+            rid
+        } else {
+            (1..=list_len)
+                .find(|index| self.list_entry(ptr_table, *index) == Ok(rid))
+                .ok_or(HResult::CLDB_E_RECORD_NOTFOUND)?
+        };
+        for td in 1..=self.tables.rows(TYPE_DEF) {
+            if self
+                .owned_range(td, list_column, list_len)?
+                .contains(&index)
+            {
+                return Ok(TypeDefToken::from_rid(td));
+            }
+        }
+        Err(HResult::CLDB_E_RECORD_NOTFOUND)
+    }
+
+    fn owned_list(
+        &self,
+        td: TypeDefToken,
+        list_column: usize,
+        ptr_table: usize,
+        table: usize,
+    ) -> Result<Vec<u32>, HResult> {
+        let list_len = self.list_len(ptr_table, table);
+        self.owned_range(td.rid(), list_column, list_len)?
+            .map(|index| self.list_entry(ptr_table, index))
+            .collect()
+    }
+
+    fn constructor_type(&self, ctor: Token) -> Result<Token, HResult> {
+        if let Ok(mb) = MethodDefToken::try_from(ctor) {
+            Ok(self.get_method_props(mb)?.class_token.token())
+        } else if let Ok(mr) = MemberRefToken::try_from(ctor) {
+            Ok(self.get_member_ref_props(mr)?.parent_token)
+        } else {
+            Err(HResult::CLDB_E_FILE_CORRUPT)
+        }
+    }
+}
+
+impl MetadataImportTrait for AssemblyFile {
+    fn get_method_props(&self, mb: MethodDefToken) -> Result<MethodProps, HResult> {
+        let rid = mb.rid();
+        let sig = self.blob_column(METHOD_DEF, rid, 4)?;
+        Ok(MethodProps {
+            class_token: self.owner(rid, 5, METHOD_PTR, METHOD_DEF)?,
+            name: self.string_column(METHOD_DEF, rid, 3)?,
+            attr_flags: CorMethodAttr::from_bits_truncate(self.column(METHOD_DEF, rid, 2)?),
+            sig: sig.as_ptr(),
+            sig_length: sig.len() as u32,
+            rva: self.column(METHOD_DEF, rid, 0)?,
+            impl_flags: CorMethodImpl::from_bits_truncate(self.column(METHOD_DEF, rid, 1)?),
+        })
+    }
+
+    fn get_type_def_props(&self, td: TypeDefToken) -> Result<TypeDefProps, HResult> {
+        let rid = td.rid();
+        Ok(TypeDefProps {
+            name: self.type_def_name(rid)?,
+            type_def_flags: CorTypeAttr::from_bits_truncate(self.column(TYPE_DEF, rid, 0)?),
+            parent_token: self.coded_column(TYPE_DEF, rid, 3, CodedIndex::TypeDefOrRef)?,
+        })
+    }
+
+    fn get_type_ref_props(&self, tr: TypeRefToken) -> Result<TypeRefProps, HResult> {
+        let rid = tr.rid();
+        let name = self.string_column(TYPE_REF, rid, 1)?;
+        let namespace = self.string_column(TYPE_REF, rid, 2)?;
+        Ok(TypeRefProps {
+            resolution_scope: self.coded_column(TYPE_REF, rid, 0, CodedIndex::ResolutionScope)?,
+            name: join_namespace(&namespace, name),
+        })
+    }
+
+    fn get_member_ref_props(&self, mr: MemberRefToken) -> Result<MemberRefProps, HResult> {
+        let rid = mr.rid();
+        let sig = self.blob_column(MEMBER_REF, rid, 2)?;
+        Ok(MemberRefProps {
+            parent_token: self.coded_column(MEMBER_REF, rid, 0, CodedIndex::MemberRefParent)?,
+            name: self.string_column(MEMBER_REF, rid, 1)?,
+            sig: sig.as_ptr(),
+            sig_length: sig.len() as u32,
+        })
+    }
+
+    fn get_field_props(&self, fd: FieldDefToken) -> Result<FieldProps, HResult> {
+        let rid = fd.rid();
+        let sig = self.blob_column(FIELD, rid, 2)?;
+        Ok(FieldProps {
+            class_token: self.owner(rid, 4, FIELD_PTR, FIELD)?,
+            name: self.string_column(FIELD, rid, 1)?,
+            attr_flags: CorFieldAttr::from_bits_truncate(self.column(FIELD, rid, 0)?),
+            sig: sig.as_ptr(),
+            sig_length: sig.len() as u32,
+        })
+    }
+
+    fn get_type_spec_from_token(&self, ts: TypeSpecToken) -> Result<TypeSpecProps, HResult> {
+        let sig = self.blob_column(TYPE_SPEC, ts.rid(), 0)?;
+        Ok(TypeSpecProps {
+            sig: sig.as_ptr(),
+            sig_length: sig.len() as u32,
+        })
+    }
+
+    fn get_method_spec_props(&self, mi: MethodSpecToken) -> Result<MethodSpecProps, HResult> {
+        let rid = mi.rid();
+        let sig = self.blob_column(METHOD_SPEC, rid, 1)?;
+        Ok(MethodSpecProps {
+            parent_token: self.coded_column(METHOD_SPEC, rid, 0, CodedIndex::MethodDefOrRef)?,
+            sig: sig.as_ptr(),
+            sig_length: sig.len() as u32,
+        })
+    }
+
+    fn get_nested_class_props(&self, td: TypeDefToken) -> Result<Option<TypeDefToken>, HResult> {
+        for rid in 1..=self.tables.rows(NESTED_CLASS) {
+            if self.column(NESTED_CLASS, rid, 0)? == td.rid() {
+                let enclosing = self.column(NESTED_CLASS, rid, 1)?;
+                return Ok(Some(TypeDefToken::from_rid(enclosing)));
+            }
+        }
+        Ok(None)
+    }
+
+    fn enum_generic_params(&self, tk: Token) -> Result<Vec<GenericParamToken>, HResult> {
+        let mut params = Vec::new();
+        for rid in 1..=self.tables.rows(GENERIC_PARAM) {
+            if self.coded_column(GENERIC_PARAM, rid, 2, CodedIndex::TypeOrMethodDef)? == tk {
+                params.push(GenericParamToken::from_rid(rid));
+            }
+        }
+        Ok(params)
+    }
+
+    fn get_generic_param_props(&self, gp: GenericParamToken) -> Result<GenericParamProps, HResult> {
+        let rid = gp.rid();
+        Ok(GenericParamProps {
+            sequence: self.column(GENERIC_PARAM, rid, 0)?,
+            flags: CorGenericParamAttr::from_bits_truncate(self.column(GENERIC_PARAM, rid, 1)?),
+            owner_token: self.coded_column(GENERIC_PARAM, rid, 2, CodedIndex::TypeOrMethodDef)?,
+            name: self.string_column(GENERIC_PARAM, rid, 3)?,
+        })
+    }
+
+    fn enum_type_defs(&self) -> Result<Vec<TypeDefToken>, HResult> {
+        // row 1 is <Module>
+        Ok((2..=self.tables.rows(TYPE_DEF))
+            .map(TypeDefToken::from_rid)
+            .collect())
+    }
+
+    fn enum_methods(&self, td: TypeDefToken) -> Result<Vec<MethodDefToken>, HResult> {
+        let methods = self.owned_list(td, 5, METHOD_PTR, METHOD_DEF)?;
+        Ok(methods.into_iter().map(MethodDefToken::from_rid).collect())
+    }
+
+    fn enum_fields(&self, td: TypeDefToken) -> Result<Vec<FieldDefToken>, HResult> {
+        let fields = self.owned_list(td, 4, FIELD_PTR, FIELD)?;
+        Ok(fields.into_iter().map(FieldDefToken::from_rid).collect())
+    }
+
+    fn get_user_string(&self, stk: StringToken) -> Result<String, HResult> {
+        let bytes = self.heap_entry(&self.user_strings, stk.rid() as usize)?;
+        // UTF-16, followed by a byte flagging strings with special characters
+        let units = bytes
+            .chunks_exact(2)
+            .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+            .collect::<Vec<_>>();
+        Ok(String::from_utf16_lossy(&units))
+    }
+
+    fn enum_user_strings(&self) -> Result<Vec<StringToken>, HResult> {
+        let heap = &self.data[self.user_strings.clone()];
+        let mut strings = Vec::new();
+        // offset 0 is the empty string
+        let mut offset = 1;
+        while offset < heap.len() {
+            let mut reader = BlobReader::new(&heap[offset..]);
+            let Ok(length) = reader.read_compressed_u32() else {
+                break;
+            };
+            if length > 0 {
+                strings.push(StringToken::from_rid(offset as u32));
+            }
+            offset += reader.position() + length as usize;
+        }
+        Ok(strings)
+    }
+
+    fn find_type_def_by_name(
+        &self,
+        name: &str,
+        enclosing_class: Option<TypeDefToken>,
+    ) -> Result<TypeDefToken, HResult> {
+        for rid in 1..=self.tables.rows(TYPE_DEF) {
+            let td = TypeDefToken::from_rid(rid);
+            if self.type_def_name(rid)? == name
+                && self.get_nested_class_props(td)? == enclosing_class
+            {
+                return Ok(td);
+            }
+        }
+        Err(HResult::CLDB_E_RECORD_NOTFOUND)
+    }
+
+    fn enum_custom_attributes(
+        &self,
+        tk: Token,
+        tk_type: Option<Token>,
+    ) -> Result<Vec<CustomAttributeToken>, HResult> {
+        let mut attributes = Vec::new();
+        for rid in 1..=self.tables.rows(CUSTOM_ATTRIBUTE) {
+            if self.coded_column(CUSTOM_ATTRIBUTE, rid, 0, CodedIndex::HasCustomAttribute)? != tk {
+                continue;
+            }
+            if let Some(tk_type) = tk_type {
+                let ctor =
+                    self.coded_column(CUSTOM_ATTRIBUTE, rid, 1, CodedIndex::CustomAttributeType)?;
+                if self.constructor_type(ctor)? != tk_type {
+                    continue;
+                }
+            }
+            attributes.push(CustomAttributeToken::from_rid(rid));
+        }
+        Ok(attributes)
+    }
+
+    fn get_custom_attribute_props(
+        &self,
+        cv: CustomAttributeToken,
+    ) -> Result<CustomAttributeProps, HResult> {
+        let rid = cv.rid();
+        Ok(CustomAttributeProps {
+            owner_token: self.coded_column(
+                CUSTOM_ATTRIBUTE,
+                rid,
+                0,
+                CodedIndex::HasCustomAttribute,
+            )?,
+            constructor_token: self.coded_column(
+                CUSTOM_ATTRIBUTE,
+                rid,
+                1,
+                CodedIndex::CustomAttributeType,
+            )?,
+            blob: self.blob_column(CUSTOM_ATTRIBUTE, rid, 2)?.to_vec(),
+        })
+    }
+
+    fn get_custom_attribute_by_name(
+        &self,
+        tk_obj: Token,
+        name: &str,
+    ) -> Result<Option<Vec<u8>>, HResult> {
+        for cv in self.enum_custom_attributes(tk_obj, None)? {
+            let props = self.get_custom_attribute_props(cv)?;
+            let type_token = self.constructor_type(props.constructor_token)?;
+            if self.get_type_name(type_token)? == name {
+                return Ok(Some(props.blob));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Only TypeRefs to types of this module can be resolved, there's no
+    /// way to locate the files of referenced assemblies.
+    fn with_resolved_type_ref<R>(
+        &self,
+        tr: TypeRefToken,
+        f: impl FnOnce(&Self, TypeDefToken) -> R,
+    ) -> Result<R, HResult> {
+        let td = resolve_local_type_ref(self, tr, 0)?;
+        Ok(f(self, td))
+    }
+}
+
+/// `depth` counts the TypeRefs of nested types resolved so far, malformed
+/// files can make them each other's scope.
+fn resolve_local_type_ref(
+    file: &AssemblyFile,
+    tr: TypeRefToken,
+    depth: usize,
+) -> Result<TypeDefToken, HResult> {
+    if depth == MAX_NESTING_DEPTH {
+        return Err(HResult::CLDB_E_FILE_CORRUPT);
+    }
+    let props = file.get_type_ref_props(tr)?;
+    let scope = props.resolution_scope;
+    let enclosing_class = if let Ok(outer) = TypeRefToken::try_from(scope) {
+        Some(resolve_local_type_ref(file, outer, depth + 1)?)
+    } else if scope.raw() >> 24 == MODULE as u32 {
+        None
+    } else {
+        return Err(HResult::CLDB_E_RECORD_NOTFOUND);
+    };
+    file.find_type_def_by_name(&props.name, enclosing_class)
+}
+
+fn join_namespace(namespace: &str, name: String) -> String {
+    if namespace.is_empty() {
+        name
+    } else {
+        format!("{namespace}.{name}")
+    }
+}
+
+/// Size of a method body including its data sections, ECMA-335 II.25.4.
+fn method_body_size(data: &[u8], start: usize) -> Result<usize, Error> {
+    const MORE_SECTS: u16 = 0x8;
+    const FAT_SECTION: u8 = 0x40;
+    const SECTION_MORE_SECTS: u8 = 0x80;
+
+    let first = u8_at(data, start)?;
+    match first & 0x3 {
+        0x2 => return Ok(1 + (first >> 2) as usize),
+        0x3 => {}
+        _ => return Err(Error::InvalidMethodBody),
+    }
+    let flags = u16_at(data, start)?;
+    let header_size = (flags >> 12) as usize * 4;
+    let code_size = u32_at(data, start + 4)? as usize;
+    let mut end = start + header_size + code_size;
+    if flags & MORE_SECTS == 0 {
+        return Ok(end - start);
+    }
+    loop {
+        let section = end.div_ceil(4) * 4;
+        let kind = u8_at(data, section)?;
+        let size = if kind & FAT_SECTION != 0 {
+            u32_at(data, section)? as usize >> 8
+        } else {
+            u8_at(data, section + 1)? as usize
+        };
+        if size == 0 {
+            return Err(Error::InvalidMethodBody);
+        }
+        end = section + size;
+        if kind & SECTION_MORE_SECTS == 0 {
+            return Ok(end - start);
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use std::io;
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    Io(io::Error),
+    UnexpectedEndOfImage,
+    InvalidDosHeader,
+    InvalidPeSignature,
+    InvalidOptionalHeader(u16),
+    NotAClrImage,
+    InvalidRva(u32),
+    InvalidMetadataSignature,
+    MissingStream(&'static str),
+    InvalidHeapIndex(u32),
+    InvalidCodedIndex(u32),
+    InvalidMethodBody,
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
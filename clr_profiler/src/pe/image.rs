@@ -0,0 +1,156 @@
+use crate::pe::Error;
+use std::ops::Range;
+
+const PE32_MAGIC: u16 = 0x10b;
+const PE32_PLUS_MAGIC: u16 = 0x20b;
+const CLI_HEADER_DIRECTORY: usize = 14;
+const METADATA_SIGNATURE: u32 = 0x424A_5342; // "BSJB"
+
+pub(crate) fn u8_at(data: &[u8], offset: usize) -> Result<u8, Error> {
+    data.get(offset).copied().ok_or(Error::UnexpectedEndOfImage)
+}
+
+pub(crate) fn u16_at(data: &[u8], offset: usize) -> Result<u16, Error> {
+    let bytes = bytes_at(data, offset, 2)?;
+    Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+pub(crate) fn u32_at(data: &[u8], offset: usize) -> Result<u32, Error> {
+    let bytes = bytes_at(data, offset, 4)?;
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+pub(crate) fn u64_at(data: &[u8], offset: usize) -> Result<u64, Error> {
+    Ok(u32_at(data, offset)? as u64 | (u32_at(data, offset + 4)? as u64) << 32)
+}
+
+pub(crate) fn bytes_at(data: &[u8], offset: usize, length: usize) -> Result<&[u8], Error> {
+    offset
+        .checked_add(length)
+        .and_then(|end| data.get(offset..end))
+        .ok_or(Error::UnexpectedEndOfImage)
+}
+
+struct Section {
+    virtual_address: u32,
+    virtual_size: u32,
+    raw_data_offset: u32,
+    raw_data_size: u32,
+}
+
+/// The parts of a PE file (ECMA-335 II.25) needed to find CLI metadata
+/// and method bodies.
+pub(crate) struct Image {
+    sections: Vec<Section>,
+    pub entry_point_token: u32,
+    pub metadata: Range<usize>,
+    /// Metadata streams by name (`#~`, `#Strings`, ...), as file offsets.
+    pub streams: Vec<(String, Range<usize>)>,
+}
+
+impl Image {
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        if bytes_at(data, 0, 2)? != b"MZ" {
+            return Err(Error::InvalidDosHeader);
+        }
+        let pe_offset = u32_at(data, 0x3c)? as usize;
+        if bytes_at(data, pe_offset, 4)? != b"PE\0\0" {
+            return Err(Error::InvalidPeSignature);
+        }
+        let coff_header = pe_offset + 4;
+        let section_count = u16_at(data, coff_header + 2)? as usize;
+        let optional_header_size = u16_at(data, coff_header + 16)? as usize;
+        let optional_header = coff_header + 20;
+        let data_directories = match u16_at(data, optional_header)? {
+            PE32_MAGIC => optional_header + 96,
+            PE32_PLUS_MAGIC => optional_header + 112,
+            magic => return Err(Error::InvalidOptionalHeader(magic)),
+        };
+        let directory_count = u32_at(data, data_directories - 4)? as usize;
+        if directory_count <= CLI_HEADER_DIRECTORY {
+            return Err(Error::NotAClrImage);
+        }
+
+        let section_table = optional_header + optional_header_size;
+        let sections = (0..section_count)
+            .map(|i| {
+                let header = section_table + i * 40;
+                Ok(Section {
+                    virtual_size: u32_at(data, header + 8)?,
+                    virtual_address: u32_at(data, header + 12)?,
+                    raw_data_size: u32_at(data, header + 16)?,
+                    raw_data_offset: u32_at(data, header + 20)?,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let mut image = Image {
+            sections,
+            entry_point_token: 0,
+            metadata: 0..0,
+            streams: Vec::new(),
+        };
+
+        let cli_header_rva = u32_at(data, data_directories + CLI_HEADER_DIRECTORY * 8)?;
+        if cli_header_rva == 0 {
+            return Err(Error::NotAClrImage);
+        }
+        let cli_header = image.rva_to_offset(cli_header_rva)?;
+        let metadata_rva = u32_at(data, cli_header + 8)?;
+        let metadata_size = u32_at(data, cli_header + 12)? as usize;
+        image.entry_point_token = u32_at(data, cli_header + 20)?;
+        let metadata = image.rva_to_offset(metadata_rva)?;
+        bytes_at(data, metadata, metadata_size)?;
+        image.metadata = metadata..metadata + metadata_size;
+        image.streams = read_stream_headers(data, metadata)?;
+        Ok(image)
+    }
+
+    pub fn rva_to_offset(&self, rva: u32) -> Result<usize, Error> {
+        self.sections
+            .iter()
+            .find(|section| {
+                let size = section.virtual_size.max(section.raw_data_size);
+                rva >= section.virtual_address && rva - section.virtual_address < size
+            })
+            .and_then(|section| {
+                (rva - section.virtual_address).checked_add(section.raw_data_offset)
+            })
+            .map(|offset| offset as usize)
+            .ok_or(Error::InvalidRva(rva))
+    }
+
+    pub fn stream(&self, name: &'static str) -> Result<Range<usize>, Error> {
+        self.streams
+            .iter()
+            .find(|(stream_name, _)| stream_name == name)
+            .map(|(_, range)| range.clone())
+            .ok_or(Error::MissingStream(name))
+    }
+}
+
+/// Reads the metadata root (ECMA-335 II.24.2.1) and its stream headers.
+fn read_stream_headers(data: &[u8], metadata: usize) -> Result<Vec<(String, Range<usize>)>, Error> {
+    if u32_at(data, metadata)? != METADATA_SIGNATURE {
+        return Err(Error::InvalidMetadataSignature);
+    }
+    let version_length = u32_at(data, metadata + 12)? as usize;
+    let flags = metadata + 16 + version_length;
+    let stream_count = u16_at(data, flags + 2)? as usize;
+    let mut header = flags + 4;
+    let mut streams = Vec::with_capacity(stream_count);
+    for _ in 0..stream_count {
+        let offset = metadata + u32_at(data, header)? as usize;
+        let size = u32_at(data, header + 4)? as usize;
+        let name_start = header + 8;
+        let name_length = data
+            .get(name_start..)
+            .and_then(|rest| rest.iter().position(|b| *b == 0))
+            .ok_or(Error::UnexpectedEndOfImage)?;
+        let name = String::from_utf8_lossy(&data[name_start..name_start + name_length]);
+        bytes_at(data, offset, size)?;
+        streams.push((name.into_owned(), offset..offset + size));
+        // names are null terminated and padded to 4 bytes
+        header = name_start + (name_length + 4) / 4 * 4;
+    }
+    Ok(streams)
+}
@@ -0,0 +1,267 @@
+use crate::{
+    metadata::Token,
+    pe::{
+        image::{u16_at, u32_at, u64_at, u8_at},
+        Error,
+    },
+};
+use std::ops::Range;
+
+pub(crate) const MODULE: usize = 0x00;
+pub(crate) const TYPE_REF: usize = 0x01;
+pub(crate) const TYPE_DEF: usize = 0x02;
+pub(crate) const FIELD_PTR: usize = 0x03;
+pub(crate) const FIELD: usize = 0x04;
+pub(crate) const METHOD_PTR: usize = 0x05;
+pub(crate) const METHOD_DEF: usize = 0x06;
+pub(crate) const PARAM: usize = 0x08;
+pub(crate) const MEMBER_REF: usize = 0x0a;
+pub(crate) const CUSTOM_ATTRIBUTE: usize = 0x0c;
+pub(crate) const EVENT: usize = 0x14;
+pub(crate) const PROPERTY: usize = 0x17;
+pub(crate) const MODULE_REF: usize = 0x1a;
+pub(crate) const TYPE_SPEC: usize = 0x1b;
+pub(crate) const ASSEMBLY_REF: usize = 0x23;
+pub(crate) const NESTED_CLASS: usize = 0x29;
+pub(crate) const GENERIC_PARAM: usize = 0x2a;
+pub(crate) const METHOD_SPEC: usize = 0x2b;
+
+const TABLE_COUNT: usize = 0x2d;
+const UNUSED: u8 = 0xff;
+
+/// Coded indexes, ECMA-335 II.24.2.6. Each lists the tables its tag selects.
+#[derive(Clone, Copy)]
+pub(crate) enum CodedIndex {
+    TypeDefOrRef,
+    HasConstant,
+    HasCustomAttribute,
+    HasFieldMarshal,
+    HasDeclSecurity,
+    MemberRefParent,
+    HasSemantics,
+    MethodDefOrRef,
+    MemberForwarded,
+    Implementation,
+    CustomAttributeType,
+    ResolutionScope,
+    TypeOrMethodDef,
+}
+
+impl CodedIndex {
+    fn tables(self) -> &'static [u8] {
+        match self {
+            CodedIndex::TypeDefOrRef => &[0x02, 0x01, 0x1b],
+            CodedIndex::HasConstant => &[0x04, 0x08, 0x17],
+            CodedIndex::HasCustomAttribute => &[
+                0x06, 0x04, 0x01, 0x02, 0x08, 0x09, 0x0a, 0x00, 0x0e, 0x17, 0x14, 0x11, 0x1a, 0x1b,
+                0x20, 0x23, 0x26, 0x27, 0x28, 0x2a, 0x2c, 0x2b,
+            ],
+            CodedIndex::HasFieldMarshal => &[0x04, 0x08],
+            CodedIndex::HasDeclSecurity => &[0x02, 0x06, 0x20],
+            CodedIndex::MemberRefParent => &[0x02, 0x01, 0x1a, 0x06, 0x1b],
+            CodedIndex::HasSemantics => &[0x14, 0x17],
+            CodedIndex::MethodDefOrRef => &[0x06, 0x0a],
+            CodedIndex::MemberForwarded => &[0x04, 0x06],
+            CodedIndex::Implementation => &[0x26, 0x23, 0x27],
+            CodedIndex::CustomAttributeType => &[UNUSED, UNUSED, 0x06, 0x0a, UNUSED],
+            CodedIndex::ResolutionScope => &[0x00, 0x1a, 0x23, 0x01],
+            CodedIndex::TypeOrMethodDef => &[0x02, 0x06],
+        }
+    }
+
+    fn tag_bits(self) -> u32 {
+        usize::BITS - (self.tables().len() - 1).leading_zeros()
+    }
+
+    pub fn decode(self, value: u32) -> Result<Token, Error> {
+        let tag_bits = self.tag_bits();
+        let table = self
+            .tables()
+            .get((value & ((1 << tag_bits) - 1)) as usize)
+            .filter(|table| **table != UNUSED)
+            .ok_or(Error::InvalidCodedIndex(value))?;
+        Ok(Token::new((*table as u32) << 24 | value >> tag_bits))
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Column {
+    U16,
+    U32,
+    String,
+    Guid,
+    Blob,
+    Table(usize),
+    Coded(CodedIndex),
+}
+
+/// Column layout of every table, ECMA-335 II.22. The one byte `Constant.Type`
+/// is followed by a padding byte, so it's read as a `U16`.
+fn schema(table: usize) -> &'static [Column] {
+    use self::{CodedIndex::*, Column::*};
+    match table {
+        0x00 => &[U16, String, Guid, Guid, Guid],
+        0x01 => &[Coded(ResolutionScope), String, String],
+        0x02 => &[
+            U32,
+            String,
+            String,
+            Coded(TypeDefOrRef),
+            Table(FIELD),
+            Table(METHOD_DEF),
+        ],
+        0x03 => &[Table(FIELD)],
+        0x04 => &[U16, String, Blob],
+        0x05 => &[Table(METHOD_DEF)],
+        0x06 => &[U32, U16, U16, String, Blob, Table(PARAM)],
+        0x07 => &[Table(PARAM)],
+        0x08 => &[U16, U16, String],
+        0x09 => &[Table(TYPE_DEF), Coded(TypeDefOrRef)],
+        0x0a => &[Coded(MemberRefParent), String, Blob],
+        0x0b => &[U16, Coded(HasConstant), Blob],
+        0x0c => &[Coded(HasCustomAttribute), Coded(CustomAttributeType), Blob],
+        0x0d => &[Coded(HasFieldMarshal), Blob],
+        0x0e => &[U16, Coded(HasDeclSecurity), Blob],
+        0x0f => &[U16, U32, Table(TYPE_DEF)],
+        0x10 => &[U32, Table(FIELD)],
+        0x11 => &[Blob],
+        0x12 => &[Table(TYPE_DEF), Table(EVENT)],
+        0x13 => &[Table(EVENT)],
+        0x14 => &[U16, String, Coded(TypeDefOrRef)],
+        0x15 => &[Table(TYPE_DEF), Table(PROPERTY)],
+        0x16 => &[Table(PROPERTY)],
+        0x17 => &[U16, String, Blob],
+        0x18 => &[U16, Table(METHOD_DEF), Coded(HasSemantics)],
+        0x19 => &[
+            Table(TYPE_DEF),
+            Coded(MethodDefOrRef),
+            Coded(MethodDefOrRef),
+        ],
+        0x1a => &[String],
+        0x1b => &[Blob],
+        0x1c => &[U16, Coded(MemberForwarded), String, Table(MODULE_REF)],
+        0x1d => &[U32, Table(FIELD)],
+        0x1e => &[U32, U32],
+        0x1f => &[U32],
+        0x20 => &[U32, U16, U16, U16, U16, U32, Blob, String, String],
+        0x21 => &[U32],
+        0x22 => &[U32, U32, U32],
+        0x23 => &[U16, U16, U16, U16, U32, Blob, String, String, Blob],
+        0x24 => &[U32, Table(ASSEMBLY_REF)],
+        0x25 => &[U32, U32, U32, Table(ASSEMBLY_REF)],
+        0x26 => &[U32, String, Blob],
+        0x27 => &[U32, U32, String, String, Coded(Implementation)],
+        0x28 => &[U32, U32, String, Coded(Implementation)],
+        0x29 => &[Table(TYPE_DEF), Table(TYPE_DEF)],
+        0x2a => &[U16, U16, Coded(TypeOrMethodDef), String],
+        0x2b => &[Coded(MethodDefOrRef), Blob],
+        0x2c => &[Table(GENERIC_PARAM), Coded(TypeDefOrRef)],
+        _ => &[],
+    }
+}
+
+#[derive(Default, Clone)]
+struct Table {
+    rows: u32,
+    offset: usize,
+    row_size: usize,
+    /// Offset and size of each column within a row.
+    columns: Vec<(usize, usize)>,
+}
+
+/// The `#~` (or uncompressed `#-`) stream, ECMA-335 II.24.2.6.
+pub(crate) struct Tables {
+    tables: Vec<Table>,
+}
+
+impl Tables {
+    pub fn parse(data: &[u8], stream: Range<usize>) -> Result<Self, Error> {
+        let heap_sizes = u8_at(data, stream.start + 6)?;
+        let valid = u64_at(data, stream.start + 8)?;
+        let mut tables = vec![Table::default(); TABLE_COUNT];
+        let mut offset = stream.start + 24;
+        for table in 0..64 {
+            if valid & (1 << table) == 0 {
+                continue;
+            }
+            let rows = u32_at(data, offset)?;
+            offset += 4;
+            // tables this reader doesn't know come after all known ones,
+            // so their rows don't need to be skipped
+            if let Some(table) = tables.get_mut(table) {
+                table.rows = rows;
+            }
+        }
+        // extra data flag, set by some obfuscators and edit-and-continue
+        if heap_sizes & 0x40 != 0 {
+            offset += 4;
+        }
+
+        let index_size = |large: bool| if large { 4 } else { 2 };
+        let string_size = index_size(heap_sizes & 0x01 != 0);
+        let guid_size = index_size(heap_sizes & 0x02 != 0);
+        let blob_size = index_size(heap_sizes & 0x04 != 0);
+        let row_counts = tables.iter().map(|table| table.rows).collect::<Vec<_>>();
+        for (id, table) in tables.iter_mut().enumerate() {
+            let mut row_size = 0;
+            for column in schema(id) {
+                let size = match column {
+                    Column::U16 => 2,
+                    Column::U32 => 4,
+                    Column::String => string_size,
+                    Column::Guid => guid_size,
+                    Column::Blob => blob_size,
+                    Column::Table(target) => index_size(row_counts[*target] > 0xffff),
+                    Column::Coded(coded) => {
+                        let max_rows = coded
+                            .tables()
+                            .iter()
+                            .filter(|table| **table != UNUSED)
+                            .map(|table| row_counts[*table as usize])
+                            .max()
+                            .unwrap_or(0);
+                        index_size(max_rows >= 1 << (16 - coded.tag_bits()))
+                    }
+                };
+                table.columns.push((row_size, size));
+                row_size += size;
+            }
+            table.offset = offset;
+            table.row_size = row_size;
+            offset += row_size * table.rows as usize;
+        }
+        if offset > stream.end.min(data.len()) {
+            return Err(Error::UnexpectedEndOfImage);
+        }
+        Ok(Tables { tables })
+    }
+
+    pub fn rows(&self, table: usize) -> u32 {
+        self.tables.get(table).map_or(0, |table| table.rows)
+    }
+
+    /// Reads a column of a row. `rid` starts at 1.
+    pub fn get(&self, data: &[u8], table: usize, rid: u32, column: usize) -> Option<u32> {
+        let table = self.tables.get(table)?;
+        if rid == 0 || rid > table.rows {
+            return None;
+        }
+        let (column_offset, size) = *table.columns.get(column)?;
+        let offset = table.offset + (rid - 1) as usize * table.row_size + column_offset;
+        match size {
+            2 => u16_at(data, offset).ok().map(u32::from),
+            _ => u32_at(data, offset).ok(),
+        }
+    }
+
+    pub fn coded(
+        &self,
+        data: &[u8],
+        table: usize,
+        rid: u32,
+        column: usize,
+        coded: CodedIndex,
+    ) -> Option<Token> {
+        coded.decode(self.get(data, table, rid, column)?).ok()
+    }
+}
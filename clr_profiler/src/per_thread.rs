@@ -0,0 +1,87 @@
+use crate::ffi::ThreadID;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+/// State for each managed thread, for profilers whose callbacks only get
+/// `&self`. Callbacks without a thread parameter, like
+/// `jit_compilation_started`, can look up their own with
+/// `ProfilerInfo::get_current_thread_id`.
+///
+/// Each thread's slot has its own lock, so threads only wait on each other
+/// while a slot is added or removed. Managed thread IDs are reused, so remove
+/// the slot in `thread_destroyed`.
+pub struct PerThread<S> {
+    slots: RwLock<HashMap<ThreadID, Slot<S>>>,
+}
+
+/// Emptied when the thread is removed, for callers that looked it up before.
+type Slot<S> = Arc<Mutex<Option<S>>>;
+
+impl<S> PerThread<S> {
+    pub fn new() -> Self {
+        PerThread {
+            slots: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Runs `f` on the state of `thread_id`, starting it from `S::default()`
+    /// if the thread has none yet.
+    ///
+    /// Only the thread's own slot is locked while `f` runs, so `f` may use
+    /// the state of other threads, but not that of `thread_id`.
+    pub fn with<R>(&self, thread_id: ThreadID, f: impl FnOnce(&mut S) -> R) -> R
+    where
+        S: Default,
+    {
+        let slot = match self.slot(thread_id) {
+            Some(slot) => slot,
+            None => self.write().entry(thread_id).or_default().clone(),
+        };
+        let mut state = lock(&slot);
+        f(state.get_or_insert_with(S::default))
+    }
+
+    /// Runs `f` on the state of `thread_id`, if the thread has any.
+    pub fn with_existing<R>(&self, thread_id: ThreadID, f: impl FnOnce(&mut S) -> R) -> Option<R> {
+        let slot = self.slot(thread_id)?;
+        let mut state = lock(&slot);
+        state.as_mut().map(f)
+    }
+
+    pub fn remove(&self, thread_id: ThreadID) -> Option<S> {
+        let slot = self.write().remove(&thread_id)?;
+        let state = lock(&slot).take();
+        state
+    }
+
+    pub fn thread_ids(&self) -> Vec<ThreadID> {
+        self.read().keys().copied().collect()
+    }
+
+    fn slot(&self, thread_id: ThreadID) -> Option<Slot<S>> {
+        self.read().get(&thread_id).cloned()
+    }
+
+    // A panic in `f` is caught at the callback, and the state is left as `f`
+    // left it; the map itself is never left half changed.
+
+    fn read(&self) -> RwLockReadGuard<'_, HashMap<ThreadID, Slot<S>>> {
+        self.slots.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, HashMap<ThreadID, Slot<S>>> {
+        self.slots.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+fn lock<S>(slot: &Mutex<Option<S>>) -> MutexGuard<'_, Option<S>> {
+    slot.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl<S> Default for PerThread<S> {
+    fn default() -> Self {
+        PerThread::new()
+    }
+}
@@ -0,0 +1,266 @@
+use crate::{
+    event_stream::event_callbacks,
+    ffi::{
+        add_events, catch_panic, CorProfilerFunctionControl, FunctionID, HResult, ModuleID, S_OK,
+        ULONG,
+    },
+    metadata::MethodDefToken,
+    ClrProfiler, CorProfilerInfo, EventMask2, ProfilerEvent, ProfilerInfo,
+};
+use log::{error, warn};
+use std::{
+    marker::PhantomData,
+    slice,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, OnceLock,
+    },
+};
+use uuid::Uuid;
+
+/// One concern of a [`PluginHost`]. Callbacks are made on the runtime's
+/// threads, several at a time.
+pub trait Plugin: Send + Sync {
+    /// Names the plugin in logs.
+    fn name(&self) -> &str;
+
+    /// The events the plugin needs. The host turns on what its plugins need
+    /// after `Initialize`; a plugin that sets the event mask itself turns off
+    /// the events of the plugins before it.
+    fn events(&self) -> EventMask2 {
+        EventMask2::NONE
+    }
+
+    /// Called for every callback. A failure is logged, and the other plugins
+    /// still get the event.
+    fn handle(&self, _event: &ProfilerEvent) -> Result<(), HResult> {
+        Ok(())
+    }
+
+    /// Rewrites a method that is being JIT compiled or rejitted. `il_body`
+    /// is the method header and body left by the plugins before this one;
+    /// `None` leaves it as it is.
+    fn rewrite_il(
+        &self,
+        _module_id: ModuleID,
+        _method_id: MethodDefToken,
+        _il_body: &[u8],
+    ) -> Result<Option<Vec<u8>>, HResult> {
+        Ok(None)
+    }
+}
+
+/// The plugins a [`PluginHost`] is created with when the runtime loads it.
+pub trait PluginSet: ClrProfiler {
+    fn plugins(&self) -> Vec<Box<dyn Plugin>>;
+}
+
+/// A profiler that hands every callback to several [`Plugin`]s, in order, so
+/// independent concerns can share the one profiler a process loads.
+///
+/// Plugins are isolated from each other: one that panics, or fails to
+/// initialize, isn't called again, and the callback carries on with the
+/// others. IL rewrites are chained, each plugin rewriting what the one before
+/// it produced, and the result is set once.
+pub struct PluginHost<S: PluginSet> {
+    state: Arc<HostState>,
+    plugins: PhantomData<fn() -> S>,
+}
+
+struct HostState {
+    clsid: Uuid,
+    plugins: Vec<Hosted>,
+    profiler_info: OnceLock<ProfilerInfo>,
+}
+
+struct Hosted {
+    plugin: Box<dyn Plugin>,
+    failed: AtomicBool,
+}
+
+impl Hosted {
+    /// `None` if the plugin has failed, or panics now.
+    fn call<R>(
+        &self,
+        entry_point: &str,
+        f: impl FnOnce(&dyn Plugin) -> Result<R, HResult>,
+    ) -> Option<Result<R, HResult>> {
+        if self.failed.load(Ordering::Acquire) {
+            return None;
+        }
+        let result = catch_panic(entry_point, || f(&*self.plugin));
+        if result.is_none() {
+            self.disable();
+        }
+        result
+    }
+
+    fn disable(&self) {
+        if !self.failed.swap(true, Ordering::AcqRel) {
+            error!("disabled plugin {}", self.plugin.name());
+        }
+    }
+}
+
+impl<S: PluginSet> PluginHost<S> {
+    pub fn with_plugins(clsid: Uuid, plugins: Vec<Box<dyn Plugin>>) -> Self {
+        let plugins = plugins
+            .into_iter()
+            .map(|plugin| Hosted {
+                plugin,
+                failed: AtomicBool::new(false),
+            })
+            .collect();
+        PluginHost {
+            state: Arc::new(HostState {
+                clsid,
+                plugins,
+                profiler_info: OnceLock::new(),
+            }),
+            plugins: PhantomData,
+        }
+    }
+
+    fn send(&self, event: ProfilerEvent) -> Result<(), HResult> {
+        let initializing = matches!(
+            event,
+            ProfilerEvent::Initialize { .. } | ProfilerEvent::InitializeForAttach { .. }
+        );
+        for hosted in &self.state.plugins {
+            match hosted.call("Plugin::handle", |plugin| plugin.handle(&event)) {
+                Some(Err(hr)) if initializing => {
+                    error!("plugin {} failed to initialize: {hr}", hosted.plugin.name());
+                    hosted.disable();
+                }
+                Some(Err(hr)) => warn!("plugin {} failed: {hr}", hosted.plugin.name()),
+                _ => {}
+            }
+        }
+        match event {
+            ProfilerEvent::Initialize { profiler_info } => self.initialize(profiler_info, false),
+            ProfilerEvent::InitializeForAttach { profiler_info, .. } => {
+                self.initialize(profiler_info, true)
+            }
+            ProfilerEvent::JITCompilationStarted { function_id, .. } => {
+                self.rewrite_jit(function_id)
+            }
+            ProfilerEvent::GetReJITParameters {
+                module_id,
+                method_id,
+                function_control,
+            } => self.rewrite_rejit(module_id, method_id, &function_control),
+            _ => Ok(()),
+        }
+    }
+
+    fn initialize(&self, profiler_info: ProfilerInfo, attach: bool) -> Result<(), HResult> {
+        let events = self
+            .state
+            .plugins
+            .iter()
+            .filter_map(|hosted| hosted.call("Plugin::events", |plugin| Ok(plugin.events())))
+            .filter_map(Result::ok)
+            .fold(EventMask2::NONE, EventMask2::union);
+        let profiler_info = self.state.profiler_info.get_or_init(|| profiler_info);
+        if events.is_empty() {
+            return Ok(());
+        }
+        add_events(profiler_info, events, attach)
+    }
+
+    fn rewrite_jit(&self, function_id: FunctionID) -> Result<(), HResult> {
+        let Some(profiler_info) = self.state.profiler_info.get() else {
+            return Ok(());
+        };
+        let function = profiler_info.get_function_info(function_id)?;
+        // methods without IL, like those implemented by the runtime
+        let Ok(body) = profiler_info.get_il_function_body(function.module_id, function.token)
+        else {
+            return Ok(());
+        };
+        let il_body =
+            unsafe { slice::from_raw_parts(body.method_header, body.method_size as usize) };
+        match self.rewrite_il(function.module_id, function.token, il_body) {
+            Some(rewritten) => profiler_info.replace_il_function_body(
+                function.module_id,
+                function.token,
+                &rewritten,
+            ),
+            None => Ok(()),
+        }
+    }
+
+    fn rewrite_rejit(
+        &self,
+        module_id: ModuleID,
+        method_id: MethodDefToken,
+        function_control: &CorProfilerFunctionControl,
+    ) -> Result<(), HResult> {
+        let Some(profiler_info) = self.state.profiler_info.get() else {
+            return Ok(());
+        };
+        // methods without IL, like those implemented by the runtime
+        let Ok(body) = profiler_info.get_il_function_body(module_id, method_id) else {
+            return Ok(());
+        };
+        let il_body =
+            unsafe { slice::from_raw_parts(body.method_header, body.method_size as usize) };
+        let Some(rewritten) = self.rewrite_il(module_id, method_id, il_body) else {
+            return Ok(());
+        };
+        let hr = unsafe {
+            function_control.SetILFunctionBody(rewritten.len() as ULONG, rewritten.as_ptr())
+        };
+        match hr {
+            S_OK => Ok(()),
+            _ => Err(HResult::from(hr)),
+        }
+    }
+
+    /// Passes `il_body` through every plugin in order, `None` if none of them
+    /// changed it. A plugin that fails to rewrite is skipped.
+    fn rewrite_il(
+        &self,
+        module_id: ModuleID,
+        method_id: MethodDefToken,
+        il_body: &[u8],
+    ) -> Option<Vec<u8>> {
+        let mut rewritten: Option<Vec<u8>> = None;
+        for hosted in &self.state.plugins {
+            let current = rewritten.as_deref().unwrap_or(il_body);
+            match hosted.call("Plugin::rewrite_il", |plugin| {
+                plugin.rewrite_il(module_id, method_id, current)
+            }) {
+                Some(Ok(Some(body))) => rewritten = Some(body),
+                Some(Err(hr)) => warn!(
+                    "plugin {} failed to rewrite {method_id}, skipping it: {hr}",
+                    hosted.plugin.name()
+                ),
+                _ => {}
+            }
+        }
+        rewritten
+    }
+}
+
+impl<S: PluginSet> Clone for PluginHost<S> {
+    fn clone(&self) -> Self {
+        PluginHost {
+            state: self.state.clone(),
+            plugins: PhantomData,
+        }
+    }
+}
+
+impl<S: PluginSet> ClrProfiler for PluginHost<S> {
+    fn new() -> Self {
+        let set = S::new();
+        Self::with_plugins(*set.clsid(), set.plugins())
+    }
+
+    fn clsid(&self) -> &Uuid {
+        &self.state.clsid
+    }
+}
+
+event_callbacks!([S: PluginSet] PluginHost<S>);
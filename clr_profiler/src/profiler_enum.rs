@@ -0,0 +1,161 @@
+use crate::ffi::{
+    ComInterface, ComPtr, CorProfilerFunctionEnum, CorProfilerMethodEnum, CorProfilerModuleEnum,
+    CorProfilerObjectEnum, CorProfilerThreadEnum, HResult, ModuleID, ObjectID, ThreadID,
+    COR_PRF_FUNCTION, COR_PRF_METHOD, HRESULT, S_OK, ULONG,
+};
+use std::{fmt, mem::MaybeUninit, vec};
+
+/// The `ICorProfiler*Enum` interfaces, which only differ in what they
+/// enumerate.
+pub trait CorProfilerEnum: ComInterface {
+    type Item;
+
+    /// # Safety
+    ///
+    /// `items` must have room for `celt` items.
+    unsafe fn next(&self, celt: ULONG, items: *mut Self::Item, fetched: *mut ULONG) -> HRESULT;
+    /// # Safety
+    ///
+    /// `count` must be valid for writes.
+    unsafe fn get_count(&self, count: *mut ULONG) -> HRESULT;
+}
+
+macro_rules! cor_profiler_enum {
+    ($enum:ty, $item:ty) => {
+        impl CorProfilerEnum for $enum {
+            type Item = $item;
+
+            unsafe fn next(&self, celt: ULONG, items: *mut $item, fetched: *mut ULONG) -> HRESULT {
+                self.Next(celt, items, fetched)
+            }
+            unsafe fn get_count(&self, count: *mut ULONG) -> HRESULT {
+                self.GetCount(count)
+            }
+        }
+    };
+}
+
+cor_profiler_enum!(CorProfilerFunctionEnum, COR_PRF_FUNCTION);
+cor_profiler_enum!(CorProfilerMethodEnum, COR_PRF_METHOD);
+cor_profiler_enum!(CorProfilerModuleEnum, ModuleID);
+cor_profiler_enum!(CorProfilerObjectEnum, ObjectID);
+cor_profiler_enum!(CorProfilerThreadEnum, ThreadID);
+
+/// Iterates over an enumerator the runtime handed out, fetching items in
+/// batches and releasing the enumerator when dropped.
+///
+/// `GetCount`, asked when the enumerator is created, is only an upper bound
+/// on the length: if a batch fails or comes up short, iteration ends early
+/// and [`ProfilerEnum::error`] returns the failure; [`ProfilerEnum::into_vec`]
+/// does both at once.
+pub struct ProfilerEnum<E: CorProfilerEnum> {
+    enumerator: ComPtr<E>,
+    batch: vec::IntoIter<E::Item>,
+    remaining: usize,
+    error: Option<HResult>,
+}
+
+impl<E: CorProfilerEnum> ProfilerEnum<E> {
+    const BATCH_SIZE: usize = 64;
+
+    pub fn new(enumerator: ComPtr<E>) -> Result<Self, HResult> {
+        let remaining = get_count(&*enumerator)? as usize;
+        Ok(ProfilerEnum {
+            enumerator,
+            batch: Vec::new().into_iter(),
+            remaining,
+            error: None,
+        })
+    }
+
+    /// Takes over an enumerator returned through an out parameter.
+    ///
+    /// # Safety
+    ///
+    /// `enumerator` must be null or point to a live `E`.
+    pub unsafe fn from_raw(enumerator: *mut E) -> Result<Self, HResult> {
+        let enumerator = ComPtr::from_raw(enumerator).ok_or(HResult::E_POINTER)?;
+        Self::new(enumerator)
+    }
+
+    /// How many items the runtime says the enumeration has, as `GetCount`
+    /// returns it now. Items already iterated over are included, and fewer
+    /// may turn up.
+    pub fn count_hint(&self) -> Result<u32, HResult> {
+        get_count(&*self.enumerator)
+    }
+
+    /// The error that ended iteration early, if any.
+    pub fn error(&self) -> Option<HResult> {
+        self.error
+    }
+
+    /// Collects the remaining items, or the error that stopped the enumeration.
+    pub fn into_vec(mut self) -> Result<Vec<E::Item>, HResult> {
+        let items = self.by_ref().collect();
+        match self.error {
+            Some(hr) => Err(hr),
+            None => Ok(items),
+        }
+    }
+
+    fn fetch(&mut self) -> Result<(), HResult> {
+        let celt = self.remaining.min(Self::BATCH_SIZE);
+        let mut items = Vec::with_capacity(celt);
+        let mut fetched = 0;
+        let hr = unsafe {
+            self.enumerator
+                .next(celt as ULONG, items.as_mut_ptr(), &mut fetched)
+        };
+        if hr < 0 {
+            return Err(hr.into());
+        }
+        // S_FALSE with fewer items than asked for marks the end
+        let fetched = (fetched as usize).min(celt);
+        unsafe { items.set_len(fetched) };
+        self.batch = items.into_iter();
+        Ok(())
+    }
+}
+
+fn get_count<E: CorProfilerEnum>(enumerator: &E) -> Result<ULONG, HResult> {
+    let mut count = MaybeUninit::uninit();
+    let hr = unsafe { enumerator.get_count(count.as_mut_ptr()) };
+    match hr {
+        S_OK => Ok(unsafe { count.assume_init() }),
+        _ => Err(hr.into()),
+    }
+}
+
+impl<E: CorProfilerEnum> Iterator for ProfilerEnum<E> {
+    type Item = E::Item;
+
+    fn next(&mut self) -> Option<E::Item> {
+        if self.batch.len() == 0 && self.remaining > 0 {
+            if let Err(hr) = self.fetch() {
+                self.error = Some(hr);
+                self.remaining = 0;
+            } else if self.batch.len() == 0 {
+                // the enumeration ended before the count said it would
+                self.remaining = 0;
+            }
+        }
+        let item = self.batch.next()?;
+        self.remaining -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining))
+    }
+}
+
+impl<E: CorProfilerEnum> fmt::Debug for ProfilerEnum<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProfilerEnum")
+            .field("enumerator", &self.enumerator)
+            .field("remaining", &self.remaining)
+            .field("error", &self.error)
+            .finish()
+    }
+}
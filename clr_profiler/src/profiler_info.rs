@@ -1,49 +1,131 @@
 use crate::{
     ffi::{
-        int, mdFieldDef, mdMethodDef, mdTypeDef, AppDomainID, AssemblyID, ClassID, ContextID,
+        int, mdMethodDef, AppDomainID, AssemblyID, ClassID, ComPtr, ContextID,
         CorElementType, CorOpenFlags, CorProfilerFunctionEnum,
-        CorProfilerInfo as FFICorProfilerInfo, CorProfilerModuleEnum, CorProfilerThreadEnum,
+        CorProfilerInfo as FFICorProfilerInfo, CorProfilerModuleEnum, CorProfilerObjectEnum,
+        CorProfilerThreadEnum,
         FunctionEnter, FunctionEnter2, FunctionEnter3, FunctionEnter3WithInfo, FunctionID,
         FunctionIDMapper, FunctionIDMapper2, FunctionLeave, FunctionLeave2, FunctionLeave3,
         FunctionLeave3WithInfo, FunctionTailcall, FunctionTailcall2, FunctionTailcall3,
-        FunctionTailcall3WithInfo, IMetaDataImport2, MethodMalloc, ModuleID, ObjectID,
+        FunctionTailcall3WithInfo, ICorProfilerInfo, ICorProfilerInfo10, ICorProfilerInfo11,
+        ICorProfilerInfo12, ICorProfilerInfo13, ICorProfilerInfo14, ICorProfilerInfo2,
+        ICorProfilerInfo3, ICorProfilerInfo4, ICorProfilerInfo5, ICorProfilerInfo6,
+        ICorProfilerInfo7, ICorProfilerInfo8, ICorProfilerInfo9, IMetaDataImport2, MethodMalloc, ModuleID, ObjectID,
         ObjectReferenceCallback, ReJITID, StackSnapshotCallback, ThreadID, BOOL, BYTE,
         COR_DEBUG_IL_TO_NATIVE_MAP, COR_FIELD_OFFSET, COR_IL_MAP, COR_PRF_CODE_INFO,
         COR_PRF_ELT_INFO, COR_PRF_EX_CLAUSE_INFO, COR_PRF_FRAME_INFO, COR_PRF_GC_GENERATION_RANGE,
         COR_PRF_HIGH_MONITOR, COR_PRF_MODULE_FLAGS, COR_PRF_MONITOR, COR_PRF_REJIT_FLAGS,
-        COR_PRF_SNAPSHOT_INFO, COR_PRF_STATIC_TYPE, DWORD, GUID, HANDLE, HRESULT, LPCBYTE, S_OK,
-        UINT_PTR, ULONG, ULONG32, WCHAR,
+        COR_PRF_SNAPSHOT_INFO, COR_PRF_STATIC_TYPE, DWORD, GUID, HANDLE, HResult, HRESULT, LPCBYTE, S_OK,
+        UINT_PTR, ULONG, ULONG32, Unknown, WCHAR, COR_PRF_EVENTPIPE_LEVEL,
+        COR_PRF_EVENTPIPE_PARAM_DESC, COR_PRF_EVENTPIPE_PROVIDER_CONFIG, COR_PRF_EVENT_DATA,
+        COR_PRF_HANDLE_TYPE, COR_PRF_NONGC_HEAP_RANGE, EVENTPIPE_EVENT, EVENTPIPE_PROVIDER,
+        EVENTPIPE_SESSION, EventPipeProviderCallback, ObjectHandleID, UINT32, UINT64,
     },
+    metadata::{FieldDefToken, MethodDefToken, TypeDefToken},
+    metadata_import::string_from_buffer,
     AppDomainInfo, ArrayClassInfo, ArrayObjectInfo, AssemblyInfo, ClassInfo, ClassInfo2,
-    ClassLayout, CorProfilerInfo, CorProfilerInfo10, CorProfilerInfo2, CorProfilerInfo3,
+    ClassLayout, CorProfilerInfo, CorProfilerInfo10, CorProfilerInfo11, CorProfilerInfo12,
+    CorProfilerInfo13, CorProfilerInfo14, CorProfilerInfo2, CorProfilerInfo3,
     CorProfilerInfo4, CorProfilerInfo5, CorProfilerInfo6, CorProfilerInfo7, CorProfilerInfo8,
     CorProfilerInfo9, DynamicFunctionInfo, EnumNgenModuleMethodsInliningThisMethod, EventMask2,
+    EventPipeEventDefinition, EventPipeProviderConfig,
     FunctionAndRejit, FunctionEnter3Info, FunctionInfo, FunctionInfo2, FunctionLeave3Info,
-    FunctionTokenAndMetadata, IlFunctionBody, MetadataImport, ModuleInfo, ModuleInfo2, RuntimeInfo,
+    FunctionTokenAndMetadata, IlFunctionBody, MetadataImport, ModuleInfo, ModuleInfo2, ProfilerEnum, RuntimeInfo,
     StringLayout,
 };
-use std::{mem::MaybeUninit, ptr};
-use uuid::Uuid;
+use std::{convert::TryFrom, mem::MaybeUninit, ptr};
 use widestring::U16CString;
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct ProfilerInfo {
-    info: *const FFICorProfilerInfo,
+    info: ComPtr<FFICorProfilerInfo>,
+    level: u32,
 }
 
+// ICorProfilerInfo can be called from any managed or profiler thread.
+unsafe impl Send for ProfilerInfo {}
+unsafe impl Sync for ProfilerInfo {}
+
+// The IIDs of ICorProfilerInfo, ICorProfilerInfo2, ..., in order.
+const INFO_IIDS: [GUID; 14] = [
+    ICorProfilerInfo::IID,
+    ICorProfilerInfo2::IID,
+    ICorProfilerInfo3::IID,
+    ICorProfilerInfo4::IID,
+    ICorProfilerInfo5::IID,
+    ICorProfilerInfo6::IID,
+    ICorProfilerInfo7::IID,
+    ICorProfilerInfo8::IID,
+    ICorProfilerInfo9::IID,
+    ICorProfilerInfo10::IID,
+    ICorProfilerInfo11::IID,
+    ICorProfilerInfo12::IID,
+    ICorProfilerInfo13::IID,
+    ICorProfilerInfo14::IID,
+];
+
 impl ProfilerInfo {
-    pub fn new(cor_profiler_info: *const FFICorProfilerInfo) -> Self {
-        ProfilerInfo {
-            info: cor_profiler_info,
+    /// Queries the object the runtime passed to `Initialize` for the newest
+    /// `ICorProfilerInfo` version it implements.
+    pub fn new(cor_profiler_info: *const FFICorProfilerInfo) -> Result<Self, HResult> {
+        let unknown = unsafe { (cor_profiler_info as *const Unknown).as_ref() }
+            .ok_or(HResult::E_POINTER)?;
+        for (index, iid) in INFO_IIDS.iter().enumerate().rev() {
+            let mut info = ptr::null_mut();
+            let hr = unsafe { unknown.QueryInterface(iid, &mut info) };
+            if hr == S_OK {
+                let info = unsafe { ComPtr::from_raw(info as *mut FFICorProfilerInfo) }
+                    .ok_or(HResult::E_POINTER)?;
+                return Ok(ProfilerInfo {
+                    info,
+                    level: index as u32 + 1,
+                });
+            }
         }
+        Err(HResult::E_NOINTERFACE)
+    }
+    /// The newest `ICorProfilerInfo` version the runtime implements, e.g. 4
+    /// for `ICorProfilerInfo4`. Methods of newer versions fail with
+    /// [`HResult::unsupported_interface`] instead of calling past the end of
+    /// the vtable.
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+    pub fn supports(&self, level: u32) -> bool {
+        level <= self.level
+    }
+    /// Sets a copy of `il_body`, method header included, as the IL of a
+    /// method. The copy is made in memory from the module's IL allocator,
+    /// the only memory `SetILFunctionBody` takes.
+    pub fn replace_il_function_body(
+        &self,
+        module_id: ModuleID,
+        method_id: MethodDefToken,
+        il_body: &[u8],
+    ) -> Result<(), HResult> {
+        let malloc = self.get_il_function_body_allocator(module_id)?;
+        let size = ULONG::try_from(il_body.len()).or(Err(HResult::E_INVALIDARG))?;
+        let buffer = unsafe { malloc.Alloc(size) } as *mut BYTE;
+        if buffer.is_null() {
+            return Err(HResult::E_OUTOFMEMORY);
+        }
+        unsafe { ptr::copy_nonoverlapping(il_body.as_ptr(), buffer, il_body.len()) };
+        self.set_il_function_body(module_id, method_id, buffer)
     }
     fn info(&self) -> &FFICorProfilerInfo {
-        unsafe { self.info.as_ref().unwrap() }
+        &self.info
+    }
+    fn info_for(&self, level: u32) -> Result<&FFICorProfilerInfo, HResult> {
+        if self.supports(level) {
+            Ok(self.info())
+        } else {
+            Err(HResult::unsupported_interface(level))
+        }
     }
 }
 
 impl CorProfilerInfo for ProfilerInfo {
-    fn get_class_from_object(&self, object_id: ObjectID) -> Result<ClassID, HRESULT> {
+    fn get_class_from_object(&self, object_id: ObjectID) -> Result<ClassID, HResult> {
         let mut class_id = MaybeUninit::uninit();
         let hr = unsafe {
             self.info()
@@ -54,21 +136,21 @@ impl CorProfilerInfo for ProfilerInfo {
                 let class_id = unsafe { class_id.assume_init() };
                 Ok(class_id)
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
-    fn get_event_mask(&self) -> Result<COR_PRF_MONITOR, HRESULT> {
+    fn get_event_mask(&self) -> Result<COR_PRF_MONITOR, HResult> {
         let mut events = MaybeUninit::uninit();
         let hr = unsafe { self.info().GetEventMask(events.as_mut_ptr()) };
         match hr {
             S_OK => {
                 let events = unsafe { events.assume_init() };
-                Ok(COR_PRF_MONITOR::from_bits(events).unwrap())
+                Ok(COR_PRF_MONITOR::from_bits_truncate(events))
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
-    fn get_function_from_ip(&self, ip: LPCBYTE) -> Result<FunctionID, HRESULT> {
+    fn get_function_from_ip(&self, ip: LPCBYTE) -> Result<FunctionID, HResult> {
         let mut function_id = MaybeUninit::uninit();
         let hr = unsafe { self.info().GetFunctionFromIP(ip, function_id.as_mut_ptr()) };
         match hr {
@@ -76,10 +158,10 @@ impl CorProfilerInfo for ProfilerInfo {
                 let function_id = unsafe { function_id.assume_init() };
                 Ok(function_id)
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
-    fn get_handle_from_thread(&self, thread_id: ThreadID) -> Result<HANDLE, HRESULT> {
+    fn get_handle_from_thread(&self, thread_id: ThreadID) -> Result<HANDLE, HResult> {
         let mut handle = MaybeUninit::uninit();
         let hr = unsafe {
             self.info()
@@ -90,10 +172,10 @@ impl CorProfilerInfo for ProfilerInfo {
                 let handle = unsafe { handle.assume_init() };
                 Ok(handle)
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
-    fn is_array_class(&self, class_id: ClassID) -> Result<ArrayClassInfo, HRESULT> {
+    fn is_array_class(&self, class_id: ClassID) -> Result<ArrayClassInfo, HResult> {
         let mut element_type = MaybeUninit::uninit();
         let mut element_class_id = MaybeUninit::uninit();
         let mut rank = MaybeUninit::uninit();
@@ -108,13 +190,9 @@ impl CorProfilerInfo for ProfilerInfo {
         match hr {
             S_OK => {
                 let element_type = unsafe { element_type.assume_init() };
-                let element_class_id = unsafe {
-                    if !element_class_id.as_ptr().is_null() {
-                        Some(element_class_id.assume_init())
-                    } else {
-                        None
-                    }
-                };
+                // arrays of primitives have no element class
+                let element_class_id =
+                    Some(unsafe { element_class_id.assume_init() }).filter(|id| *id != 0);
                 let rank = unsafe { rank.assume_init() };
                 Ok(ArrayClassInfo {
                     element_type: CorElementType::from(element_type),
@@ -122,10 +200,10 @@ impl CorProfilerInfo for ProfilerInfo {
                     rank,
                 })
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
-    fn get_thread_info(&self, thread_id: ThreadID) -> Result<DWORD, HRESULT> {
+    fn get_thread_info(&self, thread_id: ThreadID) -> Result<DWORD, HResult> {
         let mut win_32_thread_id = MaybeUninit::uninit();
         let hr = unsafe {
             self.info()
@@ -136,10 +214,10 @@ impl CorProfilerInfo for ProfilerInfo {
                 let win_32_thread_id = unsafe { win_32_thread_id.assume_init() };
                 Ok(win_32_thread_id)
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
-    fn get_current_thread_id(&self) -> Result<ThreadID, HRESULT> {
+    fn get_current_thread_id(&self) -> Result<ThreadID, HResult> {
         let mut thread_id = MaybeUninit::uninit();
         let hr = unsafe { self.info().GetCurrentThreadID(thread_id.as_mut_ptr()) };
         match hr {
@@ -147,10 +225,10 @@ impl CorProfilerInfo for ProfilerInfo {
                 let thread_id = unsafe { thread_id.assume_init() };
                 Ok(thread_id)
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
-    fn get_class_id_info(&self, class_id: ClassID) -> Result<ClassInfo, HRESULT> {
+    fn get_class_id_info(&self, class_id: ClassID) -> Result<ClassInfo, HResult> {
         let mut module_id = MaybeUninit::uninit();
         let mut token = MaybeUninit::uninit();
         let hr = unsafe {
@@ -160,13 +238,13 @@ impl CorProfilerInfo for ProfilerInfo {
         match hr {
             S_OK => {
                 let module_id = unsafe { module_id.assume_init() };
-                let token = unsafe { token.assume_init() };
+                let token = TypeDefToken::from_raw_unchecked(unsafe { token.assume_init() });
                 Ok(ClassInfo { module_id, token })
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
-    fn get_function_info(&self, function_id: FunctionID) -> Result<FunctionInfo, HRESULT> {
+    fn get_function_info(&self, function_id: FunctionID) -> Result<FunctionInfo, HResult> {
         let mut class_id = MaybeUninit::uninit();
         let mut module_id = MaybeUninit::uninit();
         let mut token = MaybeUninit::uninit();
@@ -180,24 +258,25 @@ impl CorProfilerInfo for ProfilerInfo {
         };
         match hr {
             S_OK => {
-                let class_id = unsafe { class_id.assume_init() };
+                // generic functions and global functions have no class
+                let class_id = Some(unsafe { class_id.assume_init() }).filter(|id| *id != 0);
                 let module_id = unsafe { module_id.assume_init() };
-                let token = unsafe { token.assume_init() };
+                let token = MethodDefToken::from_raw_unchecked(unsafe { token.assume_init() });
                 Ok(FunctionInfo {
                     class_id,
                     module_id,
                     token,
                 })
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
-    fn set_event_mask(&self, events: COR_PRF_MONITOR) -> Result<(), HRESULT> {
+    fn set_event_mask(&self, events: COR_PRF_MONITOR) -> Result<(), HResult> {
         let events = events.bits();
         let hr = unsafe { self.info().SetEventMask(events) };
         match hr {
             S_OK => Ok(()),
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     fn set_enter_leave_function_hooks(
@@ -205,7 +284,7 @@ impl CorProfilerInfo for ProfilerInfo {
         func_enter: FunctionEnter,
         func_leave: FunctionLeave,
         func_tailcall: FunctionTailcall,
-    ) -> Result<(), HRESULT> {
+    ) -> Result<(), HResult> {
         let func_enter = func_enter as *const FunctionEnter;
         let func_leave = func_leave as *const FunctionLeave;
         let func_tailcall = func_tailcall as *const FunctionTailcall;
@@ -215,24 +294,24 @@ impl CorProfilerInfo for ProfilerInfo {
         };
         match hr {
             S_OK => Ok(()),
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
-    fn set_function_id_mapper(&self, func: FunctionIDMapper) -> Result<(), HRESULT> {
+    fn set_function_id_mapper(&self, func: FunctionIDMapper) -> Result<(), HResult> {
         let func = func as *const FunctionIDMapper;
         let hr = unsafe { self.info().SetFunctionIDMapper(func) };
         match hr {
             S_OK => Ok(()),
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     fn get_token_and_metadata_from_function(
         &self,
         function_id: FunctionID,
-    ) -> Result<FunctionTokenAndMetadata, HRESULT> {
+    ) -> Result<FunctionTokenAndMetadata, HResult> {
         let mut metadata_import = MaybeUninit::uninit();
         let mut token = MaybeUninit::uninit();
-        let riid = GUID::from(Uuid::parse_str("7DAC8207-D3AE-4C75-9B67-92801A497D44").unwrap()); // TODO: This needs to come from an IMetaDataImport implementation
+        let riid = IMetaDataImport2::IID;
         let hr = unsafe {
             self.info().GetTokenAndMetaDataFromFunction(
                 function_id,
@@ -244,33 +323,32 @@ impl CorProfilerInfo for ProfilerInfo {
 
         match hr {
             S_OK => {
-                let metadata_import = unsafe { metadata_import.assume_init() };
-                let token = unsafe { token.assume_init() };
+                let metadata_import = unsafe { ComPtr::from_raw(metadata_import.assume_init()) }
+                    .ok_or(HResult::E_POINTER)?;
+                let token = MethodDefToken::from_raw_unchecked(unsafe { token.assume_init() });
                 Ok(FunctionTokenAndMetadata {
                     token,
-                    metadata_import,
+                    metadata_import: MetadataImport::new(metadata_import),
                 })
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
-    fn get_module_info(&self, module_id: ModuleID) -> Result<ModuleInfo, HRESULT> {
-        let mut name_buffer_length = MaybeUninit::uninit();
+    fn get_module_info(&self, module_id: ModuleID) -> Result<ModuleInfo, HResult> {
+        let mut name_buffer_length = 0;
         unsafe {
             self.info().GetModuleInfo(
                 module_id,
                 ptr::null_mut(),
                 0,
-                name_buffer_length.as_mut_ptr(),
+                &mut name_buffer_length,
                 ptr::null_mut(),
                 ptr::null_mut(),
             )
         };
 
         let mut base_load_address = MaybeUninit::uninit();
-        let name_buffer_length = unsafe { name_buffer_length.assume_init() };
-        let mut name_buffer = Vec::<WCHAR>::with_capacity(name_buffer_length as usize);
-        unsafe { name_buffer.set_len(name_buffer_length as usize) };
+        let mut name_buffer = unsafe { zeroed_buffer::<WCHAR>(name_buffer_length) };
         let mut name_length = MaybeUninit::uninit();
         let mut assembly_id = MaybeUninit::uninit();
         let hr = unsafe {
@@ -285,10 +363,10 @@ impl CorProfilerInfo for ProfilerInfo {
         };
         match hr {
             S_OK => {
-                let base_load_address = unsafe { base_load_address.assume_init() };
-                let file_name = U16CString::from_vec_with_nul(name_buffer)
-                    .unwrap()
-                    .to_string_lossy();
+                // dynamic modules have no base address
+                let base_load_address = Some(unsafe { base_load_address.assume_init() })
+                    .filter(|address| !address.is_null());
+                let file_name = string_from_buffer(&name_buffer);
                 let assembly_id = unsafe { assembly_id.assume_init() };
                 Ok(ModuleInfo {
                     base_load_address,
@@ -296,14 +374,14 @@ impl CorProfilerInfo for ProfilerInfo {
                     assembly_id,
                 })
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     fn get_module_metadata(
         &self,
         module_id: ModuleID,
         open_flags: CorOpenFlags,
-    ) -> Result<MetadataImport, HRESULT> {
+    ) -> Result<MetadataImport, HResult> {
         let mut metadata_import = MaybeUninit::uninit();
         let open_flags = open_flags.bits();
         let riid = IMetaDataImport2::IID;
@@ -318,24 +396,24 @@ impl CorProfilerInfo for ProfilerInfo {
 
         match hr {
             S_OK => {
-                let metadata_import = unsafe { metadata_import.assume_init().as_mut().unwrap() };
-                let metadata_import = MetadataImport::new(metadata_import);
-                Ok(metadata_import)
+                let metadata_import = unsafe { ComPtr::from_raw(metadata_import.assume_init()) }
+                    .ok_or(HResult::E_POINTER)?;
+                Ok(MetadataImport::new(metadata_import))
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     fn get_il_function_body(
         &self,
         module_id: ModuleID,
-        method_id: mdMethodDef,
-    ) -> Result<IlFunctionBody, HRESULT> {
+        method_id: MethodDefToken,
+    ) -> Result<IlFunctionBody, HResult> {
         let mut method_header = MaybeUninit::uninit();
         let mut method_size = MaybeUninit::uninit();
         let hr = unsafe {
             self.info().GetILFunctionBody(
                 module_id,
-                method_id,
+                method_id.raw(),
                 method_header.as_mut_ptr(),
                 method_size.as_mut_ptr(),
             )
@@ -344,19 +422,22 @@ impl CorProfilerInfo for ProfilerInfo {
         match hr {
             S_OK => {
                 let method_header = unsafe { method_header.assume_init() };
+                if method_header.is_null() {
+                    return Err(HResult::E_POINTER);
+                }
                 let method_size = unsafe { method_size.assume_init() };
                 Ok(IlFunctionBody {
                     method_header,
                     method_size,
                 })
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     fn get_il_function_body_allocator(
         &self,
         module_id: ModuleID,
-    ) -> Result<&mut MethodMalloc, HRESULT> {
+    ) -> Result<ComPtr<MethodMalloc>, HResult> {
         let mut malloc = MaybeUninit::uninit();
         let hr = unsafe {
             self.info()
@@ -364,45 +445,39 @@ impl CorProfilerInfo for ProfilerInfo {
         };
 
         match hr {
-            S_OK => {
-                let malloc = unsafe { malloc.assume_init().as_mut().unwrap() };
-                Ok(malloc)
-            }
-            _ => Err(hr),
+            S_OK => unsafe { ComPtr::from_raw(malloc.assume_init()) }.ok_or(HResult::E_POINTER),
+            _ => Err(hr.into()),
         }
     }
     fn set_il_function_body(
         &self,
         module_id: ModuleID,
-        method_id: mdMethodDef,
+        method_id: MethodDefToken,
         new_il_method_header: LPCBYTE,
-    ) -> Result<(), HRESULT> {
+    ) -> Result<(), HResult> {
         let hr = unsafe {
             self.info()
-                .SetILFunctionBody(module_id, method_id, new_il_method_header)
+                .SetILFunctionBody(module_id, method_id.raw(), new_il_method_header)
         };
 
         match hr {
             S_OK => Ok(()),
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
-    fn get_app_domain_info(&self, app_domain_id: AppDomainID) -> Result<AppDomainInfo, HRESULT> {
+    fn get_app_domain_info(&self, app_domain_id: AppDomainID) -> Result<AppDomainInfo, HResult> {
         // get app domain name length, with zero-length buffer call
-        let mut name_buffer_length = MaybeUninit::uninit();
+        let mut name_buffer_length = 0;
         unsafe {
             self.info().GetAppDomainInfo(
                 app_domain_id,
                 0,
-                name_buffer_length.as_mut_ptr(),
+                &mut name_buffer_length,
                 ptr::null_mut(),
                 ptr::null_mut(),
             )
         };
-
-        let name_buffer_length = unsafe { name_buffer_length.assume_init() };
-        let mut name_buffer = Vec::<WCHAR>::with_capacity(name_buffer_length as usize);
-        unsafe { name_buffer.set_len(name_buffer_length as usize) };
+        let mut name_buffer = unsafe { zeroed_buffer::<WCHAR>(name_buffer_length) };
         let mut name_length = MaybeUninit::uninit();
         let mut process_id = MaybeUninit::uninit();
         let hr = unsafe {
@@ -416,32 +491,27 @@ impl CorProfilerInfo for ProfilerInfo {
         };
         match hr {
             S_OK => {
-                let name = U16CString::from_vec_with_nul(name_buffer)
-                    .unwrap()
-                    .to_string_lossy();
+                let name = string_from_buffer(&name_buffer);
                 let process_id = unsafe { process_id.assume_init() };
                 Ok(AppDomainInfo { name, process_id })
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
-    fn get_assembly_info(&self, assembly_id: AssemblyID) -> Result<AssemblyInfo, HRESULT> {
+    fn get_assembly_info(&self, assembly_id: AssemblyID) -> Result<AssemblyInfo, HResult> {
         // get assembly name length, with zero-length buffer call
-        let mut name_buffer_length = MaybeUninit::uninit();
+        let mut name_buffer_length = 0;
         unsafe {
             self.info().GetAssemblyInfo(
                 assembly_id,
                 0,
-                name_buffer_length.as_mut_ptr(),
+                &mut name_buffer_length,
                 ptr::null_mut(),
                 ptr::null_mut(),
                 ptr::null_mut(),
             )
         };
-
-        let name_buffer_length = unsafe { name_buffer_length.assume_init() };
-        let mut name_buffer = Vec::<WCHAR>::with_capacity(name_buffer_length as usize);
-        unsafe { name_buffer.set_len(name_buffer_length as usize) };
+        let mut name_buffer = unsafe { zeroed_buffer::<WCHAR>(name_buffer_length) };
         let mut name_length = MaybeUninit::uninit();
         let mut app_domain_id = MaybeUninit::uninit();
         let mut module_id = MaybeUninit::uninit();
@@ -457,9 +527,7 @@ impl CorProfilerInfo for ProfilerInfo {
         };
         match hr {
             S_OK => {
-                let name = U16CString::from_vec_with_nul(name_buffer)
-                    .unwrap()
-                    .to_string_lossy();
+                let name = string_from_buffer(&name_buffer);
                 let app_domain_id = unsafe { app_domain_id.assume_init() };
                 let module_id = unsafe { module_id.assume_init() };
                 Ok(AssemblyInfo {
@@ -468,14 +536,14 @@ impl CorProfilerInfo for ProfilerInfo {
                     module_id,
                 })
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
-    fn force_gc(&self) -> Result<(), HRESULT> {
+    fn force_gc(&self) -> Result<(), HResult> {
         let hr = unsafe { self.info().ForceGC() };
         match hr {
             S_OK => Ok(()),
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     fn set_il_instrumented_code_map(
@@ -483,7 +551,7 @@ impl CorProfilerInfo for ProfilerInfo {
         function_id: FunctionID,
         start_jit: bool,
         il_map_entries: &[COR_IL_MAP],
-    ) -> Result<(), HRESULT> {
+    ) -> Result<(), HResult> {
         let start_jit: BOOL = if start_jit { 1 } else { 0 };
         let hr = unsafe {
             self.info().SetILInstrumentedCodeMap(
@@ -495,10 +563,10 @@ impl CorProfilerInfo for ProfilerInfo {
         };
         match hr {
             S_OK => Ok(()),
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
-    fn get_thread_context(&self, thread_id: ThreadID) -> Result<ContextID, HRESULT> {
+    fn get_thread_context(&self, thread_id: ThreadID) -> Result<ContextID, HResult> {
         let mut context_id = MaybeUninit::uninit();
         let hr = unsafe {
             self.info()
@@ -510,38 +578,38 @@ impl CorProfilerInfo for ProfilerInfo {
                 let context_id = unsafe { context_id.assume_init() };
                 Ok(context_id)
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     fn get_il_to_native_mapping(
         &self,
         function_id: FunctionID,
-    ) -> Result<Vec<COR_DEBUG_IL_TO_NATIVE_MAP>, HRESULT> {
-        let mut map_buffer_length = MaybeUninit::uninit();
+    ) -> Result<Vec<COR_DEBUG_IL_TO_NATIVE_MAP>, HResult> {
+        let mut map_buffer_length = 0;
         unsafe {
             self.info().GetILToNativeMapping(
                 function_id,
                 0,
-                map_buffer_length.as_mut_ptr(),
+                &mut map_buffer_length,
                 ptr::null_mut(),
             )
         };
-
-        let map_buffer_length = unsafe { map_buffer_length.assume_init() };
-        let mut map = Vec::<COR_DEBUG_IL_TO_NATIVE_MAP>::with_capacity(map_buffer_length as usize);
-        unsafe { map.set_len(map_buffer_length as usize) };
-        let mut map_length = MaybeUninit::uninit();
+        let mut map = unsafe { zeroed_buffer::<COR_DEBUG_IL_TO_NATIVE_MAP>(map_buffer_length) };
+        let mut map_length = map_buffer_length;
         let hr = unsafe {
             self.info().GetILToNativeMapping(
                 function_id,
                 map_buffer_length,
-                map_length.as_mut_ptr(),
+                &mut map_length,
                 map.as_mut_ptr(),
             )
         };
         match hr {
-            S_OK => Ok(map),
-            _ => Err(hr),
+            S_OK => {
+                map.truncate(map_length as usize);
+                Ok(map)
+            }
+            _ => Err(hr.into()),
         }
     }
 }
@@ -555,10 +623,10 @@ impl CorProfilerInfo2 for ProfilerInfo {
         client_data: *const std::ffi::c_void, // TODO: How will ownership of this client_data work? Needs to leak, what about cleanup?
         context: *const BYTE, // TODO: This should be a Win32 CONTEXT structure. This is CPU-arch dependent though, how to implement? What about ownership?
         context_size: u32,
-    ) -> Result<(), HRESULT> {
+    ) -> Result<(), HResult> {
         let callback = callback as *const StackSnapshotCallback;
         let hr = unsafe {
-            self.info().DoStackSnapshot(
+            self.info_for(2)?.DoStackSnapshot(
                 thread,
                 callback,
                 info_flags as ULONG32,
@@ -569,7 +637,7 @@ impl CorProfilerInfo2 for ProfilerInfo {
         };
         match hr {
             S_OK => Ok(()),
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     fn set_enter_leave_function_hooks_2(
@@ -577,65 +645,64 @@ impl CorProfilerInfo2 for ProfilerInfo {
         func_enter: FunctionEnter2,
         func_leave: FunctionLeave2,
         func_tailcall: FunctionTailcall2,
-    ) -> Result<(), HRESULT> {
+    ) -> Result<(), HResult> {
         let func_enter = func_enter as *const FunctionEnter2;
         let func_leave = func_leave as *const FunctionLeave2;
         let func_tailcall = func_tailcall as *const FunctionTailcall2;
         let hr = unsafe {
-            self.info()
+            self.info_for(2)?
                 .SetEnterLeaveFunctionHooks2(func_enter, func_leave, func_tailcall)
         };
         match hr {
             S_OK => Ok(()),
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     fn get_function_info_2(
         &self,
         func_id: FunctionID,
         frame_info: COR_PRF_FRAME_INFO,
-    ) -> Result<FunctionInfo2, HRESULT> {
+    ) -> Result<FunctionInfo2, HResult> {
         // get type args length, with zero-length buffer call
-        let mut type_args_buffer_length = MaybeUninit::uninit();
+        let mut type_args_buffer_length = 0;
         unsafe {
-            self.info().GetFunctionInfo2(
+            self.info_for(2)?.GetFunctionInfo2(
                 func_id,
                 frame_info,
                 ptr::null_mut(),
                 ptr::null_mut(),
                 ptr::null_mut(),
                 0,
-                type_args_buffer_length.as_mut_ptr(),
+                &mut type_args_buffer_length,
                 ptr::null_mut(),
             )
         };
-
-        let type_args_buffer_length = unsafe { type_args_buffer_length.assume_init() };
-        let mut type_args = Vec::<ClassID>::with_capacity(type_args_buffer_length as usize);
-        unsafe { type_args.set_len(type_args_buffer_length as usize) };
+        let mut type_args = unsafe { zeroed_buffer::<ClassID>(type_args_buffer_length) };
 
         let mut class_id = MaybeUninit::uninit();
         let mut module_id = MaybeUninit::uninit();
         let mut token = MaybeUninit::uninit();
-        let mut type_args_length = MaybeUninit::uninit();
+        let mut type_args_length = type_args_buffer_length;
         let hr = unsafe {
-            self.info().GetFunctionInfo2(
+            self.info_for(2)?.GetFunctionInfo2(
                 func_id,
                 frame_info,
                 class_id.as_mut_ptr(),
                 module_id.as_mut_ptr(),
                 token.as_mut_ptr(),
                 type_args_buffer_length,
-                type_args_length.as_mut_ptr(),
+                &mut type_args_length,
                 type_args.as_mut_ptr(),
             )
         };
 
         match hr {
             S_OK => {
-                let class_id = unsafe { class_id.assume_init() };
+                // generic functions and global functions have no class
+                let class_id = Some(unsafe { class_id.assume_init() }).filter(|id| *id != 0);
                 let module_id = unsafe { module_id.assume_init() };
-                let token = unsafe { token.assume_init() };
+                let token = MethodDefToken::from_raw_unchecked(unsafe { token.assume_init() });
+                type_args.truncate(type_args_length as usize);
                 Ok(FunctionInfo2 {
                     class_id,
                     module_id,
@@ -643,35 +710,31 @@ impl CorProfilerInfo2 for ProfilerInfo {
                     type_args,
                 })
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
-    fn get_class_layout(&self, class_id: ClassID) -> Result<ClassLayout, HRESULT> {
+    fn get_class_layout(&self, class_id: ClassID) -> Result<ClassLayout, HResult> {
         // get field offset length, with zero-length buffer call
-        let mut field_offset_buffer_length = MaybeUninit::uninit();
+        let mut field_offset_buffer_length = 0;
         unsafe {
-            self.info().GetClassLayout(
+            self.info_for(2)?.GetClassLayout(
                 class_id,
                 ptr::null_mut(),
                 0,
-                field_offset_buffer_length.as_mut_ptr(),
+                &mut field_offset_buffer_length,
                 ptr::null_mut(),
             )
         };
+        let mut field_offset = unsafe { zeroed_buffer::<COR_FIELD_OFFSET>(field_offset_buffer_length) };
 
-        let field_offset_buffer_length = unsafe { field_offset_buffer_length.assume_init() };
-        let mut field_offset =
-            Vec::<COR_FIELD_OFFSET>::with_capacity(field_offset_buffer_length as usize);
-        unsafe { field_offset.set_len(field_offset_buffer_length as usize) };
-
-        let mut field_offset_length = MaybeUninit::uninit();
+        let mut field_offset_length = field_offset_buffer_length;
         let mut class_size_bytes = MaybeUninit::uninit();
         let hr = unsafe {
-            self.info().GetClassLayout(
+            self.info_for(2)?.GetClassLayout(
                 class_id,
                 field_offset.as_mut_ptr(),
                 field_offset_buffer_length,
-                field_offset_length.as_mut_ptr(),
+                &mut field_offset_length,
                 class_size_bytes.as_mut_ptr(),
             )
         };
@@ -679,45 +742,43 @@ impl CorProfilerInfo2 for ProfilerInfo {
         match hr {
             S_OK => {
                 let class_size_bytes = unsafe { class_size_bytes.assume_init() };
+                field_offset.truncate(field_offset_length as usize);
                 Ok(ClassLayout {
                     field_offset,
                     class_size_bytes,
                 })
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
-    fn get_class_id_info_2(&self, class_id: ClassID) -> Result<ClassInfo2, HRESULT> {
+    fn get_class_id_info_2(&self, class_id: ClassID) -> Result<ClassInfo2, HResult> {
         // get type args length, with zero-length buffer call
-        let mut type_args_buffer_length = MaybeUninit::uninit();
+        let mut type_args_buffer_length = 0;
         unsafe {
-            self.info().GetClassIDInfo2(
+            self.info_for(2)?.GetClassIDInfo2(
                 class_id,
                 ptr::null_mut(),
                 ptr::null_mut(),
                 ptr::null_mut(),
                 0,
-                type_args_buffer_length.as_mut_ptr(),
+                &mut type_args_buffer_length,
                 ptr::null_mut(),
             )
         };
-
-        let type_args_buffer_length = unsafe { type_args_buffer_length.assume_init() };
-        let mut type_args = Vec::<ClassID>::with_capacity(type_args_buffer_length as usize);
-        unsafe { type_args.set_len(type_args_buffer_length as usize) };
+        let mut type_args = unsafe { zeroed_buffer::<ClassID>(type_args_buffer_length) };
 
         let mut module_id = MaybeUninit::uninit();
         let mut token = MaybeUninit::uninit();
         let mut parent_class_id = MaybeUninit::uninit();
-        let mut type_args_length = MaybeUninit::uninit();
+        let mut type_args_length = type_args_buffer_length;
         let hr = unsafe {
-            self.info().GetClassIDInfo2(
+            self.info_for(2)?.GetClassIDInfo2(
                 class_id,
                 module_id.as_mut_ptr(),
                 token.as_mut_ptr(),
                 parent_class_id.as_mut_ptr(),
                 type_args_buffer_length,
-                type_args_length.as_mut_ptr(),
+                &mut type_args_length,
                 type_args.as_mut_ptr(),
             )
         };
@@ -725,8 +786,11 @@ impl CorProfilerInfo2 for ProfilerInfo {
         match hr {
             S_OK => {
                 let module_id = unsafe { module_id.assume_init() };
-                let token = unsafe { token.assume_init() };
-                let parent_class_id = unsafe { parent_class_id.assume_init() };
+                let token = TypeDefToken::from_raw_unchecked(unsafe { token.assume_init() });
+                // System.Object has no parent
+                let parent_class_id =
+                    Some(unsafe { parent_class_id.assume_init() }).filter(|id| *id != 0);
+                type_args.truncate(type_args_length as usize);
                 Ok(ClassInfo2 {
                     module_id,
                     token,
@@ -734,55 +798,54 @@ impl CorProfilerInfo2 for ProfilerInfo {
                     type_args,
                 })
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
-    fn get_code_info_2(&self, function_id: FunctionID) -> Result<Vec<COR_PRF_CODE_INFO>, HRESULT> {
-        let mut code_info_buffer_length = MaybeUninit::uninit();
+    fn get_code_info_2(&self, function_id: FunctionID) -> Result<Vec<COR_PRF_CODE_INFO>, HResult> {
+        let mut code_info_buffer_length = 0;
         unsafe {
-            self.info().GetCodeInfo2(
+            self.info_for(2)?.GetCodeInfo2(
                 function_id,
                 0,
-                code_info_buffer_length.as_mut_ptr(),
+                &mut code_info_buffer_length,
                 ptr::null_mut(),
             )
         };
+        let mut code_info = unsafe { zeroed_buffer::<COR_PRF_CODE_INFO>(code_info_buffer_length) };
 
-        let code_info_buffer_length = unsafe { code_info_buffer_length.assume_init() };
-        let mut code_info =
-            Vec::<COR_PRF_CODE_INFO>::with_capacity(code_info_buffer_length as usize);
-        unsafe { code_info.set_len(code_info_buffer_length as usize) };
-
-        let mut code_info_length = MaybeUninit::uninit();
+        let mut code_info_length = code_info_buffer_length;
         let hr = unsafe {
-            self.info().GetCodeInfo2(
+            self.info_for(2)?.GetCodeInfo2(
                 function_id,
                 code_info_buffer_length,
-                code_info_length.as_mut_ptr(),
+                &mut code_info_length,
                 code_info.as_mut_ptr(),
             )
         };
 
         match hr {
-            S_OK => Ok(code_info),
-            _ => Err(hr),
+            S_OK => {
+                code_info.truncate(code_info_length as usize);
+                Ok(code_info)
+            }
+            _ => Err(hr.into()),
         }
     }
     fn get_class_from_token_and_type_args(
         &self,
         module_id: ModuleID,
-        type_def: mdTypeDef,
+        type_def: TypeDefToken,
         type_args: Option<&[ClassID]>,
-    ) -> Result<ClassID, HRESULT> {
+    ) -> Result<ClassID, HResult> {
         let mut class_id = MaybeUninit::uninit();
         let (type_args, type_args_length) = match type_args {
             Some(args) => (args.as_ptr(), args.len()),
             None => (ptr::null(), 0),
         };
         let hr = unsafe {
-            self.info().GetClassFromTokenAndTypeArgs(
+            self.info_for(2)?.GetClassFromTokenAndTypeArgs(
                 module_id,
-                type_def,
+                type_def.raw(),
                 type_args_length as ULONG32,
                 type_args,
                 class_id.as_mut_ptr(),
@@ -794,25 +857,25 @@ impl CorProfilerInfo2 for ProfilerInfo {
                 let class_id = unsafe { class_id.assume_init() };
                 Ok(class_id)
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     fn get_function_from_token_and_type_args(
         &self,
         module_id: ModuleID,
-        func_def: mdMethodDef,
+        func_def: MethodDefToken,
         class_id: ClassID,
         type_args: Option<&[ClassID]>,
-    ) -> Result<FunctionID, HRESULT> {
+    ) -> Result<FunctionID, HResult> {
         let mut function_id = MaybeUninit::uninit();
         let (type_args, type_args_length) = match type_args {
             Some(args) => (args.as_ptr(), args.len()),
             None => (ptr::null(), 0),
         };
         let hr = unsafe {
-            self.info().GetFunctionFromTokenAndTypeArgs(
+            self.info_for(2)?.GetFunctionFromTokenAndTypeArgs(
                 module_id,
-                func_def,
+                func_def.raw(),
                 class_id,
                 type_args_length as ULONG32,
                 type_args,
@@ -825,22 +888,35 @@ impl CorProfilerInfo2 for ProfilerInfo {
                 let function_id = unsafe { function_id.assume_init() };
                 Ok(function_id)
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
+        }
+    }
+    fn enum_module_frozen_objects(
+        &self,
+        module_id: ModuleID,
+    ) -> Result<ProfilerEnum<CorProfilerObjectEnum>, HResult> {
+        let mut object_enum = MaybeUninit::uninit();
+        let hr = unsafe {
+            self.info_for(2)?
+                .EnumModuleFrozenObjects(module_id, object_enum.as_mut_ptr())
+        };
+
+        match hr {
+            S_OK => unsafe { ProfilerEnum::from_raw(object_enum.assume_init()) },
+            _ => Err(hr.into()),
         }
     }
     fn get_array_object_info(
         &self,
         object_id: ObjectID,
         dimensions: u32,
-    ) -> Result<ArrayObjectInfo, HRESULT> {
-        let mut dimension_sizes = Vec::<ULONG32>::with_capacity(dimensions as usize);
-        unsafe { dimension_sizes.set_len(dimensions as usize) };
-        let mut dimension_lower_bounds = Vec::<int>::with_capacity(dimensions as usize);
-        unsafe { dimension_lower_bounds.set_len(dimensions as usize) };
+    ) -> Result<ArrayObjectInfo, HResult> {
+        let mut dimension_sizes = unsafe { zeroed_buffer::<ULONG32>(dimensions) };
+        let mut dimension_lower_bounds = unsafe { zeroed_buffer::<int>(dimensions) };
 
         let mut data = MaybeUninit::uninit();
         let hr = unsafe {
-            self.info().GetArrayObjectInfo(
+            self.info_for(2)?.GetArrayObjectInfo(
                 object_id,
                 dimensions,
                 dimension_sizes.as_mut_ptr(),
@@ -851,20 +927,21 @@ impl CorProfilerInfo2 for ProfilerInfo {
 
         match hr {
             S_OK => {
-                let data = unsafe { data.assume_init() };
+                // empty arrays may have no data
+                let data = Some(unsafe { data.assume_init() }).filter(|data| !data.is_null());
                 Ok(ArrayObjectInfo {
                     dimension_sizes,
                     dimension_lower_bounds,
                     data,
                 })
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
-    fn get_box_class_layout(&self, class_id: ClassID) -> Result<u32, HRESULT> {
+    fn get_box_class_layout(&self, class_id: ClassID) -> Result<u32, HResult> {
         let mut buffer_offset = MaybeUninit::uninit();
         let hr = unsafe {
-            self.info()
+            self.info_for(2)?
                 .GetBoxClassLayout(class_id, buffer_offset.as_mut_ptr())
         };
 
@@ -873,13 +950,13 @@ impl CorProfilerInfo2 for ProfilerInfo {
                 let buffer_offset = unsafe { buffer_offset.assume_init() };
                 Ok(buffer_offset)
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
-    fn get_thread_app_domain(&self, thread_id: ThreadID) -> Result<AppDomainID, HRESULT> {
+    fn get_thread_app_domain(&self, thread_id: ThreadID) -> Result<AppDomainID, HResult> {
         let mut app_domain_id = MaybeUninit::uninit();
         let hr = unsafe {
-            self.info()
+            self.info_for(2)?
                 .GetThreadAppDomain(thread_id, app_domain_id.as_mut_ptr())
         };
 
@@ -888,18 +965,18 @@ impl CorProfilerInfo2 for ProfilerInfo {
                 let app_domain_id = unsafe { app_domain_id.assume_init() };
                 Ok(app_domain_id)
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     fn get_rva_static_address(
         &self,
         class_id: ClassID,
-        field_token: mdFieldDef,
-    ) -> Result<*const std::ffi::c_void, HRESULT> {
+        field_token: FieldDefToken,
+    ) -> Result<*const std::ffi::c_void, HResult> {
         let mut address = MaybeUninit::uninit();
         let hr = unsafe {
-            self.info()
-                .GetRVAStaticAddress(class_id, field_token, address.as_mut_ptr())
+            self.info_for(2)?
+                .GetRVAStaticAddress(class_id, field_token.raw(), address.as_mut_ptr())
         };
 
         match hr {
@@ -907,20 +984,20 @@ impl CorProfilerInfo2 for ProfilerInfo {
                 let address = unsafe { address.assume_init() };
                 Ok(address)
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     fn get_app_domain_static_address(
         &self,
         class_id: ClassID,
-        field_token: mdFieldDef,
+        field_token: FieldDefToken,
         app_domain_id: AppDomainID,
-    ) -> Result<*const std::ffi::c_void, HRESULT> {
+    ) -> Result<*const std::ffi::c_void, HResult> {
         let mut address = MaybeUninit::uninit();
         let hr = unsafe {
-            self.info().GetAppDomainStaticAddress(
+            self.info_for(2)?.GetAppDomainStaticAddress(
                 class_id,
-                field_token,
+                field_token.raw(),
                 app_domain_id,
                 address.as_mut_ptr(),
             )
@@ -931,20 +1008,20 @@ impl CorProfilerInfo2 for ProfilerInfo {
                 let address = unsafe { address.assume_init() };
                 Ok(address)
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     fn get_thread_static_address(
         &self,
         class_id: ClassID,
-        field_token: mdFieldDef,
+        field_token: FieldDefToken,
         thread_id: ThreadID,
-    ) -> Result<*const std::ffi::c_void, HRESULT> {
+    ) -> Result<*const std::ffi::c_void, HResult> {
         let mut address = MaybeUninit::uninit();
         let hr = unsafe {
-            self.info().GetThreadStaticAddress(
+            self.info_for(2)?.GetThreadStaticAddress(
                 class_id,
-                field_token,
+                field_token.raw(),
                 thread_id,
                 address.as_mut_ptr(),
             )
@@ -955,20 +1032,20 @@ impl CorProfilerInfo2 for ProfilerInfo {
                 let address = unsafe { address.assume_init() };
                 Ok(address)
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     fn get_context_static_address(
         &self,
         class_id: ClassID,
-        field_token: mdFieldDef,
+        field_token: FieldDefToken,
         context_id: ContextID,
-    ) -> Result<*const std::ffi::c_void, HRESULT> {
+    ) -> Result<*const std::ffi::c_void, HResult> {
         let mut address = MaybeUninit::uninit();
         let hr = unsafe {
-            self.info().GetContextStaticAddress(
+            self.info_for(2)?.GetContextStaticAddress(
                 class_id,
-                field_token,
+                field_token.raw(),
                 context_id,
                 address.as_mut_ptr(),
             )
@@ -979,18 +1056,18 @@ impl CorProfilerInfo2 for ProfilerInfo {
                 let address = unsafe { address.assume_init() };
                 Ok(address)
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     fn get_static_field_info(
         &self,
         class_id: ClassID,
-        field_token: mdFieldDef,
-    ) -> Result<COR_PRF_STATIC_TYPE, HRESULT> {
+        field_token: FieldDefToken,
+    ) -> Result<COR_PRF_STATIC_TYPE, HResult> {
         let mut field_info = MaybeUninit::uninit();
         let hr = unsafe {
-            self.info()
-                .GetStaticFieldInfo(class_id, field_token, field_info.as_mut_ptr())
+            self.info_for(2)?
+                .GetStaticFieldInfo(class_id, field_token.raw(), field_info.as_mut_ptr())
         };
 
         match hr {
@@ -998,42 +1075,41 @@ impl CorProfilerInfo2 for ProfilerInfo {
                 let field_info = unsafe { field_info.assume_init() };
                 Ok(field_info)
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
-    fn get_generation_bounds(&self) -> Result<Vec<COR_PRF_GC_GENERATION_RANGE>, HRESULT> {
-        let mut ranges_buffer_length = MaybeUninit::uninit();
+    fn get_generation_bounds(&self) -> Result<Vec<COR_PRF_GC_GENERATION_RANGE>, HResult> {
+        let mut ranges_buffer_length = 0;
         unsafe {
-            self.info()
-                .GetGenerationBounds(0, ranges_buffer_length.as_mut_ptr(), ptr::null_mut())
+            self.info_for(2)?
+                .GetGenerationBounds(0, &mut ranges_buffer_length, ptr::null_mut())
         };
+        let mut ranges = unsafe { zeroed_buffer::<COR_PRF_GC_GENERATION_RANGE>(ranges_buffer_length) };
 
-        let ranges_buffer_length = unsafe { ranges_buffer_length.assume_init() };
-        let mut ranges =
-            Vec::<COR_PRF_GC_GENERATION_RANGE>::with_capacity(ranges_buffer_length as usize);
-        unsafe { ranges.set_len(ranges_buffer_length as usize) };
-
-        let mut ranges_length = MaybeUninit::uninit();
+        let mut ranges_length = ranges_buffer_length;
         let hr = unsafe {
-            self.info().GetGenerationBounds(
+            self.info_for(2)?.GetGenerationBounds(
                 ranges_buffer_length,
-                ranges_length.as_mut_ptr(),
+                &mut ranges_length,
                 ranges.as_mut_ptr(),
             )
         };
 
         match hr {
-            S_OK => Ok(ranges),
-            _ => Err(hr),
+            S_OK => {
+                ranges.truncate(ranges_length as usize);
+                Ok(ranges)
+            }
+            _ => Err(hr.into()),
         }
     }
     fn get_object_generation(
         &self,
         object_id: ObjectID,
-    ) -> Result<COR_PRF_GC_GENERATION_RANGE, HRESULT> {
+    ) -> Result<COR_PRF_GC_GENERATION_RANGE, HResult> {
         let mut range = MaybeUninit::uninit();
         let hr = unsafe {
-            self.info()
+            self.info_for(2)?
                 .GetObjectGeneration(object_id, range.as_mut_ptr())
         };
 
@@ -1042,13 +1118,13 @@ impl CorProfilerInfo2 for ProfilerInfo {
                 let range = unsafe { range.assume_init() };
                 Ok(range)
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
-    fn get_notified_exception_clause_info(&self) -> Result<COR_PRF_EX_CLAUSE_INFO, HRESULT> {
+    fn get_notified_exception_clause_info(&self) -> Result<COR_PRF_EX_CLAUSE_INFO, HResult> {
         let mut exception_clause_info = MaybeUninit::uninit();
         let hr = unsafe {
-            self.info()
+            self.info_for(2)?
                 .GetNotifiedExceptionClauseInfo(exception_clause_info.as_mut_ptr())
         };
 
@@ -1057,56 +1133,53 @@ impl CorProfilerInfo2 for ProfilerInfo {
                 let exception_clause_info = unsafe { exception_clause_info.assume_init() };
                 Ok(exception_clause_info)
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
 }
 
 impl CorProfilerInfo3 for ProfilerInfo {
-    fn enum_jited_functions(&self) -> Result<&mut CorProfilerFunctionEnum, HRESULT> {
+    fn enum_jited_functions(&self) -> Result<ProfilerEnum<CorProfilerFunctionEnum>, HResult> {
         let mut function_enum = MaybeUninit::uninit();
-        let hr = unsafe { self.info().EnumJITedFunctions(function_enum.as_mut_ptr()) };
+        let hr = unsafe { self.info_for(3)?.EnumJITedFunctions(function_enum.as_mut_ptr()) };
 
         match hr {
-            S_OK => {
-                let function_enum = unsafe { function_enum.assume_init().as_mut().unwrap() };
-                Ok(function_enum)
-            }
-            _ => Err(hr),
+            S_OK => unsafe { ProfilerEnum::from_raw(function_enum.assume_init()) },
+            _ => Err(hr.into()),
         }
     }
     fn request_profiler_detach(
         &self,
         expected_completion_milliseconds: u32,
-    ) -> Result<(), HRESULT> {
+    ) -> Result<(), HResult> {
         let hr = unsafe {
-            self.info()
+            self.info_for(3)?
                 .RequestProfilerDetach(expected_completion_milliseconds)
         };
 
         match hr {
             S_OK => Ok(()),
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     fn set_function_id_mapper_2(
         &self,
         func: FunctionIDMapper2,
         client_data: *const std::ffi::c_void,
-    ) -> Result<(), HRESULT> {
+    ) -> Result<(), HResult> {
         let func = func as *const FunctionIDMapper2;
-        let hr = unsafe { self.info().SetFunctionIDMapper2(func, client_data) };
+        let hr = unsafe { self.info_for(3)?.SetFunctionIDMapper2(func, client_data) };
 
         match hr {
             S_OK => Ok(()),
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
-    fn get_string_layout_2(&self) -> Result<StringLayout, HRESULT> {
+    fn get_string_layout_2(&self) -> Result<StringLayout, HResult> {
         let mut string_length_offset = MaybeUninit::uninit();
         let mut buffer_offset = MaybeUninit::uninit();
         let hr = unsafe {
-            self.info().GetStringLayout2(
+            self.info_for(3)?.GetStringLayout2(
                 string_length_offset.as_mut_ptr(),
                 buffer_offset.as_mut_ptr(),
             )
@@ -1121,7 +1194,7 @@ impl CorProfilerInfo3 for ProfilerInfo {
                     buffer_offset,
                 })
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     fn set_enter_leave_function_hooks_3(
@@ -1129,17 +1202,17 @@ impl CorProfilerInfo3 for ProfilerInfo {
         func_enter_3: FunctionEnter3,
         func_leave_3: FunctionLeave3,
         func_tailcall_3: FunctionTailcall3,
-    ) -> Result<(), HRESULT> {
+    ) -> Result<(), HResult> {
         let func_enter_3 = func_enter_3 as *const FunctionEnter3;
         let func_leave_3 = func_leave_3 as *const FunctionLeave3;
         let func_tailcall_3 = func_tailcall_3 as *const FunctionTailcall3;
         let hr = unsafe {
-            self.info()
+            self.info_for(3)?
                 .SetEnterLeaveFunctionHooks3(func_enter_3, func_leave_3, func_tailcall_3)
         };
         match hr {
             S_OK => Ok(()),
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     fn set_enter_leave_function_hooks_3_with_info(
@@ -1147,13 +1220,13 @@ impl CorProfilerInfo3 for ProfilerInfo {
         func_enter_3_with_info: FunctionEnter3WithInfo,
         func_leave_3_with_info: FunctionLeave3WithInfo,
         func_tailcall_3_with_info: FunctionTailcall3WithInfo,
-    ) -> Result<(), HRESULT> {
+    ) -> Result<(), HResult> {
         let func_enter_3_with_info = func_enter_3_with_info as *const FunctionEnter3WithInfo;
         let func_leave_3_with_info = func_leave_3_with_info as *const FunctionLeave3WithInfo;
         let func_tailcall_3_with_info =
             func_tailcall_3_with_info as *const FunctionTailcall3WithInfo;
         let hr = unsafe {
-            self.info().SetEnterLeaveFunctionHooks3WithInfo(
+            self.info_for(3)?.SetEnterLeaveFunctionHooks3WithInfo(
                 func_enter_3_with_info,
                 func_leave_3_with_info,
                 func_tailcall_3_with_info,
@@ -1161,19 +1234,19 @@ impl CorProfilerInfo3 for ProfilerInfo {
         };
         match hr {
             S_OK => Ok(()),
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     fn get_function_enter_3_info(
         &self,
         function_id: FunctionID,
         elt_info: COR_PRF_ELT_INFO,
-    ) -> Result<FunctionEnter3Info, HRESULT> {
+    ) -> Result<FunctionEnter3Info, HResult> {
         let mut frame_info = MaybeUninit::uninit();
         let mut argument_info_length = MaybeUninit::uninit();
         let mut argument_info = MaybeUninit::uninit();
         let hr = unsafe {
-            self.info().GetFunctionEnter3Info(
+            self.info_for(3)?.GetFunctionEnter3Info(
                 function_id,
                 elt_info,
                 frame_info.as_mut_ptr(),
@@ -1194,18 +1267,18 @@ impl CorProfilerInfo3 for ProfilerInfo {
                     argument_info,
                 })
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     fn get_function_leave_3_info(
         &self,
         function_id: FunctionID,
         elt_info: COR_PRF_ELT_INFO,
-    ) -> Result<FunctionLeave3Info, HRESULT> {
+    ) -> Result<FunctionLeave3Info, HResult> {
         let mut frame_info = MaybeUninit::uninit();
         let mut retval_range = MaybeUninit::uninit();
         let hr = unsafe {
-            self.info().GetFunctionLeave3Info(
+            self.info_for(3)?.GetFunctionLeave3Info(
                 function_id,
                 elt_info,
                 frame_info.as_mut_ptr(),
@@ -1222,17 +1295,17 @@ impl CorProfilerInfo3 for ProfilerInfo {
                     retval_range,
                 })
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     fn get_function_tailcall_3_info(
         &self,
         function_id: FunctionID,
         elt_info: COR_PRF_ELT_INFO,
-    ) -> Result<COR_PRF_FRAME_INFO, HRESULT> {
+    ) -> Result<COR_PRF_FRAME_INFO, HResult> {
         let mut frame_info = MaybeUninit::uninit();
         let hr = unsafe {
-            self.info()
+            self.info_for(3)?
                 .GetFunctionTailcall3Info(function_id, elt_info, frame_info.as_mut_ptr())
         };
 
@@ -1241,25 +1314,22 @@ impl CorProfilerInfo3 for ProfilerInfo {
                 let frame_info = unsafe { frame_info.assume_init() };
                 Ok(frame_info)
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
-    fn enum_modules(&self) -> Result<&mut CorProfilerModuleEnum, HRESULT> {
+    fn enum_modules(&self) -> Result<ProfilerEnum<CorProfilerModuleEnum>, HResult> {
         let mut module_enum = MaybeUninit::uninit();
-        let hr = unsafe { self.info().EnumModules(module_enum.as_mut_ptr()) };
+        let hr = unsafe { self.info_for(3)?.EnumModules(module_enum.as_mut_ptr()) };
 
         match hr {
-            S_OK => {
-                let module_enum = unsafe { module_enum.assume_init().as_mut().unwrap() };
-                Ok(module_enum)
-            }
-            _ => Err(hr),
+            S_OK => unsafe { ProfilerEnum::from_raw(module_enum.assume_init()) },
+            _ => Err(hr.into()),
         }
     }
-    fn get_runtime_information(&self) -> Result<RuntimeInfo, HRESULT> {
-        let mut version_string_buffer_length = MaybeUninit::uninit();
+    fn get_runtime_information(&self) -> Result<RuntimeInfo, HResult> {
+        let mut version_string_buffer_length = 0;
         unsafe {
-            self.info().GetRuntimeInformation(
+            self.info_for(3)?.GetRuntimeInformation(
                 ptr::null_mut(),
                 ptr::null_mut(),
                 ptr::null_mut(),
@@ -1267,15 +1337,11 @@ impl CorProfilerInfo3 for ProfilerInfo {
                 ptr::null_mut(),
                 ptr::null_mut(),
                 0,
-                version_string_buffer_length.as_mut_ptr(),
+                &mut version_string_buffer_length,
                 ptr::null_mut(),
             )
         };
-
-        let version_string_buffer_length = unsafe { version_string_buffer_length.assume_init() };
-        let mut version_string_buffer =
-            Vec::<WCHAR>::with_capacity(version_string_buffer_length as usize);
-        unsafe { version_string_buffer.set_len(version_string_buffer_length as usize) };
+        let mut version_string_buffer = unsafe { zeroed_buffer::<WCHAR>(version_string_buffer_length) };
 
         let mut clr_instance_id = MaybeUninit::uninit();
         let mut runtime_type = MaybeUninit::uninit();
@@ -1285,7 +1351,7 @@ impl CorProfilerInfo3 for ProfilerInfo {
         let mut qfe_version = MaybeUninit::uninit();
         let mut version_string_length = MaybeUninit::uninit();
         let hr = unsafe {
-            self.info().GetRuntimeInformation(
+            self.info_for(3)?.GetRuntimeInformation(
                 clr_instance_id.as_mut_ptr(),
                 runtime_type.as_mut_ptr(),
                 major_version.as_mut_ptr(),
@@ -1306,9 +1372,7 @@ impl CorProfilerInfo3 for ProfilerInfo {
                 let minor_version = unsafe { minor_version.assume_init() };
                 let build_number = unsafe { build_number.assume_init() };
                 let qfe_version = unsafe { qfe_version.assume_init() };
-                let version_string = U16CString::from_vec_with_nul(version_string_buffer)
-                    .unwrap()
-                    .to_string_lossy();
+                let version_string = string_from_buffer(&version_string_buffer);
                 Ok(RuntimeInfo {
                     clr_instance_id,
                     runtime_type,
@@ -1319,21 +1383,21 @@ impl CorProfilerInfo3 for ProfilerInfo {
                     version_string,
                 })
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     fn get_thread_static_address_2(
         &self,
         class_id: ClassID,
-        field_token: mdFieldDef,
+        field_token: FieldDefToken,
         app_domain_id: AppDomainID,
         thread_id: ThreadID,
-    ) -> Result<*const std::ffi::c_void, HRESULT> {
+    ) -> Result<*const std::ffi::c_void, HResult> {
         let mut address = MaybeUninit::uninit();
         let hr = unsafe {
-            self.info().GetThreadStaticAddress2(
+            self.info_for(3)?.GetThreadStaticAddress2(
                 class_id,
-                field_token,
+                field_token.raw(),
                 app_domain_id,
                 thread_id,
                 address.as_mut_ptr(),
@@ -1345,67 +1409,63 @@ impl CorProfilerInfo3 for ProfilerInfo {
                 let address = unsafe { address.assume_init() };
                 Ok(address)
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     fn get_app_domains_containing_module(
         &self,
         module_id: ModuleID,
-    ) -> Result<Vec<AppDomainID>, HRESULT> {
-        let mut app_domains_buffer_length = MaybeUninit::uninit();
+    ) -> Result<Vec<AppDomainID>, HResult> {
+        let mut app_domains_buffer_length = 0;
         unsafe {
-            self.info().GetAppDomainsContainingModule(
+            self.info_for(3)?.GetAppDomainsContainingModule(
                 module_id,
                 0,
-                app_domains_buffer_length.as_mut_ptr(),
+                &mut app_domains_buffer_length,
                 ptr::null_mut(),
             )
         };
+        let mut app_domains_buffer = unsafe { zeroed_buffer::<AppDomainID>(app_domains_buffer_length) };
 
-        let app_domains_buffer_length = unsafe { app_domains_buffer_length.assume_init() };
-        let mut app_domains_buffer =
-            Vec::<AppDomainID>::with_capacity(app_domains_buffer_length as usize);
-        unsafe { app_domains_buffer.set_len(app_domains_buffer_length as usize) };
-
-        let mut app_domains_length = MaybeUninit::uninit();
+        let mut app_domains_length = app_domains_buffer_length;
         let hr = unsafe {
-            self.info().GetAppDomainsContainingModule(
+            self.info_for(3)?.GetAppDomainsContainingModule(
                 module_id,
                 app_domains_buffer_length,
-                app_domains_length.as_mut_ptr(),
+                &mut app_domains_length,
                 app_domains_buffer.as_mut_ptr(),
             )
         };
 
         match hr {
-            S_OK => Ok(app_domains_buffer),
-            _ => Err(hr),
+            S_OK => {
+                app_domains_buffer.truncate(app_domains_length as usize);
+                Ok(app_domains_buffer)
+            }
+            _ => Err(hr.into()),
         }
     }
-    fn get_module_info_2(&self, module_id: ModuleID) -> Result<ModuleInfo2, HRESULT> {
-        let mut file_name_buffer_length = MaybeUninit::uninit();
+    fn get_module_info_2(&self, module_id: ModuleID) -> Result<ModuleInfo2, HResult> {
+        let mut file_name_buffer_length = 0;
         unsafe {
-            self.info().GetModuleInfo2(
+            self.info_for(3)?.GetModuleInfo2(
                 module_id,
                 ptr::null_mut(),
                 0,
-                file_name_buffer_length.as_mut_ptr(),
+                &mut file_name_buffer_length,
                 ptr::null_mut(),
                 ptr::null_mut(),
                 ptr::null_mut(),
             )
         };
-
-        let file_name_buffer_length = unsafe { file_name_buffer_length.assume_init() };
-        let mut file_name_buffer = Vec::<WCHAR>::with_capacity(file_name_buffer_length as usize);
-        unsafe { file_name_buffer.set_len(file_name_buffer_length as usize) };
+        let mut file_name_buffer = unsafe { zeroed_buffer::<WCHAR>(file_name_buffer_length) };
 
         let mut base_load_address = MaybeUninit::uninit();
         let mut file_name_length = MaybeUninit::uninit();
         let mut assembly_id = MaybeUninit::uninit();
         let mut module_flags = MaybeUninit::uninit();
         let hr = unsafe {
-            self.info().GetModuleInfo2(
+            self.info_for(3)?.GetModuleInfo2(
                 module_id,
                 base_load_address.as_mut_ptr(),
                 file_name_buffer_length,
@@ -1418,13 +1478,13 @@ impl CorProfilerInfo3 for ProfilerInfo {
 
         match hr {
             S_OK => {
-                let base_load_address = unsafe { base_load_address.assume_init() };
+                // dynamic modules have no base address
+                let base_load_address = Some(unsafe { base_load_address.assume_init() })
+                    .filter(|address| !address.is_null());
                 let assembly_id = unsafe { assembly_id.assume_init() };
                 let module_flags = unsafe { module_flags.assume_init() };
-                let module_flags = COR_PRF_MODULE_FLAGS::from_bits(module_flags).unwrap();
-                let file_name = U16CString::from_vec_with_nul(file_name_buffer)
-                    .unwrap()
-                    .to_string_lossy();
+                let module_flags = COR_PRF_MODULE_FLAGS::from_bits_truncate(module_flags);
+                let file_name = string_from_buffer(&file_name_buffer);
                 Ok(ModuleInfo2 {
                     base_load_address,
                     file_name,
@@ -1432,61 +1492,63 @@ impl CorProfilerInfo3 for ProfilerInfo {
                     module_flags,
                 })
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
 }
 impl CorProfilerInfo4 for ProfilerInfo {
-    fn enum_threads(&self) -> Result<&mut CorProfilerThreadEnum, HRESULT> {
+    fn enum_threads(&self) -> Result<ProfilerEnum<CorProfilerThreadEnum>, HResult> {
         let mut thread_enum = MaybeUninit::uninit();
-        let hr = unsafe { self.info().EnumThreads(thread_enum.as_mut_ptr()) };
+        let hr = unsafe { self.info_for(4)?.EnumThreads(thread_enum.as_mut_ptr()) };
 
         match hr {
-            S_OK => {
-                let thread_enum = unsafe { thread_enum.assume_init().as_mut().unwrap() };
-                Ok(thread_enum)
-            }
-            _ => Err(hr),
+            S_OK => unsafe { ProfilerEnum::from_raw(thread_enum.assume_init()) },
+            _ => Err(hr.into()),
         }
     }
-    fn initialize_current_thread(&self) -> Result<(), HRESULT> {
-        let hr = unsafe { self.info().InitializeCurrentThread() };
+    fn initialize_current_thread(&self) -> Result<(), HResult> {
+        let hr = unsafe { self.info_for(4)?.InitializeCurrentThread() };
 
         match hr {
             S_OK => Ok(()),
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     fn request_rejit(
         &self,
         module_ids: &[ModuleID],
-        method_ids: &[mdMethodDef], // TODO: Maybe make the pairs actual tuples? Simple zip op.
-    ) -> Result<(), HRESULT> {
+        method_ids: &[MethodDefToken], // TODO: Maybe make the pairs actual tuples? Simple zip op.
+    ) -> Result<(), HResult> {
+        if module_ids.len() != method_ids.len() {
+            return Err(HResult::E_INVALIDARG);
+        }
         let methods_length = module_ids.len() as u32;
         let module_ids = module_ids.as_ptr();
-        let method_ids = method_ids.as_ptr();
+        let method_ids = method_ids.as_ptr() as *const mdMethodDef;
         let hr = unsafe {
-            self.info()
+            self.info_for(4)?
                 .RequestReJIT(methods_length, module_ids, method_ids)
         };
 
         match hr {
             S_OK => Ok(()),
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     fn request_revert(
         &self,
         module_ids: &[ModuleID],
-        method_ids: &[mdMethodDef], // TODO: Maybe make the pairs actual tuples? Simple zip op.
-    ) -> Result<Vec<HRESULT>, HRESULT> {
+        method_ids: &[MethodDefToken], // TODO: Maybe make the pairs actual tuples? Simple zip op.
+    ) -> Result<Vec<HRESULT>, HResult> {
+        if module_ids.len() != method_ids.len() {
+            return Err(HResult::E_INVALIDARG);
+        }
         let methods_length = module_ids.len() as u32;
         let module_ids = module_ids.as_ptr();
-        let method_ids = method_ids.as_ptr();
-        let mut statuses_buffer = Vec::<HRESULT>::with_capacity(methods_length as usize);
-        unsafe { statuses_buffer.set_len(methods_length as usize) };
+        let method_ids = method_ids.as_ptr() as *const mdMethodDef;
+        let mut statuses_buffer = unsafe { zeroed_buffer::<HRESULT>(methods_length) };
         let hr = unsafe {
-            self.info().RequestRevert(
+            self.info_for(4)?.RequestRevert(
                 methods_length,
                 module_ids,
                 method_ids,
@@ -1496,51 +1558,50 @@ impl CorProfilerInfo4 for ProfilerInfo {
 
         match hr {
             S_OK => Ok(statuses_buffer),
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     fn get_code_info_3(
         &self,
         function_id: FunctionID,
         rejit_id: ReJITID,
-    ) -> Result<Vec<COR_PRF_CODE_INFO>, HRESULT> {
-        let mut code_info_buffer_length = MaybeUninit::uninit();
+    ) -> Result<Vec<COR_PRF_CODE_INFO>, HResult> {
+        let mut code_info_buffer_length = 0;
         unsafe {
-            self.info().GetCodeInfo3(
+            self.info_for(4)?.GetCodeInfo3(
                 function_id,
                 rejit_id,
                 0,
-                code_info_buffer_length.as_mut_ptr(),
+                &mut code_info_buffer_length,
                 ptr::null_mut(),
             )
         };
+        let mut code_info = unsafe { zeroed_buffer::<COR_PRF_CODE_INFO>(code_info_buffer_length) };
 
-        let code_info_buffer_length = unsafe { code_info_buffer_length.assume_init() };
-        let mut code_info =
-            Vec::<COR_PRF_CODE_INFO>::with_capacity(code_info_buffer_length as usize);
-        unsafe { code_info.set_len(code_info_buffer_length as usize) };
-
-        let mut code_info_length = MaybeUninit::uninit();
+        let mut code_info_length = code_info_buffer_length;
         let hr = unsafe {
-            self.info().GetCodeInfo3(
+            self.info_for(4)?.GetCodeInfo3(
                 function_id,
                 rejit_id,
                 code_info_buffer_length,
-                code_info_length.as_mut_ptr(),
+                &mut code_info_length,
                 code_info.as_mut_ptr(),
             )
         };
 
         match hr {
-            S_OK => Ok(code_info),
-            _ => Err(hr),
+            S_OK => {
+                code_info.truncate(code_info_length as usize);
+                Ok(code_info)
+            }
+            _ => Err(hr.into()),
         }
     }
-    fn get_function_from_ip_2(&self, ip: LPCBYTE) -> Result<FunctionAndRejit, HRESULT> {
+    fn get_function_from_ip_2(&self, ip: LPCBYTE) -> Result<FunctionAndRejit, HResult> {
         let mut function_id = MaybeUninit::uninit();
         let mut rejit_id = MaybeUninit::uninit();
         let hr = unsafe {
-            self.info()
+            self.info_for(4)?
                 .GetFunctionFromIP2(ip, function_id.as_mut_ptr(), rejit_id.as_mut_ptr())
         };
         match hr {
@@ -1552,89 +1613,86 @@ impl CorProfilerInfo4 for ProfilerInfo {
                     rejit_id,
                 })
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
-    fn get_rejit_ids(&self, function_id: FunctionID) -> Result<Vec<ReJITID>, HRESULT> {
-        let mut rejit_ids_buffer_length = MaybeUninit::uninit();
+    fn get_rejit_ids(&self, function_id: FunctionID) -> Result<Vec<ReJITID>, HResult> {
+        let mut rejit_ids_buffer_length = 0;
         unsafe {
-            self.info().GetReJITIDs(
+            self.info_for(4)?.GetReJITIDs(
                 function_id,
                 0,
-                rejit_ids_buffer_length.as_mut_ptr(),
+                &mut rejit_ids_buffer_length,
                 ptr::null_mut(),
             )
         };
+        let mut rejit_ids = unsafe { zeroed_buffer::<ReJITID>(rejit_ids_buffer_length) };
 
-        let rejit_ids_buffer_length = unsafe { rejit_ids_buffer_length.assume_init() };
-        let mut rejit_ids = Vec::<ReJITID>::with_capacity(rejit_ids_buffer_length as usize);
-        unsafe { rejit_ids.set_len(rejit_ids_buffer_length as usize) };
-
-        let mut rejit_ids_length = MaybeUninit::uninit();
+        let mut rejit_ids_length = rejit_ids_buffer_length;
         let hr = unsafe {
-            self.info().GetReJITIDs(
+            self.info_for(4)?.GetReJITIDs(
                 function_id,
                 rejit_ids_buffer_length,
-                rejit_ids_length.as_mut_ptr(),
+                &mut rejit_ids_length,
                 rejit_ids.as_mut_ptr(),
             )
         };
 
         match hr {
-            S_OK => Ok(rejit_ids),
-            _ => Err(hr),
+            S_OK => {
+                rejit_ids.truncate(rejit_ids_length as usize);
+                Ok(rejit_ids)
+            }
+            _ => Err(hr.into()),
         }
     }
     fn get_il_to_native_mapping_2(
         &self,
         function_id: FunctionID,
         rejit_id: ReJITID,
-    ) -> Result<Vec<COR_DEBUG_IL_TO_NATIVE_MAP>, HRESULT> {
-        let mut map_buffer_length = MaybeUninit::uninit();
+    ) -> Result<Vec<COR_DEBUG_IL_TO_NATIVE_MAP>, HResult> {
+        let mut map_buffer_length = 0;
         unsafe {
-            self.info().GetILToNativeMapping2(
+            self.info_for(4)?.GetILToNativeMapping2(
                 function_id,
                 rejit_id,
                 0,
-                map_buffer_length.as_mut_ptr(),
+                &mut map_buffer_length,
                 ptr::null_mut(),
             )
         };
-
-        let map_buffer_length = unsafe { map_buffer_length.assume_init() };
-        let mut map = Vec::<COR_DEBUG_IL_TO_NATIVE_MAP>::with_capacity(map_buffer_length as usize);
-        unsafe { map.set_len(map_buffer_length as usize) };
-        let mut map_length = MaybeUninit::uninit();
+        let mut map = unsafe { zeroed_buffer::<COR_DEBUG_IL_TO_NATIVE_MAP>(map_buffer_length) };
+        let mut map_length = map_buffer_length;
         let hr = unsafe {
-            self.info().GetILToNativeMapping2(
+            self.info_for(4)?.GetILToNativeMapping2(
                 function_id,
                 rejit_id,
                 map_buffer_length,
-                map_length.as_mut_ptr(),
+                &mut map_length,
                 map.as_mut_ptr(),
             )
         };
         match hr {
-            S_OK => Ok(map),
-            _ => Err(hr),
+            S_OK => {
+                map.truncate(map_length as usize);
+                Ok(map)
+            }
+            _ => Err(hr.into()),
         }
     }
-    fn enum_jited_functions_2(&self) -> Result<&mut CorProfilerFunctionEnum, HRESULT> {
+    fn enum_jited_functions_2(&self) -> Result<ProfilerEnum<CorProfilerFunctionEnum>, HResult> {
         let mut function_enum = MaybeUninit::uninit();
-        let hr = unsafe { self.info().EnumJITedFunctions2(function_enum.as_mut_ptr()) };
+        let hr = unsafe { self.info_for(4)?.EnumJITedFunctions2(function_enum.as_mut_ptr()) };
 
         match hr {
-            S_OK => {
-                let function_enum = unsafe { function_enum.assume_init().as_mut().unwrap() };
-                Ok(function_enum)
-            }
-            _ => Err(hr),
+            S_OK => unsafe { ProfilerEnum::from_raw(function_enum.assume_init()) },
+            _ => Err(hr.into()),
         }
     }
-    fn get_object_size_2(&self, object_id: ObjectID) -> Result<usize, HRESULT> {
+    fn get_object_size_2(&self, object_id: ObjectID) -> Result<usize, HResult> {
         let mut object_size = MaybeUninit::uninit();
         let hr = unsafe {
-            self.info()
+            self.info_for(4)?
                 .GetObjectSize2(object_id, object_size.as_mut_ptr())
         };
 
@@ -1643,16 +1701,16 @@ impl CorProfilerInfo4 for ProfilerInfo {
                 let object_size = unsafe { object_size.assume_init() };
                 Ok(object_size)
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
 }
 impl CorProfilerInfo5 for ProfilerInfo {
-    fn get_event_mask_2(&self) -> Result<EventMask2, HRESULT> {
+    fn get_event_mask_2(&self) -> Result<EventMask2, HResult> {
         let mut events_low = MaybeUninit::uninit();
         let mut events_high = MaybeUninit::uninit();
         let hr = unsafe {
-            self.info()
+            self.info_for(5)?
                 .GetEventMask2(events_low.as_mut_ptr(), events_high.as_mut_ptr())
         };
         match hr {
@@ -1660,24 +1718,24 @@ impl CorProfilerInfo5 for ProfilerInfo {
                 let events_low = unsafe { events_low.assume_init() };
                 let events_high = unsafe { events_high.assume_init() };
                 Ok(EventMask2 {
-                    events_low: COR_PRF_MONITOR::from_bits(events_low).unwrap(),
-                    events_high: COR_PRF_HIGH_MONITOR::from_bits(events_high).unwrap(),
+                    events_low: COR_PRF_MONITOR::from_bits_truncate(events_low),
+                    events_high: COR_PRF_HIGH_MONITOR::from_bits_truncate(events_high),
                 })
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     fn set_event_mask_2(
         &self,
         events_low: COR_PRF_MONITOR,
         events_high: COR_PRF_HIGH_MONITOR,
-    ) -> Result<(), HRESULT> {
+    ) -> Result<(), HResult> {
         let events_low = events_low.bits();
         let events_high = events_high.bits();
-        let hr = unsafe { self.info().SetEventMask2(events_low, events_high) };
+        let hr = unsafe { self.info_for(5)?.SetEventMask2(events_low, events_high) };
         match hr {
             S_OK => Ok(()),
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
 }
@@ -1686,15 +1744,15 @@ impl CorProfilerInfo6 for ProfilerInfo {
         &self,
         inliners_module_id: ModuleID,
         inlinee_module_id: ModuleID,
-        inlinee_method_id: mdMethodDef,
-    ) -> Result<EnumNgenModuleMethodsInliningThisMethod, HRESULT> {
+        inlinee_method_id: MethodDefToken,
+    ) -> Result<EnumNgenModuleMethodsInliningThisMethod, HResult> {
         let mut incomplete_data = MaybeUninit::uninit();
         let mut method_enum = MaybeUninit::uninit();
         let hr = unsafe {
-            self.info().EnumNgenModuleMethodsInliningThisMethod(
+            self.info_for(6)?.EnumNgenModuleMethodsInliningThisMethod(
                 inliners_module_id,
                 inlinee_module_id,
-                inlinee_method_id,
+                inlinee_method_id.raw(),
                 incomplete_data.as_mut_ptr(),
                 method_enum.as_mut_ptr(),
             )
@@ -1703,28 +1761,28 @@ impl CorProfilerInfo6 for ProfilerInfo {
             S_OK => {
                 let incomplete_data = unsafe { incomplete_data.assume_init() };
                 let incomplete_data = incomplete_data > 0;
-                let method_enum = unsafe { method_enum.assume_init().as_mut().unwrap() };
+                let method_enum = unsafe { ProfilerEnum::from_raw(method_enum.assume_init())? };
                 Ok(EnumNgenModuleMethodsInliningThisMethod {
                     incomplete_data,
                     method_enum,
                 })
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
 }
 impl CorProfilerInfo7 for ProfilerInfo {
-    fn apply_metadata(&self, module_id: ModuleID) -> Result<(), HRESULT> {
-        let hr = unsafe { self.info().ApplyMetaData(module_id) };
+    fn apply_metadata(&self, module_id: ModuleID) -> Result<(), HResult> {
+        let hr = unsafe { self.info_for(7)?.ApplyMetaData(module_id) };
         match hr {
             S_OK => Ok(()),
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
-    fn get_in_memory_symbols_length(&self, module_id: ModuleID) -> Result<u32, HRESULT> {
+    fn get_in_memory_symbols_length(&self, module_id: ModuleID) -> Result<u32, HResult> {
         let mut symbol_bytes = MaybeUninit::uninit();
         let hr = unsafe {
-            self.info()
+            self.info_for(7)?
                 .GetInMemorySymbolsLength(module_id, symbol_bytes.as_mut_ptr())
         };
         match hr {
@@ -1732,7 +1790,7 @@ impl CorProfilerInfo7 for ProfilerInfo {
                 let symbol_bytes = unsafe { symbol_bytes.assume_init() };
                 Ok(symbol_bytes)
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     fn read_in_memory_symbols(
@@ -1740,11 +1798,11 @@ impl CorProfilerInfo7 for ProfilerInfo {
         module_id: ModuleID,
         symbols_read_offset: u32,
         count_symbol_bytes: u32,
-    ) -> Result<Vec<BYTE>, HRESULT> {
+    ) -> Result<Vec<BYTE>, HResult> {
         let mut buffer = Vec::<BYTE>::with_capacity((count_symbol_bytes + 1024) as usize);
         let mut symbol_bytes_read = MaybeUninit::uninit();
         let hr = unsafe {
-            self.info().ReadInMemorySymbols(
+            self.info_for(7)?.ReadInMemorySymbols(
                 module_id,
                 symbols_read_offset,
                 buffer.as_mut_ptr(),
@@ -1755,18 +1813,19 @@ impl CorProfilerInfo7 for ProfilerInfo {
         match hr {
             S_OK => {
                 let symbol_bytes_read = unsafe { symbol_bytes_read.assume_init() };
+                let symbol_bytes_read = symbol_bytes_read.min(count_symbol_bytes);
                 unsafe { buffer.set_len(symbol_bytes_read as usize) };
                 Ok(buffer)
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
 }
 impl CorProfilerInfo8 for ProfilerInfo {
-    fn is_function_dynamic(&self, function_id: FunctionID) -> Result<bool, HRESULT> {
+    fn is_function_dynamic(&self, function_id: FunctionID) -> Result<bool, HResult> {
         let mut is_dynamic = MaybeUninit::uninit();
         let hr = unsafe {
-            self.info()
+            self.info_for(8)?
                 .IsFunctionDynamic(function_id, is_dynamic.as_mut_ptr())
         };
         match hr {
@@ -1775,14 +1834,14 @@ impl CorProfilerInfo8 for ProfilerInfo {
                 let is_dynamic = is_dynamic > 0;
                 Ok(is_dynamic)
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
-    fn get_function_from_ip_3(&self, ip: LPCBYTE) -> Result<FunctionAndRejit, HRESULT> {
+    fn get_function_from_ip_3(&self, ip: LPCBYTE) -> Result<FunctionAndRejit, HResult> {
         let mut function_id = MaybeUninit::uninit();
         let mut rejit_id = MaybeUninit::uninit();
         let hr = unsafe {
-            self.info()
+            self.info_for(8)?
                 .GetFunctionFromIP3(ip, function_id.as_mut_ptr(), rejit_id.as_mut_ptr())
         };
         match hr {
@@ -1794,36 +1853,33 @@ impl CorProfilerInfo8 for ProfilerInfo {
                     rejit_id,
                 })
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     fn get_dynamic_function_info(
         &self,
         function_id: FunctionID,
-    ) -> Result<DynamicFunctionInfo, HRESULT> {
-        let mut name_buffer_length = MaybeUninit::uninit();
+    ) -> Result<DynamicFunctionInfo, HResult> {
+        let mut name_buffer_length = 0;
         unsafe {
-            self.info().GetDynamicFunctionInfo(
+            self.info_for(8)?.GetDynamicFunctionInfo(
                 function_id,
                 ptr::null_mut(),
                 ptr::null_mut(),
                 ptr::null_mut(),
                 0,
-                name_buffer_length.as_mut_ptr(),
+                &mut name_buffer_length,
                 ptr::null_mut(),
             )
         };
-
-        let name_buffer_length = unsafe { name_buffer_length.assume_init() };
-        let mut name_buffer = Vec::<WCHAR>::with_capacity(name_buffer_length as usize);
-        unsafe { name_buffer.set_len(name_buffer_length as usize) };
+        let mut name_buffer = unsafe { zeroed_buffer::<WCHAR>(name_buffer_length) };
 
         let mut name_length = MaybeUninit::uninit();
         let mut module_id = MaybeUninit::uninit();
         let mut sig = MaybeUninit::uninit();
         let mut sig_length = MaybeUninit::uninit();
         let hr = unsafe {
-            self.info().GetDynamicFunctionInfo(
+            self.info_for(8)?.GetDynamicFunctionInfo(
                 function_id,
                 module_id.as_mut_ptr(),
                 sig.as_mut_ptr(),
@@ -1838,9 +1894,7 @@ impl CorProfilerInfo8 for ProfilerInfo {
                 let module_id = unsafe { module_id.assume_init() };
                 let sig = unsafe { sig.assume_init() };
                 let sig_length = unsafe { sig_length.assume_init() };
-                let name = U16CString::from_vec_with_nul(name_buffer)
-                    .unwrap()
-                    .to_string_lossy();
+                let name = string_from_buffer(&name_buffer);
                 Ok(DynamicFunctionInfo {
                     module_id,
                     sig,
@@ -1848,7 +1902,7 @@ impl CorProfilerInfo8 for ProfilerInfo {
                     name,
                 })
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
 }
@@ -1857,100 +1911,99 @@ impl CorProfilerInfo9 for ProfilerInfo {
         &self,
         function_id: FunctionID,
         rejit_id: ReJITID,
-    ) -> Result<Vec<UINT_PTR>, HRESULT> {
-        let mut addresses_buffer_length = MaybeUninit::uninit();
+    ) -> Result<Vec<UINT_PTR>, HResult> {
+        let mut addresses_buffer_length = 0;
         unsafe {
-            self.info().GetNativeCodeStartAddresses(
+            self.info_for(9)?.GetNativeCodeStartAddresses(
                 function_id,
                 rejit_id,
                 0,
-                addresses_buffer_length.as_mut_ptr(),
+                &mut addresses_buffer_length,
                 ptr::null_mut(),
             )
         };
+        let mut addresses_buffer = unsafe { zeroed_buffer::<UINT_PTR>(addresses_buffer_length) };
 
-        let addresses_buffer_length = unsafe { addresses_buffer_length.assume_init() };
-        let mut addresses_buffer = Vec::<UINT_PTR>::with_capacity(addresses_buffer_length as usize);
-        unsafe { addresses_buffer.set_len(addresses_buffer_length as usize) };
-
-        let mut addresses_length = MaybeUninit::uninit();
+        let mut addresses_length = addresses_buffer_length;
         let hr = unsafe {
-            self.info().GetNativeCodeStartAddresses(
+            self.info_for(9)?.GetNativeCodeStartAddresses(
                 function_id,
                 rejit_id,
                 addresses_buffer_length,
-                addresses_length.as_mut_ptr(),
+                &mut addresses_length,
                 addresses_buffer.as_mut_ptr(),
             )
         };
         match hr {
-            S_OK => Ok(addresses_buffer),
-            _ => Err(hr),
+            S_OK => {
+                addresses_buffer.truncate(addresses_length as usize);
+                Ok(addresses_buffer)
+            }
+            _ => Err(hr.into()),
         }
     }
     fn get_il_to_native_mapping_3(
         &self,
         native_code_start_address: UINT_PTR,
-    ) -> Result<Vec<COR_DEBUG_IL_TO_NATIVE_MAP>, HRESULT> {
-        let mut map_buffer_length = MaybeUninit::uninit();
+    ) -> Result<Vec<COR_DEBUG_IL_TO_NATIVE_MAP>, HResult> {
+        let mut map_buffer_length = 0;
         unsafe {
-            self.info().GetILToNativeMapping3(
+            self.info_for(9)?.GetILToNativeMapping3(
                 native_code_start_address,
                 0,
-                map_buffer_length.as_mut_ptr(),
+                &mut map_buffer_length,
                 ptr::null_mut(),
             )
         };
-
-        let map_buffer_length = unsafe { map_buffer_length.assume_init() };
-        let mut map = Vec::<COR_DEBUG_IL_TO_NATIVE_MAP>::with_capacity(map_buffer_length as usize);
-        unsafe { map.set_len(map_buffer_length as usize) };
-        let mut map_length = MaybeUninit::uninit();
+        let mut map = unsafe { zeroed_buffer::<COR_DEBUG_IL_TO_NATIVE_MAP>(map_buffer_length) };
+        let mut map_length = map_buffer_length;
         let hr = unsafe {
-            self.info().GetILToNativeMapping3(
+            self.info_for(9)?.GetILToNativeMapping3(
                 native_code_start_address,
                 map_buffer_length,
-                map_length.as_mut_ptr(),
+                &mut map_length,
                 map.as_mut_ptr(),
             )
         };
         match hr {
-            S_OK => Ok(map),
-            _ => Err(hr),
+            S_OK => {
+                map.truncate(map_length as usize);
+                Ok(map)
+            }
+            _ => Err(hr.into()),
         }
     }
     fn get_code_info_4(
         &self,
         native_code_start_address: UINT_PTR,
-    ) -> Result<Vec<COR_PRF_CODE_INFO>, HRESULT> {
-        let mut code_info_buffer_length = MaybeUninit::uninit();
+    ) -> Result<Vec<COR_PRF_CODE_INFO>, HResult> {
+        let mut code_info_buffer_length = 0;
         unsafe {
-            self.info().GetCodeInfo4(
+            self.info_for(9)?.GetCodeInfo4(
                 native_code_start_address,
                 0,
-                code_info_buffer_length.as_mut_ptr(),
+                &mut code_info_buffer_length,
                 ptr::null_mut(),
             )
         };
+        let mut code_info = unsafe { zeroed_buffer::<COR_PRF_CODE_INFO>(code_info_buffer_length) };
 
-        let code_info_buffer_length = unsafe { code_info_buffer_length.assume_init() };
-        let mut code_info =
-            Vec::<COR_PRF_CODE_INFO>::with_capacity(code_info_buffer_length as usize);
-        unsafe { code_info.set_len(code_info_buffer_length as usize) };
-
-        let mut code_info_length = MaybeUninit::uninit();
+        let mut code_info_length = code_info_buffer_length;
         let hr = unsafe {
-            self.info().GetCodeInfo4(
+            self.info_for(9)?.GetCodeInfo4(
                 native_code_start_address,
                 code_info_buffer_length,
-                code_info_length.as_mut_ptr(),
+                &mut code_info_length,
                 code_info.as_mut_ptr(),
             )
         };
 
         match hr {
-            S_OK => Ok(code_info),
-            _ => Err(hr),
+            S_OK => {
+                code_info.truncate(code_info_length as usize);
+                Ok(code_info)
+            }
+            _ => Err(hr.into()),
         }
     }
 }
@@ -1960,21 +2013,21 @@ impl CorProfilerInfo10 for ProfilerInfo {
         object_id: ObjectID,
         callback: ObjectReferenceCallback,
         client_data: *const std::ffi::c_void,
-    ) -> Result<(), HRESULT> {
+    ) -> Result<(), HResult> {
         let hr = unsafe {
-            self.info()
+            self.info_for(10)?
                 .EnumerateObjectReferences(object_id, callback, client_data)
         };
 
         match hr {
             S_OK => Ok(()),
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
-    fn is_frozen_object(&self, object_id: ObjectID) -> Result<bool, HRESULT> {
+    fn is_frozen_object(&self, object_id: ObjectID) -> Result<bool, HResult> {
         let mut is_frozen = MaybeUninit::uninit();
         let hr = unsafe {
-            self.info()
+            self.info_for(10)?
                 .IsFrozenObject(object_id, is_frozen.as_mut_ptr())
         };
         match hr {
@@ -1983,13 +2036,13 @@ impl CorProfilerInfo10 for ProfilerInfo {
                 let is_frozen = is_frozen > 0;
                 Ok(is_frozen)
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
-    fn get_loh_object_size_threshold(&self) -> Result<u32, HRESULT> {
+    fn get_loh_object_size_threshold(&self) -> Result<u32, HResult> {
         let mut threshold = MaybeUninit::uninit();
         let hr = unsafe {
-            self.info()
+            self.info_for(10)?
                 .GetLOHObjectSizeThreshold(threshold.as_mut_ptr())
         };
         match hr {
@@ -1997,21 +2050,24 @@ impl CorProfilerInfo10 for ProfilerInfo {
                 let threshold = unsafe { threshold.assume_init() };
                 Ok(threshold)
             }
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
     fn request_rejit_with_inliners(
         &self,
         dw_rejit_flags: COR_PRF_REJIT_FLAGS,
         module_ids: &[ModuleID],
-        method_ids: &[mdMethodDef], // TODO: Maybe we want the pairs to be actual tuples. Simple zip op.
-    ) -> Result<(), HRESULT> {
+        method_ids: &[MethodDefToken], // TODO: Maybe we want the pairs to be actual tuples. Simple zip op.
+    ) -> Result<(), HResult> {
         let dw_rejit_flags = dw_rejit_flags.bits();
+        if module_ids.len() != method_ids.len() {
+            return Err(HResult::E_INVALIDARG);
+        }
         let methods_length = module_ids.len() as u32;
         let module_ids = module_ids.as_ptr();
-        let method_ids = method_ids.as_ptr();
+        let method_ids = method_ids.as_ptr() as *const mdMethodDef;
         let hr = unsafe {
-            self.info().RequestReJITWithInliners(
+            self.info_for(10)?.RequestReJITWithInliners(
                 dw_rejit_flags,
                 methods_length,
                 module_ids,
@@ -2020,21 +2076,373 @@ impl CorProfilerInfo10 for ProfilerInfo {
         };
         match hr {
             S_OK => Ok(()),
-            _ => Err(hr),
+            _ => Err(hr.into()),
+        }
+    }
+    fn suspend_runtime(&self) -> Result<(), HResult> {
+        let hr = unsafe { self.info_for(10)?.SuspendRuntime() };
+        match hr {
+            S_OK => Ok(()),
+            _ => Err(hr.into()),
+        }
+    }
+    fn resume_runtime(&self) -> Result<(), HResult> {
+        let hr = unsafe { self.info_for(10)?.ResumeRuntime() };
+        match hr {
+            S_OK => Ok(()),
+            _ => Err(hr.into()),
+        }
+    }
+}
+impl CorProfilerInfo11 for ProfilerInfo {
+    fn get_environment_variable(&self, name: &str) -> Result<String, HResult> {
+        let name = U16CString::from_str(name).or(Err(HResult::E_INVALIDARG))?;
+        let mut value_buffer_length = 0;
+        unsafe {
+            self.info_for(11)?.GetEnvironmentVariable(
+                name.as_ptr(),
+                0,
+                &mut value_buffer_length,
+                ptr::null_mut(),
+            )
+        };
+        let mut value_buffer = unsafe { zeroed_buffer::<WCHAR>(value_buffer_length) };
+
+        let mut value_length = MaybeUninit::uninit();
+        let hr = unsafe {
+            self.info_for(11)?.GetEnvironmentVariable(
+                name.as_ptr(),
+                value_buffer_length,
+                value_length.as_mut_ptr(),
+                value_buffer.as_mut_ptr(),
+            )
+        };
+        match hr {
+            S_OK => Ok(string_from_buffer(&value_buffer)),
+            _ => Err(hr.into()),
+        }
+    }
+    fn set_environment_variable(&self, name: &str, value: Option<&str>) -> Result<(), HResult> {
+        let name = U16CString::from_str(name).or(Err(HResult::E_INVALIDARG))?;
+        let value = value
+            .map(U16CString::from_str)
+            .transpose()
+            .or(Err(HResult::E_INVALIDARG))?;
+        let value = value.as_ref().map_or(ptr::null(), |value| value.as_ptr());
+        let hr = unsafe { self.info_for(11)?.SetEnvironmentVariable(name.as_ptr(), value) };
+        match hr {
+            S_OK => Ok(()),
+            _ => Err(hr.into()),
         }
     }
-    fn suspend_runtime(&self) -> Result<(), HRESULT> {
-        let hr = unsafe { self.info().SuspendRuntime() };
+}
+impl CorProfilerInfo12 for ProfilerInfo {
+    fn event_pipe_start_session(
+        &self,
+        provider_configs: &[EventPipeProviderConfig],
+        request_rundown: bool,
+    ) -> Result<EVENTPIPE_SESSION, HResult> {
+        let provider_configs = provider_configs
+            .iter()
+            .map(WideProviderConfig::new)
+            .collect::<Result<Vec<_>, _>>()?;
+        let provider_configs = provider_configs
+            .iter()
+            .map(WideProviderConfig::as_ffi)
+            .collect::<Vec<_>>();
+        let mut session = MaybeUninit::uninit();
+        let hr = unsafe {
+            self.info_for(12)?.EventPipeStartSession(
+                provider_configs.len() as UINT32,
+                provider_configs.as_ptr(),
+                request_rundown as BOOL,
+                session.as_mut_ptr(),
+            )
+        };
+        match hr {
+            S_OK => Ok(unsafe { session.assume_init() }),
+            _ => Err(hr.into()),
+        }
+    }
+    fn event_pipe_add_provider_to_session(
+        &self,
+        session: EVENTPIPE_SESSION,
+        provider_config: &EventPipeProviderConfig,
+    ) -> Result<(), HResult> {
+        let provider_config = WideProviderConfig::new(provider_config)?;
+        let hr = unsafe {
+            self.info_for(12)?
+                .EventPipeAddProviderToSession(session, provider_config.as_ffi())
+        };
         match hr {
             S_OK => Ok(()),
-            _ => Err(hr),
+            _ => Err(hr.into()),
         }
     }
-    fn resume_runtime(&self) -> Result<(), HRESULT> {
-        let hr = unsafe { self.info().ResumeRuntime() };
+    fn event_pipe_stop_session(&self, session: EVENTPIPE_SESSION) -> Result<(), HResult> {
+        let hr = unsafe { self.info_for(12)?.EventPipeStopSession(session) };
         match hr {
             S_OK => Ok(()),
-            _ => Err(hr),
+            _ => Err(hr.into()),
+        }
+    }
+    fn event_pipe_create_provider(
+        &self,
+        provider_name: &str,
+    ) -> Result<EVENTPIPE_PROVIDER, HResult> {
+        let provider_name = U16CString::from_str(provider_name).or(Err(HResult::E_INVALIDARG))?;
+        let mut provider = MaybeUninit::uninit();
+        let hr = unsafe {
+            self.info_for(12)?
+                .EventPipeCreateProvider(provider_name.as_ptr(), provider.as_mut_ptr())
+        };
+        match hr {
+            S_OK => Ok(unsafe { provider.assume_init() }),
+            _ => Err(hr.into()),
+        }
+    }
+    fn event_pipe_get_provider_info(
+        &self,
+        provider: EVENTPIPE_PROVIDER,
+    ) -> Result<String, HResult> {
+        let mut name_buffer_length = 0;
+        unsafe {
+            self.info_for(12)?.EventPipeGetProviderInfo(
+                provider,
+                0,
+                &mut name_buffer_length,
+                ptr::null_mut(),
+            )
+        };
+        let mut name_buffer = unsafe { zeroed_buffer::<WCHAR>(name_buffer_length) };
+
+        let mut name_length = MaybeUninit::uninit();
+        let hr = unsafe {
+            self.info_for(12)?.EventPipeGetProviderInfo(
+                provider,
+                name_buffer_length,
+                name_length.as_mut_ptr(),
+                name_buffer.as_mut_ptr(),
+            )
+        };
+        match hr {
+            S_OK => Ok(string_from_buffer(&name_buffer)),
+            _ => Err(hr.into()),
         }
     }
+    fn event_pipe_define_event(
+        &self,
+        provider: EVENTPIPE_PROVIDER,
+        event: &EventPipeEventDefinition,
+    ) -> Result<EVENTPIPE_EVENT, HResult> {
+        let name = U16CString::from_str(&event.name).or(Err(HResult::E_INVALIDARG))?;
+        let param_names = event
+            .params
+            .iter()
+            .map(|param| U16CString::from_str(&param.name).or(Err(HResult::E_INVALIDARG)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let params = event
+            .params
+            .iter()
+            .zip(&param_names)
+            .map(|(param, name)| COR_PRF_EVENTPIPE_PARAM_DESC {
+                r#type: param.param_type as UINT32,
+                elementType: param.element_type.map_or(0, |element_type| element_type as UINT32),
+                name: name.as_ptr(),
+            })
+            .collect::<Vec<_>>();
+        let mut event_handle = MaybeUninit::uninit();
+        let hr = unsafe {
+            self.info_for(12)?.EventPipeDefineEvent(
+                provider,
+                name.as_ptr(),
+                event.event_id,
+                event.keywords,
+                event.event_version,
+                event.level as UINT32,
+                event.opcode,
+                event.need_stack as BOOL,
+                params.len() as UINT32,
+                params.as_ptr(),
+                event_handle.as_mut_ptr(),
+            )
+        };
+        match hr {
+            S_OK => Ok(unsafe { event_handle.assume_init() }),
+            _ => Err(hr.into()),
+        }
+    }
+    fn event_pipe_write_event(
+        &self,
+        event: EVENTPIPE_EVENT,
+        data: &[&[u8]],
+        activity_id: Option<&GUID>,
+        related_activity_id: Option<&GUID>,
+    ) -> Result<(), HResult> {
+        let data = data
+            .iter()
+            .map(|bytes| COR_PRF_EVENT_DATA {
+                ptr: bytes.as_ptr() as UINT64,
+                size: bytes.len() as UINT32,
+                reserved: 0,
+            })
+            .collect::<Vec<_>>();
+        let activity_id = activity_id.map_or(ptr::null(), |id| id as *const GUID);
+        let related_activity_id = related_activity_id.map_or(ptr::null(), |id| id as *const GUID);
+        let hr = unsafe {
+            self.info_for(12)?.EventPipeWriteEvent(
+                event,
+                data.len() as UINT32,
+                data.as_ptr(),
+                activity_id,
+                related_activity_id,
+            )
+        };
+        match hr {
+            S_OK => Ok(()),
+            _ => Err(hr.into()),
+        }
+    }
+}
+impl CorProfilerInfo13 for ProfilerInfo {
+    fn create_handle(
+        &self,
+        object_id: ObjectID,
+        handle_type: COR_PRF_HANDLE_TYPE,
+    ) -> Result<ObjectHandleID, HResult> {
+        let mut handle = MaybeUninit::uninit();
+        let hr = unsafe {
+            self.info_for(13)?
+                .CreateHandle(object_id, handle_type, handle.as_mut_ptr())
+        };
+        match hr {
+            S_OK => Ok(unsafe { handle.assume_init() }),
+            _ => Err(hr.into()),
+        }
+    }
+    unsafe fn destroy_handle(&self, handle: ObjectHandleID) -> Result<(), HResult> {
+        let hr = self.info_for(13)?.DestroyHandle(handle);
+        match hr {
+            S_OK => Ok(()),
+            _ => Err(hr.into()),
+        }
+    }
+    unsafe fn get_object_id_from_handle(
+        &self,
+        handle: ObjectHandleID,
+    ) -> Result<Option<ObjectID>, HResult> {
+        let mut object_id = MaybeUninit::uninit();
+        let hr = self
+            .info_for(13)?
+            .GetObjectIDFromHandle(handle, object_id.as_mut_ptr());
+        match hr {
+            S_OK => Ok(Some(object_id.assume_init()).filter(|id| *id != 0)),
+            _ => Err(hr.into()),
+        }
+    }
+}
+impl CorProfilerInfo14 for ProfilerInfo {
+    fn enumerate_non_gc_objects(&self) -> Result<ProfilerEnum<CorProfilerObjectEnum>, HResult> {
+        let mut object_enum = MaybeUninit::uninit();
+        let hr = unsafe {
+            self.info_for(14)?
+                .EnumerateNonGCObjects(object_enum.as_mut_ptr())
+        };
+        match hr {
+            S_OK => unsafe { ProfilerEnum::from_raw(object_enum.assume_init()) },
+            _ => Err(hr.into()),
+        }
+    }
+    fn get_non_gc_heap_bounds(&self) -> Result<Vec<COR_PRF_NONGC_HEAP_RANGE>, HResult> {
+        let mut ranges_buffer_length = 0;
+        unsafe {
+            self.info_for(14)?
+                .GetNonGCHeapBounds(0, &mut ranges_buffer_length, ptr::null_mut())
+        };
+        let mut ranges = unsafe { zeroed_buffer::<COR_PRF_NONGC_HEAP_RANGE>(ranges_buffer_length) };
+
+        let mut ranges_length = ranges_buffer_length;
+        let hr = unsafe {
+            self.info_for(14)?.GetNonGCHeapBounds(
+                ranges_buffer_length,
+                &mut ranges_length,
+                ranges.as_mut_ptr(),
+            )
+        };
+        match hr {
+            S_OK => {
+                ranges.truncate(ranges_length as usize);
+                Ok(ranges)
+            }
+            _ => Err(hr.into()),
+        }
+    }
+    fn event_pipe_create_provider_2(
+        &self,
+        provider_name: &str,
+        callback: Option<EventPipeProviderCallback>,
+    ) -> Result<EVENTPIPE_PROVIDER, HResult> {
+        let provider_name = U16CString::from_str(provider_name).or(Err(HResult::E_INVALIDARG))?;
+        let mut provider = MaybeUninit::uninit();
+        let hr = unsafe {
+            self.info_for(14)?.EventPipeCreateProvider2(
+                provider_name.as_ptr(),
+                callback,
+                provider.as_mut_ptr(),
+            )
+        };
+        match hr {
+            S_OK => Ok(unsafe { provider.assume_init() }),
+            _ => Err(hr.into()),
+        }
+    }
+}
+
+/// Wide copies of a provider config's strings, which have to outlive the
+/// `COR_PRF_EVENTPIPE_PROVIDER_CONFIG` pointing into them.
+struct WideProviderConfig {
+    provider_name: U16CString,
+    keywords: u64,
+    logging_level: COR_PRF_EVENTPIPE_LEVEL,
+    filter_data: Option<U16CString>,
+}
+
+impl WideProviderConfig {
+    fn new(config: &EventPipeProviderConfig) -> Result<Self, HResult> {
+        let provider_name =
+            U16CString::from_str(&config.provider_name).or(Err(HResult::E_INVALIDARG))?;
+        let filter_data = config
+            .filter_data
+            .as_deref()
+            .map(U16CString::from_str)
+            .transpose()
+            .or(Err(HResult::E_INVALIDARG))?;
+        Ok(WideProviderConfig {
+            provider_name,
+            keywords: config.keywords,
+            logging_level: config.logging_level,
+            filter_data,
+        })
+    }
+    fn as_ffi(&self) -> COR_PRF_EVENTPIPE_PROVIDER_CONFIG {
+        COR_PRF_EVENTPIPE_PROVIDER_CONFIG {
+            providerName: self.provider_name.as_ptr(),
+            keywords: self.keywords,
+            loggingLevel: self.logging_level as UINT32,
+            filterData: self
+                .filter_data
+                .as_ref()
+                .map_or(ptr::null(), |filter_data| filter_data.as_ptr()),
+        }
+    }
+}
+
+/// A buffer for the runtime to fill in.
+///
+/// # Safety
+///
+/// All zeroes must be a valid `T`, which holds for the integers and plain C
+/// structs the runtime hands out.
+unsafe fn zeroed_buffer<T>(length: ULONG32) -> Vec<T> {
+    (0..length).map(|_| std::mem::zeroed()).collect()
 }
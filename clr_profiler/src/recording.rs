@@ -0,0 +1,1049 @@
+//! Records the callbacks a profiler gets from a real runtime, to replay them
+//! offline with [`ProfilerDriver::replay`](crate::testing::ProfilerDriver::replay).
+//!
+//! When [`RECORD_VARIABLE`] names a file as the profiler initializes, every
+//! callback is appended to it with its arguments. Ahead of a callback goes
+//! what the runtime can tell about the functions, classes and modules it
+//! mentions: module names, type and method definitions, the types their
+//! signatures refer to and original IL bodies. Object and thread IDs and
+//! other handles are kept as plain numbers.
+use crate::{
+    ffi::{
+        AssemblyID, ClassID, CorMethodAttr, CorMethodImpl, CorOpenFlags, CorTypeAttr, FunctionID,
+        HResult, ModuleID, GUID,
+    },
+    metadata::{
+        signature_bytes, MethodDefToken, MethodSig, Token, TypeDefToken, TypeRefToken, TypeSig,
+    },
+    CorProfilerInfo, MetadataImport, MetadataImportTrait, ProfilerInfo,
+};
+use log::error;
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    convert::TryFrom,
+    env,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+    slice,
+    sync::{Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+/// The environment variable naming the file to record to.
+pub const RECORD_VARIABLE: &str = "CLR_PROFILER_RECORD";
+
+const MAGIC: &[u8; 8] = b"CLRPREC\x01";
+
+/// How long records may sit in the buffer before they're written out.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Records are written out at the latest when this much is buffered.
+const BUFFER_SIZE: usize = 64 * 1024;
+
+macro_rules! callbacks {
+    ($($version:literal => [$($name:ident),* $(,)?])*) => {
+        /// A method of `ICorProfilerCallback` to `ICorProfilerCallback11`.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Callback {
+            $($($name,)*)*
+        }
+
+        impl Callback {
+            const ALL: &'static [(Callback, u32)] = &[$($((Callback::$name, $version),)*)*];
+
+            /// The `ICorProfilerCallbackN` the method was added in.
+            pub fn version(self) -> u32 {
+                Self::ALL[self as usize].1
+            }
+
+            fn from_index(index: u64) -> Option<Self> {
+                let index = usize::try_from(index).ok()?;
+                Self::ALL.get(index).map(|&(callback, _)| callback)
+            }
+        }
+    };
+}
+
+callbacks! {
+    1 => [
+        Initialize, Shutdown, AppDomainCreationStarted, AppDomainCreationFinished,
+        AppDomainShutdownStarted, AppDomainShutdownFinished, AssemblyLoadStarted,
+        AssemblyLoadFinished, AssemblyUnloadStarted, AssemblyUnloadFinished, ModuleLoadStarted,
+        ModuleLoadFinished, ModuleUnloadStarted, ModuleUnloadFinished, ModuleAttachedToAssembly,
+        ClassLoadStarted, ClassLoadFinished, ClassUnloadStarted, ClassUnloadFinished,
+        FunctionUnloadStarted, JITCompilationStarted, JITCompilationFinished,
+        JITCachedFunctionSearchStarted, JITCachedFunctionSearchFinished, JITFunctionPitched,
+        JITInlining, ThreadCreated, ThreadDestroyed, ThreadAssignedToOSThread,
+        RemotingClientInvocationStarted, RemotingClientSendingMessage,
+        RemotingClientReceivingReply, RemotingClientInvocationFinished,
+        RemotingServerReceivingMessage, RemotingServerInvocationStarted,
+        RemotingServerInvocationReturned, RemotingServerSendingReply,
+        UnmanagedToManagedTransition, ManagedToUnmanagedTransition, RuntimeSuspendStarted,
+        RuntimeSuspendFinished, RuntimeSuspendAborted, RuntimeResumeStarted,
+        RuntimeResumeFinished, RuntimeThreadSuspended, RuntimeThreadResumed, MovedReferences,
+        ObjectAllocated, ObjectsAllocatedByClass, ObjectReferences, RootReferences,
+        ExceptionThrown, ExceptionSearchFunctionEnter, ExceptionSearchFunctionLeave,
+        ExceptionSearchFilterEnter, ExceptionSearchFilterLeave, ExceptionSearchCatcherFound,
+        ExceptionOSHandlerEnter, ExceptionOSHandlerLeave, ExceptionUnwindFunctionEnter,
+        ExceptionUnwindFunctionLeave, ExceptionUnwindFinallyEnter, ExceptionUnwindFinallyLeave,
+        ExceptionCatcherEnter, ExceptionCatcherLeave, COMClassicVTableCreated,
+        COMClassicVTableDestroyed, ExceptionCLRCatcherFound, ExceptionCLRCatcherExecute,
+    ]
+    2 => [
+        ThreadNameChanged, GarbageCollectionStarted, SurvivingReferences,
+        GarbageCollectionFinished, FinalizeableObjectQueued, RootReferences2, HandleCreated,
+        HandleDestroyed,
+    ]
+    3 => [InitializeForAttach, ProfilerAttachComplete, ProfilerDetachSucceeded]
+    4 => [
+        ReJITCompilationStarted, GetReJITParameters, ReJITCompilationFinished, ReJITError,
+        MovedReferences2, SurvivingReferences2,
+    ]
+    5 => [ConditionalWeakTableElementReferences]
+    6 => [GetAssemblyReferences]
+    7 => [ModuleInMemorySymbolsUpdated]
+    8 => [DynamicMethodJITCompilationStarted, DynamicMethodJITCompilationFinished]
+    9 => [DynamicMethodUnloaded]
+    10 => [EventPipeEventDelivered, EventPipeProviderCreated]
+    11 => [LoadAsNotificationOnly]
+}
+
+/// An argument of a recorded callback. Out parameters aren't recorded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Arg {
+    /// A number, flags, an `HRESULT` or a `BOOL`.
+    Int(u64),
+    /// Recorded along with the function's class, module and method.
+    Function(FunctionID),
+    /// Recorded along with the module.
+    Module(ModuleID),
+    /// Recorded along with the class's module and type.
+    Class(ClassID),
+    /// A method of the callback's module, recorded along with it.
+    MethodDef(MethodDefToken),
+    /// An array of IDs, counts or flags.
+    Ints(Vec<u64>),
+    /// A buffer, or a GUID. Empty for a null pointer.
+    Bytes(Vec<u8>),
+    Text(String),
+}
+
+impl Arg {
+    /// The value of a number or an ID, 0 for other arguments.
+    pub fn int(&self) -> u64 {
+        match *self {
+            Arg::Int(value) => value,
+            Arg::Function(id) | Arg::Module(id) | Arg::Class(id) => id as u64,
+            Arg::MethodDef(token) => token.raw() as u64,
+            _ => 0,
+        }
+    }
+
+    pub fn ints(&self) -> &[u64] {
+        match self {
+            Arg::Ints(ints) => ints,
+            _ => &[],
+        }
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        match self {
+            Arg::Bytes(bytes) => bytes,
+            _ => &[],
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        match self {
+            Arg::Text(text) => text,
+            _ => "",
+        }
+    }
+
+    /// The GUID a `Bytes` argument holds, `None` for a null pointer.
+    pub fn guid(&self) -> Option<GUID> {
+        let bytes = <[u8; 16]>::try_from(self.bytes()).ok()?;
+        Some(GUID {
+            data1: u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            data2: u16::from_le_bytes([bytes[4], bytes[5]]),
+            data3: u16::from_le_bytes([bytes[6], bytes[7]]),
+            data4: <[u8; 8]>::try_from(&bytes[8..]).unwrap(),
+        })
+    }
+
+    pub(crate) unsafe fn from_guid(guid: *const GUID) -> Self {
+        let Some(guid) = guid.as_ref() else {
+            return Arg::Bytes(Vec::new());
+        };
+        let mut bytes = guid.data1.to_le_bytes().to_vec();
+        bytes.extend(guid.data2.to_le_bytes());
+        bytes.extend(guid.data3.to_le_bytes());
+        bytes.extend(guid.data4);
+        Arg::Bytes(bytes)
+    }
+
+    pub(crate) unsafe fn from_array<I: Element>(items: *const I, count: usize) -> Self {
+        if items.is_null() {
+            return Arg::Ints(Vec::new());
+        }
+        let items = slice::from_raw_parts(items, count);
+        Arg::Ints(items.iter().map(|item| item.widen()).collect())
+    }
+
+    pub(crate) unsafe fn from_buffer(buffer: *const u8, length: usize) -> Self {
+        if buffer.is_null() {
+            return Arg::Bytes(Vec::new());
+        }
+        Arg::Bytes(slice::from_raw_parts(buffer, length).to_vec())
+    }
+}
+
+/// Integers, like `HRESULT`s and `BOOL`s, are kept as their unsigned bits.
+impl From<i32> for Arg {
+    fn from(value: i32) -> Self {
+        Arg::Int(value as u32 as u64)
+    }
+}
+
+impl From<u32> for Arg {
+    fn from(value: u32) -> Self {
+        Arg::Int(value as u64)
+    }
+}
+
+impl From<usize> for Arg {
+    fn from(value: usize) -> Self {
+        Arg::Int(value as u64)
+    }
+}
+
+/// The integers arrays passed to callbacks are made of.
+pub(crate) trait Element: Copy {
+    fn widen(self) -> u64;
+}
+
+impl Element for i32 {
+    fn widen(self) -> u64 {
+        self as u32 as u64
+    }
+}
+
+impl Element for u32 {
+    fn widen(self) -> u64 {
+        self as u64
+    }
+}
+
+impl Element for usize {
+    fn widen(self) -> u64 {
+        self as u64
+    }
+}
+
+/// An entry of a recording. Everything a callback mentions is recorded
+/// before it, once.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Record {
+    Callback {
+        callback: Callback,
+        args: Vec<Arg>,
+    },
+    Module {
+        module_id: ModuleID,
+        assembly_id: AssemblyID,
+        name: String,
+    },
+    Class {
+        class_id: ClassID,
+        module_id: ModuleID,
+        token: TypeDefToken,
+    },
+    TypeDef {
+        module_id: ModuleID,
+        token: TypeDefToken,
+        name: String,
+        flags: CorTypeAttr,
+        enclosing: Option<TypeDefToken>,
+        generic_params: Vec<String>,
+    },
+    TypeRef {
+        module_id: ModuleID,
+        token: TypeRefToken,
+        resolution_scope: Token,
+        name: String,
+    },
+    Method {
+        module_id: ModuleID,
+        token: MethodDefToken,
+        class: TypeDefToken,
+        name: String,
+        attr_flags: CorMethodAttr,
+        impl_flags: CorMethodImpl,
+        sig: Vec<u8>,
+        generic_params: Vec<String>,
+    },
+    Function {
+        function_id: FunctionID,
+        class_id: Option<ClassID>,
+        module_id: ModuleID,
+        token: MethodDefToken,
+    },
+    /// The body the method had before the profiler got to it, header included.
+    IlBody {
+        module_id: ModuleID,
+        token: MethodDefToken,
+        body: Vec<u8>,
+    },
+}
+
+/// The records of a recording file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Recording {
+    pub records: Vec<Record>,
+}
+
+impl Recording {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+
+    /// Reads a recording up to its end, or up to a record the process died
+    /// while writing.
+    pub fn read(mut input: impl Read) -> io::Result<Self> {
+        let mut magic = [0; 8];
+        input.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a profiler recording"));
+        }
+        let mut records = Vec::new();
+        loop {
+            match Record::read(&mut input) {
+                Ok(record) => records.push(record),
+                Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(Recording { records })
+    }
+
+    pub fn write(&self, mut output: impl Write) -> io::Result<()> {
+        output.write_all(MAGIC)?;
+        for record in &self.records {
+            record.write(&mut output)?;
+        }
+        output.flush()
+    }
+
+    /// The recorded callbacks, in the order they were made.
+    pub fn callbacks(&self) -> impl Iterator<Item = (Callback, &[Arg])> + '_ {
+        self.records.iter().filter_map(|record| match record {
+            Record::Callback { callback, args } => Some((*callback, &args[..])),
+            _ => None,
+        })
+    }
+}
+
+// Records are a tag byte followed by their fields. Integers are LEB128,
+// strings and buffers are prefixed with their length.
+
+const CALLBACK: u8 = 0;
+const MODULE: u8 = 1;
+const CLASS: u8 = 2;
+const TYPE_DEF: u8 = 3;
+const TYPE_REF: u8 = 4;
+const METHOD: u8 = 5;
+const FUNCTION: u8 = 6;
+const IL_BODY: u8 = 7;
+
+const INT: u8 = 0;
+const FUNCTION_ID: u8 = 1;
+const MODULE_ID: u8 = 2;
+const CLASS_ID: u8 = 3;
+const METHOD_DEF: u8 = 4;
+const INTS: u8 = 5;
+const BYTES: u8 = 6;
+const TEXT: u8 = 7;
+
+impl Record {
+    /// The module a described record belongs to.
+    fn module_id(&self) -> Option<ModuleID> {
+        match *self {
+            Record::Callback { .. } => None,
+            Record::Module { module_id, .. }
+            | Record::Class { module_id, .. }
+            | Record::TypeDef { module_id, .. }
+            | Record::TypeRef { module_id, .. }
+            | Record::Method { module_id, .. }
+            | Record::Function { module_id, .. }
+            | Record::IlBody { module_id, .. } => Some(module_id),
+        }
+    }
+
+    fn write(&self, output: &mut impl Write) -> io::Result<()> {
+        match self {
+            Record::Callback { callback, args } => {
+                output.write_all(&[CALLBACK])?;
+                write_uint(output, *callback as u64)?;
+                write_uint(output, args.len() as u64)?;
+                for arg in args {
+                    write_arg(output, arg)?;
+                }
+            }
+            Record::Module {
+                module_id,
+                assembly_id,
+                name,
+            } => {
+                output.write_all(&[MODULE])?;
+                write_uint(output, *module_id as u64)?;
+                write_uint(output, *assembly_id as u64)?;
+                write_bytes(output, name.as_bytes())?;
+            }
+            Record::Class {
+                class_id,
+                module_id,
+                token,
+            } => {
+                output.write_all(&[CLASS])?;
+                write_uint(output, *class_id as u64)?;
+                write_uint(output, *module_id as u64)?;
+                write_uint(output, token.raw() as u64)?;
+            }
+            Record::TypeDef {
+                module_id,
+                token,
+                name,
+                flags,
+                enclosing,
+                generic_params,
+            } => {
+                output.write_all(&[TYPE_DEF])?;
+                write_uint(output, *module_id as u64)?;
+                write_uint(output, token.raw() as u64)?;
+                write_bytes(output, name.as_bytes())?;
+                write_uint(output, flags.bits() as u64)?;
+                write_uint(output, enclosing.map_or(0, TypeDefToken::raw) as u64)?;
+                write_strings(output, generic_params)?;
+            }
+            Record::TypeRef {
+                module_id,
+                token,
+                resolution_scope,
+                name,
+            } => {
+                output.write_all(&[TYPE_REF])?;
+                write_uint(output, *module_id as u64)?;
+                write_uint(output, token.raw() as u64)?;
+                write_uint(output, resolution_scope.raw() as u64)?;
+                write_bytes(output, name.as_bytes())?;
+            }
+            Record::Method {
+                module_id,
+                token,
+                class,
+                name,
+                attr_flags,
+                impl_flags,
+                sig,
+                generic_params,
+            } => {
+                output.write_all(&[METHOD])?;
+                write_uint(output, *module_id as u64)?;
+                write_uint(output, token.raw() as u64)?;
+                write_uint(output, class.raw() as u64)?;
+                write_bytes(output, name.as_bytes())?;
+                write_uint(output, attr_flags.bits() as u64)?;
+                write_uint(output, impl_flags.bits() as u64)?;
+                write_bytes(output, sig)?;
+                write_strings(output, generic_params)?;
+            }
+            Record::Function {
+                function_id,
+                class_id,
+                module_id,
+                token,
+            } => {
+                output.write_all(&[FUNCTION])?;
+                write_uint(output, *function_id as u64)?;
+                write_uint(output, class_id.unwrap_or(0) as u64)?;
+                write_uint(output, *module_id as u64)?;
+                write_uint(output, token.raw() as u64)?;
+            }
+            Record::IlBody {
+                module_id,
+                token,
+                body,
+            } => {
+                output.write_all(&[IL_BODY])?;
+                write_uint(output, *module_id as u64)?;
+                write_uint(output, token.raw() as u64)?;
+                write_bytes(output, body)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn read(input: &mut impl Read) -> io::Result<Self> {
+        let mut tag = [0];
+        input.read_exact(&mut tag)?;
+        let record = match tag[0] {
+            CALLBACK => {
+                let callback = Callback::from_index(read_uint(input)?)
+                    .ok_or_else(|| invalid_data("unknown callback"))?;
+                let count = read_uint(input)?;
+                let args = (0..count)
+                    .map(|_| read_arg(input))
+                    .collect::<io::Result<_>>()?;
+                Record::Callback { callback, args }
+            }
+            MODULE => Record::Module {
+                module_id: read_id(input)?,
+                assembly_id: read_id(input)?,
+                name: read_string(input)?,
+            },
+            CLASS => Record::Class {
+                class_id: read_id(input)?,
+                module_id: read_id(input)?,
+                token: read_token(input)?,
+            },
+            TYPE_DEF => Record::TypeDef {
+                module_id: read_id(input)?,
+                token: read_token(input)?,
+                name: read_string(input)?,
+                flags: CorTypeAttr::from_bits_retain(read_u32(input)?),
+                enclosing: match read_u32(input)? {
+                    0 => None,
+                    raw => Some(typed_token(raw)?),
+                },
+                generic_params: read_strings(input)?,
+            },
+            TYPE_REF => Record::TypeRef {
+                module_id: read_id(input)?,
+                token: read_token(input)?,
+                resolution_scope: Token::new(read_u32(input)?),
+                name: read_string(input)?,
+            },
+            METHOD => Record::Method {
+                module_id: read_id(input)?,
+                token: read_token(input)?,
+                class: read_token(input)?,
+                name: read_string(input)?,
+                attr_flags: CorMethodAttr::from_bits_retain(read_u32(input)?),
+                impl_flags: CorMethodImpl::from_bits_retain(read_u32(input)?),
+                sig: read_bytes(input)?,
+                generic_params: read_strings(input)?,
+            },
+            FUNCTION => Record::Function {
+                function_id: read_id(input)?,
+                class_id: Some(read_id(input)?).filter(|&class_id| class_id != 0),
+                module_id: read_id(input)?,
+                token: read_token(input)?,
+            },
+            IL_BODY => Record::IlBody {
+                module_id: read_id(input)?,
+                token: read_token(input)?,
+                body: read_bytes(input)?,
+            },
+            _ => return Err(invalid_data("unknown record")),
+        };
+        Ok(record)
+    }
+}
+
+fn write_arg(output: &mut impl Write, arg: &Arg) -> io::Result<()> {
+    match arg {
+        Arg::Int(value) => {
+            output.write_all(&[INT])?;
+            write_uint(output, *value)
+        }
+        Arg::Function(id) => {
+            output.write_all(&[FUNCTION_ID])?;
+            write_uint(output, *id as u64)
+        }
+        Arg::Module(id) => {
+            output.write_all(&[MODULE_ID])?;
+            write_uint(output, *id as u64)
+        }
+        Arg::Class(id) => {
+            output.write_all(&[CLASS_ID])?;
+            write_uint(output, *id as u64)
+        }
+        Arg::MethodDef(token) => {
+            output.write_all(&[METHOD_DEF])?;
+            write_uint(output, token.raw() as u64)
+        }
+        Arg::Ints(ints) => {
+            output.write_all(&[INTS])?;
+            write_uint(output, ints.len() as u64)?;
+            ints.iter().try_for_each(|&value| write_uint(output, value))
+        }
+        Arg::Bytes(bytes) => {
+            output.write_all(&[BYTES])?;
+            write_bytes(output, bytes)
+        }
+        Arg::Text(text) => {
+            output.write_all(&[TEXT])?;
+            write_bytes(output, text.as_bytes())
+        }
+    }
+}
+
+fn read_arg(input: &mut impl Read) -> io::Result<Arg> {
+    let mut tag = [0];
+    input.read_exact(&mut tag)?;
+    let arg = match tag[0] {
+        INT => Arg::Int(read_uint(input)?),
+        FUNCTION_ID => Arg::Function(read_id(input)?),
+        MODULE_ID => Arg::Module(read_id(input)?),
+        CLASS_ID => Arg::Class(read_id(input)?),
+        METHOD_DEF => Arg::MethodDef(read_token(input)?),
+        INTS => {
+            let count = read_uint(input)?;
+            Arg::Ints(
+                (0..count)
+                    .map(|_| read_uint(input))
+                    .collect::<io::Result<_>>()?,
+            )
+        }
+        BYTES => Arg::Bytes(read_bytes(input)?),
+        TEXT => Arg::Text(read_string(input)?),
+        _ => return Err(invalid_data("unknown argument")),
+    };
+    Ok(arg)
+}
+
+fn write_uint(output: &mut impl Write, mut value: u64) -> io::Result<()> {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            return output.write_all(&[byte]);
+        }
+        output.write_all(&[byte | 0x80])?;
+    }
+}
+
+fn write_bytes(output: &mut impl Write, bytes: &[u8]) -> io::Result<()> {
+    write_uint(output, bytes.len() as u64)?;
+    output.write_all(bytes)
+}
+
+fn write_strings(output: &mut impl Write, strings: &[String]) -> io::Result<()> {
+    write_uint(output, strings.len() as u64)?;
+    strings
+        .iter()
+        .try_for_each(|string| write_bytes(output, string.as_bytes()))
+}
+
+fn read_uint(input: &mut impl Read) -> io::Result<u64> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0];
+        input.read_exact(&mut byte)?;
+        value |= ((byte[0] & 0x7f) as u64) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(invalid_data("integer too long"))
+}
+
+fn read_u32(input: &mut impl Read) -> io::Result<u32> {
+    u32::try_from(read_uint(input)?).or(Err(invalid_data("integer too long")))
+}
+
+fn read_id(input: &mut impl Read) -> io::Result<usize> {
+    usize::try_from(read_uint(input)?).or(Err(invalid_data("ID too long")))
+}
+
+fn read_token<T: TryFrom<u32>>(input: &mut impl Read) -> io::Result<T> {
+    typed_token(read_u32(input)?)
+}
+
+fn typed_token<T: TryFrom<u32>>(raw: u32) -> io::Result<T> {
+    T::try_from(raw).or(Err(invalid_data("unexpected token kind")))
+}
+
+fn read_bytes(input: &mut impl Read) -> io::Result<Vec<u8>> {
+    let length = read_id(input)?;
+    let mut bytes = Vec::new();
+    input.take(length as u64).read_to_end(&mut bytes)?;
+    if bytes.len() < length {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(bytes)
+}
+
+fn read_string(input: &mut impl Read) -> io::Result<String> {
+    String::from_utf8(read_bytes(input)?).or(Err(invalid_data("invalid UTF-8")))
+}
+
+fn read_strings(input: &mut impl Read) -> io::Result<Vec<String>> {
+    let count = read_uint(input)?;
+    (0..count).map(|_| read_string(input)).collect()
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Appends callbacks to a recording file as the runtime makes them.
+///
+/// What the runtime can tell about a callback's arguments is queried before
+/// the file is locked, so callbacks on other threads only wait for each
+/// other's writes.
+pub(crate) struct Recorder {
+    output: Mutex<Output>,
+}
+
+struct Output {
+    writer: BufWriter<File>,
+    last_flush: Instant,
+    /// What's recorded, with the module it belongs to.
+    recorded: HashMap<Described, ModuleID>,
+}
+
+/// Something recorded once, ahead of the callbacks that mention it.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Described {
+    Module(ModuleID),
+    Class(ClassID),
+    Function(FunctionID),
+    Type(ModuleID, Token),
+    Method(ModuleID, MethodDefToken),
+    IlBody(ModuleID, MethodDefToken),
+}
+
+/// What a callback mentions that isn't recorded yet, gathered without
+/// holding the lock on the file.
+struct Description<'a> {
+    recorder: &'a Recorder,
+    records: Vec<(Described, Record)>,
+    described: HashSet<Described>,
+}
+
+impl Recorder {
+    /// Starts a recording in the file [`RECORD_VARIABLE`] names, if it's set.
+    pub fn from_env() -> Option<Self> {
+        let path = env::var_os(RECORD_VARIABLE)?;
+        match Self::create(Path::new(&path)) {
+            Ok(recorder) => Some(recorder),
+            Err(error) => {
+                error!("failed to record to {}: {error}", path.to_string_lossy());
+                None
+            }
+        }
+    }
+
+    fn create(path: &Path) -> io::Result<Self> {
+        let mut writer = BufWriter::with_capacity(BUFFER_SIZE, File::create(path)?);
+        writer.write_all(MAGIC)?;
+        Ok(Recorder {
+            output: Mutex::new(Output {
+                writer,
+                last_flush: Instant::now(),
+                recorded: HashMap::new(),
+            }),
+        })
+    }
+
+    /// Records a callback, after what `profiler_info` can tell about the
+    /// functions, classes and modules among its arguments. The file is
+    /// flushed at shutdown and otherwise every [`FLUSH_INTERVAL`] at most,
+    /// so a crash loses only the last moments of the recording.
+    ///
+    /// What's unloaded is forgotten, to be recorded again if the runtime
+    /// reuses its ID.
+    pub fn record(&self, profiler_info: Option<&ProfilerInfo>, callback: Callback, args: Vec<Arg>) {
+        let mut description = Description {
+            recorder: self,
+            records: Vec::new(),
+            described: HashSet::new(),
+        };
+        if let Some(profiler_info) = profiler_info {
+            description.describe(profiler_info, &args);
+        }
+
+        let mut output = self.output();
+        for (described, record) in description.records {
+            // another thread may have recorded it in the meantime
+            if let (Entry::Vacant(entry), Some(module_id)) =
+                (output.recorded.entry(described), record.module_id())
+            {
+                entry.insert(module_id);
+                output.write(&record);
+            }
+        }
+        match (callback, args.first()) {
+            (Callback::ModuleUnloadFinished, Some(&Arg::Module(module_id))) => {
+                output.recorded.retain(|_, module| *module != module_id);
+            }
+            (Callback::ClassUnloadFinished, Some(&Arg::Class(class_id))) => {
+                output.recorded.remove(&Described::Class(class_id));
+            }
+            (Callback::FunctionUnloadStarted, Some(&Arg::Function(function_id))) => {
+                output.recorded.remove(&Described::Function(function_id));
+            }
+            _ => {}
+        }
+        output.write(&Record::Callback { callback, args });
+        if callback == Callback::Shutdown || output.last_flush.elapsed() >= FLUSH_INTERVAL {
+            if let Err(error) = output.writer.flush() {
+                error!("failed to record {callback:?}: {error}");
+            }
+            output.last_flush = Instant::now();
+        }
+    }
+
+    fn output(&self) -> MutexGuard<'_, Output> {
+        self.output
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Output {
+    fn write(&mut self, record: &Record) {
+        if let Err(error) = record.write(&mut self.writer) {
+            error!("failed to record {record:?}: {error}");
+        }
+    }
+}
+
+impl Description<'_> {
+    fn is_described(&self, described: Described) -> bool {
+        self.described.contains(&described)
+            || self.recorder.output().recorded.contains_key(&described)
+    }
+
+    fn push(&mut self, described: Described, record: Record) {
+        self.described.insert(described);
+        self.records.push((described, record));
+    }
+
+    fn describe(&mut self, info: &ProfilerInfo, args: &[Arg]) {
+        let module_id = args.iter().find_map(|arg| match *arg {
+            Arg::Module(module_id) => Some(module_id),
+            _ => None,
+        });
+        for arg in args {
+            // the runtime can't tell everything yet while a module or class
+            // is loading; it's tried again with the next callback
+            let _ = match *arg {
+                Arg::Function(function_id) => self.function(info, function_id),
+                Arg::Module(module_id) => self.module(info, module_id),
+                Arg::Class(class_id) => self.class(info, class_id),
+                Arg::MethodDef(token) => match module_id {
+                    Some(module_id) => self.method(info, module_id, token),
+                    None => Ok(()),
+                },
+                _ => Ok(()),
+            };
+        }
+    }
+
+    fn module(&mut self, info: &ProfilerInfo, module_id: ModuleID) -> Result<(), HResult> {
+        if self.is_described(Described::Module(module_id)) {
+            return Ok(());
+        }
+        let module = info.get_module_info(module_id)?;
+        self.push(
+            Described::Module(module_id),
+            Record::Module {
+                module_id,
+                assembly_id: module.assembly_id,
+                name: module.file_name,
+            },
+        );
+        Ok(())
+    }
+
+    fn class(&mut self, info: &ProfilerInfo, class_id: ClassID) -> Result<(), HResult> {
+        if self.is_described(Described::Class(class_id)) {
+            return Ok(());
+        }
+        let class = info.get_class_id_info(class_id)?;
+        // arrays have no TypeDef
+        if class.token.is_nil() {
+            return Ok(());
+        }
+        self.module(info, class.module_id)?;
+        let metadata = info.get_module_metadata(class.module_id, CorOpenFlags::ofRead)?;
+        self.type_def(&metadata, class.module_id, class.token)?;
+        self.push(
+            Described::Class(class_id),
+            Record::Class {
+                class_id,
+                module_id: class.module_id,
+                token: class.token,
+            },
+        );
+        Ok(())
+    }
+
+    fn function(&mut self, info: &ProfilerInfo, function_id: FunctionID) -> Result<(), HResult> {
+        if self.is_described(Described::Function(function_id)) {
+            return Ok(());
+        }
+        let function = info.get_function_info(function_id)?;
+        self.method(info, function.module_id, function.token)?;
+        self.push(
+            Described::Function(function_id),
+            Record::Function {
+                function_id,
+                class_id: function.class_id,
+                module_id: function.module_id,
+                token: function.token,
+            },
+        );
+        Ok(())
+    }
+
+    fn method(
+        &mut self,
+        info: &ProfilerInfo,
+        module_id: ModuleID,
+        token: MethodDefToken,
+    ) -> Result<(), HResult> {
+        if self.is_described(Described::Method(module_id, token)) {
+            return Ok(());
+        }
+        self.module(info, module_id)?;
+        let metadata = info.get_module_metadata(module_id, CorOpenFlags::ofRead)?;
+        let props = metadata.get_method_props(token)?;
+        self.type_def(&metadata, module_id, props.class_token)?;
+        let sig = unsafe { signature_bytes(props.sig, props.sig_length) }.to_vec();
+        let mut tokens = Vec::new();
+        if let Ok(method_sig) = MethodSig::from_bytes(&sig) {
+            method_sig_tokens(&method_sig, &mut tokens);
+        }
+        for token in tokens {
+            self.type_def_or_ref(&metadata, module_id, token)?;
+        }
+        let generic_params = metadata.get_generic_param_names(token.token())?;
+        self.push(
+            Described::Method(module_id, token),
+            Record::Method {
+                module_id,
+                token,
+                class: props.class_token,
+                name: props.name,
+                attr_flags: props.attr_flags,
+                impl_flags: props.impl_flags,
+                sig,
+                generic_params,
+            },
+        );
+        // abstract and runtime implemented methods have no IL
+        if let Ok(body) = info.get_il_function_body(module_id, token) {
+            let body =
+                unsafe { slice::from_raw_parts(body.method_header, body.method_size as usize) };
+            self.push(
+                Described::IlBody(module_id, token),
+                Record::IlBody {
+                    module_id,
+                    token,
+                    body: body.to_vec(),
+                },
+            );
+        }
+        Ok(())
+    }
+
+    fn type_def_or_ref(
+        &mut self,
+        metadata: &MetadataImport,
+        module_id: ModuleID,
+        token: Token,
+    ) -> Result<(), HResult> {
+        if let Ok(td) = TypeDefToken::try_from(token) {
+            self.type_def(metadata, module_id, td)
+        } else if let Ok(tr) = TypeRefToken::try_from(token) {
+            self.type_ref(metadata, module_id, tr)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn type_def(
+        &mut self,
+        metadata: &MetadataImport,
+        module_id: ModuleID,
+        token: TypeDefToken,
+    ) -> Result<(), HResult> {
+        if self.is_described(Described::Type(module_id, token.token())) {
+            return Ok(());
+        }
+        let props = metadata.get_type_def_props(token)?;
+        let enclosing = metadata.get_nested_class_props(token)?;
+        if let Some(enclosing) = enclosing {
+            self.type_def(metadata, module_id, enclosing)?;
+        }
+        let generic_params = metadata.get_generic_param_names(token.token())?;
+        self.push(
+            Described::Type(module_id, token.token()),
+            Record::TypeDef {
+                module_id,
+                token,
+                name: props.name,
+                flags: props.type_def_flags,
+                enclosing,
+                generic_params,
+            },
+        );
+        Ok(())
+    }
+
+    fn type_ref(
+        &mut self,
+        metadata: &MetadataImport,
+        module_id: ModuleID,
+        token: TypeRefToken,
+    ) -> Result<(), HResult> {
+        if self.is_described(Described::Type(module_id, token.token())) {
+            return Ok(());
+        }
+        let props = metadata.get_type_ref_props(token)?;
+        // the enclosing type of a nested type
+        if let Ok(scope) = TypeRefToken::try_from(props.resolution_scope) {
+            self.type_ref(metadata, module_id, scope)?;
+        }
+        self.push(
+            Described::Type(module_id, token.token()),
+            Record::TypeRef {
+                module_id,
+                token,
+                resolution_scope: props.resolution_scope,
+                name: props.name,
+            },
+        );
+        Ok(())
+    }
+}
+
+fn method_sig_tokens(method_sig: &MethodSig, tokens: &mut Vec<Token>) {
+    type_sig_tokens(&method_sig.return_type, tokens);
+    for param in &method_sig.params {
+        type_sig_tokens(param, tokens);
+    }
+}
+
+fn type_sig_tokens(type_sig: &TypeSig, tokens: &mut Vec<Token>) {
+    match type_sig {
+        TypeSig::Class(token) | TypeSig::ValueType(token) => tokens.push(*token),
+        TypeSig::SzArray(element)
+        | TypeSig::Array(element, _)
+        | TypeSig::Ptr(element)
+        | TypeSig::ByRef(element)
+        | TypeSig::Pinned(element) => type_sig_tokens(element, tokens),
+        TypeSig::GenericInst(generic_type, args) => {
+            type_sig_tokens(generic_type, tokens);
+            for arg in args {
+                type_sig_tokens(arg, tokens);
+            }
+        }
+        TypeSig::FnPtr(method_sig) => method_sig_tokens(method_sig, tokens),
+        _ => {}
+    }
+}
@@ -0,0 +1,48 @@
+//! An in-process stand-in for the CLR, to unit test profilers without .NET.
+//!
+//! [`FakeRuntime`] holds modules, types and methods in memory and hands them
+//! to the profiler through real `ICorProfilerInfo` and `IMetaDataImport`
+//! vtables. [`ProfilerDriver`] loads a profiler the way the runtime does and
+//! calls its `ICorProfilerCallback` vtable in the order the runtime would.
+//! [`ProfilerDriver::replay`] plays back the callbacks of a
+//! [`Recording`](crate::recording::Recording) instead.
+
+// Methods the fakes don't model fail, whatever their arguments.
+macro_rules! not_implemented {
+    ($($name:ident($($arg:ident),*);)*) => {
+        $(
+            unsafe extern "system" fn $name<T, $($arg),*>(_: &T, $(_: $arg),*) -> crate::ffi::HRESULT {
+                crate::ffi::E_NOTIMPL
+            }
+        )*
+    };
+}
+
+mod driver;
+mod fake_function_control;
+mod fake_info;
+mod fake_metadata;
+mod fake_method_malloc;
+mod fake_reference_provider;
+mod replay;
+mod runtime;
+
+pub use self::driver::*;
+pub use self::runtime::*;
+
+use crate::ffi::{ULONG, WCHAR};
+use std::ptr;
+
+/// Copies `name` and its terminating null into a caller's buffer, as far as
+/// it fits, and reports the length the whole name needs.
+unsafe fn copy_name(name: &str, buffer_length: ULONG, length: *mut ULONG, buffer: *mut WCHAR) {
+    let name = name.encode_utf16().chain(Some(0)).collect::<Vec<_>>();
+    if !length.is_null() {
+        *length = name.len() as ULONG;
+    }
+    if !buffer.is_null() && buffer_length > 0 {
+        let copied = name.len().min(buffer_length as usize);
+        ptr::copy_nonoverlapping(name.as_ptr(), buffer, copied);
+        *buffer.add(copied - 1) = 0;
+    }
+}
@@ -0,0 +1,301 @@
+#![allow(non_snake_case)]
+use super::FakeRuntime;
+use crate::{
+    ffi::{
+        CorProfilerCallback as FFICorProfilerCallback, FunctionID, HResult, IClassFactory,
+        ICorProfilerCallback, ICorProfilerCallback10, ICorProfilerCallback11,
+        ICorProfilerCallback2, ICorProfilerCallback3, ICorProfilerCallback4, ICorProfilerCallback5,
+        ICorProfilerCallback6, ICorProfilerCallback7, ICorProfilerCallback8, ICorProfilerCallback9,
+        IUnknown, ModuleID, COR_PRF_MONITOR, GUID, HRESULT, LPVOID, REFCLSID, REFIID, S_OK, UINT,
+    },
+    traits::CorProfilerCallback11,
+    EventMask2,
+};
+use std::{ffi::c_void, ptr, ptr::NonNull};
+
+/// The `DllGetClassObject` a profiler exports, e.g. through `#[clr_profiler]`.
+pub type DllGetClassObject =
+    unsafe extern "system" fn(rclsid: REFCLSID, riid: REFIID, ppv: *mut LPVOID) -> HRESULT;
+
+// The parts of the profiler's vtables the driver calls, whatever the
+// profiler's type.
+
+#[repr(C)]
+struct ClassFactoryVtbl {
+    IUnknown: IUnknown<ClassFactory>,
+    IClassFactory: IClassFactory<ClassFactory>,
+}
+
+#[repr(C)]
+struct ClassFactory {
+    lpVtbl: *const ClassFactoryVtbl,
+}
+
+#[repr(C)]
+pub(super) struct CallbackVtbl {
+    pub IUnknown: IUnknown<Callback>,
+    pub ICorProfilerCallback: ICorProfilerCallback<Callback>,
+    pub ICorProfilerCallback2: ICorProfilerCallback2<Callback>,
+    pub ICorProfilerCallback3: ICorProfilerCallback3<Callback>,
+    pub ICorProfilerCallback4: ICorProfilerCallback4<Callback>,
+    pub ICorProfilerCallback5: ICorProfilerCallback5<Callback>,
+    pub ICorProfilerCallback6: ICorProfilerCallback6<Callback>,
+    pub ICorProfilerCallback7: ICorProfilerCallback7<Callback>,
+    pub ICorProfilerCallback8: ICorProfilerCallback8<Callback>,
+    pub ICorProfilerCallback9: ICorProfilerCallback9<Callback>,
+    pub ICorProfilerCallback10: ICorProfilerCallback10<Callback>,
+    pub ICorProfilerCallback11: ICorProfilerCallback11<Callback>,
+}
+
+#[repr(C)]
+pub(super) struct Callback {
+    lpVtbl: *const CallbackVtbl,
+}
+
+/// Calls a profiler through its `ICorProfilerCallback` vtable, in the order
+/// the runtime would, with a [`FakeRuntime`] behind the `ICorProfilerInfo`
+/// it initializes with.
+///
+/// Like the runtime, the driver leaves out callbacks for events the profiler
+/// didn't turn on, and carries on with a sequence after a callback fails,
+/// unless the profiler failed to initialize. Each method returns the first
+/// failure. The profiler is released on drop.
+pub struct ProfilerDriver {
+    runtime: FakeRuntime,
+    callback: NonNull<Callback>,
+}
+
+impl ProfilerDriver {
+    /// Creates the profiler the way the runtime does: asks
+    /// `dll_get_class_object` for the class factory of `clsid` and asks the
+    /// factory for an `ICorProfilerCallback2`.
+    ///
+    /// # Safety
+    ///
+    /// `dll_get_class_object` must hand out COM objects implementing the
+    /// interfaces it's asked for.
+    pub unsafe fn load(
+        dll_get_class_object: DllGetClassObject,
+        clsid: &GUID,
+        runtime: &FakeRuntime,
+    ) -> Result<Self, HResult> {
+        let mut factory: LPVOID = ptr::null_mut();
+        let hr = dll_get_class_object(clsid, &IClassFactory::IID, &mut factory);
+        succeeded(hr)?;
+        let factory = (factory as *mut ClassFactory)
+            .as_ref()
+            .ok_or(HResult::E_POINTER)?;
+        let vtbl = &*factory.lpVtbl;
+        let mut callback: *mut c_void = ptr::null_mut();
+        let hr = (vtbl.IClassFactory.CreateInstance)(
+            factory,
+            ptr::null_mut(),
+            &ICorProfilerCallback2::IID,
+            &mut callback,
+        );
+        (vtbl.IUnknown.Release)(factory);
+        succeeded(hr)?;
+        let callback = NonNull::new(callback as *mut Callback).ok_or(HResult::E_POINTER)?;
+        Ok(ProfilerDriver {
+            runtime: runtime.clone(),
+            callback,
+        })
+    }
+
+    /// Drives `profiler` directly, adding `events` to the ones it turns on,
+    /// like `#[clr_profiler]` does.
+    pub fn new<T: CorProfilerCallback11>(
+        profiler: T,
+        events: EventMask2,
+        runtime: &FakeRuntime,
+    ) -> Self {
+        let callback = FFICorProfilerCallback::with_events(profiler, events);
+        ProfilerDriver {
+            runtime: runtime.clone(),
+            callback: NonNull::from(callback).cast(),
+        }
+    }
+
+    pub fn runtime(&self) -> &FakeRuntime {
+        &self.runtime
+    }
+
+    /// The profiler and its vtable. Only the interfaces up to
+    /// [`version`](Self::version) may be called.
+    pub(super) fn callback(&self) -> (&Callback, &CallbackVtbl) {
+        let callback = unsafe { self.callback.as_ref() };
+        (callback, unsafe { &*callback.lpVtbl })
+    }
+
+    /// The newest `ICorProfilerCallbackN` the profiler implements.
+    pub(super) fn version(&self) -> u32 {
+        let iids = [
+            ICorProfilerCallback11::IID,
+            ICorProfilerCallback10::IID,
+            ICorProfilerCallback9::IID,
+            ICorProfilerCallback8::IID,
+            ICorProfilerCallback7::IID,
+            ICorProfilerCallback6::IID,
+            ICorProfilerCallback5::IID,
+            ICorProfilerCallback4::IID,
+            ICorProfilerCallback3::IID,
+        ];
+        let (callback, vtbl) = self.callback();
+        for (version, iid) in (3..=11).rev().zip(&iids) {
+            let mut interface: *mut c_void = ptr::null_mut();
+            let hr = unsafe { (vtbl.IUnknown.QueryInterface)(callback, iid, &mut interface) };
+            if hr == S_OK {
+                unsafe { (vtbl.IUnknown.Release)(callback) };
+                return version;
+            }
+        }
+        // the runtime asks for it when creating the profiler
+        2
+    }
+
+    fn monitors(&self, events: COR_PRF_MONITOR) -> bool {
+        self.runtime.event_mask().events_low.contains(events)
+    }
+
+    /// Starts the profiler at startup, with `Initialize`.
+    pub fn initialize(&self) -> Result<(), HResult> {
+        let (callback, vtbl) = self.callback();
+        let info = self.runtime.profiler_info();
+        let hr = unsafe { (vtbl.ICorProfilerCallback.Initialize)(callback, info.as_ptr()) };
+        self.runtime.state().initialized = true;
+        succeeded(hr)
+    }
+
+    /// Attaches the profiler to a running process, with `InitializeForAttach`
+    /// and `ProfilerAttachComplete`.
+    pub fn attach(&self, client_data: &[u8]) -> Result<(), HResult> {
+        let (callback, vtbl) = self.callback();
+        let mut callback_3: *mut c_void = ptr::null_mut();
+        let hr = unsafe {
+            (vtbl.IUnknown.QueryInterface)(callback, &ICorProfilerCallback3::IID, &mut callback_3)
+        };
+        succeeded(hr)?;
+        unsafe { (vtbl.IUnknown.Release)(callback) };
+
+        let info = self.runtime.profiler_info();
+        self.runtime.state().initialized = true;
+        let client_data_ptr = if client_data.is_empty() {
+            ptr::null()
+        } else {
+            client_data.as_ptr() as *const c_void
+        };
+        let hr = unsafe {
+            (vtbl.ICorProfilerCallback3.InitializeForAttach)(
+                callback,
+                info.as_ptr(),
+                client_data_ptr,
+                client_data.len() as UINT,
+            )
+        };
+        succeeded(hr)?;
+        succeeded(unsafe { (vtbl.ICorProfilerCallback3.ProfilerAttachComplete)(callback) })
+    }
+
+    /// Loads a module and the assembly it makes up.
+    pub fn load_module(&self, module_id: ModuleID) -> Result<(), HResult> {
+        let (callback, vtbl) = self.callback();
+        let assembly_id = self
+            .runtime
+            .state()
+            .module(module_id)
+            .expect("unknown module")
+            .assembly_id;
+        let mut results = Vec::new();
+        let assembly_loads = self.monitors(COR_PRF_MONITOR::COR_PRF_MONITOR_ASSEMBLY_LOADS);
+        let module_loads = self.monitors(COR_PRF_MONITOR::COR_PRF_MONITOR_MODULE_LOADS);
+        let v1 = &vtbl.ICorProfilerCallback;
+        unsafe {
+            if assembly_loads {
+                results.push((v1.AssemblyLoadStarted)(callback, assembly_id));
+            }
+            if module_loads {
+                results.push((v1.ModuleLoadStarted)(callback, module_id));
+                results.push((v1.ModuleLoadFinished)(callback, module_id, S_OK));
+                results.push((v1.ModuleAttachedToAssembly)(
+                    callback,
+                    module_id,
+                    assembly_id,
+                ));
+            }
+            if assembly_loads {
+                results.push((v1.AssemblyLoadFinished)(callback, assembly_id, S_OK));
+            }
+        }
+        first_failure(results)
+    }
+
+    /// Unloads a module and the assembly it makes up. The runtime keeps
+    /// knowing about them, as if the IDs were reused.
+    pub fn unload_module(&self, module_id: ModuleID) -> Result<(), HResult> {
+        let (callback, vtbl) = self.callback();
+        let assembly_id = self
+            .runtime
+            .state()
+            .module(module_id)
+            .expect("unknown module")
+            .assembly_id;
+        let mut results = Vec::new();
+        let assembly_loads = self.monitors(COR_PRF_MONITOR::COR_PRF_MONITOR_ASSEMBLY_LOADS);
+        let module_loads = self.monitors(COR_PRF_MONITOR::COR_PRF_MONITOR_MODULE_LOADS);
+        let v1 = &vtbl.ICorProfilerCallback;
+        unsafe {
+            if assembly_loads {
+                results.push((v1.AssemblyUnloadStarted)(callback, assembly_id));
+            }
+            if module_loads {
+                results.push((v1.ModuleUnloadStarted)(callback, module_id));
+                results.push((v1.ModuleUnloadFinished)(callback, module_id, S_OK));
+            }
+            if assembly_loads {
+                results.push((v1.AssemblyUnloadFinished)(callback, assembly_id, S_OK));
+            }
+        }
+        first_failure(results)
+    }
+
+    /// JIT compiles a function. The profiler may rewrite its IL in
+    /// `JITCompilationStarted`.
+    pub fn jit(&self, function_id: FunctionID) -> Result<(), HResult> {
+        if !self.monitors(COR_PRF_MONITOR::COR_PRF_MONITOR_JIT_COMPILATION) {
+            return Ok(());
+        }
+        let (callback, vtbl) = self.callback();
+        let v1 = &vtbl.ICorProfilerCallback;
+        let results = unsafe {
+            vec![
+                (v1.JITCompilationStarted)(callback, function_id, 1),
+                (v1.JITCompilationFinished)(callback, function_id, S_OK, 1),
+            ]
+        };
+        first_failure(results)
+    }
+
+    /// Shuts the profiler down, with `Shutdown`.
+    pub fn shutdown(&self) -> Result<(), HResult> {
+        let (callback, vtbl) = self.callback();
+        succeeded(unsafe { (vtbl.ICorProfilerCallback.Shutdown)(callback) })
+    }
+}
+
+impl Drop for ProfilerDriver {
+    fn drop(&mut self) {
+        let (callback, vtbl) = self.callback();
+        unsafe { (vtbl.IUnknown.Release)(callback) };
+    }
+}
+
+pub(super) fn succeeded(hr: HRESULT) -> Result<(), HResult> {
+    match hr {
+        S_OK => Ok(()),
+        _ => Err(hr.into()),
+    }
+}
+
+pub(super) fn first_failure(results: Vec<HRESULT>) -> Result<(), HResult> {
+    results.into_iter().try_for_each(succeeded)
+}
@@ -0,0 +1,120 @@
+#![allow(non_snake_case)]
+use super::FakeRuntime;
+use crate::{
+    ffi::{
+        ComPtr, CorProfilerFunctionControl, CorProfilerFunctionControlVtbl,
+        ICorProfilerFunctionControl, IUnknown, ModuleID, DWORD, E_INVALIDARG, E_NOINTERFACE,
+        E_POINTER, HRESULT, LPCBYTE, REFIID, S_OK, ULONG,
+    },
+    metadata::MethodDefToken,
+};
+use std::{
+    ffi::c_void,
+    ptr, slice,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+not_implemented! {
+    not_implemented_2(A, B);
+}
+
+/// `ICorProfilerFunctionControl` for rejitting one method of a
+/// [`FakeRuntime`]. The new body replaces the method's current one.
+#[repr(C)]
+pub(crate) struct FakeFunctionControl {
+    control: CorProfilerFunctionControl,
+    ref_count: AtomicU32,
+    runtime: FakeRuntime,
+    module_id: ModuleID,
+    token: MethodDefToken,
+}
+
+impl FakeFunctionControl {
+    pub fn create(
+        runtime: FakeRuntime,
+        module_id: ModuleID,
+        token: MethodDefToken,
+    ) -> ComPtr<CorProfilerFunctionControl> {
+        let fake = Box::new(FakeFunctionControl {
+            control: CorProfilerFunctionControl { lpVtbl: &VTBL },
+            ref_count: AtomicU32::new(1),
+            runtime,
+            module_id,
+            token,
+        });
+        unsafe { ComPtr::from_raw(Box::into_raw(fake) as *mut CorProfilerFunctionControl) }.unwrap()
+    }
+}
+
+unsafe extern "system" fn query_interface(
+    this: &CorProfilerFunctionControl,
+    riid: REFIID,
+    ppv_object: *mut *mut c_void,
+) -> HRESULT {
+    if ppv_object.is_null() {
+        return E_POINTER;
+    }
+    if *riid == IUnknown::IID || *riid == ICorProfilerFunctionControl::IID {
+        add_ref(this);
+        *ppv_object = this as *const CorProfilerFunctionControl as *mut c_void;
+        S_OK
+    } else {
+        *ppv_object = ptr::null_mut();
+        E_NOINTERFACE
+    }
+}
+
+unsafe extern "system" fn add_ref(this: &CorProfilerFunctionControl) -> ULONG {
+    let fake = &*(this as *const CorProfilerFunctionControl as *const FakeFunctionControl);
+    fake.ref_count.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+unsafe extern "system" fn release(this: &CorProfilerFunctionControl) -> ULONG {
+    let fake = this as *const CorProfilerFunctionControl as *mut FakeFunctionControl;
+    let count = (*fake).ref_count.fetch_sub(1, Ordering::AcqRel) - 1;
+    if count == 0 {
+        drop(Box::from_raw(fake));
+    }
+    count
+}
+
+unsafe extern "system" fn set_codegen_flags(
+    _this: &CorProfilerFunctionControl,
+    _flags: DWORD,
+) -> HRESULT {
+    // code isn't generated
+    S_OK
+}
+
+unsafe extern "system" fn set_il_function_body(
+    this: &CorProfilerFunctionControl,
+    cb_new_il_method_header: ULONG,
+    pb_new_il_method_header: LPCBYTE,
+) -> HRESULT {
+    if pb_new_il_method_header.is_null() {
+        return E_POINTER;
+    }
+    let fake = &*(this as *const CorProfilerFunctionControl as *const FakeFunctionControl);
+    let body = slice::from_raw_parts(pb_new_il_method_header, cb_new_il_method_header as usize);
+    if fake
+        .runtime
+        .set_il_function_body(fake.module_id, fake.token, body.to_vec())
+    {
+        S_OK
+    } else {
+        E_INVALIDARG
+    }
+}
+
+static VTBL: CorProfilerFunctionControlVtbl = CorProfilerFunctionControlVtbl {
+    IUnknown: IUnknown {
+        QueryInterface: query_interface,
+        AddRef: add_ref,
+        Release: release,
+    },
+    ICorProfilerFunctionControl: ICorProfilerFunctionControl {
+        SetCodegenFlags: set_codegen_flags,
+        SetILFunctionBody: set_il_function_body,
+        SetILInstrumentedCodeMap: not_implemented_2,
+    },
+};
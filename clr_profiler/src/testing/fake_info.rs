@@ -0,0 +1,868 @@
+#![allow(non_snake_case)]
+use super::{
+    copy_name, fake_metadata::FakeMetadata, fake_method_malloc::FakeMethodMalloc, FakeRuntime,
+};
+use crate::{
+    ffi::{
+        int, mdMethodDef, mdToken, mdTypeDef, AppDomainID, AssemblyID, ClassID, ComPtr,
+        CorElementType, CorProfilerInfo, CorProfilerInfoVtbl, FunctionID, HResult,
+        ICorProfilerInfo, ICorProfilerInfo10, ICorProfilerInfo11, ICorProfilerInfo12,
+        ICorProfilerInfo13, ICorProfilerInfo14, ICorProfilerInfo2, ICorProfilerInfo3,
+        ICorProfilerInfo4, ICorProfilerInfo5, ICorProfilerInfo6, ICorProfilerInfo7,
+        ICorProfilerInfo8, ICorProfilerInfo9, IUnknown, MetaDataImport, MethodMalloc, ModuleID,
+        ObjectHandleID, ObjectID, Unknown, BYTE, COR_PRF_CODE_INFO, COR_PRF_FRAME_INFO,
+        COR_PRF_HANDLE_TYPE, DWORD, E_FAIL, E_INVALIDARG, E_NOINTERFACE, E_POINTER, GUID, HRESULT,
+        LPCBYTE, REFIID, S_FALSE, S_OK, ULONG, ULONG32, WCHAR,
+    },
+    metadata::MethodDefToken,
+};
+use std::{
+    convert::{TryFrom, TryInto},
+    ffi::c_void,
+    ptr, slice,
+    sync::atomic::{AtomicU32, Ordering},
+};
+use widestring::U16CStr;
+
+not_implemented! {
+    not_implemented_0();
+    not_implemented_1(A);
+    not_implemented_2(A, B);
+    not_implemented_3(A, B, C);
+    not_implemented_4(A, B, C, D);
+    not_implemented_5(A, B, C, D, E);
+    not_implemented_6(A, B, C, D, E, F);
+    not_implemented_7(A, B, C, D, E, F, G);
+    not_implemented_9(A, B, C, D, E, F, G, H, I);
+    not_implemented_11(A, B, C, D, E, F, G, H, I, J, K);
+}
+
+const IIDS: [GUID; 15] = [
+    IUnknown::IID,
+    ICorProfilerInfo::IID,
+    ICorProfilerInfo2::IID,
+    ICorProfilerInfo3::IID,
+    ICorProfilerInfo4::IID,
+    ICorProfilerInfo5::IID,
+    ICorProfilerInfo6::IID,
+    ICorProfilerInfo7::IID,
+    ICorProfilerInfo8::IID,
+    ICorProfilerInfo9::IID,
+    ICorProfilerInfo10::IID,
+    ICorProfilerInfo11::IID,
+    ICorProfilerInfo12::IID,
+    ICorProfilerInfo13::IID,
+    ICorProfilerInfo14::IID,
+];
+
+const ERROR_ENVVAR_NOT_FOUND: u32 = 203;
+
+/// `ICorProfilerInfo14` over a [`FakeRuntime`].
+#[repr(C)]
+pub(crate) struct FakeInfo {
+    info: CorProfilerInfo,
+    ref_count: AtomicU32,
+    runtime: FakeRuntime,
+}
+
+impl FakeInfo {
+    pub fn create(runtime: FakeRuntime) -> ComPtr<CorProfilerInfo> {
+        let fake = Box::new(FakeInfo {
+            info: CorProfilerInfo { lpVtbl: &VTBL },
+            ref_count: AtomicU32::new(1),
+            runtime,
+        });
+        unsafe { ComPtr::from_raw(Box::into_raw(fake) as *mut CorProfilerInfo) }.unwrap()
+    }
+
+    unsafe fn runtime(this: &CorProfilerInfo) -> &FakeRuntime {
+        &(*(this as *const CorProfilerInfo as *const FakeInfo)).runtime
+    }
+}
+
+/// The size of a method body, from its header and the sections following
+/// the code. `None` if the header is malformed.
+unsafe fn method_size(header: LPCBYTE) -> Option<usize> {
+    const FAT_FORMAT: u8 = 0x3;
+    const TINY_FORMAT: u8 = 0x2;
+    const MORE_SECTS: u8 = 0x8;
+    const SECT_FAT_FORMAT: u8 = 0x40;
+    const SECT_MORE_SECTS: u8 = 0x80;
+
+    let flags = *header;
+    if flags & FAT_FORMAT == TINY_FORMAT {
+        return Some(1 + (flags >> 2) as usize);
+    }
+    if flags & FAT_FORMAT != FAT_FORMAT {
+        return None;
+    }
+    let fat_header = slice::from_raw_parts(header, 12);
+    let code_size = u32::from_le_bytes(fat_header[4..8].try_into().unwrap());
+    let mut size = 12 + code_size as usize;
+    let mut more_sects = flags & MORE_SECTS != 0;
+    while more_sects {
+        // sections are DWORD aligned
+        size = (size + 3) & !3;
+        let section = slice::from_raw_parts(header.add(size), 4);
+        let data_size = if section[0] & SECT_FAT_FORMAT != 0 {
+            u32::from_le_bytes([section[1], section[2], section[3], 0]) as usize
+        } else {
+            section[1] as usize
+        };
+        if data_size < 4 {
+            return None;
+        }
+        size += data_size;
+        more_sects = section[0] & SECT_MORE_SECTS != 0;
+    }
+    Some(size)
+}
+
+unsafe extern "system" fn query_interface(
+    this: &CorProfilerInfo,
+    riid: REFIID,
+    ppv_object: *mut *mut c_void,
+) -> HRESULT {
+    if ppv_object.is_null() {
+        return E_POINTER;
+    }
+    let level = FakeInfo::runtime(this).state().interface_level;
+    let implemented = level.map_or(&IIDS[..], |level| &IIDS[..=level as usize]);
+    if implemented.contains(&*riid) {
+        add_ref(this);
+        *ppv_object = this as *const CorProfilerInfo as *mut c_void;
+        S_OK
+    } else {
+        *ppv_object = ptr::null_mut();
+        E_NOINTERFACE
+    }
+}
+
+unsafe extern "system" fn add_ref(this: &CorProfilerInfo) -> ULONG {
+    let fake = &*(this as *const CorProfilerInfo as *const FakeInfo);
+    fake.ref_count.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+unsafe extern "system" fn release(this: &CorProfilerInfo) -> ULONG {
+    let fake = this as *const CorProfilerInfo as *mut FakeInfo;
+    let count = (*fake).ref_count.fetch_sub(1, Ordering::AcqRel) - 1;
+    if count == 0 {
+        drop(Box::from_raw(fake));
+    }
+    count
+}
+
+unsafe extern "system" fn get_event_mask(
+    this: &CorProfilerInfo,
+    pdw_events: *mut DWORD,
+) -> HRESULT {
+    *pdw_events = FakeInfo::runtime(this).state().events.0;
+    S_OK
+}
+
+unsafe extern "system" fn set_event_mask(this: &CorProfilerInfo, dw_events: DWORD) -> HRESULT {
+    let mut state = FakeInfo::runtime(this).state();
+    let (_, events_high) = state.events;
+    if !state.can_set_events(dw_events, events_high) {
+        return E_FAIL;
+    }
+    state.events.0 = dw_events;
+    S_OK
+}
+
+unsafe extern "system" fn get_event_mask_2(
+    this: &CorProfilerInfo,
+    pdw_events_low: *mut DWORD,
+    pdw_events_high: *mut DWORD,
+) -> HRESULT {
+    let (low, high) = FakeInfo::runtime(this).state().events;
+    *pdw_events_low = low;
+    *pdw_events_high = high;
+    S_OK
+}
+
+unsafe extern "system" fn set_event_mask_2(
+    this: &CorProfilerInfo,
+    dw_events_low: DWORD,
+    dw_events_high: DWORD,
+) -> HRESULT {
+    let mut state = FakeInfo::runtime(this).state();
+    if !state.can_set_events(dw_events_low, dw_events_high) {
+        return E_FAIL;
+    }
+    state.events = (dw_events_low, dw_events_high);
+    S_OK
+}
+
+unsafe extern "system" fn get_class_from_object(
+    this: &CorProfilerInfo,
+    object_id: ObjectID,
+    p_class_id: *mut ClassID,
+) -> HRESULT {
+    let state = FakeInfo::runtime(this).state();
+    let Some(object) = state.object(object_id) else {
+        return E_INVALIDARG;
+    };
+    *p_class_id = object.class_id;
+    S_OK
+}
+
+unsafe extern "system" fn get_function_info(
+    this: &CorProfilerInfo,
+    function_id: FunctionID,
+    class_id: *mut ClassID,
+    module_id: *mut ModuleID,
+    token: *mut mdToken,
+) -> HRESULT {
+    let state = FakeInfo::runtime(this).state();
+    let Some(function) = state.function(function_id) else {
+        return E_INVALIDARG;
+    };
+    if !class_id.is_null() {
+        *class_id = function.class_id.unwrap_or(0);
+    }
+    if !module_id.is_null() {
+        *module_id = function.module_id;
+    }
+    if !token.is_null() {
+        *token = function.token.raw();
+    }
+    S_OK
+}
+
+unsafe extern "system" fn get_function_from_token(
+    this: &CorProfilerInfo,
+    module_id: ModuleID,
+    token: mdToken,
+    function_id: *mut FunctionID,
+) -> HRESULT {
+    let state = FakeInfo::runtime(this).state();
+    let Some(function) = state
+        .functions
+        .iter()
+        .find(|function| function.module_id == module_id && function.token.raw() == token)
+    else {
+        return E_INVALIDARG;
+    };
+    *function_id = function.id;
+    S_OK
+}
+
+unsafe extern "system" fn get_class_id_info(
+    this: &CorProfilerInfo,
+    class_id: ClassID,
+    module_id: *mut ModuleID,
+    type_def_token: *mut mdTypeDef,
+) -> HRESULT {
+    let Some((class_module, class_token)) = FakeInfo::runtime(this).state().class(class_id) else {
+        return E_INVALIDARG;
+    };
+    if !module_id.is_null() {
+        *module_id = class_module;
+    }
+    if !type_def_token.is_null() {
+        *type_def_token = class_token.raw();
+    }
+    S_OK
+}
+
+unsafe extern "system" fn get_module_info(
+    this: &CorProfilerInfo,
+    module_id: ModuleID,
+    base_load_address: *mut LPCBYTE,
+    name_buffer_length: ULONG,
+    name_length: *mut ULONG,
+    name: *mut WCHAR,
+    assembly_id: *mut AssemblyID,
+) -> HRESULT {
+    get_module_info_2(
+        this,
+        module_id,
+        base_load_address,
+        name_buffer_length,
+        name_length,
+        name,
+        assembly_id,
+        ptr::null_mut(),
+    )
+}
+
+unsafe extern "system" fn get_module_info_2(
+    this: &CorProfilerInfo,
+    module_id: ModuleID,
+    base_load_address: *mut LPCBYTE,
+    name_buffer_length: ULONG,
+    name_length: *mut ULONG,
+    name: *mut WCHAR,
+    assembly_id: *mut AssemblyID,
+    module_flags: *mut DWORD,
+) -> HRESULT {
+    let state = FakeInfo::runtime(this).state();
+    let Some(module) = state.module(module_id) else {
+        return E_INVALIDARG;
+    };
+    // modules only exist in memory, like dynamic ones
+    if !base_load_address.is_null() {
+        *base_load_address = ptr::null();
+    }
+    copy_name(&module.name, name_buffer_length, name_length, name);
+    if !assembly_id.is_null() {
+        *assembly_id = module.assembly_id;
+    }
+    if !module_flags.is_null() {
+        *module_flags = module.flags.bits();
+    }
+    S_OK
+}
+
+unsafe extern "system" fn get_assembly_info(
+    this: &CorProfilerInfo,
+    assembly_id: AssemblyID,
+    name_buffer_length: ULONG,
+    name_length: *mut ULONG,
+    name: *mut WCHAR,
+    app_domain_id: *mut AppDomainID,
+    module_id: *mut ModuleID,
+) -> HRESULT {
+    let state = FakeInfo::runtime(this).state();
+    let Some(module) = state
+        .modules
+        .iter()
+        .find(|module| module.assembly_id == assembly_id)
+    else {
+        return E_INVALIDARG;
+    };
+    // every module makes up an assembly of its own, named after it
+    let assembly_name = module
+        .name
+        .rsplit_once('.')
+        .map_or(module.name.as_str(), |(stem, _)| stem);
+    copy_name(assembly_name, name_buffer_length, name_length, name);
+    if !app_domain_id.is_null() {
+        *app_domain_id = 0;
+    }
+    if !module_id.is_null() {
+        *module_id = module.id;
+    }
+    S_OK
+}
+
+unsafe extern "system" fn get_module_metadata(
+    this: &CorProfilerInfo,
+    module_id: ModuleID,
+    _dw_open_flags: DWORD,
+    riid: REFIID,
+    pp_out: *mut *mut MetaDataImport,
+) -> HRESULT {
+    let runtime = FakeInfo::runtime(this);
+    {
+        let mut state = runtime.state();
+        if state.module(module_id).is_none() {
+            return E_INVALIDARG;
+        }
+        state.metadata_requests += 1;
+    }
+    let metadata = FakeMetadata::create(runtime.clone(), module_id);
+    let unknown = &*(metadata.as_ptr() as *const Unknown);
+    unknown.QueryInterface(riid, pp_out as *mut *mut c_void)
+}
+
+unsafe extern "system" fn get_token_and_metadata_from_function(
+    this: &CorProfilerInfo,
+    function_id: FunctionID,
+    riid: REFIID,
+    pp_import: *mut *mut MetaDataImport,
+    p_token: *mut mdToken,
+) -> HRESULT {
+    let state = FakeInfo::runtime(this).state();
+    let Some((module_id, token)) = state
+        .function(function_id)
+        .map(|function| (function.module_id, function.token))
+    else {
+        return E_INVALIDARG;
+    };
+    if state.quirks.null_results {
+        *pp_import = ptr::null_mut();
+        *p_token = token.raw();
+        return S_OK;
+    }
+    drop(state);
+    let hr = get_module_metadata(this, module_id, 0, riid, pp_import);
+    if hr == S_OK {
+        *p_token = token.raw();
+    }
+    hr
+}
+
+unsafe extern "system" fn get_il_function_body(
+    this: &CorProfilerInfo,
+    module_id: ModuleID,
+    method_id: mdMethodDef,
+    pp_method_header: *mut LPCBYTE,
+    pcb_method_size: *mut ULONG,
+) -> HRESULT {
+    let state = FakeInfo::runtime(this).state();
+    let Some(method) = MethodDefToken::try_from(method_id)
+        .ok()
+        .and_then(|token| state.method(module_id, token))
+    else {
+        return E_INVALIDARG;
+    };
+    let Some(body) = method.bodies.last() else {
+        return HResult::CORPROF_E_FUNCTION_NOT_IL.into();
+    };
+    if state.quirks.null_results {
+        *pp_method_header = ptr::null();
+        return S_OK;
+    }
+    *pp_method_header = body.as_ptr();
+    if !pcb_method_size.is_null() {
+        *pcb_method_size = body.len() as ULONG;
+    }
+    S_OK
+}
+
+unsafe extern "system" fn set_il_function_body(
+    this: &CorProfilerInfo,
+    module_id: ModuleID,
+    method_id: mdMethodDef,
+    pb_new_il_method_header: LPCBYTE,
+) -> HRESULT {
+    if pb_new_il_method_header.is_null() {
+        return E_POINTER;
+    }
+    let runtime = FakeInfo::runtime(this);
+    // like the runtime, only take bodies from the module's IL allocator
+    let Some(left) = runtime
+        .state()
+        .il_allocation_left(module_id, pb_new_il_method_header)
+    else {
+        return E_INVALIDARG;
+    };
+    let Some(size) = method_size(pb_new_il_method_header).filter(|&size| size <= left) else {
+        return HResult::COR_E_INVALIDPROGRAM.into();
+    };
+    let body = slice::from_raw_parts(pb_new_il_method_header, size).to_vec();
+    match MethodDefToken::try_from(method_id) {
+        Ok(token) if runtime.set_il_function_body(module_id, token, body) => S_OK,
+        _ => E_INVALIDARG,
+    }
+}
+
+unsafe extern "system" fn get_il_function_body_allocator(
+    this: &CorProfilerInfo,
+    module_id: ModuleID,
+    pp_malloc: *mut *mut MethodMalloc,
+) -> HRESULT {
+    let runtime = FakeInfo::runtime(this);
+    {
+        let state = runtime.state();
+        if state.module(module_id).is_none() {
+            return E_INVALIDARG;
+        }
+        if state.quirks.null_results {
+            *pp_malloc = ptr::null_mut();
+            return S_OK;
+        }
+    }
+    *pp_malloc = FakeMethodMalloc::create(runtime.clone(), module_id).into_raw();
+    S_OK
+}
+
+unsafe extern "system" fn is_array_class(
+    this: &CorProfilerInfo,
+    class_id: ClassID,
+    p_base_elem_type: *mut CorElementType,
+    p_base_class_id: *mut ClassID,
+    pc_rank: *mut ULONG,
+) -> HRESULT {
+    let state = FakeInfo::runtime(this).state();
+    let Some(array) = state.array_class(class_id) else {
+        return match state.class(class_id) {
+            Some(_) => S_FALSE,
+            None => E_INVALIDARG,
+        };
+    };
+    if !p_base_elem_type.is_null() {
+        *p_base_elem_type = CorElementType::from(array.element_type);
+    }
+    if !p_base_class_id.is_null() {
+        *p_base_class_id = array.element_class_id.unwrap_or(0);
+    }
+    if !pc_rank.is_null() {
+        *pc_rank = array.rank;
+    }
+    S_OK
+}
+
+unsafe extern "system" fn get_class_id_info_2(
+    this: &CorProfilerInfo,
+    class_id: ClassID,
+    p_module_id: *mut ModuleID,
+    p_type_def_token: *mut mdTypeDef,
+    p_parent_class_id: *mut ClassID,
+    c_num_type_args: ULONG32,
+    pc_num_type_args: *mut ULONG32,
+    type_args: *mut ClassID,
+) -> HRESULT {
+    let state = FakeInfo::runtime(this).state();
+    if state.array_class(class_id).is_some() {
+        return HResult::CORPROF_E_CLASSID_IS_ARRAY.into();
+    }
+    let Some((module_id, token)) = state.class(class_id) else {
+        return E_INVALIDARG;
+    };
+    if !p_module_id.is_null() {
+        *p_module_id = module_id;
+    }
+    if !p_type_def_token.is_null() {
+        *p_type_def_token = token.raw();
+    }
+    // types don't derive from anything the runtime loaded, like
+    // System.Object
+    if !p_parent_class_id.is_null() {
+        *p_parent_class_id = 0;
+    }
+    copy_type_args(
+        state.type_args(class_id),
+        c_num_type_args,
+        pc_num_type_args,
+        type_args,
+    )
+}
+
+/// Copies as many type arguments as fit, and reports how many there are.
+unsafe fn copy_type_args(
+    class_ids: &[ClassID],
+    c_type_args: ULONG32,
+    pc_type_args: *mut ULONG32,
+    type_args: *mut ClassID,
+) -> HRESULT {
+    if !pc_type_args.is_null() {
+        *pc_type_args = class_ids.len() as ULONG32;
+    }
+    if c_type_args > 0 {
+        if type_args.is_null() {
+            return E_POINTER;
+        }
+        let copied = class_ids.len().min(c_type_args as usize);
+        ptr::copy_nonoverlapping(class_ids.as_ptr(), type_args, copied);
+    }
+    S_OK
+}
+
+unsafe extern "system" fn get_function_info_2(
+    this: &CorProfilerInfo,
+    function_id: FunctionID,
+    _frame_info: COR_PRF_FRAME_INFO,
+    p_class_id: *mut ClassID,
+    p_module_id: *mut ModuleID,
+    p_token: *mut mdToken,
+    c_type_args: ULONG32,
+    pc_type_args: *mut ULONG32,
+    type_args: *mut ClassID,
+) -> HRESULT {
+    let hr = get_function_info(this, function_id, p_class_id, p_module_id, p_token);
+    if hr != S_OK {
+        return hr;
+    }
+    // methods aren't generic
+    copy_type_args(&[], c_type_args, pc_type_args, type_args)
+}
+
+unsafe extern "system" fn get_code_info_2(
+    this: &CorProfilerInfo,
+    function_id: FunctionID,
+    c_code_infos: ULONG32,
+    pc_code_infos: *mut ULONG32,
+    code_infos: *mut COR_PRF_CODE_INFO,
+) -> HRESULT {
+    let state = FakeInfo::runtime(this).state();
+    let Some(function) = state.function(function_id) else {
+        return E_INVALIDARG;
+    };
+    if c_code_infos == 0 {
+        let overstated = state.quirks.overstated_lengths as usize;
+        *pc_code_infos = (function.code.len() + overstated) as ULONG32;
+        return S_OK;
+    }
+    let filled = function.code.len().min(c_code_infos as usize);
+    for (i, &(start, size)) in function.code[..filled].iter().enumerate() {
+        *code_infos.add(i) = COR_PRF_CODE_INFO {
+            startAddress: start,
+            size,
+        };
+    }
+    *pc_code_infos = filled as ULONG32;
+    S_OK
+}
+
+unsafe extern "system" fn get_array_object_info(
+    this: &CorProfilerInfo,
+    object_id: ObjectID,
+    c_dimensions: ULONG32,
+    p_dimension_sizes: *mut ULONG32,
+    p_dimension_lower_bounds: *mut int,
+    pp_data: *mut *mut BYTE,
+) -> HRESULT {
+    let mut state = FakeInfo::runtime(this).state();
+    let Some(object) = state
+        .objects
+        .iter_mut()
+        .find(|object| object.id == object_id)
+    else {
+        return E_INVALIDARG;
+    };
+    if c_dimensions as usize != object.dimension_sizes.len() {
+        return E_INVALIDARG;
+    }
+    for (i, &size) in object.dimension_sizes.iter().enumerate() {
+        *p_dimension_sizes.add(i) = size;
+        *p_dimension_lower_bounds.add(i) = 0;
+    }
+    // empty arrays have no data
+    *pp_data = if object.data.is_empty() {
+        ptr::null_mut()
+    } else {
+        object.data.as_mut_ptr()
+    };
+    S_OK
+}
+
+unsafe extern "system" fn get_environment_variable(
+    this: &CorProfilerInfo,
+    sz_name: *const WCHAR,
+    cch_value: ULONG,
+    pcch_value: *mut ULONG,
+    sz_value: *mut WCHAR,
+) -> HRESULT {
+    if sz_name.is_null() {
+        return E_POINTER;
+    }
+    let name = U16CStr::from_ptr_str(sz_name).to_string_lossy();
+    let state = FakeInfo::runtime(this).state();
+    match state.environment.get(&name) {
+        Some(value) => {
+            copy_name(value, cch_value, pcch_value, sz_value);
+            S_OK
+        }
+        None => HResult::from_win32(ERROR_ENVVAR_NOT_FOUND).into(),
+    }
+}
+
+unsafe extern "system" fn set_environment_variable(
+    this: &CorProfilerInfo,
+    sz_name: *const WCHAR,
+    sz_value: *const WCHAR,
+) -> HRESULT {
+    if sz_name.is_null() {
+        return E_POINTER;
+    }
+    let name = U16CStr::from_ptr_str(sz_name).to_string_lossy();
+    let mut state = FakeInfo::runtime(this).state();
+    if sz_value.is_null() {
+        state.environment.remove(&name);
+    } else {
+        let value = U16CStr::from_ptr_str(sz_value).to_string_lossy();
+        state.environment.insert(name, value);
+    }
+    S_OK
+}
+
+unsafe extern "system" fn create_handle(
+    this: &CorProfilerInfo,
+    object: ObjectID,
+    handle_type: COR_PRF_HANDLE_TYPE,
+    p_handle: *mut ObjectHandleID,
+) -> HRESULT {
+    let mut state = FakeInfo::runtime(this).state();
+    if state.object(object).is_none() {
+        return E_INVALIDARG;
+    }
+    let handle = state.next_id();
+    state.handles.push((handle, object, handle_type));
+    *p_handle = handle as ObjectHandleID;
+    S_OK
+}
+
+unsafe extern "system" fn destroy_handle(
+    this: &CorProfilerInfo,
+    handle: ObjectHandleID,
+) -> HRESULT {
+    let mut state = FakeInfo::runtime(this).state();
+    let count = state.handles.len();
+    state.handles.retain(|&(id, ..)| id != handle as usize);
+    if state.handles.len() == count {
+        return E_INVALIDARG;
+    }
+    S_OK
+}
+
+unsafe extern "system" fn get_object_id_from_handle(
+    this: &CorProfilerInfo,
+    handle: ObjectHandleID,
+    p_object: *mut ObjectID,
+) -> HRESULT {
+    let state = FakeInfo::runtime(this).state();
+    let Some(&(_, object, _)) = state
+        .handles
+        .iter()
+        .find(|&&(id, ..)| id == handle as usize)
+    else {
+        return E_INVALIDARG;
+    };
+    // weak handles outlive the objects they refer to
+    *p_object = if state.object(object).is_some() {
+        object
+    } else {
+        0
+    };
+    S_OK
+}
+
+static VTBL: CorProfilerInfoVtbl = CorProfilerInfoVtbl {
+    IUnknown: IUnknown {
+        QueryInterface: query_interface,
+        AddRef: add_ref,
+        Release: release,
+    },
+    ICorProfilerInfo: ICorProfilerInfo {
+        GetClassFromObject: get_class_from_object,
+        GetClassFromToken: not_implemented_3,
+        GetCodeInfo: not_implemented_3,
+        GetEventMask: get_event_mask,
+        GetFunctionFromIP: not_implemented_2,
+        GetFunctionFromToken: get_function_from_token,
+        GetHandleFromThread: not_implemented_2,
+        GetObjectSize: not_implemented_2,
+        IsArrayClass: is_array_class,
+        GetThreadInfo: not_implemented_2,
+        GetCurrentThreadID: not_implemented_1,
+        GetClassIDInfo: get_class_id_info,
+        GetFunctionInfo: get_function_info,
+        SetEventMask: set_event_mask,
+        SetEnterLeaveFunctionHooks: not_implemented_3,
+        SetFunctionIDMapper: not_implemented_1,
+        GetTokenAndMetaDataFromFunction: get_token_and_metadata_from_function,
+        GetModuleInfo: get_module_info,
+        GetModuleMetaData: get_module_metadata,
+        GetILFunctionBody: get_il_function_body,
+        GetILFunctionBodyAllocator: get_il_function_body_allocator,
+        SetILFunctionBody: set_il_function_body,
+        GetAppDomainInfo: not_implemented_5,
+        GetAssemblyInfo: get_assembly_info,
+        SetFunctionReJIT: not_implemented_1,
+        ForceGC: not_implemented_0,
+        SetILInstrumentedCodeMap: not_implemented_4,
+        GetInprocInspectionInterface: not_implemented_1,
+        GetInprocInspectionIThisThread: not_implemented_1,
+        GetThreadContext: not_implemented_2,
+        BeginInprocDebugging: not_implemented_2,
+        EndInprocDebugging: not_implemented_1,
+        GetILToNativeMapping: not_implemented_4,
+    },
+    ICorProfilerInfo2: ICorProfilerInfo2 {
+        DoStackSnapshot: not_implemented_6,
+        SetEnterLeaveFunctionHooks2: not_implemented_3,
+        GetFunctionInfo2: get_function_info_2,
+        GetStringLayout: not_implemented_3,
+        GetClassLayout: not_implemented_5,
+        GetClassIDInfo2: get_class_id_info_2,
+        GetCodeInfo2: get_code_info_2,
+        GetClassFromTokenAndTypeArgs: not_implemented_5,
+        GetFunctionFromTokenAndTypeArgs: not_implemented_6,
+        EnumModuleFrozenObjects: not_implemented_2,
+        GetArrayObjectInfo: get_array_object_info,
+        GetBoxClassLayout: not_implemented_2,
+        GetThreadAppDomain: not_implemented_2,
+        GetRVAStaticAddress: not_implemented_3,
+        GetAppDomainStaticAddress: not_implemented_4,
+        GetThreadStaticAddress: not_implemented_4,
+        GetContextStaticAddress: not_implemented_4,
+        GetStaticFieldInfo: not_implemented_3,
+        GetGenerationBounds: not_implemented_3,
+        GetObjectGeneration: not_implemented_2,
+        GetNotifiedExceptionClauseInfo: not_implemented_1,
+    },
+    ICorProfilerInfo3: ICorProfilerInfo3 {
+        EnumJITedFunctions: not_implemented_1,
+        RequestProfilerDetach: not_implemented_1,
+        SetFunctionIDMapper2: not_implemented_2,
+        GetStringLayout2: not_implemented_2,
+        SetEnterLeaveFunctionHooks3: not_implemented_3,
+        SetEnterLeaveFunctionHooks3WithInfo: not_implemented_3,
+        GetFunctionEnter3Info: not_implemented_5,
+        GetFunctionLeave3Info: not_implemented_4,
+        GetFunctionTailcall3Info: not_implemented_3,
+        EnumModules: not_implemented_1,
+        GetRuntimeInformation: not_implemented_9,
+        GetThreadStaticAddress2: not_implemented_5,
+        GetAppDomainsContainingModule: not_implemented_4,
+        GetModuleInfo2: get_module_info_2,
+    },
+    ICorProfilerInfo4: ICorProfilerInfo4 {
+        EnumThreads: not_implemented_1,
+        InitializeCurrentThread: not_implemented_0,
+        RequestReJIT: not_implemented_3,
+        RequestRevert: not_implemented_4,
+        GetCodeInfo3: not_implemented_5,
+        GetFunctionFromIP2: not_implemented_3,
+        GetReJITIDs: not_implemented_4,
+        GetILToNativeMapping2: not_implemented_5,
+        EnumJITedFunctions2: not_implemented_1,
+        GetObjectSize2: not_implemented_2,
+    },
+    ICorProfilerInfo5: ICorProfilerInfo5 {
+        GetEventMask2: get_event_mask_2,
+        SetEventMask2: set_event_mask_2,
+    },
+    ICorProfilerInfo6: ICorProfilerInfo6 {
+        EnumNgenModuleMethodsInliningThisMethod: not_implemented_5,
+    },
+    ICorProfilerInfo7: ICorProfilerInfo7 {
+        ApplyMetaData: not_implemented_1,
+        GetInMemorySymbolsLength: not_implemented_2,
+        ReadInMemorySymbols: not_implemented_5,
+    },
+    ICorProfilerInfo8: ICorProfilerInfo8 {
+        IsFunctionDynamic: not_implemented_2,
+        GetFunctionFromIP3: not_implemented_3,
+        GetDynamicFunctionInfo: not_implemented_7,
+    },
+    ICorProfilerInfo9: ICorProfilerInfo9 {
+        GetNativeCodeStartAddresses: not_implemented_5,
+        GetILToNativeMapping3: not_implemented_4,
+        GetCodeInfo4: not_implemented_4,
+    },
+    ICorProfilerInfo10: ICorProfilerInfo10 {
+        EnumerateObjectReferences: not_implemented_3,
+        IsFrozenObject: not_implemented_2,
+        GetLOHObjectSizeThreshold: not_implemented_1,
+        RequestReJITWithInliners: not_implemented_4,
+        SuspendRuntime: not_implemented_0,
+        ResumeRuntime: not_implemented_0,
+    },
+    ICorProfilerInfo11: ICorProfilerInfo11 {
+        GetEnvironmentVariable: get_environment_variable,
+        SetEnvironmentVariable: set_environment_variable,
+    },
+    ICorProfilerInfo12: ICorProfilerInfo12 {
+        EventPipeStartSession: not_implemented_4,
+        EventPipeAddProviderToSession: not_implemented_2,
+        EventPipeStopSession: not_implemented_1,
+        EventPipeCreateProvider: not_implemented_2,
+        EventPipeGetProviderInfo: not_implemented_4,
+        EventPipeDefineEvent: not_implemented_11,
+        EventPipeWriteEvent: not_implemented_5,
+    },
+    ICorProfilerInfo13: ICorProfilerInfo13 {
+        CreateHandle: create_handle,
+        DestroyHandle: destroy_handle,
+        GetObjectIDFromHandle: get_object_id_from_handle,
+    },
+    ICorProfilerInfo14: ICorProfilerInfo14 {
+        EnumerateNonGCObjects: not_implemented_1,
+        GetNonGCHeapBounds: not_implemented_3,
+        EventPipeCreateProvider2: not_implemented_3,
+    },
+};
@@ -0,0 +1,540 @@
+#![allow(non_snake_case)]
+use super::{copy_name, runtime::FakeModule, FakeRuntime};
+use crate::{
+    ffi::{
+        mdGenericParam, mdMethodDef, mdString, mdToken, mdTypeDef, mdTypeRef, ComPtr,
+        IMetaDataImport, IMetaDataImport2, IUnknown, MetaDataImport, MetaDataImportVtbl, ModuleID,
+        BOOL, CLDB_E_RECORD_NOTFOUND, CLDB_S_TRUNCATION, DWORD, E_INVALIDARG, E_NOINTERFACE,
+        E_POINTER, HCORENUM, HRESULT, LPCWSTR, PCCOR_SIGNATURE, REFIID, S_FALSE, S_OK, ULONG,
+        WCHAR,
+    },
+    metadata::{GenericParamToken, MethodDefToken, StringToken, TypeDefToken, TypeRefToken},
+};
+use std::{
+    convert::TryFrom,
+    ffi::c_void,
+    ptr,
+    sync::atomic::{AtomicU32, Ordering},
+};
+use widestring::U16CStr;
+
+not_implemented! {
+    not_implemented_1(A);
+    not_implemented_2(A, B);
+    not_implemented_3(A, B, C);
+    not_implemented_4(A, B, C, D);
+    not_implemented_5(A, B, C, D, E);
+    not_implemented_6(A, B, C, D, E, F);
+    not_implemented_7(A, B, C, D, E, F, G);
+    not_implemented_10(A, B, C, D, E, F, G, H, I, J);
+    not_implemented_11(A, B, C, D, E, F, G, H, I, J, K);
+    not_implemented_13(A, B, C, D, E, F, G, H, I, J, K, L, M);
+    not_implemented_16(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P);
+}
+
+/// `IMetaDataImport2` over one module of a [`FakeRuntime`]. Only type
+/// definitions and references, methods, generic parameters and user strings
+/// are modeled.
+#[repr(C)]
+pub(crate) struct FakeMetadata {
+    import: MetaDataImport,
+    ref_count: AtomicU32,
+    runtime: FakeRuntime,
+    module_id: ModuleID,
+}
+
+impl FakeMetadata {
+    pub fn create(runtime: FakeRuntime, module_id: ModuleID) -> ComPtr<MetaDataImport> {
+        let fake = Box::new(FakeMetadata {
+            import: MetaDataImport { lpVtbl: &VTBL },
+            ref_count: AtomicU32::new(1),
+            runtime,
+            module_id,
+        });
+        unsafe { ComPtr::from_raw(Box::into_raw(fake) as *mut MetaDataImport) }.unwrap()
+    }
+
+    unsafe fn with_module<R>(this: &MetaDataImport, f: impl FnOnce(&FakeModule) -> R) -> R {
+        let fake = &*(this as *const MetaDataImport as *const FakeMetadata);
+        let state = fake.runtime.state();
+        f(state.module(fake.module_id).unwrap())
+    }
+}
+
+/// A token enumeration in progress, behind an `HCORENUM`.
+struct Enumerator {
+    tokens: Vec<mdToken>,
+    position: usize,
+}
+
+/// Hands out the next `max` tokens of an enumeration, starting it with
+/// `tokens` on the first call.
+unsafe fn enumerate(
+    ph_enum: *mut HCORENUM,
+    tokens: impl FnOnce() -> Vec<mdToken>,
+    out: *mut mdToken,
+    max: ULONG,
+    fetched: *mut ULONG,
+) -> HRESULT {
+    if ph_enum.is_null() || out.is_null() {
+        return E_POINTER;
+    }
+    if (*ph_enum).is_null() {
+        let enumerator = Box::new(Enumerator {
+            tokens: tokens(),
+            position: 0,
+        });
+        *ph_enum = Box::into_raw(enumerator) as HCORENUM;
+    }
+    let enumerator = &mut *(*ph_enum as *mut Enumerator);
+    let next = &enumerator.tokens[enumerator.position..];
+    let count = next.len().min(max as usize);
+    ptr::copy_nonoverlapping(next.as_ptr(), out, count);
+    enumerator.position += count;
+    if !fetched.is_null() {
+        *fetched = count as ULONG;
+    }
+    if count == 0 {
+        S_FALSE
+    } else {
+        S_OK
+    }
+}
+
+unsafe extern "system" fn query_interface(
+    this: &MetaDataImport,
+    riid: REFIID,
+    ppv_object: *mut *mut c_void,
+) -> HRESULT {
+    if ppv_object.is_null() {
+        return E_POINTER;
+    }
+    if *riid == IUnknown::IID || *riid == IMetaDataImport::IID || *riid == IMetaDataImport2::IID {
+        add_ref(this);
+        *ppv_object = this as *const MetaDataImport as *mut c_void;
+        S_OK
+    } else {
+        *ppv_object = ptr::null_mut();
+        E_NOINTERFACE
+    }
+}
+
+unsafe extern "system" fn add_ref(this: &MetaDataImport) -> ULONG {
+    let fake = &*(this as *const MetaDataImport as *const FakeMetadata);
+    fake.ref_count.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+unsafe extern "system" fn release(this: &MetaDataImport) -> ULONG {
+    let fake = this as *const MetaDataImport as *mut FakeMetadata;
+    let count = (*fake).ref_count.fetch_sub(1, Ordering::AcqRel) - 1;
+    if count == 0 {
+        drop(Box::from_raw(fake));
+    }
+    count
+}
+
+unsafe extern "system" fn close_enum(_this: &MetaDataImport, h_enum: HCORENUM) {
+    if !h_enum.is_null() {
+        drop(Box::from_raw(h_enum as *mut Enumerator));
+    }
+}
+
+unsafe extern "system" fn count_enum(
+    _this: &MetaDataImport,
+    h_enum: HCORENUM,
+    pul_count: *mut ULONG,
+) -> HRESULT {
+    // enumerations that were never started are empty
+    *pul_count = match (h_enum as *const Enumerator).as_ref() {
+        Some(enumerator) => enumerator.tokens.len() as ULONG,
+        None => 0,
+    };
+    S_OK
+}
+
+unsafe extern "system" fn enum_type_defs(
+    this: &MetaDataImport,
+    ph_enum: *mut HCORENUM,
+    r_type_defs: *const mdTypeDef,
+    c_max: ULONG,
+    pc_type_defs: *mut ULONG,
+) -> HRESULT {
+    let tokens = || {
+        FakeMetadata::with_module(this, |module| {
+            module.types.keys().map(|token| token.raw()).collect()
+        })
+    };
+    enumerate(
+        ph_enum,
+        tokens,
+        r_type_defs as *mut mdTypeDef,
+        c_max,
+        pc_type_defs,
+    )
+}
+
+unsafe extern "system" fn enum_methods(
+    this: &MetaDataImport,
+    ph_enum: *mut HCORENUM,
+    cl: mdTypeDef,
+    r_methods: *mut mdMethodDef,
+    c_max: ULONG,
+    pc_tokens: *mut ULONG,
+) -> HRESULT {
+    let tokens = || {
+        FakeMetadata::with_module(this, |module| {
+            module
+                .methods
+                .iter()
+                .filter(|(_, method)| method.class.raw() == cl)
+                .map(|(token, _)| token.raw())
+                .collect()
+        })
+    };
+    enumerate(ph_enum, tokens, r_methods, c_max, pc_tokens)
+}
+
+unsafe extern "system" fn enum_generic_params(
+    this: &MetaDataImport,
+    ph_enum: *mut HCORENUM,
+    tk: mdToken,
+    r_generic_params: *mut mdToken,
+    c_max: ULONG,
+    pc_generic_params: *mut ULONG,
+) -> HRESULT {
+    let tokens = || {
+        FakeMetadata::with_module(this, |module| {
+            (1..)
+                .zip(&module.generic_params)
+                .filter(|(_, (owner, _))| owner.raw() == tk)
+                .map(|(rid, _)| GenericParamToken::from_rid(rid).raw())
+                .collect()
+        })
+    };
+    enumerate(ph_enum, tokens, r_generic_params, c_max, pc_generic_params)
+}
+
+unsafe extern "system" fn get_generic_param_props(
+    this: &MetaDataImport,
+    gp: mdGenericParam,
+    pul_param_seq: *mut ULONG,
+    pdw_param_flags: *mut DWORD,
+    pt_owner: *mut mdToken,
+    _reserved: *mut DWORD,
+    wzname: *mut WCHAR,
+    cch_name: ULONG,
+    pch_name: *mut ULONG,
+) -> HRESULT {
+    FakeMetadata::with_module(this, |module| {
+        let Some(index) = GenericParamToken::try_from(gp)
+            .ok()
+            .and_then(|token| (token.rid() as usize).checked_sub(1))
+            .filter(|&index| index < module.generic_params.len())
+        else {
+            return CLDB_E_RECORD_NOTFOUND;
+        };
+        let (owner, name) = &module.generic_params[index];
+        if !pul_param_seq.is_null() {
+            let earlier = &module.generic_params[..index];
+            *pul_param_seq = earlier.iter().filter(|param| param.0 == *owner).count() as ULONG;
+        }
+        if !pdw_param_flags.is_null() {
+            *pdw_param_flags = 0;
+        }
+        if !pt_owner.is_null() {
+            *pt_owner = owner.raw();
+        }
+        copy_name(name, cch_name, pch_name, wzname);
+        S_OK
+    })
+}
+
+unsafe extern "system" fn find_type_def_by_name(
+    this: &MetaDataImport,
+    sz_type_def: LPCWSTR,
+    tk_enclosing_class: mdToken,
+    ptd: *mut mdTypeDef,
+) -> HRESULT {
+    if sz_type_def.is_null() || ptd.is_null() {
+        return E_POINTER;
+    }
+    let name = U16CStr::from_ptr_str(sz_type_def).to_string_lossy();
+    let found = FakeMetadata::with_module(this, |module| {
+        module
+            .types
+            .iter()
+            .find(|(_, ty)| {
+                ty.name == name && ty.enclosing.map_or(0, TypeDefToken::raw) == tk_enclosing_class
+            })
+            .map(|(&token, _)| token)
+    });
+    match found {
+        Some(token) => {
+            *ptd = token.raw();
+            S_OK
+        }
+        None => CLDB_E_RECORD_NOTFOUND,
+    }
+}
+
+unsafe extern "system" fn get_type_def_props(
+    this: &MetaDataImport,
+    td: mdTypeDef,
+    sz_type_def: *mut WCHAR,
+    cch_type_def: ULONG,
+    pch_type_def: *mut ULONG,
+    pdw_type_def_flags: *mut DWORD,
+    ptk_extends: *mut mdToken,
+) -> HRESULT {
+    FakeMetadata::with_module(this, |module| {
+        let Some(ty) = TypeDefToken::try_from(td)
+            .ok()
+            .and_then(|token| module.type_def(token))
+        else {
+            return CLDB_E_RECORD_NOTFOUND;
+        };
+        copy_name(&ty.name, cch_type_def, pch_type_def, sz_type_def);
+        if !pdw_type_def_flags.is_null() {
+            *pdw_type_def_flags = ty.flags.bits();
+        }
+        if !ptk_extends.is_null() {
+            // a nil TypeRef, like System.Object and interfaces
+            *ptk_extends = 0x0100_0000;
+        }
+        S_OK
+    })
+}
+
+unsafe extern "system" fn get_type_ref_props(
+    this: &MetaDataImport,
+    tr: mdTypeRef,
+    ptk_resolution_scope: *mut mdToken,
+    sz_name: *mut WCHAR,
+    cch_name: ULONG,
+    pch_name: *mut ULONG,
+) -> HRESULT {
+    FakeMetadata::with_module(this, |module| {
+        let Some(type_ref) = TypeRefToken::try_from(tr)
+            .ok()
+            .and_then(|token| module.type_refs.get(&token))
+        else {
+            return CLDB_E_RECORD_NOTFOUND;
+        };
+        if !ptk_resolution_scope.is_null() {
+            *ptk_resolution_scope = type_ref.resolution_scope.raw();
+        }
+        copy_name(&type_ref.name, cch_name, pch_name, sz_name);
+        S_OK
+    })
+}
+
+unsafe extern "system" fn get_method_props(
+    this: &MetaDataImport,
+    mb: mdMethodDef,
+    p_class: *mut mdTypeDef,
+    sz_method: *mut WCHAR,
+    cch_method: ULONG,
+    pch_method: *mut ULONG,
+    pdw_attr: *mut DWORD,
+    ppv_sig_blob: *mut PCCOR_SIGNATURE,
+    pcb_sig_blob: *mut ULONG,
+    pul_code_rva: *mut ULONG,
+    pdw_impl_flags: *mut DWORD,
+) -> HRESULT {
+    FakeMetadata::with_module(this, |module| {
+        let Some(method) = MethodDefToken::try_from(mb)
+            .ok()
+            .and_then(|token| module.method(token))
+        else {
+            return CLDB_E_RECORD_NOTFOUND;
+        };
+        if !p_class.is_null() {
+            *p_class = method.class.raw();
+        }
+        copy_name(&method.name, cch_method, pch_method, sz_method);
+        if !pdw_attr.is_null() {
+            *pdw_attr = method.attr_flags.bits();
+        }
+        // signatures are never changed, so they outlive the lock
+        if !ppv_sig_blob.is_null() {
+            *ppv_sig_blob = method.sig.as_ptr();
+        }
+        if !pcb_sig_blob.is_null() {
+            *pcb_sig_blob = method.sig.len() as ULONG;
+        }
+        if !pul_code_rva.is_null() {
+            // bodies aren't mapped from an image
+            *pul_code_rva = 0;
+        }
+        if !pdw_impl_flags.is_null() {
+            *pdw_impl_flags = method.impl_flags.bits();
+        }
+        S_OK
+    })
+}
+
+unsafe extern "system" fn enum_user_strings(
+    this: &MetaDataImport,
+    ph_enum: *mut HCORENUM,
+    r_strings: *mut mdString,
+    c_max: ULONG,
+    pc_strings: *mut ULONG,
+) -> HRESULT {
+    let tokens = || {
+        FakeMetadata::with_module(this, |module| {
+            module
+                .user_strings
+                .keys()
+                .map(|token| token.raw())
+                .collect()
+        })
+    };
+    enumerate(ph_enum, tokens, r_strings, c_max, pc_strings)
+}
+
+unsafe extern "system" fn get_user_string(
+    this: &MetaDataImport,
+    stk: mdString,
+    sz_string: *mut WCHAR,
+    cch_string: ULONG,
+    pch_string: *mut ULONG,
+) -> HRESULT {
+    FakeMetadata::with_module(this, |module| {
+        let Some(string) = StringToken::try_from(stk)
+            .ok()
+            .and_then(|token| module.user_strings.get(&token))
+        else {
+            return CLDB_E_RECORD_NOTFOUND;
+        };
+        // unlike names, user strings aren't null terminated
+        let string = string.encode_utf16().collect::<Vec<_>>();
+        if !pch_string.is_null() {
+            *pch_string = string.len() as ULONG;
+        }
+        let copied = string.len().min(cch_string as usize);
+        if !sz_string.is_null() {
+            ptr::copy_nonoverlapping(string.as_ptr(), sz_string, copied);
+        }
+        if copied < string.len() {
+            CLDB_S_TRUNCATION
+        } else {
+            S_OK
+        }
+    })
+}
+
+unsafe extern "system" fn is_valid_token(this: &MetaDataImport, tk: mdToken) -> BOOL {
+    FakeMetadata::with_module(this, |module| {
+        let valid = if let Ok(td) = TypeDefToken::try_from(tk) {
+            module.type_def(td).is_some()
+        } else if let Ok(tr) = TypeRefToken::try_from(tk) {
+            module.type_refs.contains_key(&tr)
+        } else if let Ok(mb) = MethodDefToken::try_from(tk) {
+            module.method(mb).is_some()
+        } else {
+            false
+        };
+        valid as BOOL
+    })
+}
+
+unsafe extern "system" fn get_nested_class_props(
+    this: &MetaDataImport,
+    td_nested_class: mdTypeDef,
+    ptd_enclosing_class: *mut mdTypeDef,
+) -> HRESULT {
+    FakeMetadata::with_module(this, |module| {
+        let Ok(td) = TypeDefToken::try_from(td_nested_class) else {
+            return E_INVALIDARG;
+        };
+        match module.type_def(td).and_then(|ty| ty.enclosing) {
+            Some(enclosing) => {
+                *ptd_enclosing_class = enclosing.raw();
+                S_OK
+            }
+            None => CLDB_E_RECORD_NOTFOUND,
+        }
+    })
+}
+
+static VTBL: MetaDataImportVtbl = MetaDataImportVtbl {
+    IUnknown: IUnknown {
+        QueryInterface: query_interface,
+        AddRef: add_ref,
+        Release: release,
+    },
+    IMetaDataImport: IMetaDataImport {
+        CloseEnum: close_enum,
+        CountEnum: count_enum,
+        ResetEnum: not_implemented_2,
+        EnumTypeDefs: enum_type_defs,
+        EnumInterfaceImpls: not_implemented_5,
+        EnumTypeRefs: not_implemented_4,
+        FindTypeDefByName: find_type_def_by_name,
+        GetScopeProps: not_implemented_4,
+        GetModuleFromScope: not_implemented_1,
+        GetTypeDefProps: get_type_def_props,
+        GetInterfaceImplProps: not_implemented_3,
+        GetTypeRefProps: get_type_ref_props,
+        ResolveTypeRef: not_implemented_4,
+        EnumMembers: not_implemented_5,
+        EnumMembersWithName: not_implemented_6,
+        EnumMethods: enum_methods,
+        EnumMethodsWithName: not_implemented_6,
+        EnumFields: not_implemented_5,
+        EnumFieldsWithName: not_implemented_6,
+        EnumParams: not_implemented_5,
+        EnumMemberRefs: not_implemented_5,
+        EnumMethodImpls: not_implemented_6,
+        EnumPermissionSets: not_implemented_6,
+        FindMember: not_implemented_5,
+        FindMethod: not_implemented_5,
+        FindField: not_implemented_5,
+        FindMemberRef: not_implemented_5,
+        GetMethodProps: get_method_props,
+        GetMemberRefProps: not_implemented_7,
+        EnumProperties: not_implemented_5,
+        EnumEvents: not_implemented_5,
+        GetEventProps: not_implemented_13,
+        EnumMethodSemantics: not_implemented_5,
+        GetMethodSemantics: not_implemented_3,
+        GetClassLayout: not_implemented_6,
+        GetFieldMarshal: not_implemented_3,
+        GetRVA: not_implemented_3,
+        GetPermissionSetProps: not_implemented_4,
+        GetSigFromToken: not_implemented_3,
+        GetModuleRefProps: not_implemented_4,
+        EnumModuleRefs: not_implemented_4,
+        GetTypeSpecFromToken: not_implemented_3,
+        GetNameFromToken: not_implemented_2,
+        EnumUnresolvedMethods: not_implemented_4,
+        GetUserString: get_user_string,
+        GetPinvokeMap: not_implemented_6,
+        EnumSignatures: not_implemented_4,
+        EnumTypeSpecs: not_implemented_4,
+        EnumUserStrings: enum_user_strings,
+        GetParamForMethodIndex: not_implemented_3,
+        EnumCustomAttributes: not_implemented_6,
+        GetCustomAttributeProps: not_implemented_5,
+        FindTypeRef: not_implemented_3,
+        GetMemberProps: not_implemented_13,
+        GetFieldProps: not_implemented_11,
+        GetPropertyProps: not_implemented_16,
+        GetParamProps: not_implemented_10,
+        GetCustomAttributeByName: not_implemented_4,
+        IsValidToken: is_valid_token,
+        GetNestedClassProps: get_nested_class_props,
+        GetNativeCallConvFromSig: not_implemented_3,
+        IsGlobal: not_implemented_2,
+    },
+    IMetaDataImport2: IMetaDataImport2 {
+        EnumGenericParams: enum_generic_params,
+        GetGenericParamProps: get_generic_param_props,
+        GetMethodSpecProps: not_implemented_4,
+        EnumGenericParamConstraints: not_implemented_5,
+        GetGenericParamConstraintProps: not_implemented_3,
+        GetPEKind: not_implemented_2,
+        GetVersionString: not_implemented_3,
+        EnumMethodSpecs: not_implemented_5,
+    },
+};
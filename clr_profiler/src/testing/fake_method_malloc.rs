@@ -0,0 +1,85 @@
+#![allow(non_snake_case)]
+use super::FakeRuntime;
+use crate::ffi::{
+    ComPtr, IMethodMalloc, IUnknown, MethodMalloc, MethodMallocVtbl, ModuleID, E_NOINTERFACE,
+    E_POINTER, HRESULT, LPVOID, REFIID, S_OK, ULONG,
+};
+use std::{
+    ffi::c_void,
+    ptr,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+/// `IMethodMalloc` for the IL bodies of one module of a [`FakeRuntime`].
+/// What it allocates lives as long as the runtime.
+#[repr(C)]
+pub(crate) struct FakeMethodMalloc {
+    malloc: MethodMalloc,
+    ref_count: AtomicU32,
+    runtime: FakeRuntime,
+    module_id: ModuleID,
+}
+
+impl FakeMethodMalloc {
+    pub fn create(runtime: FakeRuntime, module_id: ModuleID) -> ComPtr<MethodMalloc> {
+        let fake = Box::new(FakeMethodMalloc {
+            malloc: MethodMalloc { lpVtbl: &VTBL },
+            ref_count: AtomicU32::new(1),
+            runtime,
+            module_id,
+        });
+        unsafe { ComPtr::from_raw(Box::into_raw(fake) as *mut MethodMalloc) }.unwrap()
+    }
+}
+
+unsafe extern "system" fn query_interface(
+    this: &MethodMalloc,
+    riid: REFIID,
+    ppv_object: *mut *mut c_void,
+) -> HRESULT {
+    if ppv_object.is_null() {
+        return E_POINTER;
+    }
+    if *riid == IUnknown::IID || *riid == IMethodMalloc::IID {
+        add_ref(this);
+        *ppv_object = this as *const MethodMalloc as *mut c_void;
+        S_OK
+    } else {
+        *ppv_object = ptr::null_mut();
+        E_NOINTERFACE
+    }
+}
+
+unsafe extern "system" fn add_ref(this: &MethodMalloc) -> ULONG {
+    let fake = &*(this as *const MethodMalloc as *const FakeMethodMalloc);
+    fake.ref_count.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+unsafe extern "system" fn release(this: &MethodMalloc) -> ULONG {
+    let fake = this as *const MethodMalloc as *mut FakeMethodMalloc;
+    let count = (*fake).ref_count.fetch_sub(1, Ordering::AcqRel) - 1;
+    if count == 0 {
+        drop(Box::from_raw(fake));
+    }
+    count
+}
+
+unsafe extern "system" fn alloc(this: &MethodMalloc, cb: ULONG) -> LPVOID {
+    let fake = &*(this as *const MethodMalloc as *const FakeMethodMalloc);
+    let mut block = vec![0u8; cb as usize].into_boxed_slice();
+    let address = block.as_mut_ptr() as LPVOID;
+    fake.runtime
+        .state()
+        .il_allocations
+        .push((fake.module_id, block));
+    address
+}
+
+static VTBL: MethodMallocVtbl = MethodMallocVtbl {
+    IUnknown: IUnknown {
+        QueryInterface: query_interface,
+        AddRef: add_ref,
+        Release: release,
+    },
+    IMethodMalloc: IMethodMalloc { Alloc: alloc },
+};
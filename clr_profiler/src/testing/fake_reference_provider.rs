@@ -0,0 +1,86 @@
+#![allow(non_snake_case)]
+use crate::ffi::{
+    ComPtr, CorProfilerAssemblyReferenceProvider, CorProfilerAssemblyReferenceProviderVtbl,
+    ICorProfilerAssemblyReferenceProvider, IUnknown, COR_PRF_ASSEMBLY_REFERENCE_INFO,
+    E_NOINTERFACE, E_POINTER, HRESULT, REFIID, S_OK, ULONG,
+};
+use std::{
+    ffi::c_void,
+    ptr,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+/// `ICorProfilerAssemblyReferenceProvider` that accepts every reference and
+/// forgets it; the fake runtime doesn't resolve assemblies.
+#[repr(C)]
+pub(crate) struct FakeReferenceProvider {
+    provider: CorProfilerAssemblyReferenceProvider,
+    ref_count: AtomicU32,
+}
+
+impl FakeReferenceProvider {
+    pub fn create() -> ComPtr<CorProfilerAssemblyReferenceProvider> {
+        let fake = Box::new(FakeReferenceProvider {
+            provider: CorProfilerAssemblyReferenceProvider { lpVtbl: &VTBL },
+            ref_count: AtomicU32::new(1),
+        });
+        unsafe {
+            ComPtr::from_raw(Box::into_raw(fake) as *mut CorProfilerAssemblyReferenceProvider)
+        }
+        .unwrap()
+    }
+}
+
+unsafe extern "system" fn query_interface(
+    this: &CorProfilerAssemblyReferenceProvider,
+    riid: REFIID,
+    ppv_object: *mut *mut c_void,
+) -> HRESULT {
+    if ppv_object.is_null() {
+        return E_POINTER;
+    }
+    if *riid == IUnknown::IID || *riid == ICorProfilerAssemblyReferenceProvider::IID {
+        add_ref(this);
+        *ppv_object = this as *const CorProfilerAssemblyReferenceProvider as *mut c_void;
+        S_OK
+    } else {
+        *ppv_object = ptr::null_mut();
+        E_NOINTERFACE
+    }
+}
+
+unsafe extern "system" fn add_ref(this: &CorProfilerAssemblyReferenceProvider) -> ULONG {
+    let fake =
+        &*(this as *const CorProfilerAssemblyReferenceProvider as *const FakeReferenceProvider);
+    fake.ref_count.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+unsafe extern "system" fn release(this: &CorProfilerAssemblyReferenceProvider) -> ULONG {
+    let fake = this as *const CorProfilerAssemblyReferenceProvider as *mut FakeReferenceProvider;
+    let count = (*fake).ref_count.fetch_sub(1, Ordering::AcqRel) - 1;
+    if count == 0 {
+        drop(Box::from_raw(fake));
+    }
+    count
+}
+
+unsafe extern "system" fn add_assembly_reference(
+    _this: &CorProfilerAssemblyReferenceProvider,
+    p_assembly_ref_info: *const COR_PRF_ASSEMBLY_REFERENCE_INFO,
+) -> HRESULT {
+    if p_assembly_ref_info.is_null() {
+        return E_POINTER;
+    }
+    S_OK
+}
+
+static VTBL: CorProfilerAssemblyReferenceProviderVtbl = CorProfilerAssemblyReferenceProviderVtbl {
+    IUnknown: IUnknown {
+        QueryInterface: query_interface,
+        AddRef: add_ref,
+        Release: release,
+    },
+    ICorProfilerAssemblyReferenceProvider: ICorProfilerAssemblyReferenceProvider {
+        AddAssemblyReference: add_assembly_reference,
+    },
+};
@@ -0,0 +1,537 @@
+use super::{
+    driver::{first_failure, succeeded},
+    fake_function_control::FakeFunctionControl,
+    fake_reference_provider::FakeReferenceProvider,
+    runtime::{FakeFunction, FakeMethod, FakeModule, FakeType, FakeTypeRef},
+    FakeRuntime, ProfilerDriver,
+};
+use crate::{
+    ffi::{
+        HResult, BOOL, COR_PRF_GC_REASON, COR_PRF_JIT_CACHE, COR_PRF_SUSPEND_REASON,
+        COR_PRF_TRANSITION_REASON, GUID, HRESULT, UINT, ULONG,
+    },
+    metadata::{MethodDefToken, Token},
+    recording::{Arg, Callback, Record, Recording},
+};
+use std::{convert::TryFrom, ffi::c_void, ptr};
+use widestring::{U16CString, U16String};
+
+impl FakeRuntime {
+    /// A runtime with the modules, classes, functions and methods a
+    /// recording describes, with the IDs and tokens they were recorded with.
+    pub fn from_recording(recording: &Recording) -> Self {
+        let runtime = FakeRuntime::new();
+        let mut state = runtime.state();
+        for record in &recording.records {
+            match record {
+                Record::Callback { .. } => {}
+                Record::Module {
+                    module_id,
+                    assembly_id,
+                    name,
+                } => {
+                    if state.module(*module_id).is_none() {
+                        state
+                            .modules
+                            .push(FakeModule::new(*module_id, *assembly_id, name));
+                    }
+                }
+                Record::Class {
+                    class_id,
+                    module_id,
+                    token,
+                } => state.classes.push((*class_id, *module_id, *token)),
+                Record::TypeDef {
+                    module_id,
+                    token,
+                    name,
+                    flags,
+                    enclosing,
+                    generic_params,
+                } => {
+                    if let Some(module) = state.module_mut(*module_id) {
+                        let ty = FakeType {
+                            name: name.clone(),
+                            flags: *flags,
+                            enclosing: *enclosing,
+                        };
+                        module.types.insert(*token, ty);
+                        add_generic_params(module, token.token(), generic_params);
+                    }
+                }
+                Record::TypeRef {
+                    module_id,
+                    token,
+                    resolution_scope,
+                    name,
+                } => {
+                    if let Some(module) = state.module_mut(*module_id) {
+                        let type_ref = FakeTypeRef {
+                            resolution_scope: *resolution_scope,
+                            name: name.clone(),
+                        };
+                        module.type_refs.insert(*token, type_ref);
+                    }
+                }
+                Record::Method {
+                    module_id,
+                    token,
+                    class,
+                    name,
+                    attr_flags,
+                    impl_flags,
+                    sig,
+                    generic_params,
+                } => {
+                    if let Some(module) = state.module_mut(*module_id) {
+                        let method = FakeMethod {
+                            class: *class,
+                            name: name.clone(),
+                            attr_flags: *attr_flags,
+                            impl_flags: *impl_flags,
+                            sig: sig.clone(),
+                            bodies: Vec::new(),
+                        };
+                        module.methods.insert(*token, method);
+                        add_generic_params(module, token.token(), generic_params);
+                    }
+                }
+                Record::Function {
+                    function_id,
+                    class_id,
+                    module_id,
+                    token,
+                } => state.functions.push(FakeFunction {
+                    id: *function_id,
+                    class_id: *class_id,
+                    module_id: *module_id,
+                    token: *token,
+                    code: Vec::new(),
+                }),
+                Record::IlBody {
+                    module_id,
+                    token,
+                    body,
+                } => {
+                    if let Some(method) = state
+                        .module_mut(*module_id)
+                        .and_then(|module| module.methods.get_mut(token))
+                    {
+                        method.bodies = vec![body.clone()];
+                    }
+                }
+            }
+        }
+        drop(state);
+        runtime
+    }
+}
+
+fn add_generic_params(module: &mut FakeModule, owner: Token, names: &[String]) {
+    module
+        .generic_params
+        .extend(names.iter().map(|name| (owner, name.clone())));
+}
+
+impl ProfilerDriver {
+    /// Makes the callbacks of a recording, in order. The driver's runtime
+    /// should come from [`FakeRuntime::from_recording`].
+    ///
+    /// Callbacks are replayed whatever events the profiler turns on, and left
+    /// out if the profiler doesn't implement their interface. Out parameters
+    /// start out `FALSE`, `GetReJITParameters` gets a function control that
+    /// sets the method's body, and `GetAssemblyReferences` a provider that
+    /// accepts any reference. Like the runtime, the replay stops if the
+    /// profiler fails to initialize.
+    pub fn replay(&self, recording: &Recording) -> Result<(), HResult> {
+        let version = self.version();
+        let mut results = Vec::new();
+        for (callback, args) in recording.callbacks() {
+            if callback.version() > version {
+                continue;
+            }
+            let hr = unsafe { self.replay_callback(callback, args) };
+            if let Callback::Initialize | Callback::InitializeForAttach = callback {
+                succeeded(hr)?;
+            }
+            results.push(hr);
+        }
+        first_failure(results)
+    }
+
+    unsafe fn replay_callback(&self, callback: Callback, args: &[Arg]) -> HRESULT {
+        let (this, vtbl) = self.callback();
+        let v1 = &vtbl.ICorProfilerCallback;
+        let v2 = &vtbl.ICorProfilerCallback2;
+        let v3 = &vtbl.ICorProfilerCallback3;
+        let v4 = &vtbl.ICorProfilerCallback4;
+        let int = |index: usize| args.get(index).map_or(0, Arg::int);
+        let id = |index: usize| int(index) as usize;
+        let hr = |index: usize| int(index) as u32 as HRESULT;
+        let dword = |index: usize| int(index) as ULONG;
+        let ids = |index: usize| -> Vec<usize> {
+            let ints = args.get(index).map_or(&[][..], Arg::ints);
+            ints.iter().map(|&value| value as usize).collect()
+        };
+        let dwords = |index: usize| -> Vec<ULONG> {
+            let ints = args.get(index).map_or(&[][..], Arg::ints);
+            ints.iter().map(|&value| value as ULONG).collect()
+        };
+        let bytes = |index: usize| args.get(index).map_or(&[][..], Arg::bytes);
+        let text = |index: usize| args.get(index).map_or("", Arg::text);
+        let guid = |index: usize| args.get(index).and_then(Arg::guid);
+        let mut out: BOOL = 0;
+
+        match callback {
+            Callback::Initialize => {
+                let info = self.runtime().profiler_info();
+                let hr = (v1.Initialize)(this, info.as_ptr());
+                self.runtime().state().initialized = true;
+                hr
+            }
+            Callback::Shutdown => (v1.Shutdown)(this),
+            Callback::AppDomainCreationStarted => (v1.AppDomainCreationStarted)(this, id(0)),
+            Callback::AppDomainCreationFinished => {
+                (v1.AppDomainCreationFinished)(this, id(0), hr(1))
+            }
+            Callback::AppDomainShutdownStarted => (v1.AppDomainShutdownStarted)(this, id(0)),
+            Callback::AppDomainShutdownFinished => {
+                (v1.AppDomainShutdownFinished)(this, id(0), hr(1))
+            }
+            Callback::AssemblyLoadStarted => (v1.AssemblyLoadStarted)(this, id(0)),
+            Callback::AssemblyLoadFinished => (v1.AssemblyLoadFinished)(this, id(0), hr(1)),
+            Callback::AssemblyUnloadStarted => (v1.AssemblyUnloadStarted)(this, id(0)),
+            Callback::AssemblyUnloadFinished => (v1.AssemblyUnloadFinished)(this, id(0), hr(1)),
+            Callback::ModuleLoadStarted => (v1.ModuleLoadStarted)(this, id(0)),
+            Callback::ModuleLoadFinished => (v1.ModuleLoadFinished)(this, id(0), hr(1)),
+            Callback::ModuleUnloadStarted => (v1.ModuleUnloadStarted)(this, id(0)),
+            Callback::ModuleUnloadFinished => (v1.ModuleUnloadFinished)(this, id(0), hr(1)),
+            Callback::ModuleAttachedToAssembly => (v1.ModuleAttachedToAssembly)(this, id(0), id(1)),
+            Callback::ClassLoadStarted => (v1.ClassLoadStarted)(this, id(0)),
+            Callback::ClassLoadFinished => (v1.ClassLoadFinished)(this, id(0), hr(1)),
+            Callback::ClassUnloadStarted => (v1.ClassUnloadStarted)(this, id(0)),
+            Callback::ClassUnloadFinished => (v1.ClassUnloadFinished)(this, id(0), hr(1)),
+            Callback::FunctionUnloadStarted => (v1.FunctionUnloadStarted)(this, id(0)),
+            Callback::JITCompilationStarted => (v1.JITCompilationStarted)(this, id(0), hr(1)),
+            Callback::JITCompilationFinished => {
+                (v1.JITCompilationFinished)(this, id(0), hr(1), hr(2))
+            }
+            Callback::JITCachedFunctionSearchStarted => {
+                (v1.JITCachedFunctionSearchStarted)(this, id(0), &mut out)
+            }
+            Callback::JITCachedFunctionSearchFinished => {
+                (v1.JITCachedFunctionSearchFinished)(this, id(0), jit_cache(int(1)))
+            }
+            Callback::JITFunctionPitched => (v1.JITFunctionPitched)(this, id(0)),
+            Callback::JITInlining => (v1.JITInlining)(this, id(0), id(1), &mut out),
+            Callback::ThreadCreated => (v1.ThreadCreated)(this, id(0)),
+            Callback::ThreadDestroyed => (v1.ThreadDestroyed)(this, id(0)),
+            Callback::ThreadAssignedToOSThread => {
+                (v1.ThreadAssignedToOSThread)(this, id(0), dword(1))
+            }
+            Callback::RemotingClientInvocationStarted => (v1.RemotingClientInvocationStarted)(this),
+            Callback::RemotingClientSendingMessage => {
+                (v1.RemotingClientSendingMessage)(this, guid_ptr(&guid(0)), hr(1))
+            }
+            Callback::RemotingClientReceivingReply => {
+                (v1.RemotingClientReceivingReply)(this, guid_ptr(&guid(0)), hr(1))
+            }
+            Callback::RemotingClientInvocationFinished => {
+                (v1.RemotingClientInvocationFinished)(this)
+            }
+            Callback::RemotingServerReceivingMessage => {
+                (v1.RemotingServerReceivingMessage)(this, guid_ptr(&guid(0)), hr(1))
+            }
+            Callback::RemotingServerInvocationStarted => (v1.RemotingServerInvocationStarted)(this),
+            Callback::RemotingServerInvocationReturned => {
+                (v1.RemotingServerInvocationReturned)(this)
+            }
+            Callback::RemotingServerSendingReply => {
+                (v1.RemotingServerSendingReply)(this, guid_ptr(&guid(0)), hr(1))
+            }
+            Callback::UnmanagedToManagedTransition => {
+                (v1.UnmanagedToManagedTransition)(this, id(0), transition_reason(int(1)))
+            }
+            Callback::ManagedToUnmanagedTransition => {
+                (v1.ManagedToUnmanagedTransition)(this, id(0), transition_reason(int(1)))
+            }
+            Callback::RuntimeSuspendStarted => {
+                (v1.RuntimeSuspendStarted)(this, suspend_reason(int(0)))
+            }
+            Callback::RuntimeSuspendFinished => (v1.RuntimeSuspendFinished)(this),
+            Callback::RuntimeSuspendAborted => (v1.RuntimeSuspendAborted)(this),
+            Callback::RuntimeResumeStarted => (v1.RuntimeResumeStarted)(this),
+            Callback::RuntimeResumeFinished => (v1.RuntimeResumeFinished)(this),
+            Callback::RuntimeThreadSuspended => (v1.RuntimeThreadSuspended)(this, id(0)),
+            Callback::RuntimeThreadResumed => (v1.RuntimeThreadResumed)(this, id(0)),
+            Callback::MovedReferences => {
+                let (old, new, lengths) = (ids(1), ids(2), dwords(3));
+                (v1.MovedReferences)(
+                    this,
+                    old.len() as ULONG,
+                    old.as_ptr(),
+                    new.as_ptr(),
+                    lengths.as_ptr(),
+                )
+            }
+            Callback::ObjectAllocated => (v1.ObjectAllocated)(this, id(0), id(1)),
+            Callback::ObjectsAllocatedByClass => {
+                let (class_ids, counts) = (ids(1), dwords(2));
+                (v1.ObjectsAllocatedByClass)(
+                    this,
+                    class_ids.len() as ULONG,
+                    class_ids.as_ptr(),
+                    counts.as_ptr(),
+                )
+            }
+            Callback::ObjectReferences => {
+                let references = ids(3);
+                (v1.ObjectReferences)(
+                    this,
+                    id(0),
+                    id(1),
+                    references.len() as ULONG,
+                    references.as_ptr(),
+                )
+            }
+            Callback::RootReferences => {
+                let references = ids(1);
+                (v1.RootReferences)(this, references.len() as ULONG, references.as_ptr())
+            }
+            Callback::ExceptionThrown => (v1.ExceptionThrown)(this, id(0)),
+            Callback::ExceptionSearchFunctionEnter => {
+                (v1.ExceptionSearchFunctionEnter)(this, id(0))
+            }
+            Callback::ExceptionSearchFunctionLeave => (v1.ExceptionSearchFunctionLeave)(this),
+            Callback::ExceptionSearchFilterEnter => (v1.ExceptionSearchFilterEnter)(this, id(0)),
+            Callback::ExceptionSearchFilterLeave => (v1.ExceptionSearchFilterLeave)(this),
+            Callback::ExceptionSearchCatcherFound => (v1.ExceptionSearchCatcherFound)(this, id(0)),
+            Callback::ExceptionOSHandlerEnter => (v1.ExceptionOSHandlerEnter)(this, id(0)),
+            Callback::ExceptionOSHandlerLeave => (v1.ExceptionOSHandlerLeave)(this, id(0)),
+            Callback::ExceptionUnwindFunctionEnter => {
+                (v1.ExceptionUnwindFunctionEnter)(this, id(0))
+            }
+            Callback::ExceptionUnwindFunctionLeave => (v1.ExceptionUnwindFunctionLeave)(this),
+            Callback::ExceptionUnwindFinallyEnter => (v1.ExceptionUnwindFinallyEnter)(this, id(0)),
+            Callback::ExceptionUnwindFinallyLeave => (v1.ExceptionUnwindFinallyLeave)(this),
+            Callback::ExceptionCatcherEnter => (v1.ExceptionCatcherEnter)(this, id(0), id(1)),
+            Callback::ExceptionCatcherLeave => (v1.ExceptionCatcherLeave)(this),
+            Callback::COMClassicVTableCreated => (v1.COMClassicVTableCreated)(
+                this,
+                id(0),
+                guid_ptr(&guid(1)),
+                id(2) as *const c_void,
+                dword(3),
+            ),
+            Callback::COMClassicVTableDestroyed => (v1.COMClassicVTableDestroyed)(
+                this,
+                id(0),
+                guid_ptr(&guid(1)),
+                id(2) as *const c_void,
+            ),
+            Callback::ExceptionCLRCatcherFound => (v1.ExceptionCLRCatcherFound)(this),
+            Callback::ExceptionCLRCatcherExecute => (v1.ExceptionCLRCatcherExecute)(this),
+            Callback::ThreadNameChanged => {
+                let name = U16String::from_str(text(1));
+                (v2.ThreadNameChanged)(this, id(0), name.len() as ULONG, name.as_ptr())
+            }
+            Callback::GarbageCollectionStarted => {
+                let generations = dwords(1);
+                (v2.GarbageCollectionStarted)(
+                    this,
+                    generations.len() as i32,
+                    generations.as_ptr() as *const BOOL,
+                    gc_reason(int(2)),
+                )
+            }
+            Callback::SurvivingReferences => {
+                let (starts, lengths) = (ids(1), dwords(2));
+                (v2.SurvivingReferences)(
+                    this,
+                    starts.len() as ULONG,
+                    starts.as_ptr(),
+                    lengths.as_ptr(),
+                )
+            }
+            Callback::GarbageCollectionFinished => (v2.GarbageCollectionFinished)(this),
+            Callback::FinalizeableObjectQueued => {
+                (v2.FinalizeableObjectQueued)(this, dword(0), id(1))
+            }
+            Callback::RootReferences2 => {
+                let (references, kinds, flags, root_ids) = (ids(1), dwords(2), dwords(3), ids(4));
+                // the kinds and flags are recorded as their values
+                (v2.RootReferences2)(
+                    this,
+                    references.len() as ULONG,
+                    references.as_ptr(),
+                    kinds.as_ptr() as *const _,
+                    flags.as_ptr() as *const _,
+                    root_ids.as_ptr(),
+                )
+            }
+            Callback::HandleCreated => (v2.HandleCreated)(this, id(0), id(1)),
+            Callback::HandleDestroyed => (v2.HandleDestroyed)(this, id(0)),
+            Callback::InitializeForAttach => {
+                let info = self.runtime().profiler_info();
+                self.runtime().state().initialized = true;
+                let client_data = bytes(0);
+                let client_data_ptr = if client_data.is_empty() {
+                    ptr::null()
+                } else {
+                    client_data.as_ptr() as *const c_void
+                };
+                (v3.InitializeForAttach)(
+                    this,
+                    info.as_ptr(),
+                    client_data_ptr,
+                    client_data.len() as UINT,
+                )
+            }
+            Callback::ProfilerAttachComplete => (v3.ProfilerAttachComplete)(this),
+            Callback::ProfilerDetachSucceeded => (v3.ProfilerDetachSucceeded)(this),
+            Callback::ReJITCompilationStarted => {
+                (v4.ReJITCompilationStarted)(this, id(0), id(1), hr(2))
+            }
+            Callback::GetReJITParameters => {
+                let Ok(token) = MethodDefToken::try_from(dword(1)) else {
+                    return HResult::E_INVALIDARG.into();
+                };
+                let control = FakeFunctionControl::create(self.runtime().clone(), id(0), token);
+                (v4.GetReJITParameters)(this, id(0), token.raw(), control.as_ptr())
+            }
+            Callback::ReJITCompilationFinished => {
+                (v4.ReJITCompilationFinished)(this, id(0), id(1), hr(2), hr(3))
+            }
+            Callback::ReJITError => (v4.ReJITError)(this, id(0), dword(1), id(2), hr(3)),
+            Callback::MovedReferences2 => {
+                let (old, new, lengths) = (ids(1), ids(2), ids(3));
+                (v4.MovedReferences2)(
+                    this,
+                    old.len() as ULONG,
+                    old.as_ptr(),
+                    new.as_ptr(),
+                    lengths.as_ptr(),
+                )
+            }
+            Callback::SurvivingReferences2 => {
+                let (starts, lengths) = (ids(1), ids(2));
+                (v4.SurvivingReferences2)(
+                    this,
+                    starts.len() as ULONG,
+                    starts.as_ptr(),
+                    lengths.as_ptr(),
+                )
+            }
+            Callback::ConditionalWeakTableElementReferences => {
+                let (keys, values, root_ids) = (ids(1), ids(2), ids(3));
+                (vtbl
+                    .ICorProfilerCallback5
+                    .ConditionalWeakTableElementReferences)(
+                    this,
+                    keys.len() as ULONG,
+                    keys.as_ptr(),
+                    values.as_ptr(),
+                    root_ids.as_ptr(),
+                )
+            }
+            Callback::GetAssemblyReferences => {
+                let path = U16CString::from_str(text(0)).unwrap_or_default();
+                let provider = FakeReferenceProvider::create();
+                (vtbl.ICorProfilerCallback6.GetAssemblyReferences)(
+                    this,
+                    path.as_ptr(),
+                    provider.as_ptr(),
+                )
+            }
+            Callback::ModuleInMemorySymbolsUpdated => {
+                (vtbl.ICorProfilerCallback7.ModuleInMemorySymbolsUpdated)(this, id(0))
+            }
+            Callback::DynamicMethodJITCompilationStarted => {
+                let header = bytes(2);
+                (vtbl
+                    .ICorProfilerCallback8
+                    .DynamicMethodJITCompilationStarted)(
+                    this,
+                    id(0),
+                    hr(1),
+                    header.as_ptr(),
+                    header.len() as ULONG,
+                )
+            }
+            Callback::DynamicMethodJITCompilationFinished => {
+                (vtbl
+                    .ICorProfilerCallback8
+                    .DynamicMethodJITCompilationFinished)(this, id(0), hr(1), hr(2))
+            }
+            Callback::DynamicMethodUnloaded => {
+                (vtbl.ICorProfilerCallback9.DynamicMethodUnloaded)(this, id(0))
+            }
+            Callback::EventPipeEventDelivered => {
+                let (metadata, data, stack) = (bytes(3), bytes(4), ids(8));
+                let (activity, related_activity) = (guid(5), guid(6));
+                (vtbl.ICorProfilerCallback10.EventPipeEventDelivered)(
+                    this,
+                    id(0),
+                    dword(1),
+                    dword(2),
+                    metadata.len() as ULONG,
+                    metadata.as_ptr(),
+                    data.len() as ULONG,
+                    data.as_ptr(),
+                    guid_ptr(&activity),
+                    guid_ptr(&related_activity),
+                    id(7),
+                    stack.len() as ULONG,
+                    stack.as_ptr(),
+                )
+            }
+            Callback::EventPipeProviderCreated => {
+                (vtbl.ICorProfilerCallback10.EventPipeProviderCreated)(this, id(0))
+            }
+            Callback::LoadAsNotificationOnly => {
+                (vtbl.ICorProfilerCallback11.LoadAsNotificationOnly)(this, &mut out)
+            }
+        }
+    }
+}
+
+fn guid_ptr(guid: &Option<GUID>) -> *const GUID {
+    guid.as_ref()
+        .map_or(ptr::null(), |guid| guid as *const GUID)
+}
+
+// recorded enums may hold values the bindings don't know about
+
+fn jit_cache(value: u64) -> COR_PRF_JIT_CACHE {
+    match value {
+        0 => COR_PRF_JIT_CACHE::COR_PRF_CACHED_FUNCTION_FOUND,
+        _ => COR_PRF_JIT_CACHE::COR_PRF_CACHED_FUNCTION_NOT_FOUND,
+    }
+}
+
+fn transition_reason(value: u64) -> COR_PRF_TRANSITION_REASON {
+    match value {
+        0 => COR_PRF_TRANSITION_REASON::COR_PRF_TRANSITION_CALL,
+        _ => COR_PRF_TRANSITION_REASON::COR_PRF_TRANSITION_RETURN,
+    }
+}
+
+fn suspend_reason(value: u64) -> COR_PRF_SUSPEND_REASON {
+    match value {
+        1 => COR_PRF_SUSPEND_REASON::COR_PRF_SUSPEND_FOR_GC,
+        2 => COR_PRF_SUSPEND_REASON::COR_PRF_SUSPEND_FOR_APPDOMAIN_SHUTDOWN,
+        3 => COR_PRF_SUSPEND_REASON::COR_PRF_SUSPEND_FOR_CODE_PITCHING,
+        4 => COR_PRF_SUSPEND_REASON::COR_PRF_SUSPEND_FOR_SHUTDOWN,
+        6 => COR_PRF_SUSPEND_REASON::COR_PRF_SUSPEND_FOR_INPROC_DEBUGGER,
+        7 => COR_PRF_SUSPEND_REASON::COR_PRF_SUSPEND_FOR_GC_PREP,
+        8 => COR_PRF_SUSPEND_REASON::COR_PRF_SUSPEND_FOR_REJIT,
+        _ => COR_PRF_SUSPEND_REASON::COR_PRF_SUSPEND_OTHER,
+    }
+}
+
+fn gc_reason(value: u64) -> COR_PRF_GC_REASON {
+    match value {
+        1 => COR_PRF_GC_REASON::COR_PRF_GC_INDUCED,
+        _ => COR_PRF_GC_REASON::COR_PRF_GC_OTHER,
+    }
+}
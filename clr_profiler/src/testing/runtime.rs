@@ -0,0 +1,581 @@
+use super::fake_info::FakeInfo;
+use crate::{
+    ffi::{
+        AssemblyID, ClassID, ComPtr, CorElementType, CorMethodAttr, CorMethodImpl, CorProfilerInfo,
+        CorTypeAttr, FunctionID, ModuleID, ObjectID, COR_PRF_HANDLE_TYPE, COR_PRF_HIGH_MONITOR,
+        COR_PRF_MODULE_FLAGS, COR_PRF_MONITOR, DWORD, SIZE_T, UINT_PTR,
+    },
+    metadata::{MethodDefToken, StringToken, Token, TypeDefToken, TypeRefToken},
+    EventMask2,
+};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+/// The modules, types and methods a fake runtime has loaded, and what the
+/// profiler changed about them. Clones share the same state.
+///
+/// Methods panic when passed a module, type or function the runtime doesn't
+/// know about.
+#[derive(Clone, Default)]
+pub struct FakeRuntime {
+    state: Arc<Mutex<RuntimeState>>,
+}
+
+/// Ways a [`FakeRuntime`] misbehaves, to test that profilers cope with
+/// runtimes that don't keep to the documentation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Quirks {
+    /// Succeeds without handing out IL bodies, allocators or metadata.
+    pub null_results: bool,
+    /// Asks for room for one more code region than it then fills in.
+    pub overstated_lengths: bool,
+}
+
+#[derive(Default)]
+pub(crate) struct RuntimeState {
+    next_id: usize,
+    /// The newest `ICorProfilerInfo` version implemented, all of them if
+    /// `None`.
+    pub interface_level: Option<u32>,
+    pub quirks: Quirks,
+    pub events: (DWORD, DWORD),
+    /// Set once the profiler is past `Initialize`, or attaching; the
+    /// immutable event flags can't change from then on.
+    pub initialized: bool,
+    pub modules: Vec<FakeModule>,
+    /// Every class the runtime handed out, with its module and TypeDef.
+    pub classes: Vec<(ClassID, ModuleID, TypeDefToken)>,
+    /// Every function the runtime handed out.
+    pub functions: Vec<FakeFunction>,
+    /// Instantiations of generic types, with their type arguments.
+    pub instantiations: Vec<(ClassID, ModuleID, TypeDefToken, Vec<ClassID>)>,
+    pub array_classes: Vec<FakeArrayClass>,
+    /// The objects on the heap.
+    pub objects: Vec<FakeObject>,
+    /// Every handle not yet destroyed, with the object it refers to.
+    pub handles: Vec<(usize, ObjectID, COR_PRF_HANDLE_TYPE)>,
+    pub environment: BTreeMap<String, String>,
+    /// The blocks profilers allocated for IL bodies, and their module.
+    pub il_allocations: Vec<(ModuleID, Box<[u8]>)>,
+    /// How many metadata scopes the profiler opened.
+    pub metadata_requests: usize,
+}
+
+pub(crate) struct FakeFunction {
+    pub id: FunctionID,
+    pub class_id: Option<ClassID>,
+    pub module_id: ModuleID,
+    pub token: MethodDefToken,
+    /// The start and size of every region of native code.
+    pub code: Vec<(UINT_PTR, SIZE_T)>,
+}
+
+pub(crate) struct FakeArrayClass {
+    pub id: ClassID,
+    pub element_type: DWORD,
+    pub element_class_id: Option<ClassID>,
+    pub rank: u32,
+}
+
+pub(crate) struct FakeObject {
+    pub id: ObjectID,
+    pub class_id: ClassID,
+    pub dimension_sizes: Vec<u32>,
+    /// The elements of an array, zeroed.
+    pub data: Vec<u8>,
+}
+
+pub(crate) struct FakeModule {
+    pub id: ModuleID,
+    pub assembly_id: AssemblyID,
+    pub name: String,
+    pub flags: COR_PRF_MODULE_FLAGS,
+    pub types: BTreeMap<TypeDefToken, FakeType>,
+    pub type_refs: BTreeMap<TypeRefToken, FakeTypeRef>,
+    pub methods: BTreeMap<MethodDefToken, FakeMethod>,
+    /// The owner and name of every generic parameter, by rid starting at 1.
+    /// An owner's parameters are in order.
+    pub generic_params: Vec<(Token, String)>,
+    pub user_strings: BTreeMap<StringToken, String>,
+}
+
+pub(crate) struct FakeType {
+    pub name: String,
+    pub flags: CorTypeAttr,
+    pub enclosing: Option<TypeDefToken>,
+}
+
+pub(crate) struct FakeTypeRef {
+    pub resolution_scope: Token,
+    pub name: String,
+}
+
+pub(crate) struct FakeMethod {
+    pub class: TypeDefToken,
+    pub name: String,
+    pub attr_flags: CorMethodAttr,
+    pub impl_flags: CorMethodImpl,
+    pub sig: Vec<u8>,
+    /// The original body first, then every body the profiler set. Old
+    /// bodies are kept, the profiler may still be reading them. Empty for
+    /// methods without IL.
+    pub bodies: Vec<Vec<u8>>,
+}
+
+impl RuntimeState {
+    pub fn next_id(&mut self) -> usize {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    pub fn module(&self, module_id: ModuleID) -> Option<&FakeModule> {
+        self.modules.iter().find(|module| module.id == module_id)
+    }
+
+    pub fn module_mut(&mut self, module_id: ModuleID) -> Option<&mut FakeModule> {
+        self.modules
+            .iter_mut()
+            .find(|module| module.id == module_id)
+    }
+
+    pub fn function(&self, function_id: FunctionID) -> Option<&FakeFunction> {
+        self.functions
+            .iter()
+            .find(|function| function.id == function_id)
+    }
+
+    pub fn method(&self, module_id: ModuleID, token: MethodDefToken) -> Option<&FakeMethod> {
+        self.module(module_id)?.method(token)
+    }
+
+    pub fn class(&self, class_id: ClassID) -> Option<(ModuleID, TypeDefToken)> {
+        self.classes
+            .iter()
+            .find(|class| class.0 == class_id)
+            .map(|&(_, module_id, token)| (module_id, token))
+            .or_else(|| {
+                self.instantiations
+                    .iter()
+                    .find(|instance| instance.0 == class_id)
+                    .map(|&(_, module_id, token, _)| (module_id, token))
+            })
+    }
+
+    /// The type arguments of a class, empty unless it instantiates a
+    /// generic type.
+    pub fn type_args(&self, class_id: ClassID) -> &[ClassID] {
+        self.instantiations
+            .iter()
+            .find(|instance| instance.0 == class_id)
+            .map_or(&[], |instance| &instance.3)
+    }
+
+    pub fn array_class(&self, class_id: ClassID) -> Option<&FakeArrayClass> {
+        self.array_classes.iter().find(|array| array.id == class_id)
+    }
+
+    pub fn object(&self, object_id: ObjectID) -> Option<&FakeObject> {
+        self.objects.iter().find(|object| object.id == object_id)
+    }
+
+    /// How many bytes are left from `address` to the end of the block the
+    /// module's IL allocator handed out that holds it.
+    pub fn il_allocation_left(&self, module_id: ModuleID, address: *const u8) -> Option<usize> {
+        self.il_allocations
+            .iter()
+            .filter(|(module, _)| *module == module_id)
+            .find_map(|(_, block)| {
+                let offset = (address as usize).checked_sub(block.as_ptr() as usize)?;
+                block.len().checked_sub(offset).filter(|&left| left > 0)
+            })
+    }
+
+    /// Whether the event mask may become `low` and `high`: once initialized,
+    /// the runtime fails masks that change the immutable flags.
+    pub fn can_set_events(&self, low: DWORD, high: DWORD) -> bool {
+        let (current_low, current_high) = self.events;
+        let changed_low = (low ^ current_low) & COR_PRF_MONITOR::COR_PRF_MONITOR_IMMUTABLE.bits();
+        let changed_high =
+            (high ^ current_high) & COR_PRF_HIGH_MONITOR::COR_PRF_HIGH_MONITOR_IMMUTABLE.bits();
+        !self.initialized || changed_low | changed_high == 0
+    }
+}
+
+impl FakeModule {
+    pub fn new(id: ModuleID, assembly_id: AssemblyID, name: &str) -> Self {
+        FakeModule {
+            id,
+            assembly_id,
+            name: name.to_string(),
+            flags: COR_PRF_MODULE_FLAGS::empty(),
+            types: BTreeMap::new(),
+            type_refs: BTreeMap::new(),
+            methods: BTreeMap::new(),
+            generic_params: Vec::new(),
+            user_strings: BTreeMap::new(),
+        }
+    }
+
+    pub fn type_def(&self, token: TypeDefToken) -> Option<&FakeType> {
+        self.types.get(&token)
+    }
+
+    pub fn method(&self, token: MethodDefToken) -> Option<&FakeMethod> {
+        self.methods.get(&token)
+    }
+}
+
+impl FakeRuntime {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A runtime that implements `ICorProfilerInfo` up to `level`, e.g. 4 for
+    /// `ICorProfilerInfo4`, and none of it for 0.
+    pub fn with_interface_level(level: u32) -> Self {
+        let runtime = Self::default();
+        runtime.state().interface_level = Some(level);
+        runtime
+    }
+
+    pub fn set_quirks(&self, quirks: Quirks) {
+        self.state().quirks = quirks;
+    }
+
+    pub(crate) fn state(&self) -> MutexGuard<'_, RuntimeState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// A new `ICorProfilerInfo` for the profiler to initialize with. It
+    /// implements every version up to `ICorProfilerInfo14`, unless the
+    /// runtime was created [`with_interface_level`](Self::with_interface_level).
+    pub fn profiler_info(&self) -> ComPtr<CorProfilerInfo> {
+        FakeInfo::create(self.clone())
+    }
+
+    /// The events the profiler turned on.
+    pub fn event_mask(&self) -> EventMask2 {
+        let (low, high) = self.state().events;
+        EventMask2 {
+            events_low: COR_PRF_MONITOR::from_bits_retain(low),
+            events_high: COR_PRF_HIGH_MONITOR::from_bits_retain(high),
+        }
+    }
+
+    /// How many times the profiler asked for the metadata of a module.
+    pub fn metadata_requests(&self) -> usize {
+        self.state().metadata_requests
+    }
+
+    pub fn add_module(&self, name: &str) -> ModuleID {
+        self.add_module_with_flags(name, COR_PRF_MODULE_FLAGS::empty())
+    }
+
+    /// Adds a module the runtime reports `flags` for, e.g.
+    /// `COR_PRF_MODULE_DYNAMIC`.
+    pub fn add_module_with_flags(&self, name: &str, flags: COR_PRF_MODULE_FLAGS) -> ModuleID {
+        let mut state = self.state();
+        let id = state.next_id();
+        let assembly_id = state.next_id();
+        let mut module = FakeModule::new(id, assembly_id, name);
+        module.flags = flags;
+        state.modules.push(module);
+        id
+    }
+
+    /// Adds a type, named with its namespace, e.g. `System.Net.Security.SslStream`.
+    pub fn add_type(&self, module_id: ModuleID, name: &str, flags: CorTypeAttr) -> TypeDefToken {
+        self.define_type(module_id, name, flags, None, &[])
+    }
+
+    /// Adds a generic type with the parameters `generic_params`, e.g.
+    /// ``System.Collections.Generic.List`1`` with `T`. Only instantiations
+    /// of it have class IDs, so its methods have none.
+    pub fn add_generic_type(
+        &self,
+        module_id: ModuleID,
+        name: &str,
+        flags: CorTypeAttr,
+        generic_params: &[&str],
+    ) -> TypeDefToken {
+        self.define_type(module_id, name, flags, None, generic_params)
+    }
+
+    /// Adds a type nested in `enclosing`, named without a namespace.
+    pub fn add_nested_type(
+        &self,
+        module_id: ModuleID,
+        enclosing: TypeDefToken,
+        name: &str,
+        flags: CorTypeAttr,
+    ) -> TypeDefToken {
+        self.define_type(module_id, name, flags, Some(enclosing), &[])
+    }
+
+    fn define_type(
+        &self,
+        module_id: ModuleID,
+        name: &str,
+        flags: CorTypeAttr,
+        enclosing: Option<TypeDefToken>,
+        generic_params: &[&str],
+    ) -> TypeDefToken {
+        let mut state = self.state();
+        let class_id = state.next_id();
+        let module = state.module_mut(module_id).expect("unknown module");
+        let rid = module
+            .types
+            .keys()
+            .next_back()
+            .map_or(1, |token| token.rid() + 1);
+        let token = TypeDefToken::from_rid(rid);
+        module.types.insert(
+            token,
+            FakeType {
+                name: name.to_string(),
+                flags,
+                enclosing,
+            },
+        );
+        module.generic_params.extend(
+            generic_params
+                .iter()
+                .map(|param| (token.token(), param.to_string())),
+        );
+        if generic_params.is_empty() {
+            state.classes.push((class_id, module_id, token));
+        }
+        token
+    }
+
+    /// The class ID of a type that isn't generic.
+    pub fn class_id(&self, module_id: ModuleID, token: TypeDefToken) -> ClassID {
+        self.state()
+            .classes
+            .iter()
+            .find(|class| class.1 == module_id && class.2 == token)
+            .expect("unknown class")
+            .0
+    }
+
+    /// Instantiates a generic type added with
+    /// [`add_generic_type`](Self::add_generic_type) with `type_args`.
+    pub fn instantiate(
+        &self,
+        module_id: ModuleID,
+        generic_type: TypeDefToken,
+        type_args: &[ClassID],
+    ) -> ClassID {
+        let mut state = self.state();
+        let module = state.module(module_id).expect("unknown module");
+        assert!(module.type_def(generic_type).is_some(), "unknown type");
+        let id = state.next_id();
+        state
+            .instantiations
+            .push((id, module_id, generic_type, type_args.to_vec()));
+        id
+    }
+
+    /// Adds an array class of `rank` dimensions. Arrays of primitives, like
+    /// `int[]`, have no `element_class_id`.
+    pub fn add_array_class(
+        &self,
+        element_type: CorElementType,
+        element_class_id: Option<ClassID>,
+        rank: u32,
+    ) -> ClassID {
+        let mut state = self.state();
+        let id = state.next_id();
+        state.array_classes.push(FakeArrayClass {
+            id,
+            element_type: element_type as DWORD,
+            element_class_id,
+            rank,
+        });
+        id
+    }
+
+    /// Allocates an array of the array class `class_id` on the heap. Its
+    /// elements are `element_size` bytes, and zeroed.
+    pub fn add_array(
+        &self,
+        class_id: ClassID,
+        dimension_sizes: &[u32],
+        element_size: usize,
+    ) -> ObjectID {
+        let mut state = self.state();
+        let rank = state
+            .array_class(class_id)
+            .expect("unknown array class")
+            .rank;
+        assert_eq!(
+            rank as usize,
+            dimension_sizes.len(),
+            "wrong number of dimensions"
+        );
+        let id = state.next_id();
+        let length = dimension_sizes.iter().product::<u32>() as usize;
+        state.objects.push(FakeObject {
+            id,
+            class_id,
+            dimension_sizes: dimension_sizes.to_vec(),
+            data: vec![0; length * element_size],
+        });
+        id
+    }
+
+    /// Frees every object no strong or pinned handle refers to. Weak handles
+    /// to them then refer to nothing.
+    pub fn collect_garbage(&self) {
+        let mut state = self.state();
+        let RuntimeState {
+            objects, handles, ..
+        } = &mut *state;
+        objects.retain(|object| {
+            handles.iter().any(|&(_, object_id, handle_type)| {
+                object_id == object.id
+                    && handle_type != COR_PRF_HANDLE_TYPE::COR_PRF_HANDLE_TYPE_WEAK
+            })
+        });
+    }
+
+    /// Sets a variable `GetEnvironmentVariable` reads.
+    pub fn set_environment_variable(&self, name: &str, value: &str) {
+        self.state()
+            .environment
+            .insert(name.to_string(), value.to_string());
+    }
+
+    /// Adds a public, non-generic IL method with a method signature `sig`
+    /// and a body `il_body` that starts with its method header.
+    pub fn add_method(
+        &self,
+        module_id: ModuleID,
+        class: TypeDefToken,
+        name: &str,
+        sig: &[u8],
+        il_body: &[u8],
+    ) -> FunctionID {
+        let mut state = self.state();
+        let function_id = state.next_id();
+        let module = state.module_mut(module_id).expect("unknown module");
+        assert!(module.type_def(class).is_some(), "unknown type");
+        let rid = module
+            .methods
+            .keys()
+            .next_back()
+            .map_or(1, |token| token.rid() + 1);
+        let token = MethodDefToken::from_rid(rid);
+        module.methods.insert(
+            token,
+            FakeMethod {
+                class,
+                name: name.to_string(),
+                attr_flags: CorMethodAttr::mdPublic,
+                impl_flags: CorMethodImpl::miIL,
+                sig: sig.to_vec(),
+                bodies: vec![il_body.to_vec()],
+            },
+        );
+        let class_id = state
+            .classes
+            .iter()
+            .find(|&&(_, module, token)| module == module_id && token == class)
+            .map(|class| class.0);
+        state.functions.push(FakeFunction {
+            id: function_id,
+            class_id,
+            module_id,
+            token,
+            code: Vec::new(),
+        });
+        function_id
+    }
+
+    /// Adds a string literal, as used by `ldstr`. Like in an image, its
+    /// token is its offset in the `#US` heap.
+    pub fn add_user_string(&self, module_id: ModuleID, string: &str) -> StringToken {
+        let mut state = self.state();
+        let module = state.module_mut(module_id).expect("unknown module");
+        // the heap starts with an empty blob, entries are a compressed
+        // length, UTF-16 and a trailing flag byte
+        let offset = module
+            .user_strings
+            .iter()
+            .next_back()
+            .map_or(1, |(token, string)| {
+                let size = string.encode_utf16().count() * 2 + 1;
+                let prefix = match size {
+                    0..=0x7f => 1,
+                    0x80..=0x3fff => 2,
+                    _ => 4,
+                };
+                token.rid() as usize + prefix + size
+            });
+        let token = StringToken::from_rid(offset as u32);
+        module.user_strings.insert(token, string.to_string());
+        token
+    }
+
+    /// Sets the regions of native code the function was compiled to, as
+    /// start address and size.
+    pub fn set_native_code(&self, function_id: FunctionID, regions: &[(UINT_PTR, SIZE_T)]) {
+        let mut state = self.state();
+        let function = state
+            .functions
+            .iter_mut()
+            .find(|function| function.id == function_id)
+            .expect("unknown function");
+        function.code = regions.to_vec();
+    }
+
+    /// The module and MethodDef of a function.
+    pub fn function_token(&self, function_id: FunctionID) -> (ModuleID, MethodDefToken) {
+        let state = self.state();
+        let function = state.function(function_id).expect("unknown function");
+        (function.module_id, function.token)
+    }
+
+    /// The function's current IL body, header included.
+    pub fn il_function_body(&self, function_id: FunctionID) -> Vec<u8> {
+        let (module_id, token) = self.function_token(function_id);
+        let state = self.state();
+        let method = state.method(module_id, token).expect("unknown method");
+        method.bodies.last().expect("method without IL").clone()
+    }
+
+    /// Whether the profiler replaced the function's IL body.
+    pub fn is_il_rewritten(&self, function_id: FunctionID) -> bool {
+        let (module_id, token) = self.function_token(function_id);
+        let state = self.state();
+        state
+            .method(module_id, token)
+            .expect("unknown method")
+            .bodies
+            .len()
+            > 1
+    }
+
+    pub(crate) fn set_il_function_body(
+        &self,
+        module_id: ModuleID,
+        token: MethodDefToken,
+        il_body: Vec<u8>,
+    ) -> bool {
+        let mut state = self.state();
+        match state
+            .module_mut(module_id)
+            .and_then(|module| module.methods.get_mut(&token))
+        {
+            Some(method) => {
+                method.bodies.push(il_body);
+                true
+            }
+            None => false,
+        }
+    }
+}
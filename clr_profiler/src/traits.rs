@@ -1,5 +1,7 @@
 mod clr_profiler;
 mod cor_profiler_callback;
+mod cor_profiler_callback_10;
+mod cor_profiler_callback_11;
 mod cor_profiler_callback_2;
 mod cor_profiler_callback_3;
 mod cor_profiler_callback_4;
@@ -10,6 +12,10 @@ mod cor_profiler_callback_8;
 mod cor_profiler_callback_9;
 mod cor_profiler_info;
 mod cor_profiler_info_10;
+mod cor_profiler_info_11;
+mod cor_profiler_info_12;
+mod cor_profiler_info_13;
+mod cor_profiler_info_14;
 mod cor_profiler_info_2;
 mod cor_profiler_info_3;
 mod cor_profiler_info_4;
@@ -22,6 +28,8 @@ mod metadata_import_trait;
 
 pub use self::clr_profiler::ClrProfiler;
 pub use self::cor_profiler_callback::CorProfilerCallback;
+pub use self::cor_profiler_callback_10::CorProfilerCallback10;
+pub use self::cor_profiler_callback_11::CorProfilerCallback11;
 pub use self::cor_profiler_callback_2::CorProfilerCallback2;
 pub use self::cor_profiler_callback_3::CorProfilerCallback3;
 pub use self::cor_profiler_callback_4::CorProfilerCallback4;
@@ -32,6 +40,10 @@ pub use self::cor_profiler_callback_8::CorProfilerCallback8;
 pub use self::cor_profiler_callback_9::CorProfilerCallback9;
 pub use self::cor_profiler_info::CorProfilerInfo;
 pub use self::cor_profiler_info_10::CorProfilerInfo10;
+pub use self::cor_profiler_info_11::CorProfilerInfo11;
+pub use self::cor_profiler_info_12::CorProfilerInfo12;
+pub use self::cor_profiler_info_13::CorProfilerInfo13;
+pub use self::cor_profiler_info_14::CorProfilerInfo14;
 pub use self::cor_profiler_info_2::CorProfilerInfo2;
 pub use self::cor_profiler_info_3::CorProfilerInfo3;
 pub use self::cor_profiler_info_4::CorProfilerInfo4;
@@ -3,4 +3,11 @@ use uuid::Uuid;
 pub trait ClrProfiler {
     fn new() -> Self;
     fn clsid(&self) -> &Uuid;
+
+    /// Whether to turn off every event that can still be turned off after a
+    /// callback panics, rather than keep calling into a profiler whose state
+    /// may be inconsistent. Either way the callback returns `E_FAIL`.
+    fn disable_events_on_panic(&self) -> bool {
+        false
+    }
 }
@@ -0,0 +1,15 @@
+#![allow(unused_variables)]
+use crate::{
+    ffi::{HResult, EVENTPIPE_PROVIDER},
+    CorProfilerCallback9, EventPipeEvent,
+};
+
+pub trait CorProfilerCallback10: CorProfilerCallback9 {
+    fn event_pipe_event_delivered(&self, event: EventPipeEvent) -> Result<(), HResult> {
+        Ok(())
+    }
+
+    fn event_pipe_provider_created(&self, provider: EVENTPIPE_PROVIDER) -> Result<(), HResult> {
+        Ok(())
+    }
+}
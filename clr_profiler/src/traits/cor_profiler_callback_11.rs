@@ -0,0 +1,9 @@
+use crate::{ffi::HResult, CorProfilerCallback10};
+
+pub trait CorProfilerCallback11: CorProfilerCallback10 {
+    /// Return `true` to be loaded for notifications only, alongside the
+    /// profiler that owns the process.
+    fn load_as_notification_only(&self) -> Result<bool, HResult> {
+        Ok(false)
+    }
+}
@@ -1,22 +1,22 @@
 #![allow(unused_variables)]
-use crate::{ffi::HRESULT, CorProfilerCallback2, ProfilerInfo};
+use crate::{ffi::HResult, CorProfilerCallback2, ProfilerInfo};
 use std::ffi::c_void;
 
 pub trait CorProfilerCallback3: CorProfilerCallback2 {
     fn initialize_for_attach(
-        &mut self,
+        &self,
         profiler_info: ProfilerInfo,
         client_data: *const c_void,
         client_data_length: u32,
-    ) -> Result<(), HRESULT> {
+    ) -> Result<(), HResult> {
         Ok(())
     }
 
-    fn profiler_attach_complete(&mut self) -> Result<(), HRESULT> {
+    fn profiler_attach_complete(&self) -> Result<(), HResult> {
         Ok(())
     }
 
-    fn profiler_detach_succeeded(&mut self) -> Result<(), HRESULT> {
+    fn profiler_detach_succeeded(&self) -> Result<(), HResult> {
         Ok(())
     }
 }
@@ -1,64 +1,63 @@
 #![allow(unused_variables)]
 use crate::{
-    ffi::{
-        mdMethodDef, CorProfilerFunctionControl, FunctionID, ModuleID, ObjectID, ReJITID, HRESULT,
-    },
+    ffi::{CorProfilerFunctionControl, FunctionID, HResult, ModuleID, ObjectID, ReJITID},
+    metadata::MethodDefToken,
     CorProfilerCallback3,
 };
 
 pub trait CorProfilerCallback4: CorProfilerCallback3 {
     fn rejit_compilation_started(
-        &mut self,
+        &self,
         function_id: FunctionID,
         rejit_id: ReJITID,
         is_safe_to_block: bool,
-    ) -> Result<(), HRESULT> {
+    ) -> Result<(), HResult> {
         Ok(())
     }
 
     fn get_rejit_parameters(
-        &mut self,
+        &self,
         module_id: ModuleID,
-        method_id: mdMethodDef,
+        method_id: MethodDefToken,
         function_control: &CorProfilerFunctionControl,
-    ) -> Result<(), HRESULT> {
+    ) -> Result<(), HResult> {
         Ok(())
     }
 
     fn rejit_compilation_finished(
-        &mut self,
+        &self,
         function_id: FunctionID,
         rejit_id: ReJITID,
-        hr_status: HRESULT, // TODO: Create enum that actual encodes possible statuses instead of hresult param
+        hr_status: HResult, // TODO: Create enum that actual encodes possible statuses instead of hresult param
         is_safe_to_block: bool,
-    ) -> Result<(), HRESULT> {
+    ) -> Result<(), HResult> {
         Ok(())
     }
 
     fn rejit_error(
-        &mut self,
+        &self,
         module_id: ModuleID,
-        method_id: mdMethodDef,
+        method_id: MethodDefToken,
         function_id: FunctionID,
-        hr_status: HRESULT, // TODO: Create enum that actual encodes possible statuses instead of hresult param
-    ) -> Result<(), HRESULT> {
+        hr_status: HResult, // TODO: Create enum that actual encodes possible statuses instead of hresult param
+    ) -> Result<(), HResult> {
         Ok(())
     }
 
     fn moved_references_2(
-        &mut self,
+        &self,
         old_object_id_range_start: &[ObjectID],
         new_object_id_range_start: &[ObjectID],
         object_id_range_length: &[usize],
-    ) -> Result<(), HRESULT> {
+    ) -> Result<(), HResult> {
         Ok(())
     }
 
     fn surviving_references_2(
-        &mut self,
+        &self,
         object_id_range_start: &[ObjectID],
         c_object_id_range_length: &[usize],
-    ) -> Result<(), HRESULT> {
+    ) -> Result<(), HResult> {
         Ok(())
     }
 }
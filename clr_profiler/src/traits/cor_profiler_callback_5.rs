@@ -1,16 +1,16 @@
 #![allow(unused_variables)]
 use crate::{
-    ffi::{GCHandleID, ObjectID, HRESULT},
+    ffi::{GCHandleID, HResult, ObjectID},
     CorProfilerCallback4,
 };
 
 pub trait CorProfilerCallback5: CorProfilerCallback4 {
     fn conditional_weak_table_element_references(
-        &mut self,
+        &self,
         key_ref_ids: &[ObjectID],
         value_ref_ids: &[ObjectID],
         root_ids: &[GCHandleID],
-    ) -> Result<(), HRESULT> {
+    ) -> Result<(), HResult> {
         Ok(())
     }
 }
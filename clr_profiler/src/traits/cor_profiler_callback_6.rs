@@ -1,15 +1,15 @@
 #![allow(unused_variables)]
 use crate::{
-    ffi::{CorProfilerAssemblyReferenceProvider, HRESULT},
+    ffi::{CorProfilerAssemblyReferenceProvider, HResult},
     CorProfilerCallback5,
 };
 
 pub trait CorProfilerCallback6: CorProfilerCallback5 {
     fn get_assembly_references(
-        &mut self,
+        &self,
         assembly_path: &str,
         asm_ref_provider: &CorProfilerAssemblyReferenceProvider,
-    ) -> Result<(), HRESULT> {
+    ) -> Result<(), HResult> {
         Ok(())
     }
 }
@@ -1,11 +1,11 @@
 #![allow(unused_variables)]
 use crate::{
-    ffi::{ModuleID, HRESULT},
+    ffi::{HResult, ModuleID},
     CorProfilerCallback6,
 };
 
 pub trait CorProfilerCallback7: CorProfilerCallback6 {
-    fn module_in_memory_symbols_updated(&mut self, module_id: ModuleID) -> Result<(), HRESULT> {
+    fn module_in_memory_symbols_updated(&self, module_id: ModuleID) -> Result<(), HResult> {
         Ok(())
     }
 }
@@ -1,11 +1,11 @@
 #![allow(unused_variables)]
 use crate::{
-    ffi::{FunctionID, HRESULT},
+    ffi::{FunctionID, HResult},
     CorProfilerCallback8,
 };
 
 pub trait CorProfilerCallback9: CorProfilerCallback8 {
-    fn dynamic_method_unloaded(&mut self, function_id: FunctionID) -> Result<(), HRESULT> {
+    fn dynamic_method_unloaded(&self, function_id: FunctionID) -> Result<(), HResult> {
         Ok(())
     }
 }
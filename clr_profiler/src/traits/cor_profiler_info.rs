@@ -1,69 +1,70 @@
 use crate::{
     ffi::{
-        mdMethodDef, AppDomainID, AssemblyID, ClassID, ContextID, CorOpenFlags, FunctionEnter,
-        FunctionID, FunctionIDMapper, FunctionLeave, FunctionTailcall, MethodMalloc, ModuleID,
-        ObjectID, ThreadID, COR_DEBUG_IL_TO_NATIVE_MAP, COR_IL_MAP, COR_PRF_MONITOR, DWORD, HANDLE,
-        HRESULT, LPCBYTE,
+        AppDomainID, AssemblyID, ClassID, ComPtr, ContextID, CorOpenFlags, FunctionEnter,
+        FunctionID, FunctionIDMapper, FunctionLeave, FunctionTailcall, HResult, MethodMalloc,
+        ModuleID, ObjectID, ThreadID, COR_DEBUG_IL_TO_NATIVE_MAP, COR_IL_MAP, COR_PRF_MONITOR,
+        DWORD, HANDLE, LPCBYTE,
     },
+    metadata::MethodDefToken,
     AppDomainInfo, ArrayClassInfo, AssemblyInfo, ClassInfo, FunctionInfo, FunctionTokenAndMetadata,
     IlFunctionBody, MetadataImport, ModuleInfo,
 };
 
 pub trait CorProfilerInfo {
-    fn get_class_from_object(&self, object_id: ObjectID) -> Result<ClassID, HRESULT>; // TODO: If class id result is null ptr, will HRESULT be an error?
-    fn get_event_mask(&self) -> Result<COR_PRF_MONITOR, HRESULT>;
-    fn get_function_from_ip(&self, ip: LPCBYTE) -> Result<FunctionID, HRESULT>;
-    fn get_handle_from_thread(&self, thread_id: ThreadID) -> Result<HANDLE, HRESULT>;
-    fn is_array_class(&self, class_id: ClassID) -> Result<ArrayClassInfo, HRESULT>;
-    fn get_thread_info(&self, thread_id: ThreadID) -> Result<DWORD, HRESULT>;
-    fn get_current_thread_id(&self) -> Result<ThreadID, HRESULT>;
-    fn get_class_id_info(&self, class_id: ClassID) -> Result<ClassInfo, HRESULT>;
-    fn get_function_info(&self, function_id: FunctionID) -> Result<FunctionInfo, HRESULT>;
-    fn set_event_mask(&self, events: COR_PRF_MONITOR) -> Result<(), HRESULT>;
+    fn get_class_from_object(&self, object_id: ObjectID) -> Result<ClassID, HResult>; // TODO: If class id result is null ptr, will HRESULT be an error?
+    fn get_event_mask(&self) -> Result<COR_PRF_MONITOR, HResult>;
+    fn get_function_from_ip(&self, ip: LPCBYTE) -> Result<FunctionID, HResult>;
+    fn get_handle_from_thread(&self, thread_id: ThreadID) -> Result<HANDLE, HResult>;
+    fn is_array_class(&self, class_id: ClassID) -> Result<ArrayClassInfo, HResult>;
+    fn get_thread_info(&self, thread_id: ThreadID) -> Result<DWORD, HResult>;
+    fn get_current_thread_id(&self) -> Result<ThreadID, HResult>;
+    fn get_class_id_info(&self, class_id: ClassID) -> Result<ClassInfo, HResult>;
+    fn get_function_info(&self, function_id: FunctionID) -> Result<FunctionInfo, HResult>;
+    fn set_event_mask(&self, events: COR_PRF_MONITOR) -> Result<(), HResult>;
     fn set_enter_leave_function_hooks(
         &self,
         func_enter: FunctionEnter,
         func_leave: FunctionLeave,
         func_tailcall: FunctionTailcall,
-    ) -> Result<(), HRESULT>;
-    fn set_function_id_mapper(&self, func: FunctionIDMapper) -> Result<(), HRESULT>;
+    ) -> Result<(), HResult>;
+    fn set_function_id_mapper(&self, func: FunctionIDMapper) -> Result<(), HResult>;
     fn get_token_and_metadata_from_function(
         &self,
         function_id: FunctionID,
-    ) -> Result<FunctionTokenAndMetadata, HRESULT>;
-    fn get_module_info(&self, module_id: ModuleID) -> Result<ModuleInfo, HRESULT>;
+    ) -> Result<FunctionTokenAndMetadata, HResult>;
+    fn get_module_info(&self, module_id: ModuleID) -> Result<ModuleInfo, HResult>;
     fn get_module_metadata(
         &self,
         module_id: ModuleID,
         open_flags: CorOpenFlags,
-    ) -> Result<MetadataImport, HRESULT>;
+    ) -> Result<MetadataImport, HResult>;
     fn get_il_function_body(
         &self,
         module_id: ModuleID,
-        method_id: mdMethodDef,
-    ) -> Result<IlFunctionBody, HRESULT>;
+        method_id: MethodDefToken,
+    ) -> Result<IlFunctionBody, HResult>;
     fn get_il_function_body_allocator(
         &self,
         module_id: ModuleID,
-    ) -> Result<&mut MethodMalloc, HRESULT>;
+    ) -> Result<ComPtr<MethodMalloc>, HResult>;
     fn set_il_function_body(
         &self,
         module_id: ModuleID,
-        method_id: mdMethodDef,
+        method_id: MethodDefToken,
         new_il_method_header: LPCBYTE,
-    ) -> Result<(), HRESULT>;
-    fn get_app_domain_info(&self, app_domain_id: AppDomainID) -> Result<AppDomainInfo, HRESULT>;
-    fn get_assembly_info(&self, assembly_id: AssemblyID) -> Result<AssemblyInfo, HRESULT>;
-    fn force_gc(&self) -> Result<(), HRESULT>;
+    ) -> Result<(), HResult>;
+    fn get_app_domain_info(&self, app_domain_id: AppDomainID) -> Result<AppDomainInfo, HResult>;
+    fn get_assembly_info(&self, assembly_id: AssemblyID) -> Result<AssemblyInfo, HResult>;
+    fn force_gc(&self) -> Result<(), HResult>;
     fn set_il_instrumented_code_map(
         &self,
         function_id: FunctionID,
         start_jit: bool,
         il_map_entries: &[COR_IL_MAP],
-    ) -> Result<(), HRESULT>;
-    fn get_thread_context(&self, thread_id: ThreadID) -> Result<ContextID, HRESULT>;
+    ) -> Result<(), HResult>;
+    fn get_thread_context(&self, thread_id: ThreadID) -> Result<ContextID, HResult>;
     fn get_il_to_native_mapping(
         &self,
         function_id: FunctionID,
-    ) -> Result<Vec<COR_DEBUG_IL_TO_NATIVE_MAP>, HRESULT>;
+    ) -> Result<Vec<COR_DEBUG_IL_TO_NATIVE_MAP>, HResult>;
 }
@@ -0,0 +1,7 @@
+use crate::{ffi::HResult, CorProfilerInfo10};
+
+pub trait CorProfilerInfo11: CorProfilerInfo10 {
+    fn get_environment_variable(&self, name: &str) -> Result<String, HResult>;
+    /// Sets a variable of the process, or removes it when `value` is `None`.
+    fn set_environment_variable(&self, name: &str, value: Option<&str>) -> Result<(), HResult>;
+}
@@ -0,0 +1,38 @@
+use crate::{
+    ffi::{HResult, EVENTPIPE_EVENT, EVENTPIPE_PROVIDER, EVENTPIPE_SESSION, GUID},
+    CorProfilerInfo11, EventPipeEventDefinition, EventPipeProviderConfig,
+};
+
+pub trait CorProfilerInfo12: CorProfilerInfo11 {
+    fn event_pipe_start_session(
+        &self,
+        provider_configs: &[EventPipeProviderConfig],
+        request_rundown: bool,
+    ) -> Result<EVENTPIPE_SESSION, HResult>;
+    fn event_pipe_add_provider_to_session(
+        &self,
+        session: EVENTPIPE_SESSION,
+        provider_config: &EventPipeProviderConfig,
+    ) -> Result<(), HResult>;
+    fn event_pipe_stop_session(&self, session: EVENTPIPE_SESSION) -> Result<(), HResult>;
+    fn event_pipe_create_provider(
+        &self,
+        provider_name: &str,
+    ) -> Result<EVENTPIPE_PROVIDER, HResult>;
+    fn event_pipe_get_provider_info(&self, provider: EVENTPIPE_PROVIDER)
+        -> Result<String, HResult>;
+    fn event_pipe_define_event(
+        &self,
+        provider: EVENTPIPE_PROVIDER,
+        event: &EventPipeEventDefinition,
+    ) -> Result<EVENTPIPE_EVENT, HResult>;
+    /// Writes an event whose payload is the concatenation of `data`, laid out
+    /// as described by the event's parameters.
+    fn event_pipe_write_event(
+        &self,
+        event: EVENTPIPE_EVENT,
+        data: &[&[u8]],
+        activity_id: Option<&GUID>,
+        related_activity_id: Option<&GUID>,
+    ) -> Result<(), HResult>;
+}
@@ -0,0 +1,28 @@
+use crate::{
+    ffi::{HResult, ObjectHandleID, ObjectID, COR_PRF_HANDLE_TYPE},
+    CorProfilerInfo12,
+};
+
+pub trait CorProfilerInfo13: CorProfilerInfo12 {
+    fn create_handle(
+        &self,
+        object_id: ObjectID,
+        handle_type: COR_PRF_HANDLE_TYPE,
+    ) -> Result<ObjectHandleID, HResult>;
+    /// # Safety
+    ///
+    /// `handle` must come from [`create_handle`](Self::create_handle) and not
+    /// have been destroyed yet. It can't be used after this.
+    unsafe fn destroy_handle(&self, handle: ObjectHandleID) -> Result<(), HResult>;
+    /// The object a handle refers to, `None` once a weak handle's object was
+    /// collected.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must come from [`create_handle`](Self::create_handle) and not
+    /// have been destroyed yet.
+    unsafe fn get_object_id_from_handle(
+        &self,
+        handle: ObjectHandleID,
+    ) -> Result<Option<ObjectID>, HResult>;
+}
@@ -0,0 +1,17 @@
+use crate::{
+    ffi::{
+        CorProfilerObjectEnum, EventPipeProviderCallback, HResult, COR_PRF_NONGC_HEAP_RANGE,
+        EVENTPIPE_PROVIDER,
+    },
+    CorProfilerInfo13, ProfilerEnum,
+};
+
+pub trait CorProfilerInfo14: CorProfilerInfo13 {
+    fn enumerate_non_gc_objects(&self) -> Result<ProfilerEnum<CorProfilerObjectEnum>, HResult>;
+    fn get_non_gc_heap_bounds(&self) -> Result<Vec<COR_PRF_NONGC_HEAP_RANGE>, HResult>;
+    fn event_pipe_create_provider_2(
+        &self,
+        provider_name: &str,
+        callback: Option<EventPipeProviderCallback>,
+    ) -> Result<EVENTPIPE_PROVIDER, HResult>;
+}
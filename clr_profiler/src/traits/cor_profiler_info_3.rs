@@ -1,64 +1,65 @@
 use crate::{
     ffi::{
-        mdFieldDef, AppDomainID, ClassID, CorProfilerFunctionEnum, CorProfilerModuleEnum,
-        FunctionEnter3, FunctionEnter3WithInfo, FunctionID, FunctionIDMapper2, FunctionLeave3,
-        FunctionLeave3WithInfo, FunctionTailcall3, FunctionTailcall3WithInfo, ModuleID, ThreadID,
-        COR_PRF_ELT_INFO, COR_PRF_FRAME_INFO, HRESULT,
+        AppDomainID, ClassID, CorProfilerFunctionEnum, CorProfilerModuleEnum, FunctionEnter3,
+        FunctionEnter3WithInfo, FunctionID, FunctionIDMapper2, FunctionLeave3,
+        FunctionLeave3WithInfo, FunctionTailcall3, FunctionTailcall3WithInfo, HResult, ModuleID,
+        ThreadID, COR_PRF_ELT_INFO, COR_PRF_FRAME_INFO,
     },
-    CorProfilerInfo2, FunctionEnter3Info, FunctionLeave3Info, ModuleInfo2, RuntimeInfo,
-    StringLayout,
+    metadata::FieldDefToken,
+    CorProfilerInfo2, FunctionEnter3Info, FunctionLeave3Info, ModuleInfo2, ProfilerEnum,
+    RuntimeInfo, StringLayout,
 };
 use std::ffi::c_void;
 
 pub trait CorProfilerInfo3: CorProfilerInfo2 {
-    fn enum_jited_functions(&self) -> Result<&mut CorProfilerFunctionEnum, HRESULT>;
+    fn enum_jited_functions(&self) -> Result<ProfilerEnum<CorProfilerFunctionEnum>, HResult>;
     fn request_profiler_detach(&self, expected_completion_milliseconds: u32)
-        -> Result<(), HRESULT>;
+        -> Result<(), HResult>;
     fn set_function_id_mapper_2(
         &self,
         func: FunctionIDMapper2,
         client_data: *const c_void,
-    ) -> Result<(), HRESULT>;
-    fn get_string_layout_2(&self) -> Result<StringLayout, HRESULT>;
+    ) -> Result<(), HResult>;
+    fn get_string_layout_2(&self) -> Result<StringLayout, HResult>;
     fn set_enter_leave_function_hooks_3(
         &self,
         func_enter_3: FunctionEnter3,
         func_leave_3: FunctionLeave3,
         func_tailcall_3: FunctionTailcall3,
-    ) -> Result<(), HRESULT>;
+    ) -> Result<(), HResult>;
     fn set_enter_leave_function_hooks_3_with_info(
         &self,
         func_enter_3_with_info: FunctionEnter3WithInfo,
         func_leave_3_with_info: FunctionLeave3WithInfo,
         func_tailcall_3_with_info: FunctionTailcall3WithInfo,
-    ) -> Result<(), HRESULT>;
+    ) -> Result<(), HResult>;
     fn get_function_enter_3_info(
         &self,
         function_id: FunctionID,
         elt_info: COR_PRF_ELT_INFO,
-    ) -> Result<FunctionEnter3Info, HRESULT>;
+    ) -> Result<FunctionEnter3Info, HResult>;
     fn get_function_leave_3_info(
         &self,
         function_id: FunctionID,
         elt_info: COR_PRF_ELT_INFO,
-    ) -> Result<FunctionLeave3Info, HRESULT>;
+    ) -> Result<FunctionLeave3Info, HResult>;
     fn get_function_tailcall_3_info(
         &self,
         function_id: FunctionID,
         elt_info: COR_PRF_ELT_INFO,
-    ) -> Result<COR_PRF_FRAME_INFO, HRESULT>;
-    fn enum_modules(&self) -> Result<&mut CorProfilerModuleEnum, HRESULT>;
-    fn get_runtime_information(&self) -> Result<RuntimeInfo, HRESULT>;
+    ) -> Result<COR_PRF_FRAME_INFO, HResult>;
+    fn enum_modules(&self) -> Result<ProfilerEnum<CorProfilerModuleEnum>, HResult>;
+    fn get_runtime_information(&self) -> Result<RuntimeInfo, HResult>;
     fn get_thread_static_address_2(
         &self,
         class_id: ClassID,
-        field_token: mdFieldDef,
+        field_token: FieldDefToken,
         app_domain_id: AppDomainID,
         thread_id: ThreadID,
-    ) -> Result<*const c_void, HRESULT>;
+    ) -> Result<*const c_void, HResult>;
     fn get_app_domains_containing_module(
         &self,
         module_id: ModuleID,
-    ) -> Result<Vec<AppDomainID>, HRESULT>;
-    fn get_module_info_2(&self, module_id: ModuleID) -> Result<ModuleInfo2, HRESULT>;
+    ) -> Result<Vec<AppDomainID>, HResult>;
+    fn get_module_info_2(&self, module_id: ModuleID) -> Result<ModuleInfo2, HResult>;
 }
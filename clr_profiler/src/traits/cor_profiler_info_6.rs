@@ -1,5 +1,6 @@
 use crate::{
-    ffi::{mdMethodDef, ModuleID, HRESULT},
+    ffi::{HResult, ModuleID},
+    metadata::MethodDefToken,
     CorProfilerInfo5, EnumNgenModuleMethodsInliningThisMethod,
 };
 
@@ -8,6 +9,6 @@ pub trait CorProfilerInfo6: CorProfilerInfo5 {
         &self,
         inliners_module_id: ModuleID,
         inlinee_module_id: ModuleID,
-        inlinee_method_id: mdMethodDef,
-    ) -> Result<EnumNgenModuleMethodsInliningThisMethod, HRESULT>;
+        inlinee_method_id: MethodDefToken,
+    ) -> Result<EnumNgenModuleMethodsInliningThisMethod, HResult>;
 }
@@ -1,5 +1,5 @@
 use crate::{
-    ffi::{FunctionID, ReJITID, COR_DEBUG_IL_TO_NATIVE_MAP, COR_PRF_CODE_INFO, HRESULT, UINT_PTR},
+    ffi::{FunctionID, HResult, ReJITID, COR_DEBUG_IL_TO_NATIVE_MAP, COR_PRF_CODE_INFO, UINT_PTR},
     CorProfilerInfo8,
 };
 
@@ -8,13 +8,13 @@ pub trait CorProfilerInfo9: CorProfilerInfo8 {
         &self,
         function_id: FunctionID,
         rejit_id: ReJITID,
-    ) -> Result<Vec<UINT_PTR>, HRESULT>;
+    ) -> Result<Vec<UINT_PTR>, HResult>;
     fn get_il_to_native_mapping_3(
         &self,
         native_code_start_address: UINT_PTR,
-    ) -> Result<Vec<COR_DEBUG_IL_TO_NATIVE_MAP>, HRESULT>;
+    ) -> Result<Vec<COR_DEBUG_IL_TO_NATIVE_MAP>, HResult>;
     fn get_code_info_4(
         &self,
         native_code_start_address: UINT_PTR,
-    ) -> Result<Vec<COR_PRF_CODE_INFO>, HRESULT>;
+    ) -> Result<Vec<COR_PRF_CODE_INFO>, HResult>;
 }
@@ -1,9 +1,244 @@
 use crate::{
-    ffi::{mdMethodDef, mdTypeDef, HRESULT},
-    MethodProps, TypeDefProps,
+    ffi::HResult,
+    metadata::{
+        signature_bytes, CustomAttributeArgType, CustomAttributeArgs, CustomAttributeToken, Error,
+        FieldDefToken, GenericParamToken, MemberRefToken, MethodDefToken, MethodSpecToken,
+        NameFormatter, StringToken, Token, TypeDefToken, TypeRefToken, TypeSpecToken, UserStrings,
+    },
+    CustomAttribute, CustomAttributeProps, FieldProps, GenericParamProps, MemberRefProps,
+    MethodProps, MethodSpecProps, TypeDefProps, TypeRefProps, TypeSpecProps,
 };
+use log::debug;
+use std::convert::TryFrom;
 
 pub trait MetadataImportTrait {
-    fn get_method_props(&self, mb: mdMethodDef) -> Result<MethodProps, HRESULT>;
-    fn get_type_def_props(&self, td: mdTypeDef) -> Result<TypeDefProps, HRESULT>;
+    fn get_method_props(&self, mb: MethodDefToken) -> Result<MethodProps, HResult>;
+    fn get_type_def_props(&self, td: TypeDefToken) -> Result<TypeDefProps, HResult>;
+    fn get_type_ref_props(&self, tr: TypeRefToken) -> Result<TypeRefProps, HResult>;
+    fn get_member_ref_props(&self, mr: MemberRefToken) -> Result<MemberRefProps, HResult>;
+    fn get_field_props(&self, fd: FieldDefToken) -> Result<FieldProps, HResult>;
+    fn get_type_spec_from_token(&self, ts: TypeSpecToken) -> Result<TypeSpecProps, HResult>;
+    fn get_method_spec_props(&self, mi: MethodSpecToken) -> Result<MethodSpecProps, HResult>;
+    /// Returns the class `td` is nested in, or `None` for top level types.
+    fn get_nested_class_props(&self, td: TypeDefToken) -> Result<Option<TypeDefToken>, HResult>;
+    /// Enumerates the generic parameters of a TypeDef or MethodDef.
+    fn enum_generic_params(&self, tk: Token) -> Result<Vec<GenericParamToken>, HResult>;
+    fn get_generic_param_props(&self, gp: GenericParamToken) -> Result<GenericParamProps, HResult>;
+    /// Enumerates the types defined in the module, except the `<Module>` type
+    /// holding global functions.
+    fn enum_type_defs(&self) -> Result<Vec<TypeDefToken>, HResult>;
+    fn enum_methods(&self, td: TypeDefToken) -> Result<Vec<MethodDefToken>, HResult>;
+    fn enum_fields(&self, td: TypeDefToken) -> Result<Vec<FieldDefToken>, HResult>;
+    /// Returns the literal loaded by an `ldstr` instruction. User strings may
+    /// contain embedded nulls, which are kept.
+    fn get_user_string(&self, stk: StringToken) -> Result<String, HResult>;
+    /// Enumerates the tokens of all strings in the module's user string heap.
+    fn enum_user_strings(&self) -> Result<Vec<StringToken>, HResult>;
+    /// Finds a TypeDef by its namespace qualified name. Pass `None` as
+    /// `enclosing_class` for top level types.
+    fn find_type_def_by_name(
+        &self,
+        name: &str,
+        enclosing_class: Option<TypeDefToken>,
+    ) -> Result<TypeDefToken, HResult>;
+    /// Enumerates the custom attributes attached to `tk`. If `tk_type` is
+    /// given, only attributes whose constructor belongs to that type are returned.
+    fn enum_custom_attributes(
+        &self,
+        tk: Token,
+        tk_type: Option<Token>,
+    ) -> Result<Vec<CustomAttributeToken>, HResult>;
+    fn get_custom_attribute_props(
+        &self,
+        cv: CustomAttributeToken,
+    ) -> Result<CustomAttributeProps, HResult>;
+    /// Returns the raw value blob of the attribute named `name` (namespace
+    /// qualified, e.g. `System.ObsoleteAttribute`), or `None` if `tk_obj`
+    /// doesn't have it.
+    fn get_custom_attribute_by_name(
+        &self,
+        tk_obj: Token,
+        name: &str,
+    ) -> Result<Option<Vec<u8>>, HResult>;
+    /// Resolves `tr` to its TypeDef in the module that defines it and calls
+    /// `f` with that module's scope.
+    fn with_resolved_type_ref<R>(
+        &self,
+        tr: TypeRefToken,
+        f: impl FnOnce(&Self, TypeDefToken) -> R,
+    ) -> Result<R, HResult>
+    where
+        Self: Sized;
+
+    /// Returns the names of the generic parameters of a TypeDef or MethodDef, in order.
+    fn get_generic_param_names(&self, tk: Token) -> Result<Vec<String>, HResult> {
+        let mut params = self
+            .enum_generic_params(tk)?
+            .into_iter()
+            .map(|gp| self.get_generic_param_props(gp))
+            .collect::<Result<Vec<_>, _>>()?;
+        params.sort_by_key(|param| param.sequence);
+        Ok(params.into_iter().map(|param| param.name).collect())
+    }
+
+    /// Returns a stable, fully qualified name for a MethodDef, MemberRef or
+    /// MethodSpec, e.g. `Namespace.Outer+Inner<T>::Method(int, string)`, for
+    /// a FieldDef (`Namespace.Type::field`), or for a TypeDef, TypeRef or TypeSpec.
+    fn qualified_name(&self, token: Token) -> Result<String, HResult>
+    where
+        Self: Sized,
+    {
+        NameFormatter::new(self).qualified_name(token)
+    }
+
+    /// Iterates over the module's string literals. Each string is read when
+    /// the iterator reaches it.
+    fn user_strings(&self) -> Result<UserStrings<'_, Self>, HResult>
+    where
+        Self: Sized,
+    {
+        Ok(UserStrings::new(self, self.enum_user_strings()?))
+    }
+
+    /// Returns the namespace qualified name of a TypeDef or TypeRef.
+    fn get_type_name(&self, token: Token) -> Result<String, HResult> {
+        if let Ok(td) = TypeDefToken::try_from(token) {
+            Ok(self.get_type_def_props(td)?.name)
+        } else if let Ok(tr) = TypeRefToken::try_from(token) {
+            Ok(self.get_type_ref_props(tr)?.name)
+        } else {
+            Err(HResult::E_INVALIDARG)
+        }
+    }
+
+    /// Returns all custom attributes of a type, method or any other token,
+    /// with their arguments decoded.
+    fn get_custom_attributes(&self, tk: Token) -> Result<Vec<CustomAttribute>, HResult> {
+        self.enum_custom_attributes(tk, None)?
+            .into_iter()
+            .map(|cv| self.get_custom_attribute(cv))
+            .collect()
+    }
+
+    fn get_custom_attribute(&self, cv: CustomAttributeToken) -> Result<CustomAttribute, HResult> {
+        let props = self.get_custom_attribute_props(cv)?;
+        let ctor = props.constructor_token;
+        let (type_token, sig, sig_length) = if let Ok(mb) = MethodDefToken::try_from(ctor) {
+            let ctor = self.get_method_props(mb)?;
+            (ctor.class_token.token(), ctor.sig, ctor.sig_length)
+        } else if let Ok(mr) = MemberRefToken::try_from(ctor) {
+            let ctor = self.get_member_ref_props(mr)?;
+            (ctor.parent_token, ctor.sig, ctor.sig_length)
+        } else {
+            return Err(HResult::META_E_CA_INVALID_BLOB);
+        };
+        let type_name = self.get_type_name(type_token)?;
+        let sig = unsafe { signature_bytes(sig, sig_length) };
+
+        let resolve_type = |token: Token| {
+            let name = self.get_type_name(token).unwrap_or_default();
+            if name == "System.Type" {
+                return Ok(CustomAttributeArgType::Type);
+            }
+            let underlying = self.get_enum_underlying_type(token);
+            Ok(CustomAttributeArgType::Enum(name, Box::new(underlying)))
+        };
+        let resolve_enum = |name: &str| {
+            // Named enum arguments only carry the (assembly qualified) type name.
+            let name = name.split(',').next().unwrap_or(name);
+            self.find_type_def_by_name(name, None)
+                .map(|td| self.get_enum_underlying_type(td.token()))
+                .unwrap_or(CustomAttributeArgType::I4)
+        };
+        let fixed_arg_types =
+            CustomAttributeArgType::from_constructor_signature(sig, &resolve_type)
+                .map_err(custom_attribute_error)?;
+        let args = CustomAttributeArgs::from_bytes(&props.blob, &fixed_arg_types, &resolve_enum)
+            .map_err(custom_attribute_error)?;
+
+        Ok(CustomAttribute {
+            token: cv,
+            type_name,
+            constructor_token: props.constructor_token,
+            args,
+        })
+    }
+
+    /// Finds a custom attribute by name. Besides the namespace qualified name,
+    /// `name` may be the simple name as written in C#, e.g. `Trace` for
+    /// `[Trace]` matches `MyCompany.Diagnostics.TraceAttribute`.
+    fn find_custom_attribute(
+        &self,
+        tk: Token,
+        name: &str,
+    ) -> Result<Option<CustomAttribute>, HResult> {
+        let attribute = self
+            .get_custom_attributes(tk)?
+            .into_iter()
+            .find(|attribute| attribute_name_matches(&attribute.type_name, name));
+        Ok(attribute)
+    }
+
+    fn has_custom_attribute(&self, tk: Token, name: &str) -> Result<bool, HResult> {
+        for cv in self.enum_custom_attributes(tk, None)? {
+            let ctor = self.get_custom_attribute_props(cv)?.constructor_token;
+            let type_token = if let Ok(mb) = MethodDefToken::try_from(ctor) {
+                self.get_method_props(mb)?.class_token.token()
+            } else if let Ok(mr) = MemberRefToken::try_from(ctor) {
+                self.get_member_ref_props(mr)?.parent_token
+            } else {
+                continue;
+            };
+            if let Ok(type_name) = self.get_type_name(type_token) {
+                if attribute_name_matches(&type_name, name) {
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    /// Returns the type of the `value__` field of an enum defined in this
+    /// module. Enums defined elsewhere can't be inspected and are assumed to
+    /// be `int` backed, which is the C# default.
+    fn get_enum_underlying_type(&self, token: Token) -> CustomAttributeArgType {
+        if let Ok(td) = TypeDefToken::try_from(token) {
+            let fields = self.enum_fields(td).unwrap_or_default();
+            for field in fields {
+                let Ok(props) = self.get_field_props(field) else {
+                    continue;
+                };
+                if props.name != "value__" {
+                    continue;
+                }
+                // FieldSig: FIELD (0x06) followed by the field type
+                let sig = unsafe { signature_bytes(props.sig, props.sig_length) };
+                if let Some(arg_type) = sig.get(1).and_then(|element_type| {
+                    CustomAttributeArgType::from_element_type(*element_type)
+                }) {
+                    return arg_type;
+                }
+            }
+        }
+        debug!("couldn't find underlying type of enum {token}, assuming int");
+        CustomAttributeArgType::I4
+    }
+}
+
+fn custom_attribute_error(error: Error) -> HResult {
+    match error {
+        Error::TooDeeplyNested => HResult::META_E_BAD_SIGNATURE,
+        _ => HResult::META_E_CA_INVALID_BLOB,
+    }
+}
+
+fn attribute_name_matches(type_name: &str, name: &str) -> bool {
+    if type_name == name {
+        return true;
+    }
+    let simple_name = type_name.rsplit(['.', '+']).next().unwrap_or(type_name);
+    simple_name == name
+        || simple_name
+            .strip_suffix("Attribute")
+            .is_some_and(|short_name| short_name == name)
 }
@@ -0,0 +1,132 @@
+use crate::{
+    ffi::{ClassID, CorOpenFlags, FunctionID, HResult},
+    metadata::{signature_bytes, GenericContext, NameFormatter, TypeSig},
+    CorProfilerInfo, CorProfilerInfo2, FunctionInfo2, MetadataImport, MetadataImportTrait,
+    ProfilerInfo,
+};
+use std::{collections::HashMap, sync::Mutex};
+
+/// Names runtime classes and functions with their full generic instantiation,
+/// e.g. `System.Collections.Generic.List<System.Collections.Generic.Dictionary<string, int>>::Add(...)`.
+///
+/// Names use the same format as [`MetadataImportTrait::qualified_name`] and are
+/// cached by ID. IDs can be reused once their module is unloaded, so call
+/// [`TypeNameResolver::clear`] from `module_unload_started`.
+pub struct TypeNameResolver {
+    profiler_info: ProfilerInfo,
+    classes: Mutex<HashMap<ClassID, String>>,
+    functions: Mutex<HashMap<FunctionID, String>>,
+}
+
+impl TypeNameResolver {
+    pub fn new(profiler_info: ProfilerInfo) -> Self {
+        TypeNameResolver {
+            profiler_info,
+            classes: Mutex::new(HashMap::new()),
+            functions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn class_name(&self, class_id: ClassID) -> Result<String, HResult> {
+        if let Some(name) = self.classes.lock().unwrap().get(&class_id) {
+            return Ok(name.clone());
+        }
+        let name = match self.profiler_info.is_array_class(class_id) {
+            Ok(array) => {
+                let element = match array.element_class_id {
+                    Some(element_class_id) => self.class_name(element_class_id)?,
+                    None => {
+                        let element_type = array.element_type as u8;
+                        TypeSig::primitive(element_type)
+                            .and_then(|primitive| primitive.keyword())
+                            .map(str::to_string)
+                            .ok_or(HResult::META_E_BAD_SIGNATURE)?
+                    }
+                };
+                let commas = ",".repeat(array.rank.saturating_sub(1) as usize);
+                format!("{element}[{commas}]")
+            }
+            Err(HResult::S_FALSE) => self.non_array_class_name(class_id)?,
+            Err(hr) => return Err(hr),
+        };
+        self.classes.lock().unwrap().insert(class_id, name.clone());
+        Ok(name)
+    }
+
+    /// Names a function as `Type<type args>::Method<method args>(params)`.
+    ///
+    /// Code shared between reference type instantiations reports
+    /// `System.__Canon` for the type arguments it was compiled for.
+    pub fn function_name(&self, function_id: FunctionID) -> Result<String, HResult> {
+        if let Some(name) = self.functions.lock().unwrap().get(&function_id) {
+            return Ok(name.clone());
+        }
+        let function_info = self.profiler_info.get_function_info_2(function_id, 0)?;
+        let metadata = self
+            .profiler_info
+            .get_module_metadata(function_info.module_id, CorOpenFlags::ofRead)?;
+        let name = self.format_function(&metadata, &function_info)?;
+        self.functions
+            .lock()
+            .unwrap()
+            .insert(function_id, name.clone());
+        Ok(name)
+    }
+
+    pub fn clear(&self) {
+        self.classes.lock().unwrap().clear();
+        self.functions.lock().unwrap().clear();
+    }
+
+    fn non_array_class_name(&self, class_id: ClassID) -> Result<String, HResult> {
+        let class_info = self.profiler_info.get_class_id_info_2(class_id)?;
+        let type_args = self.class_names(&class_info.type_args)?;
+        let metadata = self
+            .profiler_info
+            .get_module_metadata(class_info.module_id, CorOpenFlags::ofRead)?;
+        let name =
+            NameFormatter::new(&metadata).type_name(class_info.token.token(), Some(&type_args))?;
+        let keyword = TypeSig::from_type_name(&name).and_then(|primitive| primitive.keyword());
+        Ok(keyword.map(str::to_string).unwrap_or(name))
+    }
+
+    fn format_function(
+        &self,
+        metadata: &MetadataImport,
+        function_info: &FunctionInfo2,
+    ) -> Result<String, HResult> {
+        let formatter = NameFormatter::new(metadata);
+        let method_props = metadata.get_method_props(function_info.token)?;
+        let (type_name, type_args) = if let Some(class_id) = function_info.class_id {
+            let class_info = self.profiler_info.get_class_id_info_2(class_id)?;
+            let type_args = self.class_names(&class_info.type_args)?;
+            (self.class_name(class_id)?, type_args)
+        } else {
+            // the class can't be determined without a frame for some shared generic code
+            let class_token = method_props.class_token.token();
+            let type_args = metadata.get_generic_param_names(class_token)?;
+            (
+                formatter.type_name(class_token, Some(&type_args))?,
+                type_args,
+            )
+        };
+        let method_args = if function_info.type_args.is_empty() {
+            metadata.get_generic_param_names(function_info.token.token())?
+        } else {
+            self.class_names(&function_info.type_args)?
+        };
+        let context = GenericContext {
+            type_args,
+            method_args,
+        };
+        let sig = unsafe { signature_bytes(method_props.sig, method_props.sig_length) };
+        formatter.member_name(&type_name, &method_props.name, sig, &context)
+    }
+
+    fn class_names(&self, class_ids: &[ClassID]) -> Result<Vec<String>, HResult> {
+        class_ids
+            .iter()
+            .map(|class_id| self.class_name(*class_id))
+            .collect()
+    }
+}
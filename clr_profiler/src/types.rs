@@ -1,9 +1,14 @@
 use crate::ffi::{
-    mdMethodDef, mdTypeDef, AppDomainID, AssemblyID, ClassID, ClrInstanceID, CorElementType, 
+    CorFieldAttr, CorGenericParamAttr, AppDomainID, AssemblyID, ClassID, ClrInstanceID, CorElementType, 
     CorMethodAttr, CorMethodImpl, CorProfilerMethodEnum, CorTypeAttr, FunctionID, 
-    MetaDataImport, ModuleID, ProcessID, ReJITID, BYTE, COR_FIELD_OFFSET, COR_PRF_FRAME_INFO, 
+    ModuleID, ProcessID, ReJITID, BYTE, COR_FIELD_OFFSET, COR_PRF_FRAME_INFO, 
     COR_PRF_FUNCTION_ARGUMENT_INFO, COR_PRF_FUNCTION_ARGUMENT_RANGE, COR_PRF_HIGH_MONITOR, 
-    COR_PRF_MODULE_FLAGS, COR_PRF_MONITOR, COR_PRF_RUNTIME_TYPE, LPCBYTE, PCCOR_SIGNATURE
+    COR_PRF_EVENTPIPE_LEVEL, COR_PRF_EVENTPIPE_PARAM_TYPE, COR_PRF_MODULE_FLAGS, COR_PRF_MONITOR, COR_PRF_RUNTIME_TYPE, LPCBYTE, PCCOR_SIGNATURE,
+    EVENTPIPE_PROVIDER, GUID, ThreadID, UINT_PTR,
+};
+use crate::{MetadataImport, ProfilerEnum};
+use crate::metadata::{
+    CustomAttributeArgs, CustomAttributeToken, MethodDefToken, Token, TypeDefToken,
 };
 
 pub struct ArrayClassInfo {
@@ -14,24 +19,24 @@ pub struct ArrayClassInfo {
 
 pub struct ClassInfo {
     pub module_id: ModuleID,
-    pub token: mdTypeDef,
+    pub token: TypeDefToken,
 }
 
 #[derive(Debug)]
 pub struct FunctionInfo {
-    pub class_id: ClassID,
+    pub class_id: Option<ClassID>,
     pub module_id: ModuleID,
-    pub token: mdMethodDef,
+    pub token: MethodDefToken,
 }
 
 pub struct FunctionTokenAndMetadata {
-    pub metadata_import: *mut MetaDataImport,
-    pub token: mdMethodDef,
+    pub metadata_import: MetadataImport,
+    pub token: MethodDefToken,
 }
 
 #[derive(Debug)]
 pub struct ModuleInfo {
-    pub base_load_address: LPCBYTE,
+    pub base_load_address: Option<LPCBYTE>,
     pub file_name: String,
     pub assembly_id: AssemblyID,
 }
@@ -53,9 +58,9 @@ pub struct AssemblyInfo {
 }
 
 pub struct FunctionInfo2 {
-    pub class_id: ClassID,
+    pub class_id: Option<ClassID>,
     pub module_id: ModuleID,
-    pub token: mdMethodDef,
+    pub token: MethodDefToken,
     pub type_args: Vec<ClassID>,
 }
 
@@ -66,15 +71,15 @@ pub struct ClassLayout {
 
 pub struct ClassInfo2 {
     pub module_id: ModuleID,
-    pub token: mdTypeDef,
-    pub parent_class_id: ClassID,
+    pub token: TypeDefToken,
+    pub parent_class_id: Option<ClassID>,
     pub type_args: Vec<ClassID>,
 }
 
 pub struct ArrayObjectInfo {
     pub dimension_sizes: Vec<u32>,
     pub dimension_lower_bounds: Vec<i32>,
-    pub data: *mut BYTE, // TODO: This should be the raw buffer for the array, which is laid out according to the C++ convention.
+    pub data: Option<*mut BYTE>, // TODO: This should be the raw buffer for the array, which is laid out according to the C++ convention.
 }
 
 pub struct StringLayout {
@@ -105,7 +110,7 @@ pub struct RuntimeInfo {
 
 #[derive(Debug)]
 pub struct ModuleInfo2 {
-    pub base_load_address: LPCBYTE,
+    pub base_load_address: Option<LPCBYTE>,
     pub file_name: String,
     pub assembly_id: AssemblyID,
     pub module_flags: COR_PRF_MODULE_FLAGS,
@@ -116,14 +121,33 @@ pub struct FunctionAndRejit {
     pub rejit_id: ReJITID,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct EventMask2 {
     pub events_low: COR_PRF_MONITOR,
     pub events_high: COR_PRF_HIGH_MONITOR,
 }
 
-pub struct EnumNgenModuleMethodsInliningThisMethod<'a> {
+impl EventMask2 {
+    pub const NONE: EventMask2 = EventMask2 {
+        events_low: COR_PRF_MONITOR::empty(),
+        events_high: COR_PRF_HIGH_MONITOR::empty(),
+    };
+
+    pub const fn union(self, other: EventMask2) -> EventMask2 {
+        EventMask2 {
+            events_low: self.events_low.union(other.events_low),
+            events_high: self.events_high.union(other.events_high),
+        }
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.events_low.is_empty() && self.events_high.is_empty()
+    }
+}
+
+pub struct EnumNgenModuleMethodsInliningThisMethod {
     pub incomplete_data: bool,
-    pub method_enum: &'a mut CorProfilerMethodEnum,
+    pub method_enum: ProfilerEnum<CorProfilerMethodEnum>,
 }
 
 pub struct DynamicFunctionInfo {
@@ -133,9 +157,40 @@ pub struct DynamicFunctionInfo {
     pub name: String,
 }
 
+/// A provider to enable in an EventPipe session.
+#[derive(Debug, Clone)]
+pub struct EventPipeProviderConfig {
+    pub provider_name: String,
+    pub keywords: u64,
+    pub logging_level: COR_PRF_EVENTPIPE_LEVEL,
+    /// Provider specific filter arguments, e.g. `key1=value1;key2=value2`.
+    pub filter_data: Option<String>,
+}
+
+/// An event for a provider created with `event_pipe_create_provider`.
+#[derive(Debug, Clone)]
+pub struct EventPipeEventDefinition {
+    pub name: String,
+    pub event_id: u32,
+    pub keywords: u64,
+    pub event_version: u32,
+    pub level: COR_PRF_EVENTPIPE_LEVEL,
+    pub opcode: u8,
+    pub need_stack: bool,
+    pub params: Vec<EventPipeParam>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EventPipeParam {
+    pub param_type: COR_PRF_EVENTPIPE_PARAM_TYPE,
+    /// The type of the elements when `param_type` is `COR_PRF_EVENTPIPE_ARRAY`.
+    pub element_type: Option<COR_PRF_EVENTPIPE_PARAM_TYPE>,
+    pub name: String,
+}
+
 #[derive(Debug)]
 pub struct MethodProps {
-    pub class_token: mdTypeDef,
+    pub class_token: TypeDefToken,
     pub name: String,
     pub attr_flags: CorMethodAttr,
     pub sig: PCCOR_SIGNATURE,
@@ -148,5 +203,84 @@ pub struct MethodProps {
 pub struct TypeDefProps {
     pub name: String,
     pub type_def_flags: CorTypeAttr,
-    pub parent_token: mdTypeDef
+    pub parent_token: Token,
+}
+
+
+#[derive(Debug)]
+pub struct TypeRefProps {
+    pub resolution_scope: Token,
+    pub name: String,
+}
+
+#[derive(Debug)]
+pub struct MemberRefProps {
+    pub parent_token: Token,
+    pub name: String,
+    pub sig: PCCOR_SIGNATURE,
+    pub sig_length: u32,
+}
+
+#[derive(Debug)]
+pub struct FieldProps {
+    pub class_token: TypeDefToken,
+    pub name: String,
+    pub attr_flags: CorFieldAttr,
+    pub sig: PCCOR_SIGNATURE,
+    pub sig_length: u32,
+}
+
+#[derive(Debug)]
+pub struct CustomAttributeProps {
+    pub owner_token: Token,
+    /// MethodDef or MemberRef of the attribute constructor.
+    pub constructor_token: Token,
+    pub blob: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct CustomAttribute {
+    pub token: CustomAttributeToken,
+    /// Namespace qualified name of the attribute type, e.g. `System.ObsoleteAttribute`.
+    pub type_name: String,
+    pub constructor_token: Token,
+    pub args: CustomAttributeArgs,
+}
+
+#[derive(Debug)]
+pub struct TypeSpecProps {
+    pub sig: PCCOR_SIGNATURE,
+    pub sig_length: u32,
+}
+
+#[derive(Debug)]
+pub struct GenericParamProps {
+    /// Position of the parameter in the owner's generic parameter list.
+    pub sequence: u32,
+    pub flags: CorGenericParamAttr,
+    /// TypeDef or MethodDef declaring the parameter.
+    pub owner_token: Token,
+    pub name: String,
+}
+
+#[derive(Debug)]
+pub struct MethodSpecProps {
+    /// MethodDef or MemberRef of the instantiated generic method.
+    pub parent_token: Token,
+    pub sig: PCCOR_SIGNATURE,
+    pub sig_length: u32,
+}
+
+/// An event delivered to an EventPipe session the profiler started.
+#[derive(Clone, Copy, Debug)]
+pub struct EventPipeEvent<'a> {
+    pub provider: EVENTPIPE_PROVIDER,
+    pub event_id: u32,
+    pub event_version: u32,
+    pub metadata_blob: &'a [u8],
+    pub event_data: &'a [u8],
+    pub activity_id: Option<&'a GUID>,
+    pub related_activity_id: Option<&'a GUID>,
+    pub event_thread: ThreadID,
+    pub stack_frames: &'a [UINT_PTR],
 }
@@ -0,0 +1,466 @@
+use clr_profiler::{
+    cil::{
+        br_s, ldc_i4_1, nop, ret, Change, DiffRow, Disassembler, Method, PatchPreview,
+        ValidationIssue,
+    },
+    ffi::HResult,
+    metadata::{FieldDefToken, MethodDefToken, StringToken, Token, TypeDefToken, TypeRefToken},
+    pe::{AssemblyFile, Error},
+    MetadataImportTrait,
+};
+
+const SECTION_RVA: u32 = 0x2000;
+const SECTION_OFFSET: usize = 0x200;
+
+/// Appends length prefixed entries to a `#Blob` or `#US` heap.
+fn heap_entry(heap: &mut Vec<u8>, bytes: &[u8]) -> u16 {
+    let index = heap.len() as u16;
+    heap.push(bytes.len() as u8);
+    heap.extend_from_slice(bytes);
+    index
+}
+
+fn pad4(bytes: &mut Vec<u8>) {
+    while bytes.len() % 4 != 0 {
+        bytes.push(0);
+    }
+}
+
+fn u16s(values: &[u16]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect()
+}
+
+/// Builds the metadata of:
+///
+/// ```csharp
+/// namespace MyApp {
+///     [System.Obsolete("old")]
+///     public class Program {
+///         static int counter;
+///         public static void Main() { string s = "https://example.com/api"; }
+///         public void Run(string arg) { try { } catch { } }
+///         public abstract class Inner<T> { public abstract T Get(); }
+///     }
+/// }
+/// ```
+fn metadata(main_rva: u32, run_rva: u32) -> Vec<u8> {
+    let mut strings = vec![0u8];
+    let mut string = |value: &str| {
+        let index = strings.len() as u16;
+        strings.extend_from_slice(value.as_bytes());
+        strings.push(0);
+        index
+    };
+    let (test_dll, object, system, obsolete, module, program, my_app, inner, counter) = (
+        string("Test.dll"),
+        string("Object"),
+        string("System"),
+        string("ObsoleteAttribute"),
+        string("<Module>"),
+        string("Program"),
+        string("MyApp"),
+        string("Inner`1"),
+        string("counter"),
+    );
+    let (main, run, get, ctor, t) = (
+        string("Main"),
+        string("Run"),
+        string("Get"),
+        string(".ctor"),
+        string("T"),
+    );
+
+    let mut user_strings = vec![0u8];
+    let mut url = "https://example.com/api"
+        .encode_utf16()
+        .flat_map(u16::to_le_bytes)
+        .collect::<Vec<_>>();
+    url.push(0);
+    heap_entry(&mut user_strings, &url);
+
+    let mut blobs = vec![0u8];
+    let int_field = heap_entry(&mut blobs, &[0x06, 0x08]);
+    let static_void = heap_entry(&mut blobs, &[0x00, 0x00, 0x01]);
+    let instance_void_string = heap_entry(&mut blobs, &[0x20, 0x01, 0x01, 0x0e]);
+    let instance_t = heap_entry(&mut blobs, &[0x20, 0x00, 0x13, 0x00]);
+    let attribute_value = heap_entry(
+        &mut blobs,
+        &[0x01, 0x00, 0x03, b'o', b'l', b'd', 0x00, 0x00],
+    );
+
+    let guids = vec![0x11u8; 16];
+
+    // all heaps and tables are small, so every index is two bytes
+    let mut tables = Vec::new();
+    tables.extend_from_slice(&[0, 0, 0, 0, 2, 0, 0, 1]);
+    let present: &[(u32, u32)] = &[
+        (0x00, 1),
+        (0x01, 2),
+        (0x02, 3),
+        (0x04, 1),
+        (0x06, 3),
+        (0x0a, 1),
+        (0x0c, 1),
+        (0x29, 1),
+        (0x2a, 1),
+    ];
+    let valid = present
+        .iter()
+        .fold(0u64, |valid, (table, _)| valid | 1 << table);
+    tables.extend_from_slice(&valid.to_le_bytes());
+    tables.extend_from_slice(&0u64.to_le_bytes());
+    for (_, rows) in present {
+        tables.extend_from_slice(&rows.to_le_bytes());
+    }
+    // Module
+    tables.extend(u16s(&[0, test_dll, 1, 0, 0]));
+    // TypeRef: System.Object, System.ObsoleteAttribute, scoped to the module
+    tables.extend(u16s(&[4, object, system, 4, obsolete, system]));
+    // TypeDef: <Module>, MyApp.Program : Object, Inner`1 : Object
+    tables.extend(0u32.to_le_bytes());
+    tables.extend(u16s(&[module, 0, 0, 1, 1]));
+    tables.extend(0x0010_0001u32.to_le_bytes());
+    tables.extend(u16s(&[program, my_app, 5, 1, 1]));
+    tables.extend(0x0000_0082u32.to_le_bytes());
+    tables.extend(u16s(&[inner, 0, 5, 2, 3]));
+    // Field
+    tables.extend(u16s(&[0x0011, counter, int_field]));
+    // MethodDef
+    tables.extend(main_rva.to_le_bytes());
+    tables.extend(u16s(&[0, 0x0096, main, static_void, 1]));
+    tables.extend(run_rva.to_le_bytes());
+    tables.extend(u16s(&[0, 0x0086, run, instance_void_string, 1]));
+    tables.extend(0u32.to_le_bytes());
+    tables.extend(u16s(&[0, 0x04c6, get, instance_t, 1]));
+    // MemberRef: ObsoleteAttribute::.ctor(string)
+    tables.extend(u16s(&[2 << 3 | 1, ctor, instance_void_string]));
+    // CustomAttribute on Program
+    tables.extend(u16s(&[2 << 5 | 3, 1 << 3 | 3, attribute_value]));
+    // NestedClass
+    tables.extend(u16s(&[3, 2]));
+    // GenericParam T of Inner`1
+    tables.extend(u16s(&[0, 0, 3 << 1, t]));
+
+    let mut streams = vec![
+        ("#~", tables),
+        ("#Strings", strings),
+        ("#US", user_strings),
+        ("#GUID", guids),
+        ("#Blob", blobs),
+    ];
+    let mut root = Vec::new();
+    root.extend_from_slice(&0x424A_5342u32.to_le_bytes());
+    root.extend(u16s(&[1, 1]));
+    root.extend_from_slice(&0u32.to_le_bytes());
+    root.extend_from_slice(&12u32.to_le_bytes());
+    root.extend_from_slice(b"v4.0.30319\0\0");
+    root.extend(u16s(&[0, streams.len() as u16]));
+    let headers_size = streams
+        .iter()
+        .map(|(name, _)| 8 + (name.len() + 4) / 4 * 4)
+        .sum::<usize>();
+    let mut offset = root.len() + headers_size;
+    for (name, stream) in streams.iter_mut() {
+        pad4(stream);
+        root.extend_from_slice(&(offset as u32).to_le_bytes());
+        root.extend_from_slice(&(stream.len() as u32).to_le_bytes());
+        root.extend_from_slice(name.as_bytes());
+        root.push(0);
+        pad4(&mut root);
+        offset += stream.len();
+    }
+    for (_, stream) in streams {
+        root.extend(stream);
+    }
+    root
+}
+
+fn assembly() -> Vec<u8> {
+    let mut section = vec![0u8; 72];
+    // Main: tiny header, ldstr "https://example.com/api"; pop; ret
+    let main_rva = SECTION_RVA + section.len() as u32;
+    section.extend_from_slice(&[0x1e, 0x72, 0x01, 0x00, 0x00, 0x70, 0x26, 0x2a]);
+    pad4(&mut section);
+    // Run: fat header with an exception handling section
+    let run_rva = SECTION_RVA + section.len() as u32;
+    section.extend(u16s(&[0x301b, 8]));
+    section.extend_from_slice(&4u32.to_le_bytes());
+    section.extend_from_slice(&0u32.to_le_bytes());
+    section.extend_from_slice(&[0x00, 0xde, 0x00, 0x2a]);
+    section.extend_from_slice(&[0x01, 16, 0, 0]);
+    section.extend_from_slice(&[0, 0, 0, 0, 1, 1, 0, 2, 0x01, 0x00, 0x00, 0x01]);
+    pad4(&mut section);
+
+    let metadata_rva = SECTION_RVA + section.len() as u32;
+    let metadata = metadata(main_rva, run_rva);
+    // CLI header
+    section[0..4].copy_from_slice(&72u32.to_le_bytes());
+    section[4..8].copy_from_slice(&u16s(&[2, 5]));
+    section[8..12].copy_from_slice(&metadata_rva.to_le_bytes());
+    section[12..16].copy_from_slice(&(metadata.len() as u32).to_le_bytes());
+    section[16..20].copy_from_slice(&1u32.to_le_bytes());
+    section[20..24].copy_from_slice(&0x0600_0001u32.to_le_bytes());
+    section.extend(metadata);
+
+    let mut image = vec![0u8; SECTION_OFFSET];
+    image[0..2].copy_from_slice(b"MZ");
+    image[0x3c..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+    image[0x80..0x84].copy_from_slice(b"PE\0\0");
+    // COFF header: i386, one section, PE32 optional header
+    image[0x84..0x88].copy_from_slice(&u16s(&[0x14c, 1]));
+    image[0x94..0x98].copy_from_slice(&u16s(&[224, 0x2102]));
+    image[0x98..0x9a].copy_from_slice(&0x10bu16.to_le_bytes());
+    image[0x98 + 92..0x98 + 96].copy_from_slice(&16u32.to_le_bytes());
+    let cli_directory = 0x98 + 96 + 14 * 8;
+    image[cli_directory..cli_directory + 4].copy_from_slice(&SECTION_RVA.to_le_bytes());
+    image[cli_directory + 4..cli_directory + 8].copy_from_slice(&72u32.to_le_bytes());
+    let section_header = 0x98 + 224;
+    image[section_header..section_header + 5].copy_from_slice(b".text");
+    let section_size = (section.len() as u32).to_le_bytes();
+    image[section_header + 8..section_header + 12].copy_from_slice(&section_size);
+    image[section_header + 12..section_header + 16].copy_from_slice(&SECTION_RVA.to_le_bytes());
+    image[section_header + 16..section_header + 20].copy_from_slice(&section_size);
+    image[section_header + 20..section_header + 24]
+        .copy_from_slice(&(SECTION_OFFSET as u32).to_le_bytes());
+    image.extend(section);
+    image
+}
+
+#[test]
+fn reads_types_methods_and_fields() {
+    let file = AssemblyFile::from_bytes(assembly()).unwrap();
+    assert_eq!(file.module_name().unwrap(), "Test.dll");
+    assert_eq!(file.mvid().unwrap(), [0x11; 16]);
+    assert_eq!(file.entry_point(), Some(MethodDefToken::from_rid(1)));
+
+    let program = TypeDefToken::from_rid(2);
+    let inner = TypeDefToken::from_rid(3);
+    assert_eq!(file.enum_type_defs().unwrap(), vec![program, inner]);
+    assert_eq!(
+        file.get_type_def_props(program).unwrap().name,
+        "MyApp.Program"
+    );
+    assert_eq!(
+        file.get_type_def_props(program).unwrap().parent_token,
+        Token::new(0x0100_0001)
+    );
+    assert_eq!(
+        file.enum_methods(program).unwrap(),
+        vec![MethodDefToken::from_rid(1), MethodDefToken::from_rid(2)]
+    );
+    assert_eq!(
+        file.enum_methods(inner).unwrap(),
+        vec![MethodDefToken::from_rid(3)]
+    );
+    assert_eq!(
+        file.enum_fields(program).unwrap(),
+        vec![FieldDefToken::from_rid(1)]
+    );
+    assert!(file.enum_fields(inner).unwrap().is_empty());
+    assert_eq!(file.get_nested_class_props(inner).unwrap(), Some(program));
+    assert_eq!(
+        file.find_type_def_by_name("Inner`1", Some(program))
+            .unwrap(),
+        inner
+    );
+    assert_eq!(
+        file.find_type_def_by_name("Inner`1", None),
+        Err(HResult::CLDB_E_RECORD_NOTFOUND)
+    );
+
+    let field = file.get_field_props(FieldDefToken::from_rid(1)).unwrap();
+    assert_eq!(field.name, "counter");
+    assert_eq!(field.class_token, program);
+}
+
+#[test]
+fn qualified_names_match_the_runtime_format() {
+    let file = AssemblyFile::from_bytes(assembly()).unwrap();
+    assert_eq!(
+        file.qualified_name(Token::new(0x0600_0002)).unwrap(),
+        "MyApp.Program::Run(string)"
+    );
+    assert_eq!(
+        file.qualified_name(Token::new(0x0600_0003)).unwrap(),
+        "MyApp.Program+Inner<T>::Get()"
+    );
+    assert_eq!(
+        file.qualified_name(Token::new(0x0a00_0001)).unwrap(),
+        "System.ObsoleteAttribute::.ctor(string)"
+    );
+}
+
+#[test]
+fn decodes_custom_attributes() {
+    let file = AssemblyFile::from_bytes(assembly()).unwrap();
+    let program = TypeDefToken::from_rid(2).token();
+    let attribute = file
+        .find_custom_attribute(program, "Obsolete")
+        .unwrap()
+        .unwrap();
+    assert_eq!(attribute.type_name, "System.ObsoleteAttribute");
+    assert_eq!(attribute.args.fixed_args[0].as_str(), Some("old"));
+    assert!(file
+        .get_custom_attribute_by_name(program, "System.ObsoleteAttribute")
+        .unwrap()
+        .is_some());
+}
+
+#[test]
+fn reads_method_bodies_and_user_strings() {
+    let file = AssemblyFile::from_bytes(assembly()).unwrap();
+
+    let main = file.method_body(MethodDefToken::from_rid(1)).unwrap();
+    let method = Method::new(main.as_ptr(), main.len() as u32).unwrap();
+    assert_eq!(
+        method.instructions[0].to_string(),
+        "ldstr String(0x70000001)"
+    );
+
+    let run = file.method_body(MethodDefToken::from_rid(2)).unwrap();
+    assert_eq!(run.len(), 32);
+    let method = Method::new(run.as_ptr(), run.len() as u32).unwrap();
+    assert_eq!(method.sections.len(), 1);
+
+    assert_eq!(
+        file.method_body(MethodDefToken::from_rid(3)),
+        Err(HResult::CLDB_E_RECORD_NOTFOUND)
+    );
+
+    let strings = file
+        .user_strings()
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        strings,
+        vec![(
+            StringToken::from_rid(1),
+            "https://example.com/api".to_string()
+        )]
+    );
+}
+
+#[test]
+fn rejects_files_that_are_not_assemblies() {
+    assert!(matches!(
+        AssemblyFile::from_bytes(vec![0; 64]),
+        Err(Error::InvalidDosHeader)
+    ));
+    let mut image = assembly();
+    // clear the CLI header data directory
+    image[0x98 + 96 + 14 * 8..0x98 + 96 + 15 * 8].fill(0);
+    assert!(matches!(
+        AssemblyFile::from_bytes(image),
+        Err(Error::NotAClrImage)
+    ));
+}
+
+#[test]
+fn rejects_malformed_images() {
+    let mut image = assembly();
+    // a section whose raw data would lie past 4 GiB, with the CLI header inside it
+    image[0x98 + 224 + 20..0x98 + 224 + 24].copy_from_slice(&0xffff_fff0u32.to_le_bytes());
+    let cli_directory = 0x98 + 96 + 14 * 8;
+    image[cli_directory..cli_directory + 4].copy_from_slice(&(SECTION_RVA + 0x20).to_le_bytes());
+    assert!(matches!(
+        AssemblyFile::from_bytes(image),
+        Err(Error::InvalidRva(_))
+    ));
+
+    let mut image = assembly();
+    // scope System.ObsoleteAttribute to itself
+    let type_refs = image
+        .windows(8)
+        .position(|window| window == u16s(&[4, 10, 17, 4]))
+        .unwrap();
+    image[type_refs + 6..type_refs + 8].copy_from_slice(&(2u16 << 2 | 3).to_le_bytes());
+    let file = AssemblyFile::from_bytes(image).unwrap();
+    assert_eq!(
+        file.with_resolved_type_ref(TypeRefToken::from_rid(2), |_, td| td),
+        Err(HResult::CLDB_E_FILE_CORRUPT)
+    );
+}
+
+#[test]
+fn disassembles_with_resolved_tokens() {
+    let file = AssemblyFile::from_bytes(assembly()).unwrap();
+    let disassembler = Disassembler::new(&file);
+
+    let main = file.method_body(MethodDefToken::from_rid(1)).unwrap();
+    let method = Method::new(main.as_ptr(), main.len() as u32).unwrap();
+    assert_eq!(disassembler.header(&method), "tiny header, code size 7");
+    assert_eq!(
+        disassembler.instructions(&method),
+        vec![
+            "IL_0000: ldstr \"https://example.com/api\"",
+            "IL_0005: pop",
+            "IL_0006: ret",
+        ]
+    );
+
+    let run = file.method_body(MethodDefToken::from_rid(2)).unwrap();
+    let method = Method::new(run.as_ptr(), run.len() as u32).unwrap();
+    assert_eq!(
+        disassembler.header(&method),
+        "fat header, code size 4, max stack 8, init locals"
+    );
+    assert_eq!(
+        disassembler.instructions(&method),
+        vec!["IL_0000: nop", "IL_0001: leave.s IL_0003", "IL_0003: ret"]
+    );
+    assert_eq!(
+        disassembler.exception_clauses(&method),
+        vec![".try IL_0000 to IL_0001 catch System.Object handler IL_0001 to IL_0003"]
+    );
+}
+
+#[test]
+fn previews_patches() {
+    let file = AssemblyFile::from_bytes(assembly()).unwrap();
+    let disassembler = Disassembler::new(&file);
+    let run = file.method_body(MethodDefToken::from_rid(2)).unwrap();
+    let old = Method::new(run.as_ptr(), run.len() as u32).unwrap();
+
+    let mut new = Method::new(run.as_ptr(), run.len() as u32).unwrap();
+    new.insert_prelude(vec![nop()]).unwrap();
+    let preview = PatchPreview::new(&disassembler, &old, &new);
+    let changes = |rows: &[DiffRow]| rows.iter().map(|row| row.change).collect::<Vec<_>>();
+    assert_eq!(
+        changes(&preview.instructions),
+        vec![
+            Change::Unchanged,
+            Change::Added,
+            Change::Moved,
+            Change::Moved
+        ]
+    );
+    assert_eq!(changes(&preview.exception_clauses), vec![Change::Moved]);
+    assert!(preview.is_valid());
+
+    let new = Method::from_instructions(vec![ldc_i4_1(), ret()]);
+    let preview = PatchPreview::new(&disassembler, &old, &new);
+    assert_eq!(preview.new_header, "tiny header, code size 2");
+    assert_eq!(
+        changes(&preview.instructions),
+        vec![Change::Changed, Change::Removed, Change::Moved]
+    );
+    assert_eq!(changes(&preview.exception_clauses), vec![Change::Removed]);
+    assert!(preview.to_string().ends_with("validation: ok"));
+
+    let new = Method::from_instructions(vec![br_s(4), ldc_i4_1()]);
+    let preview = PatchPreview::new(&disassembler, &old, &new);
+    assert_eq!(
+        preview.issues,
+        vec![
+            ValidationIssue::FallsThroughEnd,
+            ValidationIssue::BranchOutOfRange {
+                offset: 0,
+                target: 6
+            },
+        ]
+    );
+}
@@ -0,0 +1,103 @@
+use clr_profiler::{
+    ffi::{
+        CorProfilerCallback as Callback, HResult, ICorProfilerCallback10, ICorProfilerCallback11,
+        BOOL, E_POINTER, S_OK,
+    },
+    ClrProfiler, CorProfilerCallback, CorProfilerCallback10, CorProfilerCallback11,
+    CorProfilerCallback2, CorProfilerCallback3, CorProfilerCallback4, CorProfilerCallback5,
+    CorProfilerCallback6, CorProfilerCallback7, CorProfilerCallback8, CorProfilerCallback9,
+    EventPipeEvent,
+};
+use std::{ffi::c_void, ptr};
+use uuid::Uuid;
+
+#[derive(Clone)]
+struct NotifyingProfiler {
+    clsid: Uuid,
+}
+
+impl ClrProfiler for NotifyingProfiler {
+    fn new() -> Self {
+        NotifyingProfiler { clsid: Uuid::nil() }
+    }
+    fn clsid(&self) -> &Uuid {
+        &self.clsid
+    }
+}
+
+impl CorProfilerCallback for NotifyingProfiler {}
+impl CorProfilerCallback2 for NotifyingProfiler {}
+impl CorProfilerCallback3 for NotifyingProfiler {}
+impl CorProfilerCallback4 for NotifyingProfiler {}
+impl CorProfilerCallback5 for NotifyingProfiler {}
+impl CorProfilerCallback6 for NotifyingProfiler {}
+impl CorProfilerCallback7 for NotifyingProfiler {}
+impl CorProfilerCallback8 for NotifyingProfiler {}
+impl CorProfilerCallback9 for NotifyingProfiler {}
+impl CorProfilerCallback10 for NotifyingProfiler {
+    fn event_pipe_event_delivered(&self, event: EventPipeEvent) -> Result<(), HResult> {
+        // a failed assertion panics, which the callback reports as E_FAIL
+        assert!(event.metadata_blob.is_empty());
+        assert_eq!(event.event_data, [1, 2, 3]);
+        assert!(event.stack_frames.is_empty());
+        Ok(())
+    }
+}
+impl CorProfilerCallback11 for NotifyingProfiler {
+    fn load_as_notification_only(&self) -> Result<bool, HResult> {
+        Ok(true)
+    }
+}
+
+#[test]
+fn newer_callback_interfaces_are_served() {
+    let callback = Callback::new(NotifyingProfiler::new());
+    for iid in [ICorProfilerCallback10::IID, ICorProfilerCallback11::IID] {
+        let mut object: *mut c_void = ptr::null_mut();
+        unsafe {
+            assert_eq!(callback.query_interface(&iid, &mut object), S_OK);
+            assert!(!object.is_null());
+            callback.release();
+        }
+    }
+    unsafe { callback.release() };
+}
+
+#[test]
+fn profilers_can_load_as_notification_only() {
+    let callback = Callback::new(NotifyingProfiler::new());
+    let mut notification_only: BOOL = 0;
+    unsafe {
+        assert_eq!(
+            callback.LoadAsNotificationOnly(&mut notification_only),
+            S_OK
+        );
+        assert_eq!(notification_only, 1);
+        assert_eq!(callback.LoadAsNotificationOnly(ptr::null_mut()), E_POINTER);
+        callback.release();
+    }
+}
+
+#[test]
+fn event_pipe_events_tolerate_empty_arrays() {
+    let callback = Callback::new(NotifyingProfiler::new());
+    let data = [1u8, 2, 3];
+    unsafe {
+        let result = callback.EventPipeEventDelivered(
+            1,
+            7,
+            0,
+            0,
+            ptr::null(),
+            data.len() as u32,
+            data.as_ptr(),
+            ptr::null(),
+            ptr::null(),
+            0,
+            0,
+            ptr::null(),
+        );
+        assert_eq!(result, S_OK);
+    }
+    unsafe { callback.release() };
+}
@@ -0,0 +1,133 @@
+use clr_profiler::{
+    ffi::{
+        can_unload_now, ClassFactory, CorProfilerCallback as Callback, IClassFactory,
+        ICorProfilerCallback9, IUnknown, CLASS_E_NOAGGREGATION, E_NOINTERFACE, S_FALSE, S_OK,
+    },
+    ClrProfiler, CorProfilerCallback, CorProfilerCallback10, CorProfilerCallback11,
+    CorProfilerCallback2, CorProfilerCallback3, CorProfilerCallback4, CorProfilerCallback5,
+    CorProfilerCallback6, CorProfilerCallback7, CorProfilerCallback8, CorProfilerCallback9,
+};
+use std::{
+    ffi::c_void,
+    ptr,
+    sync::{Mutex, MutexGuard},
+};
+use uuid::Uuid;
+
+#[derive(Clone)]
+struct Profiler {
+    clsid: Uuid,
+}
+
+impl ClrProfiler for Profiler {
+    fn new() -> Self {
+        Profiler { clsid: Uuid::nil() }
+    }
+    fn clsid(&self) -> &Uuid {
+        &self.clsid
+    }
+}
+
+impl CorProfilerCallback for Profiler {}
+impl CorProfilerCallback2 for Profiler {}
+impl CorProfilerCallback3 for Profiler {}
+impl CorProfilerCallback4 for Profiler {}
+impl CorProfilerCallback5 for Profiler {}
+impl CorProfilerCallback6 for Profiler {}
+impl CorProfilerCallback7 for Profiler {}
+impl CorProfilerCallback8 for Profiler {}
+impl CorProfilerCallback9 for Profiler {}
+impl CorProfilerCallback10 for Profiler {}
+impl CorProfilerCallback11 for Profiler {}
+
+// the objects and locks that keep the DLL loaded are counted process wide
+static SERIAL: Mutex<()> = Mutex::new(());
+
+fn serial() -> MutexGuard<'static, ()> {
+    SERIAL
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[test]
+fn create_instance_returns_the_interface_asked_for() {
+    let _serial = serial();
+    let factory = ClassFactory::new(Profiler::new());
+    let mut object: *mut c_void = ptr::null_mut();
+    unsafe {
+        let vtbl = &*factory.lpVtbl;
+        let hr = (vtbl.IClassFactory.CreateInstance)(
+            factory,
+            ptr::null_mut(),
+            &ICorProfilerCallback9::IID,
+            &mut object,
+        );
+        assert_eq!(hr, S_OK);
+        let callback = &*(object as *const Callback<Profiler>);
+        let callback_vtbl = &*callback.lpVtbl;
+        // the caller holds the only reference
+        assert_eq!((callback_vtbl.IUnknown.AddRef)(callback), 2);
+        assert_eq!((callback_vtbl.IUnknown.Release)(callback), 1);
+        assert_eq!((callback_vtbl.IUnknown.Release)(callback), 0);
+        assert_eq!((vtbl.IUnknown.Release)(factory), 0);
+    }
+    assert_eq!(can_unload_now(), S_OK);
+}
+
+#[test]
+fn create_instance_refuses_other_interfaces_and_aggregation() {
+    let _serial = serial();
+    let factory = ClassFactory::new(Profiler::new());
+    // never called, aggregation is refused up front
+    let outer = ptr::NonNull::<IUnknown<()>>::dangling().as_ptr();
+    let mut object: *mut c_void = ptr::null_mut();
+    unsafe {
+        let vtbl = &*factory.lpVtbl;
+        let hr = (vtbl.IClassFactory.CreateInstance)(
+            factory,
+            ptr::null_mut(),
+            &IClassFactory::IID,
+            &mut object,
+        );
+        assert_eq!(hr, E_NOINTERFACE);
+        assert!(object.is_null());
+        let hr = (vtbl.IClassFactory.CreateInstance)(
+            factory,
+            outer,
+            &ICorProfilerCallback9::IID,
+            &mut object,
+        );
+        assert_eq!(hr, CLASS_E_NOAGGREGATION);
+        assert!(object.is_null());
+        assert_eq!((vtbl.IUnknown.Release)(factory), 0);
+    }
+    // the instance nobody asked for is gone too
+    assert_eq!(can_unload_now(), S_OK);
+}
+
+#[test]
+fn locked_servers_and_live_objects_stay_loaded() {
+    let _serial = serial();
+    let factory = ClassFactory::new(Profiler::new());
+    unsafe {
+        let vtbl = &*factory.lpVtbl;
+        assert_eq!(can_unload_now(), S_FALSE);
+        assert_eq!((vtbl.IClassFactory.LockServer)(factory, 1), S_OK);
+        assert_eq!((vtbl.IUnknown.Release)(factory), 0);
+        assert_eq!(can_unload_now(), S_FALSE);
+        assert_eq!((vtbl.IClassFactory.LockServer)(factory, 0), S_OK);
+    }
+    assert_eq!(can_unload_now(), S_OK);
+}
+
+#[test]
+fn callbacks_are_freed_with_their_last_reference() {
+    let _serial = serial();
+    let callback = Callback::new(Profiler::new());
+    unsafe {
+        assert_eq!(callback.add_ref(), 2);
+        assert_eq!(callback.release(), 1);
+        assert_eq!(callback.release(), 0);
+    }
+    assert_eq!(can_unload_now(), S_OK);
+}
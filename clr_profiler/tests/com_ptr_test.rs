@@ -0,0 +1,79 @@
+use clr_profiler::ffi::{
+    ComPtr, IUnknown, Unknown, UnknownVtbl, E_NOINTERFACE, HRESULT, REFIID, ULONG,
+};
+use std::{
+    ffi::c_void,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+/// An object that only counts its references.
+#[repr(C)]
+struct Counted {
+    unknown: Unknown,
+    refs: AtomicU32,
+}
+
+unsafe extern "system" fn query_interface(
+    _this: &Unknown,
+    _riid: REFIID,
+    _ppv_object: *mut *mut c_void,
+) -> HRESULT {
+    E_NOINTERFACE
+}
+
+unsafe extern "system" fn add_ref(this: &Unknown) -> ULONG {
+    let counted = &*(this as *const Unknown as *const Counted);
+    counted.refs.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+unsafe extern "system" fn release(this: &Unknown) -> ULONG {
+    let counted = &*(this as *const Unknown as *const Counted);
+    counted.refs.fetch_sub(1, Ordering::Relaxed) - 1
+}
+
+static VTBL: UnknownVtbl = UnknownVtbl {
+    IUnknown: IUnknown {
+        QueryInterface: query_interface,
+        AddRef: add_ref,
+        Release: release,
+    },
+};
+
+fn counted(refs: u32) -> Box<Counted> {
+    Box::new(Counted {
+        unknown: Unknown { lpVtbl: &VTBL },
+        refs: AtomicU32::new(refs),
+    })
+}
+
+#[test]
+fn clones_add_references_and_drops_release_them() {
+    // the reference handed out by the "runtime"
+    let object = counted(1);
+    let ptr = &object.unknown as *const Unknown as *mut Unknown;
+
+    let com_ptr = unsafe { ComPtr::from_raw(ptr) }.unwrap();
+    assert_eq!(object.refs.load(Ordering::Relaxed), 1);
+    let clone = com_ptr.clone();
+    assert_eq!(object.refs.load(Ordering::Relaxed), 2);
+    assert_eq!(clone.as_ptr(), ptr);
+    drop(com_ptr);
+    assert_eq!(object.refs.load(Ordering::Relaxed), 1);
+    drop(clone);
+    assert_eq!(object.refs.load(Ordering::Relaxed), 0);
+}
+
+#[test]
+fn borrowed_pointers_take_their_own_reference() {
+    let object = counted(1);
+    let ptr = &object.unknown as *const Unknown as *mut Unknown;
+
+    let com_ptr = unsafe { ComPtr::from_borrowed(ptr) }.unwrap();
+    assert_eq!(object.refs.load(Ordering::Relaxed), 2);
+    let raw = com_ptr.into_raw();
+    assert_eq!(object.refs.load(Ordering::Relaxed), 2);
+    drop(unsafe { ComPtr::from_raw(raw) });
+    assert_eq!(object.refs.load(Ordering::Relaxed), 1);
+
+    assert!(unsafe { ComPtr::<Unknown>::from_raw(std::ptr::null_mut()) }.is_none());
+}
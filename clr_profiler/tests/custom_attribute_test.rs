@@ -0,0 +1,131 @@
+use clr_profiler::metadata::{
+    CustomAttributeArgType, CustomAttributeArgs, CustomAttributeNamedArgKind,
+    CustomAttributeValue, Error, Token, TokenKind,
+};
+
+fn ser_string(value: &str) -> Vec<u8> {
+    let mut bytes = vec![value.len() as u8];
+    bytes.extend_from_slice(value.as_bytes());
+    bytes
+}
+
+fn no_enums(_: &str) -> CustomAttributeArgType {
+    CustomAttributeArgType::I4
+}
+
+#[test]
+fn decodes_fixed_string_and_bool_args() {
+    // instance void .ctor(string, bool)
+    let ctor_sig = [0x20, 0x02, 0x01, 0x0e, 0x02];
+    let fixed_arg_types =
+        CustomAttributeArgType::from_constructor_signature(&ctor_sig, &|_| unreachable!())
+            .unwrap();
+    assert_eq!(
+        fixed_arg_types,
+        vec![CustomAttributeArgType::String, CustomAttributeArgType::Boolean]
+    );
+
+    let mut blob = vec![0x01, 0x00];
+    blob.extend(ser_string("use Trace2"));
+    blob.extend([0x01, 0x00, 0x00]);
+    let args = CustomAttributeArgs::from_bytes(&blob, &fixed_arg_types, &no_enums).unwrap();
+
+    assert_eq!(args.fixed_args[0].as_str(), Some("use Trace2"));
+    assert_eq!(args.fixed_args[1].as_bool(), Some(true));
+    assert!(args.named_args.is_empty());
+}
+
+#[test]
+fn decodes_enum_ctor_arg_and_named_property() {
+    // instance void .ctor(valuetype System.AttributeTargets), TypeRef row 5
+    let ctor_sig = [0x20, 0x01, 0x01, 0x11, (5 << 2) | 1];
+    let fixed_arg_types = CustomAttributeArgType::from_constructor_signature(&ctor_sig, &|token| {
+        assert_eq!(token, Token::from_parts(TokenKind::TypeRef, 5));
+        Ok(CustomAttributeArgType::Enum(
+            "System.AttributeTargets".into(),
+            Box::new(CustomAttributeArgType::I4),
+        ))
+    })
+    .unwrap();
+
+    let mut blob = vec![0x01, 0x00, 0x40, 0x00, 0x00, 0x00, 0x01, 0x00, 0x54, 0x02];
+    blob.extend(ser_string("AllowMultiple"));
+    blob.push(0x01);
+    let args = CustomAttributeArgs::from_bytes(&blob, &fixed_arg_types, &no_enums).unwrap();
+
+    assert_eq!(args.fixed_args[0].as_i64(), Some(0x40));
+    assert_eq!(format!("{}", args.fixed_args[0]), "(System.AttributeTargets)64");
+    assert_eq!(args.named_args[0].kind, CustomAttributeNamedArgKind::Property);
+    assert_eq!(args.named_arg("AllowMultiple"), Some(&CustomAttributeValue::Boolean(true)));
+}
+
+#[test]
+fn decodes_arrays_boxed_values_and_named_enums() {
+    // instance void .ctor(string[], object)
+    let ctor_sig = [0x20, 0x02, 0x01, 0x1d, 0x0e, 0x1c];
+    let fixed_arg_types =
+        CustomAttributeArgType::from_constructor_signature(&ctor_sig, &|_| unreachable!())
+            .unwrap();
+
+    let mut blob = vec![0x01, 0x00, 0x02, 0x00, 0x00, 0x00];
+    blob.extend(ser_string("a"));
+    blob.push(0xFF); // null string
+    blob.extend([0x0a, 0x2a, 0, 0, 0, 0, 0, 0, 0]); // boxed long 42
+    blob.extend([0x01, 0x00, 0x53, 0x55]); // one named field of enum type
+    blob.extend(ser_string("Level"));
+    blob.extend(ser_string("Severity"));
+    blob.push(0x03);
+    let args = CustomAttributeArgs::from_bytes(&blob, &fixed_arg_types, &|name| {
+        assert_eq!(name, "Level");
+        CustomAttributeArgType::U1
+    })
+    .unwrap();
+
+    assert_eq!(
+        args.fixed_args[0],
+        CustomAttributeValue::Array(Some(vec![
+            CustomAttributeValue::String(Some("a".into())),
+            CustomAttributeValue::String(None),
+        ]))
+    );
+    assert_eq!(args.fixed_args[1], CustomAttributeValue::I8(42));
+    assert_eq!(args.named_args[0].kind, CustomAttributeNamedArgKind::Field);
+    assert_eq!(args.named_arg("Severity").and_then(|v| v.as_i64()), Some(3));
+}
+
+#[test]
+fn rejects_invalid_blobs() {
+    let fixed_arg_types = [CustomAttributeArgType::I4];
+    assert!(matches!(
+        CustomAttributeArgs::from_bytes(&[0x02, 0x00], &fixed_arg_types, &no_enums),
+        Err(Error::InvalidCustomAttributeProlog)
+    ));
+    assert!(matches!(
+        CustomAttributeArgs::from_bytes(&[0x01, 0x00, 0x01], &fixed_arg_types, &no_enums),
+        Err(Error::UnexpectedEndOfBlob)
+    ));
+    assert_eq!(
+        CustomAttributeArgs::from_bytes(&[], &[], &no_enums).unwrap(),
+        CustomAttributeArgs::default()
+    );
+}
+
+#[test]
+fn rejects_deeply_nested_types() {
+    // void (object[][]...[])
+    let mut ctor_sig = vec![0x20, 0x01, 0x01];
+    ctor_sig.extend([0x1d; 100_000]);
+    ctor_sig.push(0x1c);
+    assert!(matches!(
+        CustomAttributeArgType::from_constructor_signature(&ctor_sig, &|_| unreachable!()),
+        Err(Error::TooDeeplyNested)
+    ));
+
+    // an object boxing an array of arrays of ... arrays
+    let mut blob = vec![0x01, 0x00];
+    blob.extend([0x1d; 100_000]);
+    assert!(matches!(
+        CustomAttributeArgs::from_bytes(&blob, &[CustomAttributeArgType::Object], &no_enums),
+        Err(Error::TooDeeplyNested)
+    ));
+}
@@ -0,0 +1,252 @@
+use clr_profiler::{
+    ffi::{CorTypeAttr, HResult, ModuleID, COR_PRF_MONITOR},
+    metadata::MethodDefToken,
+    testing::{FakeRuntime, ProfilerDriver},
+    ClrProfiler, CorProfilerCallback, CorProfilerInfo, EventHandler, EventMask2, EventStream,
+    IlRewriter, OverflowPolicy, ProfilerEvent,
+};
+use std::{
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread::{self, ThreadId},
+};
+use uuid::Uuid;
+
+// void ()
+const SIG: &[u8] = &[0x00, 0x00, 0x01];
+// tiny header, ret
+const RETURN: &[u8] = &[0x06, 0x2A];
+// tiny header, nop, ret
+const NOP_RETURN: &[u8] = &[0x0A, 0x00, 0x2A];
+
+/// Sends what it handles back to the test. `BLOCK` picks the overflow policy.
+struct Collector<const BLOCK: bool> {
+    clsid: Uuid,
+    seen: Sender<(String, ThreadId)>,
+    /// Waited on before handling the first module load.
+    gate: Option<Receiver<()>>,
+    rewriter: Arc<NopInserter>,
+}
+
+impl<const BLOCK: bool> Collector<BLOCK> {
+    fn start(gate: Option<Receiver<()>>) -> (Self, Receiver<(String, ThreadId)>) {
+        let (seen, received) = mpsc::channel();
+        let collector = Collector {
+            clsid: Uuid::nil(),
+            seen,
+            gate,
+            rewriter: Arc::default(),
+        };
+        (collector, received)
+    }
+}
+
+/// Puts a nop in front of every method, remembering the threads it ran on.
+#[derive(Default)]
+struct NopInserter {
+    threads: Mutex<Vec<ThreadId>>,
+}
+
+impl IlRewriter for NopInserter {
+    fn rewrite_il(
+        &self,
+        _module_id: ModuleID,
+        _method_id: MethodDefToken,
+        il_body: &[u8],
+    ) -> Result<Option<Vec<u8>>, HResult> {
+        self.threads.lock().unwrap().push(thread::current().id());
+        // the original body is a tiny header and a single instruction
+        assert_eq!(il_body, RETURN);
+        Ok(Some(NOP_RETURN.to_vec()))
+    }
+}
+
+impl<const BLOCK: bool> ClrProfiler for Collector<BLOCK> {
+    fn new() -> Self {
+        unimplemented!()
+    }
+    fn clsid(&self) -> &Uuid {
+        &self.clsid
+    }
+}
+
+impl<const BLOCK: bool> EventHandler for Collector<BLOCK> {
+    const CAPACITY: usize = 2;
+    const OVERFLOW_POLICY: OverflowPolicy = if BLOCK {
+        OverflowPolicy::Block
+    } else {
+        OverflowPolicy::Count
+    };
+
+    fn handle(&mut self, event: ProfilerEvent) -> Result<(), HResult> {
+        let seen = match event {
+            ProfilerEvent::Initialize { profiler_info } => {
+                profiler_info.set_event_mask(
+                    COR_PRF_MONITOR::COR_PRF_MONITOR_MODULE_LOADS
+                        | COR_PRF_MONITOR::COR_PRF_MONITOR_JIT_COMPILATION,
+                )?;
+                "initialize".to_string()
+            }
+            ProfilerEvent::ModuleLoadStarted { module_id } => {
+                if let Some(gate) = self.gate.take() {
+                    gate.recv().unwrap();
+                }
+                format!("module {}", module_id)
+            }
+            ProfilerEvent::JITCompilationStarted { .. } => "jit".to_string(),
+            ProfilerEvent::Dropped { count } => format!("dropped {}", count),
+            ProfilerEvent::Shutdown => "shutdown".to_string(),
+            _ => "other".to_string(),
+        };
+        self.seen.send((seen, thread::current().id())).unwrap();
+        Ok(())
+    }
+
+    fn il_rewriter(&self) -> Option<Arc<dyn IlRewriter>> {
+        Some(self.rewriter.clone())
+    }
+}
+
+fn add_modules(runtime: &FakeRuntime, count: usize) -> Vec<ModuleID> {
+    (0..count)
+        .map(|index| runtime.add_module(&format!("Module{}.dll", index)))
+        .collect()
+}
+
+#[test]
+fn events_are_handled_in_order_on_the_worker() {
+    let runtime = FakeRuntime::new();
+    let module = runtime.add_module("Example.dll");
+    let class = runtime.add_type(module, "Example.Program", CorTypeAttr::tdPublic);
+    let function = runtime.add_method(module, class, "Main", SIG, RETURN);
+    let (collector, seen) = Collector::<true>::start(None);
+    let rewriter = collector.rewriter.clone();
+    let driver = ProfilerDriver::new(
+        EventStream::with_handler(collector),
+        EventMask2::NONE,
+        &runtime,
+    );
+
+    driver.initialize().unwrap();
+    driver.load_module(module).unwrap();
+    driver.jit(function).unwrap();
+    // the rewrite is done on the compiling thread, before the callback returns
+    assert!(runtime.is_il_rewritten(function));
+    assert_eq!(*rewriter.threads.lock().unwrap(), [thread::current().id()]);
+    driver.shutdown().unwrap();
+
+    let seen = seen.try_iter().collect::<Vec<_>>();
+    let names = seen
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .filter(|&name| name != "other")
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![
+            "initialize",
+            &format!("module {}", module),
+            "jit",
+            "shutdown"
+        ]
+    );
+    assert!(seen.iter().all(|&(_, id)| id != thread::current().id()));
+    assert_eq!(runtime.il_function_body(function), NOP_RETURN);
+}
+
+#[test]
+fn events_that_dont_fit_are_counted() {
+    let runtime = FakeRuntime::new();
+    let modules = add_modules(&runtime, 10);
+    let (open, gate) = mpsc::channel();
+    let (collector, seen) = Collector::<false>::start(Some(gate));
+    let driver = ProfilerDriver::new(
+        EventStream::with_handler(collector),
+        EventMask2::NONE,
+        &runtime,
+    );
+
+    driver.initialize().unwrap();
+    for &module in &modules {
+        driver.load_module(module).unwrap();
+    }
+    open.send(()).unwrap();
+    driver.shutdown().unwrap();
+
+    let seen = seen.try_iter().map(|(name, _)| name).collect::<Vec<_>>();
+    let handled = seen
+        .iter()
+        .filter(|name| name.starts_with("module") || *name == "other")
+        .count();
+    let dropped: usize = seen
+        .iter()
+        .filter_map(|name| name.strip_prefix("dropped "))
+        .map(|count| count.parse::<usize>().unwrap())
+        .sum();
+    // loading a module makes three callbacks
+    assert!(dropped > 0);
+    assert_eq!(handled + dropped, 3 * modules.len());
+    // the last loads were dropped while the worker waited, and are reported
+    // where they happened: before the next event that fit
+    assert!(seen[seen.len() - 2].starts_with("dropped "));
+    assert_eq!(seen.last().unwrap(), "shutdown");
+}
+
+#[test]
+fn blocking_streams_keep_every_event() {
+    let runtime = FakeRuntime::new();
+    let modules = add_modules(&runtime, 10);
+    let (collector, seen) = Collector::<true>::start(None);
+    let driver = ProfilerDriver::new(
+        EventStream::with_handler(collector),
+        EventMask2::NONE,
+        &runtime,
+    );
+
+    driver.initialize().unwrap();
+    for &module in &modules {
+        driver.load_module(module).unwrap();
+    }
+    driver.shutdown().unwrap();
+
+    let seen = seen.try_iter().map(|(name, _)| name).collect::<Vec<_>>();
+    let loads = seen.iter().filter(|name| name.starts_with("module"));
+    let expected = modules.iter().map(|module| format!("module {}", module));
+    assert!(loads.cloned().eq(expected));
+    assert_eq!(seen.len(), 3 * modules.len() + 2);
+}
+
+#[test]
+fn callbacks_from_many_threads_share_the_queue() {
+    let (collector, seen) = Collector::<true>::start(None);
+    let stream = EventStream::with_handler(collector);
+    let threads = (0..4)
+        .map(|thread| {
+            let stream = stream.clone();
+            thread::spawn(move || {
+                for index in 0..1000 {
+                    stream.module_load_started(thread * 1000 + index).unwrap();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    // dropping the last clone waits for the worker to finish
+    drop(stream);
+
+    let mut modules = seen
+        .try_iter()
+        .map(|(name, _)| name["module ".len()..].parse::<usize>().unwrap())
+        .collect::<Vec<_>>();
+    // each thread's events stay in order
+    for thread in 0..4 {
+        let own = modules.iter().filter(|&&module| module / 1000 == thread);
+        assert!(own.cloned().eq(thread * 1000..(thread + 1) * 1000));
+    }
+    modules.sort_unstable();
+    assert!(modules.into_iter().eq(0..4000));
+}
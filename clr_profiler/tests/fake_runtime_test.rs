@@ -0,0 +1,165 @@
+use clr_profiler::{
+    cil::{nop, Method},
+    ffi::{CorOpenFlags, CorTypeAttr, FunctionID, HResult, ModuleID, COR_PRF_MONITOR},
+    testing::{FakeRuntime, ProfilerDriver},
+    ClrProfiler, CorProfilerCallback, CorProfilerCallback10, CorProfilerCallback11,
+    CorProfilerCallback2, CorProfilerCallback3, CorProfilerCallback4, CorProfilerCallback5,
+    CorProfilerCallback6, CorProfilerCallback7, CorProfilerCallback8, CorProfilerCallback9,
+    CorProfilerInfo, EventMask2, MetadataImportTrait, ProfilerInfo,
+};
+use std::sync::{Arc, Mutex, OnceLock};
+use uuid::Uuid;
+
+// void (int32)
+const SIG: &[u8] = &[0x00, 0x01, 0x01, 0x08];
+// tiny header, ret
+const RETURN: &[u8] = &[0x06, 0x2A];
+
+/// Records what it sees and pads the body of every method it compiles.
+#[derive(Clone, Default)]
+struct RecordingProfiler {
+    profiler_info: Arc<OnceLock<ProfilerInfo>>,
+    seen: Arc<Mutex<Vec<String>>>,
+}
+
+impl RecordingProfiler {
+    fn record(&self, seen: String) {
+        self.seen.lock().unwrap().push(seen);
+    }
+}
+
+impl ClrProfiler for RecordingProfiler {
+    fn new() -> Self {
+        Self::default()
+    }
+    fn clsid(&self) -> &Uuid {
+        unimplemented!()
+    }
+}
+
+impl CorProfilerCallback for RecordingProfiler {
+    fn initialize(&self, profiler_info: ProfilerInfo) -> Result<(), HResult> {
+        self.record(format!("initialize {}", profiler_info.level()));
+        let _ = self.profiler_info.set(profiler_info);
+        Ok(())
+    }
+
+    fn module_load_finished(
+        &self,
+        module_id: ModuleID,
+        _hr_status: HResult,
+    ) -> Result<(), HResult> {
+        let module = self
+            .profiler_info
+            .get()
+            .unwrap()
+            .get_module_info(module_id)?;
+        self.record(format!("module {}", module.file_name));
+        Ok(())
+    }
+
+    fn jit_compilation_started(
+        &self,
+        function_id: FunctionID,
+        _is_safe_to_block: bool,
+    ) -> Result<(), HResult> {
+        let info = self.profiler_info.get().unwrap();
+        let function = info.get_function_info(function_id)?;
+        let metadata = info.get_module_metadata(function.module_id, CorOpenFlags::ofRead)?;
+        self.record(format!(
+            "jit {}",
+            metadata.qualified_name(function.token.token())?
+        ));
+
+        let body = info.get_il_function_body(function.module_id, function.token)?;
+        let method = Method::new(body.method_header, body.method_size).or(Err(HResult::E_FAIL))?;
+        let mut instructions = (0..70).map(|_| nop()).collect::<Vec<_>>();
+        instructions.extend(method.instructions);
+        let bytes = Method::from_instructions(instructions).into_bytes();
+        info.replace_il_function_body(function.module_id, function.token, &bytes)
+    }
+
+    fn shutdown(&self) -> Result<(), HResult> {
+        self.record("shutdown".to_string());
+        Ok(())
+    }
+}
+impl CorProfilerCallback2 for RecordingProfiler {}
+impl CorProfilerCallback3 for RecordingProfiler {}
+impl CorProfilerCallback4 for RecordingProfiler {}
+impl CorProfilerCallback5 for RecordingProfiler {}
+impl CorProfilerCallback6 for RecordingProfiler {}
+impl CorProfilerCallback7 for RecordingProfiler {}
+impl CorProfilerCallback8 for RecordingProfiler {}
+impl CorProfilerCallback9 for RecordingProfiler {}
+impl CorProfilerCallback10 for RecordingProfiler {}
+impl CorProfilerCallback11 for RecordingProfiler {}
+
+const EVENTS: EventMask2 = EventMask2 {
+    events_low: COR_PRF_MONITOR::COR_PRF_MONITOR_MODULE_LOADS
+        .union(COR_PRF_MONITOR::COR_PRF_MONITOR_JIT_COMPILATION),
+    events_high: EventMask2::NONE.events_high,
+};
+
+fn runtime() -> (FakeRuntime, ModuleID, FunctionID) {
+    let runtime = FakeRuntime::new();
+    let module = runtime.add_module("Example.dll");
+    let outer = runtime.add_type(module, "Example.Outer", CorTypeAttr::tdPublic);
+    let inner = runtime.add_nested_type(module, outer, "Inner", CorTypeAttr::tdNestedPublic);
+    let function = runtime.add_method(module, inner, "Run", SIG, RETURN);
+    (runtime, module, function)
+}
+
+#[test]
+fn profilers_see_the_runtime_through_their_info() {
+    let (runtime, module, function) = runtime();
+    let profiler = RecordingProfiler::default();
+    let driver = ProfilerDriver::new(profiler.clone(), EVENTS, &runtime);
+
+    driver.initialize().unwrap();
+    driver.load_module(module).unwrap();
+    driver.jit(function).unwrap();
+    driver.shutdown().unwrap();
+
+    assert_eq!(
+        *profiler.seen.lock().unwrap(),
+        vec![
+            "initialize 14",
+            "module Example.dll",
+            "jit Example.Outer+Inner::Run(int)",
+            "shutdown",
+        ]
+    );
+    assert_eq!(runtime.event_mask(), EVENTS);
+}
+
+#[test]
+fn rewritten_bodies_replace_the_original() {
+    let (runtime, module, function) = runtime();
+    let driver = ProfilerDriver::new(RecordingProfiler::default(), EVENTS, &runtime);
+    driver.initialize().unwrap();
+    driver.load_module(module).unwrap();
+    driver.jit(function).unwrap();
+
+    assert!(runtime.is_il_rewritten(function));
+    // 70 nops don't fit in a tiny header
+    let body = runtime.il_function_body(function);
+    let mut code = vec![0x00; 70];
+    code.push(0x2A);
+    assert_eq!(body[0] & 0x3, 0x3);
+    assert_eq!(body[12..], code[..]);
+}
+
+#[test]
+fn callbacks_for_events_that_are_off_are_left_out() {
+    let (runtime, module, function) = runtime();
+    let profiler = RecordingProfiler::default();
+    let driver = ProfilerDriver::new(profiler.clone(), EventMask2::NONE, &runtime);
+
+    driver.initialize().unwrap();
+    driver.load_module(module).unwrap();
+    driver.jit(function).unwrap();
+
+    assert_eq!(*profiler.seen.lock().unwrap(), vec!["initialize 14"]);
+    assert!(!runtime.is_il_rewritten(function));
+}
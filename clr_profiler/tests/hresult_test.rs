@@ -0,0 +1,54 @@
+use clr_profiler::ffi::{HResult, CORPROF_E_DATAINCOMPLETE, COR_E_INDEXOUTOFRANGE, HRESULT};
+
+#[test]
+fn displays_known_codes_by_name() {
+    let hr = HResult::from(CORPROF_E_DATAINCOMPLETE);
+    assert_eq!(hr.name(), Some("CORPROF_E_DATAINCOMPLETE"));
+    assert_eq!(hr.to_string(), "CORPROF_E_DATAINCOMPLETE (0x80131351)");
+    assert_eq!(
+        format!("{hr:#}"),
+        "CORPROF_E_DATAINCOMPLETE (0x80131351): the ID isn't fully loaded or defined yet"
+    );
+    assert_eq!(format!("{hr:?}"), "HResult(CORPROF_E_DATAINCOMPLETE)");
+    assert_eq!(format!("{hr:#x}"), "0x80131351");
+    assert_eq!(
+        HResult::COR_E_INDEXOUTOFRANGE,
+        HResult(COR_E_INDEXOUTOFRANGE)
+    );
+    assert_eq!(HResult::COR_E_INDEXOUTOFRANGE.code() as u32, 0x8013_1508);
+}
+
+#[test]
+fn displays_unknown_and_win32_codes() {
+    let unknown = HResult(0x8013_1FFE_u32 as HRESULT);
+    assert_eq!(unknown.name(), None);
+    assert_eq!(unknown.to_string(), "0x80131ffe");
+    assert_eq!(unknown.facility_name(), Some("FACILITY_URT"));
+
+    let not_found = HResult::from_win32(2);
+    assert_eq!(not_found.to_string(), "ERROR_FILE_NOT_FOUND (0x80070002)");
+    assert_eq!(
+        HResult::from_win32(1234).to_string(),
+        "HRESULT_FROM_WIN32(1234) (0x800704d2)"
+    );
+    assert_eq!(HResult::from_win32(0), HResult::S_OK);
+}
+
+#[test]
+fn converts_at_the_ffi_boundary() {
+    let raw: HRESULT = HResult::E_FAIL.into();
+    assert_eq!(HResult::from(raw), HResult::E_FAIL);
+    assert!(HResult::S_FALSE.is_success());
+    assert!(!HResult::E_FAIL.is_success());
+    let error: Box<dyn std::error::Error> = Box::new(HResult::E_INVALIDARG);
+    assert_eq!(error.to_string(), "E_INVALIDARG (0x80070057)");
+}
+
+#[test]
+fn unsupported_interfaces_carry_the_required_version() {
+    let hr = HResult::unsupported_interface(11);
+    assert_eq!(hr.required_interface(), Some(11));
+    assert!(!hr.is_success());
+    assert_eq!(hr.to_string(), "ICorProfilerInfo11 required (0xa004000b)");
+    assert_eq!(HResult::E_NOINTERFACE.required_interface(), None);
+}
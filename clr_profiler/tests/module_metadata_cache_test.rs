@@ -0,0 +1,137 @@
+use clr_profiler::{
+    ffi::{CorTypeAttr, HResult, ModuleID, COR_PRF_MONITOR},
+    testing::{FakeRuntime, ProfilerDriver},
+    CacheStats, ClrProfiler, CorProfilerCallback, CorProfilerCallback10, CorProfilerCallback11,
+    CorProfilerCallback2, CorProfilerCallback3, CorProfilerCallback4, CorProfilerCallback5,
+    CorProfilerCallback6, CorProfilerCallback7, CorProfilerCallback8, CorProfilerCallback9,
+    CorProfilerInfo, EventMask2, ModuleMetadata, ModuleMetadataCache, ProfilerInfo,
+};
+use std::sync::{Arc, OnceLock};
+use uuid::Uuid;
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn cache_can_be_shared_between_threads() {
+    assert_send_sync::<ModuleMetadataCache>();
+    assert_send_sync::<ModuleMetadata>();
+    assert_eq!(CacheStats::default().module_hits, 0);
+}
+
+/// Keeps a cache the way profilers are meant to: evicting modules as they
+/// unload.
+#[derive(Clone, Default)]
+struct CachingProfiler {
+    clsid: Uuid,
+    cache: Arc<OnceLock<ModuleMetadataCache>>,
+}
+
+impl CachingProfiler {
+    fn cache(&self) -> &ModuleMetadataCache {
+        self.cache.get().unwrap()
+    }
+}
+
+impl ClrProfiler for CachingProfiler {
+    fn new() -> Self {
+        Self::default()
+    }
+    fn clsid(&self) -> &Uuid {
+        &self.clsid
+    }
+}
+
+impl CorProfilerCallback for CachingProfiler {
+    fn initialize(&self, profiler_info: ProfilerInfo) -> Result<(), HResult> {
+        profiler_info.set_event_mask(COR_PRF_MONITOR::COR_PRF_MONITOR_MODULE_LOADS)?;
+        let _ = self.cache.set(ModuleMetadataCache::new(profiler_info));
+        Ok(())
+    }
+
+    fn module_load_finished(
+        &self,
+        module_id: ModuleID,
+        _hr_status: HResult,
+    ) -> Result<(), HResult> {
+        self.cache().get(module_id).map(|_| ())
+    }
+
+    fn module_unload_started(&self, module_id: ModuleID) -> Result<(), HResult> {
+        self.cache().evict(module_id);
+        Ok(())
+    }
+}
+
+impl CorProfilerCallback2 for CachingProfiler {}
+impl CorProfilerCallback3 for CachingProfiler {}
+impl CorProfilerCallback4 for CachingProfiler {}
+impl CorProfilerCallback5 for CachingProfiler {}
+impl CorProfilerCallback6 for CachingProfiler {}
+impl CorProfilerCallback7 for CachingProfiler {}
+impl CorProfilerCallback8 for CachingProfiler {}
+impl CorProfilerCallback9 for CachingProfiler {}
+impl CorProfilerCallback10 for CachingProfiler {}
+impl CorProfilerCallback11 for CachingProfiler {}
+
+#[test]
+fn modules_and_names_are_looked_up_once() {
+    let runtime = FakeRuntime::new();
+    let module = runtime.add_module("Example.dll");
+    let class = runtime.add_type(module, "Example.Program", CorTypeAttr::tdPublic);
+    let profiler = CachingProfiler::default();
+    let driver = ProfilerDriver::new(profiler.clone(), EventMask2::NONE, &runtime);
+    driver.initialize().unwrap();
+    driver.load_module(module).unwrap();
+
+    let cached = profiler.cache().get(module).unwrap();
+    assert!(Arc::ptr_eq(&cached, &profiler.cache().get(module).unwrap()));
+    assert_eq!(cached.assembly_name, "Example");
+    assert_eq!(cached.file_name, "Example.dll");
+    for _ in 0..2 {
+        assert_eq!(
+            cached.qualified_name(class.token()).unwrap(),
+            "Example.Program"
+        );
+    }
+
+    assert_eq!(runtime.metadata_requests(), 1);
+    assert_eq!(
+        profiler.cache().stats(),
+        CacheStats {
+            module_hits: 2,
+            module_misses: 1,
+            name_hits: 1,
+            name_misses: 1,
+            evictions: 0,
+        }
+    );
+}
+
+#[test]
+fn unloaded_modules_are_evicted() {
+    let runtime = FakeRuntime::new();
+    let modules = [
+        runtime.add_module("First.dll"),
+        runtime.add_module("Second.dll"),
+    ];
+    let profiler = CachingProfiler::default();
+    let driver = ProfilerDriver::new(profiler.clone(), EventMask2::NONE, &runtime);
+    driver.initialize().unwrap();
+    for &module in &modules {
+        driver.load_module(module).unwrap();
+    }
+    let unloaded = profiler.cache().get(modules[0]).unwrap();
+
+    driver.unload_module(modules[0]).unwrap();
+    assert_eq!(profiler.cache().len(), 1);
+    assert_eq!(profiler.cache().stats().evictions, 1);
+    // a scope handed out before the unload stays usable
+    assert_eq!(unloaded.file_name, "First.dll");
+
+    // the ID may be reused, so it is looked up again
+    let reloaded = profiler.cache().get(modules[0]).unwrap();
+    assert!(!Arc::ptr_eq(&unloaded, &reloaded));
+    assert_eq!(runtime.metadata_requests(), 3);
+    assert!(profiler.cache().evict(modules[0]));
+    assert!(!profiler.cache().evict(modules[0]));
+}
@@ -0,0 +1,73 @@
+use clr_profiler::{
+    ffi::{
+        ClassFactory, CorProfilerCallback as Callback, HResult, ICorProfilerCallback, E_FAIL,
+        E_NOTIMPL,
+    },
+    ClrProfiler, CorProfilerCallback, CorProfilerCallback10, CorProfilerCallback11,
+    CorProfilerCallback2, CorProfilerCallback3, CorProfilerCallback4, CorProfilerCallback5,
+    CorProfilerCallback6, CorProfilerCallback7, CorProfilerCallback8, CorProfilerCallback9,
+};
+use std::ptr;
+use uuid::Uuid;
+
+struct PanickingProfiler {
+    clsid: Uuid,
+}
+
+impl Clone for PanickingProfiler {
+    fn clone(&self) -> Self {
+        panic!("clone failed");
+    }
+}
+
+impl ClrProfiler for PanickingProfiler {
+    fn new() -> Self {
+        PanickingProfiler { clsid: Uuid::nil() }
+    }
+    fn clsid(&self) -> &Uuid {
+        &self.clsid
+    }
+}
+
+impl CorProfilerCallback for PanickingProfiler {
+    fn shutdown(&self) -> Result<(), HResult> {
+        panic!("shutdown failed");
+    }
+    fn app_domain_creation_started(&self, _app_domain_id: usize) -> Result<(), HResult> {
+        Err(HResult::E_NOTIMPL)
+    }
+}
+impl CorProfilerCallback2 for PanickingProfiler {}
+impl CorProfilerCallback3 for PanickingProfiler {}
+impl CorProfilerCallback4 for PanickingProfiler {}
+impl CorProfilerCallback5 for PanickingProfiler {}
+impl CorProfilerCallback6 for PanickingProfiler {}
+impl CorProfilerCallback7 for PanickingProfiler {}
+impl CorProfilerCallback8 for PanickingProfiler {}
+impl CorProfilerCallback9 for PanickingProfiler {}
+impl CorProfilerCallback10 for PanickingProfiler {}
+impl CorProfilerCallback11 for PanickingProfiler {}
+
+#[test]
+fn panics_in_callbacks_become_e_fail() {
+    let callback = Callback::new(PanickingProfiler::new());
+    unsafe {
+        assert_eq!(callback.Shutdown(), E_FAIL);
+        // the profiler is still reachable after a panic
+        assert_eq!(callback.AppDomainCreationStarted(1), E_NOTIMPL);
+        assert_eq!(callback.release(), 0);
+    }
+}
+
+#[test]
+fn panics_in_the_class_factory_are_contained() {
+    let factory = ClassFactory::new(PanickingProfiler::new());
+    let mut callback = ptr::null_mut();
+    unsafe {
+        // every instance is a clone of the factory's profiler
+        let hr = factory.CreateInstance(ptr::null_mut(), &ICorProfilerCallback::IID, &mut callback);
+        assert_eq!(hr, E_FAIL);
+        assert!(callback.is_null());
+        assert_eq!(factory.Release(), 0);
+    }
+}
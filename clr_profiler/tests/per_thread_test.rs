@@ -0,0 +1,135 @@
+use clr_profiler::{
+    ffi::{CorProfilerCallback as Callback, HResult, ThreadID, S_OK},
+    ClrProfiler, CorProfilerCallback, CorProfilerCallback10, CorProfilerCallback11,
+    CorProfilerCallback2, CorProfilerCallback3, CorProfilerCallback4, CorProfilerCallback5,
+    CorProfilerCallback6, CorProfilerCallback7, CorProfilerCallback8, CorProfilerCallback9,
+    PerThread,
+};
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+use uuid::Uuid;
+
+#[derive(Default)]
+struct ThreadStats {
+    transitions: u32,
+}
+
+struct CountingProfiler {
+    clsid: Uuid,
+    threads: PerThread<ThreadStats>,
+}
+
+// transitions of destroyed threads, the profiler itself is owned by the callback
+static TRANSITIONS: AtomicUsize = AtomicUsize::new(0);
+
+impl ClrProfiler for CountingProfiler {
+    fn new() -> Self {
+        CountingProfiler {
+            clsid: Uuid::nil(),
+            threads: PerThread::new(),
+        }
+    }
+    fn clsid(&self) -> &Uuid {
+        &self.clsid
+    }
+}
+
+impl CorProfilerCallback for CountingProfiler {
+    fn thread_assigned_to_os_thread(
+        &self,
+        managed_thread_id: ThreadID,
+        _os_thread_id: u32,
+    ) -> Result<(), HResult> {
+        self.threads
+            .with(managed_thread_id, |stats| stats.transitions += 1);
+        Ok(())
+    }
+    fn thread_destroyed(&self, thread_id: ThreadID) -> Result<(), HResult> {
+        if let Some(stats) = self.threads.remove(thread_id) {
+            TRANSITIONS.fetch_add(stats.transitions as usize, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+}
+impl CorProfilerCallback2 for CountingProfiler {}
+impl CorProfilerCallback3 for CountingProfiler {}
+impl CorProfilerCallback4 for CountingProfiler {}
+impl CorProfilerCallback5 for CountingProfiler {}
+impl CorProfilerCallback6 for CountingProfiler {}
+impl CorProfilerCallback7 for CountingProfiler {}
+impl CorProfilerCallback8 for CountingProfiler {}
+impl CorProfilerCallback9 for CountingProfiler {}
+impl CorProfilerCallback10 for CountingProfiler {}
+impl CorProfilerCallback11 for CountingProfiler {}
+
+#[test]
+fn callbacks_can_run_concurrently() {
+    let callback: &Callback<CountingProfiler> = Callback::new(CountingProfiler::new());
+    thread::scope(|scope| {
+        for thread_id in 1..=8 {
+            scope.spawn(move || {
+                for _ in 0..100 {
+                    let hr = unsafe { callback.ThreadAssignedToOSThread(thread_id, 0) };
+                    assert_eq!(hr, S_OK);
+                }
+            });
+        }
+    });
+    for thread_id in 1..=8 {
+        assert_eq!(unsafe { callback.ThreadDestroyed(thread_id) }, S_OK);
+    }
+    assert_eq!(TRANSITIONS.load(Ordering::Relaxed), 800);
+    unsafe { callback.release() };
+}
+
+#[test]
+fn per_thread_state_is_kept_apart() {
+    let threads = PerThread::<Vec<u32>>::new();
+    threads.with(1, |calls| calls.push(10));
+    threads.with(2, |calls| calls.push(20));
+    threads.with(1, |calls| calls.push(11));
+
+    assert_eq!(
+        threads.with_existing(1, |calls| calls.clone()),
+        Some(vec![10, 11])
+    );
+    assert_eq!(threads.with_existing(3, |calls| calls.len()), None);
+    let mut thread_ids = threads.thread_ids();
+    thread_ids.sort();
+    assert_eq!(thread_ids, vec![1, 2]);
+
+    assert_eq!(threads.remove(2), Some(vec![20]));
+    assert_eq!(threads.remove(2), None);
+    assert_eq!(threads.thread_ids(), vec![1]);
+}
+
+#[test]
+fn state_of_other_threads_can_be_used_while_running() {
+    let threads = PerThread::<Vec<u32>>::new();
+    threads.with(1, |first| {
+        first.push(10);
+        // starting another thread's state doesn't wait for this one
+        threads.with(2, |second| second.push(20));
+        assert_eq!(threads.with_existing(2, |second| second.len()), Some(1));
+    });
+    assert_eq!(threads.remove(1), Some(vec![10]));
+    assert_eq!(threads.remove(2), Some(vec![20]));
+}
+
+#[test]
+fn a_panic_leaves_the_state_usable() {
+    let threads = PerThread::<Vec<u32>>::new();
+    let panicked = panic::catch_unwind(AssertUnwindSafe(|| {
+        threads.with(1, |calls| {
+            calls.push(10);
+            panic!("callback failed");
+        })
+    }));
+    assert!(panicked.is_err());
+
+    threads.with(1, |calls| calls.push(11));
+    assert_eq!(threads.remove(1), Some(vec![10, 11]));
+}
@@ -0,0 +1,281 @@
+use clr_profiler::{
+    ffi::{
+        CorMethodAttr, CorMethodImpl, CorTypeAttr, HResult, ModuleID, COR_PRF_HIGH_MONITOR,
+        COR_PRF_MONITOR,
+    },
+    metadata::{MethodDefToken, TypeDefToken},
+    recording::{Arg, Callback, Record, Recording},
+    testing::{FakeRuntime, ProfilerDriver},
+    ClrProfiler, EventMask2, Plugin, PluginHost, PluginSet, ProfilerEvent,
+};
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+// void ()
+const SIG: &[u8] = &[0x00, 0x00, 0x01];
+// tiny header, ret
+const RETURN: &[u8] = &[0x06, 0x2A];
+
+type Log = Arc<Mutex<Vec<String>>>;
+
+enum Behavior {
+    /// Puts an instruction in front of the method body.
+    Prepend(u8),
+    FailRewrite,
+    PanicOnModuleLoad,
+}
+
+struct TestPlugin {
+    name: &'static str,
+    events: COR_PRF_MONITOR,
+    behavior: Behavior,
+    log: Log,
+}
+
+impl TestPlugin {
+    fn boxed(
+        name: &'static str,
+        events: COR_PRF_MONITOR,
+        behavior: Behavior,
+        log: &Log,
+    ) -> Box<dyn Plugin> {
+        Box::new(TestPlugin {
+            name,
+            events,
+            behavior,
+            log: log.clone(),
+        })
+    }
+}
+
+impl Plugin for TestPlugin {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn events(&self) -> EventMask2 {
+        EventMask2 {
+            events_low: self.events,
+            events_high: COR_PRF_HIGH_MONITOR::empty(),
+        }
+    }
+
+    fn handle(&self, event: &ProfilerEvent) -> Result<(), HResult> {
+        let seen = match event {
+            ProfilerEvent::Initialize { .. } => "initialize",
+            ProfilerEvent::ModuleLoadStarted { .. } => {
+                if let Behavior::PanicOnModuleLoad = self.behavior {
+                    panic!("{} can't load modules", self.name);
+                }
+                "module"
+            }
+            ProfilerEvent::JITCompilationStarted { .. } => "jit",
+            ProfilerEvent::Shutdown => "shutdown",
+            _ => return Ok(()),
+        };
+        self.log
+            .lock()
+            .unwrap()
+            .push(format!("{} {}", self.name, seen));
+        Ok(())
+    }
+
+    fn rewrite_il(
+        &self,
+        _module_id: ModuleID,
+        _method_id: MethodDefToken,
+        il_body: &[u8],
+    ) -> Result<Option<Vec<u8>>, HResult> {
+        match self.behavior {
+            Behavior::Prepend(opcode) => {
+                let code = &il_body[1..];
+                let mut rewritten = vec![((code.len() as u8 + 1) << 2) | 2, opcode];
+                rewritten.extend_from_slice(code);
+                Ok(Some(rewritten))
+            }
+            Behavior::FailRewrite => Err(HResult::E_FAIL),
+            Behavior::PanicOnModuleLoad => Ok(None),
+        }
+    }
+}
+
+struct Plugins;
+
+impl ClrProfiler for Plugins {
+    fn new() -> Self {
+        Plugins
+    }
+    fn clsid(&self) -> &Uuid {
+        unimplemented!()
+    }
+}
+
+impl PluginSet for Plugins {
+    fn plugins(&self) -> Vec<Box<dyn Plugin>> {
+        Vec::new()
+    }
+}
+
+fn logged(log: &Log) -> Vec<String> {
+    log.lock().unwrap().clone()
+}
+
+#[test]
+fn every_plugin_gets_every_event_and_the_events_they_need() {
+    let runtime = FakeRuntime::new();
+    let module = runtime.add_module("Example.dll");
+    let log = Log::default();
+    let host = PluginHost::<Plugins>::with_plugins(
+        Uuid::nil(),
+        vec![
+            TestPlugin::boxed(
+                "loads",
+                COR_PRF_MONITOR::COR_PRF_MONITOR_MODULE_LOADS,
+                Behavior::Prepend(0x00),
+                &log,
+            ),
+            TestPlugin::boxed(
+                "jit",
+                COR_PRF_MONITOR::COR_PRF_MONITOR_JIT_COMPILATION,
+                Behavior::Prepend(0x00),
+                &log,
+            ),
+        ],
+    );
+    let driver = ProfilerDriver::new(host, EventMask2::NONE, &runtime);
+
+    driver.initialize().unwrap();
+    driver.load_module(module).unwrap();
+    driver.shutdown().unwrap();
+
+    assert_eq!(
+        runtime.event_mask().events_low,
+        COR_PRF_MONITOR::COR_PRF_MONITOR_MODULE_LOADS
+            | COR_PRF_MONITOR::COR_PRF_MONITOR_JIT_COMPILATION
+    );
+    assert_eq!(
+        logged(&log),
+        vec![
+            "loads initialize",
+            "jit initialize",
+            "loads module",
+            "jit module",
+            "loads shutdown",
+            "jit shutdown",
+        ]
+    );
+}
+
+#[test]
+fn rewrites_of_the_same_method_are_chained_in_order() {
+    let runtime = FakeRuntime::new();
+    let module = runtime.add_module("Example.dll");
+    let class = runtime.add_type(module, "Example.Program", CorTypeAttr::tdPublic);
+    let function = runtime.add_method(module, class, "Main", SIG, RETURN);
+    let log = Log::default();
+    let jit = COR_PRF_MONITOR::COR_PRF_MONITOR_JIT_COMPILATION;
+    let host = PluginHost::<Plugins>::with_plugins(
+        Uuid::nil(),
+        vec![
+            // nop
+            TestPlugin::boxed("first", jit, Behavior::Prepend(0x00), &log),
+            TestPlugin::boxed("failing", jit, Behavior::FailRewrite, &log),
+            // break
+            TestPlugin::boxed("second", jit, Behavior::Prepend(0x01), &log),
+        ],
+    );
+    let driver = ProfilerDriver::new(host, EventMask2::NONE, &runtime);
+
+    driver.initialize().unwrap();
+    driver.jit(function).unwrap();
+
+    // the failed rewrite is skipped, and the plugin still gets events
+    assert_eq!(runtime.il_function_body(function), [0x0E, 0x01, 0x00, 0x2A]);
+    assert!(logged(&log).contains(&"failing jit".to_string()));
+}
+
+#[test]
+fn a_panicking_plugin_is_disabled_without_affecting_the_others() {
+    let runtime = FakeRuntime::new();
+    let modules = [
+        runtime.add_module("First.dll"),
+        runtime.add_module("Second.dll"),
+    ];
+    let log = Log::default();
+    let loads = COR_PRF_MONITOR::COR_PRF_MONITOR_MODULE_LOADS;
+    let host = PluginHost::<Plugins>::with_plugins(
+        Uuid::nil(),
+        vec![
+            TestPlugin::boxed("panicking", loads, Behavior::PanicOnModuleLoad, &log),
+            TestPlugin::boxed("healthy", loads, Behavior::Prepend(0x00), &log),
+        ],
+    );
+    let driver = ProfilerDriver::new(host, EventMask2::NONE, &runtime);
+
+    driver.initialize().unwrap();
+    for &module in &modules {
+        driver.load_module(module).unwrap();
+    }
+    driver.shutdown().unwrap();
+
+    assert_eq!(
+        logged(&log),
+        vec![
+            "panicking initialize",
+            "healthy initialize",
+            "healthy module",
+            "healthy module",
+            "healthy shutdown",
+        ]
+    );
+}
+
+#[test]
+fn methods_without_il_are_rejitted_unchanged() {
+    let (module_id, class) = (1, TypeDefToken::from_rid(2));
+    let token = MethodDefToken::from_rid(1);
+    let recording = Recording {
+        records: vec![
+            Record::Module {
+                module_id,
+                assembly_id: 2,
+                name: "Example.dll".to_string(),
+            },
+            Record::TypeDef {
+                module_id,
+                token: class,
+                name: "Example.Program".to_string(),
+                flags: CorTypeAttr::tdPublic,
+                enclosing: None,
+                generic_params: Vec::new(),
+            },
+            // implemented by the runtime, so there's no IL body
+            Record::Method {
+                module_id,
+                token,
+                class,
+                name: "Run".to_string(),
+                attr_flags: CorMethodAttr::mdPublic,
+                impl_flags: CorMethodImpl::miRuntime,
+                sig: SIG.to_vec(),
+                generic_params: Vec::new(),
+            },
+            Record::Callback {
+                callback: Callback::Initialize,
+                args: Vec::new(),
+            },
+            Record::Callback {
+                callback: Callback::GetReJITParameters,
+                args: vec![Arg::Module(module_id), Arg::MethodDef(token)],
+            },
+        ],
+    };
+    let runtime = FakeRuntime::from_recording(&recording);
+    let log = Log::default();
+    let jit = COR_PRF_MONITOR::COR_PRF_MONITOR_JIT_COMPILATION;
+    let plugin = TestPlugin::boxed("rewriting", jit, Behavior::Prepend(0x00), &log);
+    let host = PluginHost::<Plugins>::with_plugins(Uuid::nil(), vec![plugin]);
+    let driver = ProfilerDriver::new(host, EventMask2::NONE, &runtime);
+
+    driver.replay(&recording).unwrap();
+}
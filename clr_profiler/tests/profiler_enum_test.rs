@@ -0,0 +1,153 @@
+use clr_profiler::{
+    ffi::{
+        ComPtr, CorProfilerModuleEnum, CorProfilerModuleEnumVtbl, HResult, ICorProfilerModuleEnum,
+        IUnknown, ModuleID, E_NOINTERFACE, E_NOTIMPL, HRESULT, REFIID, S_FALSE, S_OK, ULONG,
+    },
+    ProfilerEnum,
+};
+use std::{
+    cell::Cell,
+    ffi::c_void,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+/// A module enumerator over `modules` that fails once `fail_after` items
+/// were handed out.
+#[repr(C)]
+struct FakeModuleEnum {
+    module_enum: CorProfilerModuleEnum,
+    refs: AtomicU32,
+    modules: Vec<ModuleID>,
+    position: Cell<usize>,
+    fail_after: usize,
+}
+
+fn fake(this: &CorProfilerModuleEnum) -> &FakeModuleEnum {
+    unsafe { &*(this as *const CorProfilerModuleEnum as *const FakeModuleEnum) }
+}
+
+unsafe extern "system" fn query_interface(
+    _this: &CorProfilerModuleEnum,
+    _riid: REFIID,
+    _ppv_object: *mut *mut c_void,
+) -> HRESULT {
+    E_NOINTERFACE
+}
+
+unsafe extern "system" fn add_ref(this: &CorProfilerModuleEnum) -> ULONG {
+    fake(this).refs.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+unsafe extern "system" fn release(this: &CorProfilerModuleEnum) -> ULONG {
+    fake(this).refs.fetch_sub(1, Ordering::Relaxed) - 1
+}
+
+unsafe extern "system" fn skip(_this: &CorProfilerModuleEnum, _celt: ULONG) -> HRESULT {
+    E_NOTIMPL
+}
+
+unsafe extern "system" fn reset(this: &CorProfilerModuleEnum) -> HRESULT {
+    fake(this).position.set(0);
+    S_OK
+}
+
+unsafe extern "system" fn clone(
+    _this: &CorProfilerModuleEnum,
+    _pp_enum: *mut *mut CorProfilerModuleEnum,
+) -> HRESULT {
+    E_NOTIMPL
+}
+
+unsafe extern "system" fn get_count(this: &CorProfilerModuleEnum, pcelt: *mut ULONG) -> HRESULT {
+    *pcelt = fake(this).modules.len() as ULONG;
+    S_OK
+}
+
+unsafe extern "system" fn next(
+    this: &CorProfilerModuleEnum,
+    celt: ULONG,
+    objects: *mut ModuleID,
+    pcelt_fetched: *mut ULONG,
+) -> HRESULT {
+    let fake = fake(this);
+    let position = fake.position.get();
+    if position >= fake.fail_after {
+        return HResult::CORPROF_E_DATAINCOMPLETE.into();
+    }
+    let end = (position + celt as usize).min(fake.modules.len());
+    for (i, module) in fake.modules[position..end].iter().enumerate() {
+        *objects.add(i) = *module;
+    }
+    fake.position.set(end);
+    *pcelt_fetched = (end - position) as ULONG;
+    if end - position < celt as usize {
+        S_FALSE
+    } else {
+        S_OK
+    }
+}
+
+static VTBL: CorProfilerModuleEnumVtbl = CorProfilerModuleEnumVtbl {
+    IUnknown: IUnknown {
+        QueryInterface: query_interface,
+        AddRef: add_ref,
+        Release: release,
+    },
+    ICorProfilerModuleEnum: ICorProfilerModuleEnum {
+        Skip: skip,
+        Reset: reset,
+        Clone: clone,
+        GetCount: get_count,
+        Next: next,
+    },
+};
+
+fn fake_enum(modules: Vec<ModuleID>, fail_after: usize) -> Box<FakeModuleEnum> {
+    Box::new(FakeModuleEnum {
+        module_enum: CorProfilerModuleEnum { lpVtbl: &VTBL },
+        refs: AtomicU32::new(1),
+        modules,
+        position: Cell::new(0),
+        fail_after,
+    })
+}
+
+fn profiler_enum(fake: &FakeModuleEnum) -> ProfilerEnum<CorProfilerModuleEnum> {
+    let ptr = &fake.module_enum as *const CorProfilerModuleEnum as *mut CorProfilerModuleEnum;
+    ProfilerEnum::new(unsafe { ComPtr::from_raw(ptr) }.unwrap()).unwrap()
+}
+
+#[test]
+fn iterates_in_batches_and_releases_the_enumerator() {
+    let modules = (1..=150).collect::<Vec<ModuleID>>();
+    let fake = fake_enum(modules.clone(), usize::MAX);
+
+    let mut iter = profiler_enum(&fake);
+    assert_eq!(iter.size_hint(), (0, Some(150)));
+    assert_eq!(iter.next(), Some(1));
+    assert_eq!(iter.size_hint(), (0, Some(149)));
+    assert_eq!(iter.by_ref().collect::<Vec<_>>(), modules[1..]);
+    assert_eq!(iter.error(), None);
+    // the runtime's count covers what's been iterated over
+    assert_eq!(iter.count_hint(), Ok(150));
+    drop(iter);
+    assert_eq!(fake.refs.load(Ordering::Relaxed), 0);
+}
+
+#[test]
+fn errors_end_iteration_and_are_reported() {
+    let fake = fake_enum((1..=100).collect(), 64);
+
+    let mut iter = profiler_enum(&fake);
+    assert_eq!(iter.size_hint(), (0, Some(100)));
+    assert_eq!(iter.by_ref().count(), 64);
+    assert_eq!(iter.error(), Some(HResult::CORPROF_E_DATAINCOMPLETE));
+    assert_eq!(iter.size_hint(), (0, Some(0)));
+
+    drop(iter);
+    // the first iterator released the only reference
+    fake.refs.store(1, Ordering::Relaxed);
+    fake.position.set(0);
+    let result = profiler_enum(&fake).into_vec();
+    assert_eq!(result, Err(HResult::CORPROF_E_DATAINCOMPLETE));
+}
@@ -0,0 +1,312 @@
+use clr_profiler::{
+    ffi::{
+        CorElementType, CorTypeAttr, HResult, COR_PRF_HANDLE_TYPE, COR_PRF_HIGH_MONITOR,
+        COR_PRF_MODULE_FLAGS, COR_PRF_MONITOR, E_NOTIMPL,
+    },
+    testing::{FakeRuntime, ProfilerDriver, Quirks},
+    ClrProfiler, CorProfilerCallback, CorProfilerCallback10, CorProfilerCallback11,
+    CorProfilerCallback2, CorProfilerCallback3, CorProfilerCallback4, CorProfilerCallback5,
+    CorProfilerCallback6, CorProfilerCallback7, CorProfilerCallback8, CorProfilerCallback9,
+    CorProfilerInfo, CorProfilerInfo11, CorProfilerInfo13, CorProfilerInfo2, CorProfilerInfo3,
+    CorProfilerInfo4, CorProfilerInfo5, EventMask2, ProfilerInfo,
+};
+use std::ptr;
+use uuid::Uuid;
+
+// ret
+const NOP_RETURN: [u8; 2] = [0x06, 0x2a];
+
+fn profiler_info(runtime: &FakeRuntime) -> ProfilerInfo {
+    ProfilerInfo::new(runtime.profiler_info().as_ptr()).unwrap()
+}
+
+#[test]
+fn missing_classes_are_none() {
+    let runtime = FakeRuntime::new();
+    let module = runtime.add_module("Example.dll");
+    let list = runtime.add_generic_type(module, "Example.List`1", CorTypeAttr::tdPublic, &["T"]);
+    let add = runtime.add_method(
+        module,
+        list,
+        "Add",
+        &[0x20, 0x01, 0x01, 0x13, 0x00],
+        &NOP_RETURN,
+    );
+    let program = runtime.add_type(module, "Example.Program", CorTypeAttr::tdPublic);
+    let ints = runtime.add_array_class(CorElementType::ELEMENT_TYPE_I4, None, 1);
+    let info = profiler_info(&runtime);
+
+    let array = info.is_array_class(ints).unwrap();
+    assert_eq!(array.element_type, CorElementType::ELEMENT_TYPE_I4);
+    assert_eq!(array.element_class_id, None);
+    assert_eq!(array.rank, 1);
+    assert_eq!(info.get_function_info(add).unwrap().class_id, None);
+    let class = info
+        .get_class_id_info_2(runtime.class_id(module, program))
+        .unwrap();
+    assert_eq!(class.token, program);
+    assert_eq!(class.parent_class_id, None);
+}
+
+#[test]
+fn missing_addresses_are_none() {
+    let runtime = FakeRuntime::new();
+    let unnamed = runtime.add_module("");
+    // a flag from a newer runtime
+    let flags = COR_PRF_MODULE_FLAGS::from_bits_retain(
+        COR_PRF_MODULE_FLAGS::COR_PRF_MODULE_DYNAMIC.bits() | 0x8000_0000,
+    );
+    let dynamic = runtime.add_module_with_flags("dynamic", flags);
+    let ints = runtime.add_array_class(CorElementType::ELEMENT_TYPE_I4, None, 2);
+    let empty = runtime.add_array(ints, &[0, 0], 4);
+    let info = profiler_info(&runtime);
+
+    let module = info.get_module_info(unnamed).unwrap();
+    assert_eq!(module.base_load_address, None);
+    assert_eq!(module.file_name, "");
+    assert_eq!(
+        info.get_assembly_info(module.assembly_id)
+            .unwrap()
+            .module_id,
+        unnamed
+    );
+
+    let module = info.get_module_info_2(dynamic).unwrap();
+    assert_eq!(module.base_load_address, None);
+    assert_eq!(module.file_name, "dynamic");
+    assert_eq!(
+        module.module_flags.bits(),
+        COR_PRF_MODULE_FLAGS::COR_PRF_MODULE_DYNAMIC.bits()
+    );
+
+    let array = info.get_array_object_info(empty, 2).unwrap();
+    assert_eq!(array.dimension_sizes, vec![0, 0]);
+    assert_eq!(array.data, None);
+}
+
+#[test]
+fn unexpected_nulls_are_errors() {
+    let runtime = FakeRuntime::new();
+    let module = runtime.add_module("Example.dll");
+    let class = runtime.add_type(module, "Example.Program", CorTypeAttr::tdPublic);
+    let main = runtime.add_method(module, class, "Main", &[0x00, 0x00, 0x01], &NOP_RETURN);
+    runtime.set_quirks(Quirks {
+        null_results: true,
+        ..Quirks::default()
+    });
+    let info = profiler_info(&runtime);
+    let (_, token) = runtime.function_token(main);
+
+    assert_eq!(
+        info.get_il_function_body(module, token).err(),
+        Some(HResult::E_POINTER)
+    );
+    assert_eq!(
+        info.get_il_function_body_allocator(module).err(),
+        Some(HResult::E_POINTER)
+    );
+    assert_eq!(
+        info.get_token_and_metadata_from_function(main).err(),
+        Some(HResult::E_POINTER)
+    );
+}
+
+#[test]
+fn buffers_are_cut_to_what_the_runtime_filled_in() {
+    let runtime = FakeRuntime::new();
+    let module = runtime.add_module("Example.dll");
+    let class = runtime.add_type(module, "Example.Program", CorTypeAttr::tdPublic);
+    let main = runtime.add_method(module, class, "Main", &[0x00, 0x00, 0x01], &NOP_RETURN);
+    runtime.set_native_code(main, &[(0x1000, 0x10), (0x2000, 0x10)]);
+    runtime.set_quirks(Quirks {
+        overstated_lengths: true,
+        ..Quirks::default()
+    });
+
+    let code_infos = profiler_info(&runtime).get_code_info_2(main).unwrap();
+    let starts = code_infos
+        .iter()
+        .map(|code_info| code_info.startAddress)
+        .collect::<Vec<_>>();
+    assert_eq!(starts, vec![0x1000, 0x2000]);
+}
+
+#[test]
+fn il_bodies_come_from_the_modules_allocator() {
+    let runtime = FakeRuntime::new();
+    let module = runtime.add_module("Example.dll");
+    let other = runtime.add_module("Other.dll");
+    let class = runtime.add_type(module, "Example.Program", CorTypeAttr::tdPublic);
+    let main = runtime.add_method(module, class, "Main", &[0x00, 0x00, 0x01], &NOP_RETURN);
+    let info = profiler_info(&runtime);
+    let (_, token) = runtime.function_token(main);
+    // tiny header, nop, ret
+    let body = [0x0a, 0x00, 0x2a];
+
+    assert_eq!(
+        info.set_il_function_body(module, token, body.as_ptr())
+            .err(),
+        Some(HResult::E_INVALIDARG)
+    );
+    let malloc = info.get_il_function_body_allocator(other).unwrap();
+    let foreign = unsafe { malloc.Alloc(body.len() as u32) } as *mut u8;
+    unsafe { ptr::copy_nonoverlapping(body.as_ptr(), foreign, body.len()) };
+    assert_eq!(
+        info.set_il_function_body(module, token, foreign).err(),
+        Some(HResult::E_INVALIDARG)
+    );
+    assert!(!runtime.is_il_rewritten(main));
+
+    info.replace_il_function_body(module, token, &body).unwrap();
+    assert_eq!(runtime.il_function_body(main), body);
+}
+
+#[test]
+fn unimplemented_methods_fail() {
+    assert_eq!(
+        profiler_info(&FakeRuntime::new()).get_thread_info(1).err(),
+        Some(HResult::from(E_NOTIMPL))
+    );
+}
+
+#[test]
+fn negotiates_the_newest_version_the_runtime_supports() {
+    let runtime = FakeRuntime::with_interface_level(3);
+    let module = runtime.add_module("Example.dll");
+    let class = runtime.add_type(module, "Example.Program", CorTypeAttr::tdPublic);
+    let main = runtime.add_method(module, class, "Main", &[0x00, 0x00, 0x01], &NOP_RETURN);
+    let info = profiler_info(&runtime);
+    assert_eq!(info.level(), 3);
+    assert!(info.supports(3));
+    assert!(!info.supports(4));
+
+    assert!(info.get_code_info_2(main).is_ok());
+    assert_eq!(
+        info.get_code_info_3(main, 0).err(),
+        Some(HResult::unsupported_interface(4))
+    );
+    let hr = info.get_code_info_3(main, 0).unwrap_err();
+    assert_eq!(hr.required_interface(), Some(4));
+    assert_ne!(hr, HResult::E_NOINTERFACE);
+}
+
+#[test]
+fn runtimes_without_any_version_are_rejected() {
+    let runtime = FakeRuntime::with_interface_level(0);
+    assert_eq!(
+        ProfilerInfo::new(runtime.profiler_info().as_ptr()).err(),
+        Some(HResult::E_NOINTERFACE)
+    );
+    assert_eq!(
+        ProfilerInfo::new(ptr::null()).err(),
+        Some(HResult::E_POINTER)
+    );
+}
+
+#[test]
+fn newer_interfaces_are_bound() {
+    let runtime = FakeRuntime::new();
+    runtime.set_environment_variable("PROFILER_TEST", "on");
+    let ints = runtime.add_array_class(CorElementType::ELEMENT_TYPE_I4, None, 1);
+    let array = runtime.add_array(ints, &[3], 4);
+    let info = profiler_info(&runtime);
+    assert_eq!(info.level(), 14);
+    assert_eq!(
+        info.get_environment_variable("PROFILER_TEST").unwrap(),
+        "on"
+    );
+    assert_eq!(
+        info.get_environment_variable("MISSING").err(),
+        Some(HResult::from_win32(203))
+    );
+
+    let handle = info
+        .create_handle(array, COR_PRF_HANDLE_TYPE::COR_PRF_HANDLE_TYPE_WEAK)
+        .unwrap();
+    assert_eq!(
+        unsafe { info.get_object_id_from_handle(handle) }.unwrap(),
+        Some(array)
+    );
+    // a weak handle to a collected object
+    runtime.collect_garbage();
+    assert_eq!(
+        unsafe { info.get_object_id_from_handle(handle) }.unwrap(),
+        None
+    );
+    unsafe { info.destroy_handle(handle) }.unwrap();
+
+    let info = profiler_info(&FakeRuntime::with_interface_level(10));
+    assert_eq!(
+        info.get_environment_variable("PROFILER_TEST").err(),
+        Some(HResult::unsupported_interface(11))
+    );
+}
+
+/// Asks for JIT events itself, the rest comes from `#[clr_profiler]`.
+#[derive(Clone)]
+struct JitProfiler;
+
+impl ClrProfiler for JitProfiler {
+    fn new() -> Self {
+        JitProfiler
+    }
+    fn clsid(&self) -> &Uuid {
+        unimplemented!()
+    }
+}
+
+impl CorProfilerCallback for JitProfiler {
+    fn initialize(&self, profiler_info: ProfilerInfo) -> Result<(), HResult> {
+        profiler_info.set_event_mask_2(
+            COR_PRF_MONITOR::COR_PRF_MONITOR_JIT_COMPILATION,
+            COR_PRF_HIGH_MONITOR::empty(),
+        )
+    }
+}
+impl CorProfilerCallback2 for JitProfiler {}
+impl CorProfilerCallback3 for JitProfiler {}
+impl CorProfilerCallback4 for JitProfiler {}
+impl CorProfilerCallback5 for JitProfiler {}
+impl CorProfilerCallback6 for JitProfiler {}
+impl CorProfilerCallback7 for JitProfiler {}
+impl CorProfilerCallback8 for JitProfiler {}
+impl CorProfilerCallback9 for JitProfiler {}
+impl CorProfilerCallback10 for JitProfiler {}
+impl CorProfilerCallback11 for JitProfiler {}
+
+const CALLBACK_EVENTS: EventMask2 = EventMask2 {
+    events_low: COR_PRF_MONITOR::COR_PRF_MONITOR_EXCEPTIONS
+        .union(COR_PRF_MONITOR::COR_PRF_DISABLE_ALL_NGEN_IMAGES),
+    events_high: COR_PRF_HIGH_MONITOR::COR_PRF_HIGH_MONITOR_EVENT_PIPE
+        .union(COR_PRF_HIGH_MONITOR::COR_PRF_HIGH_DISABLE_TIERED_COMPILATION),
+};
+
+#[test]
+fn initialize_adds_the_events_callbacks_need() {
+    let runtime = FakeRuntime::new();
+    let driver = ProfilerDriver::new(JitProfiler, CALLBACK_EVENTS, &runtime);
+    driver.initialize().unwrap();
+    let low = COR_PRF_MONITOR::COR_PRF_MONITOR_JIT_COMPILATION | CALLBACK_EVENTS.events_low;
+    assert_eq!(
+        runtime.event_mask(),
+        EventMask2 {
+            events_low: low,
+            events_high: CALLBACK_EVENTS.events_high,
+        }
+    );
+}
+
+#[test]
+fn attaching_leaves_out_events_only_allowed_at_startup() {
+    let runtime = FakeRuntime::new();
+    let driver = ProfilerDriver::new(JitProfiler, CALLBACK_EVENTS, &runtime);
+    // the immutable flags would fail the whole mask
+    driver.attach(&[]).unwrap();
+    assert_eq!(
+        runtime.event_mask(),
+        EventMask2 {
+            events_low: COR_PRF_MONITOR::COR_PRF_MONITOR_EXCEPTIONS,
+            events_high: COR_PRF_HIGH_MONITOR::COR_PRF_HIGH_MONITOR_EVENT_PIPE,
+        }
+    );
+}
@@ -0,0 +1,339 @@
+use clr_profiler::{
+    ffi::{
+        CorFieldAttr, CorGenericParamAttr, CorMethodAttr, CorMethodImpl, CorTypeAttr,
+        HResult,
+    },
+    metadata::{
+        CustomAttributeToken, Error, FieldDefToken, GenericParamToken, MemberRefToken,
+        MethodDefToken, MethodSig, MethodSpecToken, StringToken, Token, TypeDefToken, TypeRefToken,
+        TypeSig, TypeSpecToken,
+    },
+    CustomAttributeProps, FieldProps, GenericParamProps, MemberRefProps, MetadataImportTrait,
+    MethodProps, MethodSpecProps, TypeDefProps, TypeRefProps, TypeSpecProps,
+};
+use std::{collections::HashMap, convert::TryFrom};
+
+/// In-memory metadata scope, keyed by raw token.
+#[derive(Default)]
+struct FakeMetadata {
+    names: HashMap<u32, String>,
+    parents: HashMap<u32, u32>,
+    sigs: HashMap<u32, Vec<u8>>,
+    generic_params: HashMap<u32, Vec<GenericParamToken>>,
+    generic_param_props: HashMap<GenericParamToken, (u32, u32, &'static str)>,
+}
+
+impl FakeMetadata {
+    fn add(&mut self, token: u32, name: &str, parent: u32, sig: &[u8]) {
+        self.names.insert(token, name.to_string());
+        self.parents.insert(token, parent);
+        self.sigs.insert(token, sig.to_vec());
+    }
+    fn add_generic_params(&mut self, owner: u32, names: &[&'static str]) {
+        for (sequence, name) in names.iter().enumerate() {
+            let gp = GenericParamToken::from_rid(self.generic_param_props.len() as u32 + 1);
+            self.generic_param_props
+                .insert(gp, (owner, sequence as u32, name));
+            self.generic_params.entry(owner).or_default().insert(0, gp);
+        }
+    }
+    fn sig(&self, token: u32) -> (*const u8, u32) {
+        let sig = &self.sigs[&token];
+        (sig.as_ptr(), sig.len() as u32)
+    }
+    fn name(&self, token: u32) -> Result<String, HResult> {
+        self.names
+            .get(&token)
+            .cloned()
+            .ok_or(HResult::CLDB_E_RECORD_NOTFOUND)
+    }
+}
+
+impl MetadataImportTrait for FakeMetadata {
+    fn get_method_props(&self, mb: MethodDefToken) -> Result<MethodProps, HResult> {
+        let (sig, sig_length) = self.sig(mb.raw());
+        Ok(MethodProps {
+            class_token: TypeDefToken::try_from(self.parents[&mb.raw()]).unwrap(),
+            name: self.name(mb.raw())?,
+            attr_flags: CorMethodAttr::empty(),
+            sig,
+            sig_length,
+            rva: 0,
+            impl_flags: CorMethodImpl::empty(),
+        })
+    }
+    fn get_type_def_props(&self, td: TypeDefToken) -> Result<TypeDefProps, HResult> {
+        Ok(TypeDefProps {
+            name: self.name(td.raw())?,
+            type_def_flags: CorTypeAttr::empty(),
+            parent_token: Token::new(0x0100_0000),
+        })
+    }
+    fn get_type_ref_props(&self, tr: TypeRefToken) -> Result<TypeRefProps, HResult> {
+        Ok(TypeRefProps {
+            resolution_scope: Token::new(self.parents[&tr.raw()]),
+            name: self.name(tr.raw())?,
+        })
+    }
+    fn get_member_ref_props(&self, mr: MemberRefToken) -> Result<MemberRefProps, HResult> {
+        let (sig, sig_length) = self.sig(mr.raw());
+        Ok(MemberRefProps {
+            parent_token: Token::new(self.parents[&mr.raw()]),
+            name: self.name(mr.raw())?,
+            sig,
+            sig_length,
+        })
+    }
+    fn get_field_props(&self, fd: FieldDefToken) -> Result<FieldProps, HResult> {
+        let (sig, sig_length) = self.sig(fd.raw());
+        Ok(FieldProps {
+            class_token: TypeDefToken::try_from(self.parents[&fd.raw()]).unwrap(),
+            name: self.name(fd.raw())?,
+            attr_flags: CorFieldAttr::empty(),
+            sig,
+            sig_length,
+        })
+    }
+    fn get_type_spec_from_token(&self, ts: TypeSpecToken) -> Result<TypeSpecProps, HResult> {
+        let (sig, sig_length) = self.sig(ts.raw());
+        Ok(TypeSpecProps { sig, sig_length })
+    }
+    fn get_method_spec_props(&self, mi: MethodSpecToken) -> Result<MethodSpecProps, HResult> {
+        let (sig, sig_length) = self.sig(mi.raw());
+        Ok(MethodSpecProps {
+            parent_token: Token::new(self.parents[&mi.raw()]),
+            sig,
+            sig_length,
+        })
+    }
+    fn get_nested_class_props(&self, td: TypeDefToken) -> Result<Option<TypeDefToken>, HResult> {
+        Ok(self
+            .parents
+            .get(&td.raw())
+            .and_then(|enclosing| TypeDefToken::try_from(*enclosing).ok()))
+    }
+    fn enum_generic_params(&self, tk: Token) -> Result<Vec<GenericParamToken>, HResult> {
+        Ok(self
+            .generic_params
+            .get(&tk.raw())
+            .cloned()
+            .unwrap_or_default())
+    }
+    fn get_generic_param_props(&self, gp: GenericParamToken) -> Result<GenericParamProps, HResult> {
+        let (owner, sequence, name) = self.generic_param_props[&gp];
+        Ok(GenericParamProps {
+            sequence,
+            flags: CorGenericParamAttr::empty(),
+            owner_token: Token::new(owner),
+            name: name.to_string(),
+        })
+    }
+    fn get_user_string(&self, stk: StringToken) -> Result<String, HResult> {
+        self.name(stk.raw())
+    }
+    fn enum_user_strings(&self) -> Result<Vec<StringToken>, HResult> {
+        let mut strings = self
+            .names
+            .keys()
+            .filter_map(|token| StringToken::try_from(*token).ok())
+            .collect::<Vec<_>>();
+        strings.sort();
+        Ok(strings)
+    }
+    fn find_type_def_by_name(
+        &self,
+        _name: &str,
+        _enclosing_class: Option<TypeDefToken>,
+    ) -> Result<TypeDefToken, HResult> {
+        Err(HResult::CLDB_E_RECORD_NOTFOUND)
+    }
+    fn enum_type_defs(&self) -> Result<Vec<TypeDefToken>, HResult> {
+        Ok(Vec::new())
+    }
+    fn enum_methods(&self, _td: TypeDefToken) -> Result<Vec<MethodDefToken>, HResult> {
+        Ok(Vec::new())
+    }
+    fn enum_fields(&self, _td: TypeDefToken) -> Result<Vec<FieldDefToken>, HResult> {
+        Ok(Vec::new())
+    }
+    fn enum_custom_attributes(
+        &self,
+        _tk: Token,
+        _tk_type: Option<Token>,
+    ) -> Result<Vec<CustomAttributeToken>, HResult> {
+        Ok(Vec::new())
+    }
+    fn get_custom_attribute_props(
+        &self,
+        _cv: CustomAttributeToken,
+    ) -> Result<CustomAttributeProps, HResult> {
+        Err(HResult::CLDB_E_RECORD_NOTFOUND)
+    }
+    fn get_custom_attribute_by_name(
+        &self,
+        _tk_obj: Token,
+        _name: &str,
+    ) -> Result<Option<Vec<u8>>, HResult> {
+        Ok(None)
+    }
+    fn with_resolved_type_ref<R>(
+        &self,
+        _tr: TypeRefToken,
+        _f: impl FnOnce(&Self, TypeDefToken) -> R,
+    ) -> Result<R, HResult> {
+        Err(HResult::E_FAIL)
+    }
+}
+
+fn metadata() -> FakeMetadata {
+    let mut metadata = FakeMetadata::default();
+    // namespace MyApp { class Outer { class Inner<T> { ... } } }
+    metadata.add(0x0200_0002, "MyApp.Outer", 0, &[]);
+    metadata.add(0x0200_0003, "Inner`1", 0x0200_0002, &[]);
+    metadata.add_generic_params(0x0200_0003, &["T"]);
+    // void Method(int, string, T)
+    metadata.add(
+        0x0600_0001,
+        "Method",
+        0x0200_0003,
+        &[0x20, 0x03, 0x01, 0x08, 0x0e, 0x13, 0x00],
+    );
+    // TResult Map<TResult>(T[])
+    metadata.add(
+        0x0600_0002,
+        "Map",
+        0x0200_0003,
+        &[0x30, 0x01, 0x01, 0x1e, 0x00, 0x1d, 0x13, 0x00],
+    );
+    metadata.add_generic_params(0x0600_0002, &["TResult"]);
+    // Map<string>
+    metadata.add(0x2b00_0001, "", 0x0600_0002, &[0x0a, 0x01, 0x0e]);
+
+    metadata.add(
+        0x0100_0001,
+        "System.Collections.Generic.List`1",
+        0x2300_0001,
+        &[],
+    );
+    metadata.add(0x0100_0002, "System.Object", 0x2300_0001, &[]);
+    metadata.add(0x0100_0003, "Entry", 0x0100_0004, &[]);
+    metadata.add(0x0100_0004, "System.Outer", 0x2300_0001, &[]);
+    // List<int>
+    metadata.add(0x1b00_0001, "", 0, &[0x15, 0x12, 0x05, 0x01, 0x08]);
+    metadata.add(
+        0x0a00_0001,
+        "Add",
+        0x1b00_0001,
+        &[0x20, 0x01, 0x01, 0x13, 0x00],
+    );
+    metadata.add(0x0a00_0002, ".ctor", 0x0100_0002, &[0x20, 0x00, 0x01]);
+    // int[,] Entry::Value
+    metadata.add(
+        0x0a00_0003,
+        "Value",
+        0x0100_0003,
+        &[0x06, 0x14, 0x08, 0x02, 0x00, 0x00],
+    );
+    metadata
+}
+
+#[test]
+fn names_method_defs_of_nested_generic_types() {
+    let metadata = metadata();
+    assert_eq!(
+        metadata.qualified_name(Token::new(0x0600_0001)).unwrap(),
+        "MyApp.Outer+Inner<T>::Method(int, string, T)"
+    );
+    assert_eq!(
+        metadata.qualified_name(Token::new(0x0600_0002)).unwrap(),
+        "MyApp.Outer+Inner<T>::Map<TResult>(T[])"
+    );
+}
+
+#[test]
+fn names_method_specs_with_their_instantiation() {
+    assert_eq!(
+        metadata().qualified_name(Token::new(0x2b00_0001)).unwrap(),
+        "MyApp.Outer+Inner<T>::Map<string>(T[])"
+    );
+}
+
+#[test]
+fn names_member_refs() {
+    let metadata = metadata();
+    assert_eq!(
+        metadata.qualified_name(Token::new(0x0a00_0001)).unwrap(),
+        "System.Collections.Generic.List<int>::Add(int)"
+    );
+    assert_eq!(
+        metadata.qualified_name(Token::new(0x0a00_0002)).unwrap(),
+        "System.Object::.ctor()"
+    );
+    assert_eq!(
+        metadata.qualified_name(Token::new(0x0a00_0003)).unwrap(),
+        "System.Outer+Entry::Value"
+    );
+}
+
+#[test]
+fn names_types() {
+    let metadata = metadata();
+    assert_eq!(
+        metadata.qualified_name(Token::new(0x0100_0003)).unwrap(),
+        "System.Outer+Entry"
+    );
+    // the definition of a generic TypeRef can't be resolved offline
+    assert_eq!(
+        metadata.qualified_name(Token::new(0x0100_0001)).unwrap(),
+        "System.Collections.Generic.List`1"
+    );
+}
+
+#[test]
+fn parses_vararg_method_signatures() {
+    // vararg void (int, ..., string)
+    let sig = MethodSig::from_bytes(&[0x05, 0x02, 0x01, 0x08, 0x41, 0x0e]).unwrap();
+    assert_eq!(
+        sig.params,
+        vec![TypeSig::I4, TypeSig::Sentinel, TypeSig::String]
+    );
+    assert!(!sig.has_this());
+}
+
+#[test]
+fn rejects_malformed_signatures() {
+    // void with 0x1fffffff parameters, of which only one is there
+    assert!(matches!(
+        MethodSig::from_bytes(&[0x00, 0xdf, 0xff, 0xff, 0xff, 0x01]),
+        Err(Error::UnexpectedEndOfBlob)
+    ));
+
+    // int32**...*
+    let mut sig = vec![0x0f; 2_000_000];
+    sig.push(0x08);
+    assert!(matches!(
+        TypeSig::from_bytes(&sig),
+        Err(Error::TooDeeplyNested)
+    ));
+    // pointers to functions returning pointers to functions returning ...
+    let sig = [0x1b, 0x00, 0x00].repeat(1_000_000);
+    assert!(matches!(
+        TypeSig::from_bytes(&sig),
+        Err(Error::TooDeeplyNested)
+    ));
+}
+
+#[test]
+fn rejects_types_nested_in_each_other() {
+    let mut metadata = metadata();
+    metadata.add(0x0200_0010, "A", 0x0200_0011, &[]);
+    metadata.add(0x0200_0011, "B", 0x0200_0010, &[]);
+    metadata.add(0x0100_0010, "C", 0x0100_0011, &[]);
+    metadata.add(0x0100_0011, "D", 0x0100_0010, &[]);
+    for token in [0x0200_0010, 0x0100_0010] {
+        assert_eq!(
+            metadata.qualified_name(Token::new(token)),
+            Err(HResult::META_E_BAD_SIGNATURE)
+        );
+    }
+}
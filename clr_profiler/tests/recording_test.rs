@@ -0,0 +1,213 @@
+use clr_profiler::{
+    ffi::{CorOpenFlags, CorTypeAttr, FunctionID, HResult, ModuleID, COR_PRF_MONITOR},
+    recording::{Arg, Callback, Record, Recording, RECORD_VARIABLE},
+    testing::{FakeRuntime, ProfilerDriver},
+    ClrProfiler, CorProfilerCallback, CorProfilerCallback10, CorProfilerCallback11,
+    CorProfilerCallback2, CorProfilerCallback3, CorProfilerCallback4, CorProfilerCallback5,
+    CorProfilerCallback6, CorProfilerCallback7, CorProfilerCallback8, CorProfilerCallback9,
+    CorProfilerInfo, EventMask2, MetadataImportTrait, ProfilerInfo,
+};
+use std::{
+    env,
+    sync::{Arc, Mutex, OnceLock},
+};
+use uuid::Uuid;
+
+// void (int32)
+const SIG: &[u8] = &[0x00, 0x01, 0x01, 0x08];
+// tiny header, ret
+const RETURN: &[u8] = &[0x06, 0x2A];
+// tiny header, nop, ret
+const NOP_RETURN: &[u8] = &[0x0A, 0x00, 0x2A];
+
+/// Names the methods it compiles and puts a nop in front of their body.
+#[derive(Clone, Default)]
+struct NamingProfiler {
+    profiler_info: Arc<OnceLock<ProfilerInfo>>,
+    seen: Arc<Mutex<Vec<String>>>,
+}
+
+impl ClrProfiler for NamingProfiler {
+    fn new() -> Self {
+        Self::default()
+    }
+    fn clsid(&self) -> &Uuid {
+        unimplemented!()
+    }
+}
+
+impl CorProfilerCallback for NamingProfiler {
+    fn initialize(&self, profiler_info: ProfilerInfo) -> Result<(), HResult> {
+        let _ = self.profiler_info.set(profiler_info);
+        Ok(())
+    }
+
+    fn module_load_finished(
+        &self,
+        module_id: ModuleID,
+        _hr_status: HResult,
+    ) -> Result<(), HResult> {
+        let info = self.profiler_info.get().unwrap();
+        let module = info.get_module_info(module_id)?;
+        self.seen
+            .lock()
+            .unwrap()
+            .push(format!("module {}", module.file_name));
+        Ok(())
+    }
+
+    fn jit_compilation_started(
+        &self,
+        function_id: FunctionID,
+        _is_safe_to_block: bool,
+    ) -> Result<(), HResult> {
+        let info = self.profiler_info.get().unwrap();
+        let function = info.get_function_info(function_id)?;
+        let metadata = info.get_module_metadata(function.module_id, CorOpenFlags::ofRead)?;
+        let name = metadata.qualified_name(function.token.token())?;
+        self.seen.lock().unwrap().push(format!("jit {}", name));
+
+        let body = info.get_il_function_body(function.module_id, function.token)?;
+        // the original body is a tiny header and a single instruction
+        let bytes = [NOP_RETURN[0], 0x00, unsafe { *body.method_header.add(1) }];
+        info.replace_il_function_body(function.module_id, function.token, &bytes)
+    }
+}
+impl CorProfilerCallback2 for NamingProfiler {}
+impl CorProfilerCallback3 for NamingProfiler {}
+impl CorProfilerCallback4 for NamingProfiler {}
+impl CorProfilerCallback5 for NamingProfiler {}
+impl CorProfilerCallback6 for NamingProfiler {}
+impl CorProfilerCallback7 for NamingProfiler {}
+impl CorProfilerCallback8 for NamingProfiler {}
+impl CorProfilerCallback9 for NamingProfiler {}
+impl CorProfilerCallback10 for NamingProfiler {}
+impl CorProfilerCallback11 for NamingProfiler {}
+
+const EVENTS: EventMask2 = EventMask2 {
+    events_low: COR_PRF_MONITOR::COR_PRF_MONITOR_MODULE_LOADS
+        .union(COR_PRF_MONITOR::COR_PRF_MONITOR_JIT_COMPILATION),
+    events_high: EventMask2::NONE.events_high,
+};
+
+#[test]
+fn recordings_read_back_what_was_written() {
+    let recording = Recording {
+        records: vec![
+            Record::Module {
+                module_id: 1,
+                assembly_id: 2,
+                name: "Example.dll".to_string(),
+            },
+            Record::Callback {
+                callback: Callback::ThreadNameChanged,
+                args: vec![Arg::Int(3), Arg::Text("Main".to_string())],
+            },
+            Record::Callback {
+                callback: Callback::RootReferences,
+                args: vec![Arg::Int(2), Arg::Ints(vec![u64::MAX, 0])],
+            },
+        ],
+    };
+    let mut bytes = Vec::new();
+    recording.write(&mut bytes).unwrap();
+
+    assert_eq!(Recording::read(&bytes[..]).unwrap(), recording);
+    // a recording cut short by a crash keeps its complete records
+    let truncated = Recording::read(&bytes[..bytes.len() - 1]).unwrap();
+    assert_eq!(truncated.records, recording.records[..2]);
+    assert!(Recording::read(&b"not a recording"[..]).is_err());
+}
+
+#[test]
+fn replays_make_the_recorded_callbacks_on_the_recorded_runtime() {
+    let path = env::temp_dir().join(format!("clr_profiler_recording_{}", std::process::id()));
+    let runtime = FakeRuntime::new();
+    let module = runtime.add_module("Example.dll");
+    let outer = runtime.add_type(module, "Example.Outer", CorTypeAttr::tdPublic);
+    let inner = runtime.add_nested_type(module, outer, "Inner", CorTypeAttr::tdNestedPublic);
+    let function = runtime.add_method(module, inner, "Run", SIG, RETURN);
+
+    let profiler = NamingProfiler::default();
+    unsafe { env::set_var(RECORD_VARIABLE, &path) };
+    let driver = ProfilerDriver::new(profiler.clone(), EVENTS, &runtime);
+    driver.initialize().unwrap();
+    unsafe { env::remove_var(RECORD_VARIABLE) };
+    driver.load_module(module).unwrap();
+    driver.jit(function).unwrap();
+    driver.shutdown().unwrap();
+
+    // flushed at shutdown, while the profiler is still around
+    let recording = Recording::open(&path).unwrap();
+    drop(driver);
+    std::fs::remove_file(&path).unwrap();
+    let modules = recording
+        .records
+        .iter()
+        .filter(|record| matches!(record, Record::Module { .. }))
+        .count();
+    assert_eq!(modules, 1);
+    let replayed_runtime = FakeRuntime::from_recording(&recording);
+    let replayed = NamingProfiler::default();
+    let driver = ProfilerDriver::new(replayed.clone(), EventMask2::NONE, &replayed_runtime);
+    driver.replay(&recording).unwrap();
+
+    assert_eq!(
+        *replayed.seen.lock().unwrap(),
+        vec!["module Example.dll", "jit Example.Outer+Inner::Run(int)"]
+    );
+    assert_eq!(
+        *replayed.seen.lock().unwrap(),
+        *profiler.seen.lock().unwrap()
+    );
+    assert_eq!(replayed_runtime.il_function_body(function), NOP_RETURN);
+    assert_eq!(
+        replayed_runtime.il_function_body(function),
+        runtime.il_function_body(function)
+    );
+}
+
+#[test]
+fn reused_ids_are_described_again() {
+    let path = env::temp_dir().join(format!("clr_profiler_reused_{}", std::process::id()));
+    let runtime = FakeRuntime::new();
+    let module = runtime.add_module("Example.dll");
+    let class = runtime.add_type(module, "Example.Program", CorTypeAttr::tdPublic);
+    let function = runtime.add_method(module, class, "Run", SIG, RETURN);
+
+    let profiler = NamingProfiler::default();
+    unsafe { env::set_var(RECORD_VARIABLE, &path) };
+    let driver = ProfilerDriver::new(profiler.clone(), EVENTS, &runtime);
+    driver.initialize().unwrap();
+    unsafe { env::remove_var(RECORD_VARIABLE) };
+    driver.load_module(module).unwrap();
+    driver.jit(function).unwrap();
+    driver.unload_module(module).unwrap();
+    driver.load_module(module).unwrap();
+    driver.jit(function).unwrap();
+    driver.shutdown().unwrap();
+
+    let recording = Recording::open(&path).unwrap();
+    drop(driver);
+    std::fs::remove_file(&path).unwrap();
+    let count = |matches: fn(&Record) -> bool| {
+        recording
+            .records
+            .iter()
+            .filter(|record| matches(record))
+            .count()
+    };
+    assert_eq!(count(|record| matches!(record, Record::Module { .. })), 2);
+    assert_eq!(count(|record| matches!(record, Record::TypeDef { .. })), 2);
+    assert_eq!(count(|record| matches!(record, Record::Function { .. })), 2);
+    assert_eq!(count(|record| matches!(record, Record::IlBody { .. })), 2);
+
+    let replayed_runtime = FakeRuntime::from_recording(&recording);
+    let replayed = NamingProfiler::default();
+    let driver = ProfilerDriver::new(replayed.clone(), EventMask2::NONE, &replayed_runtime);
+    driver.replay(&recording).unwrap();
+    assert_eq!(
+        *replayed.seen.lock().unwrap(),
+        *profiler.seen.lock().unwrap()
+    );
+}
@@ -0,0 +1,65 @@
+use clr_profiler::{
+    ffi::{ClassFactory, IClassFactory, CLASS_E_CLASSNOTAVAILABLE, GUID, LPVOID, S_FALSE, S_OK},
+    register, ClrProfiler, CorProfilerCallback, CorProfilerCallback10, CorProfilerCallback11,
+    CorProfilerCallback2, CorProfilerCallback3, CorProfilerCallback4, CorProfilerCallback5,
+    CorProfilerCallback6, CorProfilerCallback7, CorProfilerCallback8, CorProfilerCallback9,
+};
+use std::ptr;
+use uuid::Uuid;
+
+const CLSID: &str = "8A2F1C62-3E5B-4C1D-9F0A-6B7D2E4C8A11";
+
+#[derive(Clone)]
+struct Profiler {
+    clsid: Uuid,
+}
+
+impl ClrProfiler for Profiler {
+    fn new() -> Self {
+        Profiler {
+            clsid: Uuid::parse_str(CLSID).unwrap(),
+        }
+    }
+    fn clsid(&self) -> &Uuid {
+        &self.clsid
+    }
+}
+
+impl CorProfilerCallback for Profiler {}
+impl CorProfilerCallback2 for Profiler {}
+impl CorProfilerCallback3 for Profiler {}
+impl CorProfilerCallback4 for Profiler {}
+impl CorProfilerCallback5 for Profiler {}
+impl CorProfilerCallback6 for Profiler {}
+impl CorProfilerCallback7 for Profiler {}
+impl CorProfilerCallback8 for Profiler {}
+impl CorProfilerCallback9 for Profiler {}
+impl CorProfilerCallback10 for Profiler {}
+impl CorProfilerCallback11 for Profiler {}
+
+register!(Profiler);
+
+#[test]
+fn exports_a_class_factory_for_the_profilers_clsid() {
+    let clsid = GUID::from(Uuid::parse_str(CLSID).unwrap());
+    let mut factory: LPVOID = ptr::null_mut();
+    unsafe {
+        assert_eq!(
+            DllGetClassObject(&clsid, &IClassFactory::IID, &mut factory),
+            S_OK
+        );
+        assert!(!factory.is_null());
+        assert_eq!(DllCanUnloadNow(), S_FALSE);
+        let factory = &*(factory as *const ClassFactory<Profiler>);
+        assert_eq!(((*factory.lpVtbl).IUnknown.Release)(factory), 0);
+
+        let mut factory: LPVOID = ptr::null_mut();
+        let other = GUID::from(Uuid::nil());
+        assert_eq!(
+            DllGetClassObject(&other, &IClassFactory::IID, &mut factory),
+            CLASS_E_CLASSNOTAVAILABLE
+        );
+        assert!(factory.is_null());
+    }
+    assert_eq!(DllCanUnloadNow(), S_OK);
+}
@@ -0,0 +1,126 @@
+use clr_profiler::{
+    clr_profiler,
+    ffi::{
+        get_class_object, HResult, IClassFactory, ICorProfilerCallback11, ObjectID, BOOL,
+        CLASS_E_CLASSNOTAVAILABLE, COR_PRF_GC_REASON, COR_PRF_HIGH_MONITOR, COR_PRF_MONITOR, GUID,
+        LPVOID, S_FALSE, S_OK,
+    },
+    ClrProfiler, CorProfilerCallback, CorProfilerCallback2, EventMask2,
+};
+use std::{
+    ptr,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use uuid::Uuid;
+
+const CLSID: &str = "DF63A541-5A33-4611-8829-F4E495985EE3";
+static LOGGING_INITIALIZED: AtomicUsize = AtomicUsize::new(0);
+
+fn init_logging() {
+    LOGGING_INITIALIZED.fetch_add(1, Ordering::SeqCst);
+}
+
+#[clr_profiler(
+    clsid = "{DF63A541-5A33-4611-8829-F4E495985EE3}",
+    callbacks(CorProfilerCallback, CorProfilerCallback2),
+    events(
+        COR_PRF_DISABLE_ALL_NGEN_IMAGES,
+        COR_PRF_HIGH_DISABLE_TIERED_COMPILATION
+    ),
+    logging = "init_logging",
+    can_unload
+)]
+#[derive(Clone)]
+struct Profiler {
+    clsid: Uuid,
+}
+
+impl ClrProfiler for Profiler {
+    fn new() -> Self {
+        Profiler {
+            clsid: Uuid::parse_str(CLSID).unwrap(),
+        }
+    }
+    fn clsid(&self) -> &Uuid {
+        &self.clsid
+    }
+}
+
+#[clr_profiler]
+impl CorProfilerCallback for Profiler {
+    fn exception_thrown(&self, _thrown_object_id: ObjectID) -> Result<(), HResult> {
+        Ok(())
+    }
+}
+
+#[clr_profiler]
+impl CorProfilerCallback2 for Profiler {
+    fn garbage_collection_started(
+        &self,
+        _generation_collected: &[BOOL],
+        _reason: COR_PRF_GC_REASON,
+    ) -> Result<(), HResult> {
+        Ok(())
+    }
+}
+
+fn clsid() -> GUID {
+    GUID::from(Uuid::parse_str(CLSID).unwrap())
+}
+
+#[test]
+fn exports_a_class_factory_for_the_clsid() {
+    let mut factory: LPVOID = ptr::null_mut();
+    unsafe {
+        assert_eq!(
+            DllGetClassObject(&clsid(), &IClassFactory::IID, &mut factory),
+            S_OK
+        );
+        assert_eq!(
+            DllGetClassObject(&clsid(), &IClassFactory::IID, &mut factory),
+            S_OK
+        );
+    }
+    assert!(!factory.is_null());
+    assert_eq!(LOGGING_INITIALIZED.load(Ordering::SeqCst), 1);
+    assert_eq!(DllCanUnloadNow(), S_FALSE);
+}
+
+#[test]
+fn other_clsids_are_refused_unless_accepted() {
+    let other = ICorProfilerCallback11::IID;
+    let mut factory: LPVOID = ptr::null_mut();
+    unsafe {
+        assert_eq!(
+            DllGetClassObject(&other, &IClassFactory::IID, &mut factory),
+            CLASS_E_CLASSNOTAVAILABLE
+        );
+        assert!(factory.is_null());
+        let hr = get_class_object::<Profiler>(
+            &other,
+            &IClassFactory::IID,
+            &mut factory,
+            &clsid(),
+            true,
+            EventMask2::NONE,
+        );
+        assert_eq!(hr, S_OK);
+    }
+    assert!(!factory.is_null());
+}
+
+#[test]
+fn overridden_callbacks_ask_for_their_events() {
+    assert_eq!(
+        Profiler::__CORPROFILERCALLBACK_EVENTS.events_low,
+        COR_PRF_MONITOR::COR_PRF_MONITOR_EXCEPTIONS
+    );
+    assert_eq!(
+        Profiler::__CORPROFILERCALLBACK2_EVENTS.events_low,
+        COR_PRF_MONITOR::COR_PRF_MONITOR_GC
+    );
+    assert_eq!(
+        Profiler::__CORPROFILERCALLBACK2_EVENTS.events_high,
+        COR_PRF_HIGH_MONITOR::empty()
+    );
+}
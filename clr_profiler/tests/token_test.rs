@@ -0,0 +1,45 @@
+use clr_profiler::{
+    cil::{call, ldstr},
+    metadata::{MemberRefToken, MethodDefToken, Token, TokenKind, TypeDefToken},
+};
+use std::convert::TryFrom;
+
+#[test]
+fn decodes_table_and_row() {
+    let token = Token::new(0x0200_0005);
+    assert_eq!(token.kind(), Some(TokenKind::TypeDef));
+    assert_eq!(token.rid(), 5);
+    assert!(!token.is_nil());
+    assert_eq!(Token::from_parts(TokenKind::TypeDef, 5), token);
+
+    assert!(Token::new(0x0100_0000).is_nil());
+    assert_eq!(Token::new(0x0300_0001).kind(), None);
+}
+
+#[test]
+fn displays_kind_and_raw_value() {
+    assert_eq!(Token::new(0x0200_0005).to_string(), "TypeDef(0x02000005)");
+    assert_eq!(Token::new(0x7000_0001).to_string(), "String(0x70000001)");
+    assert_eq!(Token::new(0x0300_0001).to_string(), "Token(0x03000001)");
+    assert_eq!(MethodDefToken::from_rid(1).to_string(), "MethodDef(0x06000001)");
+}
+
+#[test]
+fn typed_tokens_check_kind() {
+    let method = MethodDefToken::try_from(0x0600_002a).unwrap();
+    assert_eq!(method.rid(), 0x2a);
+    assert_eq!(u32::from(method), 0x0600_002a);
+    assert_eq!(Token::from(method).kind(), Some(MethodDefToken::KIND));
+
+    assert_eq!(
+        TypeDefToken::try_from(0x0100_0001),
+        Err(Token::new(0x0100_0001))
+    );
+    assert!(MemberRefToken::try_from(method.token()).is_err());
+}
+
+#[test]
+fn disassembly_prints_tokens() {
+    assert_eq!(call(0x0a00_000c).to_string(), "call MemberRef(0x0a00000c)");
+    assert_eq!(ldstr(0x7000_0001).to_string(), "ldstr String(0x70000001)");
+}
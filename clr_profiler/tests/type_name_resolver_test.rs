@@ -0,0 +1,139 @@
+use clr_profiler::{
+    ffi::{CorElementType, CorTypeAttr},
+    metadata::TypeSig,
+    testing::FakeRuntime,
+    ProfilerInfo, TypeNameResolver,
+};
+
+// ret
+const NOP_RETURN: [u8; 2] = [0x06, 0x2a];
+
+fn resolver(runtime: &FakeRuntime) -> TypeNameResolver {
+    TypeNameResolver::new(ProfilerInfo::new(runtime.profiler_info().as_ptr()).unwrap())
+}
+
+#[test]
+fn maps_runtime_type_names_to_keywords() {
+    assert_eq!(TypeSig::primitive(0x08), Some(TypeSig::I4));
+    assert_eq!(TypeSig::primitive(0x12), None);
+    assert_eq!(
+        TypeSig::from_type_name("System.String").and_then(|sig| sig.keyword()),
+        Some("string")
+    );
+    assert_eq!(TypeSig::from_type_name("System.Int32[]"), None);
+}
+
+#[test]
+fn names_generic_instantiations() {
+    let runtime = FakeRuntime::new();
+    let core_lib = runtime.add_module("System.Private.CoreLib.dll");
+    let public = CorTypeAttr::tdPublic;
+    let string = runtime.add_type(core_lib, "System.String", public);
+    let int = runtime.add_type(core_lib, "System.Int32", public);
+    let list = runtime.add_generic_type(
+        core_lib,
+        "System.Collections.Generic.List`1",
+        public,
+        &["T"],
+    );
+    let dictionary = runtime.add_generic_type(
+        core_lib,
+        "System.Collections.Generic.Dictionary`2",
+        public,
+        &["TKey", "TValue"],
+    );
+    let dictionary = runtime.instantiate(
+        core_lib,
+        dictionary,
+        &[
+            runtime.class_id(core_lib, string),
+            runtime.class_id(core_lib, int),
+        ],
+    );
+    let list = runtime.instantiate(core_lib, list, &[dictionary]);
+
+    assert_eq!(
+        resolver(&runtime).class_name(list).unwrap(),
+        "System.Collections.Generic.List<System.Collections.Generic.Dictionary<string, int>>"
+    );
+}
+
+#[test]
+fn names_arrays_by_their_element_types() {
+    let runtime = FakeRuntime::new();
+    let module = runtime.add_module("Example.dll");
+    let program = runtime.add_type(module, "Example.Program", CorTypeAttr::tdPublic);
+    let program = runtime.class_id(module, program);
+    let programs = runtime.add_array_class(CorElementType::ELEMENT_TYPE_CLASS, Some(program), 1);
+    let matrix = runtime.add_array_class(CorElementType::ELEMENT_TYPE_I4, None, 2);
+    let jagged = runtime.add_array_class(CorElementType::ELEMENT_TYPE_ARRAY, Some(matrix), 1);
+    let nested = runtime.add_array_class(CorElementType::ELEMENT_TYPE_SZARRAY, Some(programs), 1);
+    let resolver = resolver(&runtime);
+
+    assert_eq!(resolver.class_name(programs).unwrap(), "Example.Program[]");
+    assert_eq!(resolver.class_name(matrix).unwrap(), "int[,]");
+    assert_eq!(resolver.class_name(jagged).unwrap(), "int[,][]");
+    assert_eq!(resolver.class_name(nested).unwrap(), "Example.Program[][]");
+}
+
+#[test]
+fn names_functions() {
+    let runtime = FakeRuntime::new();
+    let module = runtime.add_module("Example.dll");
+    let public = CorTypeAttr::tdPublic;
+    let program = runtime.add_type(module, "Example.Program", public);
+    // static void Main(string[])
+    let main = runtime.add_method(
+        module,
+        program,
+        "Main",
+        &[0x00, 0x01, 0x01, 0x1d, 0x0e],
+        &NOP_RETURN,
+    );
+    let list = runtime.add_generic_type(module, "Example.List`1", public, &["T"]);
+    // instance void Add(T)
+    let add = runtime.add_method(
+        module,
+        list,
+        "Add",
+        &[0x20, 0x01, 0x01, 0x13, 0x00],
+        &NOP_RETURN,
+    );
+    let resolver = resolver(&runtime);
+
+    assert_eq!(
+        resolver.function_name(main).unwrap(),
+        "Example.Program::Main(string[])"
+    );
+    // shared generic code has no class to name the type arguments by
+    assert_eq!(
+        resolver.function_name(add).unwrap(),
+        "Example.List<T>::Add(T)"
+    );
+}
+
+#[test]
+fn caches_names_until_cleared() {
+    let runtime = FakeRuntime::new();
+    let module = runtime.add_module("Example.dll");
+    let program = runtime.add_type(module, "Example.Program", CorTypeAttr::tdPublic);
+    let main = runtime.add_method(module, program, "Main", &[0x00, 0x00, 0x01], &NOP_RETURN);
+    let program = runtime.class_id(module, program);
+    let programs = runtime.add_array_class(CorElementType::ELEMENT_TYPE_CLASS, Some(program), 1);
+    let resolver = resolver(&runtime);
+
+    for _ in 0..2 {
+        assert_eq!(resolver.class_name(programs).unwrap(), "Example.Program[]");
+        assert_eq!(resolver.class_name(program).unwrap(), "Example.Program");
+        assert_eq!(
+            resolver.function_name(main).unwrap(),
+            "Example.Program::Main()"
+        );
+    }
+    // one scope for the class, one for the function
+    assert_eq!(runtime.metadata_requests(), 2);
+
+    resolver.clear();
+    assert_eq!(resolver.class_name(programs).unwrap(), "Example.Program[]");
+    assert_eq!(runtime.metadata_requests(), 3);
+}
@@ -0,0 +1,52 @@
+use clr_profiler::{
+    ffi::{CorOpenFlags, HResult, ModuleID},
+    metadata::StringToken,
+    testing::FakeRuntime,
+    CorProfilerInfo, MetadataImport, MetadataImportTrait, ProfilerInfo,
+};
+
+fn metadata(runtime: &FakeRuntime, module_id: ModuleID) -> MetadataImport {
+    ProfilerInfo::new(runtime.profiler_info().as_ptr())
+        .unwrap()
+        .get_module_metadata(module_id, CorOpenFlags::ofRead)
+        .unwrap()
+}
+
+#[test]
+fn iterates_user_strings() {
+    let runtime = FakeRuntime::new();
+    let module = runtime.add_module("Example.dll");
+    let url = runtime.add_user_string(module, "https://example.com/api");
+    let nul = runtime.add_user_string(module, "a\0b");
+    let long = "x".repeat(100);
+    let long_token = runtime.add_user_string(module, &long);
+    let strings = metadata(&runtime, module)
+        .user_strings()
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(
+        strings,
+        vec![
+            (url, "https://example.com/api".to_string()),
+            (nul, "a\0b".to_string()),
+            (long_token, long),
+        ]
+    );
+    // heap offsets, like in an image
+    assert_eq!(url, StringToken::from_rid(1));
+    assert_eq!(nul, StringToken::from_rid(0x31));
+}
+
+#[test]
+fn modules_without_user_strings_have_none() {
+    let runtime = FakeRuntime::new();
+    let module = runtime.add_module("Example.dll");
+    let metadata = metadata(&runtime, module);
+    assert_eq!(metadata.user_strings().unwrap().len(), 0);
+    assert_eq!(
+        metadata.get_user_string(StringToken::from_rid(1)),
+        Err(HResult::CLDB_E_RECORD_NOTFOUND)
+    );
+}
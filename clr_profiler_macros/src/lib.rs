@@ -1,29 +1,555 @@
 extern crate proc_macro;
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, DeriveInput, Type};
+use syn::{
+    parse_macro_input, spanned::Spanned, AttributeArgs, DeriveInput, Error, Ident, ImplItem,
+    ItemImpl, Lit, Meta, NestedMeta, Path, Type,
+};
 
+const CALLBACKS: [&str; 11] = [
+    "CorProfilerCallback",
+    "CorProfilerCallback2",
+    "CorProfilerCallback3",
+    "CorProfilerCallback4",
+    "CorProfilerCallback5",
+    "CorProfilerCallback6",
+    "CorProfilerCallback7",
+    "CorProfilerCallback8",
+    "CorProfilerCallback9",
+    "CorProfilerCallback10",
+    "CorProfilerCallback11",
+];
+
+/// The events each callback needs. Callbacks that are missing here are
+/// always called.
+const CALLBACK_EVENTS: &[(&str, &[&str])] = &[
+    (
+        "app_domain_creation_started",
+        &["COR_PRF_MONITOR_APPDOMAIN_LOADS"],
+    ),
+    (
+        "app_domain_creation_finished",
+        &["COR_PRF_MONITOR_APPDOMAIN_LOADS"],
+    ),
+    (
+        "app_domain_shutdown_started",
+        &["COR_PRF_MONITOR_APPDOMAIN_LOADS"],
+    ),
+    (
+        "app_domain_shutdown_finished",
+        &["COR_PRF_MONITOR_APPDOMAIN_LOADS"],
+    ),
+    ("assembly_load_started", &["COR_PRF_MONITOR_ASSEMBLY_LOADS"]),
+    (
+        "assembly_load_finished",
+        &["COR_PRF_MONITOR_ASSEMBLY_LOADS"],
+    ),
+    (
+        "assembly_unload_started",
+        &["COR_PRF_MONITOR_ASSEMBLY_LOADS"],
+    ),
+    (
+        "assembly_unload_finished",
+        &["COR_PRF_MONITOR_ASSEMBLY_LOADS"],
+    ),
+    ("module_load_started", &["COR_PRF_MONITOR_MODULE_LOADS"]),
+    ("module_load_finished", &["COR_PRF_MONITOR_MODULE_LOADS"]),
+    ("module_unload_started", &["COR_PRF_MONITOR_MODULE_LOADS"]),
+    ("module_unload_finished", &["COR_PRF_MONITOR_MODULE_LOADS"]),
+    (
+        "module_attached_to_assembly",
+        &["COR_PRF_MONITOR_MODULE_LOADS"],
+    ),
+    ("class_load_started", &["COR_PRF_MONITOR_CLASS_LOADS"]),
+    ("class_load_finished", &["COR_PRF_MONITOR_CLASS_LOADS"]),
+    ("class_unload_started", &["COR_PRF_MONITOR_CLASS_LOADS"]),
+    ("class_unload_finished", &["COR_PRF_MONITOR_CLASS_LOADS"]),
+    (
+        "function_unload_started",
+        &["COR_PRF_MONITOR_FUNCTION_UNLOADS"],
+    ),
+    (
+        "jit_compilation_started",
+        &["COR_PRF_MONITOR_JIT_COMPILATION"],
+    ),
+    (
+        "jit_compilation_finished",
+        &["COR_PRF_MONITOR_JIT_COMPILATION"],
+    ),
+    (
+        "jit_cached_function_search_started",
+        &["COR_PRF_MONITOR_CACHE_SEARCHES"],
+    ),
+    (
+        "jit_cached_function_search_finished",
+        &["COR_PRF_MONITOR_CACHE_SEARCHES"],
+    ),
+    ("jit_function_pitched", &["COR_PRF_MONITOR_JIT_COMPILATION"]),
+    ("jit_inlining", &["COR_PRF_MONITOR_JIT_COMPILATION"]),
+    ("thread_created", &["COR_PRF_MONITOR_THREADS"]),
+    ("thread_destroyed", &["COR_PRF_MONITOR_THREADS"]),
+    ("thread_assigned_to_os_thread", &["COR_PRF_MONITOR_THREADS"]),
+    (
+        "remoting_client_invocation_started",
+        &["COR_PRF_MONITOR_REMOTING"],
+    ),
+    (
+        "remoting_client_sending_message",
+        &["COR_PRF_MONITOR_REMOTING"],
+    ),
+    (
+        "remoting_client_receiving_reply",
+        &["COR_PRF_MONITOR_REMOTING"],
+    ),
+    (
+        "remoting_client_invocation_finished",
+        &["COR_PRF_MONITOR_REMOTING"],
+    ),
+    (
+        "remoting_server_receiving_message",
+        &["COR_PRF_MONITOR_REMOTING"],
+    ),
+    (
+        "remoting_server_invocation_started",
+        &["COR_PRF_MONITOR_REMOTING"],
+    ),
+    (
+        "remoting_server_invocation_returned",
+        &["COR_PRF_MONITOR_REMOTING"],
+    ),
+    (
+        "remoting_server_sending_reply",
+        &["COR_PRF_MONITOR_REMOTING"],
+    ),
+    (
+        "unmanaged_to_managed_transition",
+        &["COR_PRF_MONITOR_CODE_TRANSITIONS"],
+    ),
+    (
+        "managed_to_unmanaged_transition",
+        &["COR_PRF_MONITOR_CODE_TRANSITIONS"],
+    ),
+    ("runtime_suspend_started", &["COR_PRF_MONITOR_SUSPENDS"]),
+    ("runtime_suspend_finished", &["COR_PRF_MONITOR_SUSPENDS"]),
+    ("runtime_suspend_aborted", &["COR_PRF_MONITOR_SUSPENDS"]),
+    ("runtime_resume_started", &["COR_PRF_MONITOR_SUSPENDS"]),
+    ("runtime_resume_finished", &["COR_PRF_MONITOR_SUSPENDS"]),
+    ("runtime_thread_suspended", &["COR_PRF_MONITOR_SUSPENDS"]),
+    ("runtime_thread_resumed", &["COR_PRF_MONITOR_SUSPENDS"]),
+    ("moved_references", &["COR_PRF_MONITOR_GC"]),
+    (
+        "object_allocated",
+        &[
+            "COR_PRF_ENABLE_OBJECT_ALLOCATED",
+            "COR_PRF_MONITOR_OBJECT_ALLOCATED",
+        ],
+    ),
+    ("objects_allocated_by_class", &["COR_PRF_MONITOR_GC"]),
+    ("object_references", &["COR_PRF_MONITOR_GC"]),
+    ("root_references", &["COR_PRF_MONITOR_GC"]),
+    ("exception_thrown", &["COR_PRF_MONITOR_EXCEPTIONS"]),
+    (
+        "exception_search_function_enter",
+        &["COR_PRF_MONITOR_EXCEPTIONS"],
+    ),
+    (
+        "exception_search_function_leave",
+        &["COR_PRF_MONITOR_EXCEPTIONS"],
+    ),
+    (
+        "exception_search_filter_enter",
+        &["COR_PRF_MONITOR_EXCEPTIONS"],
+    ),
+    (
+        "exception_search_filter_leave",
+        &["COR_PRF_MONITOR_EXCEPTIONS"],
+    ),
+    (
+        "exception_search_catcher_found",
+        &["COR_PRF_MONITOR_EXCEPTIONS"],
+    ),
+    (
+        "exception_os_handler_enter",
+        &["COR_PRF_MONITOR_EXCEPTIONS"],
+    ),
+    (
+        "exception_os_handler_leave",
+        &["COR_PRF_MONITOR_EXCEPTIONS"],
+    ),
+    (
+        "exception_unwind_function_enter",
+        &["COR_PRF_MONITOR_EXCEPTIONS"],
+    ),
+    (
+        "exception_unwind_function_leave",
+        &["COR_PRF_MONITOR_EXCEPTIONS"],
+    ),
+    (
+        "exception_unwind_finally_enter",
+        &["COR_PRF_MONITOR_EXCEPTIONS"],
+    ),
+    (
+        "exception_unwind_finally_leave",
+        &["COR_PRF_MONITOR_EXCEPTIONS"],
+    ),
+    ("exception_catcher_enter", &["COR_PRF_MONITOR_EXCEPTIONS"]),
+    ("exception_catcher_leave", &["COR_PRF_MONITOR_EXCEPTIONS"]),
+    ("com_classic_vtable_created", &["COR_PRF_MONITOR_CCW"]),
+    ("com_classic_vtable_destroyed", &["COR_PRF_MONITOR_CCW"]),
+    (
+        "exception_clr_catcher_found",
+        &["COR_PRF_MONITOR_CLR_EXCEPTIONS"],
+    ),
+    (
+        "exception_clr_catcher_execute",
+        &["COR_PRF_MONITOR_CLR_EXCEPTIONS"],
+    ),
+    ("thread_name_changed", &["COR_PRF_MONITOR_THREADS"]),
+    ("garbage_collection_started", &["COR_PRF_MONITOR_GC"]),
+    ("surviving_references", &["COR_PRF_MONITOR_GC"]),
+    ("garbage_collection_finished", &["COR_PRF_MONITOR_GC"]),
+    ("finalizeable_object_queued", &["COR_PRF_MONITOR_GC"]),
+    ("root_references_2", &["COR_PRF_MONITOR_GC"]),
+    ("handle_created", &["COR_PRF_MONITOR_GC"]),
+    ("handle_destroyed", &["COR_PRF_MONITOR_GC"]),
+    ("rejit_compilation_started", &["COR_PRF_ENABLE_REJIT"]),
+    ("get_rejit_parameters", &["COR_PRF_ENABLE_REJIT"]),
+    ("rejit_compilation_finished", &["COR_PRF_ENABLE_REJIT"]),
+    ("rejit_error", &["COR_PRF_ENABLE_REJIT"]),
+    ("moved_references_2", &["COR_PRF_MONITOR_GC"]),
+    ("surviving_references_2", &["COR_PRF_MONITOR_GC"]),
+    (
+        "conditional_weak_table_element_references",
+        &["COR_PRF_MONITOR_GC"],
+    ),
+    (
+        "get_assembly_references",
+        &["COR_PRF_HIGH_ADD_ASSEMBLY_REFERENCES"],
+    ),
+    (
+        "module_in_memory_symbols_updated",
+        &["COR_PRF_HIGH_IN_MEMORY_SYMBOLS_UPDATED"],
+    ),
+    (
+        "dynamic_method_jit_compilation_started",
+        &["COR_PRF_MONITOR_JIT_COMPILATION"],
+    ),
+    (
+        "dynamic_method_jit_compilation_finished",
+        &["COR_PRF_MONITOR_JIT_COMPILATION"],
+    ),
+    (
+        "dynamic_method_unloaded",
+        &["COR_PRF_HIGH_MONITOR_DYNAMIC_FUNCTION_UNLOADS"],
+    ),
+    (
+        "event_pipe_event_delivered",
+        &["COR_PRF_HIGH_MONITOR_EVENT_PIPE"],
+    ),
+    (
+        "event_pipe_provider_created",
+        &["COR_PRF_HIGH_MONITOR_EVENT_PIPE"],
+    ),
+];
+
+/// Exports a profiler from a cdylib:
+///
+/// ```ignore
+/// #[clr_profiler(clsid = "DF63A541-5A33-4611-8829-F4E495985EE3", callbacks(CorProfilerCallback))]
+/// #[derive(Clone)]
+/// struct Profiler { ... }
+/// ```
+///
+/// - `clsid` is the CLSID the runtime is configured to load.
+/// - `accept_any_clsid` creates the profiler whichever CLSID the runtime asks for.
+/// - `callbacks(...)` names the callback traits implemented by hand, the others
+///   are implemented with their defaults. Each of those impls is marked
+///   `#[clr_profiler]` as well, which turns on the events its callbacks need
+///   once the profiler has initialized.
+/// - `events(...)` turns on further `COR_PRF_MONITOR` or `COR_PRF_HIGH_MONITOR`
+///   flags, e.g. `events(COR_PRF_DISABLE_ALL_NGEN_IMAGES)`.
+/// - `logging = "path::to::init"` is called once before the first export runs.
+/// - `can_unload` also exports `DllCanUnloadNow`.
+///
+/// The profiler still implements `ClrProfiler` itself. The exports are public,
+/// so tests can load the profiler with `clr_profiler::testing::ProfilerDriver`.
+#[proc_macro_attribute]
+pub fn clr_profiler(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as AttributeArgs);
+    if let Ok(input) = syn::parse::<ItemImpl>(item.clone()) {
+        return match expand_callbacks(args, &input) {
+            Ok(output) => output.into(),
+            Err(error) => error.to_compile_error().into(),
+        };
+    }
+    let input = parse_macro_input!(item as DeriveInput);
+    match expand_clr_profiler(args, &input) {
+        Ok(output) => output.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn expand_clr_profiler(
+    args: AttributeArgs,
+    input: &DeriveInput,
+) -> Result<proc_macro2::TokenStream, Error> {
+    let name = &input.ident;
+    let mut clsid = None;
+    let mut accept_any_clsid = false;
+    let mut can_unload = false;
+    let mut logging: Option<Path> = None;
+    let mut callbacks = Vec::new();
+    let mut events = Vec::new();
+    for arg in &args {
+        match arg {
+            NestedMeta::Meta(Meta::NameValue(pair)) => match (&pair.lit, pair.path.get_ident()) {
+                (Lit::Str(value), Some(key)) if key == "clsid" => {
+                    clsid = Some(parse_guid(&value.value()).ok_or_else(|| {
+                        Error::new(
+                            value.span(),
+                            "expected a CLSID like \"DF63A541-5A33-4611-8829-F4E495985EE3\"",
+                        )
+                    })?);
+                }
+                (Lit::Str(value), Some(key)) if key == "logging" => {
+                    logging = Some(value.parse()?);
+                }
+                _ => return Err(Error::new(pair.span(), "unknown clr_profiler argument")),
+            },
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("accept_any_clsid") => {
+                accept_any_clsid = true;
+            }
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("can_unload") => {
+                can_unload = true;
+            }
+            NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("callbacks") => {
+                for callback in &list.nested {
+                    match callback {
+                        NestedMeta::Meta(Meta::Path(path))
+                            if CALLBACKS.iter().any(|known| path.is_ident(known)) =>
+                        {
+                            callbacks.push(path.get_ident().unwrap().to_string());
+                        }
+                        _ => {
+                            return Err(Error::new(
+                                callback.span(),
+                                "expected a CorProfilerCallback trait",
+                            ))
+                        }
+                    }
+                }
+            }
+            NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("events") => {
+                for event in &list.nested {
+                    match event {
+                        NestedMeta::Meta(Meta::Path(path)) if path.get_ident().is_some() => {
+                            events.push(path.get_ident().unwrap().clone());
+                        }
+                        _ => {
+                            return Err(Error::new(event.span(), "expected a COR_PRF_MONITOR flag"))
+                        }
+                    }
+                }
+            }
+            _ => return Err(Error::new(arg.span(), "unknown clr_profiler argument")),
+        }
+    }
+    let (data1, data2, data3, data4) = clsid
+        .ok_or_else(|| Error::new(proc_macro2::Span::call_site(), "clr_profiler needs a clsid"))?;
+
+    let default_callbacks = CALLBACKS
+        .iter()
+        .filter(|callback| !callbacks.iter().any(|c| c == *callback))
+        .map(|callback| {
+            let callback = syn::Ident::new(callback, proc_macro2::Span::call_site());
+            quote! { impl clr_profiler::#callback for #name {} }
+        });
+    let callback_events = callbacks.iter().map(|callback| {
+        let events = events_const(callback);
+        quote! { .union(#name::#events) }
+    });
+    let events = event_mask(&events);
+    let init_logging = logging.map(|logging| {
+        quote! {
+            static LOGGING: ::std::sync::Once = ::std::sync::Once::new();
+            LOGGING.call_once(|| #logging());
+        }
+    });
+    let can_unload_now = if can_unload {
+        Some(quote! {
+            #[unsafe(no_mangle)]
+            pub extern "system" fn DllCanUnloadNow() -> clr_profiler::ffi::HRESULT {
+                clr_profiler::ffi::can_unload_now()
+            }
+        })
+    } else {
+        None
+    };
+
+    Ok(quote! {
+        #input
+
+        #(#default_callbacks)*
+
+        #[unsafe(no_mangle)]
+        pub unsafe extern "system" fn DllGetClassObject(
+            rclsid: clr_profiler::ffi::REFCLSID,
+            riid: clr_profiler::ffi::REFIID,
+            ppv: *mut clr_profiler::ffi::LPVOID,
+        ) -> clr_profiler::ffi::HRESULT {
+            #init_logging
+            const CLSID: clr_profiler::ffi::GUID = clr_profiler::ffi::GUID {
+                data1: #data1,
+                data2: #data2,
+                data3: #data3,
+                data4: [#(#data4),*],
+            };
+            const EVENTS: clr_profiler::EventMask2 = #events #(#callback_events)*;
+            unsafe {
+                clr_profiler::ffi::get_class_object::<#name>(
+                    rclsid,
+                    riid,
+                    ppv,
+                    &CLSID,
+                    #accept_any_clsid,
+                    EVENTS,
+                )
+            }
+        }
+
+        #can_unload_now
+    })
+}
+
+/// Records the events needed by the callbacks a `CorProfilerCallbackN` impl
+/// overrides, for `#[clr_profiler]` on the profiler to pick up.
+fn expand_callbacks(
+    args: AttributeArgs,
+    input: &ItemImpl,
+) -> Result<proc_macro2::TokenStream, Error> {
+    if let Some(arg) = args.first() {
+        return Err(Error::new(
+            arg.span(),
+            "expected no arguments on a callback impl",
+        ));
+    }
+    let callback = input
+        .trait_
+        .as_ref()
+        .and_then(|(_, path, _)| path.segments.last())
+        .map(|segment| segment.ident.to_string())
+        .filter(|callback| CALLBACKS.contains(&callback.as_str()))
+        .ok_or_else(|| {
+            Error::new(
+                input.span(),
+                "expected an impl of a CorProfilerCallback trait",
+            )
+        })?;
+    let self_ty = &input.self_ty;
+    let events: Vec<Ident> = input
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            ImplItem::Method(method) => Some(method.sig.ident.to_string()),
+            _ => None,
+        })
+        .filter_map(|method| CALLBACK_EVENTS.iter().find(|(name, _)| *name == method))
+        .flat_map(|(_, events)| events.iter())
+        .map(|event| Ident::new(event, proc_macro2::Span::call_site()))
+        .collect();
+    let events = event_mask(&events);
+    let events_const = events_const(&callback);
+    Ok(quote! {
+        #input
+
+        impl #self_ty {
+            #[doc(hidden)]
+            pub const #events_const: clr_profiler::EventMask2 = #events;
+        }
+    })
+}
+
+/// The associated constant `#[clr_profiler]` puts the events of a callback impl in.
+fn events_const(callback: &str) -> Ident {
+    let name = format!("__{}_EVENTS", callback.to_uppercase());
+    Ident::new(&name, proc_macro2::Span::call_site())
+}
+
+/// A constant `EventMask2` with `events`, sorted into the low and high flags
+/// by name.
+fn event_mask(events: &[Ident]) -> proc_macro2::TokenStream {
+    let (high, low): (Vec<&Ident>, Vec<&Ident>) = events
+        .iter()
+        .partition(|event| event.to_string().starts_with("COR_PRF_HIGH_"));
+    quote! {
+        clr_profiler::EventMask2 {
+            events_low: clr_profiler::ffi::COR_PRF_MONITOR::empty()
+                #(.union(clr_profiler::ffi::COR_PRF_MONITOR::#low))*,
+            events_high: clr_profiler::ffi::COR_PRF_HIGH_MONITOR::empty()
+                #(.union(clr_profiler::ffi::COR_PRF_HIGH_MONITOR::#high))*,
+        }
+    }
+}
+
+/// Splits `XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX`, optionally in braces, into
+/// the fields of a `GUID`.
+fn parse_guid(value: &str) -> Option<(u32, u16, u16, [u8; 8])> {
+    let value = value.trim_start_matches('{').trim_end_matches('}');
+    let groups: Vec<&str> = value.split('-').collect();
+    let lengths = [8, 4, 4, 4, 12];
+    if groups.len() != lengths.len()
+        || groups.iter().zip(lengths.iter()).any(|(group, length)| {
+            group.len() != *length || !group.chars().all(|c| c.is_ascii_hexdigit())
+        })
+    {
+        return None;
+    }
+    let tail = format!("{}{}", groups[3], groups[4]);
+    let mut data4 = [0; 8];
+    for (i, byte) in data4.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&tail[2 * i..2 * i + 2], 16).ok()?;
+    }
+    Some((
+        u32::from_str_radix(groups[0], 16).ok()?,
+        u16::from_str_radix(groups[1], 16).ok()?,
+        u16::from_str_radix(groups[2], 16).ok()?,
+        data4,
+    ))
+}
+
+/// Exports `DllGetClassObject` and `DllCanUnloadNow` for a profiler that
+/// already implements every `CorProfilerCallbackN` trait, accepting only the
+/// CLSID returned by its `ClrProfiler::clsid`. `#[clr_profiler]` does the same
+/// and more.
 #[proc_macro]
 pub fn register(item: TokenStream) -> TokenStream {
     let profiler_type = parse_macro_input!(item as Type);
     let output = quote! {
-        use clr_profiler::ffi::{ClassFactory as FFIClassFactory, CorProfilerCallback as FFICorProfilerCallback, E_FAIL as FFI_E_FAIL, GUID as FFI_GUID, HRESULT as FFI_HRESULT, LPVOID as FFI_LPVOID, REFCLSID as FFI_REFCLSID, REFIID as FFI_REFIID};
-        #[no_mangle]
-        unsafe extern "system" fn DllGetClassObject(
-            rclsid: FFI_REFCLSID,
-            riid: FFI_REFIID,
-            ppv: *mut FFI_LPVOID,
-        ) -> FFI_HRESULT {
-            let profiler = <#profiler_type>::new();
-            let clsid = FFI_GUID::from(*profiler.clsid());
-            if ppv.is_null() || *rclsid != clsid {
-                println!("CLSID didn't match. CLSID: {:?}", clsid);
-                FFI_E_FAIL
-            } else {
-                let class_factory: &mut FFIClassFactory<#profiler_type> = FFIClassFactory::new(profiler);
-                class_factory.QueryInterface(riid, ppv)
+        #[unsafe(no_mangle)]
+        pub unsafe extern "system" fn DllGetClassObject(
+            rclsid: clr_profiler::ffi::REFCLSID,
+            riid: clr_profiler::ffi::REFIID,
+            ppv: *mut clr_profiler::ffi::LPVOID,
+        ) -> clr_profiler::ffi::HRESULT {
+            let clsid = clr_profiler::ffi::GUID::from(
+                *<#profiler_type as clr_profiler::ClrProfiler>::new().clsid(),
+            );
+            unsafe {
+                clr_profiler::ffi::get_class_object::<#profiler_type>(
+                    rclsid,
+                    riid,
+                    ppv,
+                    &clsid,
+                    false,
+                    clr_profiler::EventMask2::NONE,
+                )
             }
         }
+
+        #[unsafe(no_mangle)]
+        pub extern "system" fn DllCanUnloadNow() -> clr_profiler::ffi::HRESULT {
+            clr_profiler::ffi::can_unload_now()
+        }
     };
     output.into()
 }
@@ -117,3 +643,23 @@ pub fn derive_cor_profiler_callback_9(item: TokenStream) -> TokenStream {
     };
     trait_impl.into()
 }
+#[proc_macro_derive(CorProfilerCallback10)]
+pub fn derive_cor_profiler_callback_10(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+    let name = &input.ident;
+
+    let trait_impl = quote! {
+        impl clr_profiler::CorProfilerCallback10 for #name {}
+    };
+    trait_impl.into()
+}
+#[proc_macro_derive(CorProfilerCallback11)]
+pub fn derive_cor_profiler_callback_11(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+    let name = &input.ident;
+
+    let trait_impl = quote! {
+        impl clr_profiler::CorProfilerCallback11 for #name {}
+    };
+    trait_impl.into()
+}
@@ -0,0 +1,63 @@
+use clr_profiler::{
+    ffi::{COR_PRF_MONITOR, CorTypeAttr, GUID},
+    testing::{FakeRuntime, ProfilerDriver},
+};
+use ssl_pin_bypass::DllGetClassObject;
+use std::env;
+use uuid::Uuid;
+
+const CLSID: &str = "DF63A541-5A33-4611-8829-F4E495985EE3";
+
+// bool (string, object, object, int32), the certificate and chain types
+// stand in as object
+const VALIDATION_CALLBACK_SIG: &[u8] = &[0x00, 0x04, 0x02, 0x0E, 0x1C, 0x1C, 0x08];
+// tiny header, ldc.i4.0, ret
+const RETURN_FALSE: &[u8] = &[0x0A, 0x16, 0x2A];
+// tiny header, ldc.i4.1, ret
+const RETURN_TRUE: &[u8] = &[0x0A, 0x17, 0x2A];
+
+#[test]
+fn certificate_validation_always_succeeds() {
+    // keep the profiler's log out of the source tree
+    unsafe { env::set_var("LOG_FILE", env::temp_dir().join("ssl_pin_bypass_test.log")) };
+
+    let runtime = FakeRuntime::new();
+    let module = runtime.add_module("System.Net.Security.dll");
+    let ssl_stream = runtime.add_type(
+        module,
+        "System.Net.Security.SslStream",
+        CorTypeAttr::tdPublic,
+    );
+    let wrapper = runtime.add_method(
+        module,
+        ssl_stream,
+        "userCertValidationCallbackWrapper",
+        VALIDATION_CALLBACK_SIG,
+        RETURN_FALSE,
+    );
+    let other = runtime.add_method(
+        module,
+        ssl_stream,
+        "VerifyRemoteCertificate",
+        VALIDATION_CALLBACK_SIG,
+        RETURN_FALSE,
+    );
+
+    let clsid = GUID::from(Uuid::parse_str(CLSID).unwrap());
+    let driver = unsafe { ProfilerDriver::load(DllGetClassObject, &clsid, &runtime) }.unwrap();
+    driver.initialize().unwrap();
+    assert!(
+        runtime
+            .event_mask()
+            .events_low
+            .contains(COR_PRF_MONITOR::COR_PRF_MONITOR_JIT_COMPILATION)
+    );
+
+    driver.load_module(module).unwrap();
+    driver.jit(other).unwrap();
+    driver.jit(wrapper).unwrap();
+    driver.shutdown().unwrap();
+
+    assert_eq!(runtime.il_function_body(wrapper), RETURN_TRUE);
+    assert!(!runtime.is_il_rewritten(other));
+}
@@ -1,29 +1,29 @@
 use clr_profiler::{
-    ffi::{AssemblyID, ClassFactory, CorOpenFlags, FunctionID, ModuleID, COR_PRF_MONITOR, HRESULT, LPVOID, REFCLSID, REFIID}, 
-    ClrProfiler, CorProfilerCallback, CorProfilerCallback2, CorProfilerCallback3, CorProfilerCallback4, CorProfilerCallback5, 
-    CorProfilerCallback6, CorProfilerCallback7, CorProfilerCallback8, CorProfilerCallback9, 
-    CorProfilerInfo, CorProfilerInfo3,
+    ffi::{AssemblyID, CorOpenFlags, FunctionID, ModuleID, COR_PRF_MONITOR, HResult}, 
+    clr_profiler, ClrProfiler, CorProfilerCallback, CorProfilerInfo, CorProfilerInfo3,
     MetadataImportTrait, ProfilerInfo
 };
-use log::{debug, info, warn};
-use log_init::init_logging;
+use log::{debug, info};
+use std::sync::OnceLock;
 use uuid::Uuid;
 
 const PROFILER_UUID: &str = "DF63A541-5A33-4611-8829-F4E495985EE3";
 
+#[clr_profiler(
+    clsid = "DF63A541-5A33-4611-8829-F4E495985EE3",
+    accept_any_clsid,
+    callbacks(CorProfilerCallback),
+    logging = "log_init::init_logging"
+)]
 #[derive(Clone)]
 struct Profiler {
     clsid: Uuid,
-    profiler_info: Option<ProfilerInfo>,
+    profiler_info: OnceLock<ProfilerInfo>,
 }
 
 impl Profiler {
     fn profiler_info(&self) -> &ProfilerInfo {
-        self.profiler_info.as_ref().unwrap()
-    }
-
-    fn with_clsid(clsid: Uuid) -> Self {
-        Self { clsid: clsid, profiler_info: None }
+        self.profiler_info.get().unwrap()
     }
 }
 
@@ -31,7 +31,7 @@ impl ClrProfiler for Profiler {
     fn new() -> Profiler {
         Profiler {
             clsid: Uuid::parse_str(PROFILER_UUID).unwrap(),
-            profiler_info: None,
+            profiler_info: OnceLock::new(),
         }
     }
     fn clsid(&self) -> &Uuid {
@@ -39,10 +39,11 @@ impl ClrProfiler for Profiler {
     }
 }
 
+#[clr_profiler]
 impl CorProfilerCallback for Profiler {
-    fn initialize(&mut self, profiler_info: ProfilerInfo) -> Result<(), HRESULT> {
+    fn initialize(&self, profiler_info: ProfilerInfo) -> Result<(), HResult> {
         // Initialize ICorProfilerInfo reference
-        self.profiler_info = Some(profiler_info);
+        let _ = self.profiler_info.set(profiler_info);
 
         // Set the event mask
         self.profiler_info()
@@ -57,19 +58,19 @@ impl CorProfilerCallback for Profiler {
         Ok(())
     }
 
-    fn assembly_load_finished(&mut self, assembly_id: AssemblyID, hr_status: HRESULT) -> Result<(), HRESULT> {
+    fn assembly_load_finished(&self, assembly_id: AssemblyID, hr_status: HResult) -> Result<(), HResult> {
         let assembly_info = self.profiler_info().get_assembly_info(assembly_id)?;
-        info!("assembly {} finished loading with status {:#x}", assembly_info.name, hr_status);
+        info!("assembly {} finished loading with status {}", assembly_info.name, hr_status);
         Ok(())
     }
 
-    fn module_load_finished(&mut self, module_id: ModuleID, hr_status: HRESULT) -> Result<(), HRESULT> {
+    fn module_load_finished(&self, module_id: ModuleID, hr_status: HResult) -> Result<(), HResult> {
         let module_info = self.profiler_info().get_module_info_2(module_id)?;
         info!("moudle load finished with status {} : {:#?}", hr_status, module_info);
         Ok(())
     }
 
-    fn jit_compilation_started(&mut self, function_id: FunctionID, _is_safe_to_block: bool) -> Result<(), HRESULT> {
+    fn jit_compilation_started(&self, function_id: FunctionID, _is_safe_to_block: bool) -> Result<(), HResult> {
         // Get function metadata from function id.
         let function_info = self.profiler_info().get_function_info(function_id)?;
         
@@ -81,12 +82,22 @@ impl CorProfilerCallback for Profiler {
         // Get method properties using module metadata interface.
         let method_props = module_metadata.get_method_props(function_info.token)?;
         
-        // Get class (Type Definition) properties using module metadata interface.
-        let class_props = module_metadata.get_type_def_props(method_props.class_token)?;
-
-        let qualified_method_name = format!("{}.{}", class_props.name, method_props.name);
+        // Get the fully qualified name, e.g. `Namespace.Class::Method(int)`.
+        let qualified_method_name = module_metadata.qualified_name(function_info.token.token())?;
+
+        // Methods and types can opt out of profiling with a [NoProfile] attribute.
+        if module_metadata.has_custom_attribute(function_info.token.token(), "NoProfile")?
+            || module_metadata.has_custom_attribute(method_props.class_token.token(), "NoProfile")?
+        {
+            debug!("skipping {qualified_method_name}, marked with [NoProfile]");
+            return Ok(());
+        }
         info!("jit compilation started for {qualified_method_name}");
 
+        if let Some(trace) = module_metadata.find_custom_attribute(function_info.token.token(), "Trace")? {
+            info!("{qualified_method_name} is marked with [{}] {:?}", trace.type_name, trace.args);
+        }
+
         // if method_props.name == "TMethod" || method_props.name == "FMethod" {
         //     let il_body = self
         //     .profiler_info()
@@ -95,7 +106,7 @@ impl CorProfilerCallback for Profiler {
         //     //     slice::from_raw_parts(il_body.method_header, il_body.method_size as usize)
         //     // };
         //     let mut method =
-        //         Method::new(il_body.method_header, il_body.method_size).or(Err(E_FAIL))?;
+        //         Method::new(il_body.method_header, il_body.method_size).or(Err(HResult::E_FAIL))?;
         //     println!("{:#?}", method);
         //     let il = vec![nop()];
         // }
@@ -106,42 +117,3 @@ impl CorProfilerCallback for Profiler {
         Ok(())
     }
 }
-
-impl CorProfilerCallback2 for Profiler {}
-impl CorProfilerCallback3 for Profiler {}
-impl CorProfilerCallback4 for Profiler {}
-impl CorProfilerCallback5 for Profiler {}
-impl CorProfilerCallback6 for Profiler {}
-impl CorProfilerCallback7 for Profiler {}
-impl CorProfilerCallback8 for Profiler {}
-impl CorProfilerCallback9 for Profiler {}
-
-#[unsafe(no_mangle)]
-unsafe extern "system" fn DllGetClassObject( rclsid: REFCLSID, riid: REFIID, ppv: *mut LPVOID) -> HRESULT {
-    println!("[PROF_DEBUG] In DllGetClassObject");
-    init_logging();
-
-    debug!("DllGetClassObject called");
-    
-    unsafe {
-        debug!("*rclsid = {}, *riid = {}", *rclsid, *riid);
-    }
-    
-    let guid = unsafe { *rclsid };
-    let profiler = match Uuid::from_fields(guid.data1, guid.data2, guid.data3, &guid.data4) {
-        Ok(uuid) => {
-            info!("creating profiler with UUID: {{{}}}", uuid.to_hyphenated());
-            Profiler::with_clsid(uuid)
-        },
-        Err(err) => {
-            warn!("failed to covert {guid:?} to UUID; err: {err}");
-            warn!("creating profiler with default GUID {{{PROFILER_UUID}}}");
-            Profiler::new()
-        }
-    };
-    
-    let class_factory: &mut ClassFactory<Profiler> = ClassFactory::new(profiler);
-
-    // Initialize [out]ppv pointer with IClassFactory instance.
-    unsafe { class_factory.QueryInterface(riid, ppv) }
-}